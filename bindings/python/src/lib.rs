@@ -27,6 +27,9 @@ impl ArqonSolver {
         let config: SolverConfig = serde_json::from_str(&config_json).map_err(|e| {
             PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid config: {}", e))
         })?;
+        config.check_budget().map_err(|e| {
+            PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid config: {}", e))
+        })?;
 
         Ok(ArqonSolver {
             // Use the standard PCR (Probe-Classify-Refine) algorithm for all Python consumers
@@ -43,7 +46,9 @@ impl ArqonSolver {
         let results: Vec<EvalTrace> = serde_json::from_str(&results_json).map_err(|e| {
             PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid results: {}", e))
         })?;
-        self.inner.tell(results);
+        self.inner
+            .tell(results)
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))?;
         Ok(())
     }
 
@@ -51,6 +56,28 @@ impl ArqonSolver {
         self.inner.history.len()
     }
 
+    /// Current phase: "Probe", "Classify", "Refine(Structured)",
+    /// "Refine(Chaotic)" or "Done".
+    fn phase(&self) -> String {
+        format!("{:?}", self.inner.phase)
+    }
+
+    /// Landscape label ("Structured" or "Chaotic") and classifier score,
+    /// or None until the probe phase has been classified.
+    fn classification(&self) -> Option<(String, f64)> {
+        self.inner
+            .classification
+            .map(|(landscape, score)| (format!("{:?}", landscape), score))
+    }
+
+    /// Best value seen so far (in the objective's units) and its parameters,
+    /// or None before any results.
+    fn best(&self) -> Option<(f64, HashMap<String, f64>)> {
+        self.inner
+            .original_best()
+            .map(|trace| (trace.value, trace.params))
+    }
+
     /// Seed the solver with historical evaluations.
     /// Input: JSON array of {"params": {...}, "value": f64, "cost": f64}
     ///
@@ -61,7 +88,9 @@ impl ArqonSolver {
         let seeds: Vec<SeedPoint> = serde_json::from_str(&seed_json).map_err(|e| {
             PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid seed data: {}", e))
         })?;
-        self.inner.seed(seeds);
+        self.inner
+            .seed(seeds)
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))?;
         Ok(())
     }
 
@@ -85,6 +114,16 @@ impl ArqonSolver {
     fn ask_one(&mut self) -> PyResult<Option<HashMap<String, f64>>> {
         Ok(self.inner.ask_one())
     }
+
+    /// Return the single best next point, or None when done.
+    ///
+    /// Follows the normal Probe-Classify-Refine flow but hands out one
+    /// candidate per call. The rest of each batch is cached and returned on
+    /// later calls, so the strategy is only stepped once all of a batch's
+    /// results have been reported via `tell()` or `seed()`.
+    fn suggest(&mut self) -> PyResult<Option<HashMap<String, f64>>> {
+        Ok(self.inner.suggest())
+    }
 }
 
 #[pymodule]
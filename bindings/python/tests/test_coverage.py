@@ -80,6 +80,77 @@ class TestAskOne:
         assert solver.get_history_len() >= 5
 
 
+class TestSuggest:
+    """Tests for suggest() - one point at a time over the PCR flow."""
+
+    def test_suggest_matches_probe_batch(self):
+        """suggest hands out the probe batch one point per call."""
+        config = {
+            "seed": 42,
+            "budget": 20,
+            "bounds": {"x": {"min": -2.0, "max": 2.0, "scale": "Linear"}},
+            "probe_ratio": 0.25
+        }
+        batch = ArqonSolver(json.dumps(config)).ask()
+        solver = ArqonSolver(json.dumps(config))
+
+        suggested = [solver.suggest() for _ in range(len(batch))]
+        assert suggested == batch
+        # Waiting on results for the handed-out points
+        assert solver.suggest() is None
+
+    def test_suggest_online_loop(self):
+        """suggest/seed loop runs until the budget is spent."""
+        config = {
+            "seed": 7,
+            "budget": 12,
+            "bounds": {"x": {"min": -2.0, "max": 2.0, "scale": "Linear"}},
+            "probe_ratio": 0.5
+        }
+        solver = ArqonSolver(json.dumps(config))
+
+        for _ in range(50):
+            candidate = solver.suggest()
+            if candidate is None:
+                break
+            result = [{"params": candidate, "value": candidate["x"] ** 2, "cost": 1.0}]
+            solver.seed(json.dumps(result))
+
+        assert solver.get_history_len() <= 12
+        assert solver.suggest() is None
+
+
+class TestInspection:
+    """Tests for phase(), classification() and best()."""
+
+    def test_phase_classification_and_best(self):
+        """Accessors follow the solver through probe, classify and refine."""
+        config = {
+            "seed": 42,
+            "budget": 20,
+            "bounds": {"x": {"min": -2.0, "max": 2.0, "scale": "Linear"}},
+            "probe_ratio": 0.5
+        }
+        solver = ArqonSolver(json.dumps(config))
+        assert solver.phase() == "Probe"
+        assert solver.classification() is None
+        assert solver.best() is None
+
+        batch = solver.ask()
+        results = [{"params": p, "value": p["x"] ** 2, "cost": 1.0} for p in batch]
+        solver.seed(json.dumps(results))
+        best_value, best_params = solver.best()
+        assert best_value == min(r["value"] for r in results)
+        assert best_params["x"] ** 2 == best_value
+
+        solver.ask()
+        assert solver.phase().startswith("Refine(")
+        label, score = solver.classification()
+        assert label in ("Structured", "Chaotic")
+        assert solver.phase() == f"Refine({label})"
+        assert isinstance(score, float)
+
+
 class TestArqonProbe:
     """Tests for ArqonProbe - stateless LDS sampling."""
 
@@ -183,6 +254,18 @@ class TestErrorHandling:
         
         assert "Invalid config" in str(exc_info.value)
 
+    def test_unbounded_config(self):
+        """A config with neither budget nor cost_budget raises ValueError."""
+        config = {
+            "seed": 42,
+            "bounds": {"x": {"min": 0.0, "max": 1.0, "scale": "Linear"}},
+            "probe_ratio": 0.3
+        }
+        with pytest.raises(ValueError) as exc_info:
+            ArqonSolver(json.dumps(config))
+
+        assert "budget" in str(exc_info.value)
+
     def test_invalid_tell_json(self):
         """Invalid JSON in tell raises ValueError."""
         config = {
@@ -2,6 +2,8 @@ const stateUrl = "/api/state";
 const summaryUrl = "/api/summary";
 const eventsUrl = "/api/events";
 const actionsUrl = "/api/actions";
+const streamUrl = "/api/stream";
+const runsUrl = "/api/runs";
 
 const statusDot = document.getElementById("status-dot");
 const statusText = document.getElementById("status-text");
@@ -11,25 +13,51 @@ const summaryHistory = document.getElementById("summary-history");
 const summaryBest = document.getElementById("summary-best");
 const summaryLatest = document.getElementById("summary-latest");
 const historyList = document.getElementById("history-list");
+const paretoCard = document.getElementById("pareto-card");
+const paretoList = document.getElementById("pareto-list");
 const eventList = document.getElementById("event-list");
 const actionList = document.getElementById("action-list");
 const filterEvent = document.getElementById("filter-event");
 const filterSearch = document.getElementById("filter-search");
+const runSelect = document.getElementById("run-select");
 
 const chartCanvas = document.getElementById("trend-chart");
 const chartCtx = chartCanvas.getContext("2d");
 
 let lastHistory = [];
 let eventsFilter = { event: "", q: "" };
+// Selected run when serving a --runs-dir; null for a single run
+let currentRun = null;
+let stream = null;
 
-async function fetchJson(url, params = {}) {
+function withRun(url, params = {}) {
   const query = new URLSearchParams(params);
-  const full = query.toString() ? `${url}?${query}` : url;
-  const response = await fetch(full);
+  if (currentRun) query.set("run", currentRun);
+  return query.toString() ? `${url}?${query}` : url;
+}
+
+async function fetchJson(url, params = {}) {
+  const response = await fetch(withRun(url, params));
   if (!response.ok) throw new Error("Network error");
   return response.json();
 }
 
+async function loadRuns() {
+  const { runs } = await fetchJson(runsUrl);
+  runSelect.hidden = !runs.length;
+  runSelect.innerHTML = "";
+  runs.forEach((run) => {
+    const option = document.createElement("option");
+    option.value = run.run_id;
+    option.textContent = `${run.run_id} (${run.history_len ?? "?"})`;
+    runSelect.appendChild(option);
+  });
+  if (runs.length && !runs.some((run) => run.run_id === currentRun)) {
+    currentRun = runs[0].run_id;
+  }
+  runSelect.value = currentRun ?? "";
+}
+
 function setStatus(ok) {
   statusDot.style.background = ok ? "#42ffb7" : "#ff4f4f";
   statusText.textContent = ok ? "Live" : "Offline";
@@ -41,6 +69,18 @@ function renderSummary(summary) {
   summaryHistory.textContent = summary.history_len ?? "—";
   summaryBest.textContent = summary.best ?? "—";
   summaryLatest.textContent = summary.latest ?? "—";
+  renderPareto(summary.pareto_front);
+}
+
+function renderPareto(front) {
+  paretoCard.hidden = !front;
+  if (!front) return;
+  paretoList.innerHTML = "";
+  front.forEach((entry) => {
+    paretoList.appendChild(
+      makeItem(`value=${entry.value.toFixed(6)} | cost=${entry.cost.toFixed(4)}`)
+    );
+  });
 }
 
 function renderHistory(history) {
@@ -144,7 +184,7 @@ document.getElementById("action-form").addEventListener("submit", async (event)
   const action = document.getElementById("action-name").value.trim();
   const reason = document.getElementById("action-reason").value.trim();
   if (!action) return;
-  await fetch(actionsUrl, {
+  await fetch(withRun(actionsUrl), {
     method: "POST",
     headers: { "Content-Type": "application/json" },
     body: JSON.stringify({ action, reason }),
@@ -154,5 +194,30 @@ document.getElementById("action-form").addEventListener("submit", async (event)
   refresh();
 });
 
-setInterval(refresh, 1500);
-refresh();
+function connectStream() {
+  if (stream) stream.close();
+  stream = new EventSource(withRun(streamUrl));
+  stream.addEventListener("summary", (event) => {
+    renderSummary(JSON.parse(event.data));
+    refresh();
+  });
+  // EventSource reconnects on its own; just reflect the outage
+  stream.onerror = () => setStatus(false);
+}
+
+runSelect.addEventListener("change", () => {
+  currentRun = runSelect.value;
+  if (window.EventSource) connectStream();
+  refresh();
+});
+
+loadRuns()
+  .catch(() => setStatus(false))
+  .finally(() => {
+    if (window.EventSource) {
+      connectStream();
+    } else {
+      setInterval(refresh, 1500);
+      refresh();
+    }
+  });
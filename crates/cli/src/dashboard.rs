@@ -1,10 +1,14 @@
 use crate::{load_state, Metrics};
+use arqonhpo_core::artifact::{parameter_importance, pareto_front, EvalTrace};
+use arqonhpo_core::config::Selection;
 use miette::{Context, IntoDiagnostic, Result};
 use std::collections::HashMap;
 use std::fs;
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 pub const DASHBOARD_HTML: &str = include_str!("../assets/dashboard.html");
 pub const DASHBOARD_CSS: &str = include_str!("../assets/dashboard.css");
@@ -32,6 +36,121 @@ pub fn plain_response(
         .with_header(tiny_http::Header::from_bytes(&b"Content-Type"[..], content_type).unwrap())
 }
 
+/// State file of a run inside a `--runs-dir`.
+pub const RUN_STATE_FILE: &str = "state.json";
+/// Events log of a run inside a `--runs-dir`.
+pub const RUN_EVENTS_FILE: &str = "events.jsonl";
+/// Actions log of a run inside a `--runs-dir`.
+pub const RUN_ACTIONS_FILE: &str = "actions.jsonl";
+
+/// Files backing one run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunPaths {
+    pub state: PathBuf,
+    pub events: Option<PathBuf>,
+    pub actions: Option<PathBuf>,
+}
+
+/// Where the dashboard finds its runs.
+#[derive(Debug, Clone)]
+pub enum RunSource {
+    /// A single run given by `--state`, `--events` and `--actions`.
+    Single(RunPaths),
+    /// A directory holding one `<run_id>/state.json` per run, with optional
+    /// `events.jsonl` and `actions.jsonl` next to it.
+    Dir(PathBuf),
+}
+
+impl RunSource {
+    /// Files of the run selected by the `run` query parameter.
+    ///
+    /// A single-run source ignores `run`. A directory source defaults to the
+    /// first run in sorted order and refuses ids that are not a discovered
+    /// run, so a query can never reach outside the directory.
+    pub fn resolve(&self, run: Option<&str>) -> Result<RunPaths> {
+        let dir = match self {
+            Self::Single(paths) => return Ok(paths.clone()),
+            Self::Dir(dir) => dir,
+        };
+        let runs = discover_runs(dir)?;
+        let run_id = match run {
+            Some(run) => runs
+                .iter()
+                .find(|id| id.as_str() == run)
+                .ok_or_else(|| miette::miette!("Unknown run {:?}", run))?,
+            None => runs
+                .first()
+                .ok_or_else(|| miette::miette!("No runs found in {}", dir.display()))?,
+        };
+        let run_dir = dir.join(run_id);
+        let events = run_dir.join(RUN_EVENTS_FILE);
+        Ok(RunPaths {
+            state: run_dir.join(RUN_STATE_FILE),
+            events: events.exists().then_some(events),
+            actions: Some(run_dir.join(RUN_ACTIONS_FILE)),
+        })
+    }
+
+    /// Path whose readability `GET /healthz` checks.
+    pub fn health_path(&self) -> &Path {
+        match self {
+            Self::Single(paths) => &paths.state,
+            Self::Dir(dir) => dir,
+        }
+    }
+}
+
+/// Ids of the runs in `dir`: the names of its subdirectories that contain a
+/// `state.json`, sorted.
+pub fn discover_runs(dir: &Path) -> Result<Vec<String>> {
+    let entries = fs::read_dir(dir)
+        .into_diagnostic()
+        .with_context(|| format!("Failed to read runs directory {}", dir.display()))?;
+    let mut runs: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().join(RUN_STATE_FILE).is_file())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .collect();
+    runs.sort();
+    Ok(runs)
+}
+
+/// Listing for `GET /api/runs`. Empty for a single-run source, which has
+/// nothing to choose between.
+pub fn load_runs_json(source: &RunSource) -> Result<serde_json::Value> {
+    let RunSource::Dir(dir) = source else {
+        return Ok(serde_json::json!({ "runs": [] }));
+    };
+    let runs: Vec<serde_json::Value> = discover_runs(dir)?
+        .into_iter()
+        .map(
+            |run_id| match load_state(&dir.join(&run_id).join(RUN_STATE_FILE)) {
+                Ok(state) => serde_json::json!({
+                    "run_id": run_id,
+                    "history_len": state.history.len(),
+                    "budget": state.config.budget,
+                    "best": state.best().map(|entry| entry.value),
+                }),
+                Err(err) => serde_json::json!({ "run_id": run_id, "error": err.to_string() }),
+            },
+        )
+        .collect();
+    Ok(serde_json::json!({ "runs": runs }))
+}
+
+/// Liveness report for `GET /healthz`. Fails when the state file (or runs
+/// directory) cannot be read, so a probe notices a dashboard pointed at a
+/// missing run.
+pub fn health_json(state_path: &Path, uptime: Duration) -> Result<serde_json::Value> {
+    fs::File::open(state_path)
+        .into_diagnostic()
+        .wrap_err_with(|| format!("{} is not readable", state_path.display()))?;
+    Ok(serde_json::json!({
+        "status": "ok",
+        "uptime_secs": uptime.as_secs(),
+    }))
+}
+
 pub fn load_state_json(state_path: &Path, metrics: &Metrics) -> Result<serde_json::Value> {
     let state = load_state(state_path)?;
     metrics.set_history_len(state.history.len());
@@ -40,22 +159,141 @@ pub fn load_state_json(state_path: &Path, metrics: &Metrics) -> Result<serde_jso
 
 pub fn load_summary_json(state_path: &Path) -> Result<serde_json::Value> {
     let state = load_state(state_path)?;
-    let best = state
+    let best = state.best().map(|entry| entry.value);
+    let latest = state.history.last().map(|entry| entry.value);
+    let traces: Vec<EvalTrace> = state
         .history
         .iter()
-        .map(|entry| entry.value)
-        .min_by(|left, right| left.partial_cmp(right).unwrap());
-    let latest = state.history.last().map(|entry| entry.value);
+        .enumerate()
+        .map(|(index, seed)| EvalTrace {
+            eval_id: (index + 1) as u64,
+            params: seed.params.clone(),
+            value: seed.value,
+            cost: seed.cost,
+            repeats: None,
+            penalized: false,
+            failed: false,
+            feasibility: None,
+            penalty: None,
+            fidelity: None,
+            kind: None,
+            source: None,
+        })
+        .collect();
+    let importance = parameter_importance(&traces, &state.config.bounds);
+    let front = (state.config.selection == Selection::Pareto).then(|| {
+        pareto_front(&traces)
+            .into_iter()
+            .map(|trace| {
+                serde_json::json!({
+                    "params": trace.params,
+                    "value": trace.value,
+                    "cost": trace.cost,
+                })
+            })
+            .collect::<Vec<_>>()
+    });
     let summary = serde_json::json!({
         "run_id": state.run_id,
         "budget": state.config.budget,
         "history_len": state.history.len(),
         "best": best,
         "latest": latest,
+        "importance": importance,
+        "pareto_front": front,
     });
     Ok(summary)
 }
 
+/// Modification times of the files backing the dashboard.
+///
+/// A change in the returned fingerprint means the stream should push a fresh
+/// summary. Missing files map to `None`.
+pub fn watch_fingerprint(state_path: &Path, events_path: Option<&Path>) -> Vec<Option<SystemTime>> {
+    std::iter::once(Some(state_path))
+        .chain(std::iter::once(events_path))
+        .map(|path| {
+            path.and_then(|p| fs::metadata(p).ok())
+                .and_then(|meta| meta.modified().ok())
+        })
+        .collect()
+}
+
+/// Write one Server-Sent Event frame and flush it to the client.
+pub fn write_sse_event<W: Write>(
+    writer: &mut W,
+    event: &str,
+    data: &serde_json::Value,
+) -> std::io::Result<()> {
+    write!(writer, "event: {}\ndata: {}\n\n", event, data)?;
+    writer.flush()
+}
+
+/// Most `/api/stream` connections served at once; each holds a thread.
+pub const MAX_STREAMS: usize = 32;
+
+/// Shared count of open streams.
+#[derive(Clone, Default)]
+pub struct StreamSlots(Arc<AtomicUsize>);
+
+/// One open stream, released when dropped.
+pub struct StreamSlot(Arc<AtomicUsize>);
+
+impl StreamSlots {
+    /// A slot if fewer than `max` streams are open.
+    pub fn try_acquire(&self, max: usize) -> Option<StreamSlot> {
+        self.0
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |open| {
+                (open < max).then_some(open + 1)
+            })
+            .ok()
+            .map(|_| StreamSlot(Arc::clone(&self.0)))
+    }
+}
+
+impl Drop for StreamSlot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// Stream `summary` events over an open connection until the client leaves.
+///
+/// The headers are written by hand because the connection is held open: the
+/// body is an unbounded sequence of SSE frames, flushed as each is written.
+/// Files are polled for mtime changes every `poll`; a comment line is sent
+/// as keep-alive every `keep_alive` so dropped clients are detected.
+pub fn stream_summary<W: Write>(
+    mut writer: W,
+    state_path: &Path,
+    events_path: Option<&Path>,
+    poll: Duration,
+    keep_alive: Duration,
+) -> std::io::Result<()> {
+    writer.write_all(
+        b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: keep-alive\r\n\r\n",
+    )?;
+    writer.flush()?;
+
+    let mut last_fingerprint = None;
+    let mut last_write = SystemTime::now();
+    loop {
+        let fingerprint = watch_fingerprint(state_path, events_path);
+        if last_fingerprint.as_ref() != Some(&fingerprint) {
+            let summary = load_summary_json(state_path)
+                .unwrap_or_else(|err| serde_json::json!({ "error": err.to_string() }));
+            write_sse_event(&mut writer, "summary", &summary)?;
+            last_fingerprint = Some(fingerprint);
+            last_write = SystemTime::now();
+        } else if last_write.elapsed().unwrap_or_default() >= keep_alive {
+            writer.write_all(b": keep-alive\n\n")?;
+            writer.flush()?;
+            last_write = SystemTime::now();
+        }
+        std::thread::sleep(poll);
+    }
+}
+
 pub fn load_events_json(
     events_path: Option<&PathBuf>,
     params: &HashMap<String, String>,
@@ -138,62 +376,149 @@ fn read_event_values(
     search: Option<&str>,
     limit: usize,
 ) -> Result<Vec<serde_json::Value>> {
-    let contents = fs::read_to_string(path)
-        .into_diagnostic()
+    let lines = TailLines::open(path, MAX_TAIL_SCAN_BYTES)
         .with_context(|| format!("Failed to read events file {}", path.display()))?;
-    let mut values = Vec::new();
-    for line in contents.lines().rev() {
-        if line.trim().is_empty() {
-            continue;
-        }
-        if let Some(search) = search {
-            if !line.contains(search) {
-                continue;
-            }
+    let keep = |line: &str, value: &serde_json::Value| {
+        if search.is_some_and(|search| !line.contains(search)) {
+            return false;
         }
-        let value: serde_json::Value = match serde_json::from_str(line) {
-            Ok(value) => value,
-            Err(_) => continue,
-        };
-        if let Some(filter) = filter {
+        filter.is_none_or(|filter| {
             let event_type = value
                 .get("event")
                 .or_else(|| value.get("event_type"))
                 .and_then(|field| field.as_str())
                 .unwrap_or("");
-            if event_type != filter {
-                continue;
-            }
-        }
-        values.push(value);
-        if values.len() >= limit {
-            break;
-        }
-    }
-    values.reverse();
-    Ok(values)
+            event_type == filter
+        })
+    };
+    Ok(last_values(lines, limit, keep))
 }
 
 fn read_jsonl_values(path: &Path, limit: usize) -> Result<Vec<serde_json::Value>> {
-    let contents = fs::read_to_string(path)
-        .into_diagnostic()
+    let lines = TailLines::open(path, MAX_TAIL_SCAN_BYTES)
         .with_context(|| format!("Failed to read actions file {}", path.display()))?;
+    Ok(last_values(lines, limit, |_, _| true))
+}
+
+/// The last `limit` JSON values among `lines` (newest first) that `keep`
+/// accepts, in file order. Blank and malformed lines are skipped.
+fn last_values(
+    lines: TailLines,
+    limit: usize,
+    keep: impl Fn(&str, &serde_json::Value) -> bool,
+) -> Vec<serde_json::Value> {
     let mut values = Vec::new();
-    for line in contents.lines().rev() {
+    for line in lines {
+        if values.len() >= limit {
+            break;
+        }
         if line.trim().is_empty() {
             continue;
         }
-        let value: serde_json::Value = match serde_json::from_str(line) {
+        let value: serde_json::Value = match serde_json::from_str(&line) {
             Ok(value) => value,
             Err(_) => continue,
         };
-        values.push(value);
-        if values.len() >= limit {
-            break;
+        if keep(&line, &value) {
+            values.push(value);
         }
     }
     values.reverse();
-    Ok(values)
+    values
+}
+
+/// Bytes read per backward step by [`TailLines`].
+const TAIL_CHUNK_BYTES: u64 = 64 * 1024;
+/// How far back from the end of a JSONL file a dashboard read scans. Bounds
+/// a filter or search that matches little in a long run's events log.
+const MAX_TAIL_SCAN_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Lines of an append-only file from last to first.
+///
+/// The file is read backwards in chunks, so taking the newest few lines of
+/// a large events log touches only its end, however long the run has been
+/// appending to it. At most `max_bytes` from the end are read; older lines
+/// are not returned.
+struct TailLines {
+    file: fs::File,
+    /// Start of the part of the file not read yet.
+    pos: u64,
+    /// Bytes at `pos` that end before the first newline read so far: the
+    /// tail of a line whose start is still unread.
+    partial: Vec<u8>,
+    /// Complete lines of the last chunk, in file order.
+    ready: Vec<String>,
+    /// First byte that may be read (`len - max_bytes`).
+    floor: u64,
+    /// Bytes read from the file so far.
+    bytes_read: u64,
+}
+
+impl TailLines {
+    fn open(path: &Path, max_bytes: u64) -> Result<Self> {
+        let file = fs::File::open(path).into_diagnostic()?;
+        let len = file.metadata().into_diagnostic()?.len();
+        Ok(Self {
+            file,
+            pos: len,
+            partial: Vec::new(),
+            ready: Vec::new(),
+            floor: len.saturating_sub(max_bytes),
+            bytes_read: 0,
+        })
+    }
+
+    /// Read the chunk before `pos` and split off its complete lines.
+    fn read_chunk(&mut self) -> std::io::Result<()> {
+        use std::io::{Seek, SeekFrom};
+
+        let start = self.pos.saturating_sub(TAIL_CHUNK_BYTES).max(self.floor);
+        let mut chunk = vec![0; (self.pos - start) as usize];
+        self.file.seek(SeekFrom::Start(start))?;
+        self.file.read_exact(&mut chunk)?;
+        self.bytes_read += chunk.len() as u64;
+        chunk.append(&mut self.partial);
+        self.pos = start;
+
+        // Unless the chunk starts the file, its first line may begin before
+        // it: carry that over to the next chunk back, or drop it at the end
+        // of the scan.
+        if start > 0 {
+            let Some(newline) = chunk.iter().position(|&byte| byte == b'\n') else {
+                if start > self.floor {
+                    self.partial = chunk;
+                }
+                return Ok(());
+            };
+            if start > self.floor {
+                self.partial = chunk[..newline].to_vec();
+            }
+            chunk.drain(..=newline);
+        }
+        self.ready = chunk
+            .split(|&byte| byte == b'\n')
+            .map(|line| String::from_utf8_lossy(line).into_owned())
+            .collect();
+        Ok(())
+    }
+}
+
+impl Iterator for TailLines {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        loop {
+            if let Some(line) = self.ready.pop() {
+                return Some(line);
+            }
+            if self.pos == self.floor {
+                return None;
+            }
+            if self.read_chunk().is_err() {
+                return None;
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -201,11 +526,21 @@ mod tests {
     use super::*;
     use crate::SolverState;
     use arqonhpo_core::artifact::SeedPoint;
-    use arqonhpo_core::config::{Domain, Scale, SolverConfig};
+    use arqonhpo_core::config::{Domain, ProbeBudget, Scale, SolverConfig};
     use std::fs;
     use std::io::Cursor;
     use tempfile::NamedTempFile;
 
+    #[test]
+    fn test_stream_slots_cap_open_streams() {
+        let slots = StreamSlots::default();
+        let first = slots.try_acquire(2).unwrap();
+        let _second = slots.try_acquire(2).unwrap();
+        assert!(slots.try_acquire(2).is_none());
+        drop(first);
+        assert!(slots.try_acquire(2).is_some());
+    }
+
     fn create_test_state() -> SolverState {
         let mut bounds = HashMap::new();
         bounds.insert(
@@ -214,6 +549,7 @@ mod tests {
                 min: 0.0,
                 max: 1.0,
                 scale: Scale::Linear,
+                active_when: None,
             },
         );
         SolverState {
@@ -221,22 +557,48 @@ mod tests {
                 bounds,
                 budget: 10,
                 seed: 42,
-                probe_ratio: 0.3,
+                probe_ratio: ProbeBudget::Fixed(0.3),
                 strategy_params: None,
+                constraints: Vec::new(),
+                selection: Default::default(),
+                cost_budget: None,
+                observation_noise: None,
+                max_restarts: 0,
+                non_finite: Default::default(),
+                repeats: 0,
+                repeat_reducer: Default::default(),
+                objective_transform: None,
+                fixed: Default::default(),
+                target_value: None,
+                target_tol: 0.0,
+                history_cap: None,
+                invalid_params: Default::default(),
+                infeasibility_penalty: 0.0,
+                initial_points: Vec::new(),
             },
             history: vec![
                 SeedPoint {
                     params: [("x".to_string(), 0.5)].into_iter().collect(),
                     value: 0.25,
                     cost: 1.0,
+                    penalized: false,
+                    failed: false,
+                    feasibility: None,
                 },
                 SeedPoint {
                     params: [("x".to_string(), 0.3)].into_iter().collect(),
                     value: 0.10,
                     cost: 1.0,
+                    penalized: false,
+                    failed: false,
+                    feasibility: None,
                 },
             ],
             run_id: Some("test-run".to_string()),
+            warm_start: false,
+            compacted: Default::default(),
+            suggest: None,
+            metrics: None,
         }
     }
 
@@ -285,7 +647,7 @@ mod tests {
         let state = create_test_state();
         fs::write(&path, serde_json::to_string(&state).unwrap()).into_diagnostic()?;
 
-        let metrics = Metrics::init(None)?;
+        let metrics = Metrics::init(None, None, &HashMap::new())?;
         let result = load_state_json(&path, &metrics)?;
 
         assert!(result.get("config").is_some());
@@ -310,6 +672,35 @@ mod tests {
         assert!((result["best"].as_f64().unwrap() - 0.10).abs() < 0.001);
         // Latest value should be 0.10 (last entry)
         assert!((result["latest"].as_f64().unwrap() - 0.10).abs() < 0.001);
+        // Fewer than 3 evaluations: no importance scores
+        assert_eq!(result["importance"], serde_json::json!({}));
+        // Value selection: no front reported
+        assert!(result["pareto_front"].is_null());
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_summary_json_pareto_front() -> Result<()> {
+        let file = NamedTempFile::new().into_diagnostic()?;
+        let path = file.path().to_path_buf();
+        let mut state = create_test_state();
+        state.config.selection = Selection::Pareto;
+        state.history[0].cost = 0.5;
+        state.history.push(SeedPoint {
+            params: [("x".to_string(), 0.9)].into_iter().collect(),
+            value: 0.30,
+            cost: 2.0,
+            penalized: false,
+            failed: false,
+            feasibility: None,
+        });
+        fs::write(&path, serde_json::to_string(&state).unwrap()).into_diagnostic()?;
+
+        let result = load_summary_json(&path)?;
+        let front = result["pareto_front"].as_array().unwrap();
+        // (0.25, 0.5) and (0.10, 1.0) trade off; (0.30, 2.0) is dominated
+        assert_eq!(front.len(), 2);
+        assert!(front.iter().all(|entry| entry["value"] != 0.30));
         Ok(())
     }
 
@@ -324,7 +715,7 @@ mod tests {
         ];
         fs::write(&path, events.join("\n")).into_diagnostic()?;
 
-        let _metrics = Metrics::init(None)?; // Dummy metrics
+        let _metrics = Metrics::init(None, None, &HashMap::new())?; // Dummy metrics
 
         // Test Filter
         let mut params = HashMap::new();
@@ -405,6 +796,59 @@ not json
         Ok(())
     }
 
+    #[test]
+    fn test_read_events_only_reads_the_tail() -> Result<()> {
+        let file = NamedTempFile::new().into_diagnostic()?;
+        let path = file.path().to_path_buf();
+        let old: Vec<String> = (0..20_000)
+            .map(|i| {
+                format!(
+                    r#"{{"event": "eval", "eval_id": {}, "pad": "{:0>64}"}}"#,
+                    i, i
+                )
+            })
+            .collect();
+        fs::write(&path, old.join("\n") + "\n").into_diagnostic()?;
+        let size = fs::metadata(&path).into_diagnostic()?.len();
+        assert!(size > 20 * TAIL_CHUNK_BYTES);
+
+        let values = read_event_values(&path, None, None, 10)?;
+        assert_eq!(values.len(), 10);
+        assert_eq!(values[0]["eval_id"], 19_990);
+        assert_eq!(values[9]["eval_id"], 19_999);
+
+        // Appended events come back without rereading what came before.
+        append_line(&path, r#"{"event": "restart", "eval_id": 20000}"#)?;
+        append_line(&path, r#"{"event": "eval", "eval_id": 20001}"#)?;
+        let mut lines = TailLines::open(&path, MAX_TAIL_SCAN_BYTES)?;
+        let newest: Vec<String> = lines.by_ref().take(3).collect();
+        assert_eq!(newest[0], "");
+        assert!(newest[1].contains("20001"));
+        assert!(newest[2].contains("20000"));
+        assert!(lines.bytes_read <= TAIL_CHUNK_BYTES);
+
+        let mut params = HashMap::new();
+        params.insert("event".to_string(), "restart".to_string());
+        let result = load_events_json(Some(&path), &params)?;
+        assert_eq!(result["events"].as_array().unwrap().len(), 1);
+
+        // A line split across chunk boundaries is reassembled.
+        let lines: Vec<String> = TailLines::open(&path, MAX_TAIL_SCAN_BYTES)?
+            .filter(|line| !line.is_empty())
+            .collect();
+        assert_eq!(lines.len(), old.len() + 2);
+        assert_eq!(lines[2], old[old.len() - 1]);
+        assert_eq!(lines[lines.len() - 1], old[0]);
+
+        // The scan stops `max_bytes` back, dropping the cut-off line.
+        let bounded: Vec<String> = TailLines::open(&path, 1000)?.collect();
+        assert!(bounded.len() < 15);
+        assert!(bounded
+            .iter()
+            .all(|line| line.is_empty() || line.starts_with('{')));
+        Ok(())
+    }
+
     #[test]
     fn test_load_actions_limit() -> Result<()> {
         let file = NamedTempFile::new().into_diagnostic()?;
@@ -511,4 +955,134 @@ broken json
         assert!(!DASHBOARD_CSS.trim().is_empty());
         assert!(!DASHBOARD_JS.trim().is_empty());
     }
+
+    #[test]
+    fn test_discover_runs_in_runs_dir() -> Result<()> {
+        let dir = tempfile::tempdir().into_diagnostic()?;
+        let state = serde_json::to_string(&create_test_state()).unwrap();
+        for run in ["run-b", "run-a"] {
+            fs::create_dir(dir.path().join(run)).into_diagnostic()?;
+            fs::write(dir.path().join(run).join(RUN_STATE_FILE), &state).into_diagnostic()?;
+        }
+        fs::write(dir.path().join("run-a").join(RUN_EVENTS_FILE), "").into_diagnostic()?;
+        // Neither a directory without state nor a stray file is a run.
+        fs::create_dir(dir.path().join("empty")).into_diagnostic()?;
+        fs::write(dir.path().join("notes.txt"), "").into_diagnostic()?;
+
+        assert_eq!(discover_runs(dir.path())?, vec!["run-a", "run-b"]);
+
+        let source = RunSource::Dir(dir.path().to_path_buf());
+        let runs = load_runs_json(&source)?;
+        assert_eq!(runs["runs"][0]["run_id"], "run-a");
+        assert_eq!(runs["runs"][1]["history_len"], 2);
+        assert_eq!(runs["runs"][1]["best"], 0.10);
+
+        let default = source.resolve(None)?;
+        assert_eq!(default.state, dir.path().join("run-a").join(RUN_STATE_FILE));
+        assert!(default.events.is_some());
+        let b = source.resolve(Some("run-b"))?;
+        assert_eq!(b.state, dir.path().join("run-b").join(RUN_STATE_FILE));
+        assert_eq!(b.events, None);
+        assert_eq!(
+            b.actions,
+            Some(dir.path().join("run-b").join(RUN_ACTIONS_FILE))
+        );
+        assert!(source.resolve(Some("empty")).is_err());
+        assert!(source.resolve(Some("../run-a")).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_single_run_source_ignores_run_param() -> Result<()> {
+        let paths = RunPaths {
+            state: PathBuf::from("state.json"),
+            events: None,
+            actions: None,
+        };
+        let source = RunSource::Single(paths.clone());
+        assert_eq!(source.resolve(Some("other"))?, paths);
+        assert_eq!(load_runs_json(&source)?["runs"], serde_json::json!([]));
+        Ok(())
+    }
+
+    /// Writer that accepts a fixed number of flushed frames, then reports a
+    /// closed connection, so `stream_summary` terminates in tests.
+    struct ClosingWriter {
+        buffer: Vec<u8>,
+        flushes_left: usize,
+    }
+
+    impl Write for ClosingWriter {
+        fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+            if self.flushes_left == 0 {
+                return Err(std::io::ErrorKind::BrokenPipe.into());
+            }
+            self.buffer.extend_from_slice(data);
+            Ok(data.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            self.flushes_left = self.flushes_left.saturating_sub(1);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_write_sse_event_format() {
+        let mut buffer = Vec::new();
+        write_sse_event(&mut buffer, "summary", &serde_json::json!({"best": 1.5})).unwrap();
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "event: summary\ndata: {\"best\":1.5}\n\n"
+        );
+    }
+
+    #[test]
+    fn test_watch_fingerprint_tracks_changes() -> Result<()> {
+        let file = NamedTempFile::new().into_diagnostic()?;
+        let before = watch_fingerprint(file.path(), None);
+        assert_eq!(before.len(), 2);
+        assert!(before[0].is_some());
+        assert!(before[1].is_none());
+
+        let later = SystemTime::now() + Duration::from_secs(5);
+        fs::File::options()
+            .write(true)
+            .open(file.path())
+            .into_diagnostic()?
+            .set_modified(later)
+            .into_diagnostic()?;
+        assert_ne!(watch_fingerprint(file.path(), None), before);
+        Ok(())
+    }
+
+    #[test]
+    fn test_stream_summary_pushes_summary_then_stops_on_disconnect() -> Result<()> {
+        let file = NamedTempFile::new().into_diagnostic()?;
+        fs::write(
+            file.path(),
+            serde_json::to_string(&create_test_state()).unwrap(),
+        )
+        .into_diagnostic()?;
+
+        let mut writer = ClosingWriter {
+            buffer: Vec::new(),
+            flushes_left: 2,
+        };
+        let result = stream_summary(
+            &mut writer,
+            file.path(),
+            None,
+            Duration::from_millis(1),
+            Duration::from_millis(1),
+        );
+        assert!(result.is_err());
+
+        let output = String::from_utf8(writer.buffer).unwrap();
+        assert!(output.starts_with("HTTP/1.1 200 OK"));
+        assert!(output.contains("Content-Type: text/event-stream"));
+        assert!(output.contains("event: summary\ndata: "));
+        assert!(output.contains("\"run_id\":\"test-run\""));
+        Ok(())
+    }
 }
@@ -1,8 +1,17 @@
 #![allow(clippy::disallowed_types)]
 
-use arqonhpo_core::artifact::{EvalTrace, RunArtifact, SeedPoint};
-use arqonhpo_core::config::SolverConfig;
-use arqonhpo_core::machine::Solver;
+use arqonhpo_core::artifact::{
+    cmp_params, cmp_traces, parameter_importance, pareto_front, EvalTrace, RunArtifact, SeedPoint,
+    TraceKind, ARTIFACT_FORMAT_VERSION, FIDELITY_KEY, RESERVED_KEYS,
+};
+use arqonhpo_core::config::{
+    diff01, Domain, NonFinitePolicy, ObjectiveTransform, Scale, Selection, SolverConfig,
+};
+use arqonhpo_core::machine::{CompactedHistory, RunPlan, Solver};
+use arqonhpo_core::strategies::halving::HalvingConfig;
+use arqonhpo_core::strategies::nelder_mead::NelderMead;
+use arqonhpo_core::strategies::tpe::TPE;
+use arqonhpo_core::strategies::StrategyKind;
 use clap::{Parser, Subcommand, ValueEnum};
 use crossterm::event::{self, Event, KeyCode};
 use crossterm::terminal::{
@@ -10,24 +19,31 @@ use crossterm::terminal::{
 };
 use crossterm::ExecutableCommand;
 use miette::{Context, IntoDiagnostic, Result};
-use prometheus::{Encoder, Histogram, HistogramOpts, IntCounter, IntGauge, Registry, TextEncoder};
+use prometheus::{
+    Counter, Encoder, Gauge, GaugeVec, Histogram, HistogramOpts, IntCounter, IntGauge, Opts,
+    Registry, TextEncoder,
+};
 use ratatui::backend::CrosstermBackend;
-use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
 use ratatui::prelude::Frame;
 use ratatui::style::Style;
+use ratatui::symbols;
 use ratatui::text::Line;
-use ratatui::widgets::{Block, Borders, List, ListItem, Paragraph};
+use ratatui::widgets::{
+    Axis, Block, Borders, Chart, Dataset, GraphType, List, ListItem, Paragraph,
+};
 use ratatui::Terminal;
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fs;
 use std::io::{self, BufRead, Read, Write};
 use std::path::{Path, PathBuf};
 use std::process::Command;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 use std::thread;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tiny_http::{Response, Server};
 
 #[derive(Parser)]
@@ -39,6 +55,9 @@ struct Cli {
     log_level: String,
     #[arg(long)]
     metrics_addr: Option<String>,
+    /// Push metrics to this Prometheus Pushgateway after every tell
+    #[arg(long)]
+    metrics_push_url: Option<String>,
     #[command(subcommand)]
     command: Commands,
 }
@@ -49,15 +68,202 @@ enum LogFormat {
     Json,
 }
 
+/// Wire format for `interactive` commands and responses.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+enum Protocol {
+    /// One JSON object per line.
+    #[default]
+    Json,
+    /// MessagePack frames, each prefixed by a 4-byte big-endian length.
+    Msgpack,
+}
+
+/// Output format for `audit`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+enum AuditFormat {
+    /// Aligned timeline, colorized on a terminal.
+    #[default]
+    Text,
+    Csv,
+    Json,
+}
+
+/// File format for configs, results and exported artifacts.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+enum DataFormat {
+    #[default]
+    Json,
+    Yaml,
+    Toml,
+}
+
+impl DataFormat {
+    /// Format implied by a file's extension (`.yaml`/`.yml`, `.toml`); JSON
+    /// for anything else.
+    fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("yaml") || ext.eq_ignore_ascii_case("yml") => {
+                Self::Yaml
+            }
+            Some(ext) if ext.eq_ignore_ascii_case("toml") => Self::Toml,
+            _ => Self::Json,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Json => "JSON",
+            Self::Yaml => "YAML",
+            Self::Toml => "TOML",
+        }
+    }
+
+    /// Deserialize `contents`. YAML goes through a JSON value so enums are
+    /// written as in JSON (`{Penalize: {value: 1.0}}`) rather than with YAML
+    /// tags.
+    fn parse<T: DeserializeOwned>(self, contents: &str) -> Result<T> {
+        match self {
+            Self::Json => serde_json::from_str(contents).into_diagnostic(),
+            Self::Yaml => {
+                let value: serde_json::Value =
+                    serde_yaml_ng::from_str(contents).into_diagnostic()?;
+                serde_json::from_value(value).into_diagnostic()
+            }
+            Self::Toml => toml::from_str(contents).into_diagnostic(),
+        }
+    }
+
+    fn render<T: Serialize>(self, value: &T) -> Result<String> {
+        match self {
+            Self::Json => serde_json::to_string_pretty(value).into_diagnostic(),
+            Self::Yaml => {
+                let value = serde_json::to_value(value).into_diagnostic()?;
+                serde_yaml_ng::to_string(&value).into_diagnostic()
+            }
+            Self::Toml => toml::to_string_pretty(value).into_diagnostic(),
+        }
+    }
+}
+
+/// Output format of `export`: the artifact in one of the [`DataFormat`]s, or
+/// its history as a flat CSV table.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+enum ExportFormat {
+    #[default]
+    Json,
+    Yaml,
+    Toml,
+    Csv,
+}
+
+impl ExportFormat {
+    /// Format implied by a file's extension: `.csv`, else as
+    /// [`DataFormat::from_path`].
+    fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("csv") => Self::Csv,
+            _ => match DataFormat::from_path(path) {
+                DataFormat::Json => Self::Json,
+                DataFormat::Yaml => Self::Yaml,
+                DataFormat::Toml => Self::Toml,
+            },
+        }
+    }
+
+    /// The artifact format, or `None` for the CSV table.
+    fn data_format(self) -> Option<DataFormat> {
+        match self {
+            Self::Json => Some(DataFormat::Json),
+            Self::Yaml => Some(DataFormat::Yaml),
+            Self::Toml => Some(DataFormat::Toml),
+            Self::Csv => None,
+        }
+    }
+}
+
+/// TOML has no top-level arrays, so TOML results files list them as
+/// `[[results]]` tables.
+#[derive(Deserialize)]
+struct TomlResults {
+    results: Vec<SeedPoint>,
+}
+
+/// Results passed to `tell`: a list of full results, or values keyed by
+/// the candidate indices of an `ask --keyed` batch.
+enum TellInput {
+    Keyed(KeyedResults),
+    Points(Vec<SeedPoint>),
+}
+
+/// `tell` input for an `ask --keyed` batch.
+#[derive(Debug, Serialize, Deserialize)]
+struct KeyedResults {
+    batch_id: String,
+    /// Values by candidate index. String keys, because TOML table keys
+    /// always are.
+    results: BTreeMap<String, f64>,
+}
+
+/// Output of `ask --keyed`.
+#[derive(Debug, Serialize, Deserialize)]
+struct KeyedBatch {
+    candidates: BTreeMap<usize, HashMap<String, f64>>,
+    batch_id: String,
+}
+
+/// Refine strategy for `run --resume-strategy`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum ResumeStrategy {
+    NelderMead,
+    Tpe,
+}
+
+impl ResumeStrategy {
+    fn kind(self) -> StrategyKind {
+        match self {
+            Self::NelderMead => StrategyKind::NelderMead,
+            Self::Tpe => StrategyKind::Tpe,
+        }
+    }
+}
+
 #[derive(Subcommand)]
 enum Commands {
     Run {
         #[arg(long)]
         config: PathBuf,
-        #[arg(long)]
-        script: PathBuf,
+        #[arg(long, required_unless_present = "external")]
+        script: Option<PathBuf>,
+        /// Write each batch to stdout and read results from stdin instead of
+        /// running a script
+        #[arg(long, conflicts_with_all = ["script", "partial"])]
+        external: bool,
         #[arg(long)]
         state: Option<PathBuf>,
+        /// Print the probe/refine plan and exit without running the script
+        #[arg(long)]
+        dry_run: bool,
+        /// JSONL log of per-candidate results, used to resume a crashed batch
+        #[arg(long, requires = "state")]
+        partial: Option<PathBuf>,
+        /// Append one CSV row per evaluation to this file as results come in
+        #[arg(long)]
+        csv: Option<PathBuf>,
+        /// Refine with this strategy whatever the landscape classification says
+        #[arg(long, value_enum)]
+        resume_strategy: Option<ResumeStrategy>,
+        /// Record an evaluation whose script fails with `--failure-value`
+        /// and keep going instead of aborting the run
+        #[arg(long, requires = "failure_value", conflicts_with = "external")]
+        continue_on_error: bool,
+        /// Value recorded for failed evaluations under `--continue-on-error`
+        #[arg(long, requires = "continue_on_error", allow_hyphen_values = true)]
+        failure_value: Option<f64>,
+        /// Stop starting new evaluations after this many seconds, then save
+        /// the state and print the best point. A running evaluation is
+        /// never cut short.
+        #[arg(long, conflicts_with = "external")]
+        max_wallclock_secs: Option<u64>,
     },
     Ask {
         #[arg(long)]
@@ -66,18 +272,35 @@ enum Commands {
         state: Option<PathBuf>,
         #[arg(long)]
         batch: Option<usize>,
+        /// Wrap the candidates in a status object with phase and history length.
+        #[arg(long)]
+        verbose: bool,
+        /// Print the candidates as an object keyed by index, with a batch id
+        /// that `tell` accepts in place of the params.
+        #[arg(long, conflicts_with = "verbose")]
+        keyed: bool,
     },
     Tell {
         #[arg(long)]
         state: PathBuf,
         #[arg(long)]
         results: Option<PathBuf>,
+        /// Print the solver status after recording the results.
+        #[arg(long)]
+        verbose: bool,
+    },
+    /// Print the single best next point as one JSON object.
+    Suggest {
+        #[arg(long)]
+        state: PathBuf,
     },
     Interactive {
         #[arg(long)]
         config: PathBuf,
         #[arg(long)]
         state: Option<PathBuf>,
+        #[arg(long, value_enum, default_value_t = Protocol::Json)]
+        protocol: Protocol,
     },
     Export {
         #[arg(long)]
@@ -86,12 +309,34 @@ enum Commands {
         output: Option<PathBuf>,
         #[arg(long)]
         run_id: Option<String>,
+        /// Attach a per-parameter importance report to the artifact
+        #[arg(long)]
+        importance: bool,
+        /// Artifact format, or `csv` for a table of the history; defaults to
+        /// the output file's extension, else JSON
+        #[arg(long, value_enum)]
+        format: Option<ExportFormat>,
+    },
+    /// Cut a state's history back to its first evaluations and continue
+    /// from there on the next `ask` or `run`
+    Truncate {
+        #[arg(long)]
+        state: PathBuf,
+        /// Number of evaluations to keep, in the order they were told
+        #[arg(long)]
+        keep: usize,
+        /// Replace the state's config with this one
+        #[arg(long)]
+        config: Option<PathBuf>,
     },
     Import {
         #[arg(long)]
         artifact: PathBuf,
         #[arg(long)]
         state: PathBuf,
+        /// Skip probing on later runs and seed the refine strategy from the imported history
+        #[arg(long)]
+        warm_start: bool,
     },
     Tui {
         #[arg(long)]
@@ -102,33 +347,237 @@ enum Commands {
         refresh_ms: u64,
     },
     Dashboard {
-        #[arg(long)]
-        state: PathBuf,
+        #[arg(long, required_unless_present = "runs_dir")]
+        state: Option<PathBuf>,
         #[arg(long)]
         events: Option<PathBuf>,
         #[arg(long)]
         actions: Option<PathBuf>,
+        /// Serve every `<run_id>/state.json` under this directory instead of
+        /// a single run
+        #[arg(long, conflicts_with_all = ["state", "events", "actions"])]
+        runs_dir: Option<PathBuf>,
         #[arg(long, default_value = "127.0.0.1:3030")]
         addr: String,
+        /// Origin allowed to call /api/* from a browser, sent as the
+        /// Access-Control-Allow-Origin header. No header without it
+        #[arg(long)]
+        cors_origin: Option<String>,
     },
     Validate {
         #[arg(long)]
         config: PathBuf,
+        /// Print the parsed config in this format instead of "Config OK"
+        #[arg(long, value_enum)]
+        format: Option<DataFormat>,
+    },
+    /// Check a config, and optionally a script and state file, for common
+    /// misconfigurations and suggest fixes
+    Doctor {
+        #[arg(long)]
+        config: PathBuf,
+        /// Evaluate this script once at the midpoint of the bounds
+        #[arg(long)]
+        script: Option<PathBuf>,
+        /// Check this state file against the config
+        #[arg(long)]
+        state: Option<PathBuf>,
+    },
+    /// Re-run an artifact's solver and check it reproduces the recorded candidates
+    Replay {
+        #[arg(long)]
+        artifact: PathBuf,
+        /// Maximum absolute difference allowed per parameter
+        #[arg(long, default_value_t = 1e-9)]
+        tolerance: f64,
+    },
+    /// Compare the best points and convergence of two run artifacts
+    Compare {
+        #[arg(long)]
+        a: PathBuf,
+        #[arg(long)]
+        b: PathBuf,
+        /// Unit-space distance below which the two best points count as the
+        /// same basin
+        #[arg(long, default_value_t = 0.1)]
+        basin_radius: f64,
+        /// Print the report as JSON instead of a table
+        #[arg(long)]
+        json: bool,
+    },
+    /// Write a config skeleton whose bounds cover the parameter values in a CSV
+    /// of past evaluations
+    InferBounds {
+        /// CSV with a header row: one column per parameter plus the value column
+        #[arg(long)]
+        data: PathBuf,
+        #[arg(long)]
+        out: Option<PathBuf>,
+        /// Widen each range by this fraction of the observed span on both sides
+        #[arg(long, default_value_t = 0.1)]
+        margin: f64,
+        /// Column holding the objective; not turned into a parameter
+        #[arg(long, default_value = "value")]
+        value_column: String,
+        #[arg(long, default_value_t = 100)]
+        budget: u64,
+    },
+    /// Turn an adaptation audit log into a timeline of proposals and outcomes
+    Audit {
+        /// JSONL audit log, one `AuditEvent` object per line
+        #[arg(long)]
+        log: PathBuf,
+        #[arg(long, value_enum, default_value = "text")]
+        format: AuditFormat,
+    },
+    /// Run the full solve once per seed and summarize the best values found
+    Bench {
+        #[arg(long)]
+        config: PathBuf,
+        #[arg(long)]
+        script: PathBuf,
+        /// Seeds to run: `start..end` (end exclusive), `start..=end`, or one seed
+        #[arg(long, value_parser = parse_seed_range)]
+        seeds: std::ops::Range<u64>,
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+    /// Evaluate a 2-D grid around the best point for plotting the landscape.
+    ///
+    /// Without `--script` or `--results` the grid candidates are printed for
+    /// evaluation; pass their values back with `--results` to get the matrix.
+    Explore {
+        #[arg(long)]
+        state: PathBuf,
+        /// Point to center the grid on
+        #[arg(long, value_enum, default_value_t = Around::Best)]
+        around: Around,
+        /// Grid points per dimension
+        #[arg(long, default_value_t = 11, value_parser = clap::value_parser!(u64).range(2..))]
+        resolution: u64,
+        /// The two parameters to vary, as `a,b`: `a` along columns, `b` along rows
+        #[arg(long, value_delimiter = ',', num_args = 1, required = true)]
+        dims: Vec<String>,
+        /// Grid width as a fraction of each parameter's range (in its scale)
+        #[arg(long, default_value_t = 0.25, value_parser = parse_span)]
+        span: f64,
+        /// Evaluate the grid with this script
+        #[arg(long, conflicts_with = "results")]
+        script: Option<PathBuf>,
+        /// Values of the printed grid candidates, in the same order
+        #[arg(long)]
+        results: Option<PathBuf>,
+        #[arg(long)]
+        out: Option<PathBuf>,
     },
 }
 
+/// Grid center for `explore`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum Around {
+    /// Lowest full-fidelity value in the state's history.
+    Best,
+}
+
 #[derive(Serialize, Deserialize)]
 struct SolverState {
     config: SolverConfig,
     history: Vec<SeedPoint>,
     #[serde(default)]
     run_id: Option<String>,
+    /// Jump straight to classification when history covers the probe budget.
+    #[serde(default)]
+    warm_start: bool,
+    /// Evaluations `history_cap` compaction dropped from `history`; they
+    /// still count against the budget when the run resumes.
+    #[serde(default, skip_serializing_if = "CompactedHistory::is_empty")]
+    compacted: CompactedHistory,
+    /// Batch remainder cached by `suggest` between invocations.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    suggest: Option<SuggestCursor>,
+    /// Counters of the `run` process that last wrote the state.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    metrics: Option<RunMetrics>,
+}
+
+impl SolverState {
+    /// Best evaluation in `history`, ranked as the solver ranks it (see
+    /// [`cmp_objective`]): infeasible ones only count while no feasible one
+    /// exists.
+    fn best(&self) -> Option<&SeedPoint> {
+        let infeasible = |p: &SeedPoint| p.feasibility.is_some_and(|f| f < 1.0);
+        self.history.iter().min_by(|a, b| {
+            infeasible(a)
+                .cmp(&infeasible(b))
+                .then_with(|| cmp_objective(&self.config, a, b))
+        })
+    }
+}
+
+/// [`arqonhpo_core::artifact::cmp_seed_points`] for values in the objective's original units: under
+/// `ObjectiveTransform::Negate` the largest value is the best.
+fn cmp_objective(config: &SolverConfig, a: &SeedPoint, b: &SeedPoint) -> std::cmp::Ordering {
+    config
+        .minimized(a.value)
+        .total_cmp(&config.minimized(b.value))
+        .then_with(|| cmp_params(&a.params, &b.params))
+}
+
+/// Durable copy of the in-memory [`Metrics`], saved with the state of a `run`.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct RunMetrics {
+    asks: u64,
+    tells: u64,
+    candidates: u64,
+    results: u64,
+    /// Sum of the `cost` of every ingested result.
+    cost: f64,
+    /// Script evaluation latency; absent if nothing was evaluated.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    eval_seconds: Option<LatencySummary>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct LatencySummary {
+    count: usize,
+    min: f64,
+    mean: f64,
+    /// Nearest-rank 95th percentile.
+    p95: f64,
+    max: f64,
+}
+
+impl LatencySummary {
+    fn from_samples(samples: &[f64]) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+        let mut sorted = samples.to_vec();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let n = sorted.len();
+        let rank = (0.95 * n as f64).ceil() as usize;
+        Some(Self {
+            count: n,
+            min: sorted[0],
+            mean: sorted.iter().sum::<f64>() / n as f64,
+            p95: sorted[rank.clamp(1, n) - 1],
+            max: sorted[n - 1],
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SuggestCursor {
+    pending: Vec<HashMap<String, f64>>,
+    until: usize,
 }
 
 struct LoadedState {
     config: SolverConfig,
     history: Vec<SeedPoint>,
     run_id: Option<String>,
+    warm_start: bool,
+    compacted: CompactedHistory,
 }
 
 #[derive(Deserialize)]
@@ -148,6 +597,29 @@ struct InteractiveTellResponse {
     ok: bool,
 }
 
+/// Output of `ask --verbose`, `tell --verbose` and `truncate`.
+#[derive(Serialize)]
+struct StatusResponse {
+    /// Proposed batch; empty once the solver is done. Absent for `tell` and
+    /// `truncate`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    candidates: Option<Vec<HashMap<String, f64>>>,
+    phase: String,
+    history_len: usize,
+    done: bool,
+}
+
+impl StatusResponse {
+    fn new(solver: &Solver, candidates: Option<Vec<HashMap<String, f64>>>) -> Self {
+        Self {
+            candidates,
+            phase: solver.phase().name().to_string(),
+            history_len: solver.history.len(),
+            done: solver.is_done(),
+        }
+    }
+}
+
 struct Metrics {
     registry: Registry,
     ask_calls: IntCounter,
@@ -156,10 +628,25 @@ struct Metrics {
     results_ingested: IntCounter,
     history_len: IntGauge,
     eval_seconds: Histogram,
+    /// Raw latencies behind `eval_seconds`, for [`Metrics::snapshot`].
+    eval_samples: Mutex<Vec<f64>>,
+    cost_total: Counter,
+    best_value: Gauge,
+    param_value: GaugeVec,
+    push_url: Option<String>,
 }
 
+/// Job label used when pushing to a Pushgateway.
+const PUSH_JOB: &str = "arqonhpo";
+
 impl Metrics {
-    fn init(addr: Option<&str>) -> Result<Arc<Self>> {
+    /// Build the registry, pre-registering one `arqonhpo_param_value` gauge per
+    /// parameter in `bounds` (NaN until a best point is recorded).
+    fn init(
+        addr: Option<&str>,
+        push_url: Option<&str>,
+        bounds: &HashMap<String, Domain>,
+    ) -> Result<Arc<Self>> {
         let registry = Registry::new();
         let ask_calls =
             IntCounter::new("arqonhpo_ask_calls", "Number of ask calls").into_diagnostic()?;
@@ -177,6 +664,22 @@ impl Metrics {
             "Evaluation latency in seconds",
         ))
         .into_diagnostic()?;
+        let cost_total = Counter::new("arqonhpo_cost_total", "Cost of the results ingested")
+            .into_diagnostic()?;
+        let best_value =
+            Gauge::new("arqonhpo_best_value", "Best objective value observed").into_diagnostic()?;
+        best_value.set(f64::NAN);
+        let param_value = GaugeVec::new(
+            Opts::new(
+                "arqonhpo_param_value",
+                "Parameter values of the best point observed",
+            ),
+            &["name"],
+        )
+        .into_diagnostic()?;
+        for name in bounds.keys() {
+            param_value.with_label_values(&[name]).set(f64::NAN);
+        }
 
         registry
             .register(Box::new(ask_calls.clone()))
@@ -196,6 +699,15 @@ impl Metrics {
         registry
             .register(Box::new(eval_seconds.clone()))
             .into_diagnostic()?;
+        registry
+            .register(Box::new(cost_total.clone()))
+            .into_diagnostic()?;
+        registry
+            .register(Box::new(best_value.clone()))
+            .into_diagnostic()?;
+        registry
+            .register(Box::new(param_value.clone()))
+            .into_diagnostic()?;
 
         let metrics = Arc::new(Self {
             registry,
@@ -205,6 +717,11 @@ impl Metrics {
             results_ingested,
             history_len,
             eval_seconds,
+            eval_samples: Mutex::new(Vec::new()),
+            cost_total,
+            best_value,
+            param_value,
+            push_url: push_url.map(str::to_string),
         });
 
         if let Some(addr) = addr {
@@ -232,6 +749,68 @@ impl Metrics {
 
     fn observe_eval(&self, seconds: f64) {
         self.eval_seconds.observe(seconds);
+        self.eval_samples
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .push(seconds);
+    }
+
+    fn record_cost(&self, results: &[SeedPoint]) {
+        self.cost_total
+            .inc_by(results.iter().map(|r| r.cost).filter(|c| *c > 0.0).sum());
+    }
+
+    /// Current counter values and eval-latency summary.
+    fn snapshot(&self) -> RunMetrics {
+        let samples = self
+            .eval_samples
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        RunMetrics {
+            asks: self.ask_calls.get(),
+            tells: self.tell_calls.get(),
+            candidates: self.candidates_emitted.get(),
+            results: self.results_ingested.get(),
+            cost: self.cost_total.get(),
+            eval_seconds: LatencySummary::from_samples(&samples),
+        }
+    }
+
+    /// Point the best-value and per-parameter gauges at the lowest finite
+    /// value among `points`. Leaves them untouched if there is none.
+    fn record_best<'a, I>(&self, config: &SolverConfig, points: I)
+    where
+        I: IntoIterator<Item = (&'a HashMap<String, f64>, f64)>,
+    {
+        let best = points
+            .into_iter()
+            .filter(|(_, value)| value.is_finite())
+            .min_by(|a, b| {
+                config
+                    .minimized(a.1)
+                    .total_cmp(&config.minimized(b.1))
+                    .then_with(|| cmp_params(a.0, b.0))
+            });
+        if let Some((params, value)) = best {
+            self.best_value.set(value);
+            for (name, param) in params {
+                self.param_value.with_label_values(&[name]).set(*param);
+            }
+        }
+    }
+
+    /// Push the registry to the Pushgateway, if one was configured. Failures
+    /// are logged rather than returned so an unreachable gateway never aborts
+    /// a run.
+    fn push(&self) {
+        let Some(url) = self.push_url.as_deref() else {
+            return;
+        };
+        if let Err(err) =
+            prometheus::push_metrics(PUSH_JOB, HashMap::new(), url, self.registry.gather(), None)
+        {
+            tracing::warn!(url, error = %err, "failed to push metrics");
+        }
     }
 }
 
@@ -268,29 +847,114 @@ fn init_tracing(log_format: LogFormat, log_level: &str) -> Result<()> {
 fn main() -> Result<()> {
     let cli = Cli::parse();
     init_tracing(cli.log_format, &cli.log_level)?;
-    let metrics = Metrics::init(cli.metrics_addr.as_deref())?;
+    let bounds = command_bounds(&cli.command);
+    let metrics = Metrics::init(
+        cli.metrics_addr.as_deref(),
+        cli.metrics_push_url.as_deref(),
+        &bounds,
+    )?;
 
     match cli.command {
         Commands::Run {
             config,
-            script,
             state,
-        } => run_command(&config, &script, state.as_ref(), &metrics),
+            dry_run: true,
+            resume_strategy,
+            ..
+        } => dry_run_command(
+            &config,
+            state.as_ref(),
+            resume_strategy.map(ResumeStrategy::kind),
+            cli.log_format,
+        ),
+        Commands::Run {
+            config,
+            state,
+            dry_run: false,
+            external: true,
+            csv,
+            resume_strategy,
+            ..
+        } => external_run_command(
+            &config,
+            state.as_ref(),
+            csv.as_ref(),
+            resume_strategy.map(ResumeStrategy::kind),
+            &metrics,
+        ),
+        Commands::Run {
+            config,
+            script: Some(script),
+            state,
+            dry_run: false,
+            partial,
+            csv,
+            resume_strategy,
+            continue_on_error,
+            failure_value,
+            max_wallclock_secs,
+            ..
+        } => run_command(
+            &config,
+            &script,
+            state.as_ref(),
+            partial.as_ref(),
+            csv.as_ref(),
+            resume_strategy.map(ResumeStrategy::kind),
+            failure_value.filter(|_| continue_on_error),
+            max_wallclock_secs.map(Duration::from_secs),
+            &metrics,
+        ),
+        Commands::Run { .. } => Err(miette::miette!(
+            "--script is required unless --external is set"
+        )),
         Commands::Ask {
             config,
             state,
             batch,
-        } => ask_command(&config, state.as_ref(), batch, &metrics),
-        Commands::Tell { state, results } => tell_command(&state, results.as_ref(), &metrics),
-        Commands::Interactive { config, state } => {
-            interactive_command(&config, state.as_ref(), &metrics)
-        }
+            verbose,
+            keyed,
+        } => ask_command(&config, state.as_ref(), batch, verbose, keyed, &metrics),
+        Commands::Tell {
+            state,
+            results,
+            verbose,
+        } => tell_command(&state, results.as_ref(), verbose, &metrics),
+        Commands::Suggest { state } => suggest_command(&state, &metrics),
+        Commands::Interactive {
+            config,
+            state,
+            protocol,
+        } => interactive_command(&config, state.as_ref(), protocol, &metrics),
         Commands::Export {
             state,
             output,
             run_id,
-        } => export_command(&state, output.as_ref(), run_id, &metrics),
-        Commands::Import { artifact, state } => import_command(&artifact, &state, &metrics),
+            importance,
+            format,
+        } => {
+            let format = format
+                .or_else(|| output.as_deref().map(ExportFormat::from_path))
+                .unwrap_or_default();
+            export_command(
+                &state,
+                output.as_ref(),
+                run_id,
+                importance,
+                format,
+                &metrics,
+            )
+        }
+        Commands::Truncate {
+            state,
+            keep,
+            config,
+        } => truncate_command(&state, keep, config.as_ref(), &metrics),
+        Commands::Import {
+            artifact,
+            state,
+            warm_start,
+        } => import_command(&artifact, &state, warm_start, &metrics),
         Commands::Tui {
             state,
             events,
@@ -300,136 +964,1132 @@ fn main() -> Result<()> {
             state,
             events,
             actions,
+            runs_dir,
             addr,
-        } => dashboard_command(&state, events.as_ref(), actions.as_ref(), &addr, &metrics),
-        Commands::Validate { config } => validate_command(&config),
+            cors_origin,
+        } => {
+            let source = match (runs_dir, state) {
+                (Some(dir), _) => dashboard::RunSource::Dir(dir),
+                (None, Some(state)) => dashboard::RunSource::Single(dashboard::RunPaths {
+                    state,
+                    events,
+                    actions,
+                }),
+                (None, None) => return Err(miette::miette!("--state or --runs-dir is required")),
+            };
+            dashboard_command(&source, &addr, cors_origin.as_deref(), &metrics)
+        }
+        Commands::Validate { config, format } => validate_command(&config, format),
+        Commands::Doctor {
+            config,
+            script,
+            state,
+        } => doctor_command(&config, script.as_deref(), state.as_deref()),
+        Commands::Replay {
+            artifact,
+            tolerance,
+        } => replay_command(&artifact, tolerance),
+        Commands::Compare {
+            a,
+            b,
+            basin_radius,
+            json,
+        } => compare_command(&a, &b, basin_radius, json),
+        Commands::InferBounds {
+            data,
+            out,
+            margin,
+            value_column,
+            budget,
+        } => infer_bounds_command(&data, out.as_ref(), margin, &value_column, budget),
+        Commands::Audit { log, format } => audit_command(&log, format),
+        Commands::Bench {
+            config,
+            script,
+            seeds,
+            out,
+        } => bench_command(&config, &script, seeds, out.as_ref(), &metrics),
+        Commands::Explore {
+            state,
+            around,
+            resolution,
+            dims,
+            span,
+            script,
+            results,
+            out,
+        } => explore_command(
+            &state,
+            around,
+            resolution as usize,
+            &dims,
+            span,
+            script.as_ref(),
+            results.as_ref(),
+            out.as_ref(),
+        ),
     }
 }
 
+/// Parameter bounds of the run a command operates on, used to pre-register
+/// per-parameter gauges. Best effort: the command itself reports load errors.
+fn command_bounds(command: &Commands) -> HashMap<String, Domain> {
+    let config = match command {
+        Commands::Run { config, state, .. }
+        | Commands::Ask { config, state, .. }
+        | Commands::Interactive { config, state, .. } => {
+            load_state_or_config(config, state.as_ref())
+                .ok()
+                .map(|loaded| loaded.config)
+        }
+        Commands::Bench { config, .. }
+        | Commands::Doctor { config, .. }
+        | Commands::Truncate {
+            config: Some(config),
+            ..
+        } => load_config(config).ok(),
+        Commands::Tell { state, .. }
+        | Commands::Suggest { state }
+        | Commands::Truncate { state, .. }
+        | Commands::Export { state, .. }
+        | Commands::Tui { state, .. }
+        | Commands::Explore { state, .. } => load_state(state).ok().map(|state| state.config),
+        Commands::Dashboard { state, .. } => state
+            .as_deref()
+            .and_then(|state| load_state(state).ok())
+            .map(|state| state.config),
+        Commands::Import { artifact, .. } => fs::read_to_string(artifact)
+            .ok()
+            .and_then(|json| serde_json::from_str::<RunArtifact>(&json).ok())
+            .map(|artifact| artifact.config),
+        Commands::Validate { .. }
+        | Commands::Replay { .. }
+        | Commands::Compare { .. }
+        | Commands::InferBounds { .. }
+        | Commands::Audit { .. } => None,
+    };
+    config.map(|config| config.bounds).unwrap_or_default()
+}
+
+#[allow(clippy::too_many_arguments)]
 fn run_command(
     config_path: &Path,
     script: &Path,
     state_path: Option<&PathBuf>,
+    partial_path: Option<&PathBuf>,
+    csv_path: Option<&PathBuf>,
+    resume_strategy: Option<StrategyKind>,
+    failure_value: Option<f64>,
+    max_wallclock: Option<Duration>,
     metrics: &Metrics,
 ) -> Result<()> {
+    let started = Instant::now();
     tracing::info!(command = "run", config = %config_path.display());
-    let loaded = load_state_or_config(config_path, state_path)?;
-    let run_id = loaded.run_id.unwrap_or_else(|| generate_run_id("run"));
-    let mut solver = Solver::pcr(loaded.config.clone());
-    if !loaded.history.is_empty() {
-        solver.seed(loaded.history.clone());
+    if failure_value.is_some_and(|value| !value.is_finite()) {
+        return Err(miette::miette!("--failure-value must be a finite number"));
     }
+    let loaded = load_state_or_config(config_path, state_path)?;
+    check_script_env(&loaded.config)?;
+    let run_id = loaded
+        .run_id
+        .clone()
+        .unwrap_or_else(|| generate_run_id("run"));
+    let mut solver = Solver::pcr(solver_config(&loaded.config, resume_strategy));
+    seed_solver(
+        &mut solver,
+        loaded.history.clone(),
+        loaded.warm_start,
+        loaded.compacted,
+    )?;
+    let mut csv = csv_path
+        .map(|path| CsvLog::open(path, &solver.config, solver.history.len()))
+        .transpose()?;
+    let interrupt = install_interrupt_handler()?;
+
+    let outcome = solve_once(
+        &mut solver,
+        script,
+        partial_path,
+        csv.as_mut(),
+        failure_value,
+        metrics,
+        Some(&interrupt),
+        max_wallclock.map(|limit| started + limit),
+        |solver| {
+            // The batch is only safe to forget once it is in the state file.
+            match (partial_path, state_path) {
+                (Some(_), Some(state)) => save_state(
+                    state,
+                    &run_state(&loaded.config, solver, &run_id, loaded.warm_start, metrics),
+                ),
+                _ => Ok(()),
+            }
+        },
+    )?;
 
-    while let Some(candidates) = solver.ask() {
-        metrics.record_ask(candidates.len());
-        let mut results = Vec::with_capacity(candidates.len());
-        for params in candidates {
-            let start = SystemTime::now();
-            let value = evaluate_script(script, &params)?;
-            let elapsed = start.elapsed().unwrap_or_default();
-            metrics.observe_eval(elapsed.as_secs_f64());
-            results.push(SeedPoint {
-                params,
-                value,
-                cost: 1.0,
-            });
+    let stopped = match outcome {
+        SolveOutcome::Finished => None,
+        SolveOutcome::Interrupted { pending } => Some(("interrupted", pending)),
+        SolveOutcome::TimedOut { pending } => Some(("timed_out", pending)),
+    };
+    if let Some((reason, pending)) = stopped {
+        let mut state = run_state(&loaded.config, &solver, &run_id, loaded.warm_start, metrics);
+        // With a partial log the pending results are already on disk and are
+        // picked up from there on resume; adding them here would count them twice.
+        if partial_path.is_none() {
+            state.history.extend(pending);
         }
-        metrics.record_tell(results.len());
-        solver.seed(results);
+        if let Some(path) = state_path {
+            save_state(path, &state)?;
+        }
+        tracing::warn!(
+            evaluations = state.history.len(),
+            reason,
+            "stopped early; state checkpointed"
+        );
+        let best = state.best();
+        let mut summary = serde_json::json!({
+            "evaluations": state.history.len(),
+            "best": best,
+        });
+        summary[reason] = serde_json::Value::Bool(true);
+        let output = serde_json::to_string_pretty(&summary).into_diagnostic()?;
+        println!("{}", output);
+        if reason == "interrupted" {
+            std::process::exit(INTERRUPTED_EXIT_CODE);
+        }
+        // Hitting the wall-clock limit is a normal end of the run.
+        return Ok(());
     }
 
     if let Some(path) = state_path {
-        let state = SolverState {
-            config: solver.config.clone(),
-            history: solver
-                .history
-                .iter()
-                .map(|trace| SeedPoint {
-                    params: trace.params.clone(),
-                    value: trace.value,
-                    cost: trace.cost,
-                })
-                .collect(),
-            run_id: Some(run_id),
-        };
-        save_state(path, &state)?;
+        save_state(
+            path,
+            &run_state(&loaded.config, &solver, &run_id, loaded.warm_start, metrics),
+        )?;
     }
 
     metrics.set_history_len(solver.history.len());
 
-    let output = serde_json::to_string_pretty(&solver.history).into_diagnostic()?;
+    let output = serde_json::to_string_pretty(&solver.original_history()).into_diagnostic()?;
     println!("{}", output);
     Ok(())
 }
 
-fn ask_command(
+/// `run --external`: hand batches to an external evaluator over stdout and
+/// read its results from stdin until the solver is done.
+fn external_run_command(
     config_path: &Path,
     state_path: Option<&PathBuf>,
-    batch: Option<usize>,
+    csv_path: Option<&PathBuf>,
+    resume_strategy: Option<StrategyKind>,
     metrics: &Metrics,
 ) -> Result<()> {
-    tracing::info!(command = "ask", config = %config_path.display());
+    tracing::info!(command = "run", external = true, config = %config_path.display());
     let loaded = load_state_or_config(config_path, state_path)?;
-    let mut solver = Solver::pcr(loaded.config);
-    if !loaded.history.is_empty() {
-        solver.seed(loaded.history);
-    }
-
-    let mut response = solver.ask();
-    if let (Some(limit), Some(ref mut candidates)) = (batch, response.as_mut()) {
-        if candidates.len() > limit {
-            candidates.truncate(limit);
-        }
-    }
-
-    if let Some(ref candidates) = response {
-        metrics.record_ask(candidates.len());
-    }
+    let run_id = loaded
+        .run_id
+        .clone()
+        .unwrap_or_else(|| generate_run_id("run"));
+    let mut solver = Solver::pcr(solver_config(&loaded.config, resume_strategy));
+    seed_solver(
+        &mut solver,
+        loaded.history.clone(),
+        loaded.warm_start,
+        loaded.compacted,
+    )?;
+    let mut csv = csv_path
+        .map(|path| CsvLog::open(path, &solver.config, solver.history.len()))
+        .transpose()?;
+
+    let stdout = io::stdout();
+    let mut output = stdout.lock();
+    solve_external(
+        &mut solver,
+        &mut io::stdin().lock(),
+        &mut output,
+        csv.as_mut(),
+        metrics,
+        |solver| match state_path {
+            // Checkpoint every batch; external runs tend to be long.
+            Some(path) => save_state(
+                path,
+                &run_state(&loaded.config, solver, &run_id, loaded.warm_start, metrics),
+            ),
+            None => Ok(()),
+        },
+    )?;
 
-    let output = serde_json::to_string_pretty(&response).into_diagnostic()?;
-    println!("{}", output);
+    metrics.set_history_len(solver.history.len());
+    let summary = serde_json::json!({
+        "done": true,
+        "evaluations": solver.evaluations(),
+        "best": solver.original_best(),
+    });
+    writeln!(output, "{}", summary).into_diagnostic()?;
     Ok(())
 }
 
-fn tell_command(
-    state_path: &Path,
-    results_path: Option<&PathBuf>,
-    metrics: &Metrics,
-) -> Result<()> {
-    tracing::info!(command = "tell", state = %state_path.display());
-    let mut state = load_state(state_path)?;
-    let results_json = read_input(results_path)?;
-    let mut results: Vec<SeedPoint> = serde_json::from_str(&results_json).into_diagnostic()?;
-    metrics.record_tell(results.len());
-    state.history.append(&mut results);
-    metrics.set_history_len(state.history.len());
-    save_state(state_path, &state)?;
-    Ok(())
+/// One result line in `run --external`: either the bare value of the
+/// candidate at the same position in the batch, or a full result.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ExternalResult {
+    Value(f64),
+    Point(SeedPoint),
 }
 
-fn interactive_command(
-    config_path: &Path,
-    state_path: Option<&PathBuf>,
+/// Write each batch to `output` as a single JSON array line, then read one
+/// result line per candidate from `input`. Blank lines are skipped.
+fn solve_external<R: BufRead, W: Write>(
+    solver: &mut Solver,
+    input: &mut R,
+    output: &mut W,
+    mut csv: Option<&mut CsvLog>,
     metrics: &Metrics,
+    mut after_batch: impl FnMut(&Solver) -> Result<()>,
 ) -> Result<()> {
-    tracing::info!(command = "interactive", config = %config_path.display());
-    let loaded = load_state_or_config(config_path, state_path)?;
-    let run_id = loaded
+    while let Some(candidates) = solver.ask() {
+        metrics.record_ask(candidates.len());
+        let line = serde_json::to_string(&candidates).into_diagnostic()?;
+        writeln!(output, "{}", line).into_diagnostic()?;
+        output.flush().into_diagnostic()?;
+
+        let expected = candidates.len();
+        let mut results = Vec::with_capacity(expected);
+        for params in candidates {
+            let mut line = String::new();
+            while line.trim().is_empty() {
+                line.clear();
+                if input.read_line(&mut line).into_diagnostic()? == 0 {
+                    return Err(miette::miette!(
+                        "stdin closed after {} of {} results",
+                        results.len(),
+                        expected
+                    ));
+                }
+            }
+            let result: ExternalResult = serde_json::from_str(line.trim())
+                .into_diagnostic()
+                .with_context(|| format!("Invalid result line: {}", line.trim()))?;
+            let point = match result {
+                ExternalResult::Value(value) => SeedPoint {
+                    cost: params.get(FIDELITY_KEY).copied().unwrap_or(1.0),
+                    params,
+                    value,
+                    penalized: false,
+                    failed: false,
+                    feasibility: None,
+                },
+                ExternalResult::Point(point) => point,
+            };
+            if let Some(log) = csv.as_deref_mut() {
+                log.record(&point, solver.phase().name())?;
+            }
+            results.push(point);
+        }
+        metrics.record_tell(results.len());
+        metrics.record_cost(&results);
+        solver.seed(results).into_diagnostic()?;
+        metrics.set_history_len(solver.history.len());
+        metrics.record_best(
+            &solver.config,
+            solver.original_best().iter().map(|t| (&t.params, t.value)),
+        );
+        metrics.push();
+        after_batch(solver)?;
+    }
+    Ok(())
+}
+
+/// Exit code of a `run` stopped by Ctrl-C (128 + SIGINT, as shells report it).
+const INTERRUPTED_EXIT_CODE: i32 = 130;
+
+/// Install a SIGINT handler that sets the returned flag.
+///
+/// The first Ctrl-C only requests a shutdown, which `solve_once` honours
+/// between evaluations. A second one while the flag is set exits immediately.
+fn install_interrupt_handler() -> Result<Arc<AtomicBool>> {
+    use signal_hook::consts::SIGINT;
+
+    let flag = Arc::new(AtomicBool::new(false));
+    // Registered first so it sees the flag before the handler below sets it.
+    signal_hook::flag::register_conditional_shutdown(
+        SIGINT,
+        INTERRUPTED_EXIT_CODE,
+        Arc::clone(&flag),
+    )
+    .into_diagnostic()?;
+    signal_hook::flag::register(SIGINT, Arc::clone(&flag)).into_diagnostic()?;
+    Ok(flag)
+}
+
+/// How `solve_once` ended.
+#[derive(Debug)]
+enum SolveOutcome {
+    /// The solver has no more candidates.
+    Finished,
+    /// A shutdown was requested. `pending` holds the results of the current
+    /// batch that were not yet told to the solver.
+    Interrupted { pending: Vec<SeedPoint> },
+    /// The wall-clock deadline passed; `pending` as for `Interrupted`.
+    TimedOut { pending: Vec<SeedPoint> },
+}
+
+/// Evaluate the solver's candidates with `script` until it is done.
+///
+/// With `partial_path`, each result is appended to that log as soon as the
+/// script returns, and results already logged are reused instead of
+/// re-running the script. `after_batch` runs once each batch has been told to
+/// the solver; the partial log is rotated only after it succeeds.
+///
+/// With `csv`, each result the script returns is also appended there as a
+/// row; results reused from the partial log were already written by the run
+/// that produced them.
+///
+/// With `failure_value`, a script that fails is logged and its evaluation
+/// recorded with that value and flagged `failed` instead of ending the run.
+///
+/// `interrupt` is checked before each evaluation; once set, the loop stops
+/// and returns the results gathered so far in the current batch. So is
+/// `deadline`: an evaluation that starts before it runs to completion.
+#[allow(clippy::too_many_arguments)]
+fn solve_once(
+    solver: &mut Solver,
+    script: &Path,
+    partial_path: Option<&PathBuf>,
+    mut csv: Option<&mut CsvLog>,
+    failure_value: Option<f64>,
+    metrics: &Metrics,
+    interrupt: Option<&AtomicBool>,
+    deadline: Option<Instant>,
+    mut after_batch: impl FnMut(&Solver) -> Result<()>,
+) -> Result<SolveOutcome> {
+    let interrupted = || interrupt.is_some_and(|flag| flag.load(Ordering::SeqCst));
+    let mut recorded = match partial_path {
+        Some(path) => load_partial(path)?,
+        None => HashMap::new(),
+    };
+
+    while let Some(candidates) = solver.ask() {
+        metrics.record_ask(candidates.len());
+        let mut results = Vec::with_capacity(candidates.len());
+        for params in candidates {
+            if interrupted() {
+                return Ok(SolveOutcome::Interrupted { pending: results });
+            }
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                return Ok(SolveOutcome::TimedOut { pending: results });
+            }
+            // The solver merges consecutive results for the same point.
+            let hash = param_hash(&params);
+            for _ in 0..solver.config.repeats.max(1) {
+                let resumed = recorded.get_mut(&hash).and_then(|points| points.pop());
+                if let Some(point) = resumed {
+                    tracing::info!(value = point.value, "reusing result from partial log");
+                    results.push(SeedPoint {
+                        params: params.clone(),
+                        ..point
+                    });
+                    continue;
+                }
+                let start = SystemTime::now();
+                let (raw, feasibility, failed) = match script_output(script, &params)
+                    .and_then(|stdout| Ok((parse_result(&stdout)?, parse_feasibility(&stdout)?)))
+                {
+                    Ok((raw, feasibility)) => (raw, feasibility, false),
+                    // The script may have been killed by the same Ctrl-C.
+                    Err(_) if interrupted() => {
+                        return Ok(SolveOutcome::Interrupted { pending: results })
+                    }
+                    Err(err) => match failure_value {
+                        Some(value) => {
+                            tracing::warn!(error = %err, value, "evaluation failed; recording failure value");
+                            (value, None, true)
+                        }
+                        None => return Err(err),
+                    },
+                };
+                let elapsed = start.elapsed().unwrap_or_default();
+                metrics.observe_eval(elapsed.as_secs_f64());
+                // Resolve before logging: the partial log is JSON, which has no
+                // NaN. The flag tells the solver the value is a penalty.
+                let (value, penalized) = solver.config.non_finite.resolve(raw).into_diagnostic()?;
+                if penalized {
+                    tracing::warn!(raw, value, "script result is not finite; recording penalty");
+                }
+                let point = SeedPoint {
+                    params: params.clone(),
+                    value,
+                    cost: params.get(FIDELITY_KEY).copied().unwrap_or(1.0),
+                    penalized,
+                    failed,
+                    feasibility,
+                };
+                if let Some(path) = partial_path {
+                    append_partial(path, &point)?;
+                }
+                if let Some(log) = csv.as_deref_mut() {
+                    log.record(&point, solver.phase().name())?;
+                }
+                results.push(point);
+            }
+        }
+        metrics.record_tell(results.len());
+        metrics.record_cost(&results);
+        solver.seed(results).into_diagnostic()?;
+        metrics.set_history_len(solver.history.len());
+        metrics.record_best(
+            &solver.config,
+            solver.original_best().iter().map(|t| (&t.params, t.value)),
+        );
+        metrics.push();
+
+        after_batch(solver)?;
+        if let Some(partial) = partial_path {
+            rotate_partial(partial)?;
+            recorded.clear();
+        }
+    }
+    Ok(SolveOutcome::Finished)
+}
+
+/// State file contents for a `run` in progress. `config` is the loaded
+/// config, which the solver's may extend with a `--resume-strategy`.
+fn run_state(
+    config: &SolverConfig,
+    solver: &Solver,
+    run_id: &str,
+    warm_start: bool,
+    metrics: &Metrics,
+) -> SolverState {
+    SolverState {
+        config: config.clone(),
+        history: solver
+            .original_history()
+            .iter()
+            .map(EvalTrace::to_seed_point)
+            .collect(),
+        run_id: Some(run_id.to_string()),
+        warm_start,
+        compacted: solver.compacted(),
+        suggest: None,
+        metrics: Some(metrics.snapshot()),
+    }
+}
+
+/// Significant digits kept when canonicalizing parameter values for hashing.
+const PARAM_HASH_DIGITS: usize = 12;
+
+/// Stable hash of a parameter set, independent of key order.
+///
+/// Values are rounded to `PARAM_HASH_DIGITS` significant digits first, so a
+/// candidate still matches its logged result after a JSON round trip or
+/// last-bit float noise. FNV-1a keeps the hash identical across builds.
+fn param_hash(params: &HashMap<String, f64>) -> u64 {
+    let mut keys: Vec<_> = params.keys().collect();
+    keys.sort();
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for key in keys {
+        let value = params[key];
+        let value = if value == 0.0 { 0.0 } else { value }; // fold -0.0
+        let canonical = format!("{}={:.*e};", key, PARAM_HASH_DIGITS - 1, value);
+        for byte in canonical.bytes() {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+    hash
+}
+
+/// Results logged by a previous, interrupted `run`, keyed by `param_hash`.
+///
+/// A missing file means nothing to resume. Unparseable lines (e.g. a line cut
+/// short by the crash) are skipped with a warning.
+fn load_partial(path: &Path) -> Result<HashMap<u64, Vec<SeedPoint>>> {
+    let mut recorded: HashMap<u64, Vec<SeedPoint>> = HashMap::new();
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(recorded),
+        Err(err) => {
+            return Err(err)
+                .into_diagnostic()
+                .with_context(|| format!("Failed to read partial log {}", path.display()))
+        }
+    };
+    for (idx, line) in contents.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<SeedPoint>(line) {
+            Ok(point) => recorded
+                .entry(param_hash(&point.params))
+                .or_default()
+                .push(point),
+            Err(err) => tracing::warn!(line = idx + 1, error = %err, "skipping partial log entry"),
+        }
+    }
+    let resumable: usize = recorded.values().map(Vec::len).sum();
+    if resumable > 0 {
+        tracing::info!(resumable, path = %path.display(), "resuming from partial log");
+    }
+    Ok(recorded)
+}
+
+/// Append one result to the partial log and flush it to disk.
+fn append_partial(path: &Path, point: &SeedPoint) -> Result<()> {
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .into_diagnostic()
+        .with_context(|| format!("Failed to open partial log {}", path.display()))?;
+    let line = serde_json::to_string(point).into_diagnostic()?;
+    writeln!(file, "{}", line).into_diagnostic()?;
+    file.sync_data().into_diagnostic()?;
+    Ok(())
+}
+
+/// Move a completed batch's log aside to `<path>.prev`.
+fn rotate_partial(path: &Path) -> Result<()> {
+    if !path.exists() {
+        return Ok(());
+    }
+    let mut rotated = path.as_os_str().to_owned();
+    rotated.push(".prev");
+    fs::rename(path, &rotated)
+        .into_diagnostic()
+        .with_context(|| format!("Failed to rotate partial log {}", path.display()))
+}
+
+/// Streaming CSV log of a `run`: one row per evaluation, flushed as soon as
+/// it is written so the file can be followed while the run is in progress.
+///
+/// Columns are `eval`, `value`, `cost`, one per parameter in sorted
+/// `config.bounds` order, `phase` and `timestamp_us`. Parameters a candidate
+/// does not carry (inactive conditionals) are left empty, and entries that are
+/// not bounded parameters (fixed values, fidelity) are not written, so the
+/// header never changes mid-run.
+struct CsvLog {
+    writer: csv::Writer<fs::File>,
+    params: Vec<String>,
+    next_eval: usize,
+}
+
+impl CsvLog {
+    /// Open `path` for appending, writing the header if the file is new or
+    /// empty. An existing file must have the same header. Rows are numbered
+    /// from `next_eval`.
+    fn open(path: &Path, config: &SolverConfig, next_eval: usize) -> Result<Self> {
+        let mut params: Vec<String> = config.bounds.keys().cloned().collect();
+        params.sort();
+        let mut header = vec!["eval".to_string(), "value".into(), "cost".into()];
+        header.extend(params.iter().cloned());
+        header.extend(["phase".into(), "timestamp_us".into()]);
+
+        let existing = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(err) => {
+                return Err(err)
+                    .into_diagnostic()
+                    .with_context(|| format!("Failed to read CSV log {}", path.display()))
+            }
+        };
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .into_diagnostic()
+            .with_context(|| format!("Failed to open CSV log {}", path.display()))?;
+        let mut writer = csv::Writer::from_writer(file);
+        match existing.lines().next() {
+            None => {
+                writer.write_record(&header).into_diagnostic()?;
+                writer.flush().into_diagnostic()?;
+            }
+            Some(line) if line == header.join(",") => {}
+            Some(line) => {
+                return Err(miette::miette!(
+                    "CSV log {} has header '{}', expected '{}'",
+                    path.display(),
+                    line,
+                    header.join(",")
+                ))
+            }
+        }
+        Ok(Self {
+            writer,
+            params,
+            next_eval,
+        })
+    }
+
+    /// Append and flush the row of one evaluation.
+    fn record(&mut self, point: &SeedPoint, phase: &str) -> Result<()> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_micros();
+        let mut row = vec![
+            self.next_eval.to_string(),
+            point.value.to_string(),
+            point.cost.to_string(),
+        ];
+        row.extend(self.params.iter().map(|name| {
+            point
+                .params
+                .get(name)
+                .map(|v| v.to_string())
+                .unwrap_or_default()
+        }));
+        row.extend([phase.to_string(), timestamp.to_string()]);
+        self.writer.write_record(&row).into_diagnostic()?;
+        self.writer.flush().into_diagnostic()?;
+        self.next_eval += 1;
+        Ok(())
+    }
+}
+
+/// Set `strategy_params["force"]` so refinement skips the classifier's choice.
+fn force_strategy(config: &mut SolverConfig, strategy: StrategyKind) {
+    config
+        .strategy_params
+        .get_or_insert_with(HashMap::new)
+        .insert(StrategyKind::PARAM_KEY.to_string(), strategy.code());
+}
+
+/// The solver's copy of `config`, forced to `resume_strategy` if given. The
+/// override only lives for this invocation; state files keep `config`.
+fn solver_config(config: &SolverConfig, resume_strategy: Option<StrategyKind>) -> SolverConfig {
+    let mut config = config.clone();
+    if let Some(strategy) = resume_strategy {
+        force_strategy(&mut config, strategy);
+    }
+    config
+}
+
+fn dry_run_command(
+    config_path: &Path,
+    state_path: Option<&PathBuf>,
+    resume_strategy: Option<StrategyKind>,
+    format: LogFormat,
+) -> Result<()> {
+    tracing::info!(command = "run", dry_run = true, config = %config_path.display());
+    let loaded = load_state_or_config(config_path, state_path)?;
+    let mut solver = Solver::pcr(solver_config(&loaded.config, resume_strategy));
+    seed_solver(
+        &mut solver,
+        loaded.history,
+        loaded.warm_start,
+        loaded.compacted,
+    )?;
+    let plan = solver.plan();
+    match format {
+        LogFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&plan).into_diagnostic()?)
+        }
+        LogFormat::Pretty => print!("{}", format_plan(&plan)),
+    }
+    Ok(())
+}
+
+fn format_plan(plan: &RunPlan) -> String {
+    let budget = match (plan.budget, plan.cost_budget) {
+        (0, Some(cap)) => format!("cost {}", cap),
+        (count, Some(cap)) => format!("{} evaluations, cost {}", count, cap),
+        (count, None) => format!("{} evaluations", count),
+    };
+    let refine = plan
+        .refine_budget
+        .map_or_else(|| "until cost budget".to_string(), |n| n.to_string());
+    let classification = match (plan.classification, plan.classification_score) {
+        (Some(mode), Some(score)) => format!("{:?} (score {:.4})", mode, score),
+        (Some(mode), None) => format!("{:?}", mode),
+        (None, _) => "pending (needs probe results)".to_string(),
+    };
+    let strategy = plan.strategy.as_deref().unwrap_or("chosen after probing");
+    format!(
+        "Budget:         {}\n\
+         Probe points:   {} ({})\n\
+         Refine budget:  {}\n\
+         Evaluated:      {}\n\
+         Classification: {}\n\
+         Strategy:       {}\n",
+        budget, plan.probe_points, plan.probe, refine, plan.evaluated, classification, strategy
+    )
+}
+
+fn ask_command(
+    config_path: &Path,
+    state_path: Option<&PathBuf>,
+    batch: Option<usize>,
+    verbose: bool,
+    keyed: bool,
+    metrics: &Metrics,
+) -> Result<()> {
+    tracing::info!(command = "ask", config = %config_path.display());
+    let output = ask_output(config_path, state_path, batch, verbose, keyed, metrics)?;
+    println!("{}", output);
+    Ok(())
+}
+
+/// What `ask` prints: the next batch, bare, keyed or in a status object.
+fn ask_output(
+    config_path: &Path,
+    state_path: Option<&PathBuf>,
+    batch: Option<usize>,
+    verbose: bool,
+    keyed: bool,
+    metrics: &Metrics,
+) -> Result<String> {
+    let loaded = load_state_or_config(config_path, state_path)?;
+    let fingerprint = if keyed {
+        state_fingerprint(
+            &loaded.config,
+            &loaded.history,
+            loaded.warm_start,
+            loaded.compacted,
+        )?
+    } else {
+        Vec::new()
+    };
+    let mut solver = Solver::pcr(loaded.config);
+    seed_solver(
+        &mut solver,
+        loaded.history,
+        loaded.warm_start,
+        loaded.compacted,
+    )?;
+
+    let mut response = solver.ask();
+    if let (Some(limit), Some(ref mut candidates)) = (batch, response.as_mut()) {
+        if candidates.len() > limit {
+            candidates.truncate(limit);
+        }
+    }
+
+    if let Some(ref candidates) = response {
+        metrics.record_ask(candidates.len());
+    }
+
+    if keyed {
+        let candidates = response.unwrap_or_default();
+        let batch_id = batch_id(&fingerprint, candidates.len());
+        serde_json::to_string_pretty(&KeyedBatch {
+            candidates: candidates.into_iter().enumerate().collect(),
+            batch_id,
+        })
+    } else if verbose {
+        let status = StatusResponse::new(&solver, Some(response.unwrap_or_default()));
+        serde_json::to_string_pretty(&status)
+    } else {
+        serde_json::to_string_pretty(&response)
+    }
+    .into_diagnostic()
+}
+
+fn tell_command(
+    state_path: &Path,
+    results_path: Option<&PathBuf>,
+    verbose: bool,
+    metrics: &Metrics,
+) -> Result<()> {
+    tracing::info!(command = "tell", state = %state_path.display());
+    let mut state = load_state(state_path)?;
+    let mut results = match read_results(results_path)? {
+        TellInput::Points(points) => points,
+        TellInput::Keyed(keyed) => resolve_keyed_results(&state, keyed)?,
+    };
+    for result in &mut results {
+        // The state is JSON, which has no NaN: store the penalty and the flag.
+        let (value, penalized) = state
+            .config
+            .non_finite
+            .resolve(result.value)
+            .into_diagnostic()?;
+        result.value = value;
+        result.penalized |= penalized;
+        // Refuse values the next `ask` could not replay, rather than saving a
+        // state that no longer loads.
+        if let Some(transform) = state.config.objective_transform {
+            transform.apply(result.value).into_diagnostic()?;
+        }
+    }
+    metrics.record_tell(results.len());
+    metrics.record_cost(&results);
+    state.history.append(&mut results);
+    metrics.set_history_len(state.history.len());
+    metrics.record_best(
+        &state.config,
+        state.history.iter().map(|p| (&p.params, p.value)),
+    );
+    save_state(state_path, &state)?;
+    metrics.push();
+
+    if verbose {
+        // The phase only advances inside `ask`, so replay one to report where
+        // the next `ask` will pick up. The batch itself is discarded.
+        let mut solver = Solver::pcr(state.config);
+        seed_solver(
+            &mut solver,
+            state.history,
+            state.warm_start,
+            state.compacted,
+        )?;
+        let _ = solver.ask();
+        let status = StatusResponse::new(&solver, None);
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&status).into_diagnostic()?
+        );
+    }
+    Ok(())
+}
+
+/// Canonical encoding of what determines the next batch: the config and
+/// history (with the count compacted out of it) as JSON with every object's keys sorted, so the bytes do not
+/// depend on `HashMap` iteration order and match across processes.
+fn state_fingerprint(
+    config: &SolverConfig,
+    history: &[SeedPoint],
+    warm_start: bool,
+    compacted: CompactedHistory,
+) -> Result<Vec<u8>> {
+    fn canonical(value: serde_json::Value) -> serde_json::Value {
+        match value {
+            serde_json::Value::Object(map) => {
+                let mut entries: Vec<_> = map.into_iter().collect();
+                entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+                serde_json::Value::Object(
+                    entries
+                        .into_iter()
+                        .map(|(key, value)| (key, canonical(value)))
+                        .collect(),
+                )
+            }
+            serde_json::Value::Array(items) => {
+                serde_json::Value::Array(items.into_iter().map(canonical).collect())
+            }
+            value => value,
+        }
+    }
+    let value = serde_json::to_value((config, history, warm_start, compacted)).into_diagnostic()?;
+    serde_json::to_vec(&canonical(value)).into_diagnostic()
+}
+
+/// Id of a batch of `size` candidates handed out for the state with this
+/// [`state_fingerprint`], formatted like a UUID.
+///
+/// Two FNV-1a passes with different offset bases give 128 bits that are
+/// identical across builds and platforms.
+fn batch_id(fingerprint: &[u8], size: usize) -> String {
+    let size = (size as u64).to_le_bytes();
+    let fnv = |basis: u64| {
+        fingerprint.iter().chain(&size).fold(basis, |hash, &byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+        })
+    };
+    let id =
+        (u128::from(fnv(0xcbf2_9ce4_8422_2325)) << 64) | u128::from(fnv(0x6c62_272e_07bb_0142));
+    let hex = format!("{:032x}", id);
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+/// Turn keyed results into full results by replaying the `ask` that handed
+/// out their batch. Fails if the state has moved on since (no batch size
+/// gives a matching id) or an index is beyond the batch `ask` handed out.
+fn resolve_keyed_results(state: &SolverState, keyed: KeyedResults) -> Result<Vec<SeedPoint>> {
+    let fingerprint = state_fingerprint(
+        &state.config,
+        &state.history,
+        state.warm_start,
+        state.compacted,
+    )?;
+    let mut solver = Solver::pcr(state.config.clone());
+    seed_solver(
+        &mut solver,
+        state.history.clone(),
+        state.warm_start,
+        state.compacted,
+    )?;
+    let mut candidates = solver.ask().unwrap_or_default();
+    // `ask --batch` may have handed out only a prefix of the batch.
+    let size = (0..=candidates.len())
+        .find(|&size| batch_id(&fingerprint, size) == keyed.batch_id)
+        .ok_or_else(|| {
+            miette::miette!(
+                "batch_id {} is not the state's current batch; results must be told for the latest `ask --keyed`, all at once",
+                keyed.batch_id
+            )
+        })?;
+    candidates.truncate(size);
+    let mut results = keyed
+        .results
+        .into_iter()
+        .map(|(key, value)| match key.parse::<usize>() {
+            Ok(index) => Ok((index, value)),
+            Err(_) => Err(miette::miette!(
+                "result key '{}' is not a candidate index",
+                key
+            )),
+        })
+        .collect::<Result<Vec<_>>>()?;
+    results.sort_by_key(|(index, _)| *index);
+    results
+        .into_iter()
+        .map(|(index, value)| {
+            let params = candidates.get(index).cloned().ok_or_else(|| {
+                miette::miette!(
+                    "batch {} has no candidate {} (it has {})",
+                    keyed.batch_id,
+                    index,
+                    candidates.len()
+                )
+            })?;
+            Ok(SeedPoint {
+                cost: params.get(FIDELITY_KEY).copied().unwrap_or(1.0),
+                params,
+                value,
+                penalized: false,
+                failed: false,
+                feasibility: None,
+            })
+        })
+        .collect()
+}
+
+fn suggest_command(state_path: &Path, metrics: &Metrics) -> Result<()> {
+    tracing::info!(command = "suggest", state = %state_path.display());
+    let mut state = load_state(state_path)?;
+    let mut solver = Solver::pcr(state.config.clone());
+    seed_solver(
+        &mut solver,
+        state.history.clone(),
+        state.warm_start,
+        state.compacted,
+    )?;
+    if let Some(cursor) = state.suggest.take() {
+        solver.suggestions = cursor.pending.into();
+        solver.suggested_until = cursor.until;
+    }
+
+    let suggestion = solver.suggest();
+    if suggestion.is_some() {
+        metrics.record_ask(1);
+    }
+
+    if solver.suggested_until > 0 {
+        state.suggest = Some(SuggestCursor {
+            pending: solver.suggestions.into(),
+            until: solver.suggested_until,
+        });
+    }
+    save_state(state_path, &state)?;
+
+    let output = serde_json::to_string_pretty(&suggestion).into_diagnostic()?;
+    println!("{}", output);
+    Ok(())
+}
+
+/// Upper bound on a single msgpack frame, guarding against corrupt length prefixes.
+const MAX_FRAME_LEN: usize = 64 * 1024 * 1024;
+
+/// Reads the next interactive command, returning `None` at end of input.
+fn read_command<R: BufRead>(
+    reader: &mut R,
+    protocol: Protocol,
+) -> Result<Option<InteractiveCommand>> {
+    match protocol {
+        Protocol::Json => {
+            let mut line = String::new();
+            loop {
+                line.clear();
+                if reader.read_line(&mut line).into_diagnostic()? == 0 {
+                    return Ok(None);
+                }
+                if !line.trim().is_empty() {
+                    break;
+                }
+            }
+            serde_json::from_str(&line).into_diagnostic().map(Some)
+        }
+        Protocol::Msgpack => {
+            let mut header = [0u8; 4];
+            match reader.read_exact(&mut header) {
+                Ok(()) => {}
+                Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+                Err(err) => return Err(err).into_diagnostic(),
+            }
+            let len = u32::from_be_bytes(header) as usize;
+            if len > MAX_FRAME_LEN {
+                return Err(miette::miette!(
+                    "msgpack frame of {} bytes exceeds limit of {} bytes",
+                    len,
+                    MAX_FRAME_LEN
+                ));
+            }
+            let mut payload = vec![0u8; len];
+            reader
+                .read_exact(&mut payload)
+                .into_diagnostic()
+                .with_context(|| "Truncated msgpack frame".to_string())?;
+            rmp_serde::from_slice(&payload).into_diagnostic().map(Some)
+        }
+    }
+}
+
+/// Writes one interactive response in the selected wire format.
+fn write_response<W: Write, T: Serialize>(
+    writer: &mut W,
+    protocol: Protocol,
+    response: &T,
+) -> Result<()> {
+    match protocol {
+        Protocol::Json => {
+            let line = serde_json::to_string(response).into_diagnostic()?;
+            writeln!(writer, "{}", line).into_diagnostic()
+        }
+        Protocol::Msgpack => {
+            let payload = rmp_serde::to_vec_named(response).into_diagnostic()?;
+            let len = u32::try_from(payload.len())
+                .map_err(|_| miette::miette!("msgpack response too large"))?;
+            writer.write_all(&len.to_be_bytes()).into_diagnostic()?;
+            writer.write_all(&payload).into_diagnostic()
+        }
+    }
+}
+
+fn interactive_command(
+    config_path: &Path,
+    state_path: Option<&PathBuf>,
+    protocol: Protocol,
+    metrics: &Metrics,
+) -> Result<()> {
+    tracing::info!(command = "interactive", config = %config_path.display());
+    let loaded = load_state_or_config(config_path, state_path)?;
+    let run_id = loaded
         .run_id
         .unwrap_or_else(|| generate_run_id("interactive"));
     let mut solver = Solver::pcr(loaded.config.clone());
-    if !loaded.history.is_empty() {
-        solver.seed(loaded.history);
-    }
+    seed_solver(
+        &mut solver,
+        loaded.history,
+        loaded.warm_start,
+        loaded.compacted,
+    )?;
 
     let stdin = io::stdin();
+    let mut input = stdin.lock();
     let mut stdout = io::stdout();
-    for line in stdin.lock().lines() {
-        let line = line.into_diagnostic()?;
-        if line.trim().is_empty() {
-            continue;
-        }
-        let command: InteractiveCommand = serde_json::from_str(&line).into_diagnostic()?;
+    while let Some(command) = read_command(&mut input, protocol)? {
         match command {
             InteractiveCommand::Ask { batch } => {
                 let mut response = solver.ask();
@@ -442,36 +2102,33 @@ fn interactive_command(
                     metrics.record_ask(candidates.len());
                 }
                 let payload = InteractiveAskResponse { params: response };
-                writeln!(
-                    stdout,
-                    "{}",
-                    serde_json::to_string(&payload).into_diagnostic()?
-                )
-                .into_diagnostic()?;
+                write_response(&mut stdout, protocol, &payload)?;
             }
             InteractiveCommand::Tell { results } => {
                 metrics.record_tell(results.len());
-                solver.seed(results);
+                metrics.record_cost(&results);
+                solver.seed(results).into_diagnostic()?;
+                metrics.set_history_len(solver.history.len());
+                metrics.record_best(
+                    &solver.config,
+                    solver.original_best().iter().map(|t| (&t.params, t.value)),
+                );
+                metrics.push();
                 let payload = InteractiveTellResponse { ok: true };
-                writeln!(
-                    stdout,
-                    "{}",
-                    serde_json::to_string(&payload).into_diagnostic()?
-                )
-                .into_diagnostic()?;
+                write_response(&mut stdout, protocol, &payload)?;
                 if let Some(path) = state_path {
                     let state = SolverState {
                         config: loaded.config.clone(),
                         history: solver
-                            .history
+                            .original_history()
                             .iter()
-                            .map(|trace| SeedPoint {
-                                params: trace.params.clone(),
-                                value: trace.value,
-                                cost: trace.cost,
-                            })
+                            .map(EvalTrace::to_seed_point)
                             .collect(),
                         run_id: Some(run_id.clone()),
+                        warm_start: loaded.warm_start,
+                        compacted: solver.compacted(),
+                        suggest: None,
+                        metrics: None,
                     };
                     save_state(path, &state)?;
                 }
@@ -486,6 +2143,8 @@ fn export_command(
     state_path: &Path,
     output_path: Option<&PathBuf>,
     run_id: Option<String>,
+    importance: bool,
+    format: ExportFormat,
     metrics: &Metrics,
 ) -> Result<()> {
     tracing::info!(command = "export", state = %state_path.display());
@@ -493,55 +2152,201 @@ fn export_command(
     let run_id = run_id
         .or(state.run_id.clone())
         .unwrap_or_else(|| generate_run_id("export"));
+    // The state keeps no phase per evaluation; the first `probe_points`
+    // were the probe.
+    let probe_points = Solver::pcr(state.config.clone()).plan().probe_points;
     let history: Vec<EvalTrace> = state
         .history
         .iter()
         .enumerate()
         .map(|(index, seed)| EvalTrace {
-            eval_id: (index + 1) as u64,
-            params: seed.params.clone(),
-            value: seed.value,
-            cost: seed.cost,
+            kind: Some(if index < probe_points {
+                TraceKind::Probe
+            } else {
+                TraceKind::Refine
+            }),
+            ..EvalTrace::from_seed_point((index + 1) as u64, seed)
         })
         .collect();
-    let artifact = RunArtifact {
+    let importance = importance.then(|| parameter_importance(&history, &state.config.bounds));
+    let mut artifact = RunArtifact {
+        format_version: ARTIFACT_FORMAT_VERSION,
+        checksum: None,
         run_id,
         seed: state.config.seed,
         budget: state.config.budget,
         config: state.config,
         history,
+        importance,
     };
+    artifact.seal();
     metrics.set_history_len(artifact.history.len());
-    write_output(output_path, &artifact)?;
+    match format.data_format() {
+        Some(format) => write_formatted(output_path, &artifact, format)?,
+        None => {
+            let table = history_csv(&artifact.history)?;
+            match output_path {
+                Some(path) => fs::write(path, table)
+                    .into_diagnostic()
+                    .with_context(|| format!("Failed to write output file {}", path.display()))?,
+                None => print!("{}", table),
+            }
+        }
+    }
     Ok(())
 }
 
-fn import_command(artifact_path: &Path, state_path: &Path, metrics: &Metrics) -> Result<()> {
-    tracing::info!(
-        command = "import",
-        artifact = %artifact_path.display(),
-        state = %state_path.display()
-    );
-    let artifact: RunArtifact = read_json(artifact_path)?;
-    let history: Vec<SeedPoint> = artifact
-        .history
+/// Columns `history_csv` writes before the parameter columns.
+const CSV_COLUMNS: [&str; 7] = [
+    "eval_id",
+    "value",
+    "cost",
+    "phase",
+    "fidelity",
+    "failed",
+    "feasibility",
+];
+
+/// `history` as CSV for dataframe tools: one row per trace with the
+/// [`CSV_COLUMNS`], then one column per parameter in sorted order.
+///
+/// The parameter columns are the union over all traces, so a parameter a
+/// trace lacks (an inactive conditional) is an empty cell, as are `phase`,
+/// `fidelity` and `feasibility` when the trace does not record them. A
+/// parameter named like one of the fixed columns is an error rather than a
+/// second, ambiguous column.
+fn history_csv(history: &[EvalTrace]) -> Result<String> {
+    let params: BTreeSet<&String> = history.iter().flat_map(|t| t.params.keys()).collect();
+    if let Some(name) = params
         .iter()
-        .map(|trace| SeedPoint {
-            params: trace.params.clone(),
-            value: trace.value,
-            cost: trace.cost,
-        })
-        .collect();
-    let state = SolverState {
-        config: artifact.config,
-        history,
-        run_id: Some(artifact.run_id),
-    };
-    metrics.set_history_len(state.history.len());
-    save_state(state_path, &state)
-}
-
-fn tui_command(
+        .find(|name| CSV_COLUMNS.contains(&name.as_str()))
+    {
+        return Err(miette::miette!(
+            "parameter {:?} has the name of a fixed CSV column; export it as json, yaml or toml",
+            name
+        ));
+    }
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    let mut header = CSV_COLUMNS.to_vec();
+    header.extend(params.iter().map(|name| name.as_str()));
+    writer.write_record(&header).into_diagnostic()?;
+    let cell = |value: Option<f64>| value.map(|v| v.to_string()).unwrap_or_default();
+    for trace in history {
+        let phase = match trace.kind {
+            Some(TraceKind::Probe) => "probe",
+            Some(TraceKind::Refine) => "refine",
+            None => "",
+        };
+        let mut row = vec![
+            trace.eval_id.to_string(),
+            trace.value.to_string(),
+            trace.cost.to_string(),
+            phase.to_string(),
+            cell(trace.fidelity),
+            trace.failed.to_string(),
+            cell(trace.feasibility),
+        ];
+        row.extend(
+            params
+                .iter()
+                .map(|name| cell(trace.params.get(*name).copied())),
+        );
+        writer.write_record(&row).into_diagnostic()?;
+    }
+    String::from_utf8(writer.into_inner().into_diagnostic()?).into_diagnostic()
+}
+
+/// Keep the first `keep` evaluations of the state at `state_path`, optionally
+/// under a new config, and report the phase the next `ask` resumes in.
+///
+/// The phase is not stored: reseeding the shorter history decides again
+/// whether the run is still probing or already refining, and a refine
+/// strategy is rebuilt from it. The `suggest` batch remainder, proposed from
+/// the full history, is dropped.
+fn truncate_command(
+    state_path: &Path,
+    keep: usize,
+    config_path: Option<&PathBuf>,
+    metrics: &Metrics,
+) -> Result<()> {
+    tracing::info!(command = "truncate", state = %state_path.display(), keep);
+    let mut state = load_state(state_path)?;
+    if !state.compacted.is_empty() {
+        return Err(miette::miette!(
+            "{} has {} evaluations compacted out of its history; it cannot be truncated",
+            state_path.display(),
+            state.compacted.evaluations
+        ));
+    }
+    if keep > state.history.len() {
+        return Err(miette::miette!(
+            "--keep {} exceeds the {} evaluations in {}",
+            keep,
+            state.history.len(),
+            state_path.display()
+        ));
+    }
+    if let Some(path) = config_path {
+        let config = load_config(path)?;
+        validate_config(&config)?;
+        state.config = config;
+    }
+    state.history.truncate(keep);
+    state.suggest = None;
+    metrics.set_history_len(state.history.len());
+    save_state(state_path, &state)?;
+
+    // As in `tell --verbose`: replay one `ask` so the phase is the one the
+    // next invocation picks up in, and discard the batch.
+    let mut solver = Solver::pcr(state.config);
+    seed_solver(
+        &mut solver,
+        state.history,
+        state.warm_start,
+        state.compacted,
+    )?;
+    let _ = solver.ask();
+    let status = StatusResponse::new(&solver, None);
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&status).into_diagnostic()?
+    );
+    Ok(())
+}
+
+fn import_command(
+    artifact_path: &Path,
+    state_path: &Path,
+    warm_start: bool,
+    metrics: &Metrics,
+) -> Result<()> {
+    tracing::info!(
+        command = "import",
+        artifact = %artifact_path.display(),
+        state = %state_path.display()
+    );
+    let artifact = RunArtifact::from_stored(read_data(artifact_path)?)
+        .into_diagnostic()
+        .with_context(|| format!("Cannot import {}", artifact_path.display()))?;
+    let history: Vec<SeedPoint> = artifact
+        .history
+        .iter()
+        .map(EvalTrace::to_seed_point)
+        .collect();
+    let state = SolverState {
+        config: artifact.config,
+        history,
+        run_id: Some(artifact.run_id),
+        warm_start,
+        compacted: CompactedHistory::default(),
+        suggest: None,
+        metrics: None,
+    };
+    metrics.set_history_len(state.history.len());
+    save_state(state_path, &state)
+}
+
+fn tui_command(
     state_path: &Path,
     events_path: Option<&PathBuf>,
     refresh_ms: u64,
@@ -588,23 +2393,68 @@ fn tui_command(
 
 mod dashboard;
 
+/// How often the dashboard loop wakes up to check for a shutdown request.
+const DASHBOARD_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Install SIGTERM and SIGINT handlers that set the returned flag.
+fn install_shutdown_handler() -> Result<Arc<AtomicBool>> {
+    use signal_hook::consts::{SIGINT, SIGTERM};
+
+    let flag = Arc::new(AtomicBool::new(false));
+    for signal in [SIGTERM, SIGINT] {
+        signal_hook::flag::register(signal, Arc::clone(&flag)).into_diagnostic()?;
+    }
+    Ok(flag)
+}
+
 fn dashboard_command(
-    state_path: &Path,
-    events_path: Option<&PathBuf>,
-    actions_path: Option<&PathBuf>,
+    source: &dashboard::RunSource,
     addr: &str,
+    cors_origin: Option<&str>,
     metrics: &Metrics,
 ) -> Result<()> {
-    tracing::info!(command = "dashboard", state = %state_path.display(), addr = %addr);
+    tracing::info!(command = "dashboard", source = %source.health_path().display(), addr = %addr);
+    let cors_header = cors_origin
+        .map(|origin| {
+            if origin.trim() == "*" {
+                return Err(miette::miette!(
+                    "--cors-origin must name the origin to allow, not \"*\""
+                ));
+            }
+            tiny_http::Header::from_bytes(&b"Access-Control-Allow-Origin"[..], origin)
+                .map_err(|_| miette::miette!("Invalid --cors-origin: {:?}", origin))
+        })
+        .transpose()?;
+    let shutdown = install_shutdown_handler()?;
     let server = Server::http(addr)
         .map_err(|e| miette::miette!("Failed to bind dashboard server to {}: {}", addr, e))?;
     let bound_addr = server.server_addr();
     println!("Dashboard running at http://{}", bound_addr);
-
-    for mut request in server.incoming_requests() {
+    let started = std::time::Instant::now();
+    let streams = dashboard::StreamSlots::default();
+
+    while !shutdown.load(Ordering::Relaxed) {
+        let mut request = match server.recv_timeout(DASHBOARD_POLL_INTERVAL) {
+            Ok(Some(request)) => request,
+            Ok(None) => continue,
+            Err(err) => return Err(err).into_diagnostic(),
+        };
         let url: &str = request.url();
         let (path, query) = split_query(url);
+        let is_api = path.starts_with("/api/");
+        let params = parse_query(query);
+        let run_paths = || source.resolve(params.get("run").map(String::as_str));
         let response = match (request.method().as_str(), path) {
+            ("GET", "/healthz") => {
+                match dashboard::health_json(source.health_path(), started.elapsed()) {
+                    Ok(health) => dashboard::json_response(Ok(health)),
+                    Err(err) => dashboard::json_response(Ok(serde_json::json!({
+                        "status": "error",
+                        "error": err.to_string(),
+                    })))
+                    .with_status_code(503),
+                }
+            }
             ("GET", "/") => dashboard::plain_response(dashboard::DASHBOARD_HTML, "text/html"),
             ("GET", "/assets/dashboard.css") => {
                 dashboard::plain_response(dashboard::DASHBOARD_CSS, "text/css")
@@ -612,27 +2462,59 @@ fn dashboard_command(
             ("GET", "/assets/dashboard.js") => {
                 dashboard::plain_response(dashboard::DASHBOARD_JS, "text/javascript")
             }
-            ("GET", "/api/state") => {
-                dashboard::json_response(dashboard::load_state_json(state_path, metrics))
-            }
-            ("GET", "/api/summary") => {
-                dashboard::json_response(dashboard::load_summary_json(state_path))
-            }
-            ("GET", "/api/events") => {
-                let params = parse_query(query);
-                dashboard::json_response(dashboard::load_events_json(events_path, &params))
-            }
-            ("GET", "/api/actions") => {
-                let params = parse_query(query);
-                dashboard::json_response(dashboard::load_actions_json(actions_path, &params))
-            }
-            ("POST", "/api/actions") => {
-                dashboard::json_response(dashboard::store_action(request.as_reader(), actions_path))
+            ("GET", "/api/runs") => dashboard::json_response(dashboard::load_runs_json(source)),
+            ("GET", "/api/state") => dashboard::json_response(
+                run_paths().and_then(|paths| dashboard::load_state_json(&paths.state, metrics)),
+            ),
+            ("GET", "/api/summary") => dashboard::json_response(
+                run_paths().and_then(|paths| dashboard::load_summary_json(&paths.state)),
+            ),
+            ("GET", "/api/stream") => {
+                match (run_paths(), streams.try_acquire(dashboard::MAX_STREAMS)) {
+                    (Ok(_), None) => dashboard::json_response(Err(miette::miette!(
+                        "too many open streams (at most {})",
+                        dashboard::MAX_STREAMS
+                    )))
+                    .with_status_code(503),
+                    (Ok(paths), Some(slot)) => {
+                        // Held open for the client's lifetime: serve it off the accept loop
+                        let writer = request.into_writer();
+                        std::thread::spawn(move || {
+                            let _slot = slot;
+                            let _ = dashboard::stream_summary(
+                                writer,
+                                &paths.state,
+                                paths.events.as_deref(),
+                                Duration::from_millis(250),
+                                Duration::from_secs(15),
+                            );
+                        });
+                        continue;
+                    }
+                    (Err(err), _) => dashboard::json_response(Err(err)),
+                }
             }
+            ("GET", "/api/events") => dashboard::json_response(
+                run_paths()
+                    .and_then(|paths| dashboard::load_events_json(paths.events.as_ref(), &params)),
+            ),
+            ("GET", "/api/actions") => dashboard::json_response(run_paths().and_then(|paths| {
+                // A run that never queued an action has no actions file yet
+                let actions = paths.actions.filter(|path| path.exists());
+                dashboard::load_actions_json(actions.as_ref(), &params)
+            })),
+            ("POST", "/api/actions") => dashboard::json_response(run_paths().and_then(|paths| {
+                dashboard::store_action(request.as_reader(), paths.actions.as_ref())
+            })),
             _ => Response::from_string("Not found").with_status_code(404),
         };
+        let response = match &cors_header {
+            Some(header) if is_api => response.with_header(header.clone()),
+            _ => response,
+        };
         let _ = request.respond(response);
     }
+    println!("Dashboard shutting down");
     Ok(())
 }
 
@@ -645,14 +2527,14 @@ fn draw_tui(frame: &mut Frame, state: Option<&SolverState>, events: &[String]) {
             Constraint::Length(8),
         ])
         .split(frame.area());
+    let middle = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(layout[1]);
 
     let summary_lines = match state {
         Some(state) => {
-            let best = state
-                .history
-                .iter()
-                .map(|entry| entry.value)
-                .min_by(|left, right| left.partial_cmp(right).unwrap());
+            let best = state.best().map(|entry| entry.value);
             let latest = state.history.last().map(|entry| entry.value);
             vec![
                 Line::from(format!(
@@ -678,7 +2560,39 @@ fn draw_tui(frame: &mut Frame, state: Option<&SolverState>, events: &[String]) {
         .block(Block::default().borders(Borders::ALL).title("Summary"));
     frame.render_widget(summary, layout[0]);
 
+    let pareto = state.is_some_and(|state| state.config.selection == Selection::Pareto);
     let history_items: Vec<ListItem> = match state {
+        Some(state) if pareto && !state.history.is_empty() => {
+            let traces: Vec<EvalTrace> = state
+                .history
+                .iter()
+                .enumerate()
+                .map(|(index, seed)| EvalTrace {
+                    eval_id: (index + 1) as u64,
+                    params: seed.params.clone(),
+                    value: seed.value,
+                    cost: seed.cost,
+                    repeats: None,
+                    penalized: seed.penalized,
+                    failed: false,
+                    feasibility: None,
+                    penalty: None,
+                    fidelity: None,
+                    kind: None,
+                    source: None,
+                })
+                .collect();
+            pareto_front(&traces)
+                .into_iter()
+                .map(|entry| {
+                    let params = format_params(&entry.params);
+                    ListItem::new(format!(
+                        "value={:.6} cost={:.4} | {}",
+                        entry.value, entry.cost, params
+                    ))
+                })
+                .collect()
+        }
         Some(state) if !state.history.is_empty() => state
             .history
             .iter()
@@ -686,17 +2600,21 @@ fn draw_tui(frame: &mut Frame, state: Option<&SolverState>, events: &[String]) {
             .take(6)
             .map(|entry| {
                 let params = format_params(&entry.params);
-                ListItem::new(format!("value={:.6} | {}", entry.value, params))
+                let failed = if entry.failed { " (failed)" } else { "" };
+                ListItem::new(format!("value={:.6}{} | {}", entry.value, failed, params))
             })
             .collect(),
         _ => vec![ListItem::new("No evaluations yet")],
     };
-    let history = List::new(history_items).block(
-        Block::default()
-            .borders(Borders::ALL)
-            .title("Recent Evaluations"),
-    );
-    frame.render_widget(history, layout[1]);
+    let history =
+        List::new(history_items).block(Block::default().borders(Borders::ALL).title(if pareto {
+            "Pareto Front"
+        } else {
+            "Recent Evaluations"
+        }));
+    frame.render_widget(history, middle[0]);
+
+    draw_progress(frame, state, middle[1]);
 
     let event_items: Vec<ListItem> = if events.is_empty() {
         vec![ListItem::new("No events")]
@@ -711,8 +2629,129 @@ fn draw_tui(frame: &mut Frame, state: Option<&SolverState>, events: &[String]) {
     frame.render_widget(event_list, layout[2]);
 }
 
+/// Convergence chart of the best value so far, above one bar per parameter
+/// showing where the best point sits within its bounds.
+fn draw_progress(frame: &mut Frame, state: Option<&SolverState>, area: Rect) {
+    let positions = state.map(best_point_positions).unwrap_or_default();
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(5),
+            Constraint::Length(positions.len().max(1) as u16 + 2),
+        ])
+        .split(area);
+
+    let points = state.map(best_so_far).unwrap_or_default();
+    let chart_block = Block::default().borders(Borders::ALL).title("Best So Far");
+    match (points.first(), points.last()) {
+        (Some(&(_, first)), Some(&(last_x, last))) => {
+            let (mut low, mut high) = (last, first);
+            if high <= low {
+                // Flat (or single-point) curve: give the axis some height.
+                let pad = (high.abs() * 0.1).max(0.5);
+                low -= pad;
+                high += pad;
+            }
+            let dataset = Dataset::default()
+                .marker(symbols::Marker::Braille)
+                .graph_type(GraphType::Line)
+                .data(&points);
+            let chart = Chart::new(vec![dataset])
+                .block(chart_block)
+                .x_axis(
+                    Axis::default()
+                        .bounds([1.0, last_x.max(2.0)])
+                        .labels(["1".to_string(), format!("{}", last_x as usize)]),
+                )
+                .y_axis(
+                    Axis::default()
+                        .bounds([low, high])
+                        .labels([format!("{low:.3}"), format!("{high:.3}")]),
+                );
+            frame.render_widget(chart, layout[0]);
+        }
+        _ => frame.render_widget(
+            Paragraph::new("No evaluations yet").block(chart_block),
+            layout[0],
+        ),
+    }
+
+    const BAR_WIDTH: usize = 20;
+    let name_width = positions
+        .iter()
+        .map(|(name, _)| name.len())
+        .max()
+        .unwrap_or(0);
+    let lines: Vec<Line> = if positions.is_empty() {
+        vec![Line::from("No best point yet")]
+    } else {
+        positions
+            .iter()
+            .map(|(name, unit)| {
+                let filled = (unit * BAR_WIDTH as f64).round() as usize;
+                Line::from(format!(
+                    "{name:<name_width$} [{}{}] {unit:.2}",
+                    "#".repeat(filled),
+                    ".".repeat(BAR_WIDTH - filled),
+                ))
+            })
+            .collect()
+    };
+    let bars = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("Best Point In Bounds"),
+    );
+    frame.render_widget(bars, layout[1]);
+}
+
+/// `(evaluation number, best value up to it)` for each evaluation.
+fn best_so_far(state: &SolverState) -> Vec<(f64, f64)> {
+    let mut best: Option<f64> = None;
+    state
+        .history
+        .iter()
+        .enumerate()
+        .map(|(index, point)| {
+            let value = match best {
+                Some(best)
+                    if state.config.minimized(best) <= state.config.minimized(point.value) =>
+                {
+                    best
+                }
+                _ => point.value,
+            };
+            best = Some(value);
+            ((index + 1) as f64, value)
+        })
+        .collect()
+}
+
+/// Position in [0, 1] of each parameter of the best point within its bounds,
+/// sorted by name. Parameters missing from the config's bounds are skipped.
+fn best_point_positions(state: &SolverState) -> Vec<(String, f64)> {
+    let Some(best) = state.best() else {
+        return Vec::new();
+    };
+    let mut positions: Vec<(String, f64)> = best
+        .params
+        .iter()
+        .filter_map(|(name, &value)| {
+            let domain = state.config.bounds.get(name)?;
+            Some((name.clone(), domain.to_unit(value)))
+        })
+        .collect();
+    positions.sort_by(|a, b| a.0.cmp(&b.0));
+    positions
+}
+
+/// `name=value` pairs sorted by name; the [`RESERVED_KEYS`] are metadata,
+/// not parameters, and are left out.
 fn format_params(params: &HashMap<String, f64>) -> String {
-    let mut keys: Vec<_> = params.keys().collect();
+    let mut keys: Vec<_> = params
+        .keys()
+        .filter(|key| !RESERVED_KEYS.contains(&key.as_str()))
+        .collect();
     keys.sort();
     let parts: Vec<String> = keys
         .into_iter()
@@ -760,7 +2799,15 @@ fn generate_run_id(prefix: &str) -> String {
 }
 
 fn write_output<T: Serialize>(path: Option<&PathBuf>, value: &T) -> Result<()> {
-    let data = serde_json::to_string_pretty(value).into_diagnostic()?;
+    write_formatted(path, value, DataFormat::Json)
+}
+
+fn write_formatted<T: Serialize>(
+    path: Option<&PathBuf>,
+    value: &T,
+    format: DataFormat,
+) -> Result<()> {
+    let data = format.render(value)?;
     if let Some(path) = path {
         fs::write(path, data)
             .into_diagnostic()
@@ -780,6 +2827,17 @@ fn read_json<T: DeserializeOwned>(path: &Path) -> Result<T> {
         .with_context(|| format!("Invalid JSON in {}", path.display()))
 }
 
+/// Read a file in the format implied by its extension.
+fn read_data<T: DeserializeOwned>(path: &Path) -> Result<T> {
+    let format = DataFormat::from_path(path);
+    let contents = fs::read_to_string(path)
+        .into_diagnostic()
+        .with_context(|| format!("Failed to read {} file {}", format.name(), path.display()))?;
+    format
+        .parse(&contents)
+        .with_context(|| format!("Invalid {} in {}", format.name(), path.display()))
+}
+
 fn split_query(url: &str) -> (&str, Option<&str>) {
     if let Some((path, query)) = url.split_once('?') {
         (path, Some(query))
@@ -806,282 +2864,2677 @@ fn parse_query(query: Option<&str>) -> HashMap<String, String> {
     params
 }
 
-fn validate_command(config_path: &Path) -> Result<()> {
-    tracing::info!(command = "validate", config = %config_path.display());
-    let config = load_config(config_path)?;
-    validate_config(&config)?;
-    println!("Config OK");
-    Ok(())
+/// First point where a replay disagrees with the recorded artifact.
+#[derive(Debug, Serialize, PartialEq)]
+struct ReplayDivergence {
+    /// 1-based `ask` call that produced the diverging candidate.
+    step: usize,
+    /// 0-based position in the artifact history (ordered by eval_id).
+    eval_index: usize,
+    /// Diverging parameter; `None` if the solver stopped early.
+    param: Option<String>,
+    expected: Option<f64>,
+    actual: Option<f64>,
 }
 
-fn load_state_or_config(config_path: &Path, state_path: Option<&PathBuf>) -> Result<LoadedState> {
-    let config = load_config(config_path)?;
-    if let Some(path) = state_path {
-        if path.exists() {
-            let state = load_state(path)?;
-            return Ok(LoadedState {
-                config: state.config,
-                history: state.history,
-                run_id: state.run_id,
+#[derive(Debug, Serialize)]
+struct ReplayReport {
+    run_id: String,
+    passed: bool,
+    steps: usize,
+    checked: usize,
+    divergence: Option<ReplayDivergence>,
+}
+
+/// Rebuild the solver from the artifact's config and seed, then step it
+/// through the recorded history. Each regenerated candidate is compared with
+/// the recorded params, and the recorded results are fed back so later
+/// batches see exactly the history of the original run. Recorded results
+/// the solver refuses are an error, not a divergence.
+fn replay_artifact(artifact: &RunArtifact, tolerance: f64) -> Result<ReplayReport> {
+    let mut history = artifact.history.clone();
+    history.sort_by_key(|trace| trace.eval_id);
+    let mut config = artifact.config.clone();
+    config.seed = artifact.seed;
+    let mut solver = Solver::pcr(config);
+
+    let mut report = ReplayReport {
+        run_id: artifact.run_id.clone(),
+        passed: true,
+        steps: 0,
+        checked: 0,
+        divergence: None,
+    };
+    while report.checked < history.len() {
+        let Some(batch) = solver.ask() else {
+            report.passed = false;
+            report.divergence = Some(ReplayDivergence {
+                step: report.steps + 1,
+                eval_index: report.checked,
+                param: None,
+                expected: None,
+                actual: None,
+            });
+            return Ok(report);
+        };
+        report.steps += 1;
+        let mut results = Vec::with_capacity(batch.len());
+        for candidate in batch {
+            let Some(recorded) = history.get(report.checked) else {
+                break;
+            };
+            if let Some((param, expected, actual)) =
+                first_param_mismatch(&recorded.params, &candidate, tolerance)
+            {
+                report.passed = false;
+                report.divergence = Some(ReplayDivergence {
+                    step: report.steps,
+                    eval_index: report.checked,
+                    param: Some(param),
+                    expected,
+                    actual,
+                });
+                return Ok(report);
+            }
+            results.push(SeedPoint {
+                params: recorded.params.clone(),
+                value: recorded.value,
+                cost: recorded.cost,
+                penalized: recorded.penalized,
+                failed: false,
+                feasibility: None,
             });
+            report.checked += 1;
         }
+        solver
+            .seed(results)
+            .into_diagnostic()
+            .with_context(|| format!("Failed to replay step {}", report.steps))?;
     }
-    Ok(LoadedState {
-        config,
-        history: Vec::new(),
-        run_id: None,
-    })
+    Ok(report)
 }
 
-fn load_state(path: &Path) -> Result<SolverState> {
-    let contents = fs::read_to_string(path)
-        .into_diagnostic()
-        .with_context(|| format!("Failed to read state file {}", path.display()))?;
-    let state: SolverState = serde_json::from_str(&contents)
-        .into_diagnostic()
-        .with_context(|| format!("Invalid state JSON in {}", path.display()))?;
-    Ok(state)
+/// Parse `start..end`, `start..=end` or a single seed into a range.
+fn parse_seed_range(spec: &str) -> std::result::Result<std::ops::Range<u64>, String> {
+    let parse = |part: &str| {
+        part.trim()
+            .parse::<u64>()
+            .map_err(|err| format!("invalid seed '{}': {}", part.trim(), err))
+    };
+    let range = if let Some((start, end)) = spec.split_once("..=") {
+        parse(start)?..parse(end)?.saturating_add(1)
+    } else if let Some((start, end)) = spec.split_once("..") {
+        parse(start)?..parse(end)?
+    } else {
+        let seed = parse(spec)?;
+        seed..seed.saturating_add(1)
+    };
+    if range.is_empty() {
+        return Err(format!("seed range '{}' is empty", spec));
+    }
+    Ok(range)
 }
 
-fn save_state(path: &Path, state: &SolverState) -> Result<()> {
-    let data = serde_json::to_string_pretty(state).into_diagnostic()?;
-    fs::write(path, data)
-        .into_diagnostic()
-        .with_context(|| format!("Failed to write state file {}", path.display()))?;
-    Ok(())
+/// Outcome of one full solve in a `bench` run.
+#[derive(Debug, Serialize)]
+struct BenchTrial {
+    seed: u64,
+    best_value: f64,
+    /// 1-based evaluation at which `best_value` was first reached.
+    best_eval: usize,
+    evaluations: usize,
 }
 
-fn load_config(path: &Path) -> Result<SolverConfig> {
-    let contents = fs::read_to_string(path)
-        .into_diagnostic()
-        .with_context(|| format!("Failed to read config file {}", path.display()))?;
-    let config: SolverConfig = serde_json::from_str(&contents)
-        .into_diagnostic()
-        .with_context(|| format!("Invalid config JSON in {}", path.display()))?;
-    validate_config(&config)?;
-    Ok(config)
+/// Aggregates over all trials of a `bench` run.
+#[derive(Debug, Serialize)]
+struct BenchSummary {
+    trials: usize,
+    mean_best: f64,
+    median_best: f64,
+    /// Sample standard deviation of `best_value` (0 for a single trial).
+    std_best: f64,
+    mean_best_eval: f64,
 }
 
-fn validate_config(config: &SolverConfig) -> Result<()> {
-    if config.budget == 0 {
-        return Err(miette::miette!("budget must be > 0"));
-    }
-    if config.bounds.is_empty() {
-        return Err(miette::miette!("bounds must not be empty"));
-    }
-    for (name, domain) in &config.bounds {
-        if domain.min >= domain.max {
-            return Err(miette::miette!(
-                "bounds for {} must satisfy min < max",
-                name
-            ));
-        }
-        if matches!(domain.scale, arqonhpo_core::config::Scale::Log)
-            && (domain.min <= 0.0 || domain.max <= 0.0)
-        {
-            return Err(miette::miette!("log scale bounds for {} must be > 0", name));
-        }
+#[derive(Debug, Serialize)]
+struct BenchReport {
+    config: SolverConfig,
+    trials: Vec<BenchTrial>,
+    summary: BenchSummary,
+}
+
+fn summarize_trials(trials: &[BenchTrial]) -> BenchSummary {
+    let n = trials.len() as f64;
+    let mut best: Vec<f64> = trials.iter().map(|t| t.best_value).collect();
+    best.sort_by(|a, b| a.total_cmp(b));
+    let mean_best = best.iter().sum::<f64>() / n;
+    let mid = best.len() / 2;
+    let median_best = if best.len().is_multiple_of(2) {
+        (best[mid - 1] + best[mid]) / 2.0
+    } else {
+        best[mid]
+    };
+    let std_best = if best.len() > 1 {
+        (best.iter().map(|v| (v - mean_best).powi(2)).sum::<f64>() / (n - 1.0)).sqrt()
+    } else {
+        0.0
+    };
+    BenchSummary {
+        trials: trials.len(),
+        mean_best,
+        median_best,
+        std_best,
+        mean_best_eval: trials.iter().map(|t| t.best_eval as f64).sum::<f64>() / n,
     }
-    Ok(())
 }
 
-fn read_input(path: Option<&PathBuf>) -> Result<String> {
-    if let Some(path) = path {
-        return fs::read_to_string(path)
-            .into_diagnostic()
-            .with_context(|| format!("Failed to read results file {}", path.display()));
+fn bench_command(
+    config_path: &Path,
+    script: &Path,
+    seeds: std::ops::Range<u64>,
+    out: Option<&PathBuf>,
+    metrics: &Metrics,
+) -> Result<()> {
+    tracing::info!(command = "bench", config = %config_path.display(), ?seeds);
+    let config = load_config(config_path)?;
+    check_script_env(&config)?;
+    let mut trials = Vec::new();
+    for seed in seeds {
+        let mut solver = Solver::pcr(SolverConfig {
+            seed,
+            ..config.clone()
+        });
+        solve_once(
+            &mut solver,
+            script,
+            None,
+            None,
+            None,
+            metrics,
+            None,
+            None,
+            |_| Ok(()),
+        )?;
+        let (best_index, best) = solver
+            .history
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| cmp_traces(a, b))
+            .ok_or_else(|| miette::miette!("seed {} produced no evaluations", seed))?;
+        let best_value = solver.original_value(best.value);
+        tracing::info!(seed, best_value, "trial finished");
+        trials.push(BenchTrial {
+            seed,
+            best_value,
+            best_eval: best_index + 1,
+            evaluations: solver.evaluations(),
+        });
     }
+    let report = BenchReport {
+        config,
+        summary: summarize_trials(&trials),
+        trials,
+    };
+    write_output(out, &report)
+}
 
-    let mut buffer = String::new();
-    io::stdin().read_to_string(&mut buffer).into_diagnostic()?;
-    Ok(buffer)
+/// Two-parameter grid around a point, for `explore`.
+#[derive(Debug)]
+struct SurfaceGrid {
+    dims: [String; 2],
+    /// Values of `dims[0]`, one per column.
+    x: Vec<f64>,
+    /// Values of `dims[1]`, one per row.
+    y: Vec<f64>,
+    /// Row-major: `candidates[row * x.len() + col]` sets `dims[0]` to
+    /// `x[col]` and `dims[1]` to `y[row]`, other parameters as in the center.
+    candidates: Vec<HashMap<String, f64>>,
 }
 
-fn evaluate_script(script: &Path, params: &HashMap<String, f64>) -> Result<f64> {
-    let mut command = Command::new(script);
-    let mut keys: Vec<_> = params.keys().collect();
-    keys.sort();
-    for key in keys {
-        let env_key = format!("ARQON_{}", key);
-        command.env(env_key, params[key].to_string());
+/// `explore` output: `values[row][col]` is the objective at `x[col]`,
+/// `y[row]`, the layout heatmap plotters expect. Failed or non-finite
+/// evaluations are `null`.
+#[derive(Debug, Serialize)]
+struct SurfaceReport {
+    dims: [String; 2],
+    x: Vec<f64>,
+    y: Vec<f64>,
+    values: Vec<Vec<Option<f64>>>,
+    center: HashMap<String, f64>,
+}
+
+/// Parse `--span`: a finite, positive fraction (values over 1 cover the
+/// whole range).
+fn parse_span(spec: &str) -> std::result::Result<f64, String> {
+    let span: f64 = spec
+        .trim()
+        .parse()
+        .map_err(|err| format!("invalid span '{}': {}", spec.trim(), err))?;
+    if !(span.is_finite() && span > 0.0) {
+        return Err(format!(
+            "span must be a finite number > 0, got {}",
+            spec.trim()
+        ));
     }
+    Ok(span)
+}
 
-    let output = command.output().into_diagnostic()?;
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
+/// `resolution` points evenly spaced in the domain's scale over a window of
+/// `span` (a fraction of the unit range) centered on `center`. Near a bound
+/// the window slides inward so it keeps its width.
+fn grid_axis(domain: &Domain, center: f64, span: f64, resolution: usize) -> Vec<f64> {
+    let width = span.clamp(0.0, 1.0);
+    let start = (domain.to_unit(center) - width / 2.0).clamp(0.0, 1.0 - width);
+    let last = resolution.saturating_sub(1).max(1) as f64;
+    (0..resolution)
+        .map(|i| domain.from_unit(start + width * i as f64 / last))
+        .collect()
+}
+
+fn surface_grid(
+    config: &SolverConfig,
+    center: &HashMap<String, f64>,
+    dims: &[String],
+    span: f64,
+    resolution: usize,
+) -> Result<SurfaceGrid> {
+    let [x_name, y_name] = dims else {
         return Err(miette::miette!(
-            "Script failed with status {}: {}",
-            output.status,
-            stderr.trim()
+            "--dims takes exactly two parameters, got {}",
+            dims.len()
         ));
+    };
+    if x_name == y_name {
+        return Err(miette::miette!("--dims must name two different parameters"));
     }
+    let axis = |name: &String| {
+        let domain = config
+            .bounds
+            .get(name)
+            .ok_or_else(|| miette::miette!("unknown parameter {} in --dims", name))?;
+        let at = center
+            .get(name)
+            .copied()
+            .unwrap_or((domain.min + domain.max) / 2.0);
+        Ok::<_, miette::Report>(grid_axis(domain, at, span, resolution))
+    };
+    let (x, y) = (axis(x_name)?, axis(y_name)?);
+    let candidates = y
+        .iter()
+        .flat_map(|&y_value| {
+            x.iter().map(move |&x_value| {
+                let mut point = center.clone();
+                point.insert(x_name.clone(), x_value);
+                point.insert(y_name.clone(), y_value);
+                point
+            })
+        })
+        .collect();
+    Ok(SurfaceGrid {
+        dims: [x_name.clone(), y_name.clone()],
+        x,
+        y,
+        candidates,
+    })
+}
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    parse_result(&stdout)
+impl SurfaceGrid {
+    /// Arrange per-candidate values (in `candidates` order) as rows of `y`.
+    fn report(self, values: Vec<Option<f64>>, center: HashMap<String, f64>) -> SurfaceReport {
+        let values = values
+            .chunks(self.x.len())
+            .map(|row| row.iter().map(|v| v.filter(|v| v.is_finite())).collect())
+            .collect();
+        SurfaceReport {
+            dims: self.dims,
+            x: self.x,
+            y: self.y,
+            values,
+            center,
+        }
+    }
 }
 
-fn parse_result(stdout: &str) -> Result<f64> {
-    let mut last_value: Option<&str> = None;
-    for line in stdout.lines() {
-        let trimmed = line.trim();
-        if trimmed.is_empty() {
-            continue;
+#[allow(clippy::too_many_arguments)]
+fn explore_command(
+    state_path: &Path,
+    around: Around,
+    resolution: usize,
+    dims: &[String],
+    span: f64,
+    script: Option<&PathBuf>,
+    results: Option<&PathBuf>,
+    out: Option<&PathBuf>,
+) -> Result<()> {
+    tracing::info!(command = "explore", state = %state_path.display(), ?dims);
+    let state = load_state(state_path)?;
+    let center = match around {
+        Around::Best => state
+            .history
+            .iter()
+            .filter(|p| p.params.get(FIDELITY_KEY).is_none_or(|&f| f >= 1.0))
+            .filter(|p| !p.failed)
+            .filter(|p| p.feasibility.is_none_or(|f| f >= 1.0))
+            .min_by(|a, b| cmp_objective(&state.config, a, b))
+            .map(|p| {
+                let mut params = p.params.clone();
+                params.remove(FIDELITY_KEY);
+                params
+            })
+            .ok_or_else(|| miette::miette!("state has no evaluations to explore around"))?,
+    };
+    let grid = surface_grid(&state.config, &center, dims, span, resolution)?;
+
+    let values: Vec<Option<f64>> = if let Some(script) = script {
+        check_script_env(&state.config)?;
+        grid.candidates
+            .iter()
+            .map(|params| match evaluate_script(script, params) {
+                Ok(value) => Some(value),
+                Err(err) => {
+                    tracing::warn!(error = %err, "grid evaluation failed");
+                    None
+                }
+            })
+            .collect()
+    } else if let Some(path) = results {
+        let results: Vec<Option<ExternalResult>> = read_json(path)?;
+        if results.len() != grid.candidates.len() {
+            return Err(miette::miette!(
+                "expected {} grid results, got {}",
+                grid.candidates.len(),
+                results.len()
+            ));
         }
-        if let Some(rest) = trimmed.strip_prefix("RESULT=") {
-            last_value = Some(rest.trim());
+        results
+            .into_iter()
+            .map(|result| match result? {
+                ExternalResult::Value(value) => Some(value),
+                ExternalResult::Point(point) => Some(point.value),
+            })
+            .collect()
+    } else {
+        return write_output(out, &grid.candidates);
+    };
+    write_output(out, &grid.report(values, center))
+}
+
+fn first_param_mismatch(
+    expected: &HashMap<String, f64>,
+    actual: &HashMap<String, f64>,
+    tolerance: f64,
+) -> Option<(String, Option<f64>, Option<f64>)> {
+    let mut keys: Vec<&String> = expected.keys().chain(actual.keys()).collect();
+    keys.sort();
+    keys.dedup();
+    keys.into_iter().find_map(|key| {
+        let (e, a) = (expected.get(key).copied(), actual.get(key).copied());
+        match (e, a) {
+            (Some(e), Some(a)) if (e - a).abs() <= tolerance => None,
+            _ => Some((key.clone(), e, a)),
+        }
+    })
+}
+
+fn replay_command(artifact_path: &Path, tolerance: f64) -> Result<()> {
+    tracing::info!(command = "replay", artifact = %artifact_path.display());
+    let artifact: RunArtifact = read_json(artifact_path)?;
+    let report = replay_artifact(&artifact, tolerance)?;
+    let output = serde_json::to_string_pretty(&report).into_diagnostic()?;
+    println!("{}", output);
+    match report.divergence {
+        None => Ok(()),
+        Some(divergence) => Err(miette::miette!(
+            "Replay diverged at step {} (evaluation {}): {}",
+            divergence.step,
+            divergence.eval_index,
+            match divergence.param {
+                Some(param) => format!(
+                    "{} expected {:?}, got {:?}",
+                    param, divergence.expected, divergence.actual
+                ),
+                None => "solver stopped before the recorded history ended".to_string(),
+            }
+        )),
+    }
+}
+
+/// Relative gap to the final best within which a run counts as converged.
+const CONVERGED_FRACTION: f64 = 0.01;
+
+/// One side of a `compare` report.
+#[derive(Debug, Serialize)]
+struct CompareRun {
+    run_id: String,
+    best_value: f64,
+    /// 1-based evaluation at which `best_value` was first reached.
+    best_eval: usize,
+    /// 1-based evaluation at which the run first came within 1% of `best_value`.
+    converged_eval: usize,
+    evaluations: usize,
+    best_params: HashMap<String, f64>,
+}
+
+/// Best-point difference for one parameter of either artifact.
+#[derive(Debug, Serialize, PartialEq)]
+struct ParamDelta {
+    param: String,
+    /// Value at A's best point; `None` if A has no such (active) parameter.
+    a: Option<f64>,
+    b: Option<f64>,
+    /// `b - a` in raw units.
+    delta: Option<f64>,
+    /// `b - a` in unit space, when both artifacts bound the parameter.
+    unit_delta: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+struct CompareReport {
+    a: CompareRun,
+    b: CompareRun,
+    params: Vec<ParamDelta>,
+    /// Euclidean distance between the best points over the `unit_delta`s;
+    /// `None` when the artifacts share no parameter.
+    distance: Option<f64>,
+    basin_radius: f64,
+    same_basin: Option<bool>,
+}
+
+/// Summarize an artifact's full-fidelity history in eval_id order.
+fn compare_run(artifact: &RunArtifact) -> Result<CompareRun> {
+    let mut history: Vec<&EvalTrace> = artifact
+        .history
+        .iter()
+        .filter(|t| t.is_full_fidelity())
+        .collect();
+    history.sort_by_key(|t| t.eval_id);
+    // Values are in the objective's units; rank them as the solver did.
+    let minimized = |t: &EvalTrace| artifact.config.minimized(t.value);
+    let (best_index, best) = history
+        .iter()
+        .enumerate()
+        .min_by(|(_, a), (_, b)| {
+            minimized(a)
+                .total_cmp(&minimized(b))
+                .then_with(|| cmp_traces(a, b))
+        })
+        .ok_or_else(|| {
+            miette::miette!(
+                "Artifact {} has no full-fidelity evaluations",
+                artifact.run_id
+            )
+        })?;
+    let threshold = minimized(best) + CONVERGED_FRACTION * best.value.abs();
+    let converged_index = history
+        .iter()
+        .position(|t| minimized(t) <= threshold)
+        .unwrap_or(best_index);
+    Ok(CompareRun {
+        run_id: artifact.run_id.clone(),
+        best_value: best.value,
+        best_eval: best_index + 1,
+        converged_eval: converged_index + 1,
+        evaluations: history.len(),
+        best_params: best.params.clone(),
+    })
+}
+
+/// Domain spanning both artifacts' bounds for `param`, so the same raw value
+/// maps to the same unit position on both sides. Differing scales fall back
+/// to linear.
+fn shared_domain(a: &Domain, b: &Domain) -> Domain {
+    Domain {
+        min: a.min.min(b.min),
+        max: a.max.max(b.max),
+        scale: if a.scale == b.scale {
+            a.scale.clone()
         } else {
-            last_value = Some(trimmed);
+            Default::default()
+        },
+        active_when: None,
+    }
+}
+
+fn compare_artifacts(a: &RunArtifact, b: &RunArtifact, basin_radius: f64) -> Result<CompareReport> {
+    let (run_a, run_b) = (compare_run(a)?, compare_run(b)?);
+    let mut names: Vec<&String> = a
+        .config
+        .bounds
+        .keys()
+        .chain(b.config.bounds.keys())
+        .collect();
+    names.sort();
+    names.dedup();
+
+    let params: Vec<ParamDelta> = names
+        .into_iter()
+        .map(|name| {
+            let va = run_a.best_params.get(name).copied();
+            let vb = run_b.best_params.get(name).copied();
+            let (delta, unit_delta) = match (va, vb) {
+                (Some(va), Some(vb)) => {
+                    let unit_delta = match (a.config.bounds.get(name), b.config.bounds.get(name)) {
+                        (Some(da), Some(db)) => {
+                            let domain = shared_domain(da, db);
+                            let (ua, ub) = (domain.to_unit(va), domain.to_unit(vb));
+                            Some(if domain.is_periodic() {
+                                diff01(ub, ua)
+                            } else {
+                                ub - ua
+                            })
+                        }
+                        _ => None,
+                    };
+                    (Some(vb - va), unit_delta)
+                }
+                _ => (None, None),
+            };
+            ParamDelta {
+                param: name.clone(),
+                a: va,
+                b: vb,
+                delta,
+                unit_delta,
+            }
+        })
+        .collect();
+
+    let unit: Vec<f64> = params.iter().filter_map(|p| p.unit_delta).collect();
+    let distance = (!unit.is_empty()).then(|| unit.iter().map(|d| d * d).sum::<f64>().sqrt());
+    Ok(CompareReport {
+        a: run_a,
+        b: run_b,
+        params,
+        distance,
+        basin_radius,
+        same_basin: distance.map(|d| d < basin_radius),
+    })
+}
+
+fn format_compare(report: &CompareReport) -> String {
+    let opt = |v: Option<f64>| v.map_or_else(|| "-".to_string(), |v| format!("{:.6}", v));
+    let mut out = format!(
+        "{:<16} {:>16} {:>16}\n\
+         {:<16} {:>16} {:>16}\n\
+         {:<16} {:>16} {:>16}\n\
+         {:<16} {:>16} {:>16}\n\
+         {:<16} {:>16} {:>16}\n\n",
+        "",
+        "A",
+        "B",
+        "run",
+        report.a.run_id,
+        report.b.run_id,
+        "best value",
+        format!("{:.6}", report.a.best_value),
+        format!("{:.6}", report.b.best_value),
+        "best at eval",
+        report.a.best_eval,
+        report.b.best_eval,
+        "within 1% at",
+        report.a.converged_eval,
+        report.b.converged_eval,
+    );
+    out.push_str(&format!(
+        "{:<16} {:>16} {:>16} {:>16} {:>12}\n",
+        "param", "A", "B", "B - A", "unit delta"
+    ));
+    for param in &report.params {
+        out.push_str(&format!(
+            "{:<16} {:>16} {:>16} {:>16} {:>12}\n",
+            param.param,
+            opt(param.a),
+            opt(param.b),
+            opt(param.delta),
+            param
+                .unit_delta
+                .map_or_else(|| "-".to_string(), |d| format!("{:.4}", d)),
+        ));
+    }
+    out.push_str(&match (report.distance, report.same_basin) {
+        (Some(distance), Some(same)) => format!(
+            "\nUnit distance {:.4} ({} basin, radius {})\n",
+            distance,
+            if same { "same" } else { "different" },
+            report.basin_radius
+        ),
+        _ => "\nNo shared parameters to measure distance\n".to_string(),
+    });
+    out
+}
+
+fn compare_command(a_path: &Path, b_path: &Path, basin_radius: f64, json: bool) -> Result<()> {
+    tracing::info!(command = "compare", a = %a_path.display(), b = %b_path.display());
+    let a: RunArtifact = read_data(a_path)?;
+    let b: RunArtifact = read_data(b_path)?;
+    let report = compare_artifacts(&a, &b, basin_radius)?;
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&report).into_diagnostic()?
+        );
+    } else {
+        print!("{}", format_compare(&report));
+    }
+    Ok(())
+}
+
+/// Observed max/min ratio from which a strictly positive column gets a log scale.
+const LOG_SPREAD_RATIO: f64 = 100.0;
+/// Half-width of the range given to a constant column, relative to its value
+/// (absolute when the value is smaller than 1).
+const CONSTANT_HALF_WIDTH: f64 = 0.1;
+
+/// Domain covering `[min, max]` widened by `margin` of the span on each side.
+///
+/// Wide strictly positive ranges get a log scale and are widened in log
+/// space; linear ranges that were non-negative stay non-negative.
+fn infer_domain(min: f64, max: f64, margin: f64) -> Domain {
+    let domain = |min, max, scale| Domain {
+        min,
+        max,
+        scale,
+        active_when: None,
+    };
+    if max <= min {
+        let half = CONSTANT_HALF_WIDTH * min.abs().max(1.0);
+        return domain(min - half, min + half, Scale::Linear);
+    }
+    if min > 0.0 && max / min >= LOG_SPREAD_RATIO {
+        let widen = margin * (max.ln() - min.ln());
+        return domain(
+            (min.ln() - widen).exp(),
+            (max.ln() + widen).exp(),
+            Scale::Log,
+        );
+    }
+    let widen = margin * (max - min);
+    let lower = if min >= 0.0 {
+        (min - widen).max(0.0)
+    } else {
+        min - widen
+    };
+    domain(lower, max + widen, Scale::Linear)
+}
+
+/// Per-column `(min, max)` of every column of `csv` except `value_column`.
+/// Empty cells (inactive parameters) are skipped.
+fn column_ranges<R: Read>(csv: R, value_column: &str) -> Result<HashMap<String, (f64, f64)>> {
+    let mut reader = csv::Reader::from_reader(csv);
+    let headers = reader.headers().into_diagnostic()?.clone();
+    if !headers.iter().any(|h| h == value_column) {
+        return Err(miette::miette!("No '{}' column in the data", value_column));
+    }
+    let mut ranges: HashMap<String, (f64, f64)> = HashMap::new();
+    for (row, record) in reader.records().enumerate() {
+        let record = record.into_diagnostic()?;
+        for (name, cell) in headers.iter().zip(record.iter()) {
+            let cell = cell.trim();
+            if name == value_column || cell.is_empty() {
+                continue;
+            }
+            let value = cell
+                .parse::<f64>()
+                .ok()
+                .filter(|v| v.is_finite())
+                .ok_or_else(|| {
+                    miette::miette!(
+                        "Row {}: '{}' is not a finite number in column '{}'",
+                        row + 1,
+                        cell,
+                        name
+                    )
+                })?;
+            let range = ranges.entry(name.to_string()).or_insert((value, value));
+            range.0 = range.0.min(value);
+            range.1 = range.1.max(value);
+        }
+    }
+    if ranges.is_empty() {
+        return Err(miette::miette!("The data has no parameter values"));
+    }
+    Ok(ranges)
+}
+
+fn infer_config<R: Read>(
+    csv: R,
+    margin: f64,
+    value_column: &str,
+    budget: u64,
+) -> Result<SolverConfig> {
+    if !(margin.is_finite() && margin >= 0.0) {
+        return Err(miette::miette!("--margin must be a non-negative number"));
+    }
+    let bounds = column_ranges(csv, value_column)?
+        .into_iter()
+        .map(|(name, (min, max))| (name, infer_domain(min, max, margin)))
+        .collect();
+    Ok(SolverConfig {
+        seed: 42,
+        budget,
+        bounds,
+        probe_ratio: Default::default(),
+        strategy_params: None,
+        constraints: Vec::new(),
+        selection: Default::default(),
+        cost_budget: None,
+        observation_noise: None,
+        max_restarts: 0,
+        non_finite: Default::default(),
+        repeats: 0,
+        repeat_reducer: Default::default(),
+        objective_transform: None,
+        fixed: Default::default(),
+        target_value: None,
+        target_tol: 0.0,
+        history_cap: None,
+        invalid_params: Default::default(),
+        infeasibility_penalty: 0.0,
+        initial_points: Vec::new(),
+    })
+}
+
+fn infer_bounds_command(
+    data: &Path,
+    out: Option<&PathBuf>,
+    margin: f64,
+    value_column: &str,
+    budget: u64,
+) -> Result<()> {
+    tracing::info!(command = "infer-bounds", data = %data.display());
+    let file = fs::File::open(data)
+        .into_diagnostic()
+        .with_context(|| format!("Failed to read CSV file {}", data.display()))?;
+    let config = infer_config(file, margin, value_column, budget)
+        .with_context(|| format!("Invalid data in {}", data.display()))?;
+    validate_config(&config)?;
+    let format = out
+        .map(|path| DataFormat::from_path(path))
+        .unwrap_or_default();
+    write_formatted(out, &config, format)
+}
+
+/// One line of an adaptation audit log: the fields of the hot path's
+/// `AuditEvent`, plus the proposed delta on `Proposal` events.
+#[derive(Debug, Deserialize)]
+struct AuditRecord {
+    event_type: String,
+    #[serde(default)]
+    timestamp_us: u64,
+    #[serde(default)]
+    proposal_id: Option<u64>,
+    #[serde(default)]
+    config_version: u64,
+    #[serde(default)]
+    payload: String,
+    #[serde(default)]
+    delta: Option<Vec<f64>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum ProposalOutcome {
+    Applied,
+    Rejected,
+    /// Proposed, but no `Apply` or `Rollback` was logged for it.
+    Pending,
+}
+
+impl ProposalOutcome {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Applied => "applied",
+            Self::Rejected => "rejected",
+            Self::Pending => "pending",
+        }
+    }
+}
+
+/// One row of the `audit` timeline: a proposal with its outcome, or an
+/// event not tied to a proposal (SafeMode, homeostasis, rollback to best).
+#[derive(Debug, PartialEq, Serialize)]
+struct AuditEntry {
+    timestamp_us: u64,
+    /// `"Proposal"` for proposal rows, else the event type.
+    event: String,
+    proposal_id: Option<u64>,
+    outcome: Option<ProposalOutcome>,
+    delta: Option<Vec<f64>>,
+    /// Config generation after the outcome, or at the event.
+    generation: u64,
+    /// Rejection reason or event payload.
+    detail: String,
+}
+
+/// Parse a JSONL audit log. Blank lines are skipped.
+fn parse_audit_log<R: BufRead>(log: R) -> Result<Vec<AuditRecord>> {
+    let mut records = Vec::new();
+    for (line_no, line) in log.lines().enumerate() {
+        let line = line.into_diagnostic()?;
+        if line.trim().is_empty() {
+            continue;
         }
+        let record = serde_json::from_str(&line)
+            .into_diagnostic()
+            .with_context(|| format!("Line {}: invalid audit event", line_no + 1))?;
+        records.push(record);
+    }
+    Ok(records)
+}
+
+/// Pair `Proposal` events with their `Apply`/`Rollback` by `proposal_id`.
+///
+/// Events are ordered by timestamp first, so the log may be interleaved or
+/// out of order; the latest outcome of a proposal wins. Digests are dropped.
+fn audit_timeline(mut records: Vec<AuditRecord>) -> Vec<AuditEntry> {
+    records.sort_by_key(|r| r.timestamp_us);
+    let mut entries: Vec<AuditEntry> = Vec::new();
+    let mut by_proposal: HashMap<u64, usize> = HashMap::new();
+
+    for record in records {
+        let paired = matches!(
+            record.event_type.as_str(),
+            "Proposal" | "Apply" | "Rollback"
+        );
+        let id = match record.proposal_id {
+            Some(id) if paired => id,
+            _ => {
+                if record.event_type != "Digest" {
+                    entries.push(AuditEntry {
+                        timestamp_us: record.timestamp_us,
+                        event: record.event_type,
+                        proposal_id: record.proposal_id,
+                        outcome: None,
+                        delta: None,
+                        generation: record.config_version,
+                        detail: record.payload,
+                    });
+                }
+                continue;
+            }
+        };
+        let index = *by_proposal.entry(id).or_insert_with(|| {
+            entries.push(AuditEntry {
+                timestamp_us: record.timestamp_us,
+                event: "Proposal".to_string(),
+                proposal_id: Some(id),
+                outcome: Some(ProposalOutcome::Pending),
+                delta: None,
+                generation: record.config_version,
+                detail: String::new(),
+            });
+            entries.len() - 1
+        });
+        let entry = &mut entries[index];
+        entry.timestamp_us = entry.timestamp_us.min(record.timestamp_us);
+        match record.event_type.as_str() {
+            "Proposal" => {
+                if record.delta.is_some() {
+                    entry.delta = record.delta;
+                }
+            }
+            outcome => {
+                entry.outcome = Some(if outcome == "Apply" {
+                    ProposalOutcome::Applied
+                } else {
+                    ProposalOutcome::Rejected
+                });
+                entry.generation = record.config_version;
+                entry.detail = record.payload;
+            }
+        }
+    }
+
+    entries.sort_by_key(|e| e.timestamp_us);
+    entries
+}
+
+fn format_delta(delta: Option<&Vec<f64>>) -> String {
+    match delta {
+        Some(delta) => format!(
+            "[{}]",
+            delta
+                .iter()
+                .map(|d| format!("{:+.4}", d))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        None => "-".to_string(),
+    }
+}
+
+/// Human-readable timeline with a per-outcome summary.
+fn format_audit_text(entries: &[AuditEntry], color: bool) -> String {
+    use crossterm::style::Stylize;
+
+    let mut out = format!(
+        "{:>14}  {:>8}  {:<16}  {:>4}  delta / detail\n",
+        "time_us", "proposal", "outcome", "gen"
+    );
+    let mut counts = [0usize; 3];
+    for entry in entries {
+        let id = entry
+            .proposal_id
+            .map(|id| format!("#{}", id))
+            .unwrap_or_default();
+        let label = format!(
+            "{:<16}",
+            entry
+                .outcome
+                .map(ProposalOutcome::label)
+                .unwrap_or(entry.event.as_str())
+        );
+        let label = match (color, entry.outcome) {
+            (false, _) => label,
+            (true, Some(ProposalOutcome::Applied)) => label.green().to_string(),
+            (true, Some(ProposalOutcome::Rejected)) => label.red().to_string(),
+            (true, Some(ProposalOutcome::Pending)) => label.yellow().to_string(),
+            (true, None) => label.cyan().to_string(),
+        };
+        let mut detail = match entry.outcome {
+            Some(_) => format_delta(entry.delta.as_ref()),
+            None => String::new(),
+        };
+        if !entry.detail.is_empty() {
+            if !detail.is_empty() {
+                detail.push_str("  ");
+            }
+            detail.push_str(&entry.detail);
+        }
+        out.push_str(&format!(
+            "{:>14}  {:>8}  {}  {:>4}  {}\n",
+            entry.timestamp_us, id, label, entry.generation, detail
+        ));
+        if let Some(outcome) = entry.outcome {
+            counts[outcome as usize] += 1;
+        }
+    }
+    out.push_str(&format!(
+        "{} proposals: {} applied, {} rejected, {} pending\n",
+        counts.iter().sum::<usize>(),
+        counts[ProposalOutcome::Applied as usize],
+        counts[ProposalOutcome::Rejected as usize],
+        counts[ProposalOutcome::Pending as usize],
+    ));
+    out
+}
+
+/// Timeline as CSV; delta components are joined with `;`.
+fn format_audit_csv(entries: &[AuditEntry]) -> Result<String> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer
+        .write_record([
+            "timestamp_us",
+            "event",
+            "proposal_id",
+            "outcome",
+            "delta",
+            "generation",
+            "detail",
+        ])
+        .into_diagnostic()?;
+    for entry in entries {
+        writer
+            .write_record([
+                entry.timestamp_us.to_string(),
+                entry.event.clone(),
+                entry
+                    .proposal_id
+                    .map(|id| id.to_string())
+                    .unwrap_or_default(),
+                entry
+                    .outcome
+                    .map(|o| o.label().to_string())
+                    .unwrap_or_default(),
+                entry
+                    .delta
+                    .as_ref()
+                    .map(|d| {
+                        d.iter()
+                            .map(|v| v.to_string())
+                            .collect::<Vec<_>>()
+                            .join(";")
+                    })
+                    .unwrap_or_default(),
+                entry.generation.to_string(),
+                entry.detail.clone(),
+            ])
+            .into_diagnostic()?;
+    }
+    String::from_utf8(writer.into_inner().into_diagnostic()?).into_diagnostic()
+}
+
+fn audit_command(log_path: &Path, format: AuditFormat) -> Result<()> {
+    use std::io::IsTerminal;
+
+    tracing::info!(command = "audit", log = %log_path.display());
+    let file = fs::File::open(log_path)
+        .into_diagnostic()
+        .with_context(|| format!("Failed to read audit log {}", log_path.display()))?;
+    let records = parse_audit_log(io::BufReader::new(file))
+        .with_context(|| format!("Invalid audit log {}", log_path.display()))?;
+    let entries = audit_timeline(records);
+    match format {
+        AuditFormat::Text => print!(
+            "{}",
+            format_audit_text(&entries, io::stdout().is_terminal())
+        ),
+        AuditFormat::Csv => print!("{}", format_audit_csv(&entries)?),
+        AuditFormat::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(&entries).into_diagnostic()?
+        ),
+    }
+    Ok(())
+}
+
+fn validate_command(config_path: &Path, format: Option<DataFormat>) -> Result<()> {
+    tracing::info!(command = "validate", config = %config_path.display());
+    let config = load_config(config_path)?;
+    validate_config(&config)?;
+    match format {
+        Some(format) => println!("{}", format.render(&config)?),
+        None => println!("Config OK"),
+    }
+    Ok(())
+}
+
+/// How urgent a `doctor` finding is; findings are listed in this order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Severity {
+    /// The run cannot start or will fail.
+    Error,
+    /// The run works but likely not as intended.
+    Warning,
+    /// Not a problem; reported for context.
+    Info,
+}
+
+impl Severity {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::Warning => "warning",
+            Self::Info => "info",
+        }
+    }
+}
+
+/// One finding of `doctor`: which check raised it, what is wrong and how to
+/// fix it.
+#[derive(Debug, Clone, PartialEq)]
+struct Diagnosis {
+    severity: Severity,
+    check: &'static str,
+    problem: String,
+    fix: String,
+}
+
+impl Diagnosis {
+    fn new(
+        severity: Severity,
+        check: &'static str,
+        problem: impl Into<String>,
+        fix: impl Into<String>,
+    ) -> Self {
+        Self {
+            severity,
+            check,
+            problem: problem.into(),
+            fix: fix.into(),
+        }
+    }
+}
+
+fn doctor_command(config_path: &Path, script: Option<&Path>, state: Option<&Path>) -> Result<()> {
+    tracing::info!(command = "doctor", config = %config_path.display());
+    let diagnoses = diagnose(config_path, script, state);
+    print!("{}", format_diagnoses(&diagnoses));
+    let errors = diagnoses
+        .iter()
+        .filter(|d| d.severity == Severity::Error)
+        .count();
+    if errors > 0 {
+        return Err(miette::miette!("doctor found {} error(s)", errors));
+    }
+    Ok(())
+}
+
+/// Run every check that applies, most severe findings first.
+///
+/// Checks that need a usable config are skipped when it cannot be read or
+/// does not validate.
+fn diagnose(config_path: &Path, script: Option<&Path>, state: Option<&Path>) -> Vec<Diagnosis> {
+    let mut diagnoses = Vec::new();
+    match parse_config(config_path) {
+        Err(err) => diagnoses.push(Diagnosis::new(
+            Severity::Error,
+            "config",
+            error_chain(&err),
+            "fix the file so it parses as a solver config (see `arqonhpo validate`)",
+        )),
+        Ok(config) => match validate_config(&config) {
+            Err(err) => diagnoses.push(Diagnosis::new(
+                Severity::Error,
+                "config",
+                err.to_string(),
+                "correct the field named in the error",
+            )),
+            Ok(()) => {
+                diagnoses.extend(diagnose_budget(&config));
+                if let Some(script) = script {
+                    diagnoses.extend(diagnose_script(&config, script));
+                }
+                if let Some(state) = state {
+                    diagnoses.extend(diagnose_state(&config, state));
+                }
+            }
+        },
+    }
+    diagnoses.sort_by_key(|d| d.severity);
+    diagnoses
+}
+
+/// Whether the evaluation budget leaves room to refine after probing.
+fn diagnose_budget(config: &SolverConfig) -> Vec<Diagnosis> {
+    let plan = Solver::pcr(config.clone()).plan();
+    let dim = config.bounds.len();
+    let Some(refine) = plan.refine_budget else {
+        return Vec::new();
+    };
+    let suggested = plan.probe_points + 10 * (dim + 1);
+    if refine == 0 {
+        vec![Diagnosis::new(
+            Severity::Error,
+            "budget",
+            format!(
+                "budget {} is spent entirely on {} probe points; nothing is left to refine",
+                plan.budget, plan.probe_points
+            ),
+            format!(
+                "raise budget to at least {} or lower probe_ratio",
+                suggested
+            ),
+        )]
+    } else if refine < 2 * (dim + 1) {
+        vec![Diagnosis::new(
+            Severity::Warning,
+            "budget",
+            format!(
+                "{} evaluations are left to refine {} parameters after {} probe points; a simplex alone needs {}",
+                refine,
+                dim,
+                plan.probe_points,
+                dim + 1
+            ),
+            format!("raise budget to about {}", suggested),
+        )]
+    } else {
+        Vec::new()
+    }
+}
+
+/// Evaluate `script` once at the midpoint of the bounds and report what it
+/// printed and whether it parsed.
+fn diagnose_script(config: &SolverConfig, script: &Path) -> Vec<Diagnosis> {
+    if let Err(err) = check_script_env(config) {
+        return vec![Diagnosis::new(
+            Severity::Error,
+            "script",
+            err.to_string(),
+            "rename the parameters so each gets its own ARQON_ variable",
+        )];
+    }
+    if !script.is_file() {
+        return vec![Diagnosis::new(
+            Severity::Error,
+            "script",
+            format!("{} does not exist or is not a file", script.display()),
+            "pass the path of the evaluation script with --script",
+        )];
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let executable = fs::metadata(script).is_ok_and(|m| m.permissions().mode() & 0o111 != 0);
+        if !executable {
+            return vec![Diagnosis::new(
+                Severity::Error,
+                "script",
+                format!("{} is not executable", script.display()),
+                format!("chmod +x {}", script.display()),
+            )];
+        }
+    }
+
+    let midpoint: HashMap<String, f64> = config
+        .bounds
+        .iter()
+        .map(|(name, domain)| (name.clone(), domain.from_unit(0.5)))
+        .collect();
+    let params = config.with_fixed(config.active_params(midpoint));
+    let stdout = match script_output(script, &params) {
+        Ok(stdout) => stdout,
+        Err(err) => {
+            return vec![Diagnosis::new(
+                Severity::Error,
+                "script",
+                format!("evaluation at the midpoint failed: {}", err),
+                "run the script by hand with the ARQON_ variables set and fix the error",
+            )]
+        }
+    };
+    let printed = if stdout.trim().is_empty() {
+        "nothing".to_string()
+    } else {
+        format!("{:?}", stdout.trim())
+    };
+    let mut diagnoses = Vec::new();
+    match parse_result(&stdout) {
+        Err(err) => diagnoses.push(Diagnosis::new(
+            Severity::Error,
+            "script",
+            format!(
+                "printed {}, which does not parse: {}",
+                printed,
+                error_chain(&err)
+            ),
+            "print the objective as the last line of output, e.g. `echo \"RESULT=0.12\"`",
+        )),
+        Ok(value) if !value.is_finite() => diagnoses.push(Diagnosis::new(
+            Severity::Warning,
+            "script",
+            format!("printed {}, which parsed as {}", printed, value),
+            "make the objective finite, or set non_finite to decide how such results are recorded",
+        )),
+        Ok(value) => diagnoses.push(Diagnosis::new(
+            Severity::Info,
+            "script",
+            format!("printed {}, which parsed as {}", printed, value),
+            "nothing to fix",
+        )),
+    }
+    if let Err(err) = parse_feasibility(&stdout) {
+        diagnoses.push(Diagnosis::new(
+            Severity::Error,
+            "script",
+            err.to_string(),
+            "print FEASIBILITY= with a number in [0, 1], or leave it out",
+        ));
+    }
+    diagnoses
+}
+
+/// Check a state file against the config a run would be started with.
+fn diagnose_state(config: &SolverConfig, path: &Path) -> Vec<Diagnosis> {
+    if !path.exists() {
+        return vec![Diagnosis::new(
+            Severity::Info,
+            "state",
+            format!("{} does not exist yet", path.display()),
+            "nothing to fix; `run --state` creates it",
+        )];
+    }
+    let state = match load_state(path) {
+        Ok(state) => state,
+        Err(err) => {
+            return vec![Diagnosis::new(
+                Severity::Error,
+                "state",
+                error_chain(&err),
+                "restore the state file from a backup or remove it to start over",
+            )]
+        }
+    };
+    let mut diagnoses = Vec::new();
+    let differing = differing_fields(config, &state.config);
+    if !differing.is_empty() {
+        diagnoses.push(Diagnosis::new(
+            Severity::Warning,
+            "state",
+            format!(
+                "the state's config differs from the given config in: {}; a run resuming this state uses the state's",
+                differing.join(", ")
+            ),
+            "to start over with the new config, remove the state file; to continue with it, use `truncate --config`",
+        ));
+    }
+    let invalid = state
+        .history
+        .iter()
+        .filter(|point| config.invalid_param(&point.params).is_some())
+        .count();
+    if invalid > 0 {
+        diagnoses.push(Diagnosis::new(
+            Severity::Warning,
+            "state",
+            format!(
+                "{} of {} evaluations in the state have params unknown to or outside the config's bounds",
+                invalid,
+                state.history.len()
+            ),
+            "widen bounds to cover them, or set invalid_params to drop or clamp them",
+        ));
+    }
+    if config.budget > 0 && state.history.len() as u64 >= config.budget {
+        diagnoses.push(Diagnosis::new(
+            Severity::Warning,
+            "state",
+            format!(
+                "the state already holds {} evaluations, the whole budget of {}",
+                state.history.len(),
+                config.budget
+            ),
+            "raise budget to continue the run",
+        ));
+    }
+    diagnoses
+}
+
+/// `err` with its causes, on one line.
+fn error_chain(err: &miette::Report) -> String {
+    err.chain()
+        .map(|cause| cause.to_string())
+        .collect::<Vec<_>>()
+        .join(": ")
+}
+
+/// Top-level config fields whose values differ between `a` and `b`.
+fn differing_fields(a: &SolverConfig, b: &SolverConfig) -> Vec<String> {
+    let (Ok(serde_json::Value::Object(a)), Ok(serde_json::Value::Object(b))) =
+        (serde_json::to_value(a), serde_json::to_value(b))
+    else {
+        return Vec::new();
+    };
+    let keys: BTreeSet<&String> = a.keys().chain(b.keys()).collect();
+    keys.into_iter()
+        .filter(|key| a.get(*key) != b.get(*key))
+        .cloned()
+        .collect()
+}
+
+fn format_diagnoses(diagnoses: &[Diagnosis]) -> String {
+    let mut out = String::new();
+    for d in diagnoses {
+        out.push_str(&format!(
+            "{:<7} [{}] {}\n",
+            d.severity.name(),
+            d.check,
+            d.problem
+        ));
+        if d.severity != Severity::Info {
+            out.push_str(&format!("        fix: {}\n", d.fix));
+        }
+    }
+    if diagnoses.iter().all(|d| d.severity == Severity::Info) {
+        out.push_str("No problems found\n");
+    }
+    out
+}
+
+fn load_state_or_config(config_path: &Path, state_path: Option<&PathBuf>) -> Result<LoadedState> {
+    let config = load_config(config_path)?;
+    if let Some(path) = state_path {
+        if path.exists() {
+            let state = load_state(path)?;
+            return Ok(LoadedState {
+                config: state.config,
+                history: state.history,
+                run_id: state.run_id,
+                warm_start: state.warm_start,
+                compacted: state.compacted,
+            });
+        }
+    }
+    Ok(LoadedState {
+        config,
+        history: Vec::new(),
+        run_id: None,
+        warm_start: false,
+        compacted: CompactedHistory::default(),
+    })
+}
+
+fn seed_solver(
+    solver: &mut Solver,
+    history: Vec<SeedPoint>,
+    warm_start: bool,
+    compacted: CompactedHistory,
+) -> Result<()> {
+    solver.restore_compacted(compacted);
+    if history.is_empty() {
+        return Ok(());
+    }
+    if !warm_start {
+        return solver.seed(history).into_diagnostic();
+    }
+    let imported = history.len();
+    if !solver.warm_start(history).into_diagnostic()? {
+        tracing::warn!(
+            imported,
+            "warm start needs history covering the probe budget; probing as usual"
+        );
+    }
+    if solver.budget_exhausted() {
+        tracing::warn!(
+            imported,
+            budget = solver.config.budget,
+            "imported history already exhausts the budget"
+        );
+    }
+    Ok(())
+}
+
+fn load_state(path: &Path) -> Result<SolverState> {
+    let contents = fs::read_to_string(path)
+        .into_diagnostic()
+        .with_context(|| format!("Failed to read state file {}", path.display()))?;
+    let state: SolverState = serde_json::from_str(&contents)
+        .into_diagnostic()
+        .with_context(|| format!("Invalid state JSON in {}", path.display()))?;
+    Ok(state)
+}
+
+fn save_state(path: &Path, state: &SolverState) -> Result<()> {
+    let data = serde_json::to_string_pretty(state).into_diagnostic()?;
+    fs::write(path, data)
+        .into_diagnostic()
+        .with_context(|| format!("Failed to write state file {}", path.display()))?;
+    Ok(())
+}
+
+fn load_config(path: &Path) -> Result<SolverConfig> {
+    let config = parse_config(path)?;
+    validate_config(&config)?;
+    Ok(config)
+}
+
+/// Read a config file without validating it.
+fn parse_config(path: &Path) -> Result<SolverConfig> {
+    let contents = fs::read_to_string(path)
+        .into_diagnostic()
+        .with_context(|| format!("Failed to read config file {}", path.display()))?;
+    let format = DataFormat::from_path(path);
+    format
+        .parse(&contents)
+        .with_context(|| format!("Invalid config {} in {}", format.name(), path.display()))
+}
+
+fn validate_config(config: &SolverConfig) -> Result<()> {
+    config
+        .check_budget()
+        .map_err(|err| miette::miette!("{}", err))?;
+    if let Some(sigma) = config.observation_noise {
+        if !(sigma.is_finite() && sigma >= 0.0) {
+            return Err(miette::miette!(
+                "observation_noise must be a non-negative number"
+            ));
+        }
+    }
+    if let NonFinitePolicy::Penalize { value } = config.non_finite {
+        if !value.is_finite() {
+            return Err(miette::miette!(
+                "non_finite penalty must be a finite number"
+            ));
+        }
+    }
+    if let Some(ObjectiveTransform::BoxCox { lambda }) = config.objective_transform {
+        if !lambda.is_finite() {
+            return Err(miette::miette!(
+                "objective_transform BoxCox lambda must be a finite number"
+            ));
+        }
+    }
+    if config.bounds.is_empty() {
+        return Err(miette::miette!("bounds must not be empty"));
+    }
+    for (name, domain) in &config.bounds {
+        if RESERVED_KEYS.contains(&name.as_str()) {
+            return Err(miette::miette!(
+                "bounds name {} is reserved for trace metadata",
+                name
+            ));
+        }
+        if domain.min >= domain.max {
+            return Err(miette::miette!(
+                "bounds for {} must satisfy min < max",
+                name
+            ));
+        }
+        if matches!(domain.scale, arqonhpo_core::config::Scale::Log)
+            && (domain.min <= 0.0 || domain.max <= 0.0)
+        {
+            return Err(miette::miette!("log scale bounds for {} must be > 0", name));
+        }
+    }
+    config
+        .check_conditions()
+        .map_err(|err| miette::miette!("{}", err))?;
+    config
+        .check_fixed()
+        .map_err(|err| miette::miette!("{}", err))?;
+    config
+        .check_history_cap()
+        .map_err(|err| miette::miette!("{}", err))?;
+    config
+        .check_initial_points()
+        .map_err(|err| miette::miette!("{}", err))?;
+    StrategyKind::check(config).map_err(|err| miette::miette!("{}", err))?;
+    TPE::check(config).map_err(|err| miette::miette!("{}", err))?;
+    NelderMead::check(config).map_err(|err| miette::miette!("{}", err))?;
+    HalvingConfig::from_config(config).map_err(|err| miette::miette!("{}", err))?;
+    if config
+        .target_value
+        .is_some_and(|target| !target.is_finite())
+    {
+        return Err(miette::miette!("target_value must be finite"));
+    }
+    if !config.target_tol.is_finite() || config.target_tol < 0.0 {
+        return Err(miette::miette!("target_tol must be finite and >= 0"));
+    }
+    if !config.infeasibility_penalty.is_finite() || config.infeasibility_penalty < 0.0 {
+        return Err(miette::miette!(
+            "infeasibility_penalty must be finite and >= 0"
+        ));
+    }
+    for constraint in &config.constraints {
+        constraint
+            .validate(config.bounds.keys())
+            .map_err(|err| miette::miette!("{}", err))?;
+    }
+    Ok(())
+}
+
+fn read_input(path: Option<&PathBuf>) -> Result<String> {
+    if let Some(path) = path {
+        return fs::read_to_string(path)
+            .into_diagnostic()
+            .with_context(|| format!("Failed to read results file {}", path.display()));
+    }
+
+    let mut buffer = String::new();
+    io::stdin().read_to_string(&mut buffer).into_diagnostic()?;
+    Ok(buffer)
+}
+
+/// Results for `tell`, in the format implied by the file's extension (JSON
+/// on stdin).
+/// Results for `tell`, as a list or keyed (in TOML, a top-level `results`
+/// array, or `batch_id` with a `results` table).
+fn read_results(path: Option<&PathBuf>) -> Result<TellInput> {
+    let contents = read_input(path)?;
+    let format = path.map_or(DataFormat::Json, |path| DataFormat::from_path(path));
+    // Pick the shape by the `batch_id` key alone, then parse for it, so a
+    // malformed file reports what is wrong with it as that shape.
+    let keyed = format
+        .parse::<serde_json::Value>(&contents)?
+        .get("batch_id")
+        .is_some();
+    Ok(match format {
+        _ if keyed => TellInput::Keyed(format.parse(&contents)?),
+        DataFormat::Toml => TellInput::Points(format.parse::<TomlResults>(&contents)?.results),
+        format => TellInput::Points(format.parse(&contents)?),
+    })
+}
+
+/// Environment variable carrying the fidelity of a successive-halving candidate.
+const FIDELITY_ENV: &str = "ARQON_FIDELITY";
+
+/// Environment variable carrying parameter `key` for the evaluation script:
+/// `ARQON_` followed by the name, with every character other than ASCII
+/// letters, digits and `_` replaced by `_` (`lr.decay` → `ARQON_lr_decay`).
+fn script_env_name(key: &str) -> String {
+    if key == FIDELITY_KEY {
+        return FIDELITY_ENV.to_string();
+    }
+    let sanitized: String = key
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    format!("ARQON_{}", sanitized)
+}
+
+/// Environment variable of every parameter the script receives, by name.
+///
+/// Fails if two parameters share a variable, or their variables differ only
+/// in case (they would collide on Windows), or one takes `ARQON_FIDELITY`.
+fn script_env_names(config: &SolverConfig) -> Result<BTreeMap<String, String>> {
+    let names: BTreeMap<String, String> = config
+        .bounds
+        .keys()
+        .chain(config.fixed.keys())
+        .map(|key| (key.clone(), script_env_name(key)))
+        .collect();
+    let mut taken: HashMap<String, &str> =
+        HashMap::from([(FIDELITY_ENV.to_ascii_uppercase(), FIDELITY_KEY)]);
+    for (key, env) in &names {
+        if let Some(other) = taken.insert(env.to_ascii_uppercase(), key) {
+            return Err(miette::miette!(
+                "parameters '{}' and '{}' both map to script environment variable {}",
+                other,
+                key,
+                env
+            ));
+        }
+    }
+    Ok(names)
+}
+
+/// Check that every parameter gets its own script environment variable, and
+/// log the ones whose variable name differs from the parameter name.
+fn check_script_env(config: &SolverConfig) -> Result<()> {
+    for (param, env) in script_env_names(config)? {
+        if env != format!("ARQON_{}", param) {
+            tracing::warn!(
+                param,
+                env,
+                "parameter name sanitized for the script environment"
+            );
+        }
+    }
+    Ok(())
+}
+
+fn evaluate_script(script: &Path, params: &HashMap<String, f64>) -> Result<f64> {
+    parse_result(&script_output(script, params)?)
+}
+
+/// Run `script` with `params` in its environment and return its stdout.
+/// A non-zero exit status is an error carrying the script's stderr.
+fn script_output(script: &Path, params: &HashMap<String, f64>) -> Result<String> {
+    let mut command = Command::new(script);
+    let mut keys: Vec<_> = params.keys().collect();
+    keys.sort();
+    for key in keys {
+        command.env(script_env_name(key), params[key].to_string());
+    }
+
+    // Keep the script out of the terminal's process group so a Ctrl-C aimed
+    // at `run` lets the in-flight evaluation finish.
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut command, 0);
+
+    let output = command.output().into_diagnostic()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(miette::miette!(
+            "Script failed with status {}: {}",
+            output.status,
+            stderr.trim()
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Script output line prefix reporting how close an infeasible evaluation
+/// came to feasibility (see `EvalTrace::feasibility`).
+const FEASIBILITY_PREFIX: &str = "FEASIBILITY=";
+
+fn parse_result(stdout: &str) -> Result<f64> {
+    let mut last_value: Option<&str> = None;
+    for line in stdout.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with(FEASIBILITY_PREFIX) {
+            continue;
+        }
+        if let Some(rest) = trimmed.strip_prefix("RESULT=") {
+            last_value = Some(rest.trim());
+        } else {
+            last_value = Some(trimmed);
+        }
+    }
+
+    let value = last_value.ok_or_else(|| miette::miette!("No RESULT found in script output"))?;
+    value
+        .parse::<f64>()
+        .into_diagnostic()
+        .with_context(|| format!("Failed to parse result '{}'", value))
+}
+
+/// The last `FEASIBILITY=` value in a script's output, if any. It must lie
+/// in [0, 1].
+fn parse_feasibility(stdout: &str) -> Result<Option<f64>> {
+    let Some(value) = stdout
+        .lines()
+        .rev()
+        .find_map(|line| line.trim().strip_prefix(FEASIBILITY_PREFIX))
+    else {
+        return Ok(None);
+    };
+    let feasibility = value
+        .trim()
+        .parse::<f64>()
+        .into_diagnostic()
+        .with_context(|| format!("Failed to parse feasibility '{}'", value.trim()))?;
+    if !(0.0..=1.0).contains(&feasibility) {
+        return Err(miette::miette!(
+            "feasibility must be in [0, 1], got {}",
+            feasibility
+        ));
+    }
+    Ok(Some(feasibility))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arqonhpo_core::config::{ProbeBudget, Scale};
+
+    fn tui_state(values: &[(f64, f64)]) -> SolverState {
+        SolverState {
+            config: create_test_config(),
+            history: values
+                .iter()
+                .map(|&(x, value)| SeedPoint {
+                    params: HashMap::from([("x".to_string(), x)]),
+                    value,
+                    cost: 1.0,
+                    penalized: false,
+                    failed: false,
+                    feasibility: None,
+                })
+                .collect(),
+            run_id: None,
+            warm_start: false,
+            compacted: Default::default(),
+            suggest: None,
+            metrics: None,
+        }
+    }
+
+    #[test]
+    fn test_best_so_far_is_running_minimum() {
+        let state = tui_state(&[(0.1, 3.0), (0.2, 1.0), (0.3, 2.0)]);
+        assert_eq!(
+            best_so_far(&state),
+            vec![(1.0, 3.0), (2.0, 1.0), (3.0, 1.0)]
+        );
+        assert!(best_so_far(&tui_state(&[])).is_empty());
+
+        // A negated objective is maximized, so its best is the running maximum.
+        let mut state = state;
+        state.config.objective_transform = Some(ObjectiveTransform::Negate);
+        assert_eq!(
+            best_so_far(&state),
+            vec![(1.0, 3.0), (2.0, 3.0), (3.0, 3.0)]
+        );
+        assert_eq!(state.best().unwrap().value, 3.0);
+    }
+
+    #[test]
+    fn test_best_point_positions() {
+        let state = tui_state(&[(0.1, 3.0), (0.75, 1.0)]);
+        let x = &state.config.bounds["x"];
+        assert_eq!(
+            best_point_positions(&state),
+            vec![("x".to_string(), x.to_unit(0.75))]
+        );
+        assert!(best_point_positions(&tui_state(&[])).is_empty());
+    }
+
+    #[test]
+    fn test_draw_tui_handles_empty_and_single_point() {
+        use ratatui::backend::TestBackend;
+
+        for values in [&[][..], &[(0.5, 1.0)][..], &[(0.1, 2.0), (0.9, 0.5)][..]] {
+            let state = tui_state(values);
+            let mut terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+            terminal
+                .draw(|frame| draw_tui(frame, Some(&state), &[]))
+                .unwrap();
+            let rendered: String = terminal
+                .backend()
+                .buffer()
+                .content()
+                .iter()
+                .map(|cell| cell.symbol())
+                .collect();
+            assert!(rendered.contains("Best So Far"));
+            if values.is_empty() {
+                assert!(rendered.contains("No best point yet"));
+            } else {
+                assert!(rendered.contains("x ["));
+            }
+        }
+        let mut terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+        terminal.draw(|frame| draw_tui(frame, None, &[])).unwrap();
+    }
+
+    #[test]
+    fn test_format_params_empty() {
+        let params: HashMap<String, f64> = HashMap::new();
+        assert_eq!(format_params(&params), "");
+    }
+
+    #[test]
+    fn test_format_params_single() {
+        let mut params = HashMap::new();
+        params.insert("alpha".to_string(), 0.1234);
+        assert_eq!(format_params(&params), "alpha=0.1234");
+    }
+
+    #[test]
+    fn test_format_params_multiple_sorted() {
+        let mut params = HashMap::new();
+        params.insert("z".to_string(), 1.0);
+        params.insert("a".to_string(), 2.0);
+        params.insert("m".to_string(), 3.0);
+        let result = format_params(&params);
+        assert!(result.starts_with("a="));
+        assert!(result.contains("m="));
+        assert!(result.ends_with("z=1.0000"));
+    }
+
+    #[test]
+    fn test_format_params_hides_reserved_keys() {
+        let params = HashMap::from([("x".to_string(), 0.5), (FIDELITY_KEY.to_string(), 0.5)]);
+        assert_eq!(format_params(&params), "x=0.5000");
+    }
+
+    #[test]
+    fn test_format_event_line_valid_json() {
+        let line = r#"{"event":"update","timestamp_us":1234567890,"value":0.5}"#;
+        let result = format_event_line(line);
+        assert!(result.is_some());
+        let formatted = result.unwrap();
+        assert!(formatted.contains("1234567890"));
+        assert!(formatted.contains("update"));
+        assert!(formatted.contains("value=0.500000"));
+    }
+
+    #[test]
+    fn test_format_event_line_invalid_json() {
+        let line = "not valid json";
+        let result = format_event_line(line);
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_format_event_line_minimal() {
+        let line = r#"{}"#;
+        let result = format_event_line(line);
+        assert!(result.is_some());
+        let formatted = result.unwrap();
+        assert!(formatted.contains("event"));
+    }
+
+    #[test]
+    fn test_generate_run_id() {
+        let run_id = generate_run_id("test");
+        assert!(run_id.starts_with("test-"));
+        assert!(run_id.len() > 5);
+    }
+
+    #[test]
+    fn test_split_query_with_query() {
+        let (path, query) = split_query("/api/data?key=value");
+        assert_eq!(path, "/api/data");
+        assert_eq!(query, Some("key=value"));
+    }
+
+    #[test]
+    fn test_split_query_without_query() {
+        let (path, query) = split_query("/api/data");
+        assert_eq!(path, "/api/data");
+        assert!(query.is_none());
+    }
+
+    #[test]
+    fn test_parse_query_empty() {
+        let params = parse_query(None);
+        assert!(params.is_empty());
+    }
+
+    #[test]
+    fn test_parse_query_single() {
+        let params = parse_query(Some("key=value"));
+        assert_eq!(params.get("key"), Some(&"value".to_string()));
+    }
+
+    #[test]
+    fn test_parse_query_multiple() {
+        let params = parse_query(Some("a=1&b=2&c=3"));
+        assert_eq!(params.len(), 3);
+        assert_eq!(params.get("a"), Some(&"1".to_string()));
+        assert_eq!(params.get("b"), Some(&"2".to_string()));
+        assert_eq!(params.get("c"), Some(&"3".to_string()));
+    }
+
+    #[test]
+    fn test_parse_query_empty_key() {
+        let params = parse_query(Some("=value&key=val"));
+        assert_eq!(params.len(), 1);
+        assert_eq!(params.get("key"), Some(&"val".to_string()));
+    }
+
+    #[test]
+    fn test_parse_result_simple() {
+        let result = parse_result("0.5");
+        assert!(result.is_ok());
+        assert!((result.unwrap() - 0.5).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_parse_result_with_prefix() {
+        let result = parse_result("RESULT=0.75");
+        assert!(result.is_ok());
+        assert!((result.unwrap() - 0.75).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_parse_result_multiline() {
+        let output = "some output\nmore output\nRESULT=0.9\n";
+        let result = parse_result(output);
+        assert!(result.is_ok());
+        assert!((result.unwrap() - 0.9).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_parse_result_empty() {
+        let result = parse_result("");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_parse_result_invalid_number() {
+        let result = parse_result("not_a_number");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_validate_config_valid() {
+        let mut bounds = HashMap::new();
+        bounds.insert(
+            "x".to_string(),
+            arqonhpo_core::config::Domain {
+                min: 0.0,
+                max: 1.0,
+                scale: arqonhpo_core::config::Scale::Linear,
+                active_when: None,
+            },
+        );
+        let config = SolverConfig {
+            bounds,
+            budget: 10,
+            probe_ratio: ProbeBudget::Fixed(0.5),
+            seed: 42,
+            strategy_params: None,
+            constraints: Vec::new(),
+            selection: Default::default(),
+            cost_budget: None,
+            observation_noise: None,
+            max_restarts: 0,
+            non_finite: Default::default(),
+            repeats: 0,
+            repeat_reducer: Default::default(),
+            objective_transform: None,
+            fixed: Default::default(),
+            target_value: None,
+            target_tol: 0.0,
+            history_cap: None,
+            invalid_params: Default::default(),
+            infeasibility_penalty: 0.0,
+            initial_points: Vec::new(),
+        };
+        assert!(validate_config(&config).is_ok());
+    }
+
+    #[test]
+    fn test_validate_config_zero_budget() {
+        let mut bounds = HashMap::new();
+        bounds.insert(
+            "x".to_string(),
+            arqonhpo_core::config::Domain {
+                min: 0.0,
+                max: 1.0,
+                scale: arqonhpo_core::config::Scale::Linear,
+                active_when: None,
+            },
+        );
+        let config = SolverConfig {
+            bounds,
+            budget: 0,
+            probe_ratio: ProbeBudget::Fixed(0.5),
+            seed: 42,
+            strategy_params: None,
+            constraints: Vec::new(),
+            selection: Default::default(),
+            cost_budget: None,
+            observation_noise: None,
+            max_restarts: 0,
+            non_finite: Default::default(),
+            repeats: 0,
+            repeat_reducer: Default::default(),
+            objective_transform: None,
+            fixed: Default::default(),
+            target_value: None,
+            target_tol: 0.0,
+            history_cap: None,
+            invalid_params: Default::default(),
+            infeasibility_penalty: 0.0,
+            initial_points: Vec::new(),
+        };
+        let result = validate_config(&config);
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("budget"));
+    }
+
+    #[test]
+    fn test_validate_config_cost_budget_only() {
+        let config: SolverConfig = serde_json::from_value(serde_json::json!({
+            "seed": 42,
+            "cost_budget": 120.0,
+            "bounds": {"x": {"min": 0.0, "max": 1.0}}
+        }))
+        .unwrap();
+        assert_eq!(config.budget, 0);
+        assert!(validate_config(&config).is_ok());
+
+        let mut invalid = config.clone();
+        invalid.cost_budget = Some(-1.0);
+        let err = validate_config(&invalid).unwrap_err();
+        assert!(err.to_string().contains("cost_budget"));
+    }
+
+    #[test]
+    fn test_validate_config_observation_noise() {
+        let mut config = create_test_state().config;
+        config.observation_noise = Some(0.05);
+        assert!(validate_config(&config).is_ok());
+
+        config.observation_noise = Some(f64::NAN);
+        let err = validate_config(&config).unwrap_err();
+        assert!(err.to_string().contains("observation_noise"));
+    }
+
+    #[test]
+    fn test_validate_config_non_finite_penalty() {
+        let mut config = create_test_state().config;
+        config.non_finite = NonFinitePolicy::Penalize { value: 1e6 };
+        assert!(validate_config(&config).is_ok());
+
+        config.non_finite = NonFinitePolicy::Penalize {
+            value: f64::INFINITY,
+        };
+        let err = validate_config(&config).unwrap_err();
+        assert!(err.to_string().contains("non_finite"));
+    }
+
+    #[test]
+    fn test_validate_config_forced_strategy() {
+        let mut config = create_test_state().config;
+        force_strategy(&mut config, StrategyKind::Tpe);
+        assert!(validate_config(&config).is_ok());
+
+        config.strategy_params = Some(HashMap::from([("force".to_string(), 9.0)]));
+        let err = validate_config(&config).unwrap_err();
+        assert!(err.to_string().contains("force"));
+    }
+
+    #[test]
+    fn test_validate_config_tpe_split() {
+        let mut config = create_test_state().config;
+        config.strategy_params = Some(HashMap::from([
+            (TPE::QUANTILE_KEY.to_string(), 0.15),
+            (TPE::TRANSFORM_KEY.to_string(), 2.0),
+        ]));
+        assert!(validate_config(&config).is_ok());
+
+        config.strategy_params = Some(HashMap::from([(TPE::QUANTILE_KEY.to_string(), 1.5)]));
+        assert!(validate_config(&config).is_err());
+        config.strategy_params = Some(HashMap::from([(TPE::TRANSFORM_KEY.to_string(), 3.0)]));
+        let err = validate_config(&config).unwrap_err();
+        assert!(err.to_string().contains("tpe_transform"));
+    }
+
+    #[test]
+    fn test_validate_config_simplex_scale() {
+        let mut config = create_test_state().config;
+        config.strategy_params = Some(HashMap::from([("nm_simplex_scale".to_string(), 0.2)]));
+        assert!(validate_config(&config).is_ok());
+
+        config.strategy_params = Some(HashMap::from([("nm_simplex_scale.x".to_string(), 2.0)]));
+        let err = validate_config(&config).unwrap_err();
+        assert!(err.to_string().contains("nm_simplex_scale.x"));
+    }
+
+    #[test]
+    fn test_validate_config_history_cap() {
+        let mut config = create_test_state().config;
+        let min = 4 * (config.bounds.len() + 1);
+        config.history_cap = Some(min);
+        assert!(validate_config(&config).is_ok());
+        config.history_cap = Some(min - 1);
+        let err = validate_config(&config).unwrap_err();
+        assert!(err.to_string().contains("history_cap"));
+    }
+
+    #[test]
+    fn test_validate_config_reserved_bounds_name() {
+        let mut config = create_test_state().config;
+        let domain = config.bounds.values().next().unwrap().clone();
+        config.bounds.insert(FIDELITY_KEY.to_string(), domain);
+        let err = validate_config(&config).unwrap_err();
+        assert!(err.to_string().contains("reserved"));
+    }
+
+    #[test]
+    fn test_validate_config_empty_bounds() {
+        let config = SolverConfig {
+            bounds: HashMap::new(),
+            budget: 10,
+            probe_ratio: ProbeBudget::Fixed(0.5),
+            seed: 42,
+            strategy_params: None,
+            constraints: Vec::new(),
+            selection: Default::default(),
+            cost_budget: None,
+            observation_noise: None,
+            max_restarts: 0,
+            non_finite: Default::default(),
+            repeats: 0,
+            repeat_reducer: Default::default(),
+            objective_transform: None,
+            fixed: Default::default(),
+            target_value: None,
+            target_tol: 0.0,
+            history_cap: None,
+            invalid_params: Default::default(),
+            infeasibility_penalty: 0.0,
+            initial_points: Vec::new(),
+        };
+        let result = validate_config(&config);
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("bounds"));
     }
 
-    let value = last_value.ok_or_else(|| miette::miette!("No RESULT found in script output"))?;
-    value
-        .parse::<f64>()
-        .into_diagnostic()
-        .with_context(|| format!("Failed to parse result '{}'", value))
-}
+    #[test]
+    fn test_validate_config_invalid_bounds() {
+        let mut bounds = HashMap::new();
+        bounds.insert(
+            "x".to_string(),
+            arqonhpo_core::config::Domain {
+                min: 1.0,
+                max: 0.0,
+                scale: arqonhpo_core::config::Scale::Linear,
+                active_when: None,
+            }, // min > max
+        );
+        let config = SolverConfig {
+            bounds,
+            budget: 10,
+            probe_ratio: ProbeBudget::Fixed(0.5),
+            seed: 42,
+            strategy_params: None,
+            constraints: Vec::new(),
+            selection: Default::default(),
+            cost_budget: None,
+            observation_noise: None,
+            max_restarts: 0,
+            non_finite: Default::default(),
+            repeats: 0,
+            repeat_reducer: Default::default(),
+            objective_transform: None,
+            fixed: Default::default(),
+            target_value: None,
+            target_tol: 0.0,
+            history_cap: None,
+            invalid_params: Default::default(),
+            infeasibility_penalty: 0.0,
+            initial_points: Vec::new(),
+        };
+        let result = validate_config(&config);
+        assert!(result.is_err());
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn test_validate_config_unknown_constraint_param() {
+        let mut bounds = HashMap::new();
+        bounds.insert(
+            "x".to_string(),
+            arqonhpo_core::config::Domain {
+                min: 0.0,
+                max: 1.0,
+                scale: arqonhpo_core::config::Scale::Linear,
+                active_when: None,
+            },
+        );
+        let config = SolverConfig {
+            bounds,
+            budget: 10,
+            probe_ratio: ProbeBudget::Fixed(0.5),
+            seed: 42,
+            strategy_params: None,
+            constraints: vec![arqonhpo_core::config::Constraint {
+                expr: "x + y".to_string(),
+                min: None,
+                max: Some(1.0),
+            }],
+            selection: Default::default(),
+            cost_budget: None,
+            observation_noise: None,
+            max_restarts: 0,
+            non_finite: Default::default(),
+            repeats: 0,
+            repeat_reducer: Default::default(),
+            objective_transform: None,
+            fixed: Default::default(),
+            target_value: None,
+            target_tol: 0.0,
+            history_cap: None,
+            invalid_params: Default::default(),
+            infeasibility_penalty: 0.0,
+            initial_points: Vec::new(),
+        };
+        let result = validate_config(&config);
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("'y'"));
+    }
 
     #[test]
-    fn test_format_params_empty() {
-        let params: HashMap<String, f64> = HashMap::new();
-        assert_eq!(format_params(&params), "");
+    fn test_dry_run_command_does_not_evaluate() {
+        use tempfile::NamedTempFile;
+
+        let mut config_file = NamedTempFile::new().unwrap();
+        serde_json::to_writer(&mut config_file, &create_test_config()).unwrap();
+        let state_file = NamedTempFile::new().unwrap();
+        let state_path = state_file.path().to_path_buf();
+        fs::remove_file(&state_path).unwrap();
+
+        let result = dry_run_command(config_file.path(), Some(&state_path), None, LogFormat::Json);
+        assert!(result.is_ok());
+        assert!(!state_path.exists(), "dry run must not write state");
     }
 
     #[test]
-    fn test_format_params_single() {
-        let mut params = HashMap::new();
-        params.insert("alpha".to_string(), 0.1234);
-        assert_eq!(format_params(&params), "alpha=0.1234");
+    fn test_format_plan() {
+        let plan = RunPlan {
+            budget: 100,
+            cost_budget: None,
+            probe_points: 20,
+            refine_budget: Some(80),
+            probe: "SobolProbe".to_string(),
+            evaluated: 0,
+            classification: None,
+            classification_score: None,
+            strategy: None,
+        };
+        let text = format_plan(&plan);
+        assert!(text.contains("Probe points:   20 (SobolProbe)"));
+        assert!(text.contains("Refine budget:  80"));
+        assert!(text.contains("pending"));
     }
 
+    // ==================== METRICS TESTS ====================
+
     #[test]
-    fn test_format_params_multiple_sorted() {
-        let mut params = HashMap::new();
-        params.insert("z".to_string(), 1.0);
-        params.insert("a".to_string(), 2.0);
-        params.insert("m".to_string(), 3.0);
-        let result = format_params(&params);
-        assert!(result.starts_with("a="));
-        assert!(result.contains("m="));
-        assert!(result.ends_with("z=1.0000"));
+    fn test_metrics_init_no_server() {
+        // Init without metrics server
+        let metrics = Metrics::init(None, None, &HashMap::new());
+        assert!(metrics.is_ok());
     }
 
     #[test]
-    fn test_format_event_line_valid_json() {
-        let line = r#"{"event":"update","timestamp_us":1234567890,"value":0.5}"#;
-        let result = format_event_line(line);
-        assert!(result.is_some());
-        let formatted = result.unwrap();
-        assert!(formatted.contains("1234567890"));
-        assert!(formatted.contains("update"));
-        assert!(formatted.contains("value=0.500000"));
+    fn test_metrics_record_ask() {
+        let metrics = Metrics::init(None, None, &HashMap::new()).unwrap();
+        metrics.record_ask(5);
+        // Counter should be incremented
+        assert_eq!(metrics.ask_calls.get(), 1);
+        assert_eq!(metrics.candidates_emitted.get(), 5);
     }
 
     #[test]
-    fn test_format_event_line_invalid_json() {
-        let line = "not valid json";
-        let result = format_event_line(line);
-        assert!(result.is_none());
+    fn test_metrics_record_tell() {
+        let metrics = Metrics::init(None, None, &HashMap::new()).unwrap();
+        metrics.record_tell(10);
+        assert_eq!(metrics.tell_calls.get(), 1);
+        assert_eq!(metrics.results_ingested.get(), 10);
     }
 
     #[test]
-    fn test_format_event_line_minimal() {
-        let line = r#"{}"#;
-        let result = format_event_line(line);
-        assert!(result.is_some());
-        let formatted = result.unwrap();
-        assert!(formatted.contains("event"));
+    fn test_metrics_set_history_len() {
+        let metrics = Metrics::init(None, None, &HashMap::new()).unwrap();
+        metrics.set_history_len(42);
+        assert_eq!(metrics.history_len.get(), 42);
     }
 
     #[test]
-    fn test_generate_run_id() {
-        let run_id = generate_run_id("test");
-        assert!(run_id.starts_with("test-"));
-        assert!(run_id.len() > 5);
+    fn test_metrics_observe_eval() {
+        let metrics = Metrics::init(None, None, &HashMap::new()).unwrap();
+        metrics.observe_eval(0.5);
+        metrics.observe_eval(1.0);
+        // Histogram should have 2 observations
+        assert_eq!(metrics.eval_seconds.get_sample_count(), 2);
     }
 
     #[test]
-    fn test_split_query_with_query() {
-        let (path, query) = split_query("/api/data?key=value");
-        assert_eq!(path, "/api/data");
-        assert_eq!(query, Some("key=value"));
+    fn test_metrics_param_gauges_track_best_point() {
+        let state = create_test_state();
+        let metrics = Metrics::init(None, None, &state.config.bounds).unwrap();
+        for name in state.config.bounds.keys() {
+            assert!(metrics
+                .param_value
+                .with_label_values(&[name])
+                .get()
+                .is_nan());
+        }
+        assert!(metrics.best_value.get().is_nan());
+
+        let history = [
+            SeedPoint {
+                params: HashMap::from([("x".to_string(), 0.2)]),
+                value: 3.0,
+                cost: 1.0,
+                penalized: false,
+                failed: false,
+                feasibility: None,
+            },
+            SeedPoint {
+                params: HashMap::from([("x".to_string(), 0.4)]),
+                value: 1.5,
+                cost: 1.0,
+                penalized: false,
+                failed: false,
+                feasibility: None,
+            },
+        ];
+        metrics.record_best(&state.config, history.iter().map(|p| (&p.params, p.value)));
+        assert_eq!(metrics.best_value.get(), 1.5);
+        assert_eq!(metrics.param_value.with_label_values(&["x"]).get(), 0.4);
+
+        // A later, better batch moves the gauges; worse or NaN results do not.
+        let later = [
+            SeedPoint {
+                params: HashMap::from([("x".to_string(), 0.9)]),
+                value: f64::NAN,
+                cost: 1.0,
+                penalized: false,
+                failed: false,
+                feasibility: None,
+            },
+            SeedPoint {
+                params: HashMap::from([("x".to_string(), 0.6)]),
+                value: 0.5,
+                cost: 1.0,
+                penalized: false,
+                failed: false,
+                feasibility: None,
+            },
+        ];
+        metrics.record_best(
+            &state.config,
+            history.iter().chain(&later).map(|p| (&p.params, p.value)),
+        );
+        assert_eq!(metrics.best_value.get(), 0.5);
+        assert_eq!(metrics.param_value.with_label_values(&["x"]).get(), 0.6);
     }
 
     #[test]
-    fn test_split_query_without_query() {
-        let (path, query) = split_query("/api/data");
-        assert_eq!(path, "/api/data");
-        assert!(query.is_none());
+    fn test_metrics_push_to_gateway() {
+        let server = Server::http("127.0.0.1:0").unwrap();
+        let addr = server.server_addr().to_ip().unwrap();
+        let handle = thread::spawn(move || {
+            let mut request = server.recv().unwrap();
+            let method = request.method().to_string();
+            let url = request.url().to_string();
+            let mut body = Vec::new();
+            request.as_reader().read_to_end(&mut body).unwrap();
+            let _ = request.respond(Response::empty(202));
+            (method, url, body)
+        });
+
+        let url = format!("http://{}", addr);
+        let bounds = create_test_state().config.bounds;
+        let metrics = Metrics::init(None, Some(&url), &bounds).unwrap();
+        metrics.record_tell(2);
+        metrics.push();
+
+        let (method, url, body) = handle.join().unwrap();
+        assert_eq!(method, "PUT");
+        assert_eq!(url, "/metrics/job/arqonhpo");
+        let body = String::from_utf8_lossy(&body);
+        assert!(body.contains("arqonhpo_tell_calls"));
+        assert!(body.contains("arqonhpo_param_value"));
     }
 
+    // ==================== FILE I/O TESTS ====================
+
     #[test]
-    fn test_parse_query_empty() {
-        let params = parse_query(None);
-        assert!(params.is_empty());
+    fn test_save_and_load_state() {
+        use tempfile::NamedTempFile;
+
+        let mut bounds = HashMap::new();
+        bounds.insert(
+            "x".to_string(),
+            arqonhpo_core::config::Domain {
+                min: 0.0,
+                max: 1.0,
+                scale: arqonhpo_core::config::Scale::Linear,
+                active_when: None,
+            },
+        );
+        let config = SolverConfig {
+            bounds,
+            budget: 10,
+            probe_ratio: ProbeBudget::Fixed(0.5),
+            seed: 42,
+            strategy_params: None,
+            constraints: Vec::new(),
+            selection: Default::default(),
+            cost_budget: None,
+            observation_noise: None,
+            max_restarts: 0,
+            non_finite: Default::default(),
+            repeats: 0,
+            repeat_reducer: Default::default(),
+            objective_transform: None,
+            fixed: Default::default(),
+            target_value: None,
+            target_tol: 0.0,
+            history_cap: None,
+            invalid_params: Default::default(),
+            infeasibility_penalty: 0.0,
+            initial_points: Vec::new(),
+        };
+
+        let state = SolverState {
+            config,
+            history: vec![SeedPoint {
+                params: [("x".to_string(), 0.5)].into_iter().collect(),
+                value: 1.0,
+                cost: 1.0,
+                penalized: false,
+                failed: false,
+                feasibility: None,
+            }],
+            run_id: Some("test-run".to_string()),
+            warm_start: false,
+            compacted: Default::default(),
+            suggest: None,
+            metrics: None,
+        };
+
+        let file = NamedTempFile::new().unwrap();
+        save_state(file.path(), &state).unwrap();
+
+        let loaded = load_state(file.path()).unwrap();
+        assert_eq!(loaded.run_id, Some("test-run".to_string()));
+        assert_eq!(loaded.history.len(), 1);
+        assert_eq!(loaded.config.budget, 10);
     }
 
     #[test]
-    fn test_parse_query_single() {
-        let params = parse_query(Some("key=value"));
-        assert_eq!(params.get("key"), Some(&"value".to_string()));
+    fn test_load_state_not_found() {
+        let result = load_state(Path::new("/nonexistent/path/state.json"));
+        assert!(result.is_err());
     }
 
     #[test]
-    fn test_parse_query_multiple() {
-        let params = parse_query(Some("a=1&b=2&c=3"));
-        assert_eq!(params.len(), 3);
-        assert_eq!(params.get("a"), Some(&"1".to_string()));
-        assert_eq!(params.get("b"), Some(&"2".to_string()));
-        assert_eq!(params.get("c"), Some(&"3".to_string()));
+    fn test_load_config_valid() {
+        use std::io::Write;
+        use tempfile::NamedTempFile;
+
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(
+            file,
+            r#"{{
+            "seed": 42,
+            "budget": 20,
+            "probe_ratio": 0.5,
+            "bounds": {{
+                "alpha": {{"min": 0.0, "max": 1.0}}
+            }}
+        }}"#
+        )
+        .unwrap();
+
+        let config = load_config(file.path()).unwrap();
+        assert_eq!(config.seed, 42);
+        assert_eq!(config.budget, 20);
+        assert!(config.bounds.contains_key("alpha"));
     }
 
     #[test]
-    fn test_parse_query_empty_key() {
-        let params = parse_query(Some("=value&key=val"));
-        assert_eq!(params.len(), 1);
-        assert_eq!(params.get("key"), Some(&"val".to_string()));
+    fn test_load_config_yaml_and_toml_match_json() {
+        let dir = tempfile::tempdir().unwrap();
+        let files = [
+            (
+                "config.json",
+                r#"{
+                    "seed": 7,
+                    "budget": 30,
+                    "probe_ratio": 0.25,
+                    "strategy_params": {"force": 1},
+                    "non_finite": {"Penalize": {"value": 1000000.0}},
+                    "bounds": {
+                        "lr": {"min": 0.0001, "max": 0.1, "scale": "Log"},
+                        "depth": {"min": 1.0, "max": 8.0}
+                    }
+                }"#,
+            ),
+            (
+                "config.yaml",
+                "seed: 7\nbudget: 30\nprobe_ratio: 0.25\nstrategy_params:\n  force: 1\n\
+                 non_finite:\n  Penalize:\n    value: 1000000.0\nbounds:\n\
+                 \x20 lr: {min: 0.0001, max: 0.1, scale: Log}\n\
+                 \x20 depth: {min: 1.0, max: 8.0}\n",
+            ),
+            (
+                "config.toml",
+                "seed = 7\nbudget = 30\nprobe_ratio = 0.25\n\
+                 strategy_params = { force = 1 }\n\
+                 non_finite = { Penalize = { value = 1000000.0 } }\n\
+                 [bounds]\nlr = { min = 0.0001, max = 0.1, scale = \"Log\" }\n\
+                 depth = { min = 1.0, max = 8.0 }\n",
+            ),
+        ];
+        let configs: Vec<SolverConfig> = files
+            .iter()
+            .map(|(name, contents)| {
+                let path = dir.path().join(name);
+                fs::write(&path, contents).unwrap();
+                load_config(&path).unwrap()
+            })
+            .collect();
+
+        assert_eq!(configs[0].bounds["lr"].scale, Scale::Log);
+        assert_eq!(
+            configs[0].non_finite,
+            NonFinitePolicy::Penalize { value: 1e6 }
+        );
+        assert_eq!(configs[0], configs[1]);
+        assert_eq!(configs[0], configs[2]);
+
+        // What validate --format prints parses back to the same config.
+        for format in [DataFormat::Json, DataFormat::Yaml, DataFormat::Toml] {
+            let rendered = format.render(&configs[0]).unwrap();
+            let parsed: SolverConfig = format.parse(&rendered).unwrap();
+            assert_eq!(parsed, configs[0], "{:?}", format);
+        }
     }
 
     #[test]
-    fn test_parse_result_simple() {
-        let result = parse_result("0.5");
-        assert!(result.is_ok());
-        assert!((result.unwrap() - 0.5).abs() < 0.001);
+    fn test_read_results_by_extension() {
+        let dir = tempfile::tempdir().unwrap();
+        let yaml = dir.path().join("results.yml");
+        fs::write(&yaml, "- params: {x: 0.5}\n  value: 0.25\n  cost: 1.0\n").unwrap();
+        let toml = dir.path().join("results.toml");
+        fs::write(
+            &toml,
+            "[[results]]\nvalue = 0.25\ncost = 1.0\nparams = { x = 0.5 }\n",
+        )
+        .unwrap();
+
+        for path in [yaml, toml] {
+            let TellInput::Points(results) = read_results(Some(&path)).unwrap() else {
+                panic!("expected a list of results");
+            };
+            assert_eq!(results.len(), 1, "{}", path.display());
+            assert_eq!(results[0].params["x"], 0.5);
+            assert_eq!(results[0].value, 0.25);
+        }
+    }
+
+    #[test]
+    fn test_load_config_not_found() {
+        let result = load_config(Path::new("/nonexistent/config.json"));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_load_config_invalid_json() {
+        use std::io::Write;
+        use tempfile::NamedTempFile;
+
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "not valid json").unwrap();
+
+        let result = load_config(file.path());
+        assert!(result.is_err());
     }
 
     #[test]
-    fn test_parse_result_with_prefix() {
-        let result = parse_result("RESULT=0.75");
-        assert!(result.is_ok());
-        assert!((result.unwrap() - 0.75).abs() < 0.001);
-    }
+    fn test_write_output_to_file() {
+        use tempfile::NamedTempFile;
 
-    #[test]
-    fn test_parse_result_multiline() {
-        let output = "some output\nmore output\nRESULT=0.9\n";
-        let result = parse_result(output);
-        assert!(result.is_ok());
-        assert!((result.unwrap() - 0.9).abs() < 0.001);
+        let file = NamedTempFile::new().unwrap();
+        let data = vec![1, 2, 3];
+        write_output(Some(&file.path().to_path_buf()), &data).unwrap();
+
+        let content = fs::read_to_string(file.path()).unwrap();
+        assert!(content.contains("["));
+        assert!(content.contains("1"));
     }
 
     #[test]
-    fn test_parse_result_empty() {
-        let result = parse_result("");
-        assert!(result.is_err());
+    fn test_read_json_valid() {
+        use std::io::Write;
+        use tempfile::NamedTempFile;
+
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, r#"{{"key": "value"}}"#).unwrap();
+
+        let result: serde_json::Value = read_json(file.path()).unwrap();
+        assert_eq!(result["key"], "value");
     }
 
     #[test]
-    fn test_parse_result_invalid_number() {
-        let result = parse_result("not_a_number");
+    fn test_read_json_invalid() {
+        use std::io::Write;
+        use tempfile::NamedTempFile;
+
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "not json").unwrap();
+
+        let result: miette::Result<serde_json::Value> = read_json(file.path());
         assert!(result.is_err());
     }
 
+    // ==================== SOLVER STATE TESTS ====================
+
     #[test]
-    fn test_validate_config_valid() {
+    fn test_solver_state_serialization() {
         let mut bounds = HashMap::new();
         bounds.insert(
             "x".to_string(),
@@ -1089,20 +5542,48 @@ mod tests {
                 min: 0.0,
                 max: 1.0,
                 scale: arqonhpo_core::config::Scale::Linear,
+                active_when: None,
             },
         );
-        let config = SolverConfig {
-            bounds,
-            budget: 10,
-            probe_ratio: 0.5,
-            seed: 42,
-            strategy_params: None,
+        let state = SolverState {
+            config: SolverConfig {
+                bounds,
+                budget: 10,
+                probe_ratio: ProbeBudget::Fixed(0.5),
+                seed: 42,
+                strategy_params: None,
+                constraints: Vec::new(),
+                selection: Default::default(),
+                cost_budget: None,
+                observation_noise: None,
+                max_restarts: 0,
+                non_finite: Default::default(),
+                repeats: 0,
+                repeat_reducer: Default::default(),
+                objective_transform: None,
+                fixed: Default::default(),
+                target_value: None,
+                target_tol: 0.0,
+                history_cap: None,
+                invalid_params: Default::default(),
+                infeasibility_penalty: 0.0,
+                initial_points: Vec::new(),
+            },
+            history: vec![],
+            run_id: Some("test".to_string()),
+            warm_start: false,
+            compacted: Default::default(),
+            suggest: None,
+            metrics: None,
         };
-        assert!(validate_config(&config).is_ok());
+
+        let json = serde_json::to_string(&state).unwrap();
+        assert!(json.contains("\"run_id\":\"test\""));
+        assert!(json.contains("\"budget\":10"));
     }
 
     #[test]
-    fn test_validate_config_zero_budget() {
+    fn test_loaded_state_from_config() {
         let mut bounds = HashMap::new();
         bounds.insert(
             "x".to_string(),
@@ -1110,615 +5591,1300 @@ mod tests {
                 min: 0.0,
                 max: 1.0,
                 scale: arqonhpo_core::config::Scale::Linear,
+                active_when: None,
             },
         );
-        let config = SolverConfig {
-            bounds,
-            budget: 0,
-            probe_ratio: 0.5,
-            seed: 42,
-            strategy_params: None,
+        let loaded = LoadedState {
+            config: SolverConfig {
+                bounds,
+                budget: 10,
+                probe_ratio: ProbeBudget::Fixed(0.5),
+                seed: 42,
+                strategy_params: None,
+                constraints: Vec::new(),
+                selection: Default::default(),
+                cost_budget: None,
+                observation_noise: None,
+                max_restarts: 0,
+                non_finite: Default::default(),
+                repeats: 0,
+                repeat_reducer: Default::default(),
+                objective_transform: None,
+                fixed: Default::default(),
+                target_value: None,
+                target_tol: 0.0,
+                history_cap: None,
+                invalid_params: Default::default(),
+                infeasibility_penalty: 0.0,
+                initial_points: Vec::new(),
+            },
+            history: vec![],
+            run_id: None,
+            warm_start: false,
+            compacted: Default::default(),
         };
-        let result = validate_config(&config);
-        assert!(result.is_err());
-        assert!(result.unwrap_err().to_string().contains("budget"));
+
+        assert!(loaded.run_id.is_none());
+        assert!(loaded.history.is_empty());
     }
 
+    // ==================== INIT TRACING TEST ====================
+
     #[test]
-    fn test_validate_config_empty_bounds() {
+    fn test_init_tracing_json() {
+        // This just verifies it doesn't panic - tracing can only init once per process
+        // so we can't really test this properly in unit tests
+        // The important thing is coverage of the match arms
+    }
+
+    // ==================== VALIDATE CONFIG EDGE CASES ====================
+
+    #[test]
+    fn test_validate_config_log_scale_negative() {
+        let mut bounds = HashMap::new();
+        bounds.insert(
+            "x".to_string(),
+            arqonhpo_core::config::Domain {
+                min: -1.0,
+                max: 1.0,
+                scale: arqonhpo_core::config::Scale::Log,
+                active_when: None,
+            },
+        );
         let config = SolverConfig {
-            bounds: HashMap::new(),
+            bounds,
             budget: 10,
-            probe_ratio: 0.5,
+            probe_ratio: ProbeBudget::Fixed(0.5),
             seed: 42,
             strategy_params: None,
+            constraints: Vec::new(),
+            selection: Default::default(),
+            cost_budget: None,
+            observation_noise: None,
+            max_restarts: 0,
+            non_finite: Default::default(),
+            repeats: 0,
+            repeat_reducer: Default::default(),
+            objective_transform: None,
+            fixed: Default::default(),
+            target_value: None,
+            target_tol: 0.0,
+            history_cap: None,
+            invalid_params: Default::default(),
+            infeasibility_penalty: 0.0,
+            initial_points: Vec::new(),
         };
         let result = validate_config(&config);
         assert!(result.is_err());
-        assert!(result.unwrap_err().to_string().contains("bounds"));
+        assert!(result.unwrap_err().to_string().contains("log scale"));
     }
 
     #[test]
-    fn test_validate_config_invalid_bounds() {
+    fn test_validate_config_log_scale_zero() {
         let mut bounds = HashMap::new();
         bounds.insert(
             "x".to_string(),
             arqonhpo_core::config::Domain {
-                min: 1.0,
-                max: 0.0,
-                scale: arqonhpo_core::config::Scale::Linear,
-            }, // min > max
+                min: 0.0,
+                max: 1.0,
+                scale: arqonhpo_core::config::Scale::Log,
+                active_when: None,
+            },
         );
         let config = SolverConfig {
             bounds,
             budget: 10,
-            probe_ratio: 0.5,
+            probe_ratio: ProbeBudget::Fixed(0.5),
             seed: 42,
             strategy_params: None,
+            constraints: Vec::new(),
+            selection: Default::default(),
+            cost_budget: None,
+            observation_noise: None,
+            max_restarts: 0,
+            non_finite: Default::default(),
+            repeats: 0,
+            repeat_reducer: Default::default(),
+            objective_transform: None,
+            fixed: Default::default(),
+            target_value: None,
+            target_tol: 0.0,
+            history_cap: None,
+            invalid_params: Default::default(),
+            infeasibility_penalty: 0.0,
+            initial_points: Vec::new(),
         };
         let result = validate_config(&config);
         assert!(result.is_err());
     }
 
-    // ==================== METRICS TESTS ====================
+    // ==================== READ INPUT TEST ====================
 
     #[test]
-    fn test_metrics_init_no_server() {
-        // Init without metrics server
-        let metrics = Metrics::init(None);
-        assert!(metrics.is_ok());
+    fn test_read_input_from_file() {
+        use std::io::Write;
+        use tempfile::NamedTempFile;
+
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "test content").unwrap();
+
+        let content = read_input(Some(&file.path().to_path_buf())).unwrap();
+        assert!(content.contains("test content"));
     }
 
     #[test]
-    fn test_metrics_record_ask() {
-        let metrics = Metrics::init(None).unwrap();
-        metrics.record_ask(5);
-        // Counter should be incremented
-        assert_eq!(metrics.ask_calls.get(), 1);
-        assert_eq!(metrics.candidates_emitted.get(), 5);
+    fn test_read_input_file_not_found() {
+        let result = read_input(Some(&PathBuf::from("/nonexistent/file.txt")));
+        assert!(result.is_err());
     }
 
+    // ==================== EVALUATE SCRIPT PARSING ====================
+
     #[test]
-    fn test_metrics_record_tell() {
-        let metrics = Metrics::init(None).unwrap();
-        metrics.record_tell(10);
-        assert_eq!(metrics.tell_calls.get(), 1);
-        assert_eq!(metrics.results_ingested.get(), 10);
+    fn test_parse_result_with_whitespace() {
+        let result = parse_result("  \n\n  0.75  \n\n  ");
+        assert!(result.is_ok());
+        assert!((result.unwrap() - 0.75).abs() < 0.001);
     }
 
     #[test]
-    fn test_metrics_set_history_len() {
-        let metrics = Metrics::init(None).unwrap();
-        metrics.set_history_len(42);
-        assert_eq!(metrics.history_len.get(), 42);
+    fn test_parse_result_last_value_wins() {
+        let output = "0.1\n0.2\n0.3";
+        let result = parse_result(output);
+        assert!(result.is_ok());
+        assert!((result.unwrap() - 0.3).abs() < 0.001);
     }
 
     #[test]
-    fn test_metrics_observe_eval() {
-        let metrics = Metrics::init(None).unwrap();
-        metrics.observe_eval(0.5);
-        metrics.observe_eval(1.0);
-        // Histogram should have 2 observations
-        assert_eq!(metrics.eval_seconds.get_sample_count(), 2);
+    fn test_parse_feasibility() {
+        let output = "RESULT=0.4\nFEASIBILITY=0.8\n";
+        assert_eq!(parse_result(output).unwrap(), 0.4);
+        assert_eq!(parse_feasibility(output).unwrap(), Some(0.8));
+        assert_eq!(parse_feasibility("RESULT=0.4").unwrap(), None);
+        assert!(parse_feasibility("FEASIBILITY=1.5").is_err());
+        assert!(parse_feasibility("FEASIBILITY=close").is_err());
     }
 
-    // ==================== FILE I/O TESTS ====================
+    #[test]
+    fn test_parse_result_result_prefix_wins() {
+        let output = "noise\nmore noise\nRESULT=0.99";
+        let result = parse_result(output);
+        assert!(result.is_ok());
+        assert!((result.unwrap() - 0.99).abs() < 0.001);
+    }
+
+    // ==================== INTERACTIVE COMMAND PARSING ====================
 
     #[test]
-    fn test_save_and_load_state() {
-        use tempfile::NamedTempFile;
+    fn test_interactive_command_deserialization_ask() {
+        let json = r#"{"cmd": "ask", "batch": 5}"#;
+        let cmd: InteractiveCommand = serde_json::from_str(json).unwrap();
+        assert!(matches!(cmd, InteractiveCommand::Ask { batch: Some(5) }));
+    }
 
-        let mut bounds = HashMap::new();
-        bounds.insert(
-            "x".to_string(),
-            arqonhpo_core::config::Domain {
-                min: 0.0,
-                max: 1.0,
-                scale: arqonhpo_core::config::Scale::Linear,
-            },
-        );
-        let config = SolverConfig {
-            bounds,
-            budget: 10,
-            probe_ratio: 0.5,
-            seed: 42,
-            strategy_params: None,
-        };
+    #[test]
+    fn test_interactive_command_deserialization_ask_no_batch() {
+        let json = r#"{"cmd": "ask"}"#;
+        let cmd: InteractiveCommand = serde_json::from_str(json).unwrap();
+        assert!(matches!(cmd, InteractiveCommand::Ask { batch: None }));
+    }
 
-        let state = SolverState {
-            config,
-            history: vec![SeedPoint {
-                params: [("x".to_string(), 0.5)].into_iter().collect(),
-                value: 1.0,
+    #[test]
+    fn test_interactive_command_deserialization_tell() {
+        let json = r#"{"cmd": "tell", "results": []}"#;
+        let cmd: InteractiveCommand = serde_json::from_str(json).unwrap();
+        assert!(matches!(cmd, InteractiveCommand::Tell { results } if results.is_empty()));
+    }
+
+    #[test]
+    fn test_interactive_ask_response_serialization() {
+        let response = InteractiveAskResponse { params: None };
+        let json = serde_json::to_string(&response).unwrap();
+        assert!(json.contains("\"params\":null"));
+    }
+
+    #[test]
+    fn test_interactive_tell_response_serialization() {
+        let response = InteractiveTellResponse { ok: true };
+        let json = serde_json::to_string(&response).unwrap();
+        assert!(json.contains("\"ok\":true"));
+    }
+
+    fn msgpack_frame(value: &serde_json::Value) -> Vec<u8> {
+        let payload = rmp_serde::to_vec_named(value).unwrap();
+        let mut frame = (payload.len() as u32).to_be_bytes().to_vec();
+        frame.extend(payload);
+        frame
+    }
+
+    #[test]
+    fn test_read_command_msgpack_tell_round_trip() {
+        let results = vec![
+            SeedPoint {
+                params: HashMap::from([("x".to_string(), 0.25), ("y".to_string(), -1.5)]),
+                value: 0.125,
+                cost: 2.0,
+                penalized: false,
+                failed: false,
+                feasibility: None,
+            },
+            SeedPoint {
+                params: HashMap::from([("x".to_string(), 1e-9), ("y".to_string(), 3.0)]),
+                value: -7.75,
                 cost: 1.0,
-            }],
-            run_id: Some("test-run".to_string()),
+                penalized: false,
+                failed: false,
+                feasibility: None,
+            },
+        ];
+        let frame = msgpack_frame(&serde_json::json!({ "cmd": "tell", "results": results }));
+        let mut reader = io::Cursor::new(frame);
+
+        let command = read_command(&mut reader, Protocol::Msgpack)
+            .unwrap()
+            .unwrap();
+        let InteractiveCommand::Tell { results: decoded } = command else {
+            panic!("expected tell command");
         };
+        assert_eq!(decoded.len(), results.len());
+        for (got, want) in decoded.iter().zip(&results) {
+            assert_eq!(got.params, want.params);
+            assert_eq!(got.value.to_bits(), want.value.to_bits());
+            assert_eq!(got.cost.to_bits(), want.cost.to_bits());
+        }
+        assert!(read_command(&mut reader, Protocol::Msgpack)
+            .unwrap()
+            .is_none());
+    }
 
-        let file = NamedTempFile::new().unwrap();
-        save_state(file.path(), &state).unwrap();
+    #[test]
+    fn test_read_command_msgpack_multiple_frames() {
+        let mut input = msgpack_frame(&serde_json::json!({ "cmd": "ask", "batch": 3 }));
+        input.extend(msgpack_frame(&serde_json::json!({ "cmd": "ask" })));
+        let mut reader = io::Cursor::new(input);
+
+        let first = read_command(&mut reader, Protocol::Msgpack).unwrap();
+        assert!(matches!(
+            first,
+            Some(InteractiveCommand::Ask { batch: Some(3) })
+        ));
+        let second = read_command(&mut reader, Protocol::Msgpack).unwrap();
+        assert!(matches!(
+            second,
+            Some(InteractiveCommand::Ask { batch: None })
+        ));
+        assert!(read_command(&mut reader, Protocol::Msgpack)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_read_command_msgpack_truncated_frame_errors() {
+        let mut frame = msgpack_frame(&serde_json::json!({ "cmd": "ask" }));
+        frame.pop();
+        let mut reader = io::Cursor::new(frame);
+        assert!(read_command(&mut reader, Protocol::Msgpack).is_err());
+    }
+
+    #[test]
+    fn test_read_command_json_skips_blank_lines() {
+        let mut reader = io::Cursor::new(b"\n  \n{\"cmd\": \"ask\", \"batch\": 2}\n".to_vec());
+        let command = read_command(&mut reader, Protocol::Json).unwrap();
+        assert!(matches!(
+            command,
+            Some(InteractiveCommand::Ask { batch: Some(2) })
+        ));
+        assert!(read_command(&mut reader, Protocol::Json).unwrap().is_none());
+    }
 
-        let loaded = load_state(file.path()).unwrap();
-        assert_eq!(loaded.run_id, Some("test-run".to_string()));
-        assert_eq!(loaded.history.len(), 1);
-        assert_eq!(loaded.config.budget, 10);
+    #[test]
+    fn test_write_response_msgpack_frame() {
+        let params = vec![HashMap::from([("x".to_string(), 0.5)])];
+        let mut out = Vec::new();
+        write_response(
+            &mut out,
+            Protocol::Msgpack,
+            &InteractiveAskResponse {
+                params: Some(params.clone()),
+            },
+        )
+        .unwrap();
+
+        let len = u32::from_be_bytes(out[..4].try_into().unwrap()) as usize;
+        assert_eq!(len, out.len() - 4);
+        #[derive(Deserialize)]
+        struct Decoded {
+            params: Option<Vec<HashMap<String, f64>>>,
+        }
+        let decoded: Decoded = rmp_serde::from_slice(&out[4..]).unwrap();
+        assert_eq!(decoded.params, Some(params));
     }
 
     #[test]
-    fn test_load_state_not_found() {
-        let result = load_state(Path::new("/nonexistent/path/state.json"));
-        assert!(result.is_err());
+    fn test_write_response_json_line() {
+        let mut out = Vec::new();
+        write_response(
+            &mut out,
+            Protocol::Json,
+            &InteractiveTellResponse { ok: true },
+        )
+        .unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "{\"ok\":true}\n");
     }
 
+    // ==================== LOAD STATE OR CONFIG TESTS ====================
+
     #[test]
-    fn test_load_config_valid() {
+    fn test_load_state_or_config_with_existing_state() {
         use std::io::Write;
         use tempfile::NamedTempFile;
 
-        let mut file = NamedTempFile::new().unwrap();
+        // Create a config file
+        let mut config_file = NamedTempFile::new().unwrap();
         writeln!(
-            file,
+            config_file,
             r#"{{
             "seed": 42,
             "budget": 20,
             "probe_ratio": 0.5,
-            "bounds": {{
-                "alpha": {{"min": 0.0, "max": 1.0}}
-            }}
+            "bounds": {{"x": {{"min": 0.0, "max": 1.0}}}}
         }}"#
         )
         .unwrap();
 
-        let config = load_config(file.path()).unwrap();
-        assert_eq!(config.seed, 42);
-        assert_eq!(config.budget, 20);
-        assert!(config.bounds.contains_key("alpha"));
-    }
+        // Create a state file
+        let mut state_file = NamedTempFile::new().unwrap();
+        writeln!(
+            state_file,
+            r#"{{
+            "config": {{
+                "seed": 99,
+                "budget": 30,
+                "probe_ratio": 0.3,
+                "bounds": {{"y": {{"min": 0.0, "max": 2.0}}}}
+            }},
+            "history": [],
+            "run_id": "existing-run"
+        }}"#
+        )
+        .unwrap();
 
-    #[test]
-    fn test_load_config_not_found() {
-        let result = load_config(Path::new("/nonexistent/config.json"));
-        assert!(result.is_err());
+        let loaded =
+            load_state_or_config(config_file.path(), Some(&state_file.path().to_path_buf()))
+                .unwrap();
+        // Should use state's config, not the config file
+        assert_eq!(loaded.config.seed, 99);
+        assert_eq!(loaded.run_id, Some("existing-run".to_string()));
     }
 
     #[test]
-    fn test_load_config_invalid_json() {
+    fn test_load_state_or_config_no_state_file() {
         use std::io::Write;
         use tempfile::NamedTempFile;
 
-        let mut file = NamedTempFile::new().unwrap();
-        writeln!(file, "not valid json").unwrap();
+        // Create a config file
+        let mut config_file = NamedTempFile::new().unwrap();
+        writeln!(
+            config_file,
+            r#"{{
+            "seed": 42,
+            "budget": 20,
+            "probe_ratio": 0.5,
+            "bounds": {{"x": {{"min": 0.0, "max": 1.0}}}}
+        }}"#
+        )
+        .unwrap();
 
-        let result = load_config(file.path());
-        assert!(result.is_err());
+        let loaded = load_state_or_config(config_file.path(), None).unwrap();
+        assert_eq!(loaded.config.seed, 42);
+        assert!(loaded.history.is_empty());
+        assert!(loaded.run_id.is_none());
     }
 
     #[test]
-    fn test_write_output_to_file() {
+    fn test_load_state_or_config_state_doesnt_exist() {
+        use std::io::Write;
         use tempfile::NamedTempFile;
 
-        let file = NamedTempFile::new().unwrap();
-        let data = vec![1, 2, 3];
-        write_output(Some(&file.path().to_path_buf()), &data).unwrap();
+        // Create a config file
+        let mut config_file = NamedTempFile::new().unwrap();
+        writeln!(
+            config_file,
+            r#"{{
+            "seed": 42,
+            "budget": 20,
+            "probe_ratio": 0.5,
+            "bounds": {{"x": {{"min": 0.0, "max": 1.0}}}}
+        }}"#
+        )
+        .unwrap();
 
-        let content = fs::read_to_string(file.path()).unwrap();
-        assert!(content.contains("["));
-        assert!(content.contains("1"));
+        // Path that doesn't exist
+        let nonexistent = PathBuf::from("/tmp/nonexistent_state_12345.json");
+        let loaded = load_state_or_config(config_file.path(), Some(&nonexistent)).unwrap();
+        // Should fall back to config
+        assert_eq!(loaded.config.seed, 42);
+        assert!(loaded.run_id.is_none());
     }
 
+    // ==================== VALIDATE COMMAND TESTS ====================
+
     #[test]
-    fn test_read_json_valid() {
+    fn test_validate_command_success() {
         use std::io::Write;
         use tempfile::NamedTempFile;
 
-        let mut file = NamedTempFile::new().unwrap();
-        writeln!(file, r#"{{"key": "value"}}"#).unwrap();
+        let mut config_file = NamedTempFile::new().unwrap();
+        writeln!(
+            config_file,
+            r#"{{
+            "seed": 42,
+            "budget": 20,
+            "probe_ratio": 0.5,
+            "bounds": {{"x": {{"min": 0.0, "max": 1.0}}}}
+        }}"#
+        )
+        .unwrap();
 
-        let result: serde_json::Value = read_json(file.path()).unwrap();
-        assert_eq!(result["key"], "value");
+        let result = validate_command(config_file.path(), None);
+        assert!(result.is_ok());
     }
 
     #[test]
-    fn test_read_json_invalid() {
+    fn test_validate_command_invalid_config() {
         use std::io::Write;
         use tempfile::NamedTempFile;
 
-        let mut file = NamedTempFile::new().unwrap();
-        writeln!(file, "not json").unwrap();
+        let mut config_file = NamedTempFile::new().unwrap();
+        writeln!(
+            config_file,
+            r#"{{
+            "seed": 42,
+            "budget": 0,
+            "probe_ratio": 0.5,
+            "bounds": {{"x": {{"min": 0.0, "max": 1.0}}}}
+        }}"#
+        )
+        .unwrap();
 
-        let result: miette::Result<serde_json::Value> = read_json(file.path());
+        let result = validate_command(config_file.path(), None);
         assert!(result.is_err());
     }
 
-    // ==================== SOLVER STATE TESTS ====================
-
-    #[test]
-    fn test_solver_state_serialization() {
-        let mut bounds = HashMap::new();
-        bounds.insert(
-            "x".to_string(),
-            arqonhpo_core::config::Domain {
-                min: 0.0,
-                max: 1.0,
-                scale: arqonhpo_core::config::Scale::Linear,
-            },
-        );
-        let state = SolverState {
-            config: SolverConfig {
-                bounds,
-                budget: 10,
-                probe_ratio: 0.5,
-                seed: 42,
-                strategy_params: None,
-            },
-            history: vec![],
-            run_id: Some("test".to_string()),
-        };
-
-        let json = serde_json::to_string(&state).unwrap();
-        assert!(json.contains("\"run_id\":\"test\""));
-        assert!(json.contains("\"budget\":10"));
-    }
-
-    #[test]
-    fn test_loaded_state_from_config() {
-        let mut bounds = HashMap::new();
-        bounds.insert(
-            "x".to_string(),
-            arqonhpo_core::config::Domain {
-                min: 0.0,
-                max: 1.0,
-                scale: arqonhpo_core::config::Scale::Linear,
-            },
-        );
-        let loaded = LoadedState {
-            config: SolverConfig {
-                bounds,
-                budget: 10,
-                probe_ratio: 0.5,
-                seed: 42,
-                strategy_params: None,
-            },
-            history: vec![],
-            run_id: None,
-        };
-
-        assert!(loaded.run_id.is_none());
-        assert!(loaded.history.is_empty());
-    }
-
-    // ==================== INIT TRACING TEST ====================
+    // ==================== READ EVENT LINES TESTS ====================
 
     #[test]
-    fn test_init_tracing_json() {
-        // This just verifies it doesn't panic - tracing can only init once per process
-        // so we can't really test this properly in unit tests
-        // The important thing is coverage of the match arms
-    }
+    fn test_read_event_lines_basic() {
+        use std::io::Write;
+        use tempfile::NamedTempFile;
 
-    // ==================== VALIDATE CONFIG EDGE CASES ====================
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, r#"{{"event": "test1", "timestamp_us": 100}}"#).unwrap();
+        writeln!(file, r#"{{"event": "test2", "timestamp_us": 200}}"#).unwrap();
+        writeln!(file, r#"{{"event": "test3", "timestamp_us": 300}}"#).unwrap();
 
-    #[test]
-    fn test_validate_config_log_scale_negative() {
-        let mut bounds = HashMap::new();
-        bounds.insert(
-            "x".to_string(),
-            arqonhpo_core::config::Domain {
-                min: -1.0,
-                max: 1.0,
-                scale: arqonhpo_core::config::Scale::Log,
-            },
-        );
-        let config = SolverConfig {
-            bounds,
-            budget: 10,
-            probe_ratio: 0.5,
-            seed: 42,
-            strategy_params: None,
-        };
-        let result = validate_config(&config);
-        assert!(result.is_err());
-        assert!(result.unwrap_err().to_string().contains("log scale"));
+        let lines = read_event_lines(file.path(), 10).unwrap();
+        assert_eq!(lines.len(), 3);
     }
 
-    #[test]
-    fn test_validate_config_log_scale_zero() {
-        let mut bounds = HashMap::new();
-        bounds.insert(
-            "x".to_string(),
-            arqonhpo_core::config::Domain {
-                min: 0.0,
-                max: 1.0,
-                scale: arqonhpo_core::config::Scale::Log,
-            },
-        );
-        let config = SolverConfig {
-            bounds,
-            budget: 10,
-            probe_ratio: 0.5,
-            seed: 42,
-            strategy_params: None,
-        };
-        let result = validate_config(&config);
-        assert!(result.is_err());
-    }
+    #[test]
+    fn test_read_event_lines_limit() {
+        use std::io::Write;
+        use tempfile::NamedTempFile;
 
-    // ==================== READ INPUT TEST ====================
+        let mut file = NamedTempFile::new().unwrap();
+        for i in 0..10 {
+            writeln!(
+                file,
+                r#"{{"event": "test{}", "timestamp_us": {}}}"#,
+                i,
+                i * 100
+            )
+            .unwrap();
+        }
+
+        let lines = read_event_lines(file.path(), 3).unwrap();
+        // Should only get the last 3
+        assert_eq!(lines.len(), 3);
+    }
 
     #[test]
-    fn test_read_input_from_file() {
+    fn test_read_event_lines_filters_invalid_json() {
         use std::io::Write;
         use tempfile::NamedTempFile;
 
         let mut file = NamedTempFile::new().unwrap();
-        writeln!(file, "test content").unwrap();
+        writeln!(file, "not valid json").unwrap();
+        writeln!(file, r#"{{"event": "valid", "timestamp_us": 100}}"#).unwrap();
+        writeln!(file, "also invalid").unwrap();
 
-        let content = read_input(Some(&file.path().to_path_buf())).unwrap();
-        assert!(content.contains("test content"));
+        let lines = read_event_lines(file.path(), 10).unwrap();
+        assert_eq!(lines.len(), 1); // Only the valid JSON line
     }
 
     #[test]
-    fn test_read_input_file_not_found() {
-        let result = read_input(Some(&PathBuf::from("/nonexistent/file.txt")));
+    fn test_read_event_lines_file_not_found() {
+        let result = read_event_lines(Path::new("/nonexistent/events.json"), 10);
         assert!(result.is_err());
     }
 
-    // ==================== EVALUATE SCRIPT PARSING ====================
+    // ==================== PARTIAL RESULTS TESTS ====================
 
     #[test]
-    fn test_parse_result_with_whitespace() {
-        let result = parse_result("  \n\n  0.75  \n\n  ");
-        assert!(result.is_ok());
-        assert!((result.unwrap() - 0.75).abs() < 0.001);
+    fn test_param_hash_canonicalizes() {
+        let a: HashMap<String, f64> = [("x".to_string(), 0.1), ("y".to_string(), -0.0)]
+            .into_iter()
+            .collect();
+        let b: HashMap<String, f64> = [("y".to_string(), 0.0), ("x".to_string(), 0.1 + 1e-17)]
+            .into_iter()
+            .collect();
+        let c: HashMap<String, f64> = [("x".to_string(), 0.1001), ("y".to_string(), 0.0)]
+            .into_iter()
+            .collect();
+        assert_eq!(param_hash(&a), param_hash(&b));
+        assert_ne!(param_hash(&a), param_hash(&c));
     }
 
     #[test]
-    fn test_parse_result_last_value_wins() {
-        let output = "0.1\n0.2\n0.3";
-        let result = parse_result(output);
-        assert!(result.is_ok());
-        assert!((result.unwrap() - 0.3).abs() < 0.001);
+    fn test_load_partial_skips_truncated_line() {
+        use tempfile::tempdir;
+
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("partial.jsonl");
+        assert!(load_partial(&path).unwrap().is_empty());
+
+        let point = SeedPoint {
+            params: [("x".to_string(), 0.25)].into_iter().collect(),
+            value: 1.5,
+            cost: 1.0,
+            penalized: false,
+            failed: false,
+            feasibility: None,
+        };
+        append_partial(&path, &point).unwrap();
+        fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(b"{\"params\":{\"x\":0.")
+            .unwrap();
+
+        let recorded = load_partial(&path).unwrap();
+        assert_eq!(recorded.len(), 1);
+        assert_eq!(recorded[&param_hash(&point.params)][0].value, 1.5);
     }
 
+    #[cfg(unix)]
     #[test]
-    fn test_parse_result_result_prefix_wins() {
-        let output = "noise\nmore noise\nRESULT=0.99";
-        let result = parse_result(output);
-        assert!(result.is_ok());
-        assert!((result.unwrap() - 0.99).abs() < 0.001);
-    }
+    fn test_run_command_resumes_partial_batch() {
+        use std::os::unix::fs::PermissionsExt;
+        use tempfile::tempdir;
 
-    // ==================== INTERACTIVE COMMAND PARSING ====================
+        let dir = tempdir().unwrap();
+        let calls = dir.path().join("calls.log");
+        let script_path = dir.path().join("objective.sh");
+        fs::write(
+            &script_path,
+            format!(
+                "#!/bin/bash\necho \"$ARQON_x\" >> {}\necho \"RESULT=0.5\"",
+                calls.display()
+            ),
+        )
+        .unwrap();
+        let mut perms = fs::metadata(&script_path).unwrap().permissions();
+        perms.set_mode(0o755);
+        fs::set_permissions(&script_path, perms).unwrap();
 
-    #[test]
-    fn test_interactive_command_deserialization_ask() {
-        let json = r#"{"cmd": "ask", "batch": 5}"#;
-        let cmd: InteractiveCommand = serde_json::from_str(json).unwrap();
-        assert!(matches!(cmd, InteractiveCommand::Ask { batch: Some(5) }));
+        let mut config = create_test_config();
+        config.budget = 6;
+        config.probe_ratio = ProbeBudget::Fixed(0.5);
+        let config_path = dir.path().join("config.json");
+        fs::write(&config_path, serde_json::to_string(&config).unwrap()).unwrap();
+        let state_path = dir.path().join("state.json");
+        let partial_path = dir.path().join("partial.jsonl");
+
+        // Simulate a crash after two candidates of the first batch finished.
+        let first_batch = Solver::pcr(config.clone()).ask().unwrap();
+        for params in first_batch.iter().take(2) {
+            let point = SeedPoint {
+                params: params.clone(),
+                value: 0.25,
+                cost: 1.0,
+                penalized: false,
+                failed: false,
+                feasibility: None,
+            };
+            append_partial(&partial_path, &point).unwrap();
+        }
+
+        let metrics = Metrics::init(None, None, &HashMap::new()).unwrap();
+        run_command(
+            &config_path,
+            &script_path,
+            Some(&state_path),
+            Some(&partial_path),
+            None,
+            None,
+            None,
+            None,
+            &metrics,
+        )
+        .unwrap();
+
+        let evaluated = fs::read_to_string(&calls).unwrap().lines().count();
+        assert_eq!(evaluated, 4, "logged candidates must not be re-evaluated");
+        let state = load_state(&state_path).unwrap();
+        assert_eq!(state.history.len(), 6);
+        assert_eq!(
+            state.history.iter().filter(|p| p.value == 0.25).count(),
+            2,
+            "logged results are reused as-is"
+        );
+        assert!(
+            !partial_path.exists(),
+            "partial log is rotated after each batch"
+        );
+        assert!(dir.path().join("partial.jsonl.prev").exists());
     }
 
+    #[cfg(unix)]
     #[test]
-    fn test_interactive_command_deserialization_ask_no_batch() {
-        let json = r#"{"cmd": "ask"}"#;
-        let cmd: InteractiveCommand = serde_json::from_str(json).unwrap();
-        assert!(matches!(cmd, InteractiveCommand::Ask { batch: None }));
+    fn test_run_command_resume_strategy_is_not_saved() {
+        use std::os::unix::fs::PermissionsExt;
+        use tempfile::tempdir;
+
+        let dir = tempdir().unwrap();
+        let script_path = dir.path().join("objective.sh");
+        fs::write(
+            &script_path,
+            "#!/bin/bash
+echo \"RESULT=0.5\"",
+        )
+        .unwrap();
+        let mut perms = fs::metadata(&script_path).unwrap().permissions();
+        perms.set_mode(0o755);
+        fs::set_permissions(&script_path, perms).unwrap();
+
+        let mut config = create_test_config();
+        config.budget = 6;
+        let config_path = dir.path().join("config.json");
+        fs::write(&config_path, serde_json::to_string(&config).unwrap()).unwrap();
+        let state_path = dir.path().join("state.json");
+
+        let metrics = Metrics::init(None, None, &HashMap::new()).unwrap();
+        run_command(
+            &config_path,
+            &script_path,
+            Some(&state_path),
+            None,
+            None,
+            Some(StrategyKind::Tpe),
+            None,
+            None,
+            &metrics,
+        )
+        .unwrap();
+
+        let state = load_state(&state_path).unwrap();
+        assert_eq!(state.history.len(), 6);
+        assert_eq!(state.config.strategy_param(StrategyKind::PARAM_KEY), None);
     }
 
+    #[cfg(unix)]
     #[test]
-    fn test_interactive_command_deserialization_tell() {
-        let json = r#"{"cmd": "tell", "results": []}"#;
-        let cmd: InteractiveCommand = serde_json::from_str(json).unwrap();
-        assert!(matches!(cmd, InteractiveCommand::Tell { results } if results.is_empty()));
+    fn test_run_command_streams_csv() {
+        use std::os::unix::fs::PermissionsExt;
+        use tempfile::tempdir;
+
+        let dir = tempdir().unwrap();
+        let script_path = dir.path().join("objective.sh");
+        fs::write(&script_path, "#!/bin/bash\necho \"RESULT=$ARQON_x\"").unwrap();
+        let mut perms = fs::metadata(&script_path).unwrap().permissions();
+        perms.set_mode(0o755);
+        fs::set_permissions(&script_path, perms).unwrap();
+
+        let mut config = create_test_config();
+        config.budget = 6;
+        config.bounds.insert(
+            "a".to_string(),
+            Domain {
+                min: 0.0,
+                max: 1.0,
+                scale: Scale::Linear,
+                active_when: None,
+            },
+        );
+        let config_path = dir.path().join("config.json");
+        fs::write(&config_path, serde_json::to_string(&config).unwrap()).unwrap();
+        let state_path = dir.path().join("state.json");
+        let csv_path = dir.path().join("history.csv");
+
+        let metrics = Metrics::init(None, None, &HashMap::new()).unwrap();
+        run_command(
+            &config_path,
+            &script_path,
+            Some(&state_path),
+            None,
+            Some(&csv_path),
+            None,
+            None,
+            None,
+            &metrics,
+        )
+        .unwrap();
+
+        let history = load_state(&state_path).unwrap().history;
+        let mut reader = csv::Reader::from_path(&csv_path).unwrap();
+        let header: Vec<String> = reader.headers().unwrap().iter().map(String::from).collect();
+        assert_eq!(
+            header,
+            ["eval", "value", "cost", "a", "x", "phase", "timestamp_us"]
+        );
+        let rows: Vec<csv::StringRecord> = reader.records().map(Result::unwrap).collect();
+        assert_eq!(rows.len(), history.len());
+        for (i, (row, point)) in rows.iter().zip(&history).enumerate() {
+            assert_eq!(row[0], i.to_string());
+            assert_eq!(row[1].parse::<f64>().unwrap(), point.value);
+            assert_eq!(row[4].parse::<f64>().unwrap(), point.params["x"]);
+            assert!(!row[5].is_empty());
+        }
+        assert_eq!(&rows[0][5], "probe");
     }
 
+    #[cfg(unix)]
     #[test]
-    fn test_interactive_ask_response_serialization() {
-        let response = InteractiveAskResponse { params: None };
-        let json = serde_json::to_string(&response).unwrap();
-        assert!(json.contains("\"params\":null"));
+    fn test_run_command_saves_metrics_snapshot() {
+        use std::os::unix::fs::PermissionsExt;
+        use tempfile::tempdir;
+
+        let dir = tempdir().unwrap();
+        let script_path = dir.path().join("objective.sh");
+        fs::write(&script_path, "#!/bin/bash\necho \"RESULT=0.5\"").unwrap();
+        let mut perms = fs::metadata(&script_path).unwrap().permissions();
+        perms.set_mode(0o755);
+        fs::set_permissions(&script_path, perms).unwrap();
+
+        let mut config = create_test_config();
+        config.budget = 6;
+        let config_path = dir.path().join("config.json");
+        fs::write(&config_path, serde_json::to_string(&config).unwrap()).unwrap();
+        let state_path = dir.path().join("state.json");
+
+        let metrics = Metrics::init(None, None, &HashMap::new()).unwrap();
+        run_command(
+            &config_path,
+            &script_path,
+            Some(&state_path),
+            None,
+            None,
+            None,
+            None,
+            None,
+            &metrics,
+        )
+        .unwrap();
+
+        let saved = load_state(&state_path).unwrap().metrics.unwrap();
+        let live = metrics.snapshot();
+        assert_eq!(saved.asks, metrics.ask_calls.get());
+        assert_eq!(saved.tells, metrics.tell_calls.get());
+        assert_eq!(saved.asks, saved.tells);
+        assert!(saved.asks >= 2, "probe and refine batches");
+        assert_eq!((saved.candidates, saved.results), (6, 6));
+        assert_eq!(saved.cost, 6.0);
+        let latency = saved.eval_seconds.unwrap();
+        let live_latency = live.eval_seconds.unwrap();
+        assert_eq!(latency.count, 6);
+        assert_eq!(
+            latency.count as u64,
+            metrics.eval_seconds.get_sample_count()
+        );
+        assert_eq!(
+            (latency.min, latency.max),
+            (live_latency.min, live_latency.max)
+        );
+        assert!((latency.mean - live_latency.mean).abs() < 1e-12);
+        assert!(latency.min <= latency.mean && latency.p95 <= latency.max);
     }
 
     #[test]
-    fn test_interactive_tell_response_serialization() {
-        let response = InteractiveTellResponse { ok: true };
-        let json = serde_json::to_string(&response).unwrap();
-        assert!(json.contains("\"ok\":true"));
+    fn test_latency_summary_nearest_rank_p95() {
+        let samples: Vec<f64> = (1..=20).map(f64::from).collect();
+        let summary = LatencySummary::from_samples(&samples).unwrap();
+        assert_eq!((summary.min, summary.p95, summary.max), (1.0, 19.0, 20.0));
+        assert_eq!(summary.mean, 10.5);
+        assert!(LatencySummary::from_samples(&[]).is_none());
     }
 
-    // ==================== LOAD STATE OR CONFIG TESTS ====================
-
+    #[cfg(unix)]
     #[test]
-    fn test_load_state_or_config_with_existing_state() {
-        use std::io::Write;
-        use tempfile::NamedTempFile;
+    fn test_run_command_non_finite_policy() {
+        use std::os::unix::fs::PermissionsExt;
+        use tempfile::tempdir;
 
-        // Create a config file
-        let mut config_file = NamedTempFile::new().unwrap();
-        writeln!(
-            config_file,
-            r#"{{
-            "seed": 42,
-            "budget": 20,
-            "probe_ratio": 0.5,
-            "bounds": {{"x": {{"min": 0.0, "max": 1.0}}}}
-        }}"#
+        let dir = tempdir().unwrap();
+        let script_path = dir.path().join("objective.sh");
+        fs::write(
+            &script_path,
+            "#!/bin/bash
+echo \"RESULT=nan\"",
         )
         .unwrap();
-
-        // Create a state file
-        let mut state_file = NamedTempFile::new().unwrap();
-        writeln!(
-            state_file,
-            r#"{{
-            "config": {{
-                "seed": 99,
-                "budget": 30,
-                "probe_ratio": 0.3,
-                "bounds": {{"y": {{"min": 0.0, "max": 2.0}}}}
-            }},
-            "history": [],
-            "run_id": "existing-run"
-        }}"#
+        let mut perms = fs::metadata(&script_path).unwrap().permissions();
+        perms.set_mode(0o755);
+        fs::set_permissions(&script_path, perms).unwrap();
+        let config_path = dir.path().join("config.json");
+        let state_path = dir.path().join("state.json");
+        let metrics = Metrics::init(None, None, &HashMap::new()).unwrap();
+
+        let mut config = create_test_config();
+        config.budget = 4;
+        fs::write(&config_path, serde_json::to_string(&config).unwrap()).unwrap();
+        let err = run_command(
+            &config_path,
+            &script_path,
+            Some(&state_path),
+            None,
+            None,
+            None,
+            None,
+            None,
+            &metrics,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("not finite"));
+
+        config.non_finite = NonFinitePolicy::Penalize { value: 1e6 };
+        fs::write(&config_path, serde_json::to_string(&config).unwrap()).unwrap();
+        run_command(
+            &config_path,
+            &script_path,
+            Some(&state_path),
+            None,
+            None,
+            None,
+            None,
+            None,
+            &metrics,
         )
         .unwrap();
+        let state = load_state(&state_path).unwrap();
+        assert_eq!(state.history.len(), 4);
+        assert!(state.history.iter().all(|p| p.value == 1e6 && p.penalized));
+
+        // The flag survives a reload into the solver.
+        let mut solver = Solver::pcr(state.config.clone());
+        solver.seed(state.history).unwrap();
+        assert!(solver.history.iter().all(|t| t.penalized));
+    }
 
-        let loaded =
-            load_state_or_config(config_file.path(), Some(&state_file.path().to_path_buf()))
-                .unwrap();
-        // Should use state's config, not the config file
-        assert_eq!(loaded.config.seed, 99);
-        assert_eq!(loaded.run_id, Some("existing-run".to_string()));
+    #[test]
+    fn test_solve_once_stops_when_interrupted() {
+        let mut solver = Solver::pcr(create_test_config());
+        let metrics = Metrics::init(None, None, &HashMap::new()).unwrap();
+        let interrupt = AtomicBool::new(true);
+
+        // The flag is checked before the script is ever run.
+        let outcome = solve_once(
+            &mut solver,
+            Path::new("/nonexistent/evaluate.sh"),
+            None,
+            None,
+            None,
+            &metrics,
+            Some(&interrupt),
+            None,
+            |_| Ok(()),
+        )
+        .unwrap();
+        assert!(matches!(outcome, SolveOutcome::Interrupted { ref pending } if pending.is_empty()));
+        assert!(solver.history.is_empty());
     }
 
+    #[cfg(unix)]
     #[test]
-    fn test_load_state_or_config_no_state_file() {
-        use std::io::Write;
-        use tempfile::NamedTempFile;
+    fn test_run_command_repeats_each_candidate() {
+        use std::os::unix::fs::PermissionsExt;
+        use tempfile::tempdir;
 
-        // Create a config file
-        let mut config_file = NamedTempFile::new().unwrap();
-        writeln!(
-            config_file,
-            r#"{{
-            "seed": 42,
-            "budget": 20,
-            "probe_ratio": 0.5,
-            "bounds": {{"x": {{"min": 0.0, "max": 1.0}}}}
-        }}"#
+        let dir = tempdir().unwrap();
+        let calls = dir.path().join("calls.log");
+        let script_path = dir.path().join("objective.sh");
+        // Alternates 1 and 3 across calls, so every point averages to 2.
+        fs::write(
+            &script_path,
+            format!(
+                "#!/bin/bash\necho x >> {0}\nn=$(wc -l < {0})\necho \"RESULT=$(( n % 2 * -2 + 3 ))\"",
+                calls.display()
+            ),
+        )
+        .unwrap();
+        let mut perms = fs::metadata(&script_path).unwrap().permissions();
+        perms.set_mode(0o755);
+        fs::set_permissions(&script_path, perms).unwrap();
+
+        let mut config = create_test_config();
+        config.budget = 4;
+        config.repeats = 2;
+        let config_path = dir.path().join("config.json");
+        fs::write(&config_path, serde_json::to_string(&config).unwrap()).unwrap();
+        let state_path = dir.path().join("state.json");
+
+        let metrics = Metrics::init(None, None, &HashMap::new()).unwrap();
+        run_command(
+            &config_path,
+            &script_path,
+            Some(&state_path),
+            None,
+            None,
+            None,
+            None,
+            None,
+            &metrics,
         )
         .unwrap();
 
-        let loaded = load_state_or_config(config_file.path(), None).unwrap();
-        assert_eq!(loaded.config.seed, 42);
-        assert!(loaded.history.is_empty());
-        assert!(loaded.run_id.is_none());
+        assert_eq!(fs::read_to_string(&calls).unwrap().lines().count(), 8);
+        let state = load_state(&state_path).unwrap();
+        assert_eq!(state.history.len(), 4);
+        assert!(state
+            .history
+            .iter()
+            .all(|p| p.value == 2.0 && p.cost == 2.0));
     }
 
+    #[cfg(unix)]
     #[test]
-    fn test_load_state_or_config_state_doesnt_exist() {
-        use std::io::Write;
-        use tempfile::NamedTempFile;
+    fn test_run_command_stops_at_wallclock_limit() {
+        use std::os::unix::fs::PermissionsExt;
+        use tempfile::tempdir;
 
-        // Create a config file
-        let mut config_file = NamedTempFile::new().unwrap();
-        writeln!(
-            config_file,
-            r#"{{
-            "seed": 42,
-            "budget": 20,
-            "probe_ratio": 0.5,
-            "bounds": {{"x": {{"min": 0.0, "max": 1.0}}}}
-        }}"#
+        let dir = tempdir().unwrap();
+        let script_path = dir.path().join("objective.sh");
+        fs::write(&script_path, "#!/bin/bash\nsleep 1.2\necho \"RESULT=1.0\"").unwrap();
+        let mut perms = fs::metadata(&script_path).unwrap().permissions();
+        perms.set_mode(0o755);
+        fs::set_permissions(&script_path, perms).unwrap();
+
+        let config_path = dir.path().join("config.json");
+        fs::write(
+            &config_path,
+            serde_json::to_string(&create_test_config()).unwrap(),
         )
         .unwrap();
+        let state_path = dir.path().join("state.json");
 
-        // Path that doesn't exist
-        let nonexistent = PathBuf::from("/tmp/nonexistent_state_12345.json");
-        let loaded = load_state_or_config(config_file.path(), Some(&nonexistent)).unwrap();
-        // Should fall back to config
-        assert_eq!(loaded.config.seed, 42);
-        assert!(loaded.run_id.is_none());
-    }
+        let metrics = Metrics::init(None, None, &HashMap::new()).unwrap();
+        let started = Instant::now();
+        run_command(
+            &config_path,
+            &script_path,
+            Some(&state_path),
+            None,
+            None,
+            None,
+            None,
+            Some(Duration::from_secs(1)),
+            &metrics,
+        )
+        .unwrap();
 
-    // ==================== VALIDATE COMMAND TESTS ====================
+        // The first evaluation outlives the limit but is not cut short.
+        assert!(started.elapsed() < Duration::from_secs(5));
+        let state = load_state(&state_path).unwrap();
+        assert_eq!(state.history.len(), 1);
+        assert_eq!(state.history[0].value, 1.0);
+    }
 
+    #[cfg(unix)]
     #[test]
-    fn test_validate_command_success() {
-        use std::io::Write;
-        use tempfile::NamedTempFile;
+    fn test_run_command_continue_on_error_records_failures() {
+        use std::os::unix::fs::PermissionsExt;
+        use tempfile::tempdir;
 
-        let mut config_file = NamedTempFile::new().unwrap();
-        writeln!(
-            config_file,
-            r#"{{
-            "seed": 42,
-            "budget": 20,
-            "probe_ratio": 0.5,
-            "bounds": {{"x": {{"min": 0.0, "max": 1.0}}}}
-        }}"#
+        let dir = tempdir().unwrap();
+        let script_path = dir.path().join("objective.sh");
+        // The evaluator crashes above 0.6; the optimum sits at 0.3.
+        fs::write(
+            &script_path,
+            "#!/bin/bash\nawk -v x=\"$ARQON_x\" 'BEGIN { if (x > 0.6) exit 1; print \"RESULT=\" (x - 0.3) ^ 2 }'",
         )
         .unwrap();
+        let mut perms = fs::metadata(&script_path).unwrap().permissions();
+        perms.set_mode(0o755);
+        fs::set_permissions(&script_path, perms).unwrap();
 
-        let result = validate_command(config_file.path());
-        assert!(result.is_ok());
-    }
+        let mut config = create_test_config();
+        config.budget = 60;
+        config.probe_ratio = ProbeBudget::Fixed(0.3);
+        let config_path = dir.path().join("config.json");
+        fs::write(&config_path, serde_json::to_string(&config).unwrap()).unwrap();
+        let state_path = dir.path().join("state.json");
+        let metrics = Metrics::init(None, None, &HashMap::new()).unwrap();
 
-    #[test]
-    fn test_validate_command_invalid_config() {
-        use std::io::Write;
-        use tempfile::NamedTempFile;
+        // Without the flag the first failure aborts the run.
+        assert!(run_command(
+            &config_path,
+            &script_path,
+            Some(&state_path),
+            None,
+            None,
+            None,
+            None,
+            None,
+            &metrics,
+        )
+        .is_err());
 
-        let mut config_file = NamedTempFile::new().unwrap();
-        writeln!(
-            config_file,
-            r#"{{
-            "seed": 42,
-            "budget": 0,
-            "probe_ratio": 0.5,
-            "bounds": {{"x": {{"min": 0.0, "max": 1.0}}}}
-        }}"#
+        run_command(
+            &config_path,
+            &script_path,
+            Some(&state_path),
+            None,
+            None,
+            None,
+            Some(1.0),
+            None,
+            &metrics,
         )
         .unwrap();
+        let state = load_state(&state_path).unwrap();
+        assert_eq!(state.history.len(), 60);
+        let failed = |p: &SeedPoint| p.failed;
+        assert!(state.history.iter().any(failed));
+        assert!(state
+            .history
+            .iter()
+            .filter(|p| failed(p))
+            .all(|p| p.value == 1.0));
+        assert!(state
+            .history
+            .iter()
+            .all(|p| failed(p) == (p.params["x"] > 0.6)));
 
-        let result = validate_command(config_file.path());
-        assert!(result.is_err());
+        let best = state
+            .history
+            .iter()
+            .min_by(|a, b| a.value.total_cmp(&b.value))
+            .unwrap();
+        assert!((best.params["x"] - 0.3).abs() < 0.05, "best {:?}", best);
+        // Refinement learns to stay out of the failing region.
+        let (probe, refine) = state.history.split_at(18);
+        let rate = |points: &[SeedPoint]| {
+            points.iter().filter(|p| failed(p)).count() as f64 / points.len() as f64
+        };
+        assert!(
+            rate(refine) < rate(probe),
+            "{} vs {}",
+            rate(refine),
+            rate(probe)
+        );
     }
 
-    // ==================== READ EVENT LINES TESTS ====================
-
     #[test]
-    fn test_read_event_lines_basic() {
-        use std::io::Write;
-        use tempfile::NamedTempFile;
-
-        let mut file = NamedTempFile::new().unwrap();
-        writeln!(file, r#"{{"event": "test1", "timestamp_us": 100}}"#).unwrap();
-        writeln!(file, r#"{{"event": "test2", "timestamp_us": 200}}"#).unwrap();
-        writeln!(file, r#"{{"event": "test3", "timestamp_us": 300}}"#).unwrap();
+    fn test_parse_seed_range() {
+        assert_eq!(parse_seed_range("0..20").unwrap(), 0..20);
+        assert_eq!(parse_seed_range("3..=5").unwrap(), 3..6);
+        assert_eq!(parse_seed_range("7").unwrap(), 7..8);
+        assert!(parse_seed_range("5..5").is_err());
+        assert!(parse_seed_range("a..3").is_err());
+    }
 
-        let lines = read_event_lines(file.path(), 10).unwrap();
-        assert_eq!(lines.len(), 3);
+    #[test]
+    fn test_summarize_trials() {
+        let trial = |seed, best_value, best_eval| BenchTrial {
+            seed,
+            best_value,
+            best_eval,
+            evaluations: 10,
+        };
+        let summary = summarize_trials(&[trial(0, 1.0, 2), trial(1, 4.0, 4), trial(2, 2.0, 9)]);
+        assert_eq!(summary.trials, 3);
+        assert!((summary.mean_best - 7.0 / 3.0).abs() < 1e-12);
+        assert_eq!(summary.median_best, 2.0);
+        assert!((summary.std_best - (7.0f64 / 3.0).sqrt()).abs() < 1e-12);
+        assert_eq!(summary.mean_best_eval, 5.0);
+
+        let single = summarize_trials(&[trial(0, 1.5, 1)]);
+        assert_eq!(single.median_best, 1.5);
+        assert_eq!(single.std_best, 0.0);
     }
 
+    #[cfg(unix)]
     #[test]
-    fn test_read_event_lines_limit() {
-        use std::io::Write;
-        use tempfile::NamedTempFile;
+    fn test_bench_command_runs_each_seed() {
+        use std::os::unix::fs::PermissionsExt;
+        use tempfile::tempdir;
 
-        let mut file = NamedTempFile::new().unwrap();
-        for i in 0..10 {
-            writeln!(
-                file,
-                r#"{{"event": "test{}", "timestamp_us": {}}}"#,
-                i,
-                i * 100
-            )
-            .unwrap();
+        let dir = tempdir().unwrap();
+        let script_path = dir.path().join("objective.sh");
+        fs::write(
+            &script_path,
+            "#!/bin/bash\nawk -v x=\"$ARQON_x\" 'BEGIN { print \"RESULT=\" (x - 0.3) ^ 2 }'",
+        )
+        .unwrap();
+        let mut perms = fs::metadata(&script_path).unwrap().permissions();
+        perms.set_mode(0o755);
+        fs::set_permissions(&script_path, perms).unwrap();
+        let mut config = create_test_config();
+        config.budget = 8;
+        let config_path = dir.path().join("config.json");
+        fs::write(&config_path, serde_json::to_string(&config).unwrap()).unwrap();
+        let out_path = dir.path().join("bench.json");
+
+        let metrics = Metrics::init(None, None, &HashMap::new()).unwrap();
+        bench_command(&config_path, &script_path, 0..3, Some(&out_path), &metrics).unwrap();
+
+        let report: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&out_path).unwrap()).unwrap();
+        let trials = report["trials"].as_array().unwrap();
+        assert_eq!(trials.len(), 3);
+        for (seed, trial) in trials.iter().enumerate() {
+            assert_eq!(trial["seed"], seed as u64);
+            assert_eq!(trial["evaluations"], 8);
+            let best_eval = trial["best_eval"].as_u64().unwrap();
+            assert!((1..=8).contains(&best_eval));
         }
+        assert_eq!(report["summary"]["trials"], 3);
+        assert!(report["summary"]["mean_best"].as_f64().unwrap() >= 0.0);
+    }
 
-        let lines = read_event_lines(file.path(), 3).unwrap();
-        // Should only get the last 3
-        assert_eq!(lines.len(), 3);
+    #[test]
+    fn test_grid_axis_slides_inside_bounds() {
+        let domain = Domain {
+            min: 0.0,
+            max: 10.0,
+            scale: arqonhpo_core::config::Scale::Linear,
+            active_when: None,
+        };
+        let assert_axis = |center: f64, span: f64, expected: &[f64]| {
+            let axis = grid_axis(&domain, center, span, expected.len());
+            assert!(
+                axis.iter().zip(expected).all(|(a, e)| (a - e).abs() < 1e-9),
+                "{:?} != {:?}",
+                axis,
+                expected
+            );
+        };
+        assert_axis(5.0, 0.4, &[3.0, 4.0, 5.0, 6.0, 7.0]);
+        // Near a bound the window keeps its width and ends exactly on it.
+        assert_axis(9.5, 0.4, &[6.0, 8.0, 10.0]);
+        assert_axis(-3.0, 0.4, &[0.0, 2.0, 4.0]);
+        // A span over 1 covers the whole range.
+        assert_axis(2.0, 3.0, &[0.0, 5.0, 10.0]);
     }
 
     #[test]
-    fn test_read_event_lines_filters_invalid_json() {
-        use std::io::Write;
-        use tempfile::NamedTempFile;
+    fn test_parse_span() {
+        assert_eq!(parse_span("0.25"), Ok(0.25));
+        assert_eq!(parse_span("3"), Ok(3.0));
+        for spec in ["nan", "inf", "0", "-0.1", "wide"] {
+            assert!(parse_span(spec).is_err(), "{}", spec);
+        }
+    }
 
-        let mut file = NamedTempFile::new().unwrap();
-        writeln!(file, "not valid json").unwrap();
-        writeln!(file, r#"{{"event": "valid", "timestamp_us": 100}}"#).unwrap();
-        writeln!(file, "also invalid").unwrap();
+    #[test]
+    fn test_surface_grid_is_row_major() {
+        let mut config = create_test_config();
+        for name in ["a", "b", "c"] {
+            config.bounds.insert(
+                name.to_string(),
+                Domain {
+                    min: 0.0,
+                    max: 1.0,
+                    scale: arqonhpo_core::config::Scale::Linear,
+                    active_when: None,
+                },
+            );
+        }
+        let center = HashMap::from([
+            ("a".to_string(), 0.5),
+            ("b".to_string(), 0.5),
+            ("c".to_string(), 0.25),
+        ]);
+        let dims = ["a".to_string(), "b".to_string()];
+        let grid = surface_grid(&config, &center, &dims, 1.0, 3).unwrap();
+        assert_eq!(grid.x, vec![0.0, 0.5, 1.0]);
+        assert_eq!(grid.y, vec![0.0, 0.5, 1.0]);
+        for (row, &y) in grid.y.iter().enumerate() {
+            for (col, &x) in grid.x.iter().enumerate() {
+                let point = &grid.candidates[row * grid.x.len() + col];
+                assert_eq!((point["a"], point["b"], point["c"]), (x, y, 0.25));
+            }
+        }
 
-        let lines = read_event_lines(file.path(), 10).unwrap();
-        assert_eq!(lines.len(), 1); // Only the valid JSON line
+        let values = grid
+            .candidates
+            .iter()
+            .map(|p| Some(p["a"] + 10.0 * p["b"]))
+            .collect();
+        let report = grid.report(values, center.clone());
+        assert_eq!(report.values[0], vec![Some(0.0), Some(0.5), Some(1.0)]);
+        assert_eq!(report.values[2][0], Some(10.0));
+
+        let unknown = ["a".to_string(), "zzz".to_string()];
+        assert!(surface_grid(&config, &center, &unknown, 1.0, 3).is_err());
+        assert!(surface_grid(&config, &center, &dims[..1], 1.0, 3).is_err());
     }
 
+    #[cfg(unix)]
     #[test]
-    fn test_read_event_lines_file_not_found() {
-        let result = read_event_lines(Path::new("/nonexistent/events.json"), 10);
-        assert!(result.is_err());
+    fn test_explore_command_evaluates_grid() {
+        use std::os::unix::fs::PermissionsExt;
+        use tempfile::tempdir;
+
+        let dir = tempdir().unwrap();
+        let script_path = dir.path().join("objective.sh");
+        fs::write(
+            &script_path,
+            "#!/bin/bash\nawk -v x=\"$ARQON_x\" -v y=\"$ARQON_y\" 'BEGIN { print \"RESULT=\" x + 10 * y }'",
+        )
+        .unwrap();
+        let mut perms = fs::metadata(&script_path).unwrap().permissions();
+        perms.set_mode(0o755);
+        fs::set_permissions(&script_path, perms).unwrap();
+        let mut state = create_test_state();
+        state.config.bounds.insert(
+            "y".to_string(),
+            Domain {
+                min: 0.0,
+                max: 1.0,
+                scale: arqonhpo_core::config::Scale::Linear,
+                active_when: None,
+            },
+        );
+        state.history = vec![SeedPoint {
+            params: HashMap::from([("x".to_string(), 0.5), ("y".to_string(), 0.5)]),
+            value: 0.0,
+            cost: 1.0,
+            penalized: false,
+            failed: false,
+            feasibility: None,
+        }];
+        let state_path = dir.path().join("state.json");
+        save_state(&state_path, &state).unwrap();
+        let out_path = dir.path().join("surface.json");
+
+        let dims = ["x".to_string(), "y".to_string()];
+        explore_command(
+            &state_path,
+            Around::Best,
+            2,
+            &dims,
+            1.0,
+            Some(&script_path),
+            None,
+            Some(&out_path),
+        )
+        .unwrap();
+
+        let report: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&out_path).unwrap()).unwrap();
+        assert_eq!(report["dims"], serde_json::json!(["x", "y"]));
+        assert_eq!(
+            report["values"],
+            serde_json::json!([[0.0, 1.0], [10.0, 11.0]])
+        );
     }
 
     // ==================== EVALUATE SCRIPT TESTS ====================
@@ -1827,13 +6993,65 @@ mod tests {
         assert!((result.unwrap() - 0.123).abs() < 0.001);
     }
 
+    #[test]
+    fn test_script_env_names_sanitize_and_detect_collisions() {
+        let domain = |min, max| Domain {
+            min,
+            max,
+            scale: Scale::Linear,
+            active_when: None,
+        };
+        let mut config = create_test_config();
+        config
+            .bounds
+            .insert("lr.decay".to_string(), domain(0.0, 1.0));
+        config
+            .bounds
+            .insert("batch-size".to_string(), domain(1.0, 64.0));
+        let names = script_env_names(&config).unwrap();
+        assert_eq!(names["x"], "ARQON_x");
+        assert_eq!(names["lr.decay"], "ARQON_lr_decay");
+        assert_eq!(names["batch-size"], "ARQON_batch_size");
+
+        // `lr.decay` and `lr_decay` would both be ARQON_lr_decay.
+        config
+            .bounds
+            .insert("lr_decay".to_string(), domain(0.0, 1.0));
+        let err = script_env_names(&config).unwrap_err();
+        assert!(err.to_string().contains("ARQON_lr_decay"), "{}", err);
+        assert!(check_script_env(&config).is_err());
+
+        // Names differing only in case collide on case-insensitive platforms.
+        let mut config = create_test_config();
+        config.bounds.insert("X".to_string(), domain(0.0, 1.0));
+        assert!(script_env_names(&config).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_evaluate_script_sanitized_env_var() {
+        use std::os::unix::fs::PermissionsExt;
+        use tempfile::tempdir;
+
+        let dir = tempdir().unwrap();
+        let script_path = dir.path().join("test_script.sh");
+        fs::write(&script_path, "#!/bin/bash\necho $ARQON_lr_decay").unwrap();
+        let mut perms = fs::metadata(&script_path).unwrap().permissions();
+        perms.set_mode(0o755);
+        fs::set_permissions(&script_path, perms).unwrap();
+
+        let params = HashMap::from([("lr.decay".to_string(), 0.25)]);
+        let result = evaluate_script(&script_path, &params).unwrap();
+        assert!((result - 0.25).abs() < 1e-12);
+    }
+
     // ==================== START METRICS SERVER TEST ====================
 
     #[test]
     fn test_metrics_init_with_server_address() {
         // Test that init with an address spawns a thread (just verify it doesn't panic)
         // Using port 0 to let OS assign an available port
-        let metrics = Metrics::init(Some("127.0.0.1:0"));
+        let metrics = Metrics::init(Some("127.0.0.1:0"), None, &HashMap::new());
         assert!(metrics.is_ok());
     }
 
@@ -1890,14 +7108,31 @@ mod tests {
                 min: 0.0,
                 max: 1.0,
                 scale: arqonhpo_core::config::Scale::Linear,
+                active_when: None,
             },
         );
         SolverConfig {
             bounds,
             budget: 10,
-            probe_ratio: 0.5,
+            probe_ratio: ProbeBudget::Fixed(0.5),
             seed: 42,
             strategy_params: None,
+            constraints: Vec::new(),
+            selection: Default::default(),
+            cost_budget: None,
+            observation_noise: None,
+            max_restarts: 0,
+            non_finite: Default::default(),
+            repeats: 0,
+            repeat_reducer: Default::default(),
+            objective_transform: None,
+            fixed: Default::default(),
+            target_value: None,
+            target_tol: 0.0,
+            history_cap: None,
+            invalid_params: Default::default(),
+            infeasibility_penalty: 0.0,
+            initial_points: Vec::new(),
         }
     }
 
@@ -1908,8 +7143,15 @@ mod tests {
                 params: [("x".to_string(), 0.5)].into_iter().collect(),
                 value: 1.0,
                 cost: 1.0,
+                penalized: false,
+                failed: false,
+                feasibility: None,
             }],
             run_id: Some("test-run".to_string()),
+            warm_start: false,
+            compacted: Default::default(),
+            suggest: None,
+            metrics: None,
         }
     }
 
@@ -1930,8 +7172,8 @@ mod tests {
         )
         .unwrap();
 
-        let metrics = Metrics::init(None).unwrap();
-        let result = ask_command(config_file.path(), None, None, &metrics);
+        let metrics = Metrics::init(None, None, &HashMap::new()).unwrap();
+        let result = ask_command(config_file.path(), None, None, false, false, &metrics);
         assert!(result.is_ok());
     }
 
@@ -1952,8 +7194,8 @@ mod tests {
         )
         .unwrap();
 
-        let metrics = Metrics::init(None).unwrap();
-        let result = ask_command(config_file.path(), None, Some(2), &metrics);
+        let metrics = Metrics::init(None, None, &HashMap::new()).unwrap();
+        let result = ask_command(config_file.path(), None, Some(2), false, false, &metrics);
         assert!(result.is_ok());
     }
 
@@ -1976,44 +7218,115 @@ mod tests {
         )
         .unwrap();
 
-        let metrics = Metrics::init(None).unwrap();
+        let metrics = Metrics::init(None, None, &HashMap::new()).unwrap();
         let result = ask_command(
             config_file.path(),
             Some(&state_file.path().to_path_buf()),
             None,
+            false,
+            false,
+            &metrics,
+        );
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_tell_command_basic() {
+        use tempfile::{tempdir, NamedTempFile};
+
+        let dir = tempdir().unwrap();
+        let state_path = dir.path().join("state.json");
+        let state = create_test_state();
+        fs::write(&state_path, serde_json::to_string(&state).unwrap()).unwrap();
+
+        let mut results_file = NamedTempFile::new().unwrap();
+        std::io::Write::write_all(
+            &mut results_file,
+            br#"[{"params": {"x": 0.7}, "value": 0.5, "cost": 1.0}]"#,
+        )
+        .unwrap();
+
+        let metrics = Metrics::init(None, None, &HashMap::new()).unwrap();
+        let result = tell_command(
+            &state_path,
+            Some(&results_file.path().to_path_buf()),
+            false,
             &metrics,
         );
         assert!(result.is_ok());
+
+        // Verify state was updated
+        let updated_state: SolverState =
+            serde_json::from_str(&fs::read_to_string(&state_path).unwrap()).unwrap();
+        assert_eq!(updated_state.history.len(), 2);
+    }
+
+    #[test]
+    fn test_tell_command_reads_feasibility_field() {
+        let dir = tempfile::tempdir().unwrap();
+        let state_path = dir.path().join("state.json");
+        let results_path = dir.path().join("results.json");
+        save_state(&state_path, &create_test_state()).unwrap();
+        fs::write(
+            &results_path,
+            r#"[{"params": {"x": 0.7}, "value": 0.5, "cost": 1.0, "feasibility": 0.25}]"#,
+        )
+        .unwrap();
+
+        let metrics = Metrics::init(None, None, &HashMap::new()).unwrap();
+        tell_command(&state_path, Some(&results_path), false, &metrics).unwrap();
+        let state = load_state(&state_path).unwrap();
+        let told = state.history.last().unwrap();
+        assert_eq!(told.feasibility, Some(0.25));
+        assert_eq!(told.params.len(), 1);
+    }
+
+    #[test]
+    fn test_tell_command_rejects_values_outside_transform_domain() {
+        let dir = tempfile::tempdir().unwrap();
+        let state_path = dir.path().join("state.json");
+        let results_path = dir.path().join("results.json");
+        let mut state = create_test_state();
+        state.config.objective_transform = Some(ObjectiveTransform::Log);
+        save_state(&state_path, &state).unwrap();
+        fs::write(
+            &results_path,
+            r#"[{"params": {"x": 0.7}, "value": 0.0, "cost": 1.0}]"#,
+        )
+        .unwrap();
+
+        let metrics = Metrics::init(None, None, &HashMap::new()).unwrap();
+        let err = tell_command(&state_path, Some(&results_path), false, &metrics).unwrap_err();
+        assert!(err.to_string().contains("strictly positive"), "{}", err);
+        assert_eq!(load_state(&state_path).unwrap().history.len(), 1);
     }
 
     #[test]
-    fn test_tell_command_basic() {
-        use tempfile::{tempdir, NamedTempFile};
+    fn test_suggest_command_persists_batch_remainder() {
+        use tempfile::tempdir;
 
         let dir = tempdir().unwrap();
         let state_path = dir.path().join("state.json");
-        let state = create_test_state();
+        let mut state = create_test_state();
+        state.history.clear();
         fs::write(&state_path, serde_json::to_string(&state).unwrap()).unwrap();
 
-        let mut results_file = NamedTempFile::new().unwrap();
-        std::io::Write::write_all(
-            &mut results_file,
-            br#"[{"params": {"x": 0.7}, "value": 0.5, "cost": 1.0}]"#,
-        )
-        .unwrap();
+        let metrics = Metrics::init(None, None, &HashMap::new()).unwrap();
+        let read_cursor = || {
+            let state: SolverState =
+                serde_json::from_str(&fs::read_to_string(&state_path).unwrap()).unwrap();
+            state.suggest.unwrap()
+        };
 
-        let metrics = Metrics::init(None).unwrap();
-        let result = tell_command(
-            &state_path,
-            Some(&results_file.path().to_path_buf()),
-            &metrics,
-        );
-        assert!(result.is_ok());
+        suggest_command(&state_path, &metrics).unwrap();
+        let first = read_cursor();
+        assert_eq!(first.until, 5);
+        assert_eq!(first.pending.len(), 4);
 
-        // Verify state was updated
-        let updated_state: SolverState =
-            serde_json::from_str(&fs::read_to_string(&state_path).unwrap()).unwrap();
-        assert_eq!(updated_state.history.len(), 2);
+        suggest_command(&state_path, &metrics).unwrap();
+        let second = read_cursor();
+        assert_eq!(second.until, 5);
+        assert_eq!(second.pending, first.pending[1..].to_vec());
     }
 
     #[test]
@@ -2027,8 +7340,15 @@ mod tests {
         let state = create_test_state();
         fs::write(&state_path, serde_json::to_string(&state).unwrap()).unwrap();
 
-        let metrics = Metrics::init(None).unwrap();
-        let result = export_command(&state_path, Some(&output_path), None, &metrics);
+        let metrics = Metrics::init(None, None, &HashMap::new()).unwrap();
+        let result = export_command(
+            &state_path,
+            Some(&output_path),
+            None,
+            false,
+            ExportFormat::Json,
+            &metrics,
+        );
         assert!(result.is_ok());
 
         // Verify artifact was created
@@ -2049,11 +7369,13 @@ mod tests {
         let state = create_test_state();
         fs::write(&state_path, serde_json::to_string(&state).unwrap()).unwrap();
 
-        let metrics = Metrics::init(None).unwrap();
+        let metrics = Metrics::init(None, None, &HashMap::new()).unwrap();
         let result = export_command(
             &state_path,
             Some(&output_path),
             Some("custom-run".to_string()),
+            false,
+            ExportFormat::Json,
             &metrics,
         );
         assert!(result.is_ok());
@@ -2063,6 +7385,141 @@ mod tests {
         assert_eq!(artifact.run_id, "custom-run");
     }
 
+    #[test]
+    fn test_export_command_with_importance() {
+        use tempfile::tempdir;
+
+        let dir = tempdir().unwrap();
+        let state_path = dir.path().join("state.json");
+        let output_path = dir.path().join("artifact.json");
+
+        let mut state = create_test_state();
+        state.history = (0..6)
+            .map(|i| SeedPoint {
+                params: [("x".to_string(), i as f64 / 5.0)].into_iter().collect(),
+                value: i as f64,
+                cost: 1.0,
+                penalized: false,
+                failed: false,
+                feasibility: None,
+            })
+            .collect();
+        fs::write(&state_path, serde_json::to_string(&state).unwrap()).unwrap();
+
+        let metrics = Metrics::init(None, None, &HashMap::new()).unwrap();
+        export_command(
+            &state_path,
+            Some(&output_path),
+            None,
+            true,
+            ExportFormat::Json,
+            &metrics,
+        )
+        .unwrap();
+
+        let artifact: RunArtifact =
+            serde_json::from_str(&fs::read_to_string(&output_path).unwrap()).unwrap();
+        let importance = artifact.importance.unwrap();
+        assert!((importance["x"] - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_export_command_csv_round_trips() {
+        use tempfile::tempdir;
+
+        let dir = tempdir().unwrap();
+        let state_path = dir.path().join("state.json");
+        let output_path = dir.path().join("history.csv");
+
+        // `depth` is conditional: only the later points carry it.
+        let mut state = create_test_state();
+        state.history = (0..8)
+            .map(|i| {
+                let mut params: HashMap<String, f64> =
+                    [("x".to_string(), i as f64 / 7.0)].into_iter().collect();
+                if i >= 4 {
+                    params.insert("depth".to_string(), i as f64);
+                }
+                SeedPoint {
+                    params,
+                    value: (i as f64 / 7.0 - 0.3).powi(2),
+                    cost: 0.5 + i as f64,
+                    penalized: false,
+                    failed: i == 6,
+                    feasibility: (i == 6).then_some(0.25),
+                }
+            })
+            .collect();
+        fs::write(&state_path, serde_json::to_string(&state).unwrap()).unwrap();
+
+        let metrics = Metrics::init(None, None, &HashMap::new()).unwrap();
+        assert_eq!(ExportFormat::from_path(&output_path), ExportFormat::Csv);
+        export_command(
+            &state_path,
+            Some(&output_path),
+            None,
+            false,
+            ExportFormat::Csv,
+            &metrics,
+        )
+        .unwrap();
+
+        let mut reader = csv::Reader::from_path(&output_path).unwrap();
+        assert_eq!(
+            reader.headers().unwrap(),
+            vec![
+                "eval_id",
+                "value",
+                "cost",
+                "phase",
+                "fidelity",
+                "failed",
+                "feasibility",
+                "depth",
+                "x"
+            ]
+        );
+        let rows: Vec<csv::StringRecord> = reader.records().map(Result::unwrap).collect();
+        let state = load_state(&state_path).unwrap();
+        assert_eq!(rows.len(), state.history.len());
+        // Probe budget is ceil(10 * 0.5) = 5.
+        let probe_points = Solver::pcr(state.config.clone()).plan().probe_points;
+        for (i, (row, point)) in rows.iter().zip(&state.history).enumerate() {
+            assert_eq!(row[0].parse::<u64>().unwrap(), i as u64 + 1);
+            assert_eq!(row[1].parse::<f64>().unwrap(), point.value);
+            assert_eq!(row[2].parse::<f64>().unwrap(), point.cost);
+            let phase = if i < probe_points { "probe" } else { "refine" };
+            assert_eq!(&row[3], phase);
+            assert_eq!(&row[4], "");
+            assert_eq!(&row[5], if i == 6 { "true" } else { "false" });
+            assert_eq!(&row[6], if i == 6 { "0.25" } else { "" });
+            match point.params.get("depth") {
+                Some(&depth) => assert_eq!(row[7].parse::<f64>().unwrap(), depth),
+                None => assert_eq!(&row[7], ""),
+            }
+            assert_eq!(row[8].parse::<f64>().unwrap(), point.params["x"]);
+        }
+
+        // A parameter named like a fixed column cannot be told apart from it.
+        let mut state = state;
+        for point in &mut state.history {
+            point.params.insert("phase".to_string(), 1.0);
+        }
+        fs::write(&state_path, serde_json::to_string(&state).unwrap()).unwrap();
+        let err = export_command(
+            &state_path,
+            Some(&output_path),
+            None,
+            false,
+            ExportFormat::Csv,
+            &metrics,
+        )
+        .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("\"phase\" has the name of a fixed CSV column"));
+    }
+
     #[test]
     fn test_import_command_basic() {
         use tempfile::tempdir;
@@ -2073,6 +7530,8 @@ mod tests {
 
         // Create artifact
         let artifact = RunArtifact {
+            format_version: ARTIFACT_FORMAT_VERSION,
+            checksum: None,
             run_id: "imported-run".to_string(),
             seed: 42,
             budget: 10,
@@ -2082,12 +7541,21 @@ mod tests {
                 params: [("x".to_string(), 0.5)].into_iter().collect(),
                 value: 1.0,
                 cost: 1.0,
+                repeats: None,
+                penalized: false,
+                failed: false,
+                feasibility: None,
+                penalty: None,
+                fidelity: None,
+                kind: None,
+                source: None,
             }],
+            importance: None,
         };
         fs::write(&artifact_path, serde_json::to_string(&artifact).unwrap()).unwrap();
 
-        let metrics = Metrics::init(None).unwrap();
-        let result = import_command(&artifact_path, &state_path, &metrics);
+        let metrics = Metrics::init(None, None, &HashMap::new()).unwrap();
+        let result = import_command(&artifact_path, &state_path, false, &metrics);
         assert!(result.is_ok());
 
         // Verify state was created
@@ -2095,6 +7563,541 @@ mod tests {
             serde_json::from_str(&fs::read_to_string(&state_path).unwrap()).unwrap();
         assert_eq!(state.run_id, Some("imported-run".to_string()));
         assert_eq!(state.history.len(), 1);
+        assert!(!state.warm_start);
+    }
+
+    #[test]
+    fn test_import_rejects_tampered_export() {
+        use tempfile::tempdir;
+
+        let dir = tempdir().unwrap();
+        let state_path = dir.path().join("state.json");
+        let artifact_path = dir.path().join("artifact.json");
+        fs::write(
+            &state_path,
+            serde_json::to_string(&create_test_state()).unwrap(),
+        )
+        .unwrap();
+
+        let metrics = Metrics::init(None, None, &HashMap::new()).unwrap();
+        export_command(
+            &state_path,
+            Some(&artifact_path),
+            None,
+            false,
+            ExportFormat::Json,
+            &metrics,
+        )
+        .unwrap();
+        let mut artifact: RunArtifact =
+            serde_json::from_str(&fs::read_to_string(&artifact_path).unwrap()).unwrap();
+        assert_eq!(artifact.format_version, ARTIFACT_FORMAT_VERSION);
+        assert!(artifact.checksum.is_some());
+
+        let imported = dir.path().join("imported.json");
+        assert!(import_command(&artifact_path, &imported, false, &metrics).is_ok());
+
+        artifact.history[0].value += 1.0;
+        fs::write(&artifact_path, serde_json::to_string(&artifact).unwrap()).unwrap();
+        let err = import_command(&artifact_path, &imported, false, &metrics).unwrap_err();
+        assert!(format!("{:?}", err).contains("checksum mismatch"));
+    }
+
+    fn recorded_artifact() -> RunArtifact {
+        let config = create_test_config();
+        let mut solver = Solver::pcr(config.clone());
+        let mut history = Vec::new();
+        while let Some(batch) = solver.ask() {
+            let results: Vec<SeedPoint> = batch
+                .into_iter()
+                .map(|params| SeedPoint {
+                    value: (params["x"] - 0.3).powi(2),
+                    params,
+                    cost: 1.0,
+                    penalized: false,
+                    failed: false,
+                    feasibility: None,
+                })
+                .collect();
+            for result in &results {
+                history.push(EvalTrace {
+                    eval_id: history.len() as u64 + 1,
+                    params: result.params.clone(),
+                    value: result.value,
+                    cost: result.cost,
+                    repeats: None,
+                    penalized: false,
+                    failed: false,
+                    feasibility: None,
+                    penalty: None,
+                    fidelity: None,
+                    kind: None,
+                    source: None,
+                });
+            }
+            solver.seed(results).unwrap();
+        }
+        RunArtifact {
+            format_version: ARTIFACT_FORMAT_VERSION,
+            checksum: None,
+            run_id: "replay".to_string(),
+            seed: config.seed,
+            budget: config.budget,
+            config,
+            history,
+            importance: None,
+        }
+    }
+
+    #[test]
+    fn test_replay_artifact_reproduces_run() {
+        let artifact = recorded_artifact();
+        let report = replay_artifact(&artifact, 1e-12).unwrap();
+        assert!(report.passed, "{:?}", report.divergence);
+        assert_eq!(report.checked, artifact.history.len());
+        assert!(report.steps > 1);
+    }
+
+    #[test]
+    fn test_replay_artifact_reports_first_divergence() {
+        let mut artifact = recorded_artifact();
+        let index = artifact.history.len() - 1;
+        *artifact.history[index].params.get_mut("x").unwrap() += 0.01;
+
+        let report = replay_artifact(&artifact, 1e-9).unwrap();
+        assert!(!report.passed);
+        let divergence = report.divergence.unwrap();
+        assert_eq!(divergence.eval_index, index);
+        assert_eq!(divergence.param.as_deref(), Some("x"));
+        assert!(replay_command_result_is_err(&artifact));
+    }
+
+    #[test]
+    fn test_replay_artifact_detects_seed_change() {
+        let mut artifact = recorded_artifact();
+        artifact.seed += 1;
+        let report = replay_artifact(&artifact, 1e-9).unwrap();
+        assert!(!report.passed);
+        assert!(report.divergence.is_some());
+    }
+
+    #[test]
+    fn test_replay_artifact_returns_rejected_results_as_error() {
+        let mut artifact = recorded_artifact();
+        artifact.history[0].value = f64::NAN;
+        let err = replay_artifact(&artifact, 1e-9).unwrap_err();
+        assert!(err.to_string().contains("replay step 1"), "{}", err);
+    }
+
+    fn artifact_from(
+        run_id: &str,
+        bounds: &[(&str, f64, f64)],
+        points: &[(f64, f64)],
+    ) -> RunArtifact {
+        let mut config = create_test_config();
+        config.bounds = bounds
+            .iter()
+            .map(|&(name, min, max)| {
+                (
+                    name.to_string(),
+                    Domain {
+                        min,
+                        max,
+                        scale: Scale::Linear,
+                        active_when: None,
+                    },
+                )
+            })
+            .collect();
+        let history = points
+            .iter()
+            .enumerate()
+            .map(|(i, &(x, value))| EvalTrace {
+                eval_id: i as u64 + 1,
+                params: bounds
+                    .iter()
+                    .map(|&(name, _, _)| (name.to_string(), x))
+                    .collect(),
+                value,
+                cost: 1.0,
+                repeats: None,
+                penalized: false,
+                failed: false,
+                feasibility: None,
+                penalty: None,
+                fidelity: None,
+                kind: None,
+                source: None,
+            })
+            .collect();
+        RunArtifact {
+            format_version: ARTIFACT_FORMAT_VERSION,
+            checksum: None,
+            run_id: run_id.to_string(),
+            seed: 42,
+            budget: 10,
+            config,
+            history,
+            importance: None,
+        }
+    }
+
+    #[test]
+    fn test_compare_artifacts_convergence_and_basin() {
+        let a = artifact_from(
+            "a",
+            &[("x", 0.0, 1.0)],
+            &[(0.9, 5.0), (0.31, 1.005), (0.3, 1.0), (0.5, 2.0)],
+        );
+        let b = artifact_from("b", &[("x", 0.0, 1.0)], &[(0.35, 1.2), (0.9, 3.0)]);
+        let report = compare_artifacts(&a, &b, 0.1).unwrap();
+
+        assert_eq!((report.a.best_eval, report.a.converged_eval), (3, 2));
+        assert_eq!((report.b.best_eval, report.b.converged_eval), (1, 1));
+        assert_eq!(report.a.evaluations, 4);
+        assert!((report.params[0].delta.unwrap() - 0.05).abs() < 1e-12);
+        assert!((report.distance.unwrap() - 0.05).abs() < 1e-12);
+        assert_eq!(report.same_basin, Some(true));
+        assert_eq!(
+            compare_artifacts(&a, &b, 0.01).unwrap().same_basin,
+            Some(false)
+        );
+        assert!(format_compare(&report).contains("same basin"));
+    }
+
+    #[test]
+    fn test_compare_run_maximizes_negated_objective() {
+        let mut artifact = artifact_from(
+            "a",
+            &[("x", 0.0, 1.0)],
+            &[(0.2, 1.0), (0.4, 3.0), (0.6, 2.995)],
+        );
+        artifact.config.objective_transform = Some(ObjectiveTransform::Negate);
+        let run = compare_run(&artifact).unwrap();
+        assert_eq!(run.best_value, 3.0);
+        assert_eq!((run.best_eval, run.converged_eval), (2, 2));
+    }
+
+    #[test]
+    fn test_compare_artifacts_with_differing_bounds() {
+        // B widens x and adds y; x is normalized over the union [0, 2].
+        let a = artifact_from("a", &[("x", 0.0, 1.0)], &[(0.5, 1.0)]);
+        let b = artifact_from("b", &[("x", 0.0, 2.0), ("y", 0.0, 1.0)], &[(1.5, 0.5)]);
+        let report = compare_artifacts(&a, &b, 0.1).unwrap();
+
+        let names: Vec<&str> = report.params.iter().map(|p| p.param.as_str()).collect();
+        assert_eq!(names, ["x", "y"]);
+        assert_eq!(report.params[0].unit_delta, Some(0.5));
+        assert_eq!(
+            report.params[1],
+            ParamDelta {
+                param: "y".to_string(),
+                a: None,
+                b: Some(1.5),
+                delta: None,
+                unit_delta: None,
+            }
+        );
+        assert_eq!(report.distance, Some(0.5));
+        assert_eq!(report.same_basin, Some(false));
+
+        let disjoint = artifact_from("c", &[("z", 0.0, 1.0)], &[(0.5, 1.0)]);
+        let report = compare_artifacts(&a, &disjoint, 0.1).unwrap();
+        assert_eq!(report.same_basin, None);
+        assert!(format_compare(&report).contains("No shared parameters"));
+    }
+
+    #[test]
+    fn test_infer_config_scales_and_margins() {
+        let csv = "lr,layers,dropout,offset,fixed,value\n\
+                   0.0001,2,0.1,-1,5,1.0\n\
+                   0.01,4,0.3,0.5,5,0.5\n\
+                   0.1,8,,1,5,0.7\n";
+        let config = infer_config(csv.as_bytes(), 0.1, "value", 50).unwrap();
+        assert_eq!(config.budget, 50);
+        assert!(!config.bounds.contains_key("value"));
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-9 * b.abs().max(1.0);
+
+        let lr = &config.bounds["lr"];
+        assert_eq!(lr.scale, Scale::Log);
+        assert!(close(lr.min, 1e-4 * 1000f64.powf(-0.1)), "{}", lr.min);
+        assert!(close(lr.max, 0.1 * 1000f64.powf(0.1)), "{}", lr.max);
+
+        let layers = &config.bounds["layers"];
+        assert_eq!(layers.scale, Scale::Linear);
+        assert!(close(layers.min, 1.4) && close(layers.max, 8.6));
+        // Empty cells are skipped rather than read as zero.
+        let dropout = &config.bounds["dropout"];
+        assert!(close(dropout.min, 0.08) && close(dropout.max, 0.32));
+        let offset = &config.bounds["offset"];
+        assert!(close(offset.min, -1.2) && close(offset.max, 1.2));
+
+        let fixed = &config.bounds["fixed"];
+        assert_eq!(fixed.scale, Scale::Linear);
+        assert!(close(fixed.min, 4.5) && close(fixed.max, 5.5));
+        // A non-negative column is not widened below zero.
+        assert_eq!(infer_domain(0.0, 1.0, 0.5).min, 0.0);
+        validate_config(&config).unwrap();
+    }
+
+    #[test]
+    fn test_infer_config_rejects_bad_data() {
+        let bad_cell = "x,value\n0.5,1\nabc,2\n";
+        let err = infer_config(bad_cell.as_bytes(), 0.1, "value", 10).unwrap_err();
+        assert!(err.to_string().contains("Row 2"), "{}", err);
+        let no_value = "x,y\n0.5,1\n";
+        assert!(infer_config(no_value.as_bytes(), 0.1, "value", 10).is_err());
+        assert!(infer_config("x,value\n1,1\n".as_bytes(), -0.1, "value", 10).is_err());
+    }
+
+    #[test]
+    fn test_compare_artifacts_rejects_empty_history() {
+        let a = artifact_from("a", &[("x", 0.0, 1.0)], &[(0.5, 1.0)]);
+        let empty = artifact_from("empty", &[("x", 0.0, 1.0)], &[]);
+        assert!(compare_artifacts(&a, &empty, 0.1).is_err());
+    }
+
+    fn replay_command_result_is_err(artifact: &RunArtifact) -> bool {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        fs::write(file.path(), serde_json::to_string(artifact).unwrap()).unwrap();
+        replay_command(file.path(), 1e-9).is_err()
+    }
+
+    const SYNTHETIC_AUDIT_LOG: &str = r#"{"event_type":"Digest","timestamp_us":50,"run_id":0,"proposal_id":null,"config_version":0,"payload":""}
+{"event_type":"Apply","timestamp_us":120,"run_id":0,"proposal_id":1,"config_version":1,"payload":""}
+{"event_type":"Proposal","timestamp_us":100,"run_id":0,"proposal_id":1,"config_version":0,"payload":"","delta":[0.01,-0.02]}
+{"event_type":"Proposal","timestamp_us":200,"run_id":0,"proposal_id":2,"config_version":1,"payload":"","delta":[0.5,0.0]}
+{"event_type":"SafeModeEntered","timestamp_us":230,"run_id":0,"proposal_id":null,"config_version":1,"payload":"audit queue full"}
+
+{"event_type":"Rollback","timestamp_us":210,"run_id":0,"proposal_id":2,"config_version":1,"payload":"DeltaTooLarge"}
+{"event_type":"Proposal","timestamp_us":300,"run_id":0,"proposal_id":3,"config_version":1,"payload":"","delta":[0.001,0.001]}
+"#;
+
+    #[test]
+    fn test_audit_timeline_pairs_out_of_order_events() {
+        let records = parse_audit_log(SYNTHETIC_AUDIT_LOG.as_bytes()).unwrap();
+        let entries = audit_timeline(records);
+
+        let summary: Vec<_> = entries
+            .iter()
+            .map(|e| (e.timestamp_us, e.proposal_id, e.outcome, e.generation))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (100, Some(1), Some(ProposalOutcome::Applied), 1),
+                (200, Some(2), Some(ProposalOutcome::Rejected), 1),
+                (230, None, None, 1),
+                (300, Some(3), Some(ProposalOutcome::Pending), 1),
+            ]
+        );
+        assert_eq!(entries[0].delta, Some(vec![0.01, -0.02]));
+        assert_eq!(entries[1].detail, "DeltaTooLarge");
+        assert_eq!(entries[2].event, "SafeModeEntered");
+
+        let text = format_audit_text(&entries, false);
+        assert!(text.contains("rejected"), "{}", text);
+        assert!(
+            text.contains("[+0.5000, +0.0000]  DeltaTooLarge"),
+            "{}",
+            text
+        );
+        assert!(text.ends_with("3 proposals: 1 applied, 1 rejected, 1 pending\n"));
+
+        let csv = format_audit_csv(&entries).unwrap();
+        let rows: Vec<&str> = csv.lines().collect();
+        assert_eq!(rows.len(), 5);
+        assert_eq!(rows[2], "200,Proposal,2,rejected,0.5;0,1,DeltaTooLarge");
+    }
+
+    #[test]
+    fn test_audit_log_rejects_malformed_line() {
+        let log = "{\"event_type\":\"Apply\",\"proposal_id\":1}\nnot json\n";
+        let err = parse_audit_log(log.as_bytes()).unwrap_err();
+        assert!(format!("{:?}", err).contains("Line 2"), "{:?}", err);
+    }
+
+    #[test]
+    fn test_warm_start_import_skips_probe() {
+        use tempfile::tempdir;
+
+        let dir = tempdir().unwrap();
+        let artifact_path = dir.path().join("artifact.json");
+        let state_path = dir.path().join("state.json");
+
+        // Imported history covers the probe budget (ceil(10 * 0.5) = 5)
+        let history: Vec<EvalTrace> = (0..6)
+            .map(|i| EvalTrace {
+                eval_id: i + 1,
+                params: [("x".to_string(), i as f64 / 6.0)].into_iter().collect(),
+                value: (i as f64 / 6.0 - 0.4).powi(2),
+                cost: 1.0,
+                repeats: None,
+                penalized: false,
+                failed: false,
+                feasibility: None,
+                penalty: None,
+                fidelity: None,
+                kind: None,
+                source: None,
+            })
+            .collect();
+        let artifact = RunArtifact {
+            format_version: ARTIFACT_FORMAT_VERSION,
+            checksum: None,
+            run_id: "warm".to_string(),
+            seed: 42,
+            budget: 10,
+            config: create_test_config(),
+            history,
+            importance: None,
+        };
+        fs::write(&artifact_path, serde_json::to_string(&artifact).unwrap()).unwrap();
+
+        let metrics = Metrics::init(None, None, &HashMap::new()).unwrap();
+        import_command(&artifact_path, &state_path, true, &metrics).unwrap();
+        let state = load_state(&state_path).unwrap();
+        assert!(state.warm_start);
+
+        let mut solver = Solver::pcr(state.config);
+        seed_solver(
+            &mut solver,
+            state.history,
+            state.warm_start,
+            state.compacted,
+        )
+        .unwrap();
+        assert_eq!(solver.phase, arqonhpo_core::machine::Phase::Classify);
+        let batch = solver.ask().unwrap();
+        assert!(batch.len() <= 4);
+    }
+
+    #[test]
+    fn test_truncate_command_resumes_probe() {
+        use tempfile::tempdir;
+
+        let dir = tempdir().unwrap();
+        let state_path = dir.path().join("state.json");
+        let mut config = create_test_config();
+        config.budget = 60;
+        config.probe_ratio = ProbeBudget::Fixed(0.3);
+
+        let mut solver = Solver::pcr(config.clone());
+        while solver.history.len() < 50 {
+            let batch = solver.ask().unwrap();
+            let first_id = solver.history.len() as u64;
+            let traces: Vec<EvalTrace> = batch
+                .into_iter()
+                .enumerate()
+                .map(|(i, params)| EvalTrace {
+                    eval_id: first_id + i as u64,
+                    value: (params["x"] - 0.3).powi(2),
+                    params,
+                    cost: 1.0,
+                    repeats: None,
+                    penalized: false,
+                    failed: false,
+                    feasibility: None,
+                    penalty: None,
+                    fidelity: None,
+                    kind: None,
+                    source: None,
+                })
+                .collect();
+            solver.tell(traces).unwrap();
+        }
+        let history: Vec<SeedPoint> = solver.history[..50]
+            .iter()
+            .map(EvalTrace::to_seed_point)
+            .collect();
+        let state = SolverState {
+            config,
+            history: history.clone(),
+            run_id: Some("truncate".to_string()),
+            warm_start: false,
+            compacted: Default::default(),
+            suggest: Some(SuggestCursor {
+                pending: Vec::new(),
+                until: 51,
+            }),
+            metrics: None,
+        };
+        save_state(&state_path, &state).unwrap();
+
+        let metrics = Metrics::init(None, None, &HashMap::new()).unwrap();
+        assert!(truncate_command(&state_path, 51, None, &metrics).is_err());
+        truncate_command(&state_path, 10, None, &metrics).unwrap();
+        let state = load_state(&state_path).unwrap();
+        assert_eq!(state.history.len(), 10);
+        assert!(state.suggest.is_none());
+
+        // Ten points are short of the 18-point probe budget, so the next ask
+        // probes the same points the original run did after its tenth.
+        let mut resumed = Solver::pcr(state.config);
+        seed_solver(
+            &mut resumed,
+            state.history,
+            state.warm_start,
+            state.compacted,
+        )
+        .unwrap();
+        let batch = resumed.ask().unwrap();
+        assert_eq!(resumed.phase(), arqonhpo_core::machine::Phase::Probe);
+        let expected: Vec<HashMap<String, f64>> =
+            history[10..18].iter().map(|p| p.params.clone()).collect();
+        assert_eq!(batch, expected);
+    }
+
+    #[test]
+    fn test_run_state_resumes_compacted_budget() {
+        let mut config = create_test_config();
+        config.budget = 60;
+        config.probe_ratio = ProbeBudget::Fixed(0.2);
+        config.history_cap = Some(8);
+
+        let mut solver = Solver::pcr(config);
+        while solver.evaluations() < 40 {
+            let batch = solver.ask().unwrap();
+            let first_id = solver.evaluations() as u64;
+            let traces: Vec<EvalTrace> = batch
+                .into_iter()
+                .enumerate()
+                .map(|(i, params)| EvalTrace {
+                    eval_id: first_id + i as u64,
+                    value: (params["x"] - 0.3).powi(2),
+                    params,
+                    cost: 1.0,
+                    repeats: None,
+                    penalized: false,
+                    failed: false,
+                    feasibility: None,
+                    penalty: None,
+                    fidelity: None,
+                    kind: None,
+                    source: None,
+                })
+                .collect();
+            solver.tell(traces).unwrap();
+        }
+        assert!(!solver.compacted().is_empty());
+
+        let metrics = Metrics::init(None, None, &HashMap::new()).unwrap();
+        let state: SolverState = serde_json::from_str(
+            &serde_json::to_string(&run_state(&solver.config, &solver, "cap", false, &metrics))
+                .unwrap(),
+        )
+        .unwrap();
+        assert!(state.history.len() < solver.evaluations());
+        let mut resumed = Solver::pcr(state.config);
+        seed_solver(
+            &mut resumed,
+            state.history,
+            state.warm_start,
+            state.compacted,
+        )
+        .unwrap();
+        assert_eq!(resumed.evaluations(), solver.evaluations());
+        assert_eq!(resumed.spent_cost(), solver.spent_cost());
     }
 
     #[test]
@@ -2107,9 +8110,142 @@ mod tests {
         let state = create_test_state();
         fs::write(&state_path, serde_json::to_string(&state).unwrap()).unwrap();
 
-        let metrics = Metrics::init(None).unwrap();
+        let metrics = Metrics::init(None, None, &HashMap::new()).unwrap();
         // When output_path is None, it prints to stdout
-        let result = export_command(&state_path, None, None, &metrics);
+        let result = export_command(&state_path, None, None, false, ExportFormat::Json, &metrics);
         assert!(result.is_ok());
     }
+
+    /// Write `config` to `dir/config.json` and return the path.
+    fn write_doctor_config(dir: &Path, config: &SolverConfig) -> PathBuf {
+        let path = dir.join("config.json");
+        fs::write(&path, serde_json::to_string(config).unwrap()).unwrap();
+        path
+    }
+
+    #[cfg(unix)]
+    fn write_doctor_script(dir: &Path, body: &str) -> PathBuf {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = dir.join("evaluate.sh");
+        fs::write(&path, format!("#!/bin/bash\n{}", body)).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    fn checks(diagnoses: &[Diagnosis], severity: Severity) -> Vec<&'static str> {
+        diagnoses
+            .iter()
+            .filter(|d| d.severity == severity)
+            .map(|d| d.check)
+            .collect()
+    }
+
+    #[test]
+    fn test_doctor_reports_invalid_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        fs::write(&path, "{ not json").unwrap();
+        let diagnoses = diagnose(&path, None, None);
+        assert_eq!(checks(&diagnoses, Severity::Error), vec!["config"]);
+
+        let mut config = create_test_config();
+        config.budget = 0;
+        let path = write_doctor_config(dir.path(), &config);
+        let diagnoses = diagnose(&path, None, None);
+        assert_eq!(diagnoses.len(), 1);
+        assert!(diagnoses[0].problem.contains("budget must be > 0"));
+    }
+
+    #[test]
+    fn test_doctor_reports_budget_too_small() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = create_test_config();
+        let path = write_doctor_config(dir.path(), &config);
+        assert!(diagnose(&path, None, None).is_empty());
+
+        config.budget = 8;
+        config.probe_ratio = ProbeBudget::Fixed(0.75);
+        let path = write_doctor_config(dir.path(), &config);
+        let diagnoses = diagnose(&path, None, None);
+        assert_eq!(checks(&diagnoses, Severity::Warning), vec!["budget"]);
+
+        config.probe_ratio = ProbeBudget::Fixed(1.0);
+        let path = write_doctor_config(dir.path(), &config);
+        let diagnoses = diagnose(&path, None, None);
+        assert_eq!(checks(&diagnoses, Severity::Error), vec!["budget"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_doctor_evaluates_script_at_midpoint() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = write_doctor_config(dir.path(), &create_test_config());
+
+        let script = write_doctor_script(dir.path(), "echo \"RESULT=$ARQON_x\"");
+        let diagnoses = diagnose(&config, Some(&script), None);
+        assert_eq!(checks(&diagnoses, Severity::Info), vec!["script"]);
+        assert!(diagnoses[0].problem.contains("parsed as 0.5"));
+        assert!(format_diagnoses(&diagnoses).ends_with("No problems found\n"));
+
+        let script = write_doctor_script(dir.path(), "echo \"loss: $ARQON_x\"");
+        let diagnoses = diagnose(&config, Some(&script), None);
+        assert_eq!(checks(&diagnoses, Severity::Error), vec!["script"]);
+        assert!(
+            diagnoses[0].problem.contains("\"loss: 0.5\""),
+            "{}",
+            diagnoses[0].problem
+        );
+
+        let script = write_doctor_script(dir.path(), "echo oops >&2\nexit 3");
+        let diagnoses = diagnose(&config, Some(&script), None);
+        assert_eq!(checks(&diagnoses, Severity::Error), vec!["script"]);
+        assert!(diagnoses[0].problem.contains("oops"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_doctor_reports_missing_or_non_executable_script() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let config = write_doctor_config(dir.path(), &create_test_config());
+        let missing = dir.path().join("missing.sh");
+        let diagnoses = diagnose(&config, Some(&missing), None);
+        assert!(diagnoses[0].problem.contains("does not exist"));
+
+        let script = write_doctor_script(dir.path(), "echo 1");
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o644)).unwrap();
+        let diagnoses = diagnose(&config, Some(&script), None);
+        assert_eq!(checks(&diagnoses, Severity::Error), vec!["script"]);
+        assert!(diagnoses[0].fix.starts_with("chmod +x"));
+    }
+
+    #[test]
+    fn test_doctor_reports_state_mismatch() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = create_test_config();
+        let config_path = write_doctor_config(dir.path(), &config);
+        let state_path = dir.path().join("state.json");
+
+        let mut state = create_test_state();
+        save_state(&state_path, &state).unwrap();
+        assert!(diagnose(&config_path, None, Some(&state_path)).is_empty());
+
+        state.config.seed = 7;
+        state.config.budget = 1;
+        state.history[0].params.insert("x".to_string(), 3.0);
+        save_state(&state_path, &state).unwrap();
+        let diagnoses = diagnose(&config_path, None, Some(&state_path));
+        assert_eq!(
+            checks(&diagnoses, Severity::Warning),
+            vec!["state", "state"]
+        );
+        assert!(diagnoses[0].problem.contains("budget, seed"));
+        assert!(diagnoses[1].problem.contains("1 of 1 evaluations"));
+
+        fs::write(&state_path, "[]").unwrap();
+        let diagnoses = diagnose(&config_path, None, Some(&state_path));
+        assert_eq!(checks(&diagnoses, Severity::Error), vec!["state"]);
+    }
 }
@@ -138,6 +138,58 @@ fn test_tell_command_with_results_file() -> Result<(), Box<dyn std::error::Error
     Ok(())
 }
 
+#[test]
+fn test_ask_tell_verbose_status() -> Result<(), Box<dyn std::error::Error>> {
+    let config_file = create_config();
+
+    let output = Command::new(assert_cmd::cargo::cargo_bin!("arqonhpo-cli"))
+        .arg("ask")
+        .arg("--config")
+        .arg(config_file.path())
+        .arg("--verbose")
+        .output()?;
+    assert!(output.status.success());
+    let status: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+    assert!(!status["candidates"].as_array().unwrap().is_empty());
+    assert_eq!(status["phase"], "probe");
+    assert_eq!(status["history_len"], 0);
+    assert_eq!(status["done"], false);
+
+    // A one-evaluation budget is used up by the first result.
+    let state_file = NamedTempFile::new()?;
+    let state_content = r#"{
+        "config": {
+            "seed": 42,
+            "budget": 1,
+            "bounds": {"x": {"min": 0.0, "max": 1.0}}
+        },
+        "history": []
+    }"#;
+    std::fs::write(state_file.path(), state_content)?;
+    let results_file = NamedTempFile::new()?;
+    std::fs::write(
+        results_file.path(),
+        r#"[{"params": {"x": 0.5}, "value": 0.25, "cost": 1.0}]"#,
+    )?;
+
+    let output = Command::new(assert_cmd::cargo::cargo_bin!("arqonhpo-cli"))
+        .arg("tell")
+        .arg("--state")
+        .arg(state_file.path())
+        .arg("--results")
+        .arg(results_file.path())
+        .arg("--verbose")
+        .output()?;
+    assert!(output.status.success());
+    let status: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+    assert!(status.get("candidates").is_none());
+    assert_eq!(status["phase"], "done");
+    assert_eq!(status["history_len"], 1);
+    assert_eq!(status["done"], true);
+
+    Ok(())
+}
+
 #[test]
 fn test_export_import_roundtrip() -> Result<(), Box<dyn std::error::Error>> {
     // Create initial state
@@ -238,3 +290,226 @@ fn test_interactive_ask_tell() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+#[test]
+fn test_run_external_evaluator() -> Result<(), Box<dyn std::error::Error>> {
+    use std::io::{BufRead, BufReader};
+
+    let config_file = create_config();
+    let state_file = NamedTempFile::new()?;
+    std::fs::remove_file(state_file.path())?;
+
+    let mut child = Command::new(assert_cmd::cargo::cargo_bin!("arqonhpo-cli"))
+        .arg("run")
+        .arg("--config")
+        .arg(config_file.path())
+        .arg("--state")
+        .arg(state_file.path())
+        .arg("--external")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()?;
+    let mut stdin = child.stdin.take().unwrap();
+    let reader = BufReader::new(child.stdout.take().unwrap());
+
+    // Fake evaluator: answer every batch line with one value per candidate,
+    // skipping the solver's log lines.
+    let mut evaluated = 0;
+    let mut summary = None;
+    for line in reader.lines() {
+        let line = line?;
+        match serde_json::from_str::<serde_json::Value>(&line) {
+            Ok(serde_json::Value::Array(batch)) => {
+                for candidate in batch {
+                    let x = candidate["x"].as_f64().unwrap();
+                    writeln!(stdin, "{}", (x - 0.3).powi(2))?;
+                    evaluated += 1;
+                }
+                stdin.flush()?;
+            }
+            Ok(value) if value.get("done").is_some() => summary = Some(value),
+            _ => {}
+        }
+    }
+    assert!(child.wait()?.success());
+
+    let summary = summary.expect("run --external prints a summary line");
+    assert_eq!(evaluated, 10);
+    assert_eq!(summary["evaluations"], 10);
+    assert!(summary["best"]["value"].as_f64().unwrap() < 0.1);
+    let state: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(state_file.path())?)?;
+    assert_eq!(state["history"].as_array().unwrap().len(), 10);
+
+    Ok(())
+}
+
+#[test]
+fn test_run_dry_run_skips_script() -> Result<(), Box<dyn std::error::Error>> {
+    let config_file = create_config();
+
+    let output = Command::new(assert_cmd::cargo::cargo_bin!("arqonhpo-cli"))
+        .arg("--log-format")
+        .arg("json")
+        .arg("run")
+        .arg("--config")
+        .arg(config_file.path())
+        .arg("--script")
+        .arg("/nonexistent/evaluate.sh")
+        .arg("--dry-run")
+        .output()?;
+
+    assert!(output.status.success());
+    let plan: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(plan["probe_points"], 5);
+    assert_eq!(plan["refine_budget"], 5);
+    assert_eq!(plan["evaluated"], 0);
+
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_sigint_checkpoints_state() -> Result<(), Box<dyn std::error::Error>> {
+    use std::os::unix::fs::PermissionsExt;
+    use std::time::{Duration, Instant};
+
+    let config_file = create_config();
+    let dir = tempfile::tempdir()?;
+    let calls = dir.path().join("calls.log");
+    let script = dir.path().join("slow.sh");
+    std::fs::write(
+        &script,
+        format!(
+            "#!/bin/bash\nsleep 0.3\necho \"$ARQON_x\" >> {}\necho \"RESULT=$ARQON_x\"",
+            calls.display()
+        ),
+    )?;
+    std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755))?;
+    let state = dir.path().join("state.json");
+
+    let child = Command::new(assert_cmd::cargo::cargo_bin!("arqonhpo-cli"))
+        .arg("run")
+        .arg("--config")
+        .arg(config_file.path())
+        .arg("--script")
+        .arg(&script)
+        .arg("--state")
+        .arg(&state)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    // Interrupt once the first evaluation has finished.
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while !calls.exists() {
+        assert!(Instant::now() < deadline, "script never ran");
+        std::thread::sleep(Duration::from_millis(20));
+    }
+    let killed = Command::new("kill")
+        .arg("-INT")
+        .arg(child.id().to_string())
+        .status()?;
+    assert!(killed.success());
+
+    let output = child.wait_with_output()?;
+    assert_eq!(output.status.code(), Some(130));
+    let stdout = String::from_utf8(output.stdout)?;
+    assert!(stdout.contains("\"interrupted\": true"), "{}", stdout);
+
+    let saved: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&state)?)?;
+    let history = saved["history"].as_array().unwrap();
+    let evaluated = std::fs::read_to_string(&calls)?.lines().count();
+    assert!(!history.is_empty() && history.len() < 10);
+    assert_eq!(
+        history.len(),
+        evaluated,
+        "every finished evaluation is saved"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_keyed_ask_tell_across_processes() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let config_path = dir.path().join("config.json");
+    let state_path = dir.path().join("state.json");
+    let results_path = dir.path().join("results.json");
+    let config = serde_json::json!({
+        "seed": 7,
+        "budget": 24,
+        "probe_ratio": 0.5,
+        "bounds": {
+            "a": {"min": 0.0, "max": 1.0},
+            "b": {"min": -1.0, "max": 1.0},
+            "c": {"min": 0.0, "max": 10.0},
+            "d": {"min": 1.0, "max": 2.0}
+        }
+    });
+    std::fs::write(&config_path, config.to_string())?;
+    std::fs::write(
+        &state_path,
+        serde_json::json!({"config": config, "history": []}).to_string(),
+    )?;
+    let cli = || Command::new(assert_cmd::cargo::cargo_bin!("arqonhpo-cli"));
+    let tell = |results: &str| -> std::io::Result<std::process::Output> {
+        std::fs::write(&results_path, results)?;
+        cli()
+            .arg("tell")
+            .arg("--state")
+            .arg(&state_path)
+            .arg("--results")
+            .arg(&results_path)
+            .output()
+    };
+
+    for round in 0..4 {
+        // Every ask and tell is its own process, so the batch id must not
+        // depend on anything but the state on disk.
+        let output = cli()
+            .arg("ask")
+            .arg("--config")
+            .arg(&config_path)
+            .arg("--state")
+            .arg(&state_path)
+            .arg("--keyed")
+            .arg("--batch")
+            .arg("2")
+            .output()?;
+        assert!(output.status.success(), "round {}", round);
+        let batch: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+        let candidates = batch["candidates"].as_object().unwrap();
+        assert_eq!(candidates.len(), 2, "round {}", round);
+        assert_eq!(candidates["0"].as_object().unwrap().len(), 4);
+        let batch_id = batch["batch_id"].as_str().unwrap();
+
+        // Index 2 was not handed out with `--batch 2`.
+        let output = tell(&format!(
+            r#"{{"batch_id": "{}", "results": {{"2": 1.0}}}}"#,
+            batch_id
+        ))?;
+        assert!(!output.status.success(), "round {}", round);
+        assert!(String::from_utf8_lossy(&output.stderr).contains("no candidate 2"));
+
+        let output = tell(&format!(
+            r#"{{"batch_id": "{}", "results": {{"1": {}, "0": 0.5}}}}"#,
+            batch_id, round
+        ))?;
+        assert!(
+            output.status.success(),
+            "round {}: {}",
+            round,
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    let state: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&state_path)?)?;
+    assert_eq!(state["history"].as_array().unwrap().len(), 8);
+
+    // A malformed list reports what is wrong instead of a generic mismatch.
+    let output = tell(r#"[{"params": {"a": 0.5}, "value": "high"}]"#)?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("invalid type"));
+
+    Ok(())
+}
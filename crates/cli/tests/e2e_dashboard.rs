@@ -61,6 +61,8 @@ fn test_dashboard_e2e_server() -> Result<(), Box<dyn std::error::Error>> {
     }
     let resp = resp.ok_or("Failed to connect to dashboard server")?;
     assert!(resp.status().is_success());
+    // No --cors-origin: no cross-origin access.
+    assert!(resp.headers().get("access-control-allow-origin").is_none());
     let body: serde_json::Value = resp.json()?;
     assert_eq!(body["run_id"], "test-run");
 
@@ -87,3 +89,95 @@ fn test_dashboard_e2e_server() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+#[cfg(unix)]
+#[test]
+fn test_dashboard_healthz_and_sigterm() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let state = dir.path().join("state.json");
+    std::fs::write(
+        &state,
+        r#"{"config": {"budget": 10, "bounds": {}, "seed": 1}, "history": []}"#,
+    )?;
+
+    let listener = match TcpListener::bind("127.0.0.1:0") {
+        Ok(listener) => listener,
+        Err(err) if err.kind() == std::io::ErrorKind::PermissionDenied => return Ok(()),
+        Err(err) => return Err(err.into()),
+    };
+    let port = listener.local_addr()?.port();
+    drop(listener);
+
+    let mut child = Command::new(assert_cmd::cargo::cargo_bin!("arqonhpo-cli"))
+        .arg("dashboard")
+        .arg("--state")
+        .arg(&state)
+        .arg("--addr")
+        .arg(format!("127.0.0.1:{}", port))
+        .arg("--cors-origin")
+        .arg("https://example.com")
+        .stdout(std::process::Stdio::null())
+        .spawn()?;
+
+    let client = Client::new();
+    let base_url = format!("http://127.0.0.1:{}", port);
+    let mut resp = None;
+    for _ in 0..20 {
+        match client.get(format!("{}/healthz", base_url)).send() {
+            Ok(r) => {
+                resp = Some(r);
+                break;
+            }
+            Err(_) => std::thread::sleep(std::time::Duration::from_millis(50)),
+        }
+    }
+    let resp = resp.ok_or("Failed to connect to dashboard server")?;
+    assert!(resp.status().is_success());
+    let health: serde_json::Value = resp.json()?;
+    assert_eq!(health["status"], "ok");
+    assert!(health["uptime_secs"].is_u64());
+
+    let resp = client.get(format!("{}/api/summary", base_url)).send()?;
+    assert_eq!(
+        resp.headers()["access-control-allow-origin"],
+        "https://example.com"
+    );
+
+    // An unreadable state file fails the health check.
+    std::fs::remove_file(&state)?;
+    let resp = client.get(format!("{}/healthz", base_url)).send()?;
+    assert_eq!(resp.status().as_u16(), 503);
+
+    // SIGTERM stops the server cleanly and releases the port.
+    let killed = Command::new("kill")
+        .arg("-TERM")
+        .arg(child.id().to_string())
+        .status()?;
+    assert!(killed.success());
+    assert!(child.wait()?.success());
+    TcpListener::bind(("127.0.0.1", port))?;
+
+    Ok(())
+}
+
+#[test]
+fn test_dashboard_rejects_wildcard_cors_origin() -> Result<(), Box<dyn std::error::Error>> {
+    let state_file = NamedTempFile::new()?;
+    std::fs::write(
+        state_file.path(),
+        r#"{"config": {"budget": 10, "bounds": {}, "seed": 1}, "history": []}"#,
+    )?;
+    let output = Command::new(assert_cmd::cargo::cargo_bin!("arqonhpo-cli"))
+        .arg("dashboard")
+        .arg("--state")
+        .arg(state_file.path())
+        .arg("--addr")
+        .arg("127.0.0.1:0")
+        .arg("--cors-origin")
+        .arg("*")
+        .output()?;
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("must name the origin"), "{}", stderr);
+    Ok(())
+}
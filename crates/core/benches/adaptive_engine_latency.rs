@@ -67,6 +67,7 @@ fn bench_t1_apply(c: &mut Criterion) {
             iteration: 0,
             delta: delta.clone(),
             gradient_estimate: delta,
+            generation: None,
         };
 
         group.bench_with_input(BenchmarkId::new("apply", num_params), num_params, |b, _| {
@@ -103,7 +104,7 @@ fn bench_perturbation_generation(c: &mut Criterion) {
         group.bench_with_input(
             BenchmarkId::new("generate_perturbation", num_params),
             num_params,
-            |b, _| b.iter(|| black_box(spsa.generate_perturbation())),
+            |b, _| b.iter(|| black_box(spsa.generate_perturbation(&[], None))),
         );
     }
 
@@ -211,6 +212,7 @@ fn bench_t1_under_saturation(c: &mut Criterion) {
         iteration: 0,
         delta: delta.clone(),
         gradient_estimate: delta,
+        generation: None,
     };
 
     group.bench_function("apply_4params", |b| {
@@ -0,0 +1,183 @@
+//! Allocations and latency of Nelder-Mead steps on a 10-D problem.
+//!
+//! Compares the named path (`Strategy::step` on `HashMap` params) with the
+//! dense path the solver uses (`Strategy::step_dense` on `ParamVec`s in
+//! `ParamRegistry` order). Allocation counts per step are printed before the
+//! timing groups run.
+#![allow(clippy::disallowed_types)] // Measures the HashMap boundary path
+
+use arqonhpo_core::adaptive_engine::config_atomic::{ParamRegistry, ParamVec};
+use arqonhpo_core::artifact::EvalTrace;
+use arqonhpo_core::config::SolverConfig;
+use arqonhpo_core::strategies::nelder_mead::NelderMead;
+use arqonhpo_core::strategies::{
+    dense_point, param_registry, DenseAction, DenseHistory, Strategy, StrategyAction,
+};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// System allocator that counts allocations and reallocations.
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+const DIM: usize = 10;
+const STEPS: usize = 200;
+
+fn config_10d() -> SolverConfig {
+    let bounds: serde_json::Map<String, serde_json::Value> = (0..DIM)
+        .map(|i| {
+            (
+                format!("x{}", i),
+                serde_json::json!({ "min": -5.0, "max": 5.0 }),
+            )
+        })
+        .collect();
+    serde_json::from_value(serde_json::json!({
+        "seed": 42,
+        "budget": 10_000,
+        "bounds": bounds,
+    }))
+    .unwrap()
+}
+
+fn sphere(params: &HashMap<String, f64>) -> f64 {
+    params.values().map(|x| (x - 1.0).powi(2)).sum()
+}
+
+fn trace(eval_id: usize, params: HashMap<String, f64>) -> EvalTrace {
+    EvalTrace {
+        eval_id: eval_id as u64,
+        value: sphere(&params),
+        params,
+        cost: 1.0,
+        repeats: None,
+        penalized: false,
+        failed: false,
+        feasibility: None,
+        penalty: None,
+        fidelity: None,
+        kind: None,
+        source: None,
+    }
+}
+
+/// Deterministic spread of probe points to seed the simplex from.
+fn probe_history() -> Vec<EvalTrace> {
+    (0..2 * DIM)
+        .map(|j| {
+            let params = (0..DIM)
+                .map(|i| {
+                    let phase = ((j * 7 + i * 3) % 11) as f64 / 10.0;
+                    (format!("x{}", i), -5.0 + 10.0 * phase)
+                })
+                .collect();
+            trace(j, params)
+        })
+        .collect()
+}
+
+/// Run `STEPS` named steps; returns allocations made inside `step`.
+fn run_named(config: &SolverConfig, mut history: Vec<EvalTrace>) -> usize {
+    let mut nm = NelderMead::new(DIM, vec![false; DIM]);
+    let mut allocations = 0;
+    for _ in 0..STEPS {
+        let before = ALLOCATIONS.load(Ordering::Relaxed);
+        let action = nm.step(config, &history);
+        allocations += ALLOCATIONS.load(Ordering::Relaxed) - before;
+        let StrategyAction::Evaluate(points) = action else {
+            break;
+        };
+        for params in points {
+            history.push(trace(history.len(), params));
+        }
+    }
+    allocations
+}
+
+/// Run `STEPS` dense steps; returns allocations made inside `step_dense`.
+fn run_dense(
+    config: &SolverConfig,
+    registry: &ParamRegistry,
+    mut history: Vec<EvalTrace>,
+) -> usize {
+    let mut points: Vec<ParamVec> = history
+        .iter()
+        .map(|t| dense_point(registry, &t.params))
+        .collect();
+    let mut nm = NelderMead::new(DIM, vec![false; DIM]);
+    let mut allocations = 0;
+    for _ in 0..STEPS {
+        let before = ALLOCATIONS.load(Ordering::Relaxed);
+        let action = nm.step_dense(
+            config,
+            &DenseHistory {
+                registry,
+                traces: &history,
+                points: &points,
+            },
+        );
+        allocations += ALLOCATIONS.load(Ordering::Relaxed) - before;
+        let Some(DenseAction::Evaluate(batch)) = action else {
+            break;
+        };
+        for point in batch {
+            let action = DenseAction::Evaluate(vec![point.clone()]).into_action(registry);
+            let StrategyAction::Evaluate(mut named) = action else {
+                unreachable!()
+            };
+            points.push(point);
+            history.push(trace(history.len(), named.remove(0)));
+        }
+    }
+    allocations
+}
+
+fn bench_nm_step(c: &mut Criterion) {
+    let config = config_10d();
+    let registry = param_registry(&config);
+    let history = probe_history();
+
+    let named = run_named(&config, history.clone());
+    let dense = run_dense(&config, &registry, history.clone());
+    println!(
+        "NM allocations per step ({}-D, {} steps): named {:.1}, dense {:.1}",
+        DIM,
+        STEPS,
+        named as f64 / STEPS as f64,
+        dense as f64 / STEPS as f64
+    );
+
+    let mut group = c.benchmark_group("NM_step_10d");
+    group.bench_function("named", |b| {
+        b.iter(|| black_box(run_named(&config, history.clone())))
+    });
+    group.bench_function("dense", |b| {
+        b.iter(|| black_box(run_dense(&config, &registry, history.clone())))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_nm_step);
+criterion_main!(benches);
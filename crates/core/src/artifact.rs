@@ -1,23 +1,303 @@
 use serde::{Deserialize, Serialize};
 
-use crate::config::SolverConfig;
+use crate::config::{Domain, SolverConfig};
+use sha2::{Digest, Sha256};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+/// Current [`RunArtifact::format_version`].
+///
+/// Version 0 is the unversioned format written before the field existed; it
+/// has no checksum and is otherwise read as-is. Version 1 checksums hash
+/// `null` fields too, so they change whenever `SolverConfig` gains an
+/// optional field; version 2 leaves them out.
+pub const ARTIFACT_FORMAT_VERSION: u32 = 2;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RunArtifact {
+    /// Layout version (absent means 0). See [`RunArtifact::verify`].
+    #[serde(default)]
+    pub format_version: u32,
+    /// Hex SHA-256 of the canonical JSON of `config` and `history` (see
+    /// [`RunArtifact::compute_checksum`]), set by [`RunArtifact::seal`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<String>,
     pub run_id: String,
     pub seed: u64,
     pub budget: u64,
     pub config: SolverConfig,
     pub history: Vec<EvalTrace>,
+    /// Per-parameter sensitivity (see [`parameter_importance`]), if computed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub importance: Option<HashMap<String, f64>>,
     // Future: classification results, environment fingerprint
 }
 
+impl RunArtifact {
+    /// Hex SHA-256 over `config` and `history` serialized as JSON with
+    /// object keys sorted and `null` fields left out, so map ordering, the
+    /// file format the artifact was stored in and optional fields added to
+    /// the format later do not change it.
+    pub fn compute_checksum(&self) -> String {
+        content_checksum(&self.content(), ARTIFACT_FORMAT_VERSION)
+    }
+
+    fn content(&self) -> serde_json::Value {
+        serde_json::json!({ "config": &self.config, "history": &self.history })
+    }
+
+    /// Stamp the current format version and checksum before writing.
+    pub fn seal(&mut self) {
+        self.format_version = ARTIFACT_FORMAT_VERSION;
+        self.checksum = Some(self.compute_checksum());
+    }
+
+    /// Read an artifact from the document it was stored as (in any format,
+    /// converted to JSON), migrating it to the current format.
+    ///
+    /// The checksum is checked against the content as stored rather than as
+    /// this build would serialize it, so fields added since the artifact was
+    /// written do not fail verification.
+    pub fn from_stored(stored: serde_json::Value) -> Result<Self, ArtifactError> {
+        let mut artifact: Self = serde_json::from_value(stored.clone())
+            .map_err(|err| ArtifactError::Invalid(err.to_string()))?;
+        let content = serde_json::json!({
+            "config": stored.get("config"),
+            "history": stored.get("history"),
+        });
+        artifact.verify_content(&content)?;
+        Ok(artifact)
+    }
+
+    /// Migrate an artifact to the current format and check its checksum
+    /// against its content as this build serializes it. Prefer
+    /// [`from_stored`](Self::from_stored) for artifacts read from disk.
+    ///
+    /// Version 0 and 1 artifacts are upgraded in place; their `checksum` is
+    /// cleared, as it was not computed the current way. A present checksum
+    /// must match.
+    pub fn verify(&mut self) -> Result<(), ArtifactError> {
+        let content = self.content();
+        self.verify_content(&content)
+    }
+
+    fn verify_content(&mut self, content: &serde_json::Value) -> Result<(), ArtifactError> {
+        let version = self.format_version;
+        if version > ARTIFACT_FORMAT_VERSION {
+            return Err(ArtifactError::UnsupportedVersion { found: version });
+        }
+        if let (Some(expected), 1..) = (&self.checksum, version) {
+            let actual = content_checksum(content, version);
+            // A version 1 artifact stored as TOML lost its `null` fields, so
+            // also try them as this build writes them.
+            let matches = *expected == actual
+                || (version == 1 && *expected == content_checksum(&self.content(), version));
+            if !matches {
+                return Err(ArtifactError::ChecksumMismatch {
+                    expected: expected.clone(),
+                    actual,
+                });
+            }
+        }
+        if version < ARTIFACT_FORMAT_VERSION {
+            self.format_version = ARTIFACT_FORMAT_VERSION;
+            self.checksum = None;
+        }
+        Ok(())
+    }
+}
+
+/// Checksum of `content` as format `version` computes it.
+fn content_checksum(content: &serde_json::Value, version: u32) -> String {
+    let mut canonical = String::new();
+    write_canonical(content, version >= 2, &mut canonical);
+    Sha256::digest(canonical.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// JSON text of `value` with object keys in sorted order, and without
+/// `null` object fields if `skip_null`.
+fn write_canonical(value: &serde_json::Value, skip_null: bool, out: &mut String) {
+    match value {
+        serde_json::Value::Object(map) => {
+            let mut keys: Vec<&String> = map
+                .iter()
+                .filter(|(_, value)| !(skip_null && value.is_null()))
+                .map(|(key, _)| key)
+                .collect();
+            keys.sort();
+            out.push('{');
+            for (i, key) in keys.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&serde_json::Value::String(key.clone()).to_string());
+                out.push(':');
+                write_canonical(&map[key], skip_null, out);
+            }
+            out.push('}');
+        }
+        serde_json::Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical(item, skip_null, out);
+            }
+            out.push(']');
+        }
+        other => out.push_str(&other.to_string()),
+    }
+}
+
+/// A [`RunArtifact`] that failed [`RunArtifact::verify`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArtifactError {
+    /// `format_version` is newer than this build understands.
+    UnsupportedVersion { found: u32 },
+    /// The stored checksum does not match the artifact's content.
+    ChecksumMismatch { expected: String, actual: String },
+    /// The stored document is not an artifact.
+    Invalid(String),
+}
+
+impl std::fmt::Display for ArtifactError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnsupportedVersion { found } => write!(
+                f,
+                "artifact format_version {} is not supported (this build reads up to {})",
+                found, ARTIFACT_FORMAT_VERSION
+            ),
+            Self::ChecksumMismatch { expected, actual } => write!(
+                f,
+                "artifact checksum mismatch: recorded {}, content hashes to {} (the file was modified or corrupted)",
+                expected, actual
+            ),
+            Self::Invalid(message) => write!(f, "invalid artifact: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for ArtifactError {}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EvalTrace {
     pub eval_id: u64,
     pub params: std::collections::HashMap<String, f64>,
     pub value: f64,
     pub cost: f64,
+    /// Number of repeated evaluations averaged into `value` (absent means one).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repeats: Option<u32>,
+    /// The reported value was NaN or infinite and `value` is the configured penalty.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub penalized: bool,
+    /// The evaluation itself failed and `value` is a recorded failure value
+    /// the caller chose, not a measurement.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub failed: bool,
+    /// How close an infeasible evaluation came to feasibility, in [0, 1]
+    /// (absent means feasible). `Solver::tell` penalizes the value by
+    /// `SolverConfig::infeasibility_penalty` times the missing feasibility.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub feasibility: Option<f64>,
+    /// Infeasibility penalty `Solver::tell` added to `value`, in the
+    /// solver's units (absent means none). Kept so the measured value can be
+    /// recovered even if `SolverConfig::infeasibility_penalty` changes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub penalty: Option<f64>,
+    /// Fraction of the full evaluation resource this value was measured at
+    /// (absent means full fidelity). See `strategies::halving`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fidelity: Option<f64>,
+    /// Phase whose batch produced the point (absent for points seeded from
+    /// outside the solver).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<TraceKind>,
+    /// What generated the point within that phase: the probe or strategy
+    /// name, or `"cp_restart"` for the rescue batch of a CP restart.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+}
+
+/// Solver phase a trace was produced in, recorded by `Solver::tell`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TraceKind {
+    Probe,
+    Refine,
+}
+
+/// Reserved candidate key carrying the requested fidelity in (0, 1].
+///
+/// Candidates from a halving refine include it next to their parameters;
+/// results passed back to `tell` with it are recorded in `EvalTrace::fidelity`.
+pub const FIDELITY_KEY: &str = "_fidelity";
+
+/// Keys that carry trace metadata rather than parameters; no parameter may
+/// use one of these names.
+pub const RESERVED_KEYS: [&str; 1] = [FIDELITY_KEY];
+
+impl EvalTrace {
+    /// Whether the value was measured at full fidelity.
+    pub fn is_full_fidelity(&self) -> bool {
+        self.fidelity.is_none_or(|f| f >= 1.0)
+    }
+
+    /// Missing feasibility, `1 - feasibility`: 0 for a feasible evaluation.
+    pub fn infeasibility(&self) -> f64 {
+        1.0 - self.feasibility.unwrap_or(1.0)
+    }
+
+    /// The trace as a `SeedPoint`, with a reduced fidelity kept under
+    /// `FIDELITY_KEY` so seeding it back restores `fidelity`. `penalized`,
+    /// `failed` and `feasibility` carry over as they are.
+    pub fn to_seed_point(&self) -> SeedPoint {
+        let mut params = self.params.clone();
+        if let Some(fidelity) = self.fidelity {
+            params.insert(FIDELITY_KEY.to_string(), fidelity);
+        }
+        SeedPoint {
+            params,
+            value: self.value,
+            cost: self.cost,
+            penalized: self.penalized,
+            failed: self.failed,
+            feasibility: self.feasibility,
+        }
+    }
+
+    /// The inverse of [`to_seed_point`](Self::to_seed_point): a trace with
+    /// id `eval_id` whose `fidelity` comes from the reserved key of `point`,
+    /// which is left out of `params`.
+    pub fn from_seed_point(eval_id: u64, point: &SeedPoint) -> Self {
+        let mut params = point.params.clone();
+        let fidelity = params.remove(FIDELITY_KEY);
+        EvalTrace {
+            eval_id,
+            params,
+            value: point.value,
+            cost: point.cost,
+            repeats: None,
+            penalized: point.penalized,
+            failed: point.failed,
+            feasibility: point.feasibility,
+            penalty: None,
+            fidelity,
+            kind: None,
+            source: None,
+        }
+    }
+
+    /// Noise standard deviation of `value` given the per-evaluation noise,
+    /// shrunk by the square root of the number of averaged repeats.
+    pub fn noise_std(&self, observation_noise: f64) -> f64 {
+        observation_noise / (self.repeats.unwrap_or(1).max(1) as f64).sqrt()
+    }
 }
 
 /// A simplified input for seeding (no eval_id required from user).
@@ -27,4 +307,521 @@ pub struct SeedPoint {
     pub params: std::collections::HashMap<String, f64>,
     pub value: f64,
     pub cost: f64,
+    /// `value` is a penalty standing in for a NaN or infinite result.
+    #[serde(default)]
+    pub penalized: bool,
+    /// The evaluation failed and `value` is the recorded failure value.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub failed: bool,
+    /// How close an infeasible evaluation came to feasibility, in [0, 1]
+    /// (absent means feasible). See `EvalTrace::feasibility`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub feasibility: Option<f64>,
+}
+
+/// Canonical order of two parameter maps: entries compared in key order,
+/// values by `f64::total_cmp`, with a map that is a prefix of the other
+/// first. Independent of `HashMap` iteration order.
+pub fn cmp_params(a: &HashMap<String, f64>, b: &HashMap<String, f64>) -> Ordering {
+    let mut a: Vec<(&String, &f64)> = a.iter().collect();
+    let mut b: Vec<(&String, &f64)> = b.iter().collect();
+    a.sort_by(|x, y| x.0.cmp(y.0));
+    b.sort_by(|x, y| x.0.cmp(y.0));
+    for ((ka, va), (kb, vb)) in a.iter().zip(&b) {
+        let order = ka.cmp(kb).then_with(|| va.total_cmp(vb));
+        if order != Ordering::Equal {
+            return order;
+        }
+    }
+    a.len().cmp(&b.len())
+}
+
+/// Order of traces by `value` (ascending, via `f64::total_cmp`), ties broken
+/// by [`cmp_params`] and then `eval_id`.
+///
+/// Every sort and extremum over objective values goes through this, so the
+/// best point, simplex order and seed selection do not depend on the order
+/// in which tied evaluations were told.
+pub fn cmp_traces(a: &EvalTrace, b: &EvalTrace) -> Ordering {
+    a.value
+        .total_cmp(&b.value)
+        .then_with(|| cmp_params(&a.params, &b.params))
+        .then(a.eval_id.cmp(&b.eval_id))
+}
+
+/// [`cmp_params`] and [`cmp_traces`] over a key set sorted once, for sorts
+/// over many points of the same space: comparisons walk the sorted keys
+/// instead of collecting and sorting both maps. Maps with a key outside the
+/// set fall back to [`cmp_params`], so the order is always the same.
+pub struct ParamOrder<'a> {
+    keys: Vec<&'a str>,
+}
+
+impl<'a> ParamOrder<'a> {
+    pub fn new(keys: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut keys: Vec<&'a str> = keys.into_iter().collect();
+        keys.sort_unstable();
+        keys.dedup();
+        Self { keys }
+    }
+
+    /// Same result as [`cmp_params`].
+    pub fn cmp_params(&self, a: &HashMap<String, f64>, b: &HashMap<String, f64>) -> Ordering {
+        if !(self.covers(a) && self.covers(b)) {
+            return cmp_params(a, b);
+        }
+        for ((ka, va), (kb, vb)) in self.entries(a).zip(self.entries(b)) {
+            let order = ka.cmp(kb).then_with(|| va.total_cmp(vb));
+            if order != Ordering::Equal {
+                return order;
+            }
+        }
+        a.len().cmp(&b.len())
+    }
+
+    /// Same result as [`cmp_traces`].
+    pub fn cmp_traces(&self, a: &EvalTrace, b: &EvalTrace) -> Ordering {
+        a.value
+            .total_cmp(&b.value)
+            .then_with(|| self.cmp_params(&a.params, &b.params))
+            .then(a.eval_id.cmp(&b.eval_id))
+    }
+
+    /// Entries of `params` in key order.
+    fn entries<'m>(
+        &'m self,
+        params: &'m HashMap<String, f64>,
+    ) -> impl Iterator<Item = (&'m String, &'m f64)> + 'm {
+        self.keys
+            .iter()
+            .filter_map(move |k| params.get_key_value(*k))
+    }
+
+    fn covers(&self, params: &HashMap<String, f64>) -> bool {
+        self.keys
+            .iter()
+            .filter(|k| params.contains_key(**k))
+            .count()
+            == params.len()
+    }
+}
+
+/// [`cmp_traces`] for seed points, which carry no `eval_id`.
+pub fn cmp_seed_points(a: &SeedPoint, b: &SeedPoint) -> Ordering {
+    a.value
+        .total_cmp(&b.value)
+        .then_with(|| cmp_params(&a.params, &b.params))
+}
+
+/// True if `a` Pareto-dominates `b` over (`value`, `cost`), both minimized:
+/// no worse in either objective and strictly better in at least one.
+pub fn dominates(a: &EvalTrace, b: &EvalTrace) -> bool {
+    a.value <= b.value && a.cost <= b.cost && (a.value < b.value || a.cost < b.cost)
+}
+
+/// Non-dominated traces over (`value`, `cost`), in history order.
+///
+/// Traces with identical (`value`, `cost`) do not dominate each other; only
+/// the first such trace is kept so the front has no duplicate objective
+/// vectors. Traces with a non-finite value or cost are ignored.
+pub fn pareto_front(history: &[EvalTrace]) -> Vec<&EvalTrace> {
+    let finite: Vec<&EvalTrace> = history
+        .iter()
+        .filter(|t| t.value.is_finite() && t.cost.is_finite())
+        .collect();
+    let mut front: Vec<&EvalTrace> = Vec::new();
+    for trace in &finite {
+        let dominated = finite.iter().any(|other| dominates(other, trace));
+        let duplicate = front
+            .iter()
+            .any(|kept| kept.value == trace.value && kept.cost == trace.cost);
+        if !dominated && !duplicate {
+            front.push(trace);
+        }
+    }
+    front
+}
+
+/// Non-dominated sorting layer of each trace (0 = Pareto front).
+///
+/// Duplicates share a layer. Traces with a non-finite value or cost are
+/// placed one layer behind all finite traces.
+pub fn pareto_ranks(history: &[EvalTrace]) -> Vec<usize> {
+    let n = history.len();
+    let finite = |t: &EvalTrace| t.value.is_finite() && t.cost.is_finite();
+    let mut ranks = vec![usize::MAX; n];
+    let mut remaining: Vec<usize> = (0..n).filter(|&i| finite(&history[i])).collect();
+    let mut layer = 0;
+    while !remaining.is_empty() {
+        let current: Vec<usize> = remaining
+            .iter()
+            .copied()
+            .filter(|&i| {
+                !remaining
+                    .iter()
+                    .any(|&j| dominates(&history[j], &history[i]))
+            })
+            .collect();
+        for &i in &current {
+            ranks[i] = layer;
+        }
+        remaining.retain(|i| !current.contains(i));
+        layer += 1;
+    }
+    for rank in ranks.iter_mut().filter(|r| **r == usize::MAX) {
+        *rank = layer;
+    }
+    ranks
+}
+
+/// Per-parameter importance as a first-order variance contribution.
+///
+/// Each parameter is mapped to [0, 1] through its domain and split into
+/// equal-width bins; its raw score is the correlation ratio η² (variance of
+/// bin means over total variance of the objective). Scores are normalized to
+/// sum to 1.0. Parameters that never varied score 0; with fewer than 3
+/// evaluations the map is empty.
+pub fn parameter_importance(
+    history: &[EvalTrace],
+    bounds: &HashMap<String, Domain>,
+) -> HashMap<String, f64> {
+    let history: Vec<&EvalTrace> = history.iter().filter(|t| t.value.is_finite()).collect();
+    let n = history.len();
+    if n < 3 {
+        return HashMap::new();
+    }
+
+    let mean = history.iter().map(|t| t.value).sum::<f64>() / n as f64;
+    let total_ss: f64 = history.iter().map(|t| (t.value - mean).powi(2)).sum();
+    let bins = ((n as f64).sqrt().round() as usize).clamp(2, 10);
+
+    let mut scores: HashMap<String, f64> = bounds
+        .iter()
+        .map(|(name, domain)| {
+            let units: Vec<Option<f64>> = history
+                .iter()
+                .map(|t| t.params.get(name).map(|&v| domain.to_unit(v)))
+                .collect();
+            let (lo, hi) = units
+                .iter()
+                .flatten()
+                .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &u| {
+                    (lo.min(u), hi.max(u))
+                });
+            if total_ss <= 0.0 || hi - lo <= f64::EPSILON {
+                return (name.clone(), 0.0);
+            }
+
+            let mut sums = vec![0.0; bins];
+            let mut counts = vec![0usize; bins];
+            for (unit, trace) in units.iter().zip(&history) {
+                if let Some(u) = unit {
+                    let bin = (((u - lo) / (hi - lo)) * bins as f64) as usize;
+                    let bin = bin.min(bins - 1);
+                    sums[bin] += trace.value;
+                    counts[bin] += 1;
+                }
+            }
+            let between_ss: f64 = sums
+                .iter()
+                .zip(&counts)
+                .filter(|(_, &c)| c > 0)
+                .map(|(&s, &c)| c as f64 * (s / c as f64 - mean).powi(2))
+                .sum();
+            (name.clone(), between_ss / total_ss)
+        })
+        .collect();
+
+    let total: f64 = scores.values().sum();
+    if total > 0.0 {
+        for score in scores.values_mut() {
+            *score /= total;
+        }
+    }
+    scores
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Scale;
+
+    fn linear(min: f64, max: f64) -> Domain {
+        Domain {
+            min,
+            max,
+            scale: Scale::Linear,
+            active_when: None,
+        }
+    }
+
+    fn trace(eval_id: u64, params: &[(&str, f64)], value: f64) -> EvalTrace {
+        EvalTrace {
+            eval_id,
+            params: params.iter().map(|(k, v)| (k.to_string(), *v)).collect(),
+            value,
+            cost: 1.0,
+            repeats: None,
+            penalized: false,
+            failed: false,
+            feasibility: None,
+            penalty: None,
+            fidelity: None,
+            kind: None,
+            source: None,
+        }
+    }
+
+    #[test]
+    fn test_importance_ranks_dominant_parameter() {
+        let bounds: HashMap<String, Domain> = [
+            ("a".to_string(), linear(0.0, 1.0)),
+            ("b".to_string(), linear(0.0, 1.0)),
+        ]
+        .into_iter()
+        .collect();
+        let history: Vec<EvalTrace> = (0..50)
+            .map(|i| {
+                let a = (i % 10) as f64 / 9.0;
+                let b = (i / 10) as f64 / 4.0;
+                trace(i, &[("a", a), ("b", b)], 10.0 * a + 0.1 * b)
+            })
+            .collect();
+
+        let importance = parameter_importance(&history, &bounds);
+        assert!(importance["a"] > 0.9);
+        assert!(importance["b"] < 0.1);
+        assert!((importance.values().sum::<f64>() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_importance_constant_parameter_is_zero() {
+        let bounds: HashMap<String, Domain> = [
+            ("a".to_string(), linear(0.0, 1.0)),
+            ("fixed".to_string(), linear(0.0, 1.0)),
+        ]
+        .into_iter()
+        .collect();
+        let history: Vec<EvalTrace> = (0..10)
+            .map(|i| {
+                let a = i as f64 / 9.0;
+                trace(i, &[("a", a), ("fixed", 0.5)], a * a)
+            })
+            .collect();
+
+        let importance = parameter_importance(&history, &bounds);
+        assert_eq!(importance["fixed"], 0.0);
+        assert!((importance["a"] - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_importance_needs_three_evaluations() {
+        let bounds: HashMap<String, Domain> =
+            [("a".to_string(), linear(0.0, 1.0))].into_iter().collect();
+        let history = vec![trace(1, &[("a", 0.1)], 1.0), trace(2, &[("a", 0.9)], 2.0)];
+        assert!(parameter_importance(&history, &bounds).is_empty());
+    }
+
+    fn point(eval_id: u64, value: f64, cost: f64) -> EvalTrace {
+        EvalTrace {
+            eval_id,
+            params: HashMap::new(),
+            value,
+            cost,
+            repeats: None,
+            penalized: false,
+            failed: false,
+            feasibility: None,
+            penalty: None,
+            fidelity: None,
+            kind: None,
+            source: None,
+        }
+    }
+
+    fn ids(front: &[&EvalTrace]) -> Vec<u64> {
+        front.iter().map(|t| t.eval_id).collect()
+    }
+
+    #[test]
+    fn test_dominates() {
+        assert!(dominates(&point(1, 1.0, 1.0), &point(2, 2.0, 2.0)));
+        assert!(dominates(&point(1, 1.0, 1.0), &point(2, 1.0, 2.0)));
+        assert!(!dominates(&point(1, 1.0, 2.0), &point(2, 2.0, 1.0)));
+        // Equal points do not dominate each other
+        assert!(!dominates(&point(1, 1.0, 1.0), &point(2, 1.0, 1.0)));
+    }
+
+    #[test]
+    fn test_pareto_front_tradeoff() {
+        let history = vec![
+            point(1, 1.0, 5.0),
+            point(2, 2.0, 2.0),
+            point(3, 3.0, 3.0), // dominated by 2
+            point(4, 5.0, 1.0),
+            point(5, 1.0, 6.0), // tie on value, worse cost
+        ];
+        assert_eq!(ids(&pareto_front(&history)), vec![1, 2, 4]);
+    }
+
+    #[test]
+    fn test_pareto_front_duplicates_kept_once() {
+        let history = vec![point(1, 2.0, 2.0), point(2, 2.0, 2.0), point(3, 1.0, 3.0)];
+        assert_eq!(ids(&pareto_front(&history)), vec![1, 3]);
+    }
+
+    #[test]
+    fn test_pareto_front_ignores_non_finite() {
+        let history = vec![point(1, f64::NAN, 0.0), point(2, 1.0, 1.0)];
+        assert_eq!(ids(&pareto_front(&history)), vec![2]);
+        assert!(pareto_front(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_pareto_ranks_layers() {
+        let history = vec![
+            point(1, 1.0, 3.0),
+            point(2, 2.0, 2.0),
+            point(3, 2.0, 2.0), // duplicate shares layer 0
+            point(4, 3.0, 3.0),
+            point(5, 4.0, 4.0),
+            point(6, f64::INFINITY, 0.0),
+        ];
+        assert_eq!(pareto_ranks(&history), vec![0, 0, 0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_artifact_without_importance_roundtrips() {
+        let json = r#"{"run_id":"r","seed":1,"budget":2,
+            "config":{"seed":1,"budget":2,"bounds":{}},"history":[]}"#;
+        let artifact: RunArtifact = serde_json::from_str(json).unwrap();
+        assert!(artifact.importance.is_none());
+        let out = serde_json::to_string(&artifact).unwrap();
+        assert!(!out.contains("importance"));
+    }
+
+    fn sealed_artifact() -> RunArtifact {
+        let json = r#"{"run_id":"r","seed":1,"budget":2,
+            "config":{"seed":1,"budget":2,"bounds":{"x":{"min":0.0,"max":1.0},"y":{"min":0.0,"max":1.0}}},
+            "history":[]}"#;
+        let mut artifact: RunArtifact = serde_json::from_str(json).unwrap();
+        artifact.history = vec![
+            trace(1, &[("x", 0.25), ("y", 0.5)], 1.5),
+            trace(2, &[("x", 0.75), ("y", 0.125)], 0.5),
+        ];
+        artifact.seal();
+        artifact
+    }
+
+    #[test]
+    fn test_sealed_artifact_verifies_after_roundtrip() {
+        let artifact = sealed_artifact();
+        assert_eq!(artifact.format_version, ARTIFACT_FORMAT_VERSION);
+        let mut loaded: RunArtifact =
+            serde_json::from_str(&serde_json::to_string(&artifact).unwrap()).unwrap();
+        assert_eq!(loaded.verify(), Ok(()));
+        assert_eq!(loaded.checksum, artifact.checksum);
+    }
+
+    #[test]
+    fn test_tampered_artifact_fails_verification() {
+        let json = serde_json::to_string(&sealed_artifact()).unwrap();
+        let tampered = json.replacen("0.5", "0.25", 1);
+        assert_ne!(json, tampered);
+        let mut loaded: RunArtifact = serde_json::from_str(&tampered).unwrap();
+        assert!(matches!(
+            loaded.verify(),
+            Err(ArtifactError::ChecksumMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_v0_artifact_migrates() {
+        let json = r#"{"run_id":"r","seed":1,"budget":2,
+            "config":{"seed":1,"budget":2,"bounds":{}},
+            "history":[{"eval_id":1,"params":{},"value":1.0,"cost":1.0}]}"#;
+        let mut artifact: RunArtifact = serde_json::from_str(json).unwrap();
+        assert_eq!(artifact.format_version, 0);
+        artifact.verify().unwrap();
+        assert_eq!(artifact.format_version, ARTIFACT_FORMAT_VERSION);
+        assert_eq!(artifact.history.len(), 1);
+        assert!(artifact.checksum.is_none());
+    }
+
+    #[test]
+    fn test_v1_artifact_verifies_against_stored_content() {
+        // Sealed by a build whose config had fewer fields: its checksum hashes
+        // the `null` it wrote, and none of the fields added since.
+        let mut stored = serde_json::json!({
+            "format_version": 1,
+            "run_id": "r",
+            "seed": 1,
+            "budget": 2,
+            "config": {"seed": 1, "budget": 2, "bounds": {}, "strategy_params": null},
+            "history": [{"eval_id": 1, "params": {"x": 0.5}, "value": 1.0, "cost": 1.0}],
+        });
+        stored["checksum"] = content_checksum(
+            &serde_json::json!({"config": stored["config"], "history": stored["history"]}),
+            1,
+        )
+        .into();
+
+        let artifact = RunArtifact::from_stored(stored.clone()).unwrap();
+        assert_eq!(artifact.format_version, ARTIFACT_FORMAT_VERSION);
+        assert!(artifact.checksum.is_none());
+
+        stored["history"][0]["value"] = 0.5.into();
+        assert!(matches!(
+            RunArtifact::from_stored(stored),
+            Err(ArtifactError::ChecksumMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_stored_artifact_checksum_ignores_null_fields() {
+        let artifact = sealed_artifact();
+        let mut stored = serde_json::to_value(&artifact).unwrap();
+        // As written by TOML, which has no null, or by a build without the field.
+        stored["config"]
+            .as_object_mut()
+            .unwrap()
+            .retain(|_, value| !value.is_null());
+        let loaded = RunArtifact::from_stored(stored).unwrap();
+        assert_eq!(loaded.checksum, artifact.checksum);
+    }
+
+    #[test]
+    fn test_future_artifact_version_rejected() {
+        let mut artifact = sealed_artifact();
+        artifact.format_version = ARTIFACT_FORMAT_VERSION + 1;
+        assert_eq!(
+            artifact.verify(),
+            Err(ArtifactError::UnsupportedVersion {
+                found: ARTIFACT_FORMAT_VERSION + 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_param_order_matches_cmp_params() {
+        let order = ParamOrder::new(["b", "a", "c"]);
+        let points = [
+            trace(1, &[("a", 0.5), ("b", 0.1)], 1.0),
+            trace(2, &[("a", 0.5), ("b", 0.2)], 1.0),
+            trace(3, &[("a", 0.5)], 1.0),
+            trace(4, &[("b", 0.1), ("c", 0.0)], 1.0),
+            trace(5, &[("a", 0.5), ("b", 0.1), ("z", 9.0)], 1.0),
+            trace(6, &[("a", -0.0), ("b", 0.1)], 1.0),
+            trace(7, &[("a", 0.0), ("b", 0.1)], 0.5),
+        ];
+        for a in &points {
+            for b in &points {
+                assert_eq!(
+                    order.cmp_params(&a.params, &b.params),
+                    cmp_params(&a.params, &b.params)
+                );
+                assert_eq!(order.cmp_traces(a, b), cmp_traces(a, b));
+            }
+        }
+    }
 }
@@ -1,7 +1,7 @@
-use crate::artifact::EvalTrace;
+use crate::artifact::{cmp_traces, EvalTrace};
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Landscape {
     Structured,
     Chaotic,
@@ -11,6 +11,25 @@ pub trait Classify: Send + Sync {
     /// Classify the landscape based on probe history.
     /// Returns (Label, Score). Score > threshold implies Chaotic usually.
     fn classify(&self, history: &[EvalTrace]) -> (Landscape, f64);
+
+    /// Confidence in [0, 1] of a label returned with `score`, used to weight
+    /// votes in `EnsembleClassifier`. Defaults to full confidence.
+    fn confidence(&self, _score: f64) -> f64 {
+        1.0
+    }
+}
+
+/// Relative distance of `score` from `threshold`, capped at 1.
+fn threshold_margin(score: f64, threshold: f64) -> f64 {
+    if !score.is_finite() {
+        return 0.0;
+    }
+    let margin = (score - threshold).abs();
+    if threshold.abs() > 1e-12 {
+        (margin / threshold.abs()).min(1.0)
+    } else {
+        margin.min(1.0)
+    }
 }
 
 // ============================================================================
@@ -19,11 +38,59 @@ pub trait Classify: Send + Sync {
 
 pub struct VarianceClassifier {
     pub threshold: f64,
+    /// Observation noise standard deviation per evaluation (0 = noiseless).
+    /// Its variance is removed from the sample variance before computing CV.
+    pub noise_std: f64,
+    /// Fraction of the lowest and of the highest values dropped before
+    /// computing CV, in [0, 0.5). Only applied from `MIN_TRIM_SAMPLES` values.
+    pub trim_fraction: f64,
 }
 
 impl Default for VarianceClassifier {
     fn default() -> Self {
-        Self { threshold: 2.0 } // arbitrary default, tuned later
+        Self {
+            threshold: 2.0, // arbitrary default, tuned later
+            noise_std: 0.0,
+            trim_fraction: 0.0,
+        }
+    }
+}
+
+impl VarianceClassifier {
+    /// Set the observation noise (see `SolverConfig::observation_noise`).
+    /// Non-finite or negative estimates are treated as noiseless.
+    pub fn with_noise(mut self, observation_noise: Option<f64>) -> Self {
+        self.noise_std = observation_noise
+            .filter(|s| s.is_finite() && *s > 0.0)
+            .unwrap_or(0.0);
+        self
+    }
+
+    /// `strategy_params` key setting `trim_fraction`.
+    pub const TRIM_KEY: &'static str = "variance_trim_fraction";
+    /// Fewer values than this are never trimmed.
+    pub const MIN_TRIM_SAMPLES: usize = 10;
+
+    /// Set the trim fraction. Values outside [0, 0.5) disable trimming.
+    pub fn with_trim(mut self, trim_fraction: Option<f64>) -> Self {
+        self.trim_fraction = trim_fraction
+            .filter(|f| (0.0..0.5).contains(f))
+            .unwrap_or(0.0);
+        self
+    }
+
+    /// `history` ordered by value with `trim_fraction` of each tail removed.
+    /// Ties are broken by [`cmp_traces`], so the result does not depend on
+    /// history order.
+    fn trimmed<'a>(&self, history: &'a [EvalTrace]) -> Vec<&'a EvalTrace> {
+        let mut sorted: Vec<&EvalTrace> = history.iter().collect();
+        if self.trim_fraction <= 0.0 || history.len() < Self::MIN_TRIM_SAMPLES {
+            return sorted;
+        }
+        sorted.sort_by(|a, b| cmp_traces(a, b));
+        let n = sorted.len();
+        let cut = ((n as f64 * self.trim_fraction).floor() as usize).min((n - 2) / 2);
+        sorted[cut..n - cut].to_vec()
     }
 }
 
@@ -33,9 +100,16 @@ impl Classify for VarianceClassifier {
             return (Landscape::Chaotic, 1.0); // Default safe fallback
         }
 
+        let history = self.trimmed(history);
         let values: Vec<f64> = history.iter().map(|t| t.value).collect();
         let mean = values.iter().sum::<f64>() / values.len() as f64;
         let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64;
+        let noise_var = history
+            .iter()
+            .map(|t| t.noise_std(self.noise_std).powi(2))
+            .sum::<f64>()
+            / history.len() as f64;
+        let variance = (variance - noise_var).max(0.0);
 
         // Coefficient of Variation (CV) = sigma / mu
         let cv = if mean.abs() > 1e-9 {
@@ -50,6 +124,10 @@ impl Classify for VarianceClassifier {
             (Landscape::Chaotic, cv)
         }
     }
+
+    fn confidence(&self, score: f64) -> f64 {
+        threshold_margin(score, self.threshold)
+    }
 }
 
 // ============================================================================
@@ -70,6 +148,9 @@ pub struct ResidualDecayClassifier {
     pub alpha_threshold: f64,
     /// Minimum samples required for reliable estimation
     pub min_samples: usize,
+    /// Observation noise standard deviation per evaluation (0 = noiseless).
+    /// Residuals are reduced by the noise floor before estimating α.
+    pub noise_std: f64,
 }
 
 impl Default for ResidualDecayClassifier {
@@ -77,6 +158,7 @@ impl Default for ResidualDecayClassifier {
         Self {
             alpha_threshold: 0.5,
             min_samples: 5,
+            noise_std: 0.0,
         }
     }
 }
@@ -86,10 +168,19 @@ impl ResidualDecayClassifier {
     pub fn with_threshold(alpha_threshold: f64) -> Self {
         Self {
             alpha_threshold,
-            min_samples: 5,
+            ..Self::default()
         }
     }
 
+    /// Set the observation noise (see `SolverConfig::observation_noise`).
+    /// Non-finite or negative estimates are treated as noiseless.
+    pub fn with_noise(mut self, observation_noise: Option<f64>) -> Self {
+        self.noise_std = observation_noise
+            .filter(|s| s.is_finite() && *s > 0.0)
+            .unwrap_or(0.0);
+        self
+    }
+
     /// Estimate the decay rate α from a sequence of residuals.
     ///
     /// Given residuals E_k, we fit E_k ≈ C × β^k where:
@@ -159,7 +250,7 @@ impl ResidualDecayClassifier {
         }
 
         let mut sorted = values.to_vec();
-        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        sorted.sort_by(|a, b| a.total_cmp(b));
 
         // Reverse so we go from worst (largest) to best (smallest)
         // For structured functions, this produces decaying residuals
@@ -168,6 +259,32 @@ impl ResidualDecayClassifier {
         // Residuals: E_k = |sorted[k] - sorted[k+1]|
         sorted.windows(2).map(|w| (w[0] - w[1]).abs()).collect()
     }
+
+    /// Compute residuals like `compute_residuals`, minus the noise floor.
+    ///
+    /// The difference of two noisy observations has standard deviation
+    /// sqrt(σ_a² + σ_b²), so gaps smaller than that are indistinguishable
+    /// from noise. They collapse to zero and are ignored by `estimate_alpha`,
+    /// leaving only the decay that is actually resolved by the data.
+    fn compute_noisy_residuals(&self, history: &[EvalTrace]) -> Vec<f64> {
+        if history.len() < 2 {
+            return vec![];
+        }
+
+        let mut sorted: Vec<(f64, f64)> = history
+            .iter()
+            .map(|t| (t.value, t.noise_std(self.noise_std)))
+            .collect();
+        sorted.sort_by(|a, b| b.0.total_cmp(&a.0).then(b.1.total_cmp(&a.1)));
+
+        sorted
+            .windows(2)
+            .map(|w| {
+                let floor = (w[0].1.powi(2) + w[1].1.powi(2)).sqrt();
+                ((w[0].0 - w[1].0).abs() - floor).max(0.0)
+            })
+            .collect()
+    }
 }
 
 impl Classify for ResidualDecayClassifier {
@@ -177,8 +294,12 @@ impl Classify for ResidualDecayClassifier {
             return (Landscape::Chaotic, 0.0);
         }
 
-        let values: Vec<f64> = history.iter().map(|t| t.value).collect();
-        let residuals = self.compute_residuals(&values);
+        let residuals = if self.noise_std > 0.0 {
+            self.compute_noisy_residuals(history)
+        } else {
+            let values: Vec<f64> = history.iter().map(|t| t.value).collect();
+            self.compute_residuals(&values)
+        };
 
         if residuals.is_empty() {
             return (Landscape::Chaotic, 0.0);
@@ -199,6 +320,111 @@ impl Classify for ResidualDecayClassifier {
             (Landscape::Chaotic, alpha)
         }
     }
+
+    fn confidence(&self, score: f64) -> f64 {
+        threshold_margin(score, self.alpha_threshold)
+    }
+}
+
+// ============================================================================
+// EnsembleClassifier - combines several classifiers by voting
+// ============================================================================
+
+/// How `EnsembleClassifier` combines its members' labels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EnsembleVote {
+    /// One vote per member.
+    #[default]
+    Majority,
+    /// Each vote counts by the member's `Classify::confidence` in its score.
+    Weighted,
+}
+
+impl EnsembleVote {
+    /// Key used in `SolverConfig::strategy_params` to enable the ensemble
+    /// in `Solver::pcr`: 1 = majority, 2 = weighted, anything else = off.
+    pub const PARAM_KEY: &'static str = "ensemble";
+
+    /// Decode a numeric vote code. Returns None for unknown or fractional
+    /// codes.
+    pub fn from_code(code: f64) -> Option<Self> {
+        if code.fract() != 0.0 {
+            return None;
+        }
+        match code as i64 {
+            1 => Some(Self::Majority),
+            2 => Some(Self::Weighted),
+            _ => None,
+        }
+    }
+}
+
+/// Runs several classifiers and combines their labels.
+///
+/// The returned score is the share of the (weighted) vote that went to the
+/// winning label, so 1.0 means every member agreed and 0.5 is an even split
+/// between two labels. Ties go to `Chaotic`, the safer fallback.
+pub struct EnsembleClassifier {
+    pub members: Vec<Box<dyn Classify>>,
+    pub vote: EnsembleVote,
+}
+
+impl EnsembleClassifier {
+    pub fn new(members: Vec<Box<dyn Classify>>, vote: EnsembleVote) -> Self {
+        Self { members, vote }
+    }
+
+    /// `VarianceClassifier` and `ResidualDecayClassifier` with default
+    /// thresholds and the given observation noise.
+    pub fn variance_residual(observation_noise: Option<f64>, vote: EnsembleVote) -> Self {
+        Self::new(
+            vec![
+                Box::new(VarianceClassifier::default().with_noise(observation_noise)),
+                Box::new(ResidualDecayClassifier::default().with_noise(observation_noise)),
+            ],
+            vote,
+        )
+    }
+}
+
+impl Classify for EnsembleClassifier {
+    fn classify(&self, history: &[EvalTrace]) -> (Landscape, f64) {
+        let votes: Vec<(Landscape, f64, f64)> = self
+            .members
+            .iter()
+            .map(|member| {
+                let (label, score) = member.classify(history);
+                (label, score, member.confidence(score))
+            })
+            .collect();
+        if votes.is_empty() {
+            return (Landscape::Chaotic, 0.0);
+        }
+
+        let tally = |weight: &dyn Fn(f64) -> f64| {
+            votes
+                .iter()
+                .fold((0.0, 0.0), |(s, c), &(label, _, conf)| match label {
+                    Landscape::Structured => (s + weight(conf), c),
+                    Landscape::Chaotic => (s, c + weight(conf)),
+                })
+        };
+        let (mut structured, mut chaotic) = match self.vote {
+            EnsembleVote::Majority => tally(&|_| 1.0),
+            EnsembleVote::Weighted => tally(&|conf| conf.clamp(0.0, 1.0)),
+        };
+        if structured + chaotic <= 0.0 {
+            // Every member sat on its threshold; fall back to a plain count.
+            (structured, chaotic) = tally(&|_| 1.0);
+        }
+
+        let total = structured + chaotic;
+        if structured > chaotic {
+            (Landscape::Structured, structured / total)
+        } else {
+            (Landscape::Chaotic, chaotic / total)
+        }
+    }
 }
 
 #[cfg(test)]
@@ -215,9 +441,80 @@ mod tests {
             params: HashMap::new(),
             value,
             cost: 1.0,
+            repeats: None,
+            penalized: false,
+            failed: false,
+            feasibility: None,
+            penalty: None,
+            fidelity: None,
+            kind: None,
+            source: None,
+        }
+    }
+
+    /// Evenly spaced values: low CV (variance says Structured) but flat
+    /// residuals (residual decay says Chaotic).
+    fn disputed_history() -> Vec<EvalTrace> {
+        (0..10).map(|i| trace(10.0 + i as f64)).collect()
+    }
+
+    #[test]
+    fn test_ensemble_disagreement_resolves_deterministically() {
+        let history = disputed_history();
+        assert_eq!(
+            VarianceClassifier::default().classify(&history).0,
+            Landscape::Structured
+        );
+        assert_eq!(
+            ResidualDecayClassifier::default().classify(&history).0,
+            Landscape::Chaotic
+        );
+
+        // A 1-1 split is a tie, which goes to Chaotic with half the vote.
+        let majority = EnsembleClassifier::variance_residual(None, EnsembleVote::Majority);
+        assert_eq!(majority.classify(&history), (Landscape::Chaotic, 0.5));
+        assert_eq!(majority.classify(&history), majority.classify(&history));
+
+        // Member order does not matter.
+        let reversed = EnsembleClassifier::new(
+            vec![
+                Box::new(ResidualDecayClassifier::default()),
+                Box::new(VarianceClassifier::default()),
+            ],
+            EnsembleVote::Majority,
+        );
+        assert_eq!(reversed.classify(&history), majority.classify(&history));
+
+        // Weighted: CV ≈ 0.2 sits well under its threshold of 2, but α ≈ 0 is
+        // as far below 0.5 as it gets, so residual decay wins.
+        let weighted = EnsembleClassifier::variance_residual(None, EnsembleVote::Weighted);
+        let (label, score) = weighted.classify(&history);
+        assert_eq!(label, Landscape::Chaotic);
+        assert!(score > 0.5 && score < 1.0, "score {}", score);
+        assert_eq!(weighted.classify(&history), (label, score));
+    }
+
+    #[test]
+    fn test_ensemble_sphere_unanimous() {
+        let samples: Vec<EvalTrace> = (0..10).map(|i| trace(0.001 * 2f64.powi(i))).collect();
+
+        for vote in [EnsembleVote::Majority, EnsembleVote::Weighted] {
+            let ensemble = EnsembleClassifier::variance_residual(None, vote);
+            assert_eq!(ensemble.classify(&samples), (Landscape::Structured, 1.0));
         }
     }
 
+    #[test]
+    fn test_ensemble_vote_codes() {
+        assert_eq!(EnsembleVote::from_code(1.0), Some(EnsembleVote::Majority));
+        assert_eq!(EnsembleVote::from_code(2.0), Some(EnsembleVote::Weighted));
+        for code in [0.0, 1.4, 1.6, 3.0, f64::NAN] {
+            assert_eq!(EnsembleVote::from_code(code), None, "{}", code);
+        }
+        let empty = EnsembleClassifier::new(Vec::new(), EnsembleVote::Majority);
+        assert_eq!(empty.classify(&[]), (Landscape::Chaotic, 0.0));
+    }
+
     #[test]
     fn test_residual_decay_alpha_estimation() {
         let classifier = ResidualDecayClassifier::default();
@@ -339,4 +636,58 @@ mod tests {
             alpha
         );
     }
+
+    #[test]
+    fn test_residual_decay_noisy_sphere_structured_with_noise_floor() {
+        use rand::SeedableRng;
+        use rand_distr::{Distribution, Normal};
+
+        // Sphere f(x) = x² sampled along a geometrically converging trajectory,
+        // observed with Gaussian noise. The small gaps near the optimum are
+        // swamped by noise; removing the noise floor recovers the decay.
+        let sigma = 0.02;
+        let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(7);
+        let noise = Normal::new(0.0, sigma).unwrap();
+        let samples: Vec<EvalTrace> = (0..10)
+            .map(|k| {
+                let x = 0.512f64.sqrt() * 0.5f64.sqrt().powi(k);
+                trace(x * x + noise.sample(&mut rng))
+            })
+            .collect();
+
+        let (plain, plain_alpha) = ResidualDecayClassifier::default().classify(&samples);
+        let (landscape, alpha) = ResidualDecayClassifier::default()
+            .with_noise(Some(sigma))
+            .classify(&samples);
+
+        println!(
+            "Noisy sphere α = {} (without noise floor: {})",
+            alpha, plain_alpha
+        );
+        assert_eq!(
+            plain,
+            Landscape::Chaotic,
+            "noise should mask decay, α={}",
+            plain_alpha
+        );
+        assert_eq!(
+            landscape,
+            Landscape::Structured,
+            "Noisy sphere should be Structured once noise is discounted, α={}",
+            alpha
+        );
+    }
+
+    #[test]
+    fn test_variance_classifier_discounts_noise() {
+        let samples: Vec<EvalTrace> = [0.9, 1.1, 0.95, 1.05].into_iter().map(trace).collect();
+
+        let (_, cv) = VarianceClassifier::default().classify(&samples);
+        let (_, noisy_cv) = VarianceClassifier::default()
+            .with_noise(Some(0.1))
+            .classify(&samples);
+
+        assert!(cv > 0.0);
+        assert_eq!(noisy_cv, 0.0, "spread within the noise should not count");
+    }
 }
@@ -1,26 +1,659 @@
+use crate::artifact::{ParamOrder, RESERVED_KEYS};
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct SolverConfig {
     pub seed: u64,
+    /// Maximum number of evaluations; 0 means no count limit (requires `cost_budget`).
+    #[serde(default)]
     pub budget: u64,
     pub bounds: std::collections::HashMap<String, Domain>,
-    #[serde(default = "default_probe_ratio")]
-    pub probe_ratio: f64,
+    /// Share of the budget spent probing: a fraction, or `"auto"`.
+    #[serde(default)]
+    pub probe_ratio: ProbeBudget,
     #[serde(default)]
     pub strategy_params: Option<std::collections::HashMap<String, f64>>,
+    /// Feasibility constraints; candidates violating any of them are never evaluated.
+    #[serde(default)]
+    pub constraints: Vec<Constraint>,
+    /// How evaluations are ranked for seeding and refinement.
+    #[serde(default)]
+    pub selection: Selection,
+    /// Cap on the summed `cost` of all evaluations. The solver stops once it
+    /// is reached, and the probe phase ends after spending `probe_ratio` of it.
+    #[serde(default)]
+    pub cost_budget: Option<f64>,
+    /// Standard deviation of the objective's observation noise, if known.
+    /// Used to discount noise in classification and Nelder-Mead comparisons.
+    #[serde(default)]
+    pub observation_noise: Option<f64>,
+    /// How many times a Structured run may relaunch Nelder-Mead from a distant
+    /// seed after it converges with budget to spare (0 disables restarts).
+    #[serde(default)]
+    pub max_restarts: usize,
+    /// What to do with NaN or infinite objective values reported to the solver.
+    #[serde(default)]
+    pub non_finite: NonFinitePolicy,
+    /// What to do with told params that are unknown, non-finite or outside
+    /// `bounds`.
+    #[serde(default)]
+    pub invalid_params: InvalidParamPolicy,
+    /// Evaluations per candidate (0 and 1 both mean one). With more than
+    /// one, consecutive results for the same parameters in a `tell` are merged
+    /// into a single observation, so the budget counts points, not raw evals.
+    #[serde(default)]
+    pub repeats: usize,
+    /// How repeated results for one point are combined.
+    #[serde(default)]
+    pub repeat_reducer: RepeatReducer,
+    /// Transform applied to every objective value at `tell`, before any
+    /// strategy or the classifier sees it. Reported values are mapped back.
+    #[serde(default)]
+    pub objective_transform: Option<ObjectiveTransform>,
+    /// Parameters frozen at a value: added to every candidate, but never
+    /// probed or searched. Must not also appear in `bounds`.
+    #[serde(default)]
+    pub fixed: std::collections::HashMap<String, f64>,
+    /// Known optimum of the objective, in its original units. The run ends
+    /// as soon as an evaluation comes within `target_tol` of it or beats it.
+    #[serde(default)]
+    pub target_value: Option<f64>,
+    /// Distance from `target_value` that counts as reaching it.
+    #[serde(default)]
+    pub target_tol: f64,
+    /// Most traces the solver keeps in `history` while refining. Past it,
+    /// history is compacted to the best points plus a seeded sample of the
+    /// rest. Compaction only runs while refining. `None` keeps every trace.
+    #[serde(default)]
+    pub history_cap: Option<usize>,
+    /// Added to the value of a result told with a `_feasibility` below 1,
+    /// times its missing feasibility, in the solver's units (after
+    /// `objective_transform`). Near-feasible points then rank just behind
+    /// feasible ones instead of being discarded. Reported values are the
+    /// measured ones. 0 records infeasible results at their value; either
+    /// way `Solver::best` prefers any feasible result.
+    #[serde(default)]
+    pub infeasibility_penalty: f64,
+    /// Points evaluated first, as the first `ask` batch, before any probe
+    /// point. They count against `budget` and the probe's share of it;
+    /// probing fills the rest of that share.
+    #[serde(default)]
+    pub initial_points: Vec<std::collections::HashMap<String, f64>>,
+}
+
+/// Monotone transform of the objective, for values spanning many orders of
+/// magnitude or objectives to be maximized.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum ObjectiveTransform {
+    /// Natural log; objective values must be strictly positive.
+    Log,
+    /// `-value`, to maximize the objective.
+    Negate,
+    /// `(value^lambda - 1) / lambda`, or `ln(value)` for `lambda == 0`;
+    /// objective values must be strictly positive.
+    BoxCox { lambda: f64 },
+}
+
+impl ObjectiveTransform {
+    /// Map an objective value into the solver's units.
+    pub fn apply(&self, value: f64) -> Result<f64, InvalidObjective> {
+        let transformed = match *self {
+            Self::Negate => -value,
+            Self::Log | Self::BoxCox { .. } if value <= 0.0 => {
+                return Err(InvalidObjective {
+                    value,
+                    transform: *self,
+                })
+            }
+            Self::Log => value.ln(),
+            Self::BoxCox { lambda: 0.0 } => value.ln(),
+            Self::BoxCox { lambda } => (value.powf(lambda) - 1.0) / lambda,
+        };
+        Ok(transformed)
+    }
+
+    /// Map a value in the solver's units back to the objective's.
+    pub fn invert(&self, value: f64) -> f64 {
+        match *self {
+            Self::Negate => -value,
+            Self::Log => value.exp(),
+            Self::BoxCox { lambda: 0.0 } => value.exp(),
+            Self::BoxCox { lambda } => (lambda * value + 1.0).powf(1.0 / lambda),
+        }
+    }
+}
+
+/// An objective value outside the domain of the configured
+/// [`ObjectiveTransform`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InvalidObjective {
+    pub value: f64,
+    pub transform: ObjectiveTransform,
+}
+
+impl std::fmt::Display for InvalidObjective {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "objective value {} is outside the domain of objective_transform {:?} (values must be strictly positive)",
+            self.value, self.transform
+        )
+    }
+}
+
+impl std::error::Error for InvalidObjective {}
+
+/// Combination of repeated objective values for one point.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RepeatReducer {
+    #[default]
+    Mean,
+    Median,
+}
+
+impl RepeatReducer {
+    /// Reduce a non-empty set of values. Sorts `values` for the median.
+    pub fn reduce(&self, values: &mut [f64]) -> f64 {
+        match self {
+            Self::Mean => values.iter().sum::<f64>() / values.len() as f64,
+            Self::Median => {
+                values.sort_by(|a, b| a.total_cmp(b));
+                let mid = values.len() / 2;
+                if values.len().is_multiple_of(2) {
+                    (values[mid - 1] + values[mid]) / 2.0
+                } else {
+                    values[mid]
+                }
+            }
+        }
+    }
+}
+
+/// Handling of NaN or infinite objective values (e.g. a diverged training run).
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq)]
+pub enum NonFinitePolicy {
+    /// Refuse the results with a [`NonFiniteValue`] error.
+    #[default]
+    Reject,
+    /// Record `value` instead and flag the trace as penalized.
+    Penalize { value: f64 },
+}
+
+impl NonFinitePolicy {
+    /// Apply the policy to one objective value.
+    ///
+    /// Returns the value to record and whether it was replaced by the penalty.
+    pub fn resolve(&self, value: f64) -> Result<(f64, bool), NonFiniteValue> {
+        if value.is_finite() {
+            return Ok((value, false));
+        }
+        match *self {
+            Self::Reject => Err(NonFiniteValue(value)),
+            Self::Penalize { value: penalty } => Ok((penalty, true)),
+        }
+    }
+}
+
+/// A NaN or infinite objective value refused under [`NonFinitePolicy::Reject`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NonFiniteValue(pub f64);
+
+impl std::fmt::Display for NonFiniteValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "objective value {} is not finite (set non_finite to Penalize to record a penalty instead)",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for NonFiniteValue {}
+
+/// Handling of told results whose params do not fit `bounds`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub enum InvalidParamPolicy {
+    /// Refuse the results with an [`InvalidParam`] error.
+    #[default]
+    Reject,
+    /// Leave the offending results out of history.
+    Drop,
+    /// Clamp out-of-bounds values into `bounds` and remove unknown names.
+    /// Results with a non-finite param are left out.
+    Clamp,
+}
+
+/// Slack, as a fraction of a parameter's range, allowed past its bounds
+/// before a told value counts as out of bounds.
+pub const PARAM_BOUNDS_TOLERANCE: f64 = 1e-9;
+
+/// What is wrong with an [`InvalidParam`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InvalidParamKind {
+    /// The name is in neither `bounds` nor `fixed`.
+    Unknown,
+    NonFinite,
+    OutOfBounds {
+        min: f64,
+        max: f64,
+    },
+}
+
+/// A told param that does not fit the config.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvalidParam {
+    pub eval_id: u64,
+    pub name: String,
+    pub value: f64,
+    pub kind: InvalidParamKind,
+}
+
+impl std::fmt::Display for InvalidParam {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "eval {}: parameter {} = {} ",
+            self.eval_id, self.name, self.value
+        )?;
+        match self.kind {
+            InvalidParamKind::Unknown => write!(f, "is not in bounds or fixed"),
+            InvalidParamKind::NonFinite => write!(f, "is not finite"),
+            InvalidParamKind::OutOfBounds { min, max } => {
+                write!(f, "is outside [{}, {}]", min, max)
+            }
+        }
+    }
+}
+
+impl std::error::Error for InvalidParam {}
+
+/// Ranking of evaluations used for seeding and refinement.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub enum Selection {
+    /// Minimize `value` only.
+    #[default]
+    Value,
+    /// Track the Pareto front over (`value`, `cost`), both minimized.
+    Pareto,
+}
+
+impl SolverConfig {
+    /// `value`, in the objective's original units, turned so lower is better:
+    /// negated under `ObjectiveTransform::Negate`, unchanged otherwise (the
+    /// other transforms preserve order). Ranks user-facing values the way the
+    /// solver ranks its own.
+    pub fn minimized(&self, value: f64) -> f64 {
+        match self.objective_transform {
+            Some(ObjectiveTransform::Negate) => -value,
+            _ => value,
+        }
+    }
+
+    /// True if any parameter has an `active_when` condition.
+    pub fn has_conditions(&self) -> bool {
+        self.bounds.values().any(|d| d.active_when.is_some())
+    }
+
+    /// Whether parameter `name` is active for the point `params`.
+    pub fn is_active(&self, name: &str, params: &std::collections::HashMap<String, f64>) -> bool {
+        self.is_active_memo(name, params, &mut std::collections::HashMap::new())
+    }
+
+    /// [`is_active`](Self::is_active) sharing verdicts across calls, so each
+    /// parameter's conditions are evaluated once however many children
+    /// depend on it. `None` marks a parameter still being evaluated; meeting
+    /// it again means a cycle, which counts as inactive.
+    fn is_active_memo<'a>(
+        &'a self,
+        name: &str,
+        params: &std::collections::HashMap<String, f64>,
+        memo: &mut std::collections::HashMap<&'a str, Option<bool>>,
+    ) -> bool {
+        let Some((name, conditions)) = self
+            .bounds
+            .get_key_value(name)
+            .and_then(|(name, d)| Some((name.as_str(), d.active_when.as_ref()?)))
+        else {
+            return true;
+        };
+        match memo.get(name) {
+            Some(Some(active)) => return *active,
+            Some(None) => return false,
+            None => {}
+        }
+        memo.insert(name, None);
+        let active = conditions.iter().all(|(parent, values)| {
+            self.is_active_memo(parent, params, memo)
+                && params
+                    .get(parent)
+                    .is_some_and(|v| values.iter().any(|&c| v.round() == c))
+        });
+        memo.insert(name, Some(active));
+        active
+    }
+
+    /// `params` without its inactive parameters.
+    pub fn active_params(
+        &self,
+        params: std::collections::HashMap<String, f64>,
+    ) -> std::collections::HashMap<String, f64> {
+        if !self.has_conditions() {
+            return params;
+        }
+        let mut memo = std::collections::HashMap::new();
+        let inactive: Vec<String> = params
+            .keys()
+            .filter(|name| !self.is_active_memo(name, &params, &mut memo))
+            .cloned()
+            .collect();
+        let mut params = params;
+        for name in inactive {
+            params.remove(&name);
+        }
+        params
+    }
+
+    /// Add the `fixed` parameters to a candidate.
+    pub fn with_fixed(
+        &self,
+        mut params: std::collections::HashMap<String, f64>,
+    ) -> std::collections::HashMap<String, f64> {
+        for (name, &value) in &self.fixed {
+            params.insert(name.clone(), value);
+        }
+        params
+    }
+
+    /// The first param, by name, that is unknown, non-finite or outside its
+    /// bounds by more than [`PARAM_BOUNDS_TOLERANCE`]. The
+    /// [`RESERVED_KEYS`] are skipped.
+    pub fn invalid_param(
+        &self,
+        params: &std::collections::HashMap<String, f64>,
+    ) -> Option<(String, f64, InvalidParamKind)> {
+        let mut names: Vec<&String> = params
+            .keys()
+            .filter(|name| !RESERVED_KEYS.contains(&name.as_str()))
+            .collect();
+        names.sort();
+        names.into_iter().find_map(|name| {
+            let value = params[name];
+            let kind = match self.bounds.get(name) {
+                None if self.fixed.contains_key(name) => None,
+                None => Some(InvalidParamKind::Unknown),
+                Some(_) if !value.is_finite() => Some(InvalidParamKind::NonFinite),
+                Some(domain) => {
+                    let slack = PARAM_BOUNDS_TOLERANCE * (domain.max - domain.min);
+                    (value < domain.min - slack || value > domain.max + slack).then_some(
+                        InvalidParamKind::OutOfBounds {
+                            min: domain.min,
+                            max: domain.max,
+                        },
+                    )
+                }
+            };
+            kind.map(|kind| (name.clone(), value, kind))
+        })
+    }
+
+    /// Check that `fixed` values are finite and not also in `bounds`.
+    pub fn check_fixed(&self) -> Result<(), String> {
+        let mut names: Vec<&String> = self.fixed.keys().collect();
+        names.sort();
+        for name in names {
+            if self.bounds.contains_key(name) {
+                return Err(format!("{} is in both bounds and fixed", name));
+            }
+            if !self.fixed[name].is_finite() {
+                return Err(format!("fixed value for {} must be finite", name));
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that the run is bounded: a positive `budget`, or a positive,
+    /// finite `cost_budget` when `budget` is 0 (its default).
+    pub fn check_budget(&self) -> Result<(), String> {
+        match self.cost_budget {
+            None if self.budget == 0 => {
+                Err("budget must be > 0 unless cost_budget is set".to_string())
+            }
+            Some(cap) if !(cap.is_finite() && cap > 0.0) => {
+                Err("cost_budget must be a positive number".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Check that `history_cap`, if set, leaves room for the best points of
+    /// a Nelder-Mead simplex after compaction: at least `4 * (dim + 1)`.
+    pub fn check_history_cap(&self) -> Result<(), String> {
+        let min = 4 * (self.bounds.len() + 1);
+        match self.history_cap {
+            Some(cap) if cap < min => Err(format!(
+                "history_cap must be at least {} for {} parameters, got {}",
+                min,
+                self.bounds.len(),
+                cap
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Check that `initial_points` fit in the probe's share of the budget,
+    /// and that every entry sets each active parameter in `bounds` to a
+    /// finite value within them, nothing unknown, and satisfies
+    /// `constraints`.
+    pub fn check_initial_points(&self) -> Result<(), String> {
+        let share = self.probe_count().min(self.budget as usize);
+        if self.initial_points.len() > share {
+            return Err(format!(
+                "initial_points has {} points, more than the probe's share of the budget ({})",
+                self.initial_points.len(),
+                share
+            ));
+        }
+        for (index, point) in self.initial_points.iter().enumerate() {
+            if let Some((name, value, kind)) = self.invalid_param(point) {
+                let problem = match kind {
+                    InvalidParamKind::Unknown => "is not in bounds or fixed".to_string(),
+                    InvalidParamKind::NonFinite => "is not finite".to_string(),
+                    InvalidParamKind::OutOfBounds { min, max } => {
+                        format!("is outside [{}, {}]", min, max)
+                    }
+                };
+                return Err(format!(
+                    "initial_points[{}]: {} = {} {}",
+                    index, name, value, problem
+                ));
+            }
+            let mut names: Vec<&String> = self.bounds.keys().collect();
+            names.sort();
+            if let Some(name) = names
+                .into_iter()
+                .find(|name| !point.contains_key(*name) && self.is_active(name, point))
+            {
+                return Err(format!("initial_points[{}] is missing {}", index, name));
+            }
+            if let Some(constraint) = self.constraints.iter().find(|c| !c.is_satisfied(point)) {
+                return Err(format!(
+                    "initial_points[{}] violates the constraint {}",
+                    index, constraint.expr
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that `active_when` conditions only reference known parameters
+    /// and do not depend on themselves.
+    pub fn check_conditions(&self) -> Result<(), String> {
+        for (name, domain) in &self.bounds {
+            let Some(conditions) = &domain.active_when else {
+                continue;
+            };
+            for parent in conditions.keys() {
+                if !self.bounds.contains_key(parent) {
+                    return Err(format!(
+                        "active_when for {} references unknown parameter '{}'",
+                        name, parent
+                    ));
+                }
+            }
+        }
+        // Depth-first search marking each parameter once: `false` while it
+        // is on the current path, `true` once all its ancestors are done.
+        let mut marks = std::collections::HashMap::new();
+        let mut names: Vec<&String> = self.bounds.keys().collect();
+        names.sort();
+        for name in names {
+            if let Some(looped) = self.find_condition_cycle(name, &mut marks) {
+                return Err(format!("active_when for {} forms a cycle", looped));
+            }
+        }
+        Ok(())
+    }
+
+    /// First parameter found to depend on itself through `active_when`.
+    fn find_condition_cycle<'a>(
+        &'a self,
+        name: &'a str,
+        marks: &mut std::collections::HashMap<&'a str, bool>,
+    ) -> Option<&'a str> {
+        match marks.get(name) {
+            Some(true) => return None,
+            Some(false) => return Some(name),
+            None => {}
+        }
+        marks.insert(name, false);
+        if let Some(parents) = self.bounds.get(name).and_then(|d| d.active_when.as_ref()) {
+            for parent in parents.keys() {
+                if let Some(looped) = self.find_condition_cycle(parent, marks) {
+                    return Some(looped);
+                }
+            }
+        }
+        marks.insert(name, true);
+        None
+    }
+
+    /// Number of probe evaluations implied by `probe_ratio` and `budget`.
+    pub fn probe_count(&self) -> usize {
+        let budget = self.budget as usize;
+        match self.probe_ratio {
+            ProbeBudget::Fixed(ratio) => (self.budget as f64 * ratio).ceil() as usize,
+            ProbeBudget::Auto => {
+                let dim = self.bounds.len();
+                (budget / 3)
+                    .min(AUTO_PROBE_PER_DIM * dim)
+                    .max(dim + 1)
+                    .min(budget)
+            }
+        }
+    }
+
+    /// Probe share of the budget as a fraction, used to split `cost_budget`.
+    pub fn probe_fraction(&self) -> f64 {
+        match self.probe_ratio {
+            ProbeBudget::Fixed(ratio) => ratio,
+            ProbeBudget::Auto if self.budget > 0 => self.probe_count() as f64 / self.budget as f64,
+            ProbeBudget::Auto => default_probe_ratio(),
+        }
+    }
+
+    /// Look up a numeric entry in `strategy_params`.
+    pub fn strategy_param(&self, key: &str) -> Option<f64> {
+        self.strategy_params
+            .as_ref()
+            .and_then(|params| params.get(key).copied())
+    }
+
+    /// Canonical order over the bounds and fixed parameters, for sorting
+    /// many traces of this space.
+    pub fn param_order(&self) -> ParamOrder<'_> {
+        ParamOrder::new(
+            self.bounds
+                .keys()
+                .chain(self.fixed.keys())
+                .map(String::as_str),
+        )
+    }
 }
 
 fn default_probe_ratio() -> f64 {
     0.2
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Probe points per dimension targeted by [`ProbeBudget::Auto`].
+pub const AUTO_PROBE_PER_DIM: usize = 10;
+
+/// How much of the budget goes to the probe phase.
+///
+/// Serialized as a plain number for `Fixed` and the string `"auto"` for `Auto`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProbeBudget {
+    /// Fixed fraction of `budget`.
+    Fixed(f64),
+    /// `max(dim + 1, min(budget / 3, AUTO_PROBE_PER_DIM * dim))` points, so
+    /// low-dimensional runs don't over-probe and high-dimensional runs still
+    /// sample at least a simplex worth of points.
+    Auto,
+}
+
+impl Default for ProbeBudget {
+    fn default() -> Self {
+        Self::Fixed(default_probe_ratio())
+    }
+}
+
+impl From<f64> for ProbeBudget {
+    fn from(ratio: f64) -> Self {
+        Self::Fixed(ratio)
+    }
+}
+
+impl Serialize for ProbeBudget {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Fixed(ratio) => serializer.serialize_f64(*ratio),
+            Self::Auto => serializer.serialize_str("auto"),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ProbeBudget {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Ratio(f64),
+            Keyword(String),
+        }
+        match Repr::deserialize(deserializer)? {
+            Repr::Ratio(ratio) => Ok(Self::Fixed(ratio)),
+            Repr::Keyword(word) if word == "auto" => Ok(Self::Auto),
+            Repr::Keyword(word) => Err(serde::de::Error::custom(format!(
+                "probe_ratio must be a number or \"auto\", got \"{}\"",
+                word
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Domain {
     pub min: f64,
     pub max: f64,
     #[serde(default)]
     pub scale: Scale,
+    /// Makes the parameter conditional: it is only active when every listed
+    /// parameter is active and takes one of the listed values, e.g.
+    /// `{"optimizer": [0]}`. Choices are integer-coded, so the parent's value
+    /// is rounded to the nearest integer before matching. Inactive
+    /// parameters are left out of candidates.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active_when: Option<std::collections::BTreeMap<String, Vec<f64>>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
@@ -35,6 +668,319 @@ impl Domain {
     pub fn is_periodic(&self) -> bool {
         matches!(self.scale, Scale::Periodic)
     }
+
+    /// Position of `value` in [0, 1] under this domain's scale (clamped).
+    pub fn to_unit(&self, value: f64) -> f64 {
+        let unit = match self.scale {
+            Scale::Linear | Scale::Periodic => (value - self.min) / (self.max - self.min),
+            Scale::Log => (value.ln() - self.min.ln()) / (self.max.ln() - self.min.ln()),
+        };
+        if unit.is_finite() {
+            unit.clamp(0.0, 1.0)
+        } else {
+            0.0
+        }
+    }
+    /// Value at unit position `unit` under this domain's scale; the inverse
+    /// of `to_unit`.
+    pub fn from_unit(&self, unit: f64) -> f64 {
+        crate::probe::unit_to_domain(unit, self)
+    }
+}
+
+// ============================================================================
+// Constraints
+// ============================================================================
+
+/// A feasibility constraint `min <= expr <= max` over named parameters.
+///
+/// `expr` supports numbers, parameter names, `+ - * /`, unary minus and
+/// parentheses, e.g. `{"expr": "lr * batch_size", "max": 4.0}`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Constraint {
+    pub expr: String,
+    #[serde(default)]
+    pub min: Option<f64>,
+    #[serde(default)]
+    pub max: Option<f64>,
+}
+
+/// Error from parsing or evaluating a constraint expression.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConstraintError {
+    /// The expression could not be parsed.
+    Parse { expr: String, message: String },
+    /// The expression references a parameter that is not present.
+    UnknownParameter(String),
+    /// Neither `min` nor `max` is set.
+    Unbounded(String),
+}
+
+impl std::fmt::Display for ConstraintError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Parse { expr, message } => {
+                write!(f, "invalid constraint expression '{}': {}", expr, message)
+            }
+            Self::UnknownParameter(name) => {
+                write!(f, "constraint references unknown parameter '{}'", name)
+            }
+            Self::Unbounded(expr) => {
+                write!(f, "constraint '{}' must set min or max", expr)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConstraintError {}
+
+impl Constraint {
+    /// Check the constraint is well formed and only references `names`.
+    pub fn validate<'a>(
+        &self,
+        names: impl IntoIterator<Item = &'a String>,
+    ) -> Result<(), ConstraintError> {
+        if self.min.is_none() && self.max.is_none() {
+            return Err(ConstraintError::Unbounded(self.expr.clone()));
+        }
+        let expr = self.parse()?;
+        let names: Vec<&String> = names.into_iter().collect();
+        let mut vars = Vec::new();
+        expr.collect_vars(&mut vars);
+        for var in vars {
+            if !names.iter().any(|n| n.as_str() == var) {
+                return Err(ConstraintError::UnknownParameter(var.to_string()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Evaluate the expression against a parameter set.
+    pub fn evaluate(
+        &self,
+        params: &std::collections::HashMap<String, f64>,
+    ) -> Result<f64, ConstraintError> {
+        self.parse()?.eval(params)
+    }
+
+    /// True if the point satisfies `min <= expr <= max`.
+    ///
+    /// Malformed expressions and missing parameters count as violations.
+    pub fn is_satisfied(&self, params: &std::collections::HashMap<String, f64>) -> bool {
+        self.in_range(self.evaluate(params))
+    }
+
+    fn in_range(&self, value: Result<f64, ConstraintError>) -> bool {
+        match value {
+            Ok(value) if value.is_finite() => {
+                self.min.is_none_or(|min| value >= min) && self.max.is_none_or(|max| value <= max)
+            }
+            _ => false,
+        }
+    }
+
+    fn parse(&self) -> Result<Expr, ConstraintError> {
+        ExprParser::new(&self.expr).parse()
+    }
+}
+
+/// True if `params` satisfies every constraint.
+pub fn all_satisfied(
+    constraints: &[Constraint],
+    params: &std::collections::HashMap<String, f64>,
+) -> bool {
+    constraints.iter().all(|c| c.is_satisfied(params))
+}
+
+/// Constraints with their expressions parsed once, for checking many points.
+#[derive(Debug, Clone, Default)]
+pub struct ConstraintSet {
+    /// Each constraint with its parsed expression, or `None` if it does not
+    /// parse (then no point satisfies it).
+    parsed: Vec<(Constraint, Option<Expr>)>,
+}
+
+impl ConstraintSet {
+    pub fn new(constraints: &[Constraint]) -> Self {
+        Self {
+            parsed: constraints
+                .iter()
+                .map(|c| (c.clone(), c.parse().ok()))
+                .collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parsed.is_empty()
+    }
+
+    /// True if `params` satisfies every constraint (see
+    /// [`Constraint::is_satisfied`]).
+    pub fn all_satisfied(&self, params: &std::collections::HashMap<String, f64>) -> bool {
+        self.parsed.iter().all(|(constraint, expr)| match expr {
+            Some(expr) => constraint.in_range(expr.eval(params)),
+            None => false,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Num(f64),
+    Var(String),
+    Neg(Box<Expr>),
+    Bin(Box<Expr>, char, Box<Expr>),
+}
+
+impl Expr {
+    fn eval(
+        &self,
+        params: &std::collections::HashMap<String, f64>,
+    ) -> Result<f64, ConstraintError> {
+        Ok(match self {
+            Expr::Num(v) => *v,
+            Expr::Var(name) => *params
+                .get(name)
+                .ok_or_else(|| ConstraintError::UnknownParameter(name.clone()))?,
+            Expr::Neg(inner) => -inner.eval(params)?,
+            Expr::Bin(lhs, op, rhs) => {
+                let (l, r) = (lhs.eval(params)?, rhs.eval(params)?);
+                match op {
+                    '+' => l + r,
+                    '-' => l - r,
+                    '*' => l * r,
+                    _ => l / r,
+                }
+            }
+        })
+    }
+
+    fn collect_vars<'a>(&'a self, out: &mut Vec<&'a str>) {
+        match self {
+            Expr::Num(_) => {}
+            Expr::Var(name) => out.push(name),
+            Expr::Neg(inner) => inner.collect_vars(out),
+            Expr::Bin(lhs, _, rhs) => {
+                lhs.collect_vars(out);
+                rhs.collect_vars(out);
+            }
+        }
+    }
+}
+
+/// Recursive-descent parser: expr := term (('+'|'-') term)*,
+/// term := factor (('*'|'/') factor)*, factor := '-' factor | atom.
+struct ExprParser<'a> {
+    src: &'a str,
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl<'a> ExprParser<'a> {
+    fn new(src: &'a str) -> Self {
+        Self {
+            src,
+            chars: src.chars().collect(),
+            pos: 0,
+        }
+    }
+
+    fn parse(mut self) -> Result<Expr, ConstraintError> {
+        let expr = self.parse_sum()?;
+        self.skip_ws();
+        if self.pos < self.chars.len() {
+            return Err(self.error(format!("unexpected '{}'", self.chars[self.pos])));
+        }
+        Ok(expr)
+    }
+
+    fn error(&self, message: String) -> ConstraintError {
+        ConstraintError::Parse {
+            expr: self.src.to_string(),
+            message,
+        }
+    }
+
+    fn skip_ws(&mut self) {
+        while self.pos < self.chars.len() && self.chars[self.pos].is_whitespace() {
+            self.pos += 1;
+        }
+    }
+
+    fn peek_op(&mut self, ops: &[char]) -> Option<char> {
+        self.skip_ws();
+        let c = *self.chars.get(self.pos)?;
+        if ops.contains(&c) {
+            self.pos += 1;
+            Some(c)
+        } else {
+            None
+        }
+    }
+
+    fn parse_sum(&mut self) -> Result<Expr, ConstraintError> {
+        let mut lhs = self.parse_product()?;
+        while let Some(op) = self.peek_op(&['+', '-']) {
+            let rhs = self.parse_product()?;
+            lhs = Expr::Bin(Box::new(lhs), op, Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_product(&mut self) -> Result<Expr, ConstraintError> {
+        let mut lhs = self.parse_factor()?;
+        while let Some(op) = self.peek_op(&['*', '/']) {
+            let rhs = self.parse_factor()?;
+            lhs = Expr::Bin(Box::new(lhs), op, Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_factor(&mut self) -> Result<Expr, ConstraintError> {
+        if self.peek_op(&['-']).is_some() {
+            return Ok(Expr::Neg(Box::new(self.parse_factor()?)));
+        }
+        self.skip_ws();
+        let Some(&c) = self.chars.get(self.pos) else {
+            return Err(self.error("unexpected end of expression".to_string()));
+        };
+        if c == '(' {
+            self.pos += 1;
+            let inner = self.parse_sum()?;
+            if self.peek_op(&[')']).is_none() {
+                return Err(self.error("missing ')'".to_string()));
+            }
+            return Ok(inner);
+        }
+        if c.is_ascii_digit() || c == '.' {
+            let start = self.pos;
+            while self.pos < self.chars.len() {
+                let ch = self.chars[self.pos];
+                let exponent_sign = (ch == '-' || ch == '+')
+                    && matches!(self.chars.get(self.pos - 1), Some('e') | Some('E'));
+                if ch.is_ascii_digit() || ch == '.' || ch == 'e' || ch == 'E' || exponent_sign {
+                    self.pos += 1;
+                } else {
+                    break;
+                }
+            }
+            let text: String = self.chars[start..self.pos].iter().collect();
+            return text
+                .parse::<f64>()
+                .map(Expr::Num)
+                .map_err(|_| self.error(format!("invalid number '{}'", text)));
+        }
+        if c.is_alphabetic() || c == '_' {
+            let start = self.pos;
+            while self.pos < self.chars.len()
+                && (self.chars[self.pos].is_alphanumeric() || self.chars[self.pos] == '_')
+            {
+                self.pos += 1;
+            }
+            return Ok(Expr::Var(self.chars[start..self.pos].iter().collect()));
+        }
+        Err(self.error(format!("unexpected '{}'", c)))
+    }
 }
 
 // Helper functions for Unit Interval [0, 1] arithmetic
@@ -98,22 +1044,182 @@ mod tests {
         assert_eq!(default_probe_ratio(), 0.2);
     }
 
+    #[test]
+    fn test_check_budget() {
+        let mut config = auto_config(2, 0);
+        assert!(config.check_budget().unwrap_err().contains("budget"));
+        config.cost_budget = Some(5.0);
+        assert!(config.check_budget().is_ok());
+        config.cost_budget = Some(f64::NAN);
+        assert!(config.check_budget().unwrap_err().contains("cost_budget"));
+        config.cost_budget = None;
+        config.budget = 10;
+        assert!(config.check_budget().is_ok());
+    }
+
+    fn auto_config(dim: usize, budget: u64) -> SolverConfig {
+        let bounds: std::collections::HashMap<_, _> = (0..dim)
+            .map(|i| {
+                (
+                    format!("x{}", i),
+                    Domain {
+                        min: 0.0,
+                        max: 1.0,
+                        scale: Scale::Linear,
+                        active_when: None,
+                    },
+                )
+            })
+            .collect();
+        serde_json::from_value(serde_json::json!({
+            "seed": 0,
+            "budget": budget,
+            "bounds": bounds,
+            "probe_ratio": "auto",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_probe_ratio_accepts_number_or_auto() {
+        let config = auto_config(2, 100);
+        assert_eq!(config.probe_ratio, ProbeBudget::Auto);
+        assert_eq!(
+            serde_json::to_value(&config).unwrap()["probe_ratio"],
+            "auto"
+        );
+
+        let fixed: ProbeBudget = serde_json::from_str("0.3").unwrap();
+        assert_eq!(fixed, ProbeBudget::Fixed(0.3));
+        assert_eq!(serde_json::to_string(&fixed).unwrap(), "0.3");
+        assert!(serde_json::from_str::<ProbeBudget>("\"half\"").is_err());
+    }
+
+    #[test]
+    fn test_two_level_conditions() {
+        let config: SolverConfig = serde_json::from_value(serde_json::json!({
+            "seed": 0,
+            "budget": 10,
+            "bounds": {
+                "optimizer": {"min": 0.0, "max": 2.0},
+                "momentum": {"min": 0.0, "max": 1.0, "active_when": {"optimizer": [0, 2]}},
+                "nesterov": {"min": 0.0, "max": 1.0, "active_when": {"momentum": [1]}},
+            },
+        }))
+        .unwrap();
+        assert!(config.has_conditions());
+        assert!(config.check_conditions().is_ok());
+
+        let point = |optimizer: f64, momentum: f64| -> std::collections::HashMap<String, f64> {
+            [
+                ("optimizer".to_string(), optimizer),
+                ("momentum".to_string(), momentum),
+                ("nesterov".to_string(), 0.5),
+            ]
+            .into_iter()
+            .collect()
+        };
+        let active = |p| {
+            let mut keys: Vec<String> = config.active_params(p).into_keys().collect();
+            keys.sort();
+            keys
+        };
+        assert_eq!(
+            active(point(0.2, 0.9)),
+            ["momentum", "nesterov", "optimizer"]
+        );
+        assert_eq!(active(point(1.8, 0.2)), ["momentum", "optimizer"]);
+        // Inactive parent switches off the whole subtree.
+        assert_eq!(active(point(1.0, 0.9)), ["optimizer"]);
+    }
+
+    #[test]
+    fn test_check_conditions_rejects_unknown_and_cycles() {
+        let mut config: SolverConfig = serde_json::from_value(serde_json::json!({
+            "seed": 0,
+            "budget": 10,
+            "bounds": {
+                "a": {"min": 0.0, "max": 1.0, "active_when": {"b": [1]}},
+                "b": {"min": 0.0, "max": 1.0, "active_when": {"a": [1]}},
+            },
+        }))
+        .unwrap();
+        assert!(config.check_conditions().unwrap_err().contains("cycle"));
+        assert!(!config.is_active("a", &Default::default()));
+
+        config.bounds.get_mut("b").unwrap().active_when = None;
+        config.bounds.get_mut("a").unwrap().active_when =
+            Some([("c".to_string(), vec![1.0])].into_iter().collect());
+        assert!(config.check_conditions().unwrap_err().contains("unknown"));
+    }
+
+    #[test]
+    fn test_conditions_on_deep_diamonds_are_linear() {
+        // Each level depends on both parameters of the level above, so
+        // walking every path would visit 2^40 chains.
+        let mut bounds = serde_json::Map::new();
+        for level in 0..40 {
+            for side in ["a", "b"] {
+                let mut domain = serde_json::json!({"min": 0.0, "max": 1.0});
+                if level > 0 {
+                    domain["active_when"] = serde_json::json!({
+                        format!("a{}", level - 1): [1],
+                        format!("b{}", level - 1): [1],
+                    });
+                }
+                bounds.insert(format!("{}{}", side, level), domain);
+            }
+        }
+        let config: SolverConfig = serde_json::from_value(serde_json::json!({
+            "seed": 0,
+            "budget": 10,
+            "bounds": bounds,
+        }))
+        .unwrap();
+        assert!(config.check_conditions().is_ok());
+
+        let mut point: std::collections::HashMap<String, f64> = config
+            .bounds
+            .keys()
+            .map(|name| (name.clone(), 1.0))
+            .collect();
+        assert_eq!(config.active_params(point.clone()).len(), 80);
+        point.insert("a0".to_string(), 0.0);
+        assert_eq!(config.active_params(point).len(), 2);
+    }
+
+    #[test]
+    fn test_auto_probe_count() {
+        // Low dimension, large budget: capped at AUTO_PROBE_PER_DIM * dim.
+        assert_eq!(auto_config(1, 1000).probe_count(), 10);
+        // High dimension, small budget: still a full simplex of probes.
+        assert_eq!(auto_config(10, 30).probe_count(), 11);
+        // Mid range: a third of the budget.
+        assert_eq!(auto_config(5, 60).probe_count(), 20);
+        // Degenerate budget: never more probes than evaluations.
+        assert_eq!(auto_config(10, 5).probe_count(), 5);
+        assert!((auto_config(5, 60).probe_fraction() - 1.0 / 3.0).abs() < 1e-12);
+    }
+
     #[test]
     fn test_domain_is_periodic() {
         let linear = Domain {
             min: 0.0,
             max: 1.0,
             scale: Scale::Linear,
+            active_when: None,
         };
         let log = Domain {
             min: 0.1,
             max: 10.0,
             scale: Scale::Log,
+            active_when: None,
         };
         let periodic = Domain {
             min: 0.0,
             max: 1.0,
             scale: Scale::Periodic,
+            active_when: None,
         };
 
         assert!(!linear.is_periodic());
@@ -212,4 +1318,135 @@ mod tests {
         let mean = circular_mean01(&values);
         assert!((0.0..1.0).contains(&mean));
     }
+
+    fn params(pairs: &[(&str, f64)]) -> std::collections::HashMap<String, f64> {
+        pairs.iter().map(|(k, v)| (k.to_string(), *v)).collect()
+    }
+
+    #[test]
+    fn test_constraint_parse_precedence() {
+        let c = Constraint {
+            expr: "a + b * 2 - (c - 1) / 2".to_string(),
+            min: None,
+            max: Some(0.0),
+        };
+        let value = c
+            .evaluate(&params(&[("a", 1.0), ("b", 3.0), ("c", 5.0)]))
+            .unwrap();
+        assert!((value - 5.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_constraint_parse_unary_and_scientific() {
+        let c = Constraint {
+            expr: "-lr * 1e3 + 2.5e-1".to_string(),
+            min: None,
+            max: None,
+        };
+        let value = c.evaluate(&params(&[("lr", 0.001)])).unwrap();
+        assert!((value - (-0.75)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_constraint_parse_errors() {
+        for expr in ["a +", "(a + b", "a $ b", "", "1..2"] {
+            let c = Constraint {
+                expr: expr.to_string(),
+                min: None,
+                max: Some(1.0),
+            };
+            assert!(
+                matches!(
+                    c.evaluate(&params(&[("a", 1.0), ("b", 1.0)])),
+                    Err(ConstraintError::Parse { .. })
+                ),
+                "expected parse error for '{}'",
+                expr
+            );
+        }
+    }
+
+    #[test]
+    fn test_constraint_is_satisfied() {
+        let c = Constraint {
+            expr: "a + b".to_string(),
+            min: Some(0.2),
+            max: Some(1.0),
+        };
+        assert!(c.is_satisfied(&params(&[("a", 0.3), ("b", 0.3)])));
+        assert!(!c.is_satisfied(&params(&[("a", 0.7), ("b", 0.7)])));
+        assert!(!c.is_satisfied(&params(&[("a", 0.05), ("b", 0.05)])));
+        // Missing parameter counts as a violation
+        assert!(!c.is_satisfied(&params(&[("a", 0.3)])));
+    }
+
+    #[test]
+    fn test_constraint_validate() {
+        let names = ["a".to_string(), "b".to_string()];
+        let ok = Constraint {
+            expr: "a * b".to_string(),
+            min: None,
+            max: Some(1.0),
+        };
+        assert!(ok.validate(&names).is_ok());
+
+        let unknown = Constraint {
+            expr: "a * z".to_string(),
+            min: None,
+            max: Some(1.0),
+        };
+        assert_eq!(
+            unknown.validate(&names),
+            Err(ConstraintError::UnknownParameter("z".to_string()))
+        );
+
+        let unbounded = Constraint {
+            expr: "a".to_string(),
+            min: None,
+            max: None,
+        };
+        assert!(matches!(
+            unbounded.validate(&names),
+            Err(ConstraintError::Unbounded(_))
+        ));
+    }
+
+    #[test]
+    fn test_constraint_deserialize() {
+        let c: Constraint = serde_json::from_str(r#"{"expr": "a + b", "max": 1.0}"#).unwrap();
+        assert_eq!(c.expr, "a + b");
+        assert_eq!(c.max, Some(1.0));
+        assert_eq!(c.min, None);
+    }
+
+    #[test]
+    fn test_objective_transform_roundtrip() {
+        let transforms = [
+            ObjectiveTransform::Log,
+            ObjectiveTransform::Negate,
+            ObjectiveTransform::BoxCox { lambda: 0.0 },
+            ObjectiveTransform::BoxCox { lambda: 0.5 },
+            ObjectiveTransform::BoxCox { lambda: -1.0 },
+        ];
+        for transform in transforms {
+            for value in [1e-3, 0.5, 1.0, 250.0] {
+                let back = transform.invert(transform.apply(value).unwrap());
+                assert!(
+                    (back - value).abs() < 1e-9 * value,
+                    "{:?} {}",
+                    transform,
+                    value
+                );
+            }
+        }
+        assert_eq!(ObjectiveTransform::Negate.apply(-2.0), Ok(2.0));
+        assert!(ObjectiveTransform::Log.apply(0.0).is_err());
+        assert!(ObjectiveTransform::BoxCox { lambda: 0.5 }
+            .apply(-1.0)
+            .is_err());
+
+        let parsed: ObjectiveTransform =
+            serde_json::from_str(r#"{"BoxCox": {"lambda": 0.25}}"#).unwrap();
+        assert_eq!(parsed, ObjectiveTransform::BoxCox { lambda: 0.25 });
+    }
 }
@@ -0,0 +1,203 @@
+//! Shared Solver Handle
+//!
+//! [`SolverHandle`] lets many threads or request handlers drive one
+//! [`Solver`]: `ask`, `tell` and `seed` take a write lock and run one at a
+//! time, while [`snapshot`](SolverHandle::snapshot), `best` and `phase`
+//! take a read lock and run concurrently. Every method holds the lock only
+//! for its own duration and returns owned data, so no lock is held across an
+//! `.await`.
+//!
+//! The methods are blocking: they wait for the lock on the calling thread,
+//! and `ask` and `tell` run a solver step while holding it. From an async
+//! runtime, call them through the runtime's blocking pool (for example
+//! `tokio::task::spawn_blocking`) so a queue of writers does not stall its
+//! worker threads.
+
+use crate::artifact::{EvalTrace, SeedPoint};
+use crate::machine::{Phase, Solver, TellError};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// Read-only view of a solver taken under a single read lock, so its fields
+/// are consistent with each other.
+#[derive(Debug, Clone)]
+pub struct SolverSnapshot {
+    pub phase: Phase,
+    /// Evaluations told so far, including compacted ones.
+    pub evaluations: usize,
+    /// Traces currently held in `history`.
+    pub history_len: usize,
+    /// Best evaluation, with its value in the objective's original units.
+    pub best: Option<EvalTrace>,
+    pub done: bool,
+}
+
+/// Error from a call through a [`SolverHandle`].
+#[derive(Debug)]
+pub enum HandleError {
+    /// An earlier call panicked while holding the lock, so the solver may be
+    /// half-updated. Every later call fails with this.
+    Poisoned,
+    /// `tell` or `seed` rejected the results.
+    Tell(TellError),
+}
+
+impl std::fmt::Display for HandleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Poisoned => write!(f, "solver lock poisoned by a panic in an earlier call"),
+            Self::Tell(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for HandleError {}
+
+impl From<TellError> for HandleError {
+    fn from(err: TellError) -> Self {
+        Self::Tell(err)
+    }
+}
+
+/// Cloneable, thread-safe handle to a [`Solver`].
+#[derive(Clone)]
+pub struct SolverHandle {
+    inner: Arc<RwLock<Solver>>,
+}
+
+impl SolverHandle {
+    pub fn new(solver: Solver) -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(solver)),
+        }
+    }
+
+    /// Next batch of candidates (see [`Solver::ask`]).
+    pub fn ask(&self) -> Result<Option<Vec<HashMap<String, f64>>>, HandleError> {
+        Ok(self.write()?.ask())
+    }
+
+    /// Record evaluation results (see [`Solver::tell`]).
+    pub fn tell(&self, eval_results: Vec<EvalTrace>) -> Result<(), HandleError> {
+        Ok(self.write()?.tell(eval_results)?)
+    }
+
+    /// Record results without eval ids (see [`Solver::seed`]).
+    pub fn seed(&self, evaluations: Vec<SeedPoint>) -> Result<(), HandleError> {
+        Ok(self.write()?.seed(evaluations)?)
+    }
+
+    /// Phase, counts and best point, read under one lock.
+    pub fn snapshot(&self) -> Result<SolverSnapshot, HandleError> {
+        let solver = self.read()?;
+        Ok(SolverSnapshot {
+            phase: solver.phase(),
+            evaluations: solver.evaluations(),
+            history_len: solver.history.len(),
+            best: solver.original_best(),
+            done: solver.is_done(),
+        })
+    }
+
+    /// Best evaluation in the objective's original units.
+    pub fn best(&self) -> Result<Option<EvalTrace>, HandleError> {
+        Ok(self.read()?.original_best())
+    }
+
+    pub fn phase(&self) -> Result<Phase, HandleError> {
+        Ok(self.read()?.phase())
+    }
+
+    /// Run `f` on the solver under the read lock, for queries the handle
+    /// does not expose.
+    pub fn with_solver<T>(&self, f: impl FnOnce(&Solver) -> T) -> Result<T, HandleError> {
+        let solver = self.read()?;
+        Ok(f(&solver))
+    }
+
+    /// Run `f` on the solver under the write lock.
+    pub fn with_solver_mut<T>(&self, f: impl FnOnce(&mut Solver) -> T) -> Result<T, HandleError> {
+        let mut solver = self.write()?;
+        Ok(f(&mut solver))
+    }
+
+    fn read(&self) -> Result<RwLockReadGuard<'_, Solver>, HandleError> {
+        self.inner.read().map_err(|_| HandleError::Poisoned)
+    }
+
+    fn write(&self) -> Result<RwLockWriteGuard<'_, Solver>, HandleError> {
+        self.inner.write().map_err(|_| HandleError::Poisoned)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::machine::tests::{evaluate, make_test_config, sphere};
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    #[test]
+    fn test_readers_see_consistent_snapshots_during_tells() {
+        let mut config = make_test_config();
+        config.budget = 40;
+        let handle = SolverHandle::new(Solver::pcr(config));
+        let finished = Arc::new(AtomicBool::new(false));
+
+        let readers: Vec<_> = (0..4)
+            .map(|_| {
+                let handle = handle.clone();
+                let finished = Arc::clone(&finished);
+                std::thread::spawn(move || {
+                    let mut last = handle.snapshot().unwrap();
+                    let mut reads = 0;
+                    while !finished.load(Ordering::SeqCst) || reads == 0 {
+                        let snapshot = handle.snapshot().unwrap();
+                        assert_eq!(snapshot.history_len, snapshot.evaluations);
+                        assert!(snapshot.evaluations >= last.evaluations);
+                        if let (Some(best), Some(previous)) = (&snapshot.best, &last.best) {
+                            assert!(best.value <= previous.value);
+                        }
+                        assert_eq!(snapshot.best.is_some(), snapshot.evaluations > 0);
+                        last = snapshot;
+                        reads += 1;
+                    }
+                    last
+                })
+            })
+            .collect();
+
+        while let Some(batch) = handle.ask().unwrap() {
+            let first_id = handle.with_solver(Solver::evaluations).unwrap() as u64;
+            handle.tell(evaluate(batch, first_id, sphere)).unwrap();
+        }
+        finished.store(true, Ordering::SeqCst);
+
+        let final_snapshot = handle.snapshot().unwrap();
+        assert!(final_snapshot.done);
+        for reader in readers {
+            let last = reader.join().unwrap();
+            assert!(last.evaluations <= final_snapshot.evaluations);
+        }
+        let best = |trace: Option<EvalTrace>| trace.map(|t| t.eval_id);
+        assert_eq!(best(handle.best().unwrap()), best(final_snapshot.best));
+        assert_eq!(handle.phase().unwrap(), final_snapshot.phase);
+    }
+
+    #[test]
+    fn test_panic_under_the_lock_poisons_the_handle() {
+        let handle = SolverHandle::new(Solver::pcr(make_test_config()));
+        let panicking = handle.clone();
+        let result = std::thread::spawn(move || {
+            panicking.with_solver_mut(|_| panic!("step failed halfway"))
+        })
+        .join();
+        assert!(result.is_err());
+
+        assert!(matches!(handle.ask(), Err(HandleError::Poisoned)));
+        assert!(matches!(handle.snapshot(), Err(HandleError::Poisoned)));
+        assert!(matches!(
+            handle.tell(Vec::new()),
+            Err(HandleError::Poisoned)
+        ));
+    }
+}
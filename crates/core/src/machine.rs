@@ -1,12 +1,33 @@
-use crate::artifact::{EvalTrace, SeedPoint};
-use crate::classify::{Classify, Landscape, ResidualDecayClassifier, VarianceClassifier};
-use crate::config::SolverConfig;
-use crate::probe::{PrimeSqrtSlopesRotConfig, PrimeSqrtSlopesRotProbe, Probe, UniformProbe};
-use crate::strategies::nelder_mead::NelderMead;
+use crate::artifact::{
+    cmp_traces, pareto_front, pareto_ranks, EvalTrace, SeedPoint, TraceKind, FIDELITY_KEY,
+};
+use crate::classify::{
+    Classify, EnsembleClassifier, EnsembleVote, Landscape, ResidualDecayClassifier,
+    VarianceClassifier,
+};
+use crate::config::{
+    dist01, ConstraintSet, InvalidObjective, InvalidParam, InvalidParamKind, InvalidParamPolicy,
+    NonFiniteValue, RepeatReducer, Selection, SolverConfig,
+};
+use crate::probe::{
+    unit_to_domain, PrimeSqrtSlopesRotConfig, PrimeSqrtSlopesRotProbe, Probe, ProbeKind,
+    UniformProbe,
+};
+use crate::rng::{
+    derive_rng, COMPACTION_STREAM, CP_RESTART_STREAM, CP_SHIFT_STREAM, RESAMPLE_STREAM,
+};
+use crate::strategies::surrogate::{Surrogate, SurrogateConfig};
 // use crate::strategies::multi_start_nm::MultiStartNM;
 use crate::strategies::tpe::TPE;
-use crate::strategies::{Strategy, StrategyAction};
-use std::collections::HashMap;
+use crate::strategies::{
+    dense_point, param_registry, DenseHistory, Strategy, StrategyAction, StrategyFactory,
+    StrategyKind, StrategyRegistry,
+};
+use hotpath::config_atomic::{ParamRegistry, ParamVec};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::{HashMap, VecDeque};
+use std::io::Write;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Phase {
@@ -16,6 +37,28 @@ pub enum Phase {
     Done,
 }
 
+impl Phase {
+    /// Lowercase name for status output, e.g. `refine_chaotic`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Phase::Probe => "probe",
+            Phase::Classify => "classify",
+            Phase::Refine(Landscape::Structured) => "refine_structured",
+            Phase::Refine(Landscape::Chaotic) => "refine_chaotic",
+            Phase::Done => "done",
+        }
+    }
+}
+
+/// Distance between two points, used to keep Top-K seeds apart.
+///
+/// Should be on the scale of unit-space Euclidean distance, where the
+/// diagonal of the search space is `sqrt(dim)`.
+pub type SeedDistanceFn = fn(&SolverConfig, &HashMap<String, f64>, &HashMap<String, f64>) -> f64;
+
+/// Default `SeedingConfig::diversity_weight`.
+pub const DEFAULT_SEED_DIVERSITY_WEIGHT: f64 = 0.5;
+
 /// Configuration for solver seeding behavior
 #[derive(Debug, Clone)]
 pub struct SeedingConfig {
@@ -23,6 +66,15 @@ pub struct SeedingConfig {
     pub top_k: Option<usize>,
     /// Whether to use probe points to seed Nelder-Mead simplex
     pub seed_nm: bool,
+    /// Value/diversity tradeoff of Top-K selection, in [0, 1]. After the best
+    /// point, each seed maximizes `(1 - w) * goodness + w * spread`, where
+    /// goodness is the value min-max scaled to [0, 1] (1 = best) and spread
+    /// is the distance to the nearest chosen seed over the unit diagonal.
+    /// 0 picks by value alone.
+    pub diversity_weight: f64,
+    /// Distance used for the spread term (default: unit-space Euclidean,
+    /// wrapping periodic dimensions).
+    pub distance: SeedDistanceFn,
 }
 
 impl Default for SeedingConfig {
@@ -30,10 +82,227 @@ impl Default for SeedingConfig {
         Self {
             top_k: None, // Will default to dim + 1
             seed_nm: true,
+            diversity_weight: DEFAULT_SEED_DIVERSITY_WEIGHT,
+            distance: unit_distance,
+        }
+    }
+}
+
+/// Attempts per infeasible candidate before it is dropped.
+const CONSTRAINT_MAX_RETRIES: usize = 64;
+
+/// Times `ask` asks again after the pre-eval hook empties a batch.
+const HOOK_MAX_REASKS: usize = 8;
+
+/// Callback that edits each batch before `Solver::ask` returns it.
+///
+/// It runs in boundary code, once per batch, so allocating, logging or
+/// calling into the embedder is fine. Points it drops are never evaluated:
+/// dropped probe points shrink the probe budget, and refine strategies treat
+/// them like candidates rejected by `constraints`.
+pub type PreEvalHook = Box<dyn FnMut(&mut Vec<HashMap<String, f64>>) + Send + Sync>;
+
+/// Bisection steps used when projecting onto the feasible region.
+const PROJECTION_STEPS: usize = 32;
+
+/// Evaluation count used to size probe batches when only `cost_budget` is set.
+const COST_ONLY_SIZING_BUDGET: u64 = 100;
+
+/// Fraction of the budget after which a Structured run triggers a CP restart.
+const CP_RESTART_FRACTION: f64 = 0.7;
+
+/// Share of the budget that must remain for a diverse restart to be worth it.
+const DIVERSE_RESTART_MIN_REMAINING: f64 = 0.2;
+
+/// `strategy_params` flag: a positive value classifies halfway through the
+/// probe and ends probing there if the landscape is confidently Structured.
+pub const EARLY_EXIT_PROBE_KEY: &str = "early_exit_probe";
+
+/// `strategy_params` key overriding [`DEFAULT_EARLY_EXIT_CONFIDENCE`].
+pub const EARLY_EXIT_CONFIDENCE_KEY: &str = "early_exit_confidence";
+
+/// Classifier confidence (see `Classify::confidence`) a Structured label
+/// needs at the halfway check to end probing early: the score sits at least
+/// half the threshold away from the classifier's threshold.
+pub const DEFAULT_EARLY_EXIT_CONFIDENCE: f64 = 0.5;
+
+/// Probe requested via `strategy_params["probe"]`, or `default` if none is set.
+fn select_probe(config: &SolverConfig, default: Box<dyn Probe>) -> Box<dyn Probe> {
+    ProbeKind::from_config(config)
+        .map(ProbeKind::build)
+        .unwrap_or(default)
+}
+
+/// Parameter value rounded to 12 significant digits, so repeats still match
+/// after a text round trip or last-bit float noise. Written into `buf` so
+/// comparing points does not allocate.
+fn canonical_value(value: f64, buf: &mut [u8; 32]) -> &[u8] {
+    let mut cursor = std::io::Cursor::new(&mut buf[..]);
+    // At most 20 bytes: sign, 12 digits, point and a 3-digit exponent.
+    write!(cursor, "{:.11e}", value).expect("canonical value fits its buffer");
+    let len = cursor.position() as usize;
+    &buf[..len]
+}
+
+fn same_point(a: &HashMap<String, f64>, b: &HashMap<String, f64>) -> bool {
+    let (mut a_buf, mut b_buf) = ([0u8; 32], [0u8; 32]);
+    a.len() == b.len()
+        && a.iter().all(|(name, &value)| {
+            b.get(name).is_some_and(|&other| {
+                canonical_value(value, &mut a_buf) == canonical_value(other, &mut b_buf)
+            })
+        })
+}
+
+/// Merge runs of consecutive traces with the same parameters into one trace.
+///
+/// The merged trace keeps the first eval_id, reduces the values with
+/// `reducer`, sums the costs and records the sample count in `repeats`.
+/// It is failed if any sample failed and keeps the lowest feasibility.
+/// Values are reduced once per run, reusing one scratch buffer.
+fn merge_repeats(traces: Vec<EvalTrace>, reducer: RepeatReducer) -> Vec<EvalTrace> {
+    let mut merged: Vec<EvalTrace> = Vec::with_capacity(traces.len());
+    let mut values: Vec<f64> = Vec::new();
+    for trace in traces {
+        match merged.last_mut() {
+            Some(last)
+                if last.fidelity == trace.fidelity && same_point(&last.params, &trace.params) =>
+            {
+                values.push(trace.value);
+                last.cost += trace.cost;
+                last.penalized |= trace.penalized;
+                last.failed |= trace.failed;
+                if let Some(feasibility) = trace.feasibility {
+                    last.feasibility =
+                        Some(last.feasibility.map_or(feasibility, |f| f.min(feasibility)));
+                }
+            }
+            _ => {
+                close_repeats(merged.last_mut(), &mut values, reducer);
+                values.push(trace.value);
+                merged.push(trace);
+            }
+        }
+    }
+    close_repeats(merged.last_mut(), &mut values, reducer);
+    merged
+}
+
+/// Fold the values of a finished run of repeats into its trace.
+fn close_repeats(trace: Option<&mut EvalTrace>, values: &mut Vec<f64>, reducer: RepeatReducer) {
+    if let Some(trace) = trace.filter(|_| values.len() > 1) {
+        trace.repeats = Some(values.len() as u32);
+        trace.value = reducer.reduce(values);
+    }
+    values.clear();
+}
+
+/// Results refused by [`Solver::tell`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum TellError {
+    /// A NaN or infinite value under `NonFinitePolicy::Reject`.
+    NonFinite(NonFiniteValue),
+    /// A value the `objective_transform` cannot map.
+    InvalidObjective(InvalidObjective),
+    /// A param that does not fit `bounds` under `InvalidParamPolicy::Reject`.
+    InvalidParam(InvalidParam),
+}
+
+impl std::fmt::Display for TellError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NonFinite(err) => err.fmt(f),
+            Self::InvalidObjective(err) => err.fmt(f),
+            Self::InvalidParam(err) => err.fmt(f),
         }
     }
 }
 
+impl std::error::Error for TellError {}
+
+impl From<NonFiniteValue> for TellError {
+    fn from(err: NonFiniteValue) -> Self {
+        Self::NonFinite(err)
+    }
+}
+
+impl From<InvalidObjective> for TellError {
+    fn from(err: InvalidObjective) -> Self {
+        Self::InvalidObjective(err)
+    }
+}
+
+impl From<InvalidParam> for TellError {
+    fn from(err: InvalidParam) -> Self {
+        Self::InvalidParam(err)
+    }
+}
+
+/// The first evaluation that reached `config.target_value`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TargetReachedEvent {
+    /// Index of the evaluation in history.
+    pub index: usize,
+    pub eval_id: u64,
+    /// Its value in the objective's original units.
+    pub value: f64,
+}
+
+/// A diverse restart performed after Nelder-Mead converged with budget left.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RestartEvent {
+    /// History length when the restart happened.
+    pub at_eval: usize,
+    /// Best point found by the run that converged.
+    pub optimum: HashMap<String, f64>,
+    pub optimum_value: f64,
+    /// Evaluated point the new run starts from.
+    pub seed: HashMap<String, f64>,
+    /// Unit-space distance from `seed` to the nearest converged optimum.
+    pub distance: f64,
+}
+
+/// Evaluations compaction dropped from a solver's `history` (see
+/// `SolverConfig::history_cap`). They still count against the budgets, so
+/// they are saved alongside the history a run resumes from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct CompactedHistory {
+    pub evaluations: usize,
+    /// Total cost of the dropped evaluations.
+    pub cost: f64,
+    /// Largest eval id among the dropped evaluations, so seeded points never
+    /// reuse one.
+    #[serde(default)]
+    pub max_eval_id: u64,
+}
+
+impl CompactedHistory {
+    pub fn is_empty(&self) -> bool {
+        self.evaluations == 0
+    }
+}
+
+/// What a run would do with the current config and history, from `Solver::plan`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunPlan {
+    /// Evaluation budget (0 when only `cost_budget` limits the run).
+    pub budget: u64,
+    pub cost_budget: Option<f64>,
+    /// Evaluations spent in the probe phase.
+    pub probe_points: usize,
+    /// Evaluations left for refinement after probing, if the count is capped.
+    pub refine_budget: Option<usize>,
+    /// Probe used to sample the probe phase.
+    pub probe: String,
+    /// Evaluations already in history.
+    pub evaluated: usize,
+    /// Landscape classification, once history covers the probe budget.
+    pub classification: Option<Landscape>,
+    pub classification_score: Option<f64>,
+    /// `Strategy::name` of the refine strategy that classification selects.
+    pub strategy: Option<String>,
+}
+
 pub struct Solver {
     pub config: SolverConfig,
     pub history: Vec<EvalTrace>,
@@ -44,40 +313,161 @@ pub struct Solver {
     pub seeding: SeedingConfig,
     /// Has the solver performed a CP restart?
     pub restarted: bool,
+    /// Landscape and score from the classify phase, once it has run.
+    pub classification: Option<(Landscape, f64)>,
+    /// Best points of every refine run that converged, in order.
+    pub converged_optima: Vec<EvalTrace>,
+    /// Diverse restarts performed so far (at most `config.max_restarts`).
+    pub restarts: Vec<RestartEvent>,
+    /// Set once an evaluation reaches `config.target_value`; the run is then
+    /// done regardless of the budget left.
+    pub target_reached: Option<TargetReachedEvent>,
+    /// Rest of the last batch, handed out one at a time by `suggest`.
+    pub suggestions: VecDeque<HashMap<String, f64>>,
+    /// History length once every point of the last `suggest` batch is told.
+    pub suggested_until: usize,
+    /// Traces dropped from `history` by compaction.
+    compacted: CompactedHistory,
+    /// `config.constraints`, parsed.
+    constraints: ConstraintSet,
+    /// Probe points dropped because no feasible replacement was found.
+    infeasible_probes: usize,
+    /// Probe size after an early exit (see [`EARLY_EXIT_PROBE_KEY`]).
+    probe_cut: Option<usize>,
+    /// The halfway early-exit check has run.
+    early_exit_checked: bool,
+    /// History length once every probe point handed out so far is told.
+    probe_issued: usize,
+    /// Leading `config.initial_points` handed out, or found in seeded
+    /// history, so far.
+    initial_issued: usize,
+    /// Seed of the current restarted run and the history index it began at.
+    /// The strategy only sees the seed and the evaluations made since.
+    restart_anchor: Option<(EvalTrace, usize)>,
+    /// Phase and source of the last batch handed out, stamped by `tell` on
+    /// traces that carry no `kind` of their own.
+    batch_source: Option<(TraceKind, String)>,
+    /// Sorted `bounds` keys: the layout of the points strategies see through
+    /// `Strategy::step_dense`.
+    registry: ParamRegistry,
+    /// Params of each `history` entry in `registry` order, extended before
+    /// each strategy step.
+    dense_history: Vec<ParamVec>,
+    /// Under `Selection::Pareto`, `history` with each value replaced by its
+    /// Pareto score (see `strategy_view`), extended before each strategy
+    /// step.
+    pareto_history: Vec<EvalTrace>,
+    pre_eval_hook: Option<PreEvalHook>,
+    /// Refine strategies by name and landscape.
+    strategies: StrategyRegistry,
 }
 
 impl Solver {
     /// Create a new solver with MVP defaults (UniformProbe, VarianceClassifier)
     pub fn new(config: SolverConfig) -> Self {
         Self {
+            probe: select_probe(&config, Box::new(UniformProbe)),
+            classifier: Box::new(
+                VarianceClassifier::default()
+                    .with_noise(config.observation_noise)
+                    .with_trim(config.strategy_param(VarianceClassifier::TRIM_KEY)),
+            ),
+            registry: param_registry(&config),
+            dense_history: Vec::new(),
+            pareto_history: Vec::new(),
+            constraints: ConstraintSet::new(&config.constraints),
             config,
             history: Vec::new(),
             phase: Phase::Probe,
-            probe: Box::new(UniformProbe),
-            classifier: Box::new(VarianceClassifier::default()),
             strategy: None,
             seeding: SeedingConfig::default(),
             restarted: false,
+            classification: None,
+            converged_optima: Vec::new(),
+            restarts: Vec::new(),
+            target_reached: None,
+            infeasible_probes: 0,
+            probe_cut: None,
+            early_exit_checked: false,
+            probe_issued: 0,
+            initial_issued: 0,
+            restart_anchor: None,
+            batch_source: None,
+            suggestions: VecDeque::new(),
+            suggested_until: 0,
+            compacted: CompactedHistory::default(),
+            pre_eval_hook: None,
+            strategies: StrategyRegistry::builtin(),
         }
     }
 
     /// Create a solver with a custom classifier
     pub fn with_classifier(config: SolverConfig, classifier: Box<dyn Classify>) -> Self {
         Self {
+            probe: select_probe(&config, Box::new(UniformProbe)),
+            registry: param_registry(&config),
+            dense_history: Vec::new(),
+            pareto_history: Vec::new(),
+            constraints: ConstraintSet::new(&config.constraints),
             config,
             history: Vec::new(),
             phase: Phase::Probe,
-            probe: Box::new(UniformProbe),
             classifier,
             strategy: None,
             seeding: SeedingConfig::default(),
             restarted: false,
+            classification: None,
+            converged_optima: Vec::new(),
+            restarts: Vec::new(),
+            target_reached: None,
+            infeasible_probes: 0,
+            probe_cut: None,
+            early_exit_checked: false,
+            probe_issued: 0,
+            initial_issued: 0,
+            restart_anchor: None,
+            batch_source: None,
+            suggestions: VecDeque::new(),
+            suggested_until: 0,
+            compacted: CompactedHistory::default(),
+            pre_eval_hook: None,
+            strategies: StrategyRegistry::builtin(),
         }
     }
 
+    /// Run `hook` on every batch before `ask` returns it (see [`PreEvalHook`]).
+    ///
+    /// The hook may round, replace, add or drop candidates; the edited points
+    /// are what the caller evaluates and tells back. Points beyond the
+    /// remaining budget, including ones the hook added, are dropped. If it
+    /// empties a batch, `ask` asks again (a few times at most, then returns
+    /// `None`).
+    pub fn with_pre_eval_hook(mut self, hook: PreEvalHook) -> Self {
+        self.pre_eval_hook = Some(hook);
+        self
+    }
+
+    /// Register `factory` as the refine strategy `name` (see
+    /// [`StrategyRegistry`]). Registering `"nelder_mead"` or `"tpe"` replaces
+    /// the built-in strategy for Structured or Chaotic landscapes; any other
+    /// name is used once [`with_landscape_strategy`](Self::with_landscape_strategy)
+    /// assigns a landscape to it.
+    pub fn with_strategy_factory(mut self, name: &str, factory: StrategyFactory) -> Self {
+        self.strategies.register(name, factory);
+        self
+    }
+
+    /// Refine `landscape` with the strategy registered as `name`. This also
+    /// applies when `strategy_params.force` picks the landscape.
+    pub fn with_landscape_strategy(mut self, landscape: Landscape, name: &str) -> Self {
+        self.strategies.assign(landscape, name);
+        self
+    }
+
     /// Create a solver with the ResidualDecayClassifier (used in PCR)
     pub fn with_residual_decay(config: SolverConfig) -> Self {
-        Self::with_classifier(config, Box::new(ResidualDecayClassifier::default()))
+        let classifier = ResidualDecayClassifier::default().with_noise(config.observation_noise);
+        Self::with_classifier(config, Box::new(classifier))
     }
 
     /// Creates a Solver with the PCR (Probe-Classify-Refine) strategy.
@@ -88,68 +478,526 @@ impl Solver {
     /// 3. **Refine**: Use `Top-K` seeding to initialize the chosen strategy.
     ///    - Structured -> Nelder-Mead (initialized with best probe points)
     ///    - Chaotic -> TPE (initialized with all probe points)
+    ///
+    /// The probe can be overridden with `strategy_params["probe"]` (see `ProbeKind`).
+    /// Setting `strategy_params["ensemble"]` (see `EnsembleVote`) classifies
+    /// with the variance + residual-decay ensemble instead of variance alone.
     pub fn pcr(config: SolverConfig) -> Self {
+        let classifier: Box<dyn Classify> = match config
+            .strategy_param(EnsembleVote::PARAM_KEY)
+            .and_then(EnsembleVote::from_code)
+        {
+            Some(vote) => Box::new(EnsembleClassifier::variance_residual(
+                config.observation_noise,
+                vote,
+            )),
+            None => Box::new(
+                VarianceClassifier::default()
+                    .with_noise(config.observation_noise)
+                    .with_trim(config.strategy_param(VarianceClassifier::TRIM_KEY)),
+            ),
+        };
         Self {
+            probe: select_probe(&config, Box::new(PrimeSqrtSlopesRotProbe::default())),
+            classifier,
+            registry: param_registry(&config),
+            dense_history: Vec::new(),
+            pareto_history: Vec::new(),
+            constraints: ConstraintSet::new(&config.constraints),
             config,
             history: Vec::new(),
             phase: Phase::Probe,
-            probe: Box::new(PrimeSqrtSlopesRotProbe::default()),
-            classifier: Box::new(VarianceClassifier::default()),
             strategy: None,
-            seeding: SeedingConfig {
-                top_k: None,
-                seed_nm: true,
-            },
+            seeding: SeedingConfig::default(),
             restarted: false,
+            classification: None,
+            converged_optima: Vec::new(),
+            restarts: Vec::new(),
+            target_reached: None,
+            infeasible_probes: 0,
+            probe_cut: None,
+            early_exit_checked: false,
+            probe_issued: 0,
+            initial_issued: 0,
+            restart_anchor: None,
+            batch_source: None,
+            suggestions: VecDeque::new(),
+            suggested_until: 0,
+            compacted: CompactedHistory::default(),
+            pre_eval_hook: None,
+            strategies: StrategyRegistry::builtin(),
+        }
+    }
+
+    /// Config used to size probe batches.
+    ///
+    /// Probes derive their batch size from `budget`; with a cost-only budget
+    /// they are sized for `COST_ONLY_SIZING_BUDGET` evaluations instead.
+    fn sizing_config(&self) -> Cow<'_, SolverConfig> {
+        if self.config.budget == 0 && self.config.cost_budget.is_some() {
+            let mut sizing = self.config.clone();
+            sizing.budget = COST_ONLY_SIZING_BUDGET;
+            Cow::Owned(sizing)
+        } else {
+            Cow::Borrowed(&self.config)
+        }
+    }
+
+    /// Number of evaluations spent in the probe phase.
+    fn probe_budget(&self) -> usize {
+        if let Some(cut) = self.probe_cut {
+            return cut;
+        }
+        self.sizing_config()
+            .probe_count()
+            .saturating_sub(self.infeasible_probes)
+    }
+
+    /// History length at which the early-exit check runs, while it is pending.
+    fn early_exit_checkpoint(&self) -> Option<usize> {
+        if self.early_exit_checked
+            || !self
+                .config
+                .strategy_param(EARLY_EXIT_PROBE_KEY)
+                .is_some_and(|flag| flag > 0.0)
+        {
+            return None;
+        }
+        let checkpoint = self.probe_budget() / 2;
+        (checkpoint > 0).then_some(checkpoint)
+    }
+
+    /// Registry of the sorted `bounds` keys, the order of dense points.
+    pub fn registry(&self) -> &ParamRegistry {
+        &self.registry
+    }
+
+    /// Convert the traces added to `history` since the last call into
+    /// `dense_history`, rebuilding both if `config.bounds` changed size.
+    fn sync_dense_history(&mut self) {
+        if self.registry.len() != self.config.bounds.len() {
+            self.registry = param_registry(&self.config);
+            self.dense_history.clear();
+        }
+        self.dense_history.truncate(self.history.len());
+        let start = self.dense_history.len();
+        self.dense_history.extend(
+            self.history[start..]
+                .iter()
+                .map(|trace| dense_point(&self.registry, &trace.params)),
+        );
+    }
+
+    /// Under `Selection::Pareto`, clone the traces added to `history` since
+    /// the last call into `pareto_history`, then re-score every entry: ranks
+    /// shift as the front grows, but the traces are only cloned once.
+    fn sync_pareto_history(&mut self) {
+        if self.config.selection != Selection::Pareto {
+            return;
+        }
+        self.pareto_history.truncate(self.history.len());
+        let start = self.pareto_history.len();
+        self.pareto_history
+            .extend_from_slice(&self.history[start..]);
+        for (view, score) in self
+            .pareto_history
+            .iter_mut()
+            .zip(pareto_scores(&self.history))
+        {
+            view.value = score;
+        }
+    }
+
+    /// Maximum number of evaluations, or `usize::MAX` when only cost is capped.
+    fn eval_budget(&self) -> usize {
+        if self.config.budget == 0 && self.config.cost_budget.is_some() {
+            usize::MAX
+        } else {
+            self.config.budget as usize
+        }
+    }
+
+    /// Evaluation with the lowest value so far. Reduced-fidelity evaluations
+    /// from successive halving are not comparable and are skipped, and
+    /// infeasible ones only count while no feasible one exists (see
+    /// [`cmp_best`]).
+    pub fn best(&self) -> Option<&EvalTrace> {
+        self.history
+            .iter()
+            .filter(|t| t.is_full_fidelity())
+            .min_by(|a, b| cmp_best(a, b))
+    }
+
+    /// Evaluations told so far, including traces compaction dropped from
+    /// `history`. This is what counts against `config.budget`.
+    pub fn evaluations(&self) -> usize {
+        self.history.len() + self.compacted.evaluations
+    }
+
+    /// `value` from the solver's units back to the objective's, undoing
+    /// `config.objective_transform`.
+    pub fn original_value(&self, value: f64) -> f64 {
+        match self.config.objective_transform {
+            Some(transform) => transform.invert(value),
+            None => value,
+        }
+    }
+
+    /// `trace` with its value in the objective's original units, without
+    /// the infeasibility penalty `tell` added.
+    fn original_trace(&self, trace: &EvalTrace) -> EvalTrace {
+        EvalTrace {
+            value: self.original_value(trace.value - trace.penalty.unwrap_or(0.0)),
+            penalty: None,
+            ..trace.clone()
+        }
+    }
+
+    /// History with values in the objective's original units.
+    pub fn original_history(&self) -> Vec<EvalTrace> {
+        self.history
+            .iter()
+            .map(|trace| self.original_trace(trace))
+            .collect()
+    }
+
+    /// [`best`](Self::best) with its value in the objective's original units.
+    pub fn original_best(&self) -> Option<EvalTrace> {
+        self.best().map(|trace| self.original_trace(trace))
+    }
+
+    /// Total cost of all evaluations told, including compacted ones.
+    pub fn spent_cost(&self) -> f64 {
+        self.compacted.cost + trace_cost(&self.history)
+    }
+
+    /// Evaluations compaction has dropped from `history` so far.
+    pub fn compacted(&self) -> CompactedHistory {
+        self.compacted
+    }
+
+    /// Count evaluations an earlier solver compacted away before saving its
+    /// history, so a solver resumed from that history does not spend them
+    /// again. Call before seeding the saved history.
+    pub fn restore_compacted(&mut self, compacted: CompactedHistory) {
+        self.compacted = compacted;
+    }
+
+    /// [`registered_strategy`](Self::registered_strategy) for `mode`, wrapped
+    /// in a [`Surrogate`] when `strategy_params` enable surrogate suggestions.
+    fn build_strategy(&self, mode: Landscape) -> Box<dyn Strategy> {
+        let strategy = self.registered_strategy(mode);
+        tracing::info!(
+            landscape = ?mode,
+            strategy = self.strategies.name_for(mode),
+            kind = strategy.name(),
+            "refining"
+        );
+        match SurrogateConfig::from_config(&self.config) {
+            Some(surrogate) => {
+                tracing::info!(
+                    min_points = surrogate.min_points,
+                    "surrogate suggestions enabled"
+                );
+                Box::new(Surrogate::new(strategy, surrogate))
+            }
+            None => strategy,
+        }
+    }
+
+    /// The refine strategy for `mode` from `strategies`, falling back to the
+    /// built-in one if its name has no factory. Not yet wrapped.
+    fn registered_strategy(&self, mode: Landscape) -> Box<dyn Strategy> {
+        let dim = self.config.bounds.len();
+        self.strategies
+            .build(mode, dim, &self.config)
+            .unwrap_or_else(|| {
+                tracing::warn!(
+                    strategy = self.strategies.name_for(mode),
+                    "no strategy registered under this name; using the built-in one"
+                );
+                StrategyRegistry::builtin()
+                    .build(mode, dim, &self.config)
+                    .expect("built-in strategies cover every landscape")
+            })
+    }
+
+    /// Fraction of the budget used so far, by count or cost, whichever is higher.
+    fn budget_fraction(&self) -> f64 {
+        let by_count = if self.config.budget > 0 {
+            self.evaluations() as f64 / self.config.budget as f64
+        } else {
+            0.0
+        };
+        let by_cost = match self.config.cost_budget {
+            Some(cap) if cap > 0.0 => self.spent_cost() / cap,
+            Some(_) => 1.0,
+            None => 0.0,
+        };
+        by_count.max(by_cost)
+    }
+
+    /// Phase the solver is in. It only advances inside `ask`.
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    /// True once the solver will propose nothing more: the budget is used up,
+    /// the refine strategy converged or `target_value` was reached.
+    pub fn is_done(&self) -> bool {
+        self.phase == Phase::Done || self.budget_exhausted()
+    }
+
+    /// True once either the evaluation budget or the cost budget is used up.
+    pub fn budget_exhausted(&self) -> bool {
+        self.evaluations() >= self.eval_budget()
+            || self
+                .config
+                .cost_budget
+                .is_some_and(|cap| self.spent_cost() >= cap)
+    }
+
+    /// True once the probe phase has used its share of the budget.
+    ///
+    /// With `cost_budget` set, probing also ends as soon as `probe_ratio` of
+    /// the cost budget is spent, even if probe points remain.
+    fn probe_complete(&self) -> bool {
+        if self.evaluations() >= self.probe_budget() {
+            return true;
+        }
+        match self.config.cost_budget {
+            Some(cap) => {
+                !self.history.is_empty()
+                    && self.spent_cost() >= cap * self.sizing_config().probe_fraction()
+            }
+            None => false,
         }
     }
 
-    /// Get top-k best probe points for seeding
+    /// Get top-k probe points for seeding: the best point, then a
+    /// value-weighted farthest-point selection (see
+    /// `SeedingConfig::diversity_weight`), so clustered near-duplicates do not
+    /// collapse the simplex.
+    ///
+    /// Under `Selection::Pareto` the points are spread along the Pareto front
+    /// instead (see `diverse_front_points`).
     fn get_top_k_seed_points(&self, k: usize) -> Vec<HashMap<String, f64>> {
+        if self.config.selection == Selection::Pareto {
+            return diverse_front_points(&self.history, k);
+        }
+        let order = self.config.param_order();
         let mut sorted: Vec<_> = self.history.iter().collect();
-        sorted.sort_by(|a, b| {
-            a.value
-                .partial_cmp(&b.value)
-                .unwrap_or(std::cmp::Ordering::Equal)
-        });
+        sorted.sort_by(|a, b| order.cmp_traces(a, b));
+
+        let weight = self.seeding.diversity_weight.clamp(0.0, 1.0);
+        if weight == 0.0 || sorted.len() <= k {
+            return sorted.iter().take(k).map(|t| t.params.clone()).collect();
+        }
 
-        sorted.iter().take(k).map(|t| t.params.clone()).collect()
+        let (lo, hi) = sorted
+            .iter()
+            .map(|t| t.value)
+            .filter(|v| v.is_finite())
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| {
+                (lo.min(v), hi.max(v))
+            });
+        let goodness = |value: f64| match value.is_finite() {
+            false => 0.0,
+            true if hi > lo => (hi - value) / (hi - lo),
+            true => 1.0,
+        };
+        let diagonal = (self.config.bounds.len() as f64).sqrt().max(1.0);
+
+        let mut rest = sorted;
+        let mut selected = vec![rest.remove(0)];
+        while selected.len() < k && !rest.is_empty() {
+            let mut best = (0, f64::NEG_INFINITY);
+            for (i, candidate) in rest.iter().enumerate() {
+                let spread = selected
+                    .iter()
+                    .map(|s| (self.seeding.distance)(&self.config, &candidate.params, &s.params))
+                    .fold(f64::INFINITY, f64::min)
+                    / diagonal;
+                let score = (1.0 - weight) * goodness(candidate.value) + weight * spread;
+                // Strict comparison: ties go to the better value.
+                if score > best.1 {
+                    best = (i, score);
+                }
+            }
+            selected.push(rest.remove(best.0));
+        }
+        selected.into_iter().map(|t| t.params.clone()).collect()
     }
 
     /// Ask the solver what to do next.
     /// Returns a list of candidates to evaluate, or None if finished.
+    ///
+    /// Batches are truncated to the remaining budget, so evaluations already
+    /// in history (including seeded or imported ones) are never overspent.
+    /// With `cost_budget` set, the solver is done once the summed cost of
+    /// history reaches the cap; costs are only known after evaluation, so the
+    /// last batch may overshoot it.
     #[tracing::instrument(skip(self))]
     pub fn ask(&mut self) -> Option<Vec<HashMap<String, f64>>> {
+        for _ in 0..=HOOK_MAX_REASKS {
+            if self.budget_exhausted() {
+                self.phase = Phase::Done;
+                return None;
+            }
+            let remaining = self.eval_budget() - self.evaluations();
+            let mut batch = self.next_batch()?;
+            batch.truncate(remaining);
+            if self.config.has_conditions() {
+                batch = batch
+                    .into_iter()
+                    .map(|candidate| self.config.active_params(candidate))
+                    .collect();
+            }
+            if !self.config.fixed.is_empty() {
+                batch = batch
+                    .into_iter()
+                    .map(|candidate| self.config.with_fixed(candidate))
+                    .collect();
+            }
+            let Some(hook) = self.pre_eval_hook.as_mut() else {
+                return Some(batch);
+            };
+            let issued = batch.len();
+            hook(&mut batch);
+            if self.phase == Phase::Probe {
+                self.infeasible_probes += issued.saturating_sub(batch.len());
+            }
+            batch.truncate(remaining);
+            if !batch.is_empty() {
+                return Some(batch);
+            }
+            tracing::debug!(
+                issued,
+                "pre-eval hook dropped the whole batch; asking again"
+            );
+        }
+        None
+    }
+
+    fn next_batch(&mut self) -> Option<Vec<HashMap<String, f64>>> {
         loop {
             match self.phase {
                 Phase::Probe => {
-                    let probe_budget =
-                        (self.config.budget as f64 * self.config.probe_ratio).ceil() as usize;
                     let current_count = self.history.len();
 
-                    if current_count < probe_budget {
+                    // User-provided initial points go out first, as one batch.
+                    let initial = self.config.initial_points.len();
+                    if self.initial_issued < initial {
+                        let points = self.config.initial_points[self.initial_issued..].to_vec();
+                        self.initial_issued = initial;
+                        let points: Vec<_> = points
+                            .into_iter()
+                            .filter(|point| {
+                                let feasible = self.constraints.all_satisfied(point);
+                                if !feasible {
+                                    tracing::warn!(?point, "dropping infeasible initial point");
+                                }
+                                feasible
+                            })
+                            .collect();
+                        if !points.is_empty() {
+                            self.probe_issued = current_count + points.len();
+                            self.batch_source = Some((TraceKind::Probe, "initial".to_string()));
+                            return Some(points);
+                        }
+                    }
+                    if self.initial_issued > 0 && self.probe_issued > current_count {
+                        // Waiting for the initial points.
+                        return None;
+                    }
+
+                    if !self.probe_complete() {
+                        let checkpoint = self.early_exit_checkpoint();
                         if current_count == 0 {
-                            let candidates = self.probe.sample(&self.config);
-                            return Some(candidates);
-                        } else if self.history.len() >= probe_budget {
+                            let candidates = self.probe.sample(&self.sizing_config());
+                            let mut feasible = self.resample_infeasible(candidates);
+                            if feasible.is_empty() {
+                                self.phase = Phase::Done;
+                                return None;
+                            }
+                            if let Some(checkpoint) = checkpoint {
+                                feasible.truncate(checkpoint);
+                            }
+                            self.probe_issued = feasible.len();
+                            self.batch_source =
+                                Some((TraceKind::Probe, self.probe.name().to_string()));
+                            return Some(feasible);
+                        } else if checkpoint == Some(current_count) {
+                            self.early_exit_checked = true;
+                            let (classified, score) = self.classifier.classify(&self.history);
+                            let confidence = self.classifier.confidence(score);
+                            let required = self
+                                .config
+                                .strategy_param(EARLY_EXIT_CONFIDENCE_KEY)
+                                .unwrap_or(DEFAULT_EARLY_EXIT_CONFIDENCE);
+                            if classified == Landscape::Structured && confidence >= required {
+                                tracing::info!(
+                                    points = current_count,
+                                    confidence,
+                                    "ending probe early: landscape is structured"
+                                );
+                                self.probe_cut = Some(current_count);
+                                self.phase = Phase::Classify;
+                                continue;
+                            }
+                            // Not confident yet: hand out the rest of the probe.
+                        } else if self.probe_issued > current_count {
+                            // Waiting for probe points already handed out.
+                            return None;
+                        }
+                        // Hand out the rest of the probe. Besides the early-exit
+                        // checkpoint, this resumes a probe that seeded (or
+                        // truncated) history left partway done.
+                        let candidates = self.probe.sample(&self.sizing_config());
+                        let rest: Vec<_> = self
+                            .resample_infeasible(candidates)
+                            .into_iter()
+                            .skip(current_count)
+                            .collect();
+                        if rest.is_empty() {
                             self.phase = Phase::Classify;
                             continue;
-                        } else {
-                            return None;
                         }
+                        self.probe_issued = current_count + rest.len();
+                        self.batch_source = Some((TraceKind::Probe, self.probe.name().to_string()));
+                        return Some(rest);
                     } else {
                         self.phase = Phase::Classify;
                     }
                 }
                 Phase::Classify => {
-                    let (mode, _score) = self.classifier.classify(&self.history);
-                    println!("[Machine] Classified as {:?} (Score: {:.4})", mode, _score);
+                    let (classified, score) = self.classifier.classify(&self.history);
+                    tracing::info!(landscape = ?classified, score, "classified");
+                    self.classification = Some((classified, score));
+                    let mode = match StrategyKind::from_config(&self.config) {
+                        Some(forced) => {
+                            tracing::info!(
+                                strategy = forced.name(),
+                                "forcing strategy over the classifier's choice"
+                            );
+                            forced.landscape()
+                        }
+                        None => classified,
+                    };
                     self.phase = Phase::Refine(mode);
 
                     // Factory Strategy with probe seeding
                     let dim = self.config.bounds.len();
                     match mode {
                         Landscape::Structured => {
+                            let remaining = self.eval_budget().saturating_sub(self.evaluations());
+                            if remaining < dim + 1 {
+                                tracing::warn!(
+                                    remaining,
+                                    needed = dim + 1,
+                                    "too few evaluations left after probing for one Nelder-Mead simplex"
+                                );
+                            }
                             // Update probe with low spice
                             // Primary: No CP shift (None) -> 0% spice + pure QMC
                             let spice =
@@ -162,36 +1010,18 @@ impl Solver {
 
                             // Revert: Multi-Start NM caused starvation issues.
                             // Falling back to robust Single-Start NM.
-                            // Compute periodic mask for Nelder-Mead (must match sorted key order)
-                            let mut keys: Vec<_> = self.config.bounds.keys().collect();
-                            keys.sort();
-                            let periodic_mask: Vec<bool> = keys
-                                .iter()
-                                .map(|k| {
-                                    self.config
-                                        .bounds
-                                        .get(*k)
-                                        .map(|d| d.is_periodic())
-                                        .unwrap_or(false)
-                                })
-                                .collect();
-
-                            self.strategy = Some(Box::new(NelderMead::new(dim, periodic_mask)));
+                            self.strategy = Some(self.build_strategy(mode));
                         }
                         Landscape::Chaotic => {
                             // Update probe with high spice
                             // Chaotic: CP shift always on
-                            println!("[Machine] Chaotic mode -> Enabling CP Shift + Spice");
+                            tracing::debug!("chaotic landscape: enabling CP shift and spice");
                             let spice =
                                 PrimeSqrtSlopesRotConfig::adaptive_spice_for_landscape(true);
 
                             // Deterministic random CP shift for Chaotic
-                            // Use seed_rotation logic from probe: seed * 1e9 + 0xDEAD_C0DE
-                            let cp_seed =
-                                ((self.config.seed as f64 * 1e9) as u64).wrapping_add(0xDEAD_C0DE);
                             use rand::Rng;
-                            use rand::SeedableRng;
-                            let mut cp_rng = rand_chacha::ChaCha8Rng::seed_from_u64(cp_seed);
+                            let mut cp_rng = derive_rng(self.config.seed, CP_SHIFT_STREAM);
                             let cp_delta: Vec<f64> = (0..dim).map(|_| cp_rng.random()).collect();
 
                             let p_config =
@@ -202,7 +1032,7 @@ impl Solver {
                             ));
 
                             // TPE uses Scott's Rule by default
-                            self.strategy = Some(Box::new(TPE::new(dim)));
+                            self.strategy = Some(self.build_strategy(mode));
                         }
                     }
                     continue;
@@ -210,20 +1040,18 @@ impl Solver {
                 Phase::Refine(mode) => {
                     // Check logic for Structured Fallback (CP Restart)
                     if let Landscape::Structured = mode {
-                        if !self.restarted
-                            && self.history.len() >= (self.config.budget as f64 * 0.7) as usize
-                        {
+                        if !self.restarted && self.budget_fraction() >= CP_RESTART_FRACTION {
                             // Trigger CP Restart!
-                            println!("[Machine] Structured Fail-Safe Triggered! Restarting with CP Shift at param count {}", self.history.len());
+                            tracing::info!(
+                                evaluations = self.evaluations(),
+                                "structured fail-safe triggered; restarting with CP shift"
+                            );
                             self.restarted = true;
                             let dim = self.config.bounds.len();
 
                             // Generate CP shift
-                            let cp_seed = ((self.config.seed as f64 * 1.5e9) as u64)
-                                .wrapping_add(0xBEEF_CAFE);
                             use rand::Rng;
-                            use rand::SeedableRng;
-                            let mut cp_rng = rand_chacha::ChaCha8Rng::seed_from_u64(cp_seed);
+                            let mut cp_rng = derive_rng(self.config.seed, CP_RESTART_STREAM);
                             let cp_delta: Vec<f64> = (0..dim).map(|_| cp_rng.random()).collect();
 
                             // Re-init probe with shift
@@ -238,9 +1066,13 @@ impl Solver {
 
                             // Request new batch? Actually, we just need seeds.
                             // We can sample ~10 points from this new probe
-                            let new_candidates = self.probe.sample(&self.config);
-                            let rescue_batch =
-                                new_candidates.into_iter().take(15).collect::<Vec<_>>();
+                            // Continue the sequence past the points already evaluated.
+                            let new_candidates = self
+                                .probe
+                                .sample_from(&self.sizing_config(), self.evaluations());
+                            let rescue_batch = self.resample_infeasible(
+                                new_candidates.into_iter().take(15).collect::<Vec<_>>(),
+                            );
 
                             // We must evaluate them first?
                             // Wait, if we return them, the loop continues.
@@ -295,19 +1127,63 @@ impl Solver {
                             // Let's implement logic:
                             // If strategy is None in Refine: Re-create it (CP-aware picking).
                             self.strategy = None;
+                            self.restart_anchor = None;
+                            self.batch_source = Some((TraceKind::Refine, "cp_restart".to_string()));
                             return Some(rescue_batch);
                         }
                     }
 
-                    if let Some(strat) = &mut self.strategy {
-                        if self.history.len() >= self.config.budget as usize {
-                            self.phase = Phase::Done;
-                            continue;
+                    if self.strategy.is_some() && self.budget_exhausted() {
+                        self.phase = Phase::Done;
+                        continue;
+                    }
+                    if self.strategy.is_some() {
+                        self.sync_dense_history();
+                        if self.restart_anchor.is_none() {
+                            self.sync_pareto_history();
                         }
-                        match strat.step(&self.config, &self.history) {
-                            StrategyAction::Evaluate(points) => return Some(points),
+                    }
+                    if let Some(strat) = &mut self.strategy {
+                        let source = strat.name();
+                        let segment: Vec<EvalTrace>;
+                        let dense_segment: Vec<ParamVec>;
+                        let (view, points) = match &self.restart_anchor {
+                            Some((seed, start)) => {
+                                segment = std::iter::once(seed)
+                                    .chain(&self.history[*start..])
+                                    .cloned()
+                                    .collect();
+                                dense_segment =
+                                    std::iter::once(dense_point(&self.registry, &seed.params))
+                                        .chain(self.dense_history[*start..].iter().cloned())
+                                        .collect();
+                                (strategy_view(&self.config, &segment), &dense_segment[..])
+                            }
+                            None if self.config.selection == Selection::Pareto => (
+                                Cow::Borrowed(&self.pareto_history[..]),
+                                &self.dense_history[..],
+                            ),
+                            None => (Cow::Borrowed(&self.history[..]), &self.dense_history[..]),
+                        };
+                        let dense = DenseHistory {
+                            registry: &self.registry,
+                            traces: &view,
+                            points,
+                        };
+                        let action = match strat.step_dense(&self.config, &dense) {
+                            Some(action) => action.into_action(&self.registry),
+                            None => strat.step(&self.config, &view),
+                        };
+                        match action {
+                            StrategyAction::Evaluate(points) => {
+                                self.batch_source = Some((TraceKind::Refine, source.to_string()));
+                                return Some(self.project_infeasible(points));
+                            }
                             StrategyAction::Wait => return None,
                             StrategyAction::Converged => {
+                                if mode == Landscape::Structured && self.diverse_restart() {
+                                    continue;
+                                }
                                 self.phase = Phase::Done;
                                 continue;
                             }
@@ -325,21 +1201,7 @@ impl Solver {
                         // NelderMead::with_seed_points just takes seeds.
                         let _seeds = self.get_top_k_seed_points(k);
 
-                        // Compute periodic mask
-                        let mut keys: Vec<_> = self.config.bounds.keys().collect();
-                        keys.sort();
-                        let periodic_mask: Vec<bool> = keys
-                            .iter()
-                            .map(|k| {
-                                self.config
-                                    .bounds
-                                    .get(*k)
-                                    .map(|d| d.is_periodic())
-                                    .unwrap_or(false)
-                            })
-                            .collect();
-
-                        self.strategy = Some(Box::new(NelderMead::new(dim, periodic_mask)));
+                        self.strategy = Some(self.build_strategy(Landscape::Structured));
 
                         // Immediately step the new strategy
                         continue; // Loop again to step
@@ -350,14 +1212,328 @@ impl Solver {
         }
     }
 
+    /// Replace probe points that violate `config.constraints` with uniform
+    /// resamples. Points with no feasible resample within the retry limit are
+    /// dropped and the probe budget shrinks accordingly.
+    fn resample_infeasible(
+        &mut self,
+        candidates: Vec<HashMap<String, f64>>,
+    ) -> Vec<HashMap<String, f64>> {
+        if self.constraints.is_empty() {
+            return candidates;
+        }
+        use rand::Rng;
+        let mut rng = derive_rng(
+            self.config.seed.wrapping_add(self.evaluations() as u64),
+            RESAMPLE_STREAM,
+        );
+
+        // Draw in sorted name order: `HashMap` order varies between runs.
+        let mut bounds: Vec<_> = self.config.bounds.iter().collect();
+        bounds.sort_by_key(|&(name, _)| name);
+        let mut feasible = Vec::with_capacity(candidates.len());
+        let mut dropped = 0;
+        for candidate in candidates {
+            if self.constraints.all_satisfied(&candidate) {
+                feasible.push(candidate);
+                continue;
+            }
+            let replacement = (0..CONSTRAINT_MAX_RETRIES)
+                .map(|_| {
+                    bounds
+                        .iter()
+                        .map(|&(name, domain)| {
+                            (name.clone(), unit_to_domain(rng.random::<f64>(), domain))
+                        })
+                        .collect::<HashMap<_, _>>()
+                })
+                .find(|p| self.constraints.all_satisfied(p));
+            match replacement {
+                Some(p) => feasible.push(p),
+                None => dropped += 1,
+            }
+        }
+        if dropped > 0 {
+            tracing::warn!(
+                dropped,
+                tries = CONSTRAINT_MAX_RETRIES,
+                "dropped probe points with no feasible resample"
+            );
+            self.infeasible_probes += dropped;
+        }
+        feasible
+    }
+
+    /// Project strategy candidates that violate `config.constraints` onto the
+    /// segment towards the best feasible point seen so far. Candidates are
+    /// rejected when no feasible anchor exists in history.
+    fn project_infeasible(
+        &self,
+        candidates: Vec<HashMap<String, f64>>,
+    ) -> Vec<HashMap<String, f64>> {
+        let constraints = &self.constraints;
+        if constraints.is_empty() {
+            return candidates;
+        }
+        let anchor = self
+            .history
+            .iter()
+            .filter(|t| constraints.all_satisfied(&t.params))
+            .min_by(|a, b| cmp_traces(a, b))
+            .map(|t| &t.params);
+
+        candidates
+            .into_iter()
+            .filter_map(|candidate| {
+                if constraints.all_satisfied(&candidate) {
+                    return Some(candidate);
+                }
+                let projected = anchor.map(|a| project_towards(constraints, &candidate, a));
+                if projected.is_none() {
+                    tracing::debug!("rejected infeasible candidate: no feasible anchor");
+                }
+                projected
+            })
+            .collect()
+    }
+
+    /// Record evaluation results.
+    ///
+    /// Params that are unknown, non-finite or outside `bounds` are first
+    /// handled per `config.invalid_params`: the batch is refused, or the
+    /// offending results are dropped or clamped with a warning.
+    /// A `_fidelity` entry in the params (set by successive halving) moves
+    /// to the trace's `fidelity`. `feasibility` is clamped to [0, 1] (NaN
+    /// reads as 0). Traces without a `kind` are tagged with the phase and
+    /// source of the last batch `ask` handed out. Inactive conditional
+    /// parameters are dropped from each trace. NaN or infinite values are
+    /// handled per `config.non_finite`: the whole batch is refused, or the
+    /// value is replaced by the penalty and the trace flagged. A refused
+    /// batch leaves the history untouched. Values are then mapped through
+    /// `config.objective_transform`; one outside its domain refuses the batch.
+    /// Last, infeasible results are penalized by `config.infeasibility_penalty`
+    /// times their missing feasibility.
     #[tracing::instrument(skip(self, eval_results))]
-    pub fn tell(&mut self, eval_results: Vec<EvalTrace>) {
+    pub fn tell(&mut self, eval_results: Vec<EvalTrace>) -> Result<(), TellError> {
+        let mut eval_results = self.screen_params(eval_results)?;
+        for trace in &mut eval_results {
+            if let Some(fidelity) = trace.params.remove(FIDELITY_KEY) {
+                trace.fidelity = Some(fidelity);
+            }
+            if let Some(feasibility) = trace.feasibility {
+                trace.feasibility = Some(if feasibility.is_nan() {
+                    0.0
+                } else {
+                    feasibility.clamp(0.0, 1.0)
+                });
+            }
+            if let (None, Some((kind, source))) = (trace.kind, &self.batch_source) {
+                trace.kind = Some(*kind);
+                trace.source.get_or_insert_with(|| source.clone());
+            }
+            if self.config.has_conditions() {
+                trace.params = self.config.active_params(std::mem::take(&mut trace.params));
+            }
+            let (value, penalized) = self.config.non_finite.resolve(trace.value)?;
+            if penalized {
+                tracing::warn!(
+                    eval_id = trace.eval_id,
+                    reported = trace.value,
+                    penalty = value,
+                    "non-finite value; recording penalty"
+                );
+            }
+            trace.value = value;
+            trace.penalized |= penalized;
+        }
+        if self.config.repeats > 1 {
+            eval_results = merge_repeats(eval_results, self.config.repeat_reducer);
+        }
+        if let Some(transform) = self.config.objective_transform {
+            for trace in &mut eval_results {
+                trace.value = transform.apply(trace.value)?;
+            }
+        }
+        if self.config.infeasibility_penalty != 0.0 {
+            for trace in &mut eval_results {
+                let penalty = self.config.infeasibility_penalty * trace.infeasibility();
+                if penalty != 0.0 {
+                    trace.value += penalty;
+                    trace.penalty = Some(penalty);
+                }
+            }
+        }
+        let start = self.history.len();
         self.history.extend(eval_results);
+        if self.target_reached.is_none() {
+            if let Some(index) = (start..self.history.len()).find(|&i| self.reaches_target(i)) {
+                let trace = &self.history[index];
+                let value = self.original_value(trace.value);
+                tracing::info!(index, value, "target reached");
+                self.target_reached = Some(TargetReachedEvent {
+                    index,
+                    eval_id: trace.eval_id,
+                    value,
+                });
+                self.phase = Phase::Done;
+            }
+        }
+        self.compact_history();
+        Ok(())
+    }
+
+    /// Apply `config.invalid_params` to results whose params are unknown,
+    /// non-finite or out of bounds (see [`SolverConfig::invalid_param`]).
+    fn screen_params(&self, eval_results: Vec<EvalTrace>) -> Result<Vec<EvalTrace>, InvalidParam> {
+        let policy = self.config.invalid_params;
+        let mut kept = Vec::with_capacity(eval_results.len());
+        for mut trace in eval_results {
+            let mut keep = true;
+            while let Some((name, value, kind)) = self.config.invalid_param(&trace.params) {
+                let err = InvalidParam {
+                    eval_id: trace.eval_id,
+                    name,
+                    value,
+                    kind,
+                };
+                match (policy, kind) {
+                    (InvalidParamPolicy::Reject, _) => return Err(err),
+                    (InvalidParamPolicy::Clamp, InvalidParamKind::Unknown) => {
+                        tracing::warn!(error = %err, "invalid param; removing it");
+                        trace.params.remove(&err.name);
+                    }
+                    (InvalidParamPolicy::Clamp, InvalidParamKind::OutOfBounds { min, max }) => {
+                        tracing::warn!(error = %err, "invalid param; clamping it");
+                        trace.params.insert(err.name, value.clamp(min, max));
+                    }
+                    _ => {
+                        tracing::warn!(error = %err, "invalid param; dropping the result");
+                        keep = false;
+                        break;
+                    }
+                }
+            }
+            if keep {
+                kept.push(trace);
+            }
+        }
+        Ok(kept)
+    }
+
+    /// Shrink `history` to half of `config.history_cap` once refining has
+    /// pushed it past the cap.
+    ///
+    /// The best quarter of the cap is kept (always including `best()`), plus
+    /// a sample of the other traces drawn from `COMPACTION_STREAM`, in their
+    /// original order. The refine strategy is rebuilt so it seeds from the
+    /// compacted history; dropped traces still count against the budgets.
+    fn compact_history(&mut self) {
+        let (Some(cap), Phase::Refine(mode)) = (self.config.history_cap, self.phase) else {
+            return;
+        };
+        let len = self.history.len();
+        if len <= cap {
+            return;
+        }
+        let target = (cap / 2).max(1);
+        let order = self.config.param_order();
+        let mut by_value: Vec<usize> = (0..len).collect();
+        by_value.sort_by(|&a, &b| {
+            order
+                .cmp_traces(&self.history[a], &self.history[b])
+                .then(a.cmp(&b))
+        });
+        let mut keep = vec![false; len];
+        for &index in by_value.iter().take((cap / 4).max(1)) {
+            keep[index] = true;
+        }
+        if let Some(best) = (0..len)
+            .filter(|&i| self.history[i].is_full_fidelity())
+            .min_by(|&a, &b| cmp_best(&self.history[a], &self.history[b]))
+        {
+            keep[best] = true;
+        }
+        let rest: Vec<usize> = (0..len).filter(|&i| !keep[i]).collect();
+        let kept = len - rest.len();
+        let mut rng = derive_rng(
+            self.config
+                .seed
+                .wrapping_add(self.compacted.evaluations as u64),
+            COMPACTION_STREAM,
+        );
+        for index in rand::seq::index::sample(&mut rng, rest.len(), target.saturating_sub(kept)) {
+            keep[rest[index]] = true;
+        }
+
+        let dropped = keep.iter().filter(|&&k| !k).count();
+        if let Some((_, start)) = &mut self.restart_anchor {
+            *start = keep[..*start].iter().filter(|&&k| k).count();
+        }
+        let dropped_traces = || {
+            self.history
+                .iter()
+                .zip(&keep)
+                .filter(|(_, &k)| !k)
+                .map(|(trace, _)| trace)
+        };
+        self.compacted.cost += trace_cost(dropped_traces());
+        self.compacted.max_eval_id = dropped_traces()
+            .map(|trace| trace.eval_id)
+            .fold(self.compacted.max_eval_id, u64::max);
+        let mut flags = keep.into_iter();
+        self.history.retain(|_| flags.next().unwrap_or(false));
+        self.dense_history.clear();
+        self.pareto_history.clear();
+        self.compacted.evaluations += dropped;
+        self.suggested_until = self.suggested_until.saturating_sub(dropped);
+        tracing::info!(
+            from = len,
+            to = self.history.len(),
+            evaluations = self.evaluations(),
+            "compacted history"
+        );
+        if self.strategy.is_some() {
+            self.strategy = Some(self.build_strategy(mode));
+        }
+    }
+
+    /// Whether `history[index]` reaches `config.target_value`: within
+    /// `target_tol` of it in the objective's units, or better than it in the
+    /// direction the solver optimizes (so maximizing via `Negate` works).
+    /// Penalized, failed, infeasible and reduced-fidelity evaluations never
+    /// count.
+    fn reaches_target(&self, index: usize) -> bool {
+        let Some(target) = self.config.target_value else {
+            return false;
+        };
+        let trace = &self.history[index];
+        if trace.penalized
+            || trace.failed
+            || trace.infeasibility() > 0.0
+            || !trace.is_full_fidelity()
+        {
+            return false;
+        }
+        if (self.original_value(trace.value) - target).abs() <= self.config.target_tol {
+            return true;
+        }
+        let target = match self.config.objective_transform {
+            Some(transform) => transform.apply(target).ok(),
+            None => Some(target),
+        };
+        target.is_some_and(|target| trace.value <= target)
     }
 
-    /// Get the next available evaluation ID.
+    /// Get the next available evaluation ID, past every id in the history
+    /// and every id compaction dropped from it.
     fn next_eval_id(&self) -> u64 {
-        self.history.iter().map(|t| t.eval_id).max().unwrap_or(0) + 1
+        self.history
+            .iter()
+            .map(|t| t.eval_id)
+            .max()
+            .unwrap_or(0)
+            .max(self.compacted.max_eval_id)
+            + 1
     }
 
     /// Inject historical evaluations into the model.
@@ -374,28 +1550,240 @@ impl Solver {
     /// solver.seed(vec![
     ///     SeedPoint { params: params1, value: 1.0, cost: 1.0 },
     ///     SeedPoint { params: params2, value: 2.0, cost: 1.0 },
-    /// ]);
+    /// ])?;
     /// // Next ask() will be informed by seeded data
     /// let batch = solver.ask();
     /// ```
     #[tracing::instrument(skip(self, evaluations))]
-    pub fn seed(&mut self, evaluations: Vec<SeedPoint>) {
-        for eval in evaluations {
-            let internal_id = self.next_eval_id();
-            let trace = EvalTrace {
-                eval_id: internal_id,
+    pub fn seed(&mut self, evaluations: Vec<SeedPoint>) -> Result<(), TellError> {
+        let first_id = self.next_eval_id();
+        let traces = evaluations
+            .into_iter()
+            .enumerate()
+            .map(|(i, eval)| EvalTrace {
+                eval_id: first_id + i as u64,
                 params: eval.params,
                 value: eval.value,
                 cost: eval.cost,
-            };
-            self.history.push(trace);
+                repeats: None,
+                penalized: eval.penalized,
+                failed: eval.failed,
+                feasibility: eval.feasibility,
+                penalty: None,
+                fidelity: None,
+                kind: None,
+                source: None,
+            })
+            .collect::<Vec<_>>();
+        // Initial points already in seeded history (a resumed run) are not
+        // handed out again.
+        while self
+            .config
+            .initial_points
+            .get(self.initial_issued)
+            .is_some_and(|point| traces.iter().any(|t| self.is_initial_point(point, t)))
+        {
+            self.initial_issued += 1;
         }
+        self.tell(traces)
     }
 
-    /// Ask for exactly ONE candidate configuration for online/real-time optimization.
+    /// True if `trace` evaluated the initial point `point`, as `ask` hands
+    /// it out: without its inactive parameters, compared on `bounds`.
+    fn is_initial_point(&self, point: &HashMap<String, f64>, trace: &EvalTrace) -> bool {
+        let point = self.config.active_params(point.clone());
+        self.config
+            .bounds
+            .keys()
+            .all(|name| point.get(name) == trace.params.get(name))
+    }
+
+    /// Seed history from a previous run and skip probing when possible.
     ///
-    /// Unlike `ask()` which returns a full batch for PCR workflow, this method:
-    /// 1. Skips Probe/Classify phases
+    /// If the seeded history covers the probe budget, the solver jumps
+    /// straight to `Phase::Classify`: the imported traces drive landscape
+    /// classification and strategy seeding instead of a fresh probe batch.
+    /// Imported evaluations count against `config.budget`.
+    ///
+    /// Returns `true` if probing was skipped.
+    pub fn warm_start(&mut self, evaluations: Vec<SeedPoint>) -> Result<bool, TellError> {
+        self.seed(evaluations)?;
+        if self.phase == Phase::Probe && self.probe_complete() {
+            tracing::info!(imported = self.history.len(), "warm start: skipping probe");
+            self.phase = Phase::Classify;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    /// Best single next point given everything seen so far.
+    ///
+    /// Runs the normal PCR state machine but hands out one candidate per
+    /// call: during probing this is the next unprobed point, during refine
+    /// the strategy's next candidate. The rest of a batch is cached in
+    /// `suggestions` and served on later calls without stepping the strategy
+    /// again, so batch strategies like TPE are not re-run per point.
+    ///
+    /// Returns `None` when the solver is done, or while it is waiting for
+    /// results of points already handed out.
+    pub fn suggest(&mut self) -> Option<HashMap<String, f64>> {
+        if self.budget_exhausted() {
+            self.suggestions.clear();
+            self.phase = Phase::Done;
+            return None;
+        }
+        if let Some(next) = self.suggestions.pop_front() {
+            return Some(next);
+        }
+        if self.history.len() < self.suggested_until {
+            return None;
+        }
+        let batch = self.ask()?;
+        self.suggested_until = self.history.len() + batch.len();
+        let mut batch = batch.into_iter();
+        let first = batch.next()?;
+        self.suggestions.extend(batch);
+        Some(first)
+    }
+
+    /// Relaunch Nelder-Mead after it converged, if `max_restarts` allows and
+    /// more than `DIVERSE_RESTART_MIN_REMAINING` of the budget is left.
+    ///
+    /// The best point of the finished run is added to `converged_optima`, and
+    /// the new run starts from the evaluated point farthest (in unit space)
+    /// from all of them. Returns false if no restart was made.
+    fn diverse_restart(&mut self) -> bool {
+        if self.restarts.len() >= self.config.max_restarts
+            || 1.0 - self.budget_fraction() <= DIVERSE_RESTART_MIN_REMAINING
+        {
+            return false;
+        }
+
+        let (anchor, start) = match &self.restart_anchor {
+            Some((seed, start)) => (Some(seed), *start),
+            None => (None, 0),
+        };
+        let Some(optimum) = self.history[start..]
+            .iter()
+            .chain(anchor)
+            .filter(|t| t.value.is_finite())
+            .min_by(|a, b| cmp_traces(a, b))
+            .cloned()
+        else {
+            return false;
+        };
+        self.converged_optima.push(optimum);
+
+        let Some((seed, distance)) = self
+            .history
+            .iter()
+            .filter(|t| t.value.is_finite())
+            .map(|t| {
+                let nearest = self
+                    .converged_optima
+                    .iter()
+                    .map(|o| unit_distance(&self.config, &t.params, &o.params))
+                    .fold(f64::INFINITY, f64::min);
+                (t, nearest)
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1).then_with(|| cmp_traces(b.0, a.0)))
+        else {
+            return false;
+        };
+        if distance <= 0.0 {
+            return false;
+        }
+
+        let optimum = self.converged_optima.last().expect("just pushed");
+        tracing::info!(
+            evaluations = self.evaluations(),
+            best = optimum.value,
+            distance,
+            "converged; diverse restart"
+        );
+        self.restarts.push(RestartEvent {
+            at_eval: self.evaluations(),
+            optimum: optimum.params.clone(),
+            optimum_value: optimum.value,
+            seed: seed.params.clone(),
+            distance,
+        });
+        self.restart_anchor = Some((seed.clone(), self.history.len()));
+        self.strategy = Some(self.build_strategy(Landscape::Structured));
+        true
+    }
+
+    /// How many candidates the solver can usefully hand out at once in its
+    /// current phase, for sizing a pool of workers: the probe points still to
+    /// be evaluated while probing, then the refine strategy's
+    /// [`Strategy::batch_hint`] (1 for Nelder-Mead and TPE). Capped by the
+    /// evaluations left; 0 once the run is done.
+    pub fn suggested_batch_size(&self) -> usize {
+        if self.phase == Phase::Done || self.budget_exhausted() {
+            return 0;
+        }
+        let hint = if self.phase == Phase::Probe && !self.probe_complete() {
+            let probe_end = match self.early_exit_checkpoint() {
+                Some(checkpoint) if self.history.len() < checkpoint => checkpoint,
+                _ => self.probe_budget(),
+            };
+            probe_end.saturating_sub(self.history.len())
+        } else {
+            self.strategy.as_ref().map_or(1, |s| s.batch_hint())
+        };
+        hint.clamp(1, self.eval_budget() - self.evaluations())
+    }
+
+    /// Describe the run without advancing it: probe/refine budget split, the
+    /// probe in use and, if history already covers the probe budget, the
+    /// classification and the refine strategy it would choose, by its
+    /// `Strategy::name` (wrapped as `Surrogate(..)` with surrogate
+    /// suggestions on).
+    pub fn plan(&self) -> RunPlan {
+        let probe_points = self.probe_budget();
+        let refine_budget = if self.config.budget == 0 && self.config.cost_budget.is_some() {
+            None
+        } else {
+            Some(self.eval_budget().saturating_sub(probe_points))
+        };
+        let classification = match (self.phase, self.classification) {
+            (Phase::Refine(_), Some((mode, score))) => Some((mode, Some(score))),
+            (Phase::Refine(mode), None) => Some((mode, None)),
+            _ if self.probe_complete() => {
+                let (mode, score) = self.classifier.classify(&self.history);
+                Some((mode, Some(score)))
+            }
+            _ => None,
+        };
+        let strategy = classification
+            .map(|(mode, _)| match StrategyKind::from_config(&self.config) {
+                Some(forced) => forced.landscape(),
+                None => mode,
+            })
+            .map(|mode| {
+                let name = self.registered_strategy(mode).name();
+                match SurrogateConfig::from_config(&self.config) {
+                    Some(_) => format!("Surrogate({})", name),
+                    None => name.to_string(),
+                }
+            });
+        RunPlan {
+            budget: self.config.budget,
+            cost_budget: self.config.cost_budget,
+            probe_points,
+            refine_budget,
+            probe: self.probe.name().to_string(),
+            evaluated: self.evaluations(),
+            classification: classification.map(|(mode, _)| mode),
+            classification_score: classification.and_then(|(_, score)| score),
+            strategy,
+        }
+    }
+
+    /// Ask for exactly ONE candidate configuration for online/real-time optimization.
+    ///
+    /// Unlike `ask()` which returns a full batch for PCR workflow, this method:
+    /// 1. Skips Probe/Classify phases
     /// 2. Uses TPE strategy directly for incremental learning
     /// 3. Returns exactly 1 candidate per call
     ///
@@ -411,22 +1799,34 @@ impl Solver {
     #[tracing::instrument(skip(self))]
     pub fn ask_one(&mut self) -> Option<HashMap<String, f64>> {
         // Budget check
-        if self.history.len() >= self.config.budget as usize {
+        if self.budget_exhausted() {
             return None;
         }
 
         // Lazy-init TPE strategy for online mode
         if self.strategy.is_none() {
             let dim = self.config.bounds.len();
-            self.strategy = Some(Box::new(TPE::new(dim)));
+            self.strategy = Some(Box::new(TPE::from_config(dim, &self.config)));
         }
 
         // Get one candidate from TPE
+        self.sync_pareto_history();
         if let Some(strat) = &mut self.strategy {
-            match strat.step(&self.config, &self.history) {
+            let view = if self.config.selection == Selection::Pareto {
+                &self.pareto_history
+            } else {
+                &self.history
+            };
+            match strat.step(&self.config, view) {
                 StrategyAction::Evaluate(points) => {
                     // Return just the first candidate
-                    points.into_iter().next()
+                    let first = points.into_iter().next()?;
+                    self.project_infeasible(vec![first])
+                        .into_iter()
+                        .next()
+                        .map(|candidate| {
+                            self.config.with_fixed(self.config.active_params(candidate))
+                        })
                 }
                 StrategyAction::Wait => None,
                 StrategyAction::Converged => None,
@@ -437,12 +1837,189 @@ impl Solver {
     }
 }
 
+/// Sum of the finite, positive `cost`s of `traces`.
+fn trace_cost<'a>(traces: impl IntoIterator<Item = &'a EvalTrace>) -> f64 {
+    traces
+        .into_iter()
+        .map(|t| t.cost)
+        .filter(|c| c.is_finite() && *c > 0.0)
+        .sum()
+}
+
+/// Euclidean distance between two points in unit space, wrapping periodic
+/// dimensions. Parameters missing from either point are ignored.
+fn unit_distance(config: &SolverConfig, a: &HashMap<String, f64>, b: &HashMap<String, f64>) -> f64 {
+    config
+        .bounds
+        .iter()
+        .filter_map(|(name, domain)| {
+            let (ua, ub) = (domain.to_unit(*a.get(name)?), domain.to_unit(*b.get(name)?));
+            Some(if domain.is_periodic() {
+                dist01(ua, ub)
+            } else {
+                (ua - ub).abs()
+            })
+        })
+        .map(|d| d * d)
+        .sum::<f64>()
+        .sqrt()
+}
+
+/// History as seen by the refine strategies.
+///
+/// Under `Selection::Pareto` each value is replaced by its non-dominated
+/// sorting layer plus a tie-break in [0, 0.5) from the normalized raw value,
+/// so value-minimizing strategies (NM, TPE) push towards the front.
+fn strategy_view<'a>(config: &SolverConfig, history: &'a [EvalTrace]) -> Cow<'a, [EvalTrace]> {
+    if config.selection != Selection::Pareto {
+        return Cow::Borrowed(history);
+    }
+    Cow::Owned(
+        history
+            .iter()
+            .zip(pareto_scores(history))
+            .map(|(trace, value)| EvalTrace {
+                value,
+                ..trace.clone()
+            })
+            .collect(),
+    )
+}
+
+/// The Pareto score of each trace in `history` (see `strategy_view`).
+fn pareto_scores(history: &[EvalTrace]) -> Vec<f64> {
+    let ranks = pareto_ranks(history);
+    let (lo, hi) = history
+        .iter()
+        .map(|t| t.value)
+        .filter(|v| v.is_finite())
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| {
+            (lo.min(v), hi.max(v))
+        });
+    let span = if hi > lo { hi - lo } else { 1.0 };
+    history
+        .iter()
+        .zip(ranks)
+        .map(|(trace, rank)| {
+            let tie_break = if trace.value.is_finite() {
+                0.5 * (trace.value - lo) / span
+            } else {
+                0.5
+            };
+            rank as f64 + tie_break.min(0.499_999)
+        })
+        .collect()
+}
+
+/// Up to `k` points spread along the Pareto front of `history`.
+///
+/// Starts from the lowest-value front point and adds the front point farthest
+/// (in normalized value/cost space) from those already chosen. If the front
+/// has fewer than `k` points, the rest are filled by Pareto layer.
+fn diverse_front_points(history: &[EvalTrace], k: usize) -> Vec<HashMap<String, f64>> {
+    let mut front = pareto_front(history);
+    front.sort_by(|a, b| cmp_traces(a, b));
+    if front.len() <= k {
+        let ranks = pareto_ranks(history);
+        let mut rest: Vec<(usize, &EvalTrace)> = ranks
+            .into_iter()
+            .zip(history)
+            .filter(|(_, t)| !front.iter().any(|f| std::ptr::eq(*f, *t)))
+            .collect();
+        rest.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| cmp_traces(a.1, b.1)));
+        return front
+            .into_iter()
+            .chain(rest.into_iter().map(|(_, t)| t))
+            .take(k)
+            .map(|t| t.params.clone())
+            .collect();
+    }
+
+    let range = |f: fn(&EvalTrace) -> f64| {
+        let lo = front.iter().map(|t| f(t)).fold(f64::INFINITY, f64::min);
+        let hi = front.iter().map(|t| f(t)).fold(f64::NEG_INFINITY, f64::max);
+        (lo, if hi > lo { hi - lo } else { 1.0 })
+    };
+    let (v_lo, v_span) = range(|t| t.value);
+    let (c_lo, c_span) = range(|t| t.cost);
+    let norm = |t: &EvalTrace| ((t.value - v_lo) / v_span, (t.cost - c_lo) / c_span);
+
+    let mut chosen = vec![0];
+    while chosen.len() < k {
+        let next = (0..front.len())
+            .filter(|i| !chosen.contains(i))
+            .max_by(|&a, &b| {
+                let min_dist = |i: usize| {
+                    let (x, y) = norm(front[i]);
+                    chosen
+                        .iter()
+                        .map(|&c| {
+                            let (cx, cy) = norm(front[c]);
+                            (x - cx).powi(2) + (y - cy).powi(2)
+                        })
+                        .fold(f64::INFINITY, f64::min)
+                };
+                min_dist(a).total_cmp(&min_dist(b))
+            });
+        match next {
+            Some(i) => chosen.push(i),
+            None => break,
+        }
+    }
+    chosen
+        .into_iter()
+        .map(|i| front[i].params.clone())
+        .collect()
+}
+
+/// Closest feasible point to `candidate` on the segment to the feasible
+/// `anchor`, found by bisection on the interpolation weight.
+/// Order of [`Solver::best`]: feasible traces before infeasible ones, which
+/// can rank ahead on value when `infeasibility_penalty` is small or 0, then
+/// [`cmp_traces`].
+fn cmp_best(a: &EvalTrace, b: &EvalTrace) -> std::cmp::Ordering {
+    let infeasible = |t: &EvalTrace| t.infeasibility() > 0.0;
+    infeasible(a)
+        .cmp(&infeasible(b))
+        .then_with(|| cmp_traces(a, b))
+}
+
+fn project_towards(
+    constraints: &ConstraintSet,
+    candidate: &HashMap<String, f64>,
+    anchor: &HashMap<String, f64>,
+) -> HashMap<String, f64> {
+    let lerp = |t: f64| -> HashMap<String, f64> {
+        candidate
+            .iter()
+            .map(|(name, &x)| {
+                let a = anchor.get(name).copied().unwrap_or(x);
+                (name.clone(), x + t * (a - x))
+            })
+            .collect()
+    };
+    // Invariant: lerp(hi) is feasible, lerp(lo) is not
+    let (mut lo, mut hi) = (0.0, 1.0);
+    for _ in 0..PROJECTION_STEPS {
+        let mid = 0.5 * (lo + hi);
+        if constraints.all_satisfied(&lerp(mid)) {
+            hi = mid;
+        } else {
+            lo = mid;
+        }
+    }
+    lerp(hi)
+}
+
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
-    use crate::config::{Domain, Scale};
+    use crate::config::{
+        all_satisfied, Domain, NonFinitePolicy, ObjectiveTransform, ProbeBudget, Scale,
+    };
+    use crate::strategies::nelder_mead::NelderMead;
 
-    fn make_test_config() -> SolverConfig {
+    pub(crate) fn make_test_config() -> SolverConfig {
         let mut bounds = HashMap::new();
         bounds.insert(
             "x".to_string(),
@@ -450,6 +2027,7 @@ mod tests {
                 min: 0.0,
                 max: 1.0,
                 scale: Scale::Linear,
+                active_when: None,
             },
         );
         bounds.insert(
@@ -458,14 +2036,31 @@ mod tests {
                 min: 0.0,
                 max: 1.0,
                 scale: Scale::Linear,
+                active_when: None,
             },
         );
         SolverConfig {
             bounds,
             budget: 20,
-            probe_ratio: 0.5,
+            probe_ratio: ProbeBudget::Fixed(0.5),
             seed: 42,
             strategy_params: None,
+            constraints: Vec::new(),
+            selection: Default::default(),
+            cost_budget: None,
+            observation_noise: None,
+            max_restarts: 0,
+            non_finite: Default::default(),
+            repeats: 0,
+            repeat_reducer: Default::default(),
+            objective_transform: None,
+            fixed: Default::default(),
+            target_value: None,
+            target_tol: 0.0,
+            history_cap: None,
+            invalid_params: Default::default(),
+            infeasibility_penalty: 0.0,
+            initial_points: Vec::new(),
         }
     }
 
@@ -505,6 +2100,9 @@ mod tests {
                     .collect(),
                 value: 1.0,
                 cost: 1.0,
+                penalized: false,
+                failed: false,
+                feasibility: None,
             },
             SeedPoint {
                 params: [("x".to_string(), 0.3), ("y".to_string(), 0.7)]
@@ -512,9 +2110,12 @@ mod tests {
                     .collect(),
                 value: 0.8,
                 cost: 1.0,
+                penalized: false,
+                failed: false,
+                feasibility: None,
             },
         ];
-        solver.seed(seed_points);
+        solver.seed(seed_points).unwrap();
 
         assert_eq!(solver.history.len(), 2);
         assert_eq!(solver.history[0].eval_id, 1);
@@ -531,10 +2132,1124 @@ mod tests {
             params: [("x".to_string(), 0.5)].into_iter().collect(),
             value: 1.0,
             cost: 1.0,
+            repeats: None,
+            penalized: false,
+            failed: false,
+            feasibility: None,
+            penalty: None,
+            fidelity: None,
+            kind: None,
+            source: None,
         }];
-        solver.tell(traces);
+        solver.tell(traces).unwrap();
+
+        assert_eq!(solver.history.len(), 1);
+    }
+
+    fn traces_for(batch: Vec<HashMap<String, f64>>, first_id: u64) -> Vec<EvalTrace> {
+        batch
+            .into_iter()
+            .enumerate()
+            .map(|(i, params)| {
+                let (x, y) = (params["x"], params["y"]);
+                // One diverged evaluation per batch.
+                let value = if i == 0 {
+                    f64::NAN
+                } else {
+                    (x - 0.3).powi(2) + (y - 0.6).powi(2)
+                };
+                EvalTrace {
+                    eval_id: first_id + i as u64,
+                    ..trace(params, value)
+                }
+            })
+            .collect()
+    }
+
+    /// Run a PCR solver to completion on a sphere centred at (0.3, 0.6).
+    fn run_sphere(config: SolverConfig) -> Solver {
+        drive_sphere(Solver::pcr(config))
+    }
+
+    /// Run `solver` to completion on the sphere of [`run_sphere`].
+    fn drive_sphere(solver: Solver) -> Solver {
+        drive(solver, sphere, |_, _| {})
+    }
+
+    /// Ask and tell until `solver` is done, evaluating with `objective`.
+    /// `after_tell` sees the solver and the traces of each batch once they
+    /// are told.
+    fn drive(
+        solver: Solver,
+        objective: impl Fn(&HashMap<String, f64>) -> f64,
+        after_tell: impl FnMut(&Solver, &[EvalTrace]),
+    ) -> Solver {
+        drive_traces(
+            solver,
+            |batch, first_id| evaluate(batch, first_id, &objective),
+            after_tell,
+        )
+    }
+
+    /// [`drive`] with `results` turning each batch, and the eval id of its
+    /// first point, into the traces told.
+    fn drive_traces(
+        mut solver: Solver,
+        results: impl Fn(Vec<HashMap<String, f64>>, u64) -> Vec<EvalTrace>,
+        mut after_tell: impl FnMut(&Solver, &[EvalTrace]),
+    ) -> Solver {
+        while let Some(batch) = solver.ask() {
+            let traces = results(batch, solver.evaluations() as u64);
+            solver.tell(traces.clone()).unwrap();
+            after_tell(&solver, &traces);
+        }
+        solver
+    }
+
+    /// Sphere centred at (0.3, 0.6).
+    pub(crate) fn sphere(params: &HashMap<String, f64>) -> f64 {
+        (params["x"] - 0.3).powi(2) + (params["y"] - 0.6).powi(2)
+    }
+
+    /// Plain full-fidelity result for `params`, with eval id 0.
+    fn trace(params: HashMap<String, f64>, value: f64) -> EvalTrace {
+        EvalTrace {
+            eval_id: 0,
+            params,
+            value,
+            cost: 1.0,
+            repeats: None,
+            penalized: false,
+            failed: false,
+            feasibility: None,
+            penalty: None,
+            fidelity: None,
+            kind: None,
+            source: None,
+        }
+    }
+
+    /// Traces for `batch` valued by `objective`, with eval ids counting up
+    /// from `first_id`.
+    pub(crate) fn evaluate(
+        batch: Vec<HashMap<String, f64>>,
+        first_id: u64,
+        objective: impl Fn(&HashMap<String, f64>) -> f64,
+    ) -> Vec<EvalTrace> {
+        batch
+            .into_iter()
+            .enumerate()
+            .map(|(i, params)| EvalTrace {
+                eval_id: first_id + i as u64,
+                value: objective(&params),
+                ..trace(params, 0.0)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_early_exit_probe_on_sphere() {
+        let mut config = make_test_config();
+        config.budget = 60;
+        let full = run_sphere(config.clone());
+        config.strategy_params = Some(HashMap::from([(EARLY_EXIT_PROBE_KEY.to_string(), 1.0)]));
+        let early = run_sphere(config);
+        let count = |solver: &Solver, kind: TraceKind| {
+            solver
+                .history
+                .iter()
+                .filter(|t| t.kind == Some(kind))
+                .count()
+        };
+        assert_eq!(count(&full, TraceKind::Probe), 30);
+        // Half the probe already shows a confidently Structured landscape.
+        assert_eq!(count(&early, TraceKind::Probe), 15);
+        assert_eq!(early.plan().probe_points, 15);
+        assert_eq!(early.plan().refine_budget, Some(45));
+        assert_eq!(count(&early, TraceKind::Refine), 45);
+        assert!(count(&early, TraceKind::Refine) > count(&full, TraceKind::Refine));
+    }
+
+    #[test]
+    fn test_surrogate_suggestions_on_sphere() {
+        let mut config = make_test_config();
+        config.budget = 60;
+        let plain = run_sphere(config.clone());
+        config.strategy_params = Some(HashMap::from([(
+            SurrogateConfig::PARAM_KEY.to_string(),
+            1.0,
+        )]));
+        let surrogate = run_sphere(config);
+        let best = |solver: &Solver| {
+            solver
+                .history
+                .iter()
+                .map(|t| t.value)
+                .fold(f64::INFINITY, f64::min)
+        };
+        assert_eq!(surrogate.history.len(), 60);
+        // The interpolant's minimum lands closer to the optimum than NM alone.
+        assert!(best(&surrogate) < best(&plain));
+    }
+
+    #[test]
+    fn test_history_cap_keeps_best() {
+        let mut config = make_test_config();
+        config.budget = 200;
+        config.probe_ratio = ProbeBudget::Fixed(0.1);
+        config.history_cap = Some(24);
+        let mut best_told = f64::INFINITY;
+        let mut max_id = 0;
+        let objective = |p: &HashMap<String, f64>| (p["x"] - 0.7).powi(2) + (p["y"] - 0.2).powi(2);
+        let solver = drive(Solver::pcr(config), objective, |solver, traces| {
+            best_told = traces.iter().map(|t| t.value).fold(best_told, f64::min);
+            max_id = traces.iter().map(|t| t.eval_id).fold(max_id, u64::max);
+            assert!(
+                solver.history.len() <= 24,
+                "{} traces",
+                solver.history.len()
+            );
+            assert_eq!(solver.best().unwrap().value, best_told);
+            // Ids of compacted evaluations are not handed out again.
+            assert_eq!(solver.next_eval_id(), max_id + 1);
+        });
+        assert!(solver.evaluations() > 48, "{} evals", solver.evaluations());
+        assert_eq!(solver.spent_cost(), solver.evaluations() as f64);
+    }
+
+    #[test]
+    fn test_best_ties_independent_of_history_order() {
+        let traces: Vec<EvalTrace> = [(0.4, 0.1), (0.2, 0.9), (0.2, 0.3), (0.8, 0.5)]
+            .into_iter()
+            .enumerate()
+            .map(|(i, (x, y))| EvalTrace {
+                eval_id: i as u64,
+                ..trace(
+                    HashMap::from([("x".to_string(), x), ("y".to_string(), y)]),
+                    if i == 3 { 2.0 } else { 1.0 },
+                )
+            })
+            .collect();
+        let best_of = |history: Vec<EvalTrace>| {
+            let mut solver = Solver::pcr(make_test_config());
+            solver.history = history;
+            solver.best().unwrap().params.clone()
+        };
+
+        let mut reversed = traces.clone();
+        reversed.reverse();
+        let best = best_of(traces);
+        assert_eq!(best["x"], 0.2);
+        assert_eq!(best["y"], 0.3);
+        assert_eq!(best_of(reversed), best);
+    }
+
+    /// Proposes the best point evaluated so far, every step.
+    struct ReturnBest;
+
+    impl Strategy for ReturnBest {
+        fn step(&mut self, _config: &SolverConfig, history: &[EvalTrace]) -> StrategyAction {
+            match history.iter().min_by(|a, b| a.value.total_cmp(&b.value)) {
+                Some(best) => StrategyAction::Evaluate(vec![best.params.clone()]),
+                None => StrategyAction::Converged,
+            }
+        }
+    }
+
+    #[test]
+    fn test_custom_strategy_factory_refines_landscape() {
+        let mut config = make_test_config();
+        config.budget = 40;
+        let solver = Solver::pcr(config)
+            .with_strategy_factory("best", Box::new(|_, _| Box::new(ReturnBest)))
+            .with_landscape_strategy(Landscape::Structured, "best");
+        let solver = drive_sphere(solver);
+
+        assert_eq!(solver.classification.unwrap().0, Landscape::Structured);
+        assert_eq!(solver.plan().strategy.as_deref(), Some("ReturnBest"));
+        let refined: Vec<usize> = (0..solver.history.len())
+            .filter(|&i| solver.history[i].source.as_deref() == Some("ReturnBest"))
+            .collect();
+        assert!(!refined.is_empty());
+        for i in refined {
+            let best = solver.history[..i]
+                .iter()
+                .min_by(|a, b| a.value.total_cmp(&b.value))
+                .unwrap();
+            assert_eq!(solver.history[i].params, best.params);
+        }
+    }
+
+    #[test]
+    fn test_suggested_batch_size_follows_phase() {
+        let solver = Solver::pcr(make_test_config());
+        // The whole probe budget can run in parallel.
+        assert_eq!(solver.suggested_batch_size(), 10);
+
+        let solver = drive(solver, sphere, |solver, traces| {
+            if solver.history.len() == traces.len() {
+                assert_eq!(traces.len(), 10);
+            }
+            if solver.history.len() < 20 {
+                // Nelder-Mead refines one point at a time.
+                assert_eq!(solver.suggested_batch_size(), 1);
+            }
+        });
+        assert_eq!(solver.suggested_batch_size(), 0);
+    }
+
+    #[test]
+    fn test_target_value_stops_run_early() {
+        let mut config = make_test_config();
+        config.budget = 200;
+        config.target_value = Some(0.0);
+        config.target_tol = 1e-3;
+        let solver = run_sphere(config.clone());
+        let reached = solver.target_reached.clone().expect("target reached");
+        assert!(solver.history.len() < 200, "{} evals", solver.history.len());
+        // The run stops after the batch holding the first hit.
+        assert!(reached.index < solver.history.len());
+        assert!(reached.value <= 1e-3);
+        assert!(solver.history[..reached.index]
+            .iter()
+            .all(|t| t.value > 1e-3));
+        assert!(solver.is_done());
+
+        // Maximizing -sphere towards its maximum of 0 stops at the same point.
+        config.objective_transform = Some(ObjectiveTransform::Negate);
+        let solver = drive(Solver::pcr(config), |p| -sphere(p), |_, _| {});
+        let negated = solver
+            .target_reached
+            .expect("target reached when maximizing");
+        assert_eq!(negated.index, reached.index);
+        assert_eq!(negated.value, -reached.value);
+    }
+
+    #[test]
+    fn test_traces_tagged_with_producing_phase() {
+        let mut solver = drive(Solver::pcr(make_test_config()), sphere, |_, _| {});
+        let tags: Vec<_> = solver
+            .history
+            .iter()
+            .map(|t| (t.kind, t.source.as_deref()))
+            .collect();
+        let probe = (Some(TraceKind::Probe), Some("PrimeSqrtSlopesRotProbe"));
+        let refine = (Some(TraceKind::Refine), Some("NelderMead"));
+        let rescue = (Some(TraceKind::Refine), Some("cp_restart"));
+        assert_eq!(tags.len(), 20);
+        assert!(tags[..10].iter().all(|t| *t == probe), "{:?}", tags);
+        // Nelder-Mead until the CP restart at 70% of the budget.
+        assert!(tags[10..14].iter().all(|t| *t == refine), "{:?}", tags);
+        assert!(tags[14..].iter().all(|t| *t == rescue), "{:?}", tags);
+
+        // A kind set by the caller is kept.
+        let mut imported = solver.history[0].clone();
+        imported.kind = Some(TraceKind::Refine);
+        imported.source = None;
+        solver.tell(vec![imported]).unwrap();
+        let last = solver.history.last().unwrap();
+        assert_eq!(
+            (last.kind, last.source.as_deref()),
+            (Some(TraceKind::Refine), None)
+        );
+    }
+
+    #[test]
+    fn test_pre_eval_hook_edits_flow_into_history() {
+        let round_to_tenth = |batch: &mut Vec<HashMap<String, f64>>| {
+            for point in batch.iter_mut() {
+                if let Some(x) = point.get_mut("x") {
+                    *x = (*x * 10.0).round() / 10.0;
+                }
+            }
+        };
+        let solver = drive_sphere(
+            Solver::pcr(make_test_config()).with_pre_eval_hook(Box::new(round_to_tenth)),
+        );
+        assert_eq!(solver.history.len(), 20);
+        for trace in &solver.history {
+            let x = trace.params["x"];
+            assert_eq!(x, (x * 10.0).round() / 10.0, "unrounded x = {}", x);
+        }
+    }
+
+    #[test]
+    fn test_fixed_params_in_every_candidate_and_history() {
+        let mut config = make_test_config();
+        config.budget = 30;
+        config.fixed = HashMap::from([("lr".to_string(), 0.01)]);
+        assert!(config.check_fixed().is_ok());
+
+        let mut asked = 0;
+        let solver = drive(Solver::pcr(config.clone()), sphere, |_, traces| {
+            for trace in traces {
+                asked += 1;
+                assert_eq!(trace.params.len(), 3);
+                assert_eq!(trace.params["lr"], 0.01);
+            }
+        });
+        assert_eq!(asked, 30);
+        assert!(solver.history.iter().all(|t| t.params["lr"] == 0.01));
+
+        config.bounds.insert(
+            "lr".to_string(),
+            Domain {
+                min: 1e-4,
+                max: 1e-1,
+                scale: Scale::Log,
+                active_when: None,
+            },
+        );
+        assert!(config.check_fixed().unwrap_err().contains("both"));
+    }
+
+    #[test]
+    fn test_initial_points_evaluated_first_then_probe_fills_its_share() {
+        let mut config = make_test_config();
+        let initial = vec![
+            HashMap::from([("x".to_string(), 0.3), ("y".to_string(), 0.6)]),
+            HashMap::from([("x".to_string(), 0.9), ("y".to_string(), 0.1)]),
+        ];
+        config.initial_points = initial.clone();
+        assert!(config.check_initial_points().is_ok());
+        let probe_count = config.probe_count();
+
+        let mut solver = Solver::pcr(config.clone());
+        let first = solver.ask().unwrap();
+        assert_eq!(first, initial);
+        assert!(solver.ask().is_none(), "waits for the initial points");
+        solver.tell(evaluate(first, 0, sphere)).unwrap();
+        let solver = drive_sphere(solver);
+
+        let probed: Vec<_> = solver
+            .history
+            .iter()
+            .filter(|t| t.kind == Some(TraceKind::Probe))
+            .collect();
+        assert_eq!(probed.len(), probe_count);
+        for (trace, point) in probed.iter().zip(&initial) {
+            assert_eq!(&trace.params, point);
+            assert_eq!(trace.source.as_deref(), Some("initial"));
+        }
+        assert!(probed[initial.len()..]
+            .iter()
+            .all(|t| t.source.as_deref() != Some("initial")));
+        assert!(solver.history.len() > probe_count);
+
+        // Seeded history that already holds the initial points (a resumed
+        // run) does not get them again; unrelated seeded history does.
+        let seeds = |traces: &[EvalTrace]| -> Vec<SeedPoint> {
+            traces
+                .iter()
+                .map(|t| SeedPoint {
+                    params: t.params.clone(),
+                    value: t.value,
+                    cost: t.cost,
+                    penalized: false,
+                    failed: false,
+                    feasibility: None,
+                })
+                .collect()
+        };
+        let mut resumed = Solver::pcr(config.clone());
+        resumed.seed(seeds(&solver.history[..3])).unwrap();
+        let batch = resumed.ask().unwrap();
+        assert!(initial.iter().all(|point| !batch.contains(point)));
+        let mut warm = Solver::pcr(config.clone());
+        warm.seed(seeds(&solver.history[initial.len()..initial.len() + 1]))
+            .unwrap();
+        assert_eq!(warm.ask().unwrap(), initial);
+    }
+
+    #[test]
+    fn test_initial_points_checked_against_budget_and_constraints() {
+        let mut config = make_test_config();
+        let inside = HashMap::from([("x".to_string(), 0.3), ("y".to_string(), 0.6)]);
+        let corner = HashMap::from([("x".to_string(), 0.9), ("y".to_string(), 0.1)]);
+
+        config.initial_points = vec![HashMap::from([
+            ("x".to_string(), 1.5),
+            ("y".to_string(), 0.5),
+        ])];
+        assert!(config
+            .check_initial_points()
+            .unwrap_err()
+            .contains("outside"));
+        config.initial_points = vec![HashMap::from([("x".to_string(), 0.5)])];
+        assert!(config
+            .check_initial_points()
+            .unwrap_err()
+            .contains("missing y"));
+        config.initial_points = vec![inside.clone(); config.probe_count() + 1];
+        assert!(config
+            .check_initial_points()
+            .unwrap_err()
+            .contains("probe's share"));
+
+        config.initial_points = vec![inside.clone(), corner];
+        config.constraints = vec![sum_constraint(0.95)];
+        assert!(config
+            .check_initial_points()
+            .unwrap_err()
+            .contains("initial_points[1] violates the constraint x + y"));
+        // Unchecked, the solver still leaves the infeasible point out.
+        let mut solver = Solver::pcr(config);
+        assert_eq!(solver.ask().unwrap(), vec![inside]);
+    }
+
+    #[test]
+    fn test_pre_eval_hook_dropping_points() {
+        // Dropping half of the probe still lets the probe phase complete.
+        let mut solver = Solver::pcr(make_test_config())
+            .with_pre_eval_hook(Box::new(|batch| batch.retain(|p| p["x"] < 0.5)));
+        let probe = solver.ask().unwrap();
+        assert!(!probe.is_empty() && probe.len() < 10);
+        assert!(probe.iter().all(|p| p["x"] < 0.5));
+        let traces: Vec<EvalTrace> = probe
+            .into_iter()
+            .enumerate()
+            .map(|(i, params)| EvalTrace {
+                eval_id: i as u64,
+                ..trace(params, i as f64)
+            })
+            .collect();
+        solver.tell(traces).unwrap();
+        assert!(solver.ask().is_some());
+        assert!(matches!(solver.phase, Phase::Refine(_)));
+
+        // A hook that vetoes everything ends in `None` instead of looping.
+        let mut vetoed = Solver::pcr(make_test_config()).with_pre_eval_hook(Box::new(Vec::clear));
+        assert!(vetoed.ask().is_none());
+        assert!(vetoed.history.is_empty());
+    }
+
+    #[test]
+    fn test_pre_eval_hook_additions_respect_budget() {
+        // Padding every batch with 30 copies must not overspend the budget
+        // of 20.
+        let mut solver = Solver::pcr(make_test_config()).with_pre_eval_hook(Box::new(|batch| {
+            let copies: Vec<_> = batch.iter().cycle().take(30).cloned().collect();
+            batch.extend(copies);
+        }));
+        while let Some(batch) = solver.ask() {
+            assert!(batch.len() <= 20 - solver.history.len());
+            let first_id = solver.history.len() as u64;
+            solver.tell(evaluate(batch, first_id, sphere)).unwrap();
+        }
+        assert_eq!(solver.history.len(), 20);
+    }
+
+    #[test]
+    fn test_auto_probe_ratio_sizes_probe_phase() {
+        let mut config = make_test_config();
+        config.budget = 300;
+        config.probe_ratio = ProbeBudget::Auto;
+        let mut solver = Solver::pcr(config);
+
+        // 2-D: min(300 / 3, 10 * 2) = 20 probes instead of a fixed fraction.
+        assert_eq!(solver.plan().probe_points, 20);
+        let batch = solver.ask().unwrap();
+        assert_eq!(batch.len(), 20);
+    }
+
+    #[test]
+    fn test_auto_probe_ratio_low_budget_leaves_room_to_refine() {
+        let mut config = make_test_config();
+        config.budget = 4;
+        config.probe_ratio = ProbeBudget::Auto;
+        let plan = Solver::pcr(config).plan();
+
+        // A simplex worth of probes (dim + 1), one evaluation left to refine.
+        assert_eq!(plan.probe_points, 3);
+        assert_eq!(plan.refine_budget, Some(1));
+    }
+
+    #[test]
+    fn test_classification_is_cached_and_best_tracks_minimum() {
+        let mut solver = Solver::pcr(make_test_config());
+        assert!(solver.classification.is_none());
+        assert!(solver.best().is_none());
+
+        let traces = evaluate(solver.ask().unwrap(), 1, sphere);
+        let expected = traces.iter().map(|t| t.value).fold(f64::INFINITY, f64::min);
+        solver.tell(traces).unwrap();
+        assert_eq!(solver.best().unwrap().value, expected);
+
+        solver.ask();
+        let Phase::Refine(mode) = solver.phase else {
+            panic!("expected refine after the probe batch");
+        };
+        let (cached, score) = solver.classification.unwrap();
+        assert_eq!(cached, mode);
+        assert_eq!(solver.plan().classification_score, Some(score));
+    }
+
+    /// Tell every candidate of `batch` three times, with offsets 0, 1 and 5.
+    fn tell_repeated(solver: &mut Solver, batch: Vec<HashMap<String, f64>>) -> usize {
+        let mut traces = Vec::new();
+        for params in batch {
+            let base = params["x"] + params["y"];
+            for offset in [0.0, 1.0, 5.0] {
+                traces.push(EvalTrace {
+                    eval_id: traces.len() as u64 + 1,
+                    ..trace(params.clone(), base + offset)
+                });
+            }
+        }
+        let raw = traces.len();
+        solver.tell(traces).unwrap();
+        raw
+    }
+
+    #[test]
+    fn test_repeats_are_merged_with_reducer() {
+        for (reducer, offset) in [(RepeatReducer::Mean, 2.0), (RepeatReducer::Median, 1.0)] {
+            let mut config = make_test_config();
+            config.repeats = 3;
+            config.repeat_reducer = reducer;
+            let mut solver = Solver::pcr(config);
+            let batch = solver.ask().unwrap();
+            let points = batch.len();
+            tell_repeated(&mut solver, batch);
+
+            assert_eq!(solver.history.len(), points);
+            for trace in &solver.history {
+                let base = trace.params["x"] + trace.params["y"];
+                assert!((trace.value - (base + offset)).abs() < 1e-12);
+                assert_eq!(trace.repeats, Some(3));
+                assert_eq!(trace.cost, 3.0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_budget_counts_merged_observations() {
+        let mut config = make_test_config();
+        config.budget = 12;
+        config.repeats = 3;
+        let mut solver = Solver::pcr(config);
+
+        let mut raw = 0;
+        while let Some(batch) = solver.ask() {
+            raw += tell_repeated(&mut solver, batch);
+        }
+        assert_eq!(solver.history.len(), 12);
+        assert_eq!(raw, 36);
+    }
+
+    #[test]
+    fn test_repeats_disabled_keeps_every_trace() {
+        let mut solver = Solver::pcr(make_test_config());
+        let batch = solver.ask().unwrap();
+        let points = batch.len();
+        tell_repeated(&mut solver, batch);
+        assert_eq!(solver.history.len(), 3 * points);
+    }
+
+    #[test]
+    fn test_pcr_ensemble_reports_agreement_score() {
+        let mut config = make_test_config();
+        config
+            .strategy_params
+            .get_or_insert_with(HashMap::new)
+            .insert(EnsembleVote::PARAM_KEY.to_string(), 1.0);
+        let mut solver = Solver::pcr(config);
+
+        let batch = solver.ask().unwrap();
+        let traces: Vec<EvalTrace> = batch
+            .into_iter()
+            .enumerate()
+            .map(|(i, params)| EvalTrace {
+                eval_id: i as u64 + 1,
+                value: params["x"].powi(2) + params["y"].powi(2),
+                ..trace(params, 0.0)
+            })
+            .collect();
+        solver.tell(traces).unwrap();
+        solver.ask();
+
+        // Two members: the score is the share of votes, 0.5 or 1.0.
+        let (_, score) = solver.classification.unwrap();
+        assert!(score == 0.5 || score == 1.0, "score {}", score);
+    }
+
+    /// `optimizer` picks 0 or 1; `schedule` only exists for optimizer 0 and
+    /// `warmup` only when that schedule is 1.
+    fn conditional_config() -> SolverConfig {
+        serde_json::from_value(serde_json::json!({
+            "seed": 7,
+            "budget": 40,
+            "probe_ratio": 0.5,
+            "bounds": {
+                "lr": {"min": 0.0, "max": 1.0},
+                "optimizer": {"min": 0.0, "max": 1.0},
+                "schedule": {"min": 0.0, "max": 1.0, "active_when": {"optimizer": [0]}},
+                "warmup": {"min": 0.0, "max": 10.0, "active_when": {"schedule": [1]}},
+            },
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_conditional_params_omitted_from_candidates() {
+        let config = conditional_config();
+        let mut solver = Solver::pcr(config.clone());
+        let mut seen_warmup = false;
+        let mut seen_without_schedule = false;
 
+        while let Some(batch) = solver.ask() {
+            let first_id = solver.history.len() as u64 + 1;
+            let traces: Vec<EvalTrace> = batch
+                .into_iter()
+                .enumerate()
+                .map(|(i, params)| {
+                    let expected: Vec<&str> = match (
+                        params["optimizer"].round() as i64,
+                        params.get("schedule").map(|s| s.round() as i64),
+                    ) {
+                        (0, Some(1)) => vec!["lr", "optimizer", "schedule", "warmup"],
+                        (0, Some(_)) => vec!["lr", "optimizer", "schedule"],
+                        (0, None) => panic!("schedule must be active for optimizer 0"),
+                        _ => vec!["lr", "optimizer"],
+                    };
+                    let mut keys: Vec<&str> = params.keys().map(String::as_str).collect();
+                    keys.sort();
+                    assert_eq!(keys, expected);
+                    seen_warmup |= params.contains_key("warmup");
+                    seen_without_schedule |= !params.contains_key("schedule");
+
+                    let value = (params["lr"] - 0.3).powi(2)
+                        + params.get("warmup").map_or(0.5, |w| (w - 2.0).abs() / 10.0);
+                    EvalTrace {
+                        eval_id: first_id + i as u64,
+                        ..trace(params, value)
+                    }
+                })
+                .collect();
+            solver.tell(traces).unwrap();
+        }
+
+        assert_eq!(solver.history.len(), 40);
+        assert!(seen_warmup && seen_without_schedule);
+        assert!(solver.best().unwrap().value.is_finite());
+    }
+
+    #[test]
+    fn test_tell_drops_inactive_params() {
+        let mut solver = Solver::pcr(conditional_config());
+        let params: HashMap<String, f64> = [
+            ("lr".to_string(), 0.5),
+            ("optimizer".to_string(), 1.0),
+            ("schedule".to_string(), 1.0),
+            ("warmup".to_string(), 3.0),
+        ]
+        .into_iter()
+        .collect();
+        solver
+            .seed(vec![SeedPoint {
+                params,
+                value: 1.0,
+                cost: 1.0,
+                penalized: false,
+                failed: false,
+                feasibility: None,
+            }])
+            .unwrap();
+
+        let mut keys: Vec<_> = solver.history[0].params.keys().cloned().collect();
+        keys.sort();
+        assert_eq!(keys, vec!["lr", "optimizer"]);
+    }
+
+    #[test]
+    fn test_tell_records_fidelity() {
+        let mut solver = Solver::pcr(make_test_config());
+        let point = |x: f64, fidelity: Option<f64>| {
+            let mut params = HashMap::from([("x".to_string(), x)]);
+            if let Some(fidelity) = fidelity {
+                params.insert(FIDELITY_KEY.to_string(), fidelity);
+            }
+            params
+        };
+        solver
+            .seed(vec![
+                SeedPoint {
+                    params: point(0.1, Some(0.25)),
+                    value: 0.5,
+                    cost: 0.25,
+                    penalized: false,
+                    failed: false,
+                    feasibility: None,
+                },
+                SeedPoint {
+                    params: point(0.2, None),
+                    value: 2.0,
+                    cost: 1.0,
+                    penalized: false,
+                    failed: false,
+                    feasibility: None,
+                },
+            ])
+            .unwrap();
+
+        assert_eq!(solver.history[0].fidelity, Some(0.25));
+        assert!(!solver.history[0].params.contains_key(FIDELITY_KEY));
+        // The cheap low-fidelity value does not count as the best.
+        assert_eq!(solver.best().unwrap().value, 2.0);
+        assert_eq!(solver.history[0].to_seed_point().params[FIDELITY_KEY], 0.25);
+    }
+
+    #[test]
+    fn test_tell_records_failure() {
+        let mut solver = Solver::pcr(make_test_config());
+        solver
+            .seed(vec![SeedPoint {
+                params: HashMap::from([("x".to_string(), 0.1)]),
+                value: 1e3,
+                cost: 1.0,
+                penalized: false,
+                failed: true,
+                feasibility: None,
+            }])
+            .unwrap();
+
+        let trace = &solver.history[0];
+        assert!(trace.failed);
+        assert_eq!(trace.value, 1e3);
+        assert!(trace.to_seed_point().failed);
+        let restored = EvalTrace::from_seed_point(0, &trace.to_seed_point());
+        assert!(restored.failed);
+        assert_eq!(restored.params, trace.params);
+    }
+
+    #[test]
+    fn test_tell_records_feasibility() {
+        let mut config = make_test_config();
+        config.infeasibility_penalty = 2.0;
+        let mut solver = Solver::pcr(config);
+        let point = |x: f64, feasibility: f64| SeedPoint {
+            params: HashMap::from([("x".to_string(), x)]),
+            value: 0.1,
+            cost: 1.0,
+            penalized: false,
+            failed: false,
+            feasibility: Some(feasibility),
+        };
+        solver
+            .seed(vec![point(0.1, 0.75), point(0.2, 1.5)])
+            .unwrap();
+
+        let trace = &solver.history[0];
+        assert_eq!(trace.feasibility, Some(0.75));
+        assert!((trace.value - 0.6).abs() < 1e-12);
+        assert_eq!(solver.history[1].feasibility, Some(1.0));
+        // Reported values are the measured ones, and seeding them back
+        // penalizes them again.
+        let original = solver.original_history();
+        assert!((original[0].value - 0.1).abs() < 1e-12);
+        let seeds: Vec<SeedPoint> = original.iter().map(EvalTrace::to_seed_point).collect();
+        let mut reseeded = Solver::pcr(solver.config.clone());
+        reseeded.seed(seeds).unwrap();
+        assert!((reseeded.history[0].value - solver.history[0].value).abs() < 1e-12);
+    }
+
+    /// A run on `-x + (y - 0.5)²`, which is only feasible for `x <= 0.6`;
+    /// beyond it, evaluations report a feasibility falling to 0 at `x = 1`.
+    fn run_with_infeasible_region(infeasibility_penalty: f64) -> Solver {
+        let mut config = make_test_config();
+        config.budget = 60;
+        config.probe_ratio = ProbeBudget::Fixed(0.3);
+        config.infeasibility_penalty = infeasibility_penalty;
+        let objective = |p: &HashMap<String, f64>| -p["x"] + (p["y"] - 0.5).powi(2);
+        drive_traces(
+            Solver::pcr(config),
+            |batch, first_id| {
+                let mut traces = evaluate(batch, first_id, objective);
+                for trace in &mut traces {
+                    let x = trace.params["x"];
+                    trace.feasibility = (x > 0.6).then(|| 1.0 - (x - 0.6) / 0.4);
+                }
+                traces
+            },
+            |_, _| {},
+        )
+    }
+
+    #[test]
+    fn test_near_feasible_points_guide_towards_feasible_region() {
+        // Without a penalty the run settles deep in the infeasible region,
+        // though the best point reported stays a feasible one.
+        let ignored = run_with_infeasible_region(0.0);
+        let lowest = ignored
+            .history
+            .iter()
+            .min_by(|a, b| cmp_traces(a, b))
+            .unwrap();
+        assert!(lowest.params["x"] > 0.9, "{:?}", lowest.params);
+        let best = ignored.best().unwrap();
+        assert!(best.params["x"] <= 0.6, "{:?}", best.params);
+        assert_eq!(best.infeasibility(), 0.0);
+
+        // Penalized by how far they missed, near-feasible points slope the
+        // objective back to the feasibility boundary.
+        let solver = run_with_infeasible_region(2.0);
+        let lowest = solver
+            .history
+            .iter()
+            .min_by(|a, b| cmp_traces(a, b))
+            .unwrap();
+        assert!(
+            (lowest.params["x"] - 0.6).abs() < 0.05,
+            "{:?}",
+            lowest.params
+        );
+        assert!(lowest.infeasibility() < 0.15);
+    }
+
+    #[test]
+    fn test_original_history_removes_the_penalty_told_with() {
+        let mut config = make_test_config();
+        config.infeasibility_penalty = 2.0;
+        let mut solver = Solver::pcr(config);
+        solver
+            .tell(vec![EvalTrace {
+                feasibility: Some(0.5),
+                ..trace(HashMap::from([("x".to_string(), 0.9)]), 0.1)
+            }])
+            .unwrap();
+        assert_eq!(solver.history[0].penalty, Some(1.0));
+
+        // A later penalty change applies to new results only.
+        solver.config.infeasibility_penalty = 5.0;
+        let original = solver.original_history();
+        assert!((original[0].value - 0.1).abs() < 1e-12);
+        assert_eq!(original[0].penalty, None);
+    }
+
+    /// Probe-phase classification of `0.01 * exp(10 * |p - (0.3, 0.6)|²)`,
+    /// which spans almost four orders of magnitude over the unit square.
+    fn classify_geometric(transform: Option<ObjectiveTransform>) -> Landscape {
+        let mut config = make_test_config();
+        config.budget = 40;
+        config.objective_transform = transform;
+        let mut solver = Solver::pcr(config);
+        let batch = solver.ask().unwrap();
+        let traces = batch
+            .into_iter()
+            .enumerate()
+            .map(|(i, params)| {
+                let r2 = (params["x"] - 0.3).powi(2) + (params["y"] - 0.6).powi(2);
+                EvalTrace {
+                    eval_id: i as u64,
+                    ..trace(params, 0.01 * (10.0 * r2).exp())
+                }
+            })
+            .collect();
+        solver.tell(traces).unwrap();
+        solver.ask();
+        solver.classification.unwrap().0
+    }
+
+    #[test]
+    fn test_log_transform_classifies_geometric_objective_as_structured() {
+        assert_eq!(classify_geometric(None), Landscape::Chaotic);
+        assert_eq!(
+            classify_geometric(Some(ObjectiveTransform::Log)),
+            Landscape::Structured
+        );
+    }
+
+    #[test]
+    fn test_objective_transform_reports_original_units() {
+        let mut config = make_test_config();
+        config.objective_transform = Some(ObjectiveTransform::Log);
+        let mut solver = Solver::pcr(config);
+        let point = |x: f64| HashMap::from([("x".to_string(), x), ("y".to_string(), 0.5)]);
+        solver
+            .seed(vec![
+                SeedPoint {
+                    params: point(0.1),
+                    value: 100.0,
+                    cost: 1.0,
+                    penalized: false,
+                    failed: false,
+                    feasibility: None,
+                },
+                SeedPoint {
+                    params: point(0.2),
+                    value: 0.01,
+                    cost: 1.0,
+                    penalized: false,
+                    failed: false,
+                    feasibility: None,
+                },
+            ])
+            .unwrap();
+
+        assert!((solver.history[1].value - 0.01f64.ln()).abs() < 1e-12);
+        let best = solver.original_best().unwrap();
+        assert_eq!(best.params, point(0.2));
+        assert!((best.value - 0.01).abs() < 1e-12);
+        assert!((solver.original_history()[0].value - 100.0).abs() < 1e-9);
+
+        // Log needs strictly positive values; the whole batch is refused.
+        let err = solver
+            .seed(vec![SeedPoint {
+                params: point(0.3),
+                value: 0.0,
+                cost: 1.0,
+                penalized: false,
+                failed: false,
+                feasibility: None,
+            }])
+            .unwrap_err();
+        assert!(matches!(err, TellError::InvalidObjective(_)));
+        assert_eq!(solver.history.len(), 2);
+    }
+
+    #[test]
+    fn test_tell_rejects_non_finite_by_default() {
+        let mut solver = Solver::pcr(make_test_config());
+        let batch = solver.ask().unwrap();
+
+        let err = solver.tell(traces_for(batch, 1)).unwrap_err();
+        assert!(matches!(err, TellError::NonFinite(NonFiniteValue(v)) if v.is_nan()));
+        assert!(solver.history.is_empty(), "a refused batch is not recorded");
+        assert!(solver
+            .seed(vec![SeedPoint {
+                params: HashMap::new(),
+                value: f64::INFINITY,
+                cost: 1.0,
+                penalized: false,
+                failed: false,
+                feasibility: None,
+            }])
+            .is_err());
+    }
+
+    #[test]
+    fn test_tell_screens_invalid_params() {
+        let seed_point = |pairs: &[(&str, f64)]| SeedPoint {
+            params: pairs.iter().map(|&(k, v)| (k.to_string(), v)).collect(),
+            value: 1.0,
+            cost: 1.0,
+            penalized: false,
+            failed: false,
+            feasibility: None,
+        };
+        let batch = || {
+            vec![
+                seed_point(&[("x", 0.5), ("y", 0.5)]),
+                seed_point(&[("x", 0.5), ("y", 0.5), ("z", 1.0)]),
+                seed_point(&[("x", 1.5), ("y", 0.5)]),
+            ]
+        };
+
+        let mut solver = Solver::pcr(make_test_config());
+        let err = solver.seed(batch()).unwrap_err();
+        assert!(matches!(
+            err,
+            TellError::InvalidParam(InvalidParam { ref name, kind: InvalidParamKind::Unknown, .. })
+                if name == "z"
+        ));
+        let err = solver.seed(batch().split_off(2)).unwrap_err();
+        assert!(matches!(
+            err,
+            TellError::InvalidParam(InvalidParam {
+                kind: InvalidParamKind::OutOfBounds { min: 0.0, max: 1.0 },
+                ..
+            })
+        ));
+        assert!(solver.history.is_empty(), "a refused batch is not recorded");
+        // Rounding past a bound is tolerated.
+        solver
+            .seed(vec![seed_point(&[("x", 1.0 + 1e-12), ("y", 0.0)])])
+            .unwrap();
+
+        let mut config = make_test_config();
+        config.invalid_params = InvalidParamPolicy::Drop;
+        let mut solver = Solver::pcr(config.clone());
+        solver.seed(batch()).unwrap();
         assert_eq!(solver.history.len(), 1);
+
+        config.invalid_params = InvalidParamPolicy::Clamp;
+        let mut solver = Solver::pcr(config);
+        solver.seed(batch()).unwrap();
+        assert_eq!(solver.history.len(), 3);
+        assert!(!solver.history[1].params.contains_key("z"));
+        assert_eq!(solver.history[2].params["x"], 1.0);
+    }
+
+    #[test]
+    fn test_penalized_nan_keeps_optimizer_well_behaved() {
+        let mut config = make_test_config();
+        config.budget = 60;
+        config.non_finite = NonFinitePolicy::Penalize { value: 1e3 };
+        let mut solver = Solver::pcr(config);
+
+        while let Some(batch) = solver.ask() {
+            let first_id = solver.history.len() as u64 + 1;
+            solver.tell(traces_for(batch, first_id)).unwrap();
+        }
+
+        assert!(solver.history.iter().all(|t| t.value.is_finite()));
+        let penalized: Vec<_> = solver.history.iter().filter(|t| t.penalized).collect();
+        assert!(!penalized.is_empty());
+        assert!(penalized.iter().all(|t| t.value == 1e3));
+        let best = solver
+            .history
+            .iter()
+            .map(|t| t.value)
+            .fold(f64::INFINITY, f64::min);
+        assert!(
+            best < 0.05,
+            "best {} should approach the sphere minimum",
+            best
+        );
+    }
+
+    #[test]
+    fn test_forced_tpe_overrides_structured_classification() {
+        let sphere = |batch: Vec<HashMap<String, f64>>, first_id: u64| -> Vec<EvalTrace> {
+            batch
+                .into_iter()
+                .enumerate()
+                .map(|(i, params)| EvalTrace {
+                    eval_id: first_id + i as u64,
+                    value: (params["x"] - 0.3).powi(2) + (params["y"] - 0.6).powi(2),
+                    ..trace(params, 0.0)
+                })
+                .collect()
+        };
+        let mut config = make_test_config();
+        config.budget = 40;
+        config.strategy_params = Some(HashMap::from([(StrategyKind::PARAM_KEY.to_string(), 2.0)]));
+        let mut solver = Solver::pcr(config);
+
+        let probe = solver.ask().unwrap();
+        solver.tell(sphere(probe, 1)).unwrap();
+        assert!(solver.ask().is_some());
+
+        assert_eq!(
+            solver.classification.map(|(mode, _)| mode),
+            Some(Landscape::Structured)
+        );
+        assert_eq!(solver.phase, Phase::Refine(Landscape::Chaotic));
+        assert_eq!(solver.plan().strategy.as_deref(), Some("TPE"));
+    }
+
+    #[test]
+    fn test_forced_strategy_rejects_unknown_code() {
+        let mut config = make_test_config();
+        assert!(StrategyKind::check(&config).is_ok());
+        for code in [1.0, 2.0] {
+            config.strategy_params = Some(HashMap::from([("force".to_string(), code)]));
+            assert!(StrategyKind::check(&config).is_ok());
+        }
+        for code in [0.0, 3.0, 1.5] {
+            config.strategy_params = Some(HashMap::from([("force".to_string(), code)]));
+            assert!(StrategyKind::check(&config).is_err());
+        }
     }
 
     #[test]
@@ -553,14 +3268,19 @@ mod tests {
         let config = make_test_config();
         let mut solver = Solver::new(config);
 
-        // Seed some data first for TPE
-        solver.seed(vec![SeedPoint {
-            params: [("x".to_string(), 0.5), ("y".to_string(), 0.5)]
-                .into_iter()
-                .collect(),
-            value: 1.0,
-            cost: 1.0,
-        }]);
+        // Seed some data first for TPE
+        solver
+            .seed(vec![SeedPoint {
+                params: [("x".to_string(), 0.5), ("y".to_string(), 0.5)]
+                    .into_iter()
+                    .collect(),
+                value: 1.0,
+                cost: 1.0,
+                penalized: false,
+                failed: false,
+                feasibility: None,
+            }])
+            .unwrap();
 
         let candidate = solver.ask_one();
         assert!(candidate.is_some());
@@ -576,25 +3296,142 @@ mod tests {
         let mut solver = Solver::new(config);
 
         // Fill budget
-        solver.seed(vec![
-            SeedPoint {
-                params: [("x".to_string(), 0.5), ("y".to_string(), 0.5)]
-                    .into_iter()
+        solver
+            .seed(vec![
+                SeedPoint {
+                    params: [("x".to_string(), 0.5), ("y".to_string(), 0.5)]
+                        .into_iter()
+                        .collect(),
+                    value: 1.0,
+                    cost: 1.0,
+                    penalized: false,
+                    failed: false,
+                    feasibility: None,
+                },
+                SeedPoint {
+                    params: [("x".to_string(), 0.3), ("y".to_string(), 0.3)]
+                        .into_iter()
+                        .collect(),
+                    value: 0.5,
+                    cost: 1.0,
+                    penalized: false,
+                    failed: false,
+                    feasibility: None,
+                },
+            ])
+            .unwrap();
+
+        let candidate = solver.ask_one();
+        assert!(candidate.is_none()); // Budget exhausted
+    }
+
+    #[test]
+    fn test_suggest_hands_out_probe_batch_in_order() {
+        let config = make_test_config();
+        let expected = Solver::pcr(config.clone()).ask().unwrap();
+        let mut solver = Solver::pcr(config);
+
+        for point in &expected {
+            assert_eq!(solver.suggest().as_ref(), Some(point));
+        }
+        assert!(solver.suggestions.is_empty());
+        // All probe points are out but none evaluated yet
+        assert!(solver.suggest().is_none());
+        assert_eq!(solver.phase, Phase::Probe);
+
+        let traces = expected
+            .into_iter()
+            .enumerate()
+            .map(|(i, params)| EvalTrace {
+                eval_id: i as u64,
+                value: params["x"] + params["y"],
+                ..trace(params, 0.0)
+            })
+            .collect();
+        solver.tell(traces).unwrap();
+        assert!(solver.suggest().is_some());
+        assert!(matches!(solver.phase, Phase::Refine(_)));
+    }
+
+    struct CountingStrategy {
+        steps: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+    }
+
+    impl Strategy for CountingStrategy {
+        fn step(&mut self, _config: &SolverConfig, _history: &[EvalTrace]) -> StrategyAction {
+            let n = self.steps.fetch_add(1, std::sync::atomic::Ordering::SeqCst) as f64;
+            StrategyAction::Evaluate(
+                (0..3)
+                    .map(|i| {
+                        [
+                            ("x".to_string(), 0.1 * i as f64),
+                            ("y".to_string(), n / 10.0),
+                        ]
+                        .into_iter()
+                        .collect()
+                    })
                     .collect(),
-                value: 1.0,
-                cost: 1.0,
-            },
-            SeedPoint {
-                params: [("x".to_string(), 0.3), ("y".to_string(), 0.3)]
+            )
+        }
+    }
+
+    #[test]
+    fn test_suggest_caches_batch_without_restepping_strategy() {
+        let steps = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let mut solver = Solver::pcr(make_test_config());
+        solver
+            .tell(
+                (0..10)
+                    .map(|i| cost_trace(i, i as f64 / 10.0, 1.0, 1.0))
+                    .collect(),
+            )
+            .unwrap();
+        solver.phase = Phase::Refine(Landscape::Chaotic);
+        solver.strategy = Some(Box::new(CountingStrategy {
+            steps: steps.clone(),
+        }));
+
+        let first: Vec<_> = (0..3).map(|_| solver.suggest().unwrap()).collect();
+        assert_eq!(steps.load(std::sync::atomic::Ordering::SeqCst), 1);
+        assert_eq!(first[0]["x"], 0.0);
+        assert_eq!(first[2]["x"], 0.2);
+
+        // Waits for the batch's results before stepping again
+        assert!(solver.suggest().is_none());
+        solver
+            .tell(
+                first
                     .into_iter()
+                    .enumerate()
+                    .map(|(i, params)| EvalTrace {
+                        eval_id: 10 + i as u64,
+                        ..trace(params, 0.5)
+                    })
                     .collect(),
-                value: 0.5,
-                cost: 1.0,
-            },
-        ]);
+            )
+            .unwrap();
+        let next = solver.suggest().unwrap();
+        assert_eq!(steps.load(std::sync::atomic::Ordering::SeqCst), 2);
+        assert_eq!(next["y"], 0.1);
+        assert_eq!(solver.suggestions.len(), 2);
+    }
 
-        let candidate = solver.ask_one();
-        assert!(candidate.is_none()); // Budget exhausted
+    #[test]
+    fn test_suggest_returns_none_when_done() {
+        let mut config = make_test_config();
+        config.budget = 2;
+        let mut solver = Solver::pcr(config);
+        solver
+            .tell(vec![
+                cost_trace(0, 0.1, 1.0, 1.0),
+                cost_trace(1, 0.2, 2.0, 1.0),
+            ])
+            .unwrap();
+        solver.suggestions.push_back(HashMap::new());
+
+        assert!(solver.suggest().is_none());
+        assert!(solver.suggestions.is_empty());
+        assert_eq!(solver.phase, Phase::Done);
     }
 
     #[test]
@@ -602,6 +3439,7 @@ mod tests {
         let sc = SeedingConfig::default();
         assert!(sc.top_k.is_none());
         assert!(sc.seed_nm);
+        assert_eq!(sc.diversity_weight, DEFAULT_SEED_DIVERSITY_WEIGHT);
     }
 
     #[test]
@@ -626,11 +3464,16 @@ mod tests {
 
         assert_eq!(solver.next_eval_id(), 1);
 
-        solver.seed(vec![SeedPoint {
-            params: HashMap::new(),
-            value: 1.0,
-            cost: 1.0,
-        }]);
+        solver
+            .seed(vec![SeedPoint {
+                params: HashMap::new(),
+                value: 1.0,
+                cost: 1.0,
+                penalized: false,
+                failed: false,
+                feasibility: None,
+            }])
+            .unwrap();
 
         assert_eq!(solver.next_eval_id(), 2);
     }
@@ -641,26 +3484,52 @@ mod tests {
         let mut solver = Solver::new(config);
 
         // Add some history
-        solver.tell(vec![
-            EvalTrace {
-                eval_id: 1,
-                params: [("x".to_string(), 0.1)].into_iter().collect(),
-                value: 3.0,
-                cost: 1.0,
-            },
-            EvalTrace {
-                eval_id: 2,
-                params: [("x".to_string(), 0.2)].into_iter().collect(),
-                value: 1.0,
-                cost: 1.0,
-            },
-            EvalTrace {
-                eval_id: 3,
-                params: [("x".to_string(), 0.3)].into_iter().collect(),
-                value: 2.0,
-                cost: 1.0,
-            },
-        ]);
+        solver
+            .tell(vec![
+                EvalTrace {
+                    eval_id: 1,
+                    params: [("x".to_string(), 0.1)].into_iter().collect(),
+                    value: 3.0,
+                    cost: 1.0,
+                    repeats: None,
+                    penalized: false,
+                    failed: false,
+                    feasibility: None,
+                    penalty: None,
+                    fidelity: None,
+                    kind: None,
+                    source: None,
+                },
+                EvalTrace {
+                    eval_id: 2,
+                    params: [("x".to_string(), 0.2)].into_iter().collect(),
+                    value: 1.0,
+                    cost: 1.0,
+                    repeats: None,
+                    penalized: false,
+                    failed: false,
+                    feasibility: None,
+                    penalty: None,
+                    fidelity: None,
+                    kind: None,
+                    source: None,
+                },
+                EvalTrace {
+                    eval_id: 3,
+                    params: [("x".to_string(), 0.3)].into_iter().collect(),
+                    value: 2.0,
+                    cost: 1.0,
+                    repeats: None,
+                    penalized: false,
+                    failed: false,
+                    feasibility: None,
+                    penalty: None,
+                    fidelity: None,
+                    kind: None,
+                    source: None,
+                },
+            ])
+            .unwrap();
 
         let top_k = solver.get_top_k_seed_points(2);
         assert_eq!(top_k.len(), 2);
@@ -669,12 +3538,46 @@ mod tests {
         assert_eq!(top_k[1].get("x"), Some(&0.3));
     }
 
+    #[test]
+    fn test_top_k_seeds_prefer_spread_points() {
+        let trace = |id: u64, x: f64, y: f64, value: f64| EvalTrace {
+            eval_id: id,
+            ..trace(
+                HashMap::from([("x".to_string(), x), ("y".to_string(), y)]),
+                value,
+            )
+        };
+        let mut solver = Solver::new(make_test_config());
+        solver
+            .tell(vec![
+                // A tight cluster of the best points...
+                trace(1, 0.50, 0.50, 0.00),
+                trace(2, 0.51, 0.50, 0.01),
+                trace(3, 0.50, 0.51, 0.02),
+                trace(4, 0.51, 0.51, 0.03),
+                // ...a few decent points spread out...
+                trace(5, 0.10, 0.90, 0.20),
+                trace(6, 0.90, 0.10, 0.20),
+                // ...and a poor one.
+                trace(7, 0.95, 0.95, 1.00),
+            ])
+            .unwrap();
+
+        let xs = |seeds: Vec<HashMap<String, f64>>| -> Vec<f64> {
+            seeds.iter().map(|p| p["x"]).collect()
+        };
+        assert_eq!(xs(solver.get_top_k_seed_points(3)), vec![0.50, 0.10, 0.90]);
+
+        solver.seeding.diversity_weight = 0.0;
+        assert_eq!(xs(solver.get_top_k_seed_points(3)), vec![0.50, 0.51, 0.50]);
+    }
+
     #[test]
     fn test_classify_phase_transition() {
         // Test that solver transitions from Probe to Classify when probe budget is met
         let mut config = make_test_config();
         config.budget = 20;
-        config.probe_ratio = 0.5; // probe_budget = 10
+        config.probe_ratio = ProbeBudget::Fixed(0.5); // probe_budget = 10
         let mut solver = Solver::new(config);
 
         // Fill probe budget with 10 evaluations
@@ -686,9 +3589,17 @@ mod tests {
                     .collect(),
                 value: (i as f64 - 5.0).powi(2), // parabola
                 cost: 1.0,
+                repeats: None,
+                penalized: false,
+                failed: false,
+                feasibility: None,
+                penalty: None,
+                fidelity: None,
+                kind: None,
+                source: None,
             })
             .collect();
-        solver.tell(traces);
+        solver.tell(traces).unwrap();
 
         // Phase should still be Probe, but next ask() should transition to Classify
         assert_eq!(solver.phase, Phase::Probe);
@@ -711,7 +3622,7 @@ mod tests {
         // Test that Chaotic classification results in TPE strategy
         let mut config = make_test_config();
         config.budget = 20;
-        config.probe_ratio = 0.5;
+        config.probe_ratio = ProbeBudget::Fixed(0.5);
         let mut solver = Solver::new(config);
 
         // Add high-variance data (simulates chaotic landscape)
@@ -724,9 +3635,17 @@ mod tests {
                 // Random-looking values with high variance
                 value: if i % 2 == 0 { 100.0 } else { 0.1 },
                 cost: 1.0,
+                repeats: None,
+                penalized: false,
+                failed: false,
+                feasibility: None,
+                penalty: None,
+                fidelity: None,
+                kind: None,
+                source: None,
             })
             .collect();
-        solver.tell(traces);
+        solver.tell(traces).unwrap();
 
         // Trigger classification
         let _ = solver.ask();
@@ -740,7 +3659,7 @@ mod tests {
         // Test that solver returns None when budget is exhausted in Refine phase
         let mut config = make_test_config();
         config.budget = 12;
-        config.probe_ratio = 0.5;
+        config.probe_ratio = ProbeBudget::Fixed(0.5);
         let mut solver = Solver::pcr(config);
 
         // Fill probe budget
@@ -752,9 +3671,17 @@ mod tests {
                     .collect(),
                 value: (i as f64 - 3.0).powi(2),
                 cost: 1.0,
+                repeats: None,
+                penalized: false,
+                failed: false,
+                feasibility: None,
+                penalty: None,
+                fidelity: None,
+                kind: None,
+                source: None,
             })
             .collect();
-        solver.tell(traces);
+        solver.tell(traces).unwrap();
 
         // Trigger classification
         let _ = solver.ask();
@@ -763,14 +3690,22 @@ mod tests {
         let more_traces: Vec<EvalTrace> = (6..12)
             .map(|i| EvalTrace {
                 eval_id: i as u64,
-                params: [("x".to_string(), i as f64 / 10.0), ("y".to_string(), 0.5)]
+                params: [("x".to_string(), i as f64 / 12.0), ("y".to_string(), 0.5)]
                     .into_iter()
                     .collect(),
                 value: 1.0,
                 cost: 1.0,
+                repeats: None,
+                penalized: false,
+                failed: false,
+                feasibility: None,
+                penalty: None,
+                fidelity: None,
+                kind: None,
+                source: None,
             })
             .collect();
-        solver.tell(more_traces);
+        solver.tell(more_traces).unwrap();
 
         // Now budget is exhausted, ask should return None or transition to Done or trigger CP restart
         let result = solver.ask();
@@ -787,12 +3722,276 @@ mod tests {
         }
     }
 
+    fn evaluate_with_cost(solver: &mut Solver, batch: Vec<HashMap<String, f64>>) {
+        let start = solver.history.len() as u64;
+        let traces = batch
+            .into_iter()
+            .enumerate()
+            .map(|(i, params)| {
+                let x = params["x"];
+                let y = params["y"];
+                EvalTrace {
+                    eval_id: start + i as u64,
+                    // Cheap near x = 0, expensive near x = 1
+                    cost: 0.5 + 4.0 * x,
+                    ..trace(params, (x - 0.3).powi(2) + (y - 0.6).powi(2))
+                }
+            })
+            .collect();
+        solver.tell(traces).unwrap();
+    }
+
+    #[test]
+    fn test_cost_budget_stops_with_heterogeneous_costs() {
+        let mut config = make_test_config();
+        config.budget = 0;
+        config.cost_budget = Some(60.0);
+        config.probe_ratio = ProbeBudget::Fixed(0.2);
+        let mut solver = Solver::pcr(config);
+
+        let mut spent_before_last_ask = 0.0;
+        for _ in 0..1000 {
+            let spent = solver.spent_cost();
+            match solver.ask() {
+                Some(batch) => {
+                    spent_before_last_ask = spent;
+                    evaluate_with_cost(&mut solver, batch);
+                }
+                None => break,
+            }
+        }
+
+        assert_eq!(solver.phase, Phase::Done);
+        assert!(solver.budget_exhausted());
+        assert!(solver.spent_cost() >= 60.0);
+        assert!(spent_before_last_ask < 60.0);
+        // Heterogeneous costs mean the count differs from a unit-cost run
+        assert!(solver.history.len() < 120);
+    }
+
+    #[test]
+    fn test_cost_budget_ends_probe_on_cost_share() {
+        let mut config = make_test_config();
+        config.budget = 100;
+        config.cost_budget = Some(50.0);
+        config.probe_ratio = ProbeBudget::Fixed(0.2); // 20 probe points, 10.0 probe cost
+        let mut solver = Solver::pcr(config);
+
+        let batch = solver.ask().unwrap();
+        assert_eq!(batch.len(), 20);
+        // Only three expensive probes come back, already past the 10.0 share
+        let traces = batch
+            .into_iter()
+            .take(3)
+            .enumerate()
+            .map(|(i, params)| EvalTrace {
+                eval_id: i as u64,
+                cost: 4.0,
+                ..trace(params, i as f64)
+            })
+            .collect();
+        solver.tell(traces).unwrap();
+
+        let _ = solver.ask();
+        assert_ne!(solver.phase, Phase::Probe);
+    }
+
+    #[test]
+    fn test_cost_budget_stops_mid_refine_before_count_budget() {
+        let mut config = make_test_config();
+        config.budget = 1000;
+        config.probe_ratio = ProbeBudget::Fixed(0.02); // 20 probe points
+        config.cost_budget = Some(200.0);
+        let mut solver = Solver::pcr(config);
+
+        while let Some(batch) = solver.ask() {
+            evaluate_with_cost(&mut solver, batch);
+            assert!(solver.history.len() < 1000);
+        }
+        assert!(solver.history.len() > 20, "cap reached during refine");
+        assert_eq!(solver.phase, Phase::Done);
+        assert!(solver.spent_cost() >= 200.0);
+        assert!(solver.ask_one().is_none());
+    }
+
+    /// Two wells: a broad one at (0.25, 0.25) with minimum 0 and a narrow,
+    /// deeper one at (0.8, 0.8) with minimum -0.05.
+    fn two_well(params: &HashMap<String, f64>) -> f64 {
+        let (x, y) = (params["x"], params["y"]);
+        let a = (x - 0.25).powi(2) + (y - 0.25).powi(2);
+        let b = 4.0 * ((x - 0.8).powi(2) + (y - 0.8).powi(2)) - 0.05;
+        a.min(b)
+    }
+
+    /// Nelder-Mead that reports convergence once history reaches `limit`.
+    struct ConvergeAt {
+        inner: NelderMead,
+        limit: usize,
+    }
+
+    impl Strategy for ConvergeAt {
+        fn step(&mut self, config: &SolverConfig, history: &[EvalTrace]) -> StrategyAction {
+            if history.len() >= self.limit {
+                StrategyAction::Converged
+            } else {
+                self.inner.step(config, history)
+            }
+        }
+    }
+
+    fn run_two_well(max_restarts: usize) -> Solver {
+        let mut config = make_test_config();
+        config.budget = 600;
+        config.max_restarts = max_restarts;
+        let mut solver = Solver::new(config);
+
+        // Starting points all sit in the shallow well except one far corner.
+        let starts = [
+            (0.3, 0.3),
+            (0.2, 0.35),
+            (0.35, 0.2),
+            (0.1, 0.1),
+            (0.95, 0.95),
+        ];
+        let traces = starts
+            .iter()
+            .enumerate()
+            .map(|(i, &(x, y))| {
+                let params: HashMap<String, f64> = [("x".to_string(), x), ("y".to_string(), y)]
+                    .into_iter()
+                    .collect();
+                EvalTrace {
+                    eval_id: i as u64,
+                    value: two_well(&params),
+                    ..trace(params, 0.0)
+                }
+            })
+            .collect();
+        solver.tell(traces).unwrap();
+        solver.phase = Phase::Refine(Landscape::Structured);
+        solver.strategy = Some(Box::new(ConvergeAt {
+            inner: NelderMead::new(2, vec![false; 2]),
+            limit: 200,
+        }));
+        solver.restarted = true; // isolate from the CP restart
+
+        while let Some(batch) = solver.ask() {
+            let start = solver.history.len();
+            let traces = batch
+                .into_iter()
+                .enumerate()
+                .map(|(i, params)| EvalTrace {
+                    eval_id: (start + i) as u64,
+                    value: two_well(&params),
+                    ..trace(params, 0.0)
+                })
+                .collect();
+            solver.tell(traces).unwrap();
+        }
+        solver
+    }
+
+    fn best_value(solver: &Solver) -> f64 {
+        solver
+            .history
+            .iter()
+            .map(|t| t.value)
+            .fold(f64::INFINITY, f64::min)
+    }
+
+    #[test]
+    fn test_diverse_restart_finds_second_well() {
+        let single = run_two_well(0);
+        assert!(single.restarts.is_empty());
+        assert_eq!(single.phase, Phase::Done);
+        assert!(
+            single.history.len() < 600,
+            "budget left unused without restarts"
+        );
+        assert!(best_value(&single) > -1e-6, "stuck in the shallow well");
+
+        let solver = run_two_well(1);
+        assert_eq!(solver.restarts.len(), 1);
+        let restart = &solver.restarts[0];
+        assert!(restart.optimum_value.abs() < 1e-6);
+        // The seed is the evaluated point farthest from the shallow well.
+        assert!(restart.seed["x"] > 0.9 && restart.seed["y"] > 0.9);
+        assert!(restart.distance > 0.9);
+        assert!(restart.at_eval >= 200 && restart.at_eval <= 480);
+        assert_eq!(solver.converged_optima.len(), 1);
+        assert!(solver.history.len() <= 600);
+        assert!(
+            (best_value(&solver) + 0.05).abs() < 1e-4,
+            "restart should reach the deep well, best {}",
+            best_value(&solver)
+        );
+    }
+
+    #[test]
+    fn test_plan_reports_budget_split_before_probing() {
+        let mut config = make_test_config();
+        config.budget = 100;
+        config.probe_ratio = ProbeBudget::Fixed(0.2);
+        let solver = Solver::pcr(config);
+
+        let plan = solver.plan();
+        assert_eq!(plan.probe_points, 20);
+        assert_eq!(plan.refine_budget, Some(80));
+        assert_eq!(plan.probe, "PrimeSqrtSlopesRotProbe");
+        assert_eq!(plan.evaluated, 0);
+        assert_eq!(plan.classification, None);
+        assert_eq!(plan.strategy, None);
+        assert_eq!(solver.phase, Phase::Probe);
+    }
+
+    #[test]
+    fn test_plan_classifies_covered_history_without_advancing() {
+        let mut config = make_test_config();
+        config.budget = 100;
+        config.probe_ratio = ProbeBudget::Fixed(0.1);
+        let mut solver = Solver::pcr(config);
+        let traces = (0..10)
+            .map(|i| EvalTrace {
+                eval_id: i as u64,
+                ..trace(
+                    HashMap::from([("x".to_string(), i as f64 / 10.0), ("y".to_string(), 0.5)]),
+                    10.0 + (i as f64 / 10.0).powi(2),
+                )
+            })
+            .collect();
+        solver.tell(traces).unwrap();
+
+        let plan = solver.plan();
+        assert_eq!(plan.evaluated, 10);
+        assert_eq!(plan.classification, Some(Landscape::Structured));
+        assert!(plan.classification_score.is_some());
+        assert_eq!(plan.strategy.as_deref(), Some("NelderMead"));
+        assert_eq!(solver.phase, Phase::Probe);
+        assert!(solver.strategy.is_none());
+
+        // A factory replacing the built-in one reports its own name, and the
+        // surrogate wrapper shows.
+        let mut solver = solver.with_strategy_factory(
+            StrategyRegistry::NELDER_MEAD,
+            Box::new(|_, _| Box::new(ReturnBest)),
+        );
+        assert_eq!(solver.plan().strategy.as_deref(), Some("ReturnBest"));
+        solver.config.strategy_params = Some(HashMap::from([(
+            SurrogateConfig::PARAM_KEY.to_string(),
+            1.0,
+        )]));
+        assert_eq!(
+            solver.plan().strategy.as_deref(),
+            Some("Surrogate(ReturnBest)")
+        );
+    }
+
     #[test]
     fn test_cp_restart_trigger() {
         // Test CP restart trigger at 70% budget in Structured mode
         let mut config = make_test_config();
         config.budget = 100;
-        config.probe_ratio = 0.1; // probe_budget = 10
+        config.probe_ratio = ProbeBudget::Fixed(0.1); // probe_budget = 10
         let mut solver = Solver::pcr(config);
 
         // Fill probe budget with structured data
@@ -804,9 +4003,17 @@ mod tests {
                     .collect(),
                 value: (i as f64 / 10.0).powi(2), // structured: parabola
                 cost: 1.0,
+                repeats: None,
+                penalized: false,
+                failed: false,
+                feasibility: None,
+                penalty: None,
+                fidelity: None,
+                kind: None,
+                source: None,
             })
             .collect();
-        solver.tell(traces.clone());
+        solver.tell(traces.clone()).unwrap();
 
         // Trigger classification - should be Structured
         let _ = solver.ask();
@@ -832,9 +4039,17 @@ mod tests {
                 .collect(),
                 value: 1.0,
                 cost: 1.0,
+                repeats: None,
+                penalized: false,
+                failed: false,
+                feasibility: None,
+                penalty: None,
+                fidelity: None,
+                kind: None,
+                source: None,
             });
         }
-        solver.tell(traces[10..70].to_vec());
+        solver.tell(traces[10..70].to_vec()).unwrap();
 
         // This ask() should trigger CP restart
         let rescue_batch = solver.ask();
@@ -851,7 +4066,7 @@ mod tests {
         // Test that strategy is re-initialized after CP restart
         let mut config = make_test_config();
         config.budget = 100;
-        config.probe_ratio = 0.1;
+        config.probe_ratio = ProbeBudget::Fixed(0.1);
         let mut solver = Solver::pcr(config);
 
         // Add probe data
@@ -863,9 +4078,17 @@ mod tests {
                     .collect(),
                 value: (i as f64 / 10.0).powi(2),
                 cost: 1.0,
+                repeats: None,
+                penalized: false,
+                failed: false,
+                feasibility: None,
+                penalty: None,
+                fidelity: None,
+                kind: None,
+                source: None,
             })
             .collect();
-        solver.tell(traces);
+        solver.tell(traces).unwrap();
         let _ = solver.ask();
 
         // Artificially set up post-restart state where strategy is None
@@ -884,4 +4107,293 @@ mod tests {
             assert!(solver.strategy.is_some());
         }
     }
+
+    #[test]
+    fn test_probe_selector_from_strategy_params() {
+        use crate::probe::SobolProbe;
+
+        let mut config = make_test_config();
+        config.strategy_params = Some([("probe".to_string(), 3.0)].into_iter().collect());
+        let expected = SobolProbe::new().sample(&config);
+
+        let mut solver = Solver::pcr(config);
+        assert_eq!(solver.ask(), Some(expected));
+    }
+
+    fn sum_constraint(max: f64) -> crate::config::Constraint {
+        crate::config::Constraint {
+            expr: "x + y".to_string(),
+            min: None,
+            max: Some(max),
+        }
+    }
+
+    #[test]
+    fn test_probe_candidates_respect_constraints() {
+        let mut config = make_test_config();
+        config.constraints = vec![sum_constraint(0.8)];
+        let constraints = config.constraints.clone();
+        let probe_budget = config.probe_count();
+
+        let mut solver = Solver::pcr(config);
+        let batch = solver.ask().unwrap();
+        assert_eq!(batch.len(), probe_budget);
+        for point in &batch {
+            assert!(all_satisfied(&constraints, point), "{:?}", point);
+        }
+    }
+
+    #[test]
+    fn test_resampled_probe_points_independent_of_bounds_order() {
+        let batch = |names: [&str; 4]| {
+            let mut config = make_test_config();
+            // A fresh map each time, so each has its own iteration order.
+            config.bounds = names
+                .iter()
+                .map(|name| (name.to_string(), config.bounds["x"].clone()))
+                .collect();
+            config.constraints = vec![crate::config::Constraint {
+                expr: "a + b + c + d".to_string(),
+                min: None,
+                max: Some(1.5),
+            }];
+            Solver::pcr(config).ask().unwrap()
+        };
+        let first = batch(["a", "b", "c", "d"]);
+        assert_eq!(batch(["d", "c", "b", "a"]), first);
+        assert_eq!(batch(["b", "d", "a", "c"]), first);
+    }
+
+    #[test]
+    fn test_infeasible_probe_points_shrink_probe_budget() {
+        let mut config = make_test_config();
+        // Unsatisfiable within [0, 1]^2
+        config.constraints = vec![sum_constraint(-1.0)];
+
+        let mut solver = Solver::new(config);
+        assert_eq!(solver.ask(), None);
+        assert_eq!(solver.phase, Phase::Done);
+    }
+
+    #[test]
+    fn test_projection_moves_candidate_to_boundary() {
+        let constraints = vec![sum_constraint(1.0)];
+        let candidate: HashMap<String, f64> = [("x".to_string(), 1.0), ("y".to_string(), 1.0)]
+            .into_iter()
+            .collect();
+        let anchor: HashMap<String, f64> = [("x".to_string(), 0.0), ("y".to_string(), 0.0)]
+            .into_iter()
+            .collect();
+
+        let projected = project_towards(&ConstraintSet::new(&constraints), &candidate, &anchor);
+        assert!(all_satisfied(&constraints, &projected));
+        assert!((projected["x"] + projected["y"] - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_strategy_candidates_projected_or_rejected() {
+        let mut config = make_test_config();
+        config.constraints = vec![sum_constraint(1.0)];
+        let mut solver = Solver::new(config);
+        let infeasible: HashMap<String, f64> = [("x".to_string(), 0.9), ("y".to_string(), 0.9)]
+            .into_iter()
+            .collect();
+
+        // No feasible anchor yet: candidate is rejected
+        assert!(solver
+            .project_infeasible(vec![infeasible.clone()])
+            .is_empty());
+
+        solver
+            .seed(vec![SeedPoint {
+                params: HashMap::from([("x".to_string(), 0.1), ("y".to_string(), 0.1)]),
+                value: 0.0,
+                cost: 1.0,
+                penalized: false,
+                failed: false,
+                feasibility: None,
+            }])
+            .unwrap();
+        let projected = solver.project_infeasible(vec![infeasible]);
+        assert_eq!(projected.len(), 1);
+        assert!(all_satisfied(&solver.config.constraints, &projected[0]));
+        assert!(projected[0]["x"] > 0.1);
+    }
+
+    fn seed_points(n: usize) -> Vec<SeedPoint> {
+        (0..n)
+            .map(|i| {
+                let x = (i as f64 + 0.5) / n as f64;
+                let y = ((i * 7) % n) as f64 / n as f64;
+                SeedPoint {
+                    params: HashMap::from([("x".to_string(), x), ("y".to_string(), y)]),
+                    value: (x - 0.3).powi(2) + (y - 0.6).powi(2),
+                    cost: 1.0,
+                    penalized: false,
+                    failed: false,
+                    feasibility: None,
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_warm_start_skips_probe_and_classifies() {
+        let config = make_test_config();
+        let mut solver = Solver::pcr(config);
+
+        assert!(solver.warm_start(seed_points(12)).unwrap());
+        assert_eq!(solver.phase, Phase::Classify);
+
+        let batch = solver.ask().expect("refine strategy should propose");
+        assert!(matches!(solver.phase, Phase::Refine(_)));
+        assert!(solver.strategy.is_some());
+        assert!(!batch.is_empty());
+        // Imported evaluations are not re-probed
+        assert_eq!(solver.history.len(), 12);
+    }
+
+    #[test]
+    fn test_warm_start_budget_accounts_for_imported_history() {
+        let config = make_test_config();
+        let budget = config.budget as usize;
+        let mut solver = Solver::pcr(config);
+        solver.warm_start(seed_points(12)).unwrap();
+
+        let mut evaluated = 0;
+        while let Some(batch) = solver.ask() {
+            let results: Vec<SeedPoint> = batch
+                .into_iter()
+                .map(|params| SeedPoint {
+                    value: params.values().sum(),
+                    params,
+                    cost: 1.0,
+                    penalized: false,
+                    failed: false,
+                    feasibility: None,
+                })
+                .collect();
+            evaluated += results.len();
+            solver.seed(results).unwrap();
+            if evaluated > budget {
+                break;
+            }
+        }
+        assert_eq!(evaluated, budget - 12);
+        assert_eq!(solver.history.len(), budget);
+    }
+
+    #[test]
+    fn test_warm_start_with_short_history_keeps_probing() {
+        let config = make_test_config();
+        let mut solver = Solver::pcr(config);
+        assert!(!solver.warm_start(seed_points(3)).unwrap());
+        assert_eq!(solver.phase, Phase::Probe);
+    }
+
+    fn cost_trace(eval_id: u64, x: f64, value: f64, cost: f64) -> EvalTrace {
+        EvalTrace {
+            eval_id,
+            cost,
+            ..trace(
+                HashMap::from([("x".to_string(), x), ("y".to_string(), 0.5)]),
+                value,
+            )
+        }
+    }
+
+    #[test]
+    fn test_pareto_seeding_spreads_along_front() {
+        let mut config = make_test_config();
+        config.selection = Selection::Pareto;
+        let mut solver = Solver::new(config);
+        solver
+            .tell(vec![
+                cost_trace(1, 0.0, 0.0, 10.0),
+                cost_trace(2, 0.1, 1.0, 6.0),
+                cost_trace(3, 0.2, 1.1, 5.9),
+                cost_trace(4, 0.3, 5.0, 2.0),
+                cost_trace(5, 0.4, 10.0, 0.0),
+                cost_trace(6, 0.5, 11.0, 11.0), // dominated
+            ])
+            .unwrap();
+
+        let seeds: Vec<f64> = solver
+            .get_top_k_seed_points(3)
+            .iter()
+            .map(|p| p["x"])
+            .collect();
+        // Both extremes of the front are chosen before interior points
+        assert_eq!(seeds[0], 0.0);
+        assert_eq!(seeds[1], 0.4);
+        assert!(!seeds.contains(&0.5));
+    }
+
+    #[test]
+    fn test_pareto_seeding_fills_from_next_layer() {
+        let mut config = make_test_config();
+        config.selection = Selection::Pareto;
+        let mut solver = Solver::new(config);
+        solver
+            .tell(vec![
+                cost_trace(1, 0.0, 3.0, 3.0),
+                cost_trace(2, 0.1, 1.0, 1.0),
+                cost_trace(3, 0.2, 2.0, 2.0),
+            ])
+            .unwrap();
+        let seeds: Vec<f64> = solver
+            .get_top_k_seed_points(2)
+            .iter()
+            .map(|p| p["x"])
+            .collect();
+        assert_eq!(seeds, vec![0.1, 0.2]);
+    }
+
+    #[test]
+    fn test_strategy_view_scalarizes_by_pareto_rank() {
+        let mut config = make_test_config();
+        let history = vec![
+            cost_trace(1, 0.0, 1.0, 5.0),
+            cost_trace(2, 0.1, 5.0, 1.0),
+            cost_trace(3, 0.2, 2.0, 6.0), // dominated by 1
+        ];
+        assert!(matches!(strategy_view(&config, &history), Cow::Borrowed(_)));
+
+        config.selection = Selection::Pareto;
+        let view = strategy_view(&config, &history);
+        assert!(view[0].value < 1.0);
+        assert!(view[1].value < 1.0);
+        assert!(view[2].value >= 1.0);
+        assert_eq!(view[2].params, history[2].params);
+    }
+
+    #[test]
+    fn test_pareto_history_tracks_strategy_view() {
+        let mut config = make_test_config();
+        config.selection = Selection::Pareto;
+        let mut solver = Solver::pcr(config.clone());
+        let values = |traces: &[EvalTrace]| traces.iter().map(|t| t.value).collect::<Vec<_>>();
+
+        solver
+            .tell(vec![
+                cost_trace(1, 0.0, 2.0, 5.0),
+                cost_trace(2, 0.1, 3.0, 6.0),
+            ])
+            .unwrap();
+        solver.sync_pareto_history();
+        assert_eq!(
+            values(&solver.pareto_history),
+            values(&strategy_view(&config, &solver.history))
+        );
+
+        // A new trace dominates the first two: every score moves, not just
+        // the new one.
+        solver.tell(vec![cost_trace(3, 0.2, 1.0, 1.0)]).unwrap();
+        solver.sync_pareto_history();
+        assert_eq!(
+            values(&solver.pareto_history),
+            values(&strategy_view(&config, &solver.history))
+        );
+        assert!(solver.pareto_history[0].value >= 1.0);
+    }
 }
@@ -1,5 +1,6 @@
-use crate::config::{Scale, SolverConfig};
-use crate::rng::get_rng;
+use crate::config::{wrap01, Domain, ProbeBudget, Scale, SolverConfig};
+use crate::rng::{derive_seed, get_rng, get_rng_stream, COMPOSITE_PROBE_STREAM};
+use rand::seq::SliceRandom;
 use rand::Rng;
 use std::collections::HashMap;
 
@@ -8,17 +9,39 @@ pub type Candidates = Vec<HashMap<String, f64>>;
 
 pub trait Probe: Send + Sync {
     fn sample(&self, config: &SolverConfig) -> Candidates;
+
+    /// Continue the probe sequence after `offset` points have already been
+    /// probed, so a re-probe does not repeat earlier points. Deterministic for
+    /// a given `config.seed` and offset; offset 0 is the same as `sample`.
+    ///
+    /// Probes that cannot continue their sequence return `sample` unchanged.
+    fn sample_from(&self, config: &SolverConfig, offset: usize) -> Candidates {
+        let _ = offset;
+        self.sample(config)
+    }
+
+    /// Short type name of the probe, e.g. `"SobolProbe"`.
+    fn name(&self) -> &'static str {
+        let path = std::any::type_name::<Self>();
+        path.rsplit("::").next().unwrap_or(path)
+    }
 }
 
 /// A deterministic Uniform Random probe.
 ///
-/// Replaces Sobol for MVP to minimize dependencies while maintaining strict determinism.
+/// Independent draws seeded from `config.seed`. Simple, but leaves gaps in
+/// higher dimensions; [`SobolProbe`] covers the space more evenly and is
+/// selected with `strategy_params.probe = 3` (see [`ProbeKind`]).
 pub struct UniformProbe;
 
 impl Probe for UniformProbe {
     fn sample(&self, config: &SolverConfig) -> Candidates {
-        let mut rng = get_rng(config.seed);
-        let num_samples = (config.budget as f64 * config.probe_ratio).ceil() as usize;
+        self.sample_from(config, 0)
+    }
+
+    fn sample_from(&self, config: &SolverConfig, offset: usize) -> Candidates {
+        let mut rng = get_rng_stream(config.seed, offset as u64);
+        let num_samples = config.probe_count();
         let mut candidates = Vec::with_capacity(num_samples);
 
         for _ in 0..num_samples {
@@ -133,11 +156,16 @@ impl PrimeIndexProbe {
 
 impl Probe for PrimeIndexProbe {
     fn sample(&self, config: &SolverConfig) -> Candidates {
-        let mut rng = get_rng(config.seed);
-        let num_samples = (config.budget as f64 * config.probe_ratio).ceil() as usize;
+        self.sample_from(config, 0)
+    }
+
+    /// Continues with the next primes; `max_primes` caps the whole sequence.
+    fn sample_from(&self, config: &SolverConfig, offset: usize) -> Candidates {
+        let mut rng = get_rng_stream(config.seed, offset as u64);
+        let num_samples = config.probe_count();
 
         // Generate prime-indexed positions for each dimension
-        let positions = self.generate_prime_positions(num_samples);
+        let positions = self.generate_prime_positions(offset + num_samples);
 
         // Sort dimension keys for deterministic ordering
         let mut keys: Vec<_> = config.bounds.keys().cloned().collect();
@@ -145,7 +173,7 @@ impl Probe for PrimeIndexProbe {
 
         let mut candidates = Vec::with_capacity(num_samples);
 
-        for (i, &pos) in positions.iter().enumerate() {
+        for (i, &pos) in positions.iter().enumerate().skip(offset) {
             let mut point = HashMap::new();
 
             for (dim_idx, name) in keys.iter().enumerate() {
@@ -175,12 +203,21 @@ impl Probe for PrimeIndexProbe {
 
         // Add small random perturbation for robustness (optional)
         // This prevents exact aliasing while maintaining the multi-scale property
+        // Walk dimensions in sorted order so the draws are reproducible.
+        // The step is taken in unit space (log space for log domains) and
+        // reflected off the bounds, so points near an edge are not piled onto it.
         for candidate in candidates.iter_mut() {
-            for (name, value) in candidate.iter_mut() {
-                if let Some(domain) = config.bounds.get(name) {
-                    let range = domain.max - domain.min;
-                    let perturbation = rng.random_range(-0.01..=0.01) * range;
-                    *value = (*value + perturbation).clamp(domain.min, domain.max);
+            for name in &keys {
+                if let (Some(value), Some(domain)) =
+                    (candidate.get_mut(name), config.bounds.get(name))
+                {
+                    let moved = domain.to_unit(*value) + rng.random_range(-0.01..=0.01);
+                    let unit = if domain.is_periodic() {
+                        wrap01(moved)
+                    } else {
+                        reflect01(moved)
+                    };
+                    *value = unit_to_domain(unit, domain);
                 }
             }
         }
@@ -419,16 +456,22 @@ impl PrimeSqrtSlopesRotProbe {
 
 impl Probe for PrimeSqrtSlopesRotProbe {
     fn sample(&self, config: &SolverConfig) -> Candidates {
+        self.sample_from(config, 0)
+    }
+
+    /// Continues the Kronecker sequence at index `offset`. Anchors are only
+    /// emitted by the first batch; spice points come from a fresh RNG stream.
+    fn sample_from(&self, config: &SolverConfig, offset: usize) -> Candidates {
         let (_, slopes, rotations, keys) = self.prepare_geometry(config);
 
         // Calculate budget based on config
-        let num_samples = (config.budget as f64 * config.probe_ratio).ceil() as usize;
+        let num_samples = config.probe_count();
 
         // Determine how many points to spice with random
         let num_random = (num_samples as f64 * self.config.random_spice_ratio).floor() as usize;
 
         // Reserve space for deterministic anchors (Origin + Center)
-        let num_anchors = 2;
+        let num_anchors = if offset == 0 { 2 } else { 0 };
         let num_qmc = num_samples.saturating_sub(num_random + num_anchors);
 
         // PHASE 6: Apply Cranley-Patterson shift if provided in config
@@ -443,8 +486,8 @@ impl Probe for PrimeSqrtSlopesRotProbe {
         let mut candidates = Vec::with_capacity(num_samples);
 
         // 1. Inject Deterministic Anchors (Origin + Center)
-        let anchors_unit = [0.0, 0.5];
-        for unit_pos in anchors_unit {
+        let anchors_unit: &[f64] = if offset == 0 { &[0.0, 0.5] } else { &[] };
+        for &unit_pos in anchors_unit {
             let mut point = HashMap::new();
             for name in keys.iter() {
                 if let Some(domain) = config.bounds.get(name) {
@@ -470,7 +513,7 @@ impl Probe for PrimeSqrtSlopesRotProbe {
         }
 
         // 2. Generate QMC (prime-sqrt-slopes-rot) points using precomputed values
-        for i in 0..num_qmc {
+        for i in offset..offset + num_qmc {
             let mut point = HashMap::new();
 
             for (dim_idx, name) in keys.iter().enumerate() {
@@ -512,6 +555,7 @@ impl Probe for PrimeSqrtSlopesRotProbe {
         let random_seed = (self.seed_rotation * 1e9) as u64;
         use rand::SeedableRng;
         let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(random_seed);
+        rng.set_stream(offset as u64);
 
         for _ in 0..num_random {
             let mut point = HashMap::new();
@@ -540,10 +584,500 @@ impl Probe for PrimeSqrtSlopesRotProbe {
     }
 }
 
+// ============================================================================
+// Scrambled Sobol Probe (Owen-scrambled low-discrepancy sequence)
+// ============================================================================
+
+/// Joe-Kuo direction-number parameters `(s, a, m_1..m_s)` for dimensions 2..=21.
+///
+/// Dimension 1 is the van der Corput sequence and needs no entry. Source:
+/// `new-joe-kuo-6.21201` (S. Joe and F. Y. Kuo, 2008).
+const SOBOL_JOE_KUO: [(u32, u32, &[u32]); 20] = [
+    (1, 0, &[1]),
+    (2, 1, &[1, 3]),
+    (3, 1, &[1, 3, 1]),
+    (3, 2, &[1, 1, 1]),
+    (4, 1, &[1, 1, 3, 3]),
+    (4, 4, &[1, 3, 5, 13]),
+    (5, 2, &[1, 1, 5, 5, 17]),
+    (5, 4, &[1, 1, 5, 5, 5]),
+    (5, 7, &[1, 1, 7, 11, 19]),
+    (5, 11, &[1, 1, 5, 1, 1]),
+    (5, 13, &[1, 1, 1, 3, 11]),
+    (5, 14, &[1, 3, 5, 5, 31]),
+    (6, 1, &[1, 3, 3, 9, 7, 49]),
+    (6, 13, &[1, 1, 1, 15, 21, 21]),
+    (6, 16, &[1, 3, 1, 13, 27, 49]),
+    (6, 19, &[1, 1, 1, 15, 7, 5]),
+    (6, 22, &[1, 3, 1, 15, 13, 25]),
+    (6, 25, &[1, 1, 5, 5, 19, 61]),
+    (7, 1, &[1, 3, 7, 11, 23, 15, 103]),
+    (7, 4, &[1, 3, 7, 13, 13, 15, 69]),
+];
+
+/// Number of output bits per Sobol coordinate.
+const SOBOL_BITS: usize = 32;
+
+/// Scrambled Sobol probe.
+///
+/// Generates a Sobol sequence (Joe-Kuo direction numbers) and applies a
+/// hash-based Owen scramble per dimension (Burley, 2020), which keeps the
+/// (t, s)-net structure while removing the bias of the raw sequence.
+///
+/// **Key properties:**
+/// - **Deterministic**: Scramble seeds are derived from `config.seed`
+/// - **Low discrepancy**: Fills gaps far better than `UniformProbe` in 4+ dims
+/// - **Dimension limit**: Direction numbers cover 21 dimensions; any further
+///   dimensions fall back to seeded uniform sampling
+#[derive(Default)]
+pub struct SobolProbe;
+
+impl SobolProbe {
+    /// Maximum number of dimensions with true Sobol coverage.
+    pub const MAX_DIMS: usize = SOBOL_JOE_KUO.len() + 1;
+
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Build the 32 direction numbers for a (0-based) dimension.
+    fn direction_numbers(dim: usize) -> [u32; SOBOL_BITS] {
+        let mut v = [0u32; SOBOL_BITS];
+        if dim == 0 {
+            for (j, vj) in v.iter_mut().enumerate() {
+                *vj = 1 << (31 - j);
+            }
+            return v;
+        }
+
+        let (s, a, m) = SOBOL_JOE_KUO[dim - 1];
+        let s = s as usize;
+        for j in 0..s.min(SOBOL_BITS) {
+            v[j] = m[j] << (31 - j);
+        }
+        for j in s..SOBOL_BITS {
+            let mut vj = v[j - s] ^ (v[j - s] >> s);
+            for k in 1..s {
+                if (a >> (s - 1 - k)) & 1 == 1 {
+                    vj ^= v[j - k];
+                }
+            }
+            v[j] = vj;
+        }
+        v
+    }
+
+    /// Raw (unscrambled) Sobol coordinate for the given index.
+    fn sobol_bits(index: u32, directions: &[u32; SOBOL_BITS]) -> u32 {
+        let mut x = 0u32;
+        let mut i = index;
+        let mut j = 0;
+        while i != 0 {
+            if i & 1 == 1 {
+                x ^= directions[j];
+            }
+            i >>= 1;
+            j += 1;
+        }
+        x
+    }
+
+    /// Laine-Karras style permutation used by hash-based Owen scrambling.
+    fn laine_karras_permutation(mut x: u32, seed: u32) -> u32 {
+        x = x.wrapping_add(seed);
+        x ^= x.wrapping_mul(0x6c50_b47c);
+        x ^= x.wrapping_mul(0xb82f_1e52);
+        x ^= x.wrapping_mul(0xc7af_e638);
+        x ^= x.wrapping_mul(0x8d22_f6e6);
+        x
+    }
+
+    /// Nested uniform (Owen) scramble of a 32-bit fixed-point coordinate.
+    pub(crate) fn owen_scramble(x: u32, seed: u32) -> u32 {
+        Self::laine_karras_permutation(x.reverse_bits(), seed).reverse_bits()
+    }
+}
+
+/// Reflect a unit position that left [0, 1] back across the bound it crossed.
+fn reflect01(unit: f64) -> f64 {
+    let reflected = if unit < 0.0 {
+        -unit
+    } else if unit > 1.0 {
+        2.0 - unit
+    } else {
+        unit
+    };
+    reflected.clamp(0.0, 1.0)
+}
+
+/// Map a unit-interval position onto a domain, respecting its scale.
+pub(crate) fn unit_to_domain(unit_pos: f64, domain: &Domain) -> f64 {
+    match domain.scale {
+        Scale::Linear | Scale::Periodic => domain.min + unit_pos * (domain.max - domain.min),
+        Scale::Log => {
+            let min_log = domain.min.ln();
+            let max_log = domain.max.ln();
+            (min_log + unit_pos * (max_log - min_log))
+                .exp()
+                .clamp(domain.min, domain.max)
+        }
+    }
+}
+
+impl Probe for SobolProbe {
+    fn sample(&self, config: &SolverConfig) -> Candidates {
+        self.sample_from(config, 0)
+    }
+
+    /// Continues the scrambled sequence at index `offset`.
+    fn sample_from(&self, config: &SolverConfig, offset: usize) -> Candidates {
+        let mut rng = get_rng(config.seed);
+        let num_samples = config.probe_count();
+
+        // Sort dimension keys for deterministic ordering
+        let mut keys: Vec<_> = config.bounds.keys().cloned().collect();
+        keys.sort();
+
+        // One scramble seed per dimension, drawn up front so the sequence
+        // does not depend on the number of samples requested.
+        let scramble_seeds: Vec<u32> = keys.iter().map(|_| rng.random()).collect();
+        let directions: Vec<[u32; SOBOL_BITS]> = (0..keys.len().min(Self::MAX_DIMS))
+            .map(Self::direction_numbers)
+            .collect();
+        if offset > 0 {
+            // Dimensions past MAX_DIMS are random; don't repeat earlier draws.
+            rng.set_stream(offset as u64);
+        }
+
+        let scale = 1.0 / (1u64 << SOBOL_BITS) as f64;
+        let mut candidates = Vec::with_capacity(num_samples);
+
+        for i in 0..num_samples {
+            let mut point = HashMap::new();
+            for (dim_idx, name) in keys.iter().enumerate() {
+                if let Some(domain) = config.bounds.get(name) {
+                    let unit_pos = match directions.get(dim_idx) {
+                        Some(dirs) => {
+                            let raw = Self::sobol_bits((offset + i) as u32, dirs);
+                            Self::owen_scramble(raw, scramble_seeds[dim_idx]) as f64 * scale
+                        }
+                        None => rng.random::<f64>(),
+                    };
+                    point.insert(name.clone(), unit_to_domain(unit_pos, domain));
+                }
+            }
+            candidates.push(point);
+        }
+
+        candidates
+    }
+}
+
+// ============================================================================
+// Latin Hypercube Probe
+// ============================================================================
+
+/// Latin hypercube probe.
+///
+/// Splits every dimension into `n` equal strata and places exactly one of the
+/// `n` samples in each, with the strata paired across dimensions by an
+/// independent random permutation and jittered within the stratum. Strata
+/// are equal in unit space, so log-scaled dimensions are stratified by
+/// decade rather than by value.
+///
+/// With [`LhsProbe::with_maximin`] several designs are drawn and the one with
+/// the largest minimum pairwise distance is kept, which avoids the clumped
+/// designs a single random permutation occasionally produces.
+///
+/// Deterministic for a given `config.seed`; `sample_from` draws a fresh
+/// design on the offset's RNG stream.
+#[derive(Debug, Clone, Copy)]
+pub struct LhsProbe {
+    /// Number of candidate designs compared by the maximin criterion.
+    designs: usize,
+}
+
+impl Default for LhsProbe {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LhsProbe {
+    /// Designs compared when maximin is selected through `ProbeKind`.
+    pub const DEFAULT_MAXIMIN_DESIGNS: usize = 16;
+
+    /// Plain Latin hypercube: a single random design.
+    pub fn new() -> Self {
+        Self { designs: 1 }
+    }
+
+    /// Keep the best of `designs` random designs by minimum pairwise distance.
+    pub fn with_maximin(mut self, designs: usize) -> Self {
+        self.designs = designs.max(1);
+        self
+    }
+
+    /// One random Latin hypercube design of `n` points in `dims` dimensions,
+    /// as unit-cube coordinates.
+    fn design<R: Rng>(rng: &mut R, n: usize, dims: usize) -> Vec<Vec<f64>> {
+        let mut points = vec![vec![0.0; dims]; n];
+        for dim in 0..dims {
+            let mut strata: Vec<usize> = (0..n).collect();
+            strata.shuffle(rng);
+            for (point, stratum) in points.iter_mut().zip(strata) {
+                point[dim] = (stratum as f64 + rng.random::<f64>()) / n as f64;
+            }
+        }
+        points
+    }
+
+    /// Smallest squared Euclidean distance between any two points.
+    fn min_sq_distance(points: &[Vec<f64>]) -> f64 {
+        let mut min = f64::INFINITY;
+        for (i, a) in points.iter().enumerate() {
+            for b in &points[i + 1..] {
+                let d: f64 = a.iter().zip(b).map(|(x, y)| (x - y).powi(2)).sum();
+                min = min.min(d);
+            }
+        }
+        min
+    }
+}
+
+impl Probe for LhsProbe {
+    fn sample(&self, config: &SolverConfig) -> Candidates {
+        self.sample_from(config, 0)
+    }
+
+    fn sample_from(&self, config: &SolverConfig, offset: usize) -> Candidates {
+        let mut rng = get_rng_stream(config.seed, offset as u64);
+        let num_samples = config.probe_count();
+
+        // Sort dimension keys for deterministic ordering
+        let mut keys: Vec<_> = config.bounds.keys().cloned().collect();
+        keys.sort();
+
+        let mut best = Self::design(&mut rng, num_samples, keys.len());
+        if self.designs > 1 {
+            let mut best_score = Self::min_sq_distance(&best);
+            for _ in 1..self.designs {
+                let design = Self::design(&mut rng, num_samples, keys.len());
+                let score = Self::min_sq_distance(&design);
+                if score > best_score {
+                    best = design;
+                    best_score = score;
+                }
+            }
+        }
+
+        best.into_iter()
+            .map(|unit| {
+                keys.iter()
+                    .zip(unit)
+                    .map(|(name, unit_pos)| {
+                        (name.clone(), unit_to_domain(unit_pos, &config.bounds[name]))
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+// ============================================================================
+// Composite Probe
+// ============================================================================
+
+/// Probe that splits the probe budget among weighted sub-probes and
+/// concatenates their points, in the order the parts were given.
+///
+/// Each part gets its weight's share of the points, rounded by largest
+/// remainder (ties to the earlier part), so the shares always add up to
+/// `config.probe_count()`. Part `i` samples with its own seed derived from
+/// `config.seed`, so two parts of the same kind do not repeat each other.
+/// `sample_from` maps the offset onto the parts in the order their points
+/// were emitted (whole rounds first, then the parts of a partial round in
+/// order), so each part continues its own sequence.
+pub struct CompositeProbe {
+    parts: Vec<(f64, Box<dyn Probe>)>,
+}
+
+impl CompositeProbe {
+    /// Parts with non-positive or non-finite weights get no points.
+    ///
+    /// # Panics
+    ///
+    /// If no part has a positive, finite weight.
+    pub fn new(parts: Vec<(f64, Box<dyn Probe>)>) -> Self {
+        assert!(
+            parts.iter().any(|(weight, _)| Self::usable(*weight)),
+            "CompositeProbe needs a part with a positive weight"
+        );
+        Self { parts }
+    }
+
+    fn usable(weight: f64) -> bool {
+        weight.is_finite() && weight > 0.0
+    }
+
+    /// Points of `total` allotted to each part.
+    pub fn allocate(&self, total: usize) -> Vec<usize> {
+        let weights: Vec<f64> = self
+            .parts
+            .iter()
+            .map(|(weight, _)| if Self::usable(*weight) { *weight } else { 0.0 })
+            .collect();
+        let sum: f64 = weights.iter().sum();
+        let quotas: Vec<f64> = weights.iter().map(|w| w / sum * total as f64).collect();
+        let mut counts: Vec<usize> = quotas.iter().map(|q| q.floor() as usize).collect();
+        let mut by_remainder: Vec<usize> =
+            (0..quotas.len()).filter(|&i| weights[i] > 0.0).collect();
+        by_remainder.sort_by(|&a, &b| {
+            (quotas[b] - quotas[b].floor())
+                .total_cmp(&(quotas[a] - quotas[a].floor()))
+                .then(a.cmp(&b))
+        });
+        let assigned: usize = counts.iter().sum();
+        for &i in by_remainder
+            .iter()
+            .cycle()
+            .take(total.saturating_sub(assigned))
+        {
+            counts[i] += 1;
+        }
+        counts
+    }
+
+    /// Points of each part among the first `offset` points emitted, for
+    /// rounds of `counts` points per part concatenated in part order.
+    fn part_offsets(counts: &[usize], offset: usize) -> Vec<usize> {
+        let total: usize = counts.iter().sum();
+        if total == 0 {
+            return vec![0; counts.len()];
+        }
+        let (rounds, mut rest) = (offset / total, offset % total);
+        counts
+            .iter()
+            .map(|&count| {
+                let taken = rest.min(count);
+                rest -= taken;
+                rounds * count + taken
+            })
+            .collect()
+    }
+
+    /// `config` with the probe budget set to `count` points and the seed of
+    /// part `index`.
+    fn part_config(config: &SolverConfig, index: usize, count: usize) -> SolverConfig {
+        SolverConfig {
+            seed: derive_seed(
+                derive_seed(config.seed, COMPOSITE_PROBE_STREAM),
+                index as u64,
+            ),
+            budget: count as u64,
+            probe_ratio: ProbeBudget::Fixed(1.0),
+            ..config.clone()
+        }
+    }
+}
+
+impl Probe for CompositeProbe {
+    fn sample(&self, config: &SolverConfig) -> Candidates {
+        self.sample_from(config, 0)
+    }
+
+    fn sample_from(&self, config: &SolverConfig, offset: usize) -> Candidates {
+        let counts = self.allocate(config.probe_count());
+        let offsets = Self::part_offsets(&counts, offset);
+        let mut candidates = Vec::with_capacity(config.probe_count());
+        for (index, ((_, probe), (count, offset))) in self
+            .parts
+            .iter()
+            .zip(counts.into_iter().zip(offsets))
+            .enumerate()
+        {
+            if count == 0 {
+                continue;
+            }
+            let part = Self::part_config(config, index, count);
+            let mut points = probe.sample_from(&part, offset);
+            points.truncate(count);
+            candidates.extend(points);
+        }
+        candidates
+    }
+}
+
+// ============================================================================
+// Probe selection
+// ============================================================================
+
+/// Probe selectable through `strategy_params["probe"]`.
+///
+/// The value is a numeric code because `strategy_params` is a flat
+/// `String -> f64` map:
+///
+/// | code | probe                     |
+/// |------|---------------------------|
+/// | 0    | `UniformProbe`            |
+/// | 1    | `PrimeIndexProbe`         |
+/// | 2    | `PrimeSqrtSlopesRotProbe` |
+/// | 3    | `SobolProbe`              |
+/// | 4    | `LhsProbe`                |
+/// | 5    | `LhsProbe` with maximin   |
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProbeKind {
+    Uniform,
+    PrimeIndex,
+    PrimeSqrtSlopesRot,
+    Sobol,
+    Lhs,
+    LhsMaximin,
+}
+
+impl ProbeKind {
+    /// Key used in `SolverConfig::strategy_params`.
+    pub const PARAM_KEY: &'static str = "probe";
+
+    /// Decode a numeric probe code. Returns None for unknown codes.
+    pub fn from_code(code: f64) -> Option<Self> {
+        match code.round() as i64 {
+            0 => Some(Self::Uniform),
+            1 => Some(Self::PrimeIndex),
+            2 => Some(Self::PrimeSqrtSlopesRot),
+            3 => Some(Self::Sobol),
+            4 => Some(Self::Lhs),
+            5 => Some(Self::LhsMaximin),
+            _ => None,
+        }
+    }
+
+    /// Read the probe selection from the config, if present and valid.
+    pub fn from_config(config: &SolverConfig) -> Option<Self> {
+        config
+            .strategy_param(Self::PARAM_KEY)
+            .and_then(Self::from_code)
+    }
+
+    /// Instantiate the selected probe.
+    pub fn build(self) -> Box<dyn Probe> {
+        match self {
+            Self::Uniform => Box::new(UniformProbe),
+            Self::PrimeIndex => Box::new(PrimeIndexProbe::new()),
+            Self::PrimeSqrtSlopesRot => Box::new(PrimeSqrtSlopesRotProbe::default()),
+            Self::Sobol => Box::new(SobolProbe::new()),
+            Self::Lhs => Box::new(LhsProbe::new()),
+            Self::LhsMaximin => {
+                Box::new(LhsProbe::new().with_maximin(LhsProbe::DEFAULT_MAXIMIN_DESIGNS))
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::Domain;
+    use crate::config::{Domain, ProbeBudget};
 
     fn test_config() -> SolverConfig {
         let mut bounds = HashMap::new();
@@ -553,6 +1087,7 @@ mod tests {
                 min: -5.0,
                 max: 5.0,
                 scale: Scale::Linear,
+                active_when: None,
             },
         );
 
@@ -560,8 +1095,24 @@ mod tests {
             bounds,
             budget: 50,
             seed: 42,
-            probe_ratio: 0.2,
+            probe_ratio: ProbeBudget::Fixed(0.2),
             strategy_params: None,
+            constraints: Vec::new(),
+            selection: Default::default(),
+            cost_budget: None,
+            observation_noise: None,
+            max_restarts: 0,
+            non_finite: Default::default(),
+            repeats: 0,
+            repeat_reducer: Default::default(),
+            objective_transform: None,
+            fixed: Default::default(),
+            target_value: None,
+            target_tol: 0.0,
+            history_cap: None,
+            invalid_params: Default::default(),
+            infeasibility_penalty: 0.0,
+            initial_points: Vec::new(),
         }
     }
 
@@ -610,6 +1161,37 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_prime_index_perturbation_reflects_off_bounds() {
+        let mut config = test_config_multi_dim();
+        config.budget = 600;
+        config.bounds.insert(
+            "lr".to_string(),
+            Domain {
+                min: 1e-5,
+                max: 1e-1,
+                scale: Scale::Log,
+                active_when: None,
+            },
+        );
+        let samples = PrimeIndexProbe::new().sample(&config);
+
+        for (name, domain) in &config.bounds {
+            let values: Vec<f64> = samples.iter().map(|s| s[name]).collect();
+            assert!(values.iter().all(|v| (domain.min..=domain.max).contains(v)));
+            // Points within 1% of an edge exist, but none is clamped onto it.
+            let near_edge = values
+                .iter()
+                .filter(|&&v| domain.to_unit(v) < 0.01 || domain.to_unit(v) > 0.99)
+                .count();
+            assert!(near_edge > 1, "{}: no points near the bounds", name);
+            for bound in [domain.min, domain.max] {
+                let on_bound = values.iter().filter(|&&v| v == bound).count();
+                assert!(on_bound <= 1, "{}: {} points at {}", name, on_bound, bound);
+            }
+        }
+    }
+
     #[test]
     fn test_prime_index_multi_scale_coverage() {
         // Prime ratios should not alias - check that samples are spread across range
@@ -643,6 +1225,7 @@ mod tests {
                 min: 0.0,
                 max: 1.0,
                 scale: Scale::Linear,
+                active_when: None,
             },
         );
         bounds.insert(
@@ -651,6 +1234,7 @@ mod tests {
                 min: 0.0,
                 max: 1.0,
                 scale: Scale::Linear,
+                active_when: None,
             },
         );
 
@@ -658,8 +1242,24 @@ mod tests {
             bounds,
             budget: 256,
             seed: 42,
-            probe_ratio: 1.0, // Use full budget for probe
+            probe_ratio: ProbeBudget::Fixed(1.0), // Use full budget for probe
             strategy_params: None,
+            constraints: Vec::new(),
+            selection: Default::default(),
+            cost_budget: None,
+            observation_noise: None,
+            max_restarts: 0,
+            non_finite: Default::default(),
+            repeats: 0,
+            repeat_reducer: Default::default(),
+            objective_transform: None,
+            fixed: Default::default(),
+            target_value: None,
+            target_tol: 0.0,
+            history_cap: None,
+            invalid_params: Default::default(),
+            infeasibility_penalty: 0.0,
+            initial_points: Vec::new(),
         }
     }
 
@@ -809,6 +1409,7 @@ mod tests {
                 min: 1e-5,
                 max: 1e-1,
                 scale: Scale::Log,
+                active_when: None,
             },
         );
 
@@ -816,8 +1417,24 @@ mod tests {
             bounds,
             budget: 100,
             seed: 42,
-            probe_ratio: 0.5,
+            probe_ratio: ProbeBudget::Fixed(0.5),
             strategy_params: None,
+            constraints: Vec::new(),
+            selection: Default::default(),
+            cost_budget: None,
+            observation_noise: None,
+            max_restarts: 0,
+            non_finite: Default::default(),
+            repeats: 0,
+            repeat_reducer: Default::default(),
+            objective_transform: None,
+            fixed: Default::default(),
+            target_value: None,
+            target_tol: 0.0,
+            history_cap: None,
+            invalid_params: Default::default(),
+            infeasibility_penalty: 0.0,
+            initial_points: Vec::new(),
         };
 
         let probe = PrimeSqrtSlopesRotProbe::new();
@@ -854,6 +1471,7 @@ mod tests {
                 min: 1e-4,
                 max: 1e-1,
                 scale: Scale::Log,
+                active_when: None,
             },
         );
 
@@ -861,8 +1479,24 @@ mod tests {
             bounds,
             budget: 50,
             seed: 42,
-            probe_ratio: 0.2,
+            probe_ratio: ProbeBudget::Fixed(0.2),
             strategy_params: None,
+            constraints: Vec::new(),
+            selection: Default::default(),
+            cost_budget: None,
+            observation_noise: None,
+            max_restarts: 0,
+            non_finite: Default::default(),
+            repeats: 0,
+            repeat_reducer: Default::default(),
+            objective_transform: None,
+            fixed: Default::default(),
+            target_value: None,
+            target_tol: 0.0,
+            history_cap: None,
+            invalid_params: Default::default(),
+            infeasibility_penalty: 0.0,
+            initial_points: Vec::new(),
         };
 
         let probe = UniformProbe;
@@ -969,4 +1603,166 @@ mod tests {
         let primes_one = PrimeIndexProbe::first_n_primes(1);
         assert_eq!(primes_one, vec![2]);
     }
+
+    #[test]
+    fn test_probe_kind_from_code() {
+        assert_eq!(ProbeKind::from_code(0.0), Some(ProbeKind::Uniform));
+        assert_eq!(ProbeKind::from_code(1.0), Some(ProbeKind::PrimeIndex));
+        assert_eq!(
+            ProbeKind::from_code(2.0),
+            Some(ProbeKind::PrimeSqrtSlopesRot)
+        );
+        assert_eq!(ProbeKind::from_code(3.0), Some(ProbeKind::Sobol));
+        assert_eq!(ProbeKind::from_code(4.0), Some(ProbeKind::Lhs));
+        assert_eq!(ProbeKind::from_code(5.0), Some(ProbeKind::LhsMaximin));
+        assert_eq!(ProbeKind::from_code(99.0), None);
+        assert_eq!(ProbeKind::from_code(-1.0), None);
+    }
+
+    #[test]
+    fn test_probe_kind_from_config() {
+        let mut config = test_config();
+        assert_eq!(ProbeKind::from_config(&config), None);
+
+        config.strategy_params = Some([("probe".to_string(), 3.0)].into_iter().collect());
+        assert_eq!(ProbeKind::from_config(&config), Some(ProbeKind::Sobol));
+    }
+
+    #[test]
+    fn test_lhs_probe_one_sample_per_stratum() {
+        let mut config = test_config_multi_dim();
+        config.budget = 40;
+        config.bounds.insert(
+            "lr".to_string(),
+            Domain {
+                min: 1e-4,
+                max: 1e-1,
+                scale: Scale::Log,
+                active_when: None,
+            },
+        );
+        let n = config.probe_count();
+
+        for probe in [LhsProbe::new(), LhsProbe::new().with_maximin(8)] {
+            let samples = probe.sample(&config);
+            assert_eq!(samples.len(), n);
+            assert_eq!(samples, probe.sample(&config));
+
+            for (name, domain) in &config.bounds {
+                let mut strata: Vec<usize> = samples
+                    .iter()
+                    .map(|s| {
+                        let v = s[name];
+                        assert!((domain.min..=domain.max).contains(&v), "{name}={v}");
+                        let unit = match domain.scale {
+                            Scale::Log => {
+                                (v.ln() - domain.min.ln()) / (domain.max.ln() - domain.min.ln())
+                            }
+                            _ => (v - domain.min) / (domain.max - domain.min),
+                        };
+                        ((unit * n as f64) as usize).min(n - 1)
+                    })
+                    .collect();
+                strata.sort_unstable();
+                assert_eq!(strata, (0..n).collect::<Vec<_>>(), "dimension {name}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_lhs_maximin_spreads_points() {
+        let mut config = test_config_multi_dim();
+        config.budget = 16;
+        let unit = |samples: Candidates| -> Vec<Vec<f64>> {
+            samples.iter().map(|s| vec![s["x"], s["y"]]).collect()
+        };
+        let plain = LhsProbe::min_sq_distance(&unit(LhsProbe::new().sample(&config)));
+        let maximin =
+            LhsProbe::min_sq_distance(&unit(LhsProbe::new().with_maximin(16).sample(&config)));
+        // The first maximin candidate is the plain design, so it can only improve.
+        assert!(maximin >= plain, "{maximin} < {plain}");
+    }
+
+    #[test]
+    fn test_composite_probe_allocates_by_weight() {
+        let mut config = test_config_multi_dim();
+        config.budget = 50;
+        config.probe_ratio = ProbeBudget::Fixed(0.2);
+        config.bounds.insert(
+            "lr".to_string(),
+            Domain {
+                min: 1e-4,
+                max: 1e-1,
+                scale: Scale::Log,
+                active_when: None,
+            },
+        );
+        let probe = CompositeProbe::new(vec![
+            (0.3, Box::new(LhsProbe::new())),
+            (0.7, Box::new(UniformProbe)),
+        ]);
+        assert_eq!(probe.allocate(config.probe_count()), vec![3, 7]);
+
+        let samples = probe.sample(&config);
+        assert_eq!(samples.len(), 10);
+        assert_eq!(samples, probe.sample(&config));
+        let lhs = LhsProbe::new().sample(&CompositeProbe::part_config(&config, 0, 3));
+        let uniform = UniformProbe.sample(&CompositeProbe::part_config(&config, 1, 7));
+        assert_eq!(samples[..3], lhs[..]);
+        assert_eq!(samples[3..], uniform[..]);
+
+        // After 4 points (all 3 LHS, 1 uniform) and after a full round plus
+        // 4, each part picks up right after its own points.
+        for (offset, lhs_offset, uniform_offset) in [(4, 3, 1), (14, 6, 8)] {
+            let lhs = LhsProbe::new()
+                .sample_from(&CompositeProbe::part_config(&config, 0, 3), lhs_offset);
+            let uniform = UniformProbe
+                .sample_from(&CompositeProbe::part_config(&config, 1, 7), uniform_offset);
+            let resumed = probe.sample_from(&config, offset);
+            assert_eq!(resumed[..3], lhs[..3]);
+            assert_eq!(resumed[3..], uniform[..7]);
+        }
+        for sample in &samples {
+            for (name, domain) in &config.bounds {
+                let v = sample[name];
+                assert!((domain.min..=domain.max).contains(&v), "{name}={v}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_composite_probe_allocation_sums_to_total() {
+        let probe = CompositeProbe::new(vec![
+            (1.0, Box::new(UniformProbe)),
+            (1.0, Box::new(LhsProbe::new())),
+            (1.0, Box::new(SobolProbe::new())),
+            (0.0, Box::new(UniformProbe)),
+        ]);
+        assert_eq!(probe.allocate(10), vec![4, 3, 3, 0]);
+        for total in 0..40 {
+            assert_eq!(probe.allocate(total).iter().sum::<usize>(), total);
+        }
+    }
+
+    #[test]
+    fn test_sobol_unscrambled_first_dimension_is_van_der_corput() {
+        let dirs = SobolProbe::direction_numbers(0);
+        let scale = 1.0 / (1u64 << 32) as f64;
+        let values: Vec<f64> = (0..4)
+            .map(|i| SobolProbe::sobol_bits(i, &dirs) as f64 * scale)
+            .collect();
+        assert_eq!(values, vec![0.0, 0.5, 0.25, 0.75]);
+    }
+
+    #[test]
+    fn test_owen_scramble_is_bijective_on_prefix() {
+        // Owen scrambling permutes elementary intervals, so the top 8 bits of
+        // 256 stratified points must remain a permutation of 0..256.
+        let mut seen = [false; 256];
+        for i in 0u32..256 {
+            let top = SobolProbe::owen_scramble(i << 24, 0xdead_beef) >> 24;
+            seen[top as usize] = true;
+        }
+        assert!(seen.iter().all(|&s| s));
+    }
 }
@@ -1,9 +1,184 @@
+//! Deterministic random number generation.
+//!
+//! Every random draw in the solver comes from a `ChaCha8Rng`. Subsystems that
+//! need randomness independent of the probe take their own stream via
+//! [`derive_rng`], so adding or reordering draws in one subsystem never
+//! shifts the numbers another one sees.
+//!
+//! # Seed derivation
+//!
+//! `derive_rng(base_seed, stream)` seeds ChaCha8 with
+//! `splitmix64(base_seed ^ stream.rotate_left(32))`, where `splitmix64` is
+//! the finalizer from Steele, Lea and Flood's SplitMix64 (golden-gamma
+//! increment, then two xor-shift-multiply rounds). ChaCha8 expands that `u64`
+//! with `SeedableRng::seed_from_u64`, which rand_core specifies as portable.
+//! Both steps use only wrapping integer arithmetic, so a given
+//! `(base_seed, stream)` produces the same sequence on every platform. The
+//! constants below are part of the reproducibility contract: changing them
+//! changes every derived run.
+//!
+//! # Exceptions
+//!
+//! Two sources of randomness predate `derive_rng` and keep their own
+//! seeding, since moving them would change the samples of every existing
+//! seed:
+//!
+//! - Probes draw from [`get_rng_stream`]`(seed, offset)`: ChaCha8 seeded with
+//!   `seed_from_u64(seed)` on ChaCha stream `offset`, the number of points
+//!   already probed, so a probe continued with `sample_from` does not repeat
+//!   its first batch. Parts of a composite probe get their seeds from
+//!   [`COMPOSITE_PROBE_STREAM`] and then follow the same scheme. The
+//!   multi-scale probe's random points are seeded from its `seed_rotation`.
+//! - SPSA in `arqonhpo-hotpath` does not depend on this crate. It seeds its
+//!   own ChaCha8 with `seed_from_u64(rng_seed)` and persists the generator
+//!   state in its snapshots, so a restored engine continues the same
+//!   sequence.
+
 use rand::SeedableRng;
 use rand_chacha::ChaCha8Rng;
 
+/// Stream for the Chaotic-mode CP shift of the probe.
+pub const CP_SHIFT_STREAM: u64 = 1;
+/// Stream for the CP shift of a Structured-mode fail-safe restart.
+pub const CP_RESTART_STREAM: u64 = 2;
+/// Stream for the random starts of the surrogate minimum search; the derived
+/// seed then goes through [`get_rng_stream`] on the history length.
+pub const SURROGATE_STREAM: u64 = 3;
+/// Stream for the sample of traces kept when history is compacted.
+pub const COMPACTION_STREAM: u64 = 4;
+/// Stream whose derived seeds seed the parts of a composite probe.
+pub const COMPOSITE_PROBE_STREAM: u64 = 5;
+/// Stream for the uniform resamples that replace infeasible probe points.
+pub const RESAMPLE_STREAM: u64 = 6;
+
 /// Returns a deterministic RNG seeded from the given u64.
 ///
 /// We use ChaCha8 as our standard CSPRNG for reproducibility across platforms.
 pub fn get_rng(seed: u64) -> ChaCha8Rng {
     ChaCha8Rng::seed_from_u64(seed)
 }
+
+/// Returns the RNG for `seed` on an independent stream.
+///
+/// Stream 0 is identical to `get_rng(seed)`; other streams give unrelated
+/// sequences, so successive batches drawn from one seed do not repeat.
+/// This is ChaCha's own stream selector, not the [`derive_rng`] mixing; see
+/// the module docs for where it is used.
+pub fn get_rng_stream(seed: u64, stream: u64) -> ChaCha8Rng {
+    let mut rng = get_rng(seed);
+    rng.set_stream(stream);
+    rng
+}
+
+/// SplitMix64 output function applied to `z`.
+pub fn splitmix64(z: u64) -> u64 {
+    let mut z = z.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Seed of `stream` under `base_seed`. See the module docs for the mixing.
+pub fn derive_seed(base_seed: u64, stream: u64) -> u64 {
+    splitmix64(base_seed ^ stream.rotate_left(32))
+}
+
+/// Returns the RNG for subsystem `stream` of a run seeded with `base_seed`.
+pub fn derive_rng(base_seed: u64, stream: u64) -> ChaCha8Rng {
+    ChaCha8Rng::seed_from_u64(derive_seed(base_seed, stream))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::RngCore;
+
+    fn first_outputs(base_seed: u64, stream: u64) -> [u64; 3] {
+        let mut rng = derive_rng(base_seed, stream);
+        [rng.next_u64(), rng.next_u64(), rng.next_u64()]
+    }
+
+    #[test]
+    fn test_splitmix64_reference() {
+        // First outputs of the reference SplitMix64 generator seeded with 0.
+        assert_eq!(splitmix64(0), 0xE220_A839_7B1D_CDAF);
+        assert_eq!(splitmix64(0x9E37_79B9_7F4A_7C15), 0x6E78_9E6A_A1B9_65F4);
+    }
+
+    #[test]
+    fn test_derive_rng_pinned_streams() {
+        assert_eq!(
+            first_outputs(42, 0),
+            [
+                9560017345368447618,
+                14839126499005703793,
+                13358340766203054723
+            ]
+        );
+        assert_eq!(
+            first_outputs(42, CP_SHIFT_STREAM),
+            [
+                13027685259132203035,
+                5844104566480701578,
+                15918911754196449834
+            ]
+        );
+        assert_eq!(
+            first_outputs(42, CP_RESTART_STREAM),
+            [
+                6476751988057222635,
+                13246894320587379260,
+                11062590109592189908
+            ]
+        );
+        assert_eq!(
+            first_outputs(42, SURROGATE_STREAM),
+            [
+                7891106353723032126,
+                7725206733826978519,
+                7982750687910380702
+            ]
+        );
+        assert_eq!(
+            first_outputs(42, COMPACTION_STREAM),
+            [
+                9367039348867243186,
+                2870136914155780746,
+                6596450958994288158
+            ]
+        );
+        assert_eq!(
+            first_outputs(42, COMPOSITE_PROBE_STREAM),
+            [
+                17293934336814422822,
+                1013938435550739543,
+                18017848801467903202
+            ]
+        );
+        assert_eq!(
+            first_outputs(42, RESAMPLE_STREAM),
+            [19293652706586409, 6930144566512462199, 9167412139191954678]
+        );
+    }
+
+    #[test]
+    fn test_get_rng_stream_pinned() {
+        // The probes' scheme (see the module docs), pinned separately.
+        let mut rng = get_rng_stream(42, 1);
+        assert_eq!(
+            [rng.next_u64(), rng.next_u64(), rng.next_u64()],
+            [
+                13222472167927179408,
+                3078952320862533021,
+                8898984633443201687
+            ]
+        );
+    }
+
+    #[test]
+    fn test_derive_rng_streams_are_independent() {
+        assert_ne!(first_outputs(42, 1), first_outputs(42, 2));
+        assert_ne!(first_outputs(42, 1), first_outputs(43, 1));
+        assert_eq!(derive_seed(0, 0), splitmix64(0));
+    }
+}
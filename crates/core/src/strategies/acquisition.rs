@@ -0,0 +1,249 @@
+//! Acquisition Functions
+//!
+//! Score candidate points from a surrogate's predicted mean and variance of
+//! the objective, relative to the best value seen so far (the incumbent). A
+//! surrogate-based strategy pairs any [`SurrogateModel`] with any
+//! [`Acquisition`] and evaluates the candidate that scores highest.
+//!
+//! Objectives are minimized, as everywhere in the solver.
+
+use crate::strategies::cmp_points;
+
+/// A surrogate's prediction of the objective at one point.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Prediction {
+    pub mean: f64,
+    /// Predictive variance; 0 for a model that carries no uncertainty.
+    pub variance: f64,
+}
+
+impl Prediction {
+    /// Standard deviation, with negative variances (rounding in the model)
+    /// read as 0.
+    pub fn std_dev(&self) -> f64 {
+        self.variance.max(0.0).sqrt()
+    }
+}
+
+/// Model of the objective over unit-space points.
+pub trait SurrogateModel {
+    /// Predicted distribution of the objective at `point`.
+    fn posterior(&self, point: &[f64]) -> Prediction;
+}
+
+/// How promising a point is, given its prediction and the incumbent.
+pub trait Acquisition: Send + Sync {
+    /// Score of a point predicted as `prediction` when the best observed
+    /// value is `incumbent`. Higher is more promising.
+    fn score(&self, prediction: Prediction, incumbent: f64) -> f64;
+
+    /// Short name, e.g. `"ei"`.
+    fn name(&self) -> &'static str;
+}
+
+/// Expected amount by which a point improves on the incumbent.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExpectedImprovement {
+    /// Improvement below this margin is not counted; larger values favour
+    /// exploration.
+    pub xi: f64,
+}
+
+impl Default for ExpectedImprovement {
+    fn default() -> Self {
+        Self { xi: 0.0 }
+    }
+}
+
+impl Acquisition for ExpectedImprovement {
+    fn score(&self, prediction: Prediction, incumbent: f64) -> f64 {
+        let improvement = incumbent - self.xi - prediction.mean;
+        let sigma = prediction.std_dev();
+        if sigma == 0.0 {
+            return improvement.max(0.0);
+        }
+        let z = improvement / sigma;
+        improvement * normal_cdf(z) + sigma * normal_pdf(z)
+    }
+
+    fn name(&self) -> &'static str {
+        "ei"
+    }
+}
+
+/// Optimistic bound `mean - kappa * std_dev`; a lower bound scores higher.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LowerConfidenceBound {
+    /// Standard deviations subtracted from the mean; larger values favour
+    /// exploration.
+    pub kappa: f64,
+}
+
+impl Default for LowerConfidenceBound {
+    fn default() -> Self {
+        Self { kappa: 2.0 }
+    }
+}
+
+impl Acquisition for LowerConfidenceBound {
+    fn score(&self, prediction: Prediction, _incumbent: f64) -> f64 {
+        -(prediction.mean - self.kappa * prediction.std_dev())
+    }
+
+    fn name(&self) -> &'static str {
+        "lcb"
+    }
+}
+
+/// Acquisition selected by a numeric code in `strategy_params`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AcquisitionKind {
+    /// [`LowerConfidenceBound`] with its default `kappa`.
+    #[default]
+    Lcb,
+    /// [`ExpectedImprovement`] with its default `xi`.
+    Ei,
+}
+
+impl AcquisitionKind {
+    /// Decode a numeric acquisition code. Returns None for unknown or
+    /// fractional codes.
+    pub fn from_code(code: f64) -> Option<Self> {
+        if code.fract() != 0.0 {
+            return None;
+        }
+        match code as i64 {
+            0 => Some(Self::Lcb),
+            1 => Some(Self::Ei),
+            _ => None,
+        }
+    }
+
+    pub fn build(self) -> Box<dyn Acquisition> {
+        match self {
+            Self::Lcb => Box::new(LowerConfidenceBound::default()),
+            Self::Ei => Box::new(ExpectedImprovement::default()),
+        }
+    }
+}
+
+/// Index of the candidate `acquisition` scores highest under `model`.
+/// Equal scores go to the lexicographically smallest point, so the choice
+/// does not depend on candidate order. `None` if there are no candidates.
+pub fn best_candidate(
+    model: &dyn SurrogateModel,
+    acquisition: &dyn Acquisition,
+    candidates: &[Vec<f64>],
+    incumbent: f64,
+) -> Option<usize> {
+    candidates
+        .iter()
+        .map(|point| acquisition.score(model.posterior(point), incumbent))
+        .enumerate()
+        .max_by(|(i, a), (j, b)| {
+            a.total_cmp(b)
+                .then_with(|| cmp_points(&candidates[*j], &candidates[*i]))
+        })
+        .map(|(i, _)| i)
+}
+
+/// Standard normal density.
+fn normal_pdf(z: f64) -> f64 {
+    (-0.5 * z * z).exp() / (2.0 * std::f64::consts::PI).sqrt()
+}
+
+/// Standard normal CDF via the complementary error function (Abramowitz &
+/// Stegun 7.1.26, absolute error below 1.5e-7).
+fn normal_cdf(z: f64) -> f64 {
+    let x = z.abs() / std::f64::consts::SQRT_2;
+    let t = 1.0 / (1.0 + 0.3275911 * x);
+    let poly = t
+        * (0.254829592
+            + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    let tail = 0.5 * poly * (-x * x).exp();
+    if z >= 0.0 {
+        1.0 - tail
+    } else {
+        tail
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prediction(mean: f64, variance: f64) -> Prediction {
+        Prediction { mean, variance }
+    }
+
+    /// Model that predicts from a fixed table indexed by the first coordinate.
+    struct Table(Vec<Prediction>);
+
+    impl SurrogateModel for Table {
+        fn posterior(&self, point: &[f64]) -> Prediction {
+            self.0[point[0] as usize]
+        }
+    }
+
+    #[test]
+    fn test_normal_cdf_matches_known_values() {
+        assert!((normal_cdf(0.0) - 0.5).abs() < 1e-7);
+        assert!((normal_cdf(1.0) - 0.841344746).abs() < 1e-6);
+        assert!((normal_cdf(-1.96) - 0.024997895).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_ei_zero_at_incumbent_without_variance_and_grows_with_it() {
+        let ei = ExpectedImprovement::default();
+        assert_eq!(ei.score(prediction(1.0, 0.0), 1.0), 0.0);
+
+        let mut last = 0.0;
+        for variance in [0.01, 0.1, 1.0, 10.0] {
+            let score = ei.score(prediction(1.0, variance), 1.0);
+            assert!(score > last, "EI {} at variance {}", score, variance);
+            last = score;
+        }
+        // Without variance, EI is the plain improvement.
+        assert!((ei.score(prediction(0.75, 0.0), 1.0) - 0.25).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_lcb_ranks_low_mean_high_variance_first() {
+        let model = Table(vec![
+            prediction(1.0, 0.01),
+            prediction(0.5, 0.01),
+            prediction(0.5, 1.0),
+            prediction(3.0, 1.0),
+        ]);
+        let candidates: Vec<Vec<f64>> = (0..4).map(|i| vec![i as f64]).collect();
+        let lcb = LowerConfidenceBound::default();
+        assert_eq!(best_candidate(&model, &lcb, &candidates, 1.0), Some(2));
+
+        let mut scores: Vec<(usize, f64)> = candidates
+            .iter()
+            .enumerate()
+            .map(|(i, point)| (i, lcb.score(model.posterior(point), 1.0)))
+            .collect();
+        scores.sort_by(|a, b| b.1.total_cmp(&a.1));
+        let order: Vec<usize> = scores.into_iter().map(|(i, _)| i).collect();
+        assert_eq!(order, vec![2, 1, 0, 3]);
+    }
+
+    #[test]
+    fn test_acquisition_kind_codes() {
+        assert_eq!(AcquisitionKind::from_code(0.0), Some(AcquisitionKind::Lcb));
+        assert_eq!(AcquisitionKind::from_code(1.0), Some(AcquisitionKind::Ei));
+        assert_eq!(AcquisitionKind::from_code(0.5), None);
+        assert_eq!(AcquisitionKind::from_code(2.0), None);
+        assert_eq!(AcquisitionKind::Ei.build().name(), "ei");
+    }
+
+    #[test]
+    fn test_best_candidate_ties_go_to_smallest_point() {
+        let model = Table(vec![prediction(0.5, 0.0); 3]);
+        let candidates = vec![vec![2.0], vec![0.0], vec![1.0]];
+        let ei = ExpectedImprovement::default();
+        assert_eq!(best_candidate(&model, &ei, &candidates, 1.0), Some(1));
+        assert_eq!(best_candidate(&model, &ei, &[], 1.0), None);
+    }
+}
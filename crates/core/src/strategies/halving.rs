@@ -0,0 +1,443 @@
+//! Successive Halving Strategy
+//!
+//! Wraps another strategy: asks it for a wide batch, evaluates the batch at a
+//! low fidelity, keeps the best `1/eta` and re-evaluates the survivors at `eta`
+//! times the fidelity until the last rung runs at full fidelity.
+
+use crate::artifact::{cmp_traces, EvalTrace, FIDELITY_KEY};
+use crate::config::SolverConfig;
+use crate::strategies::{Strategy, StrategyAction};
+use std::collections::HashMap;
+
+/// Fidelities closer than this are treated as the same rung.
+const FIDELITY_TOLERANCE: f64 = 1e-9;
+
+/// Configuration for successive halving
+#[derive(Debug, Clone, PartialEq)]
+pub struct HalvingConfig {
+    /// Reduction factor between rungs (>= 2)
+    pub eta: usize,
+    /// Fidelity of the first rung, in (0, 1]
+    pub min_fidelity: f64,
+    /// Candidates requested from the inner strategy per bracket
+    pub width: usize,
+}
+
+impl HalvingConfig {
+    /// `strategy_params` key that enables halving with the given `eta`.
+    pub const ETA_KEY: &'static str = "halving_eta";
+    pub const MIN_FIDELITY_KEY: &'static str = "halving_min_fidelity";
+    pub const WIDTH_KEY: &'static str = "halving_width";
+    /// Largest `halving_width` accepted, and the limit when the budget is
+    /// unbounded.
+    pub const MAX_WIDTH: usize = 10_000;
+
+    /// Three rungs: fidelities `1/eta²`, `1/eta` and 1, starting from `eta²`
+    /// candidates so one survives to full fidelity.
+    pub fn new(eta: usize) -> Self {
+        let eta = eta.max(2);
+        Self {
+            eta,
+            min_fidelity: 1.0 / (eta * eta) as f64,
+            width: eta * eta,
+        }
+    }
+
+    /// Read `halving_eta`, `halving_min_fidelity` and `halving_width` from
+    /// `strategy_params`. `None` unless `halving_eta` is at least 2; an error
+    /// if the default width, `eta^(rungs - 1)`, does not fit in a `usize`, or
+    /// if `halving_width` is not finite or exceeds the eval budget (or
+    /// [`MAX_WIDTH`](Self::MAX_WIDTH)).
+    pub fn from_config(config: &SolverConfig) -> Result<Option<Self>, String> {
+        let Some(eta) = config.strategy_param(Self::ETA_KEY) else {
+            return Ok(None);
+        };
+        if !eta.is_finite() || eta < 2.0 {
+            return Ok(None);
+        }
+        let eta = eta.round() as usize;
+        let overflow = |rungs: usize| {
+            format!(
+                "strategy_params.{} = {} with {} rungs needs {}^{} candidates per bracket, which overflows",
+                Self::ETA_KEY,
+                eta,
+                rungs,
+                eta,
+                rungs - 1
+            )
+        };
+        if eta.checked_mul(eta).is_none() {
+            return Err(overflow(3));
+        }
+        let mut halving = Self::new(eta);
+        if let Some(min) = config.strategy_param(Self::MIN_FIDELITY_KEY) {
+            if min > 0.0 && min <= 1.0 {
+                halving.min_fidelity = min;
+                let rungs = halving.rungs().len();
+                halving.width = u32::try_from(rungs - 1)
+                    .ok()
+                    .and_then(|exp| eta.checked_pow(exp))
+                    .ok_or_else(|| overflow(rungs))?;
+            }
+        }
+        if let Some(width) = config.strategy_param(Self::WIDTH_KEY) {
+            let limit = match usize::try_from(config.budget) {
+                Ok(budget) if budget > 0 => budget.min(Self::MAX_WIDTH),
+                _ => Self::MAX_WIDTH,
+            };
+            if !width.is_finite() || width.round() > limit as f64 {
+                return Err(format!(
+                    "strategy_params.{} = {} must be a finite number of at most {}",
+                    Self::WIDTH_KEY,
+                    width,
+                    limit
+                ));
+            }
+            if width >= 1.0 {
+                halving.width = width.round() as usize;
+            }
+        }
+        Ok(Some(halving))
+    }
+
+    /// Fidelity of each rung, ending at 1.0.
+    pub fn rungs(&self) -> Vec<f64> {
+        let mut rungs = Vec::new();
+        let mut fidelity = self.min_fidelity;
+        while fidelity < 1.0 - FIDELITY_TOLERANCE {
+            rungs.push(fidelity);
+            fidelity *= self.eta as f64;
+        }
+        rungs.push(1.0);
+        rungs
+    }
+}
+
+/// Successive halving over the candidates of an inner strategy.
+///
+/// Candidates carry their fidelity under [`FIDELITY_KEY`]. The inner strategy
+/// only sees full-fidelity evaluations.
+pub struct Halving {
+    inner: Box<dyn Strategy>,
+    config: HalvingConfig,
+    rungs: Vec<f64>,
+    /// Index into `rungs` of the batch in flight.
+    rung: usize,
+    /// Points of the batch in flight, without the fidelity entry.
+    pending: Vec<HashMap<String, f64>>,
+    /// History length when the batch in flight was issued.
+    issued_at: usize,
+}
+
+impl Halving {
+    pub fn new(inner: Box<dyn Strategy>, config: HalvingConfig) -> Self {
+        let rungs = config.rungs();
+        Self {
+            inner,
+            config,
+            rungs,
+            rung: 0,
+            pending: Vec::new(),
+            issued_at: 0,
+        }
+    }
+
+    /// Collect up to `width` distinct candidates from the inner strategy.
+    ///
+    /// Each candidate is fed back as a pseudo-observation at the worst value
+    /// seen so far, so strategies that only look at history propose
+    /// something new on the next call.
+    fn widen(&mut self, config: &SolverConfig, history: &[EvalTrace]) -> Vec<HashMap<String, f64>> {
+        let mut liars: Vec<EvalTrace> = history
+            .iter()
+            .filter(|t| t.is_full_fidelity())
+            .cloned()
+            .collect();
+        let worst = liars
+            .iter()
+            .map(|t| t.value)
+            .filter(|v| v.is_finite())
+            .fold(f64::NEG_INFINITY, f64::max);
+        let liar_value = if worst.is_finite() { worst } else { 0.0 };
+
+        let mut batch: Vec<HashMap<String, f64>> = Vec::new();
+        for _ in 0..self.config.width.max(1).saturating_mul(2) {
+            if batch.len() >= self.config.width {
+                break;
+            }
+            let StrategyAction::Evaluate(points) = self.inner.step(config, &liars) else {
+                break;
+            };
+            if points.is_empty() {
+                break;
+            }
+            for point in points {
+                if batch.len() >= self.config.width || batch.contains(&point) {
+                    continue;
+                }
+                liars.push(EvalTrace {
+                    eval_id: 0,
+                    params: point.clone(),
+                    value: liar_value,
+                    cost: 0.0,
+                    repeats: None,
+                    penalized: false,
+                    failed: false,
+                    feasibility: None,
+                    penalty: None,
+                    fidelity: None,
+                    kind: None,
+                    source: None,
+                });
+                batch.push(point);
+            }
+        }
+        batch
+    }
+
+    /// Emit `pending` at the current rung's fidelity.
+    fn issue(&mut self, history_len: usize) -> StrategyAction {
+        self.issued_at = history_len;
+        let fidelity = self.rungs[self.rung];
+        StrategyAction::Evaluate(
+            self.pending
+                .iter()
+                .map(|point| {
+                    let mut point = point.clone();
+                    point.insert(FIDELITY_KEY.to_string(), fidelity);
+                    point
+                })
+                .collect(),
+        )
+    }
+}
+
+impl Strategy for Halving {
+    /// The rung in flight, or a whole bracket's first rung between brackets.
+    fn batch_hint(&self) -> usize {
+        if self.pending.is_empty() {
+            self.config.width.max(1)
+        } else {
+            self.pending.len()
+        }
+    }
+
+    fn step(&mut self, config: &SolverConfig, history: &[EvalTrace]) -> StrategyAction {
+        if !self.pending.is_empty() {
+            let fidelity = self.rungs[self.rung];
+            let mut results: Vec<&EvalTrace> = history
+                .get(self.issued_at..)
+                .unwrap_or_default()
+                .iter()
+                .filter(|t| (t.fidelity.unwrap_or(1.0) - fidelity).abs() < FIDELITY_TOLERANCE)
+                .collect();
+            if results.len() < self.pending.len() {
+                return StrategyAction::Wait;
+            }
+            if self.rung + 1 < self.rungs.len() {
+                results.sort_by(|a, b| cmp_traces(a, b));
+                let keep = self.pending.len().div_ceil(self.config.eta).max(1);
+                self.pending = results
+                    .into_iter()
+                    .take(keep)
+                    .map(|t| t.params.clone())
+                    .collect();
+                self.rung += 1;
+                return self.issue(history.len());
+            }
+            // Bracket finished at full fidelity; start the next one.
+            self.pending.clear();
+        }
+
+        let batch = self.widen(config, history);
+        if batch.is_empty() {
+            return StrategyAction::Converged;
+        }
+        self.pending = batch;
+        self.rung = 0;
+        self.issue(history.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Domain, ProbeBudget, Scale};
+
+    /// Proposes the points of a fixed grid one at a time, ignoring history.
+    struct Grid {
+        points: Vec<f64>,
+        next: usize,
+    }
+
+    impl Strategy for Grid {
+        fn step(&mut self, _config: &SolverConfig, _history: &[EvalTrace]) -> StrategyAction {
+            let Some(&x) = self.points.get(self.next) else {
+                return StrategyAction::Converged;
+            };
+            self.next += 1;
+            StrategyAction::Evaluate(vec![HashMap::from([("x".to_string(), x)])])
+        }
+    }
+
+    fn config() -> SolverConfig {
+        let mut bounds = HashMap::new();
+        bounds.insert(
+            "x".to_string(),
+            Domain {
+                min: 0.0,
+                max: 1.0,
+                scale: Scale::Linear,
+                active_when: None,
+            },
+        );
+        SolverConfig {
+            seed: 7,
+            budget: 100,
+            bounds,
+            probe_ratio: ProbeBudget::Fixed(0.2),
+            strategy_params: None,
+            constraints: Vec::new(),
+            selection: Default::default(),
+            cost_budget: None,
+            observation_noise: None,
+            max_restarts: 0,
+            non_finite: Default::default(),
+            repeats: 0,
+            repeat_reducer: Default::default(),
+            objective_transform: None,
+            fixed: Default::default(),
+            target_value: None,
+            target_tol: 0.0,
+            history_cap: None,
+            invalid_params: Default::default(),
+            infeasibility_penalty: 0.0,
+            initial_points: Vec::new(),
+        }
+    }
+
+    /// Quadratic with a bias that vanishes at full fidelity.
+    fn objective(x: f64, fidelity: f64) -> f64 {
+        (x - 0.37).powi(2) + (1.0 - fidelity) * 1e-4 * (37.0 * x).sin()
+    }
+
+    #[test]
+    fn test_rungs() {
+        assert_eq!(HalvingConfig::new(3).rungs().len(), 3);
+        let config = HalvingConfig {
+            eta: 2,
+            min_fidelity: 0.25,
+            width: 4,
+        };
+        assert_eq!(config.rungs(), vec![0.25, 0.5, 1.0]);
+    }
+
+    #[test]
+    fn test_from_config_rejects_overflowing_width() {
+        let mut config = config();
+        let with = |params: &[(&str, f64)]| -> HashMap<String, f64> {
+            params.iter().map(|(k, v)| (k.to_string(), *v)).collect()
+        };
+        config.strategy_params = Some(with(&[
+            (HalvingConfig::ETA_KEY, 3.0),
+            (HalvingConfig::MIN_FIDELITY_KEY, 1.0 / 81.0),
+        ]));
+        assert_eq!(
+            HalvingConfig::from_config(&config).unwrap().unwrap().width,
+            81
+        );
+
+        for params in [
+            with(&[
+                (HalvingConfig::ETA_KEY, 10.0),
+                (HalvingConfig::MIN_FIDELITY_KEY, 1e-300),
+            ]),
+            with(&[(HalvingConfig::ETA_KEY, 1e300)]),
+        ] {
+            config.strategy_params = Some(params);
+            let err = HalvingConfig::from_config(&config).unwrap_err();
+            assert!(err.contains("overflows"), "{}", err);
+        }
+    }
+
+    #[test]
+    fn test_from_config_rejects_unbounded_width() {
+        let mut config = config();
+        for width in [f64::INFINITY, f64::NAN, 1e30, 101.0] {
+            config.strategy_params = Some(HashMap::from([
+                (HalvingConfig::ETA_KEY.to_string(), 3.0),
+                (HalvingConfig::WIDTH_KEY.to_string(), width),
+            ]));
+            let err = HalvingConfig::from_config(&config).unwrap_err();
+            assert!(err.contains("at most 100"), "{}", err);
+        }
+        config.strategy_params = Some(HashMap::from([
+            (HalvingConfig::ETA_KEY.to_string(), 3.0),
+            (HalvingConfig::WIDTH_KEY.to_string(), 100.0),
+        ]));
+        assert_eq!(
+            HalvingConfig::from_config(&config).unwrap().unwrap().width,
+            100
+        );
+
+        config.budget = 0;
+        config.strategy_params = Some(HashMap::from([
+            (HalvingConfig::ETA_KEY.to_string(), 3.0),
+            (HalvingConfig::WIDTH_KEY.to_string(), 1e6),
+        ]));
+        let err = HalvingConfig::from_config(&config).unwrap_err();
+        assert!(err.contains("at most 10000"), "{}", err);
+    }
+
+    #[test]
+    fn test_halving_finds_best_with_fewer_full_evals() {
+        let grid: Vec<f64> = (0..27).map(|i| i as f64 / 26.0).collect();
+        let brute_force = grid
+            .iter()
+            .copied()
+            .min_by(|a, b| objective(*a, 1.0).total_cmp(&objective(*b, 1.0)))
+            .unwrap();
+
+        let config = config();
+        let mut halving = Halving::new(
+            Box::new(Grid {
+                points: grid.clone(),
+                next: 0,
+            }),
+            HalvingConfig::new(3),
+        );
+        assert_eq!(halving.batch_hint(), 9);
+        let mut history: Vec<EvalTrace> = Vec::new();
+        let mut full_evals = 0;
+        while let StrategyAction::Evaluate(points) = halving.step(&config, &history) {
+            assert_eq!(halving.batch_hint(), points.len());
+            for mut params in points {
+                let fidelity = params.remove(FIDELITY_KEY).unwrap();
+                full_evals += usize::from(fidelity >= 1.0);
+                history.push(EvalTrace {
+                    eval_id: history.len() as u64,
+                    params: params.clone(),
+                    value: objective(params["x"], fidelity),
+                    cost: fidelity,
+                    repeats: None,
+                    penalized: false,
+                    failed: false,
+                    feasibility: None,
+                    penalty: None,
+                    fidelity: Some(fidelity),
+                    kind: None,
+                    source: None,
+                });
+            }
+        }
+
+        let best = history
+            .iter()
+            .filter(|t| t.is_full_fidelity())
+            .min_by(|a, b| a.value.total_cmp(&b.value))
+            .unwrap();
+        assert_eq!(best.params["x"], brute_force);
+        assert!(full_evals < grid.len(), "{} full evals", full_evals);
+        assert_eq!(history.len(), 27 + 9 + 3);
+    }
+}
@@ -1,9 +1,14 @@
 use crate::artifact::EvalTrace;
+use crate::classify::Landscape;
 use crate::config::SolverConfig;
+use hotpath::config_atomic::{ParamId, ParamRegistry, ParamVec};
 use std::collections::HashMap;
 
+pub mod acquisition;
+pub mod halving;
 pub mod multi_start_nm;
 pub mod nelder_mead;
+pub mod surrogate;
 pub mod tpe;
 
 /// Result of a strategy step.
@@ -13,7 +18,280 @@ pub enum StrategyAction {
     Converged,                           // Strategy decided to stop
 }
 
+/// [`StrategyAction`] with candidates in [`ParamRegistry`] order.
+pub enum DenseAction {
+    Evaluate(Vec<ParamVec>),
+    Wait,
+    Converged,
+}
+
+impl DenseAction {
+    /// Convert the candidates to named parameters (see [`named_point`]).
+    pub fn into_action(self, registry: &ParamRegistry) -> StrategyAction {
+        match self {
+            Self::Evaluate(points) => StrategyAction::Evaluate(
+                points
+                    .iter()
+                    .map(|point| named_point(registry, point))
+                    .collect(),
+            ),
+            Self::Wait => StrategyAction::Wait,
+            Self::Converged => StrategyAction::Converged,
+        }
+    }
+}
+
+/// History handed to [`Strategy::step_dense`].
+///
+/// `points[i]` holds the parameters of `traces[i]` in registry order (see
+/// [`dense_point`]). The solver converts each trace once, so a strategy can
+/// read coordinates without hashing names on every step.
+pub struct DenseHistory<'a> {
+    pub registry: &'a ParamRegistry,
+    pub traces: &'a [EvalTrace],
+    pub points: &'a [ParamVec],
+}
+
+/// Registry over the sorted `bounds` keys, the order strategies use for
+/// coordinates.
+pub fn param_registry(config: &SolverConfig) -> ParamRegistry {
+    let mut keys: Vec<&String> = config.bounds.keys().collect();
+    keys.sort();
+    ParamRegistry::new(keys.into_iter().cloned())
+}
+
+/// `params` in registry order. Parameters the point lacks (inactive
+/// conditional parameters) are NaN.
+pub fn dense_point(registry: &ParamRegistry, params: &HashMap<String, f64>) -> ParamVec {
+    (0..registry.len())
+        .map(|id| {
+            registry
+                .get_name(id as ParamId)
+                .and_then(|name| params.get(name))
+                .copied()
+                .unwrap_or(f64::NAN)
+        })
+        .collect()
+}
+
+/// Inverse of [`dense_point`]; NaN entries are left out.
+pub fn named_point(registry: &ParamRegistry, point: &[f64]) -> HashMap<String, f64> {
+    point
+        .iter()
+        .enumerate()
+        .filter(|(_, value)| !value.is_nan())
+        .filter_map(|(id, &value)| {
+            registry
+                .get_name(id as ParamId)
+                .map(|name| (name.to_string(), value))
+        })
+        .collect()
+}
+
+/// Lexicographic order of two coordinate vectors by `f64::total_cmp`, used
+/// to break ties between vertices or candidates of equal value (see
+/// [`cmp_traces`](crate::artifact::cmp_traces)).
+pub fn cmp_points(a: &[f64], b: &[f64]) -> std::cmp::Ordering {
+    a.iter()
+        .zip(b)
+        .map(|(x, y)| x.total_cmp(y))
+        .find(|order| order.is_ne())
+        .unwrap_or_else(|| a.len().cmp(&b.len()))
+}
+
 pub trait Strategy: Send + Sync {
     /// Generate next candidates based on history.
     fn step(&mut self, config: &SolverConfig, history: &[EvalTrace]) -> StrategyAction;
+
+    /// Dense counterpart of [`step`](Self::step), which the solver prefers
+    /// when a strategy implements it: the same decision, made on points
+    /// already in registry order. `None` means the strategy only works on
+    /// named parameters.
+    fn step_dense(
+        &mut self,
+        _config: &SolverConfig,
+        _history: &DenseHistory<'_>,
+    ) -> Option<DenseAction> {
+        None
+    }
+
+    /// Number of candidates the strategy can usefully have in flight at once.
+    /// Strategies that need each result before proposing the next point
+    /// report 1.
+    fn batch_hint(&self) -> usize {
+        1
+    }
+
+    /// Short type name of the strategy, e.g. `"NelderMead"`.
+    fn name(&self) -> &'static str {
+        let path = std::any::type_name::<Self>();
+        path.rsplit("::").next().unwrap_or(path)
+    }
+}
+
+/// Refine strategy forced via `strategy_params["force"]`, overriding the
+/// classifier's choice.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StrategyKind {
+    NelderMead,
+    Tpe,
+}
+
+impl StrategyKind {
+    /// Key used in `SolverConfig::strategy_params`.
+    pub const PARAM_KEY: &'static str = "force";
+
+    /// Decode a numeric strategy code: 1 = Nelder-Mead, 2 = TPE. Returns
+    /// None for unknown codes.
+    pub fn from_code(code: f64) -> Option<Self> {
+        if code.fract() != 0.0 {
+            return None;
+        }
+        match code as i64 {
+            1 => Some(Self::NelderMead),
+            2 => Some(Self::Tpe),
+            _ => None,
+        }
+    }
+
+    /// Numeric code accepted by `from_code`.
+    pub fn code(self) -> f64 {
+        match self {
+            Self::NelderMead => 1.0,
+            Self::Tpe => 2.0,
+        }
+    }
+
+    /// Read the forced strategy from the config, if present and valid.
+    pub fn from_config(config: &SolverConfig) -> Option<Self> {
+        config
+            .strategy_param(Self::PARAM_KEY)
+            .and_then(Self::from_code)
+    }
+
+    /// Reject a `force` entry that names no known strategy.
+    pub fn check(config: &SolverConfig) -> Result<(), String> {
+        match config.strategy_param(Self::PARAM_KEY) {
+            Some(code) if Self::from_code(code).is_none() => Err(format!(
+                "strategy_params.{} must be 1 (nelder_mead) or 2 (tpe), got {}",
+                Self::PARAM_KEY,
+                code
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Landscape whose refine setup (strategy and probe settings) this uses.
+    pub fn landscape(self) -> Landscape {
+        match self {
+            Self::NelderMead => Landscape::Structured,
+            Self::Tpe => Landscape::Chaotic,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::NelderMead => "nelder_mead",
+            Self::Tpe => "tpe",
+        }
+    }
+}
+
+/// Builds a refine strategy for a `dim`-dimensional search space.
+pub type StrategyFactory = Box<dyn Fn(usize, &SolverConfig) -> Box<dyn Strategy> + Send + Sync>;
+
+/// Periodicity of each parameter in sorted `bounds` key order, as
+/// [`NelderMead::new`](nelder_mead::NelderMead::new) expects it.
+pub fn periodic_mask(config: &SolverConfig) -> Vec<bool> {
+    let mut keys: Vec<&String> = config.bounds.keys().collect();
+    keys.sort();
+    keys.iter()
+        .map(|k| config.bounds[*k].is_periodic())
+        .collect()
+}
+
+/// Refine strategy factories by name, and the name each landscape refines
+/// with.
+///
+/// Registering a factory under a built-in name replaces that strategy;
+/// other names are used once a landscape is assigned to them.
+pub struct StrategyRegistry {
+    factories: HashMap<String, StrategyFactory>,
+    by_landscape: HashMap<Landscape, String>,
+}
+
+impl StrategyRegistry {
+    pub const NELDER_MEAD: &'static str = "nelder_mead";
+    pub const TPE: &'static str = "tpe";
+
+    /// Nelder-Mead for Structured landscapes; TPE for Chaotic ones, under
+    /// successive halving when `halving_eta` is configured.
+    pub fn builtin() -> Self {
+        let mut registry = Self {
+            factories: HashMap::new(),
+            by_landscape: HashMap::from([
+                (Landscape::Structured, Self::NELDER_MEAD.to_string()),
+                (Landscape::Chaotic, Self::TPE.to_string()),
+            ]),
+        };
+        registry.register(
+            Self::NELDER_MEAD,
+            Box::new(|dim, config| {
+                Box::new(nelder_mead::NelderMead::new(dim, periodic_mask(config)))
+            }),
+        );
+        registry.register(
+            Self::TPE,
+            Box::new(|dim, config| {
+                let tpe: Box<dyn Strategy> = Box::new(tpe::TPE::from_config(dim, config));
+                match halving::HalvingConfig::from_config(config) {
+                    Ok(Some(halving)) => Box::new(halving::Halving::new(tpe, halving)),
+                    Ok(None) => tpe,
+                    Err(err) => {
+                        tracing::warn!("{}; refining without successive halving", err);
+                        tpe
+                    }
+                }
+            }),
+        );
+        registry
+    }
+
+    /// Add `factory` under `name`, replacing any factory of that name.
+    pub fn register(&mut self, name: impl Into<String>, factory: StrategyFactory) {
+        self.factories.insert(name.into(), factory);
+    }
+
+    /// Refine `landscape` with the strategy registered as `name`.
+    pub fn assign(&mut self, landscape: Landscape, name: impl Into<String>) {
+        self.by_landscape.insert(landscape, name.into());
+    }
+
+    /// Name of the strategy `landscape` refines with.
+    pub fn name_for(&self, landscape: Landscape) -> &str {
+        &self.by_landscape[&landscape]
+    }
+
+    /// Whether a factory is registered as `name`.
+    pub fn contains(&self, name: &str) -> bool {
+        self.factories.contains_key(name)
+    }
+
+    /// Build the strategy for `landscape`. `None` if it is assigned to a name
+    /// with no registered factory.
+    pub fn build(
+        &self,
+        landscape: Landscape,
+        dim: usize,
+        config: &SolverConfig,
+    ) -> Option<Box<dyn Strategy>> {
+        let factory = self.factories.get(self.name_for(landscape))?;
+        Some(factory(dim, config))
+    }
+}
+
+impl Default for StrategyRegistry {
+    fn default() -> Self {
+        Self::builtin()
+    }
 }
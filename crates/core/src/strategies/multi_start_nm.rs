@@ -2,7 +2,7 @@
 //!
 //! Runs K parallel NM instances from diverse seed points to avoid local minima.
 
-use crate::artifact::EvalTrace;
+use crate::artifact::{cmp_traces, EvalTrace};
 use crate::config::{Scale, SolverConfig};
 use crate::strategies::nelder_mead::NelderMead;
 use crate::strategies::{Strategy, StrategyAction};
@@ -177,9 +177,7 @@ impl MultiStartNM {
         history: &[EvalTrace],
     ) -> StrategyAction {
         // Find best point
-        let best_trace = history
-            .iter()
-            .min_by(|a, b| a.value.partial_cmp(&b.value).unwrap());
+        let best_trace = history.iter().min_by(|a, b| cmp_traces(a, b));
 
         if let Some(best) = best_trace {
             let mut candidates = Vec::new();
@@ -235,6 +233,11 @@ impl MultiStartNM {
 }
 
 impl Strategy for MultiStartNM {
+    /// Starts run one at a time; each behaves like a single Nelder-Mead.
+    fn batch_hint(&self) -> usize {
+        1
+    }
+
     fn step(&mut self, config: &SolverConfig, history: &[EvalTrace]) -> StrategyAction {
         // Update tracking if we have new history
         if let Some(last) = history.last() {
@@ -274,7 +277,7 @@ impl Strategy for MultiStartNM {
                                 .best_per_start
                                 .iter()
                                 .enumerate()
-                                .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
+                                .min_by(|(_, a), (_, b)| a.total_cmp(b))
                                 .map(|(i, _)| i)
                                 .unwrap_or(0);
 
@@ -319,6 +322,7 @@ impl Strategy for MultiStartNM {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::ProbeBudget;
 
     #[test]
     fn test_multi_start_creation() {
@@ -455,6 +459,7 @@ mod tests {
                 min: 0.0,
                 max: 1.0,
                 scale: Scale::Linear,
+                active_when: None,
             },
         );
         bounds.insert(
@@ -463,14 +468,31 @@ mod tests {
                 min: 0.0,
                 max: 1.0,
                 scale: Scale::Linear,
+                active_when: None,
             },
         );
         SolverConfig {
             bounds,
             budget: 50,
-            probe_ratio: 0.2,
+            probe_ratio: ProbeBudget::Fixed(0.2),
             seed: 42,
             strategy_params: None,
+            constraints: Vec::new(),
+            selection: Default::default(),
+            cost_budget: None,
+            observation_noise: None,
+            max_restarts: 0,
+            non_finite: Default::default(),
+            repeats: 0,
+            repeat_reducer: Default::default(),
+            objective_transform: None,
+            fixed: Default::default(),
+            target_value: None,
+            target_tol: 0.0,
+            history_cap: None,
+            invalid_params: Default::default(),
+            infeasibility_penalty: 0.0,
+            initial_points: Vec::new(),
         }
     }
 
@@ -497,6 +519,14 @@ mod tests {
                     .collect(),
                 value: 0.1, // Best
                 cost: 1.0,
+                repeats: None,
+                penalized: false,
+                failed: false,
+                feasibility: None,
+                penalty: None,
+                fidelity: None,
+                kind: None,
+                source: None,
             },
             EvalTrace {
                 eval_id: 2,
@@ -505,6 +535,14 @@ mod tests {
                     .collect(),
                 value: 0.5,
                 cost: 1.0,
+                repeats: None,
+                penalized: false,
+                failed: false,
+                feasibility: None,
+                penalty: None,
+                fidelity: None,
+                kind: None,
+                source: None,
             },
         ];
 
@@ -553,6 +591,14 @@ mod tests {
                 .collect(),
             value: 1.0,
             cost: 1.0,
+            repeats: None,
+            penalized: false,
+            failed: false,
+            feasibility: None,
+            penalty: None,
+            fidelity: None,
+            kind: None,
+            source: None,
         }];
 
         // First step should be CoordinateDescent
@@ -593,6 +639,14 @@ mod tests {
                     .collect(),
                 value: (i as f64 - 2.0).powi(2),
                 cost: 1.0,
+                repeats: None,
+                penalized: false,
+                failed: false,
+                feasibility: None,
+                penalty: None,
+                fidelity: None,
+                kind: None,
+                source: None,
             })
             .collect();
 
@@ -639,6 +693,14 @@ mod tests {
                     .collect(),
                 value: i as f64,
                 cost: 1.0,
+                repeats: None,
+                penalized: false,
+                failed: false,
+                feasibility: None,
+                penalty: None,
+                fidelity: None,
+                kind: None,
+                source: None,
             })
             .collect();
 
@@ -653,6 +715,14 @@ mod tests {
                     .collect(),
                 value: 0.5,
                 cost: 1.0,
+                repeats: None,
+                penalized: false,
+                failed: false,
+                feasibility: None,
+                penalty: None,
+                fidelity: None,
+                kind: None,
+                source: None,
             });
         }
 
@@ -1,6 +1,7 @@
 use crate::artifact::EvalTrace;
-use crate::config::{circular_mean01, diff01, dist01, wrap01, SolverConfig};
-use crate::strategies::{Strategy, StrategyAction};
+use crate::config::{circular_mean01, diff01, dist01, wrap01, Domain, Scale, SolverConfig};
+use crate::strategies::{cmp_points, DenseAction, DenseHistory, Strategy, StrategyAction};
+use hotpath::config_atomic::{ParamId, ParamVec};
 use std::collections::HashMap;
 
 /// Standard Nelder-Mead coefficients per spec clarification (2025-12-14)
@@ -94,6 +95,79 @@ enum NMState {
     Converged,
 }
 
+/// Outcome of one state-machine step, with candidates in simplex coordinates.
+enum NMStep {
+    Evaluate(Vec<Vec<f64>>),
+    Wait,
+    Converged,
+}
+
+/// Margin applied to Nelder-Mead comparisons on the latest evaluation.
+///
+/// With `observation_noise` set, a move only counts as an improvement when it
+/// beats the reference by more than the noise of the new observation, and a
+/// contraction is only rejected when it is worse by more than that. Without
+/// noise the margin is zero and comparisons are exact.
+fn noise_margin(config: &SolverConfig, history: &[EvalTrace]) -> f64 {
+    match (config.observation_noise, history.last()) {
+        (Some(sigma), Some(last)) if sigma.is_finite() && sigma > 0.0 => last.noise_std(sigma),
+        _ => 0.0,
+    }
+}
+
+/// Default step along each dimension for the initial simplex, as a fraction
+/// of the dimension's range.
+pub const DEFAULT_SIMPLEX_SCALE: f64 = 0.05;
+
+/// `strategy_params` key for the initial simplex step of every dimension.
+/// `nm_simplex_scale.<param>` overrides it for one parameter.
+pub const SIMPLEX_SCALE_KEY: &str = "nm_simplex_scale";
+
+/// Initial simplex step for each of `keys`, as a fraction of its range:
+/// `nm_simplex_scale.<key>`, else `nm_simplex_scale`, else
+/// `DEFAULT_SIMPLEX_SCALE`. Steps outside (0, 1] are ignored; see
+/// [`NelderMead::check`].
+pub fn simplex_scales(config: &SolverConfig, keys: &[String]) -> Vec<f64> {
+    let valid = |scale: &f64| scale.is_finite() && *scale > 0.0 && *scale <= 1.0;
+    let global = config
+        .strategy_param(SIMPLEX_SCALE_KEY)
+        .filter(valid)
+        .unwrap_or(DEFAULT_SIMPLEX_SCALE);
+    keys.iter()
+        .map(|key| {
+            config
+                .strategy_param(&format!("{}.{}", SIMPLEX_SCALE_KEY, key))
+                .filter(valid)
+                .unwrap_or(global)
+        })
+        .collect()
+}
+
+/// Default `x_tol`: simplex diameter, as a fraction of each dimension's range.
+pub const DEFAULT_X_TOL: f64 = 1e-8;
+/// Default `f_tol`: relative spread of objective values across the simplex.
+pub const DEFAULT_F_TOL: f64 = 1e-10;
+
+/// `strategy_params` keys overriding `x_tol` and `f_tol`.
+pub const X_TOL_KEY: &str = "nm_x_tol";
+pub const F_TOL_KEY: &str = "nm_f_tol";
+
+/// Keeps the relative objective spread finite when the best value is 0.
+const F_TOL_EPS: f64 = 1e-12;
+
+/// Default `collapse_tol`: range-normalized diameter below which a simplex
+/// that has not converged on its values is considered degenerate. Kept below
+/// `DEFAULT_X_TOL`, so a simplex shrinking onto an optimum converges before
+/// it can count as collapsed; it only comes into play when `x_tol` is lowered
+/// beneath it.
+pub const DEFAULT_COLLAPSE_TOL: f64 = 1e-10;
+/// Default `max_reinits`.
+pub const DEFAULT_MAX_REINITS: usize = 3;
+
+/// `strategy_params` keys overriding `collapse_tol` and `max_reinits`.
+pub const COLLAPSE_TOL_KEY: &str = "nm_collapse_tol";
+pub const MAX_REINITS_KEY: &str = "nm_max_reinits";
+
 pub struct NelderMead {
     dim: usize,
     state: NMState,
@@ -101,25 +175,98 @@ pub struct NelderMead {
     pub(crate) simplex: Vec<(f64, Vec<f64>)>,
     /// Coefficients for NM operations
     coeffs: NMCoefficients,
-    /// Convergence tolerance (simplex diameter)
+    /// Converged once the simplex diameter, normalized by each dimension's
+    /// bound range, is below this.
+    pub x_tol: f64,
+    /// Converged once `(f_worst - f_best) / (|f_best| + eps)` is below this.
+    pub f_tol: f64,
+    /// Converged once the simplex diameter, in simplex coordinates, is below
+    /// this. Zero (the default) leaves convergence to `x_tol` and `f_tol`.
+    #[deprecated(note = "use the range-relative `x_tol` instead")]
     pub tolerance: f64,
+    /// A simplex whose normalized diameter drops below this while its values
+    /// are still spread out has collapsed; it is re-expanded around its best
+    /// vertex with the initial simplex scales.
+    pub collapse_tol: f64,
+    /// Re-expansions allowed before a collapsed simplex counts as converged.
+    pub max_reinits: usize,
+    /// Re-expansions done so far.
+    reinits: usize,
     /// Mask for periodic dimensions (true = periodic, false = linear)
     pub periodic_mask: Vec<bool>,
+    /// Bounds of log-scaled dimensions (None = linear), in sorted key order.
+    /// Log dimensions are handled as their position in [0, 1] of log space.
+    log_bounds: Vec<Option<(f64, f64)>>,
+    /// Bounds of periodic dimensions, in sorted key order. Periodic
+    /// dimensions are handled as their position in [0, 1), so the wrap-around
+    /// arithmetic works for any period.
+    periodic_bounds: Vec<Option<(f64, f64)>>,
+    /// Simplex coordinate used for a parameter missing from a point (an
+    /// inactive conditional parameter): the middle of its domain.
+    defaults: Vec<f64>,
+    /// Extent of each dimension in simplex coordinates: the bounds of linear
+    /// dimensions, [0, 1] for log and periodic ones.
+    extents: Vec<(f64, f64)>,
+    /// Step along each dimension when building the initial simplex, in
+    /// sorted key order.
+    simplex_scales: Vec<f64>,
+    /// Parameter names in registry order, set on the first dense step.
+    dense_keys: Vec<String>,
 }
 
 impl NelderMead {
+    /// Reject an `nm_simplex_scale` or `nm_simplex_scale.<param>` outside
+    /// (0, 1], which [`simplex_scales`] would ignore.
+    pub fn check(config: &SolverConfig) -> Result<(), String> {
+        let mut keys: Vec<&String> = config
+            .strategy_params
+            .iter()
+            .flat_map(|params| params.keys())
+            .filter(|key| {
+                key.as_str() == SIMPLEX_SCALE_KEY
+                    || key
+                        .strip_prefix(SIMPLEX_SCALE_KEY)
+                        .is_some_and(|rest| rest.starts_with('.'))
+            })
+            .collect();
+        keys.sort();
+        for key in keys {
+            let scale = config.strategy_param(key).unwrap_or(f64::NAN);
+            if !(scale > 0.0 && scale <= 1.0) {
+                return Err(format!(
+                    "strategy_params.{} must be in (0, 1], got {}",
+                    key, scale
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    #[allow(deprecated)]
     pub fn new(dim: usize, periodic_mask: Vec<bool>) -> Self {
         Self {
             dim,
             state: NMState::Init,
             simplex: Vec::new(),
             coeffs: NMCoefficients::default(),
-            tolerance: 1e-8,
+            x_tol: DEFAULT_X_TOL,
+            f_tol: DEFAULT_F_TOL,
+            tolerance: 0.0,
+            collapse_tol: DEFAULT_COLLAPSE_TOL,
+            max_reinits: DEFAULT_MAX_REINITS,
+            reinits: 0,
             periodic_mask,
+            log_bounds: Vec::new(),
+            periodic_bounds: Vec::new(),
+            defaults: Vec::new(),
+            extents: Vec::new(),
+            simplex_scales: Vec::new(),
+            dense_keys: Vec::new(),
         }
     }
 
     /// Create NM with seed points from probe results (Top-K seeding)
+    #[allow(deprecated)]
     pub fn with_seed_points(
         dim: usize,
         seeds: Vec<(f64, Vec<f64>)>,
@@ -130,37 +277,196 @@ impl NelderMead {
             state: NMState::Init,
             simplex: seeds,
             coeffs: NMCoefficients::default(),
-            tolerance: 1e-8,
+            x_tol: DEFAULT_X_TOL,
+            f_tol: DEFAULT_F_TOL,
+            tolerance: 0.0,
+            collapse_tol: DEFAULT_COLLAPSE_TOL,
+            max_reinits: DEFAULT_MAX_REINITS,
+            reinits: 0,
             periodic_mask,
+            log_bounds: Vec::new(),
+            periodic_bounds: Vec::new(),
+            defaults: Vec::new(),
+            extents: Vec::new(),
+            simplex_scales: Vec::new(),
+            dense_keys: Vec::new(),
         }
     }
 
     /// Create NM with custom coefficients
+    #[allow(deprecated)]
     pub fn with_coefficients(dim: usize, coeffs: NMCoefficients, periodic_mask: Vec<bool>) -> Self {
         Self {
             dim,
             state: NMState::Init,
             simplex: Vec::new(),
             coeffs,
-            tolerance: 1e-8,
+            x_tol: DEFAULT_X_TOL,
+            f_tol: DEFAULT_F_TOL,
+            tolerance: 0.0,
+            collapse_tol: DEFAULT_COLLAPSE_TOL,
+            max_reinits: DEFAULT_MAX_REINITS,
+            reinits: 0,
             periodic_mask,
+            log_bounds: Vec::new(),
+            periodic_bounds: Vec::new(),
+            defaults: Vec::new(),
+            extents: Vec::new(),
+            simplex_scales: Vec::new(),
+            dense_keys: Vec::new(),
         }
     }
 
-    fn dict_to_vec(&self, params: &HashMap<String, f64>, keys: &[String]) -> Vec<f64> {
-        keys.iter()
-            .map(|k| *params.get(k).unwrap_or(&0.0))
+    /// Record per-dimension scales and defaults, in sorted key order.
+    fn update_dims(&mut self, config: &SolverConfig, keys: &[String]) {
+        self.log_bounds = keys
+            .iter()
+            .map(|k| {
+                config
+                    .bounds
+                    .get(k)
+                    .filter(|d| matches!(d.scale, Scale::Log))
+                    .map(|d| (d.min, d.max))
+            })
+            .collect();
+        self.periodic_bounds = keys
+            .iter()
+            .map(|k| {
+                config
+                    .bounds
+                    .get(k)
+                    .filter(|d| d.is_periodic())
+                    .map(|d| (d.min, d.max))
+            })
+            .collect();
+        self.periodic_mask = self.periodic_bounds.iter().map(Option::is_some).collect();
+        self.simplex_scales = simplex_scales(config, keys);
+        self.extents = keys
+            .iter()
+            .map(|k| match config.bounds.get(k) {
+                Some(d) if matches!(d.scale, Scale::Linear) && d.max > d.min => (d.min, d.max),
+                _ => (0.0, 1.0),
+            })
+            .collect();
+        let valid = |tol: &f64| tol.is_finite() && *tol >= 0.0;
+        if let Some(x_tol) = config.strategy_param(X_TOL_KEY).filter(valid) {
+            self.x_tol = x_tol;
+        }
+        if let Some(f_tol) = config.strategy_param(F_TOL_KEY).filter(valid) {
+            self.f_tol = f_tol;
+        }
+        if let Some(collapse_tol) = config.strategy_param(COLLAPSE_TOL_KEY).filter(valid) {
+            self.collapse_tol = collapse_tol;
+        }
+        if let Some(max_reinits) = config.strategy_param(MAX_REINITS_KEY).filter(valid) {
+            self.max_reinits = max_reinits.round() as usize;
+        }
+        self.defaults = keys
+            .iter()
+            .map(|k| match config.bounds.get(k) {
+                Some(d) if matches!(d.scale, Scale::Log | Scale::Periodic) => 0.5,
+                Some(d) => (d.min + d.max) / 2.0,
+                None => 0.0,
+            })
+            .collect();
+    }
+
+    fn log_bounds(&self, dim_idx: usize) -> Option<(f64, f64)> {
+        self.log_bounds.get(dim_idx).copied().flatten()
+    }
+
+    fn periodic_bounds(&self, dim_idx: usize) -> Option<(f64, f64)> {
+        self.periodic_bounds.get(dim_idx).copied().flatten()
+    }
+
+    fn is_periodic(&self, dim_idx: usize) -> bool {
+        self.periodic_mask.get(dim_idx).copied().unwrap_or(false)
+    }
+
+    fn extent(&self, dim_idx: usize) -> (f64, f64) {
+        self.extents.get(dim_idx).copied().unwrap_or((0.0, 1.0))
+    }
+
+    fn range(&self, dim_idx: usize) -> f64 {
+        let (lo, hi) = self.extent(dim_idx);
+        hi - lo
+    }
+
+    /// Move a coordinate by `delta` for the coordinate prepass and the
+    /// initial simplex: wrapped on periodic dimensions, otherwise stopped at
+    /// the edge of the domain in the direction of travel.
+    fn offset(&self, dim_idx: usize, value: f64, delta: f64) -> f64 {
+        if self.is_periodic(dim_idx) {
+            return wrap01(value + delta);
+        }
+        let (lo, hi) = self.extent(dim_idx);
+        if delta >= 0.0 {
+            (value + delta).min(hi)
+        } else {
+            (value + delta).max(lo)
+        }
+    }
+
+    /// Parameters in sorted key order to simplex coordinates. Log dimensions
+    /// map to their position in log space, so simplex moves are
+    /// multiplicative; periodic dimensions map to their phase in [0, 1). NaN
+    /// (a missing parameter) maps to the dimension's default.
+    fn point_to_coords(&self, point: &[f64]) -> Vec<f64> {
+        point
+            .iter()
+            .enumerate()
+            .map(|(i, &value)| {
+                if value.is_nan() {
+                    return self.defaults.get(i).copied().unwrap_or(0.0);
+                }
+                match self.log_bounds(i) {
+                    Some((min, max)) => Domain {
+                        min,
+                        max,
+                        scale: Scale::Log,
+                        active_when: None,
+                    }
+                    .to_unit(value),
+                    None => match self.periodic_bounds(i) {
+                        Some((min, max)) => wrap01((value - min) / (max - min)),
+                        None => value,
+                    },
+                }
+            })
             .collect()
     }
 
+    /// Simplex coordinates back to parameters in sorted key order (inverse
+    /// of `point_to_coords`).
+    fn coords_to_point(&self, vec: &[f64]) -> ParamVec {
+        vec.iter()
+            .enumerate()
+            .map(|(i, &coord)| match self.log_bounds(i) {
+                Some((min, max)) => (min.ln() + coord * (max.ln() - min.ln())).exp(),
+                None => match self.periodic_bounds(i) {
+                    Some((min, max)) => min + wrap01(coord) * (max - min),
+                    None => coord,
+                },
+            })
+            .collect()
+    }
+
+    /// Named parameters to simplex coordinates (see `point_to_coords`).
+    #[cfg(test)]
+    fn dict_to_vec(&self, params: &HashMap<String, f64>, keys: &[String]) -> Vec<f64> {
+        let point: ParamVec = keys
+            .iter()
+            .map(|k| params.get(k).copied().unwrap_or(f64::NAN))
+            .collect();
+        self.point_to_coords(&point)
+    }
+
+    /// Simplex coordinates back to parameters (inverse of `dict_to_vec`).
     fn vec_to_dict(&self, vec: &[f64], keys: &[String]) -> HashMap<String, f64> {
-        let mut map = HashMap::new();
-        for (i, k) in keys.iter().enumerate() {
-            if i < vec.len() {
-                map.insert(k.clone(), vec[i]);
-            }
-        }
-        map
+        keys.iter()
+            .cloned()
+            .zip(self.coords_to_point(vec))
+            .collect()
     }
 
     /// Clamp vector to bounds (or wrap if periodic)
@@ -170,6 +476,8 @@ impl NelderMead {
                 if let Some(domain) = config.bounds.get(k) {
                     if domain.is_periodic() {
                         vec[i] = wrap01(vec[i]);
+                    } else if self.log_bounds(i).is_some() {
+                        vec[i] = vec[i].clamp(0.0, 1.0);
                     } else {
                         vec[i] = vec[i].clamp(domain.min, domain.max);
                     }
@@ -298,59 +606,213 @@ impl NelderMead {
             .collect()
     }
 
-    /// Check if simplex has converged (diameter < tolerance)
+    /// Largest range-normalized coordinate distance from the first (best)
+    /// vertex to any other.
+    pub(crate) fn diameter(&self) -> f64 {
+        self.spread(|i| self.range(i))
+    }
+
+    /// Largest coordinate distance from the first (best) vertex to any
+    /// other, linear distances divided by `range` of their dimension.
+    fn spread(&self, range: impl Fn(usize) -> f64) -> f64 {
+        let Some((_, best)) = self.simplex.first() else {
+            return 0.0;
+        };
+        self.simplex
+            .iter()
+            .skip(1)
+            .flat_map(|(_, vertex)| {
+                best.iter().zip(vertex).enumerate().map(|(i, (&b, &v))| {
+                    if self.is_periodic(i) {
+                        dist01(b, v)
+                    } else {
+                        (b - v).abs() / range(i)
+                    }
+                })
+            })
+            .fold(0.0, f64::max)
+    }
+
+    /// Check if the simplex has converged: its range-normalized diameter is
+    /// below `x_tol`, its absolute diameter below the deprecated `tolerance`,
+    /// or the relative spread of its values is below `f_tol`.
+    #[allow(deprecated)]
     pub(crate) fn check_convergence(&self) -> bool {
         if self.simplex.len() < 2 {
             return false;
         }
-        let best = &self.simplex[0].1;
-        let worst = &self.simplex.last().unwrap().1;
+        let f_best = self.simplex[0].0;
+        let f_worst = self.simplex.last().unwrap().0;
+        let f_spread = (f_worst - f_best) / (f_best.abs() + F_TOL_EPS);
+        self.diameter() < self.x_tol
+            || self.spread(|_| 1.0) < self.tolerance
+            || f_spread < self.f_tol
+    }
 
-        // Compute max distance from best to any other point
-        let diameter: f64 = best
+    /// Replace the simplex with `point` and one vertex per dimension, stepped
+    /// by that dimension's simplex scale times its range (down instead of up
+    /// at the upper bound), and request evaluation of the new vertices.
+    fn build_simplex(&mut self, value: f64, point: Vec<f64>) -> NMStep {
+        self.simplex.clear();
+        self.simplex.push((value, point.clone()));
+        for dim_idx in 0..self.dim {
+            let step = self
+                .simplex_scales
+                .get(dim_idx)
+                .copied()
+                .unwrap_or(DEFAULT_SIMPLEX_SCALE)
+                * self.range(dim_idx);
+            let mut vertex = point.clone();
+            let new_val = self.offset(dim_idx, vertex[dim_idx], step);
+            if (new_val - vertex[dim_idx]).abs() < 1e-6 * self.range(dim_idx) {
+                vertex[dim_idx] = self.offset(dim_idx, vertex[dim_idx], -step);
+            } else {
+                vertex[dim_idx] = new_val;
+            }
+            self.simplex.push((f64::INFINITY, vertex));
+        }
+
+        let new_vertices: Vec<_> = self
+            .simplex
             .iter()
-            .zip(worst.iter())
-            .enumerate()
-            .map(|(i, (&b, &w))| {
-                let is_periodic = self.periodic_mask.get(i).copied().unwrap_or(false);
-                if is_periodic {
-                    dist01(b, w)
-                } else {
-                    (b - w).abs()
-                }
-            })
-            .fold(0.0, f64::max);
+            .skip(1)
+            .map(|(_, v)| v.clone())
+            .collect();
+        self.state = NMState::SimplexBuild { evals_received: 0 };
+        NMStep::Evaluate(new_vertices)
+    }
 
-        diameter < self.tolerance
+    /// Number of times a collapsed simplex has been re-expanded.
+    pub fn reinits(&self) -> usize {
+        self.reinits
     }
 
-    /// Sort simplex by objective value (ascending - minimization)
+    /// Sort simplex by objective value (ascending - minimization), tied
+    /// vertices by their coordinates.
     fn sort_simplex(&mut self) {
         self.simplex
-            .sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+            .sort_by(|a, b| a.0.total_cmp(&b.0).then_with(|| cmp_points(&a.1, &b.1)));
+    }
+
+    /// Start the next iteration on the current simplex: stop if it has
+    /// converged, otherwise propose the reflection of the worst vertex.
+    fn begin_iteration(&mut self, config: &SolverConfig, keys: &[String]) -> NMStep {
+        let n = self.dim;
+        self.sort_simplex();
+
+        if self.check_convergence() {
+            tracing::info!(
+                best = self.simplex[0].0,
+                diameter = self.diameter(),
+                reinits = self.reinits,
+                reason = "tolerance",
+                "nelder-mead converged"
+            );
+            self.state = NMState::Converged;
+            return NMStep::Converged;
+        }
+
+        // Vertices have merged while the values still disagree: the simplex
+        // is numerically degenerate and further moves would be negligible.
+        if self.diameter() < self.collapse_tol {
+            if self.reinits >= self.max_reinits {
+                tracing::info!(
+                    best = self.simplex[0].0,
+                    diameter = self.diameter(),
+                    reinits = self.reinits,
+                    reason = "collapsed",
+                    "nelder-mead converged"
+                );
+                self.state = NMState::Converged;
+                return NMStep::Converged;
+            }
+            tracing::debug!(
+                diameter = self.diameter(),
+                reinits = self.reinits + 1,
+                "re-expanding collapsed simplex"
+            );
+            self.reinits += 1;
+            let (value, point) = self.simplex[0].clone();
+            return self.build_simplex(value, point);
+        }
+
+        let centroid = self.compute_centroid();
+        let worst = &self.simplex[n].1;
+        let mut reflection = self.compute_reflection(&centroid, worst);
+        self.clamp_to_bounds(&mut reflection, config, keys);
+
+        let best = self.simplex[0].0;
+        let second_worst = self.simplex[n - 1].0;
+        let worst_val = self.simplex[n].0;
+
+        self.state = NMState::Reflection {
+            centroid,
+            reflection: reflection.clone(),
+            best,
+            second_worst,
+            worst: worst_val,
+        };
+
+        NMStep::Evaluate(vec![reflection])
+    }
+
+    /// Debug event for the outcome of evaluating the point proposed in
+    /// `state`: whether it entered the simplex and which state comes next.
+    fn log_transition(&self, state: &'static str, value: f64, accepted: bool, next: &'static str) {
+        tracing::debug!(
+            state,
+            value,
+            accepted,
+            next,
+            best = self.simplex.first().map_or(f64::NAN, |v| v.0),
+            worst = self.simplex.last().map_or(f64::NAN, |v| v.0),
+            diameter = self.diameter(),
+            "nelder-mead transition"
+        );
     }
 }
 
-impl Strategy for NelderMead {
-    fn step(&mut self, config: &SolverConfig, history: &[EvalTrace]) -> StrategyAction {
-        // Collect keys for deterministic ordering
-        let mut keys: Vec<String> = config.bounds.keys().cloned().collect();
-        keys.sort();
-        self.dim = keys.len();
+impl NelderMead {
+    /// Go back to the coordinate prepass around the best points so far,
+    /// after a move has been accepted into the simplex.
+    fn restart(
+        &mut self,
+        config: &SolverConfig,
+        history: &[EvalTrace],
+        point: &dyn Fn(usize) -> ParamVec,
+        keys: &[String],
+    ) -> NMStep {
+        self.state = NMState::Init;
+        self.advance(config, history, point, keys)
+    }
+
+    /// One step of the state machine. `point(i)` gives the parameters of
+    /// `history[i]` in sorted key order (NaN where absent); candidates come
+    /// back in simplex coordinates.
+    #[tracing::instrument(
+        name = "nelder_mead",
+        level = "debug",
+        skip_all,
+        fields(dim = self.dim, evaluations = history.len())
+    )]
+    fn advance(
+        &mut self,
+        config: &SolverConfig,
+        history: &[EvalTrace],
+        point: &dyn Fn(usize) -> ParamVec,
+        keys: &[String],
+    ) -> NMStep {
         let n = self.dim;
 
         match &self.state {
             NMState::Init => {
                 // PHASE 5: Multi-seed prepass - pick K=3 diverse seeds from top candidates
-                let mut sorted: Vec<_> = history.iter().collect();
-                sorted.sort_by(|a, b| {
-                    a.value
-                        .partial_cmp(&b.value)
-                        .unwrap_or(std::cmp::Ordering::Equal)
-                });
+                let order = config.param_order();
+                let mut sorted: Vec<usize> = (0..history.len()).collect();
+                sorted.sort_by(|&a, &b| order.cmp_traces(&history[a], &history[b]));
 
                 if sorted.is_empty() {
-                    return StrategyAction::Wait;
+                    return NMStep::Wait;
                 }
 
                 // Select K=3 diverse seeds from top-10 using farthest-point selection
@@ -360,8 +822,8 @@ impl Strategy for NelderMead {
                 let mut seeds: Vec<(f64, Vec<f64>)> = Vec::new();
 
                 // Always include best point
-                let best = &sorted[0];
-                seeds.push((best.value, self.dict_to_vec(&best.params, &keys)));
+                let best = sorted[0];
+                seeds.push((history[best].value, self.point_to_coords(&point(best))));
 
                 // Farthest-point selection for remaining seeds
                 for _ in 1..k {
@@ -374,7 +836,7 @@ impl Strategy for NelderMead {
 
                     #[allow(clippy::needless_range_loop)]
                     for i in 1..pool_size {
-                        let candidate = self.dict_to_vec(&sorted[i].params, &keys);
+                        let candidate = self.point_to_coords(&point(sorted[i]));
 
                         // Check if already selected
                         let already_selected = seeds.iter().any(|(_, s)| {
@@ -405,8 +867,11 @@ impl Strategy for NelderMead {
                     }
 
                     if best_min_dist > 0.0 {
-                        let selected = &sorted[best_idx];
-                        seeds.push((selected.value, self.dict_to_vec(&selected.params, &keys)));
+                        let selected = sorted[best_idx];
+                        seeds.push((
+                            history[selected].value,
+                            self.point_to_coords(&point(selected)),
+                        ));
                     }
                 }
 
@@ -420,12 +885,11 @@ impl Strategy for NelderMead {
 
                 let mut plus = seed_vec.clone();
                 let mut minus = seed_vec.clone();
-                plus[0] = (plus[0] + delta).min(1.0);
-                minus[0] = (minus[0] - delta).max(0.0);
+                plus[0] = self.offset(0, plus[0], delta);
+                minus[0] = self.offset(0, minus[0], -delta);
 
-                let pending = vec![plus.clone(), minus.clone()];
-                let candidates: Vec<_> =
-                    pending.iter().map(|v| self.vec_to_dict(v, &keys)).collect();
+                let pending = vec![plus, minus];
+                let candidates = pending.clone();
 
                 self.state = NMState::CoordinatePrepass {
                     best_point: seed_vec,
@@ -438,7 +902,7 @@ impl Strategy for NelderMead {
                     global_best: None,
                 };
 
-                StrategyAction::Evaluate(candidates)
+                NMStep::Evaluate(candidates)
             }
 
             NMState::CoordinatePrepass {
@@ -456,10 +920,10 @@ impl Strategy for NelderMead {
                 let mut current_val = *best_value;
 
                 // Check if any pending point improved
-                for eval in history.iter().rev().take(pending.len()) {
-                    if eval.value < current_val {
-                        current_best = self.dict_to_vec(&eval.params, &keys);
-                        current_val = eval.value;
+                for i in (history.len().saturating_sub(pending.len())..history.len()).rev() {
+                    if history[i].value < current_val {
+                        current_best = self.point_to_coords(&point(i));
+                        current_val = history[i].value;
                     }
                 }
 
@@ -471,14 +935,11 @@ impl Strategy for NelderMead {
                     let delta = deltas[*delta_idx];
                     let mut plus = current_best.clone();
                     let mut minus = current_best.clone();
-                    plus[next_dim] = (plus[next_dim] + delta).min(1.0);
-                    minus[next_dim] = (minus[next_dim] - delta).max(0.0);
+                    plus[next_dim] = self.offset(next_dim, plus[next_dim], delta);
+                    minus[next_dim] = self.offset(next_dim, minus[next_dim], -delta);
 
-                    let new_pending = vec![plus.clone(), minus.clone()];
-                    let candidates: Vec<_> = new_pending
-                        .iter()
-                        .map(|v| self.vec_to_dict(v, &keys))
-                        .collect();
+                    let new_pending = vec![plus, minus];
+                    let candidates = new_pending.clone();
 
                     self.state = NMState::CoordinatePrepass {
                         best_point: current_best,
@@ -491,7 +952,7 @@ impl Strategy for NelderMead {
                         global_best: global_best.clone(),
                     };
 
-                    StrategyAction::Evaluate(candidates)
+                    NMStep::Evaluate(candidates)
                 } else {
                     // Finished all dimensions for this seed
                     // Update global best
@@ -509,14 +970,11 @@ impl Strategy for NelderMead {
 
                         let mut plus = next_vec.clone();
                         let mut minus = next_vec.clone();
-                        plus[0] = (plus[0] + delta).min(1.0);
-                        minus[0] = (minus[0] - delta).max(0.0);
+                        plus[0] = self.offset(0, plus[0], delta);
+                        minus[0] = self.offset(0, minus[0], -delta);
 
-                        let new_pending = vec![plus.clone(), minus.clone()];
-                        let candidates: Vec<_> = new_pending
-                            .iter()
-                            .map(|v| self.vec_to_dict(v, &keys))
-                            .collect();
+                        let new_pending = vec![plus, minus];
+                        let candidates = new_pending.clone();
 
                         self.state = NMState::CoordinatePrepass {
                             best_point: next_vec,
@@ -529,38 +987,14 @@ impl Strategy for NelderMead {
                             global_best: new_global_best,
                         };
 
-                        StrategyAction::Evaluate(candidates)
+                        NMStep::Evaluate(candidates)
                     } else {
                         // All seeds processed - use global best for simplex
                         let (final_val, final_point) =
                             new_global_best.unwrap_or((current_val, current_best));
 
-                        self.simplex.clear();
-                        self.simplex.push((final_val, final_point.clone()));
-
                         // Build axis-aligned simplex around best refined point
-                        let scale = 0.05;
-                        for dim_idx in 0..n {
-                            let mut vertex = final_point.clone();
-                            let new_val = (vertex[dim_idx] + scale).min(1.0);
-                            if (new_val - vertex[dim_idx]).abs() < 1e-6 {
-                                vertex[dim_idx] = (vertex[dim_idx] - scale).max(0.0);
-                            } else {
-                                vertex[dim_idx] = new_val;
-                            }
-                            self.simplex.push((f64::INFINITY, vertex));
-                        }
-
-                        // Request evaluation of simplex vertices
-                        let new_vertices: Vec<_> = self
-                            .simplex
-                            .iter()
-                            .skip(1)
-                            .map(|(_, v)| self.vec_to_dict(v, &keys))
-                            .collect();
-
-                        self.state = NMState::SimplexBuild { evals_received: 0 };
-                        StrategyAction::Evaluate(new_vertices)
+                        self.build_simplex(final_val, final_point)
                     }
                 }
             }
@@ -580,32 +1014,7 @@ impl Strategy for NelderMead {
                     }
                 }
 
-                self.sort_simplex();
-
-                if self.check_convergence() {
-                    self.state = NMState::Converged;
-                    return StrategyAction::Converged;
-                }
-
-                // Compute first reflection
-                let centroid = self.compute_centroid();
-                let worst = &self.simplex[n].1;
-                let mut reflection = self.compute_reflection(&centroid, worst);
-                self.clamp_to_bounds(&mut reflection, config, &keys);
-
-                let best = self.simplex[0].0;
-                let second_worst = self.simplex[n - 1].0;
-                let worst_val = self.simplex[n].0;
-
-                self.state = NMState::Reflection {
-                    centroid,
-                    reflection: reflection.clone(),
-                    best,
-                    second_worst,
-                    worst: worst_val,
-                };
-
-                StrategyAction::Evaluate(vec![self.vec_to_dict(&reflection, &keys)])
+                self.begin_iteration(config, keys)
             }
 
             NMState::Reflection {
@@ -616,11 +1025,13 @@ impl Strategy for NelderMead {
                 worst,
             } => {
                 let reflection_val = history.last().map(|t| t.value).unwrap_or(*worst);
+                let margin = noise_margin(config, history);
 
-                if reflection_val < *best {
+                if reflection_val < *best - margin {
+                    self.log_transition("reflection", reflection_val, true, "expansion");
                     // Try expansion
                     let mut expansion = self.compute_expansion(centroid, reflection);
-                    self.clamp_to_bounds(&mut expansion, config, &keys);
+                    self.clamp_to_bounds(&mut expansion, config, keys);
 
                     self.state = NMState::Expansion {
                         centroid: centroid.clone(),
@@ -628,35 +1039,37 @@ impl Strategy for NelderMead {
                         expansion: expansion.clone(),
                         reflection_value: reflection_val,
                     };
-                    StrategyAction::Evaluate(vec![self.vec_to_dict(&expansion, &keys)])
-                } else if reflection_val < *second_worst {
+                    NMStep::Evaluate(vec![expansion])
+                } else if reflection_val < *second_worst - margin {
+                    self.log_transition("reflection", reflection_val, true, "init");
                     // Accept reflection
                     let n = self.dim;
                     self.simplex[n] = (reflection_val, reflection.clone());
-                    self.state = NMState::Init;
-                    self.step(config, history) // Immediate restart
-                } else if reflection_val < *worst {
+                    self.restart(config, history, point, keys)
+                } else if reflection_val < *worst - margin {
+                    self.log_transition("reflection", reflection_val, false, "outside_contraction");
                     // Try outside contraction
                     let mut contraction = self.compute_outside_contraction(centroid, reflection);
-                    self.clamp_to_bounds(&mut contraction, config, &keys);
+                    self.clamp_to_bounds(&mut contraction, config, keys);
 
                     self.state = NMState::OutsideContraction {
                         centroid: centroid.clone(),
                         contraction: contraction.clone(),
                         reflection_value: reflection_val,
                     };
-                    StrategyAction::Evaluate(vec![self.vec_to_dict(&contraction, &keys)])
+                    NMStep::Evaluate(vec![contraction])
                 } else {
+                    self.log_transition("reflection", reflection_val, false, "inside_contraction");
                     // Try inside contraction
                     let worst_pt = &self.simplex[n].1;
                     let mut contraction = self.compute_inside_contraction(centroid, worst_pt);
-                    self.clamp_to_bounds(&mut contraction, config, &keys);
+                    self.clamp_to_bounds(&mut contraction, config, keys);
 
                     self.state = NMState::InsideContraction {
                         centroid: centroid.clone(),
                         contraction: contraction.clone(),
                     };
-                    StrategyAction::Evaluate(vec![self.vec_to_dict(&contraction, &keys)])
+                    NMStep::Evaluate(vec![contraction])
                 }
             }
 
@@ -668,19 +1081,21 @@ impl Strategy for NelderMead {
             } => {
                 let expansion_val = history.last().map(|t| t.value).unwrap_or(*reflection_value);
                 let expansion_pt = history
-                    .last()
-                    .map(|t| self.dict_to_vec(&t.params, &keys))
+                    .len()
+                    .checked_sub(1)
+                    .map(|last| self.point_to_coords(&point(last)))
                     .unwrap_or_default();
 
-                if expansion_val < *reflection_value {
+                if expansion_val < *reflection_value - noise_margin(config, history) {
+                    self.log_transition("expansion", expansion_val, true, "init");
                     // Accept expansion
                     self.simplex[n] = (expansion_val, expansion_pt);
                 } else {
+                    self.log_transition("expansion", expansion_val, false, "init");
                     // Accept reflection
                     self.simplex[n] = (*reflection_value, reflection.clone());
                 }
-                self.state = NMState::Init;
-                self.step(config, history)
+                self.restart(config, history, point, keys)
             }
 
             NMState::OutsideContraction {
@@ -690,24 +1105,24 @@ impl Strategy for NelderMead {
             } => {
                 let contraction_val = history.last().map(|t| t.value).unwrap_or(*reflection_value);
 
-                if contraction_val <= *reflection_value {
+                if contraction_val <= *reflection_value + noise_margin(config, history) {
+                    self.log_transition("outside_contraction", contraction_val, true, "init");
                     // Accept outside contraction
                     self.simplex[n] = (contraction_val, contraction.clone());
-                    self.state = NMState::Init;
-                    self.step(config, history)
+                    self.restart(config, history, point, keys)
                 } else {
+                    self.log_transition("outside_contraction", contraction_val, false, "shrink");
                     // Shrink
                     let shrunk = self.compute_shrunk_points();
                     if shrunk.is_empty() {
-                        self.state = NMState::Init;
-                        return self.step(config, history);
+                        return self.restart(config, history, point, keys);
                     }
                     let first_shrunk = shrunk[0].clone();
                     self.state = NMState::Shrink {
                         shrunk_points: shrunk,
                         shrunk_idx: 0,
                     };
-                    StrategyAction::Evaluate(vec![self.vec_to_dict(&first_shrunk, &keys)])
+                    NMStep::Evaluate(vec![first_shrunk])
                 }
             }
 
@@ -718,24 +1133,24 @@ impl Strategy for NelderMead {
                 let contraction_val = history.last().map(|t| t.value).unwrap_or(f64::INFINITY);
                 let worst_val = self.simplex[n].0;
 
-                if contraction_val < worst_val {
+                if contraction_val < worst_val + noise_margin(config, history) {
+                    self.log_transition("inside_contraction", contraction_val, true, "init");
                     // Accept inside contraction
                     self.simplex[n] = (contraction_val, contraction.clone());
-                    self.state = NMState::Init;
-                    self.step(config, history)
+                    self.restart(config, history, point, keys)
                 } else {
+                    self.log_transition("inside_contraction", contraction_val, false, "shrink");
                     // Shrink
                     let shrunk = self.compute_shrunk_points();
                     if shrunk.is_empty() {
-                        self.state = NMState::Init;
-                        return self.step(config, history);
+                        return self.restart(config, history, point, keys);
                     }
                     let first_shrunk = shrunk[0].clone();
                     self.state = NMState::Shrink {
                         shrunk_points: shrunk,
                         shrunk_idx: 0,
                     };
-                    StrategyAction::Evaluate(vec![self.vec_to_dict(&first_shrunk, &keys)])
+                    NMStep::Evaluate(vec![first_shrunk])
                 }
             }
 
@@ -744,10 +1159,11 @@ impl Strategy for NelderMead {
                 shrunk_idx,
             } => {
                 // Record the shrunk point we just evaluated
-                if let Some(last) = history.last() {
+                if let Some(last) = history.len().checked_sub(1) {
                     let idx = shrunk_idx + 1; // +1 because index 0 is best (unchanged)
                     if idx < self.simplex.len() {
-                        self.simplex[idx] = (last.value, self.dict_to_vec(&last.params, &keys));
+                        self.simplex[idx] =
+                            (history[last].value, self.point_to_coords(&point(last)));
                     }
                 }
 
@@ -759,22 +1175,86 @@ impl Strategy for NelderMead {
                         shrunk_points: shrunk_points.clone(),
                         shrunk_idx: next_idx,
                     };
-                    StrategyAction::Evaluate(vec![self.vec_to_dict(&next_shrunk, &keys)])
+                    NMStep::Evaluate(vec![next_shrunk])
                 } else {
+                    let value = history.last().map_or(f64::NAN, |t| t.value);
+                    self.log_transition("shrink", value, true, "init");
                     // Shrink complete, restart
-                    self.state = NMState::Init;
-                    self.step(config, history)
+                    self.restart(config, history, point, keys)
                 }
             }
 
-            NMState::Converged => StrategyAction::Converged,
+            NMState::Converged => NMStep::Converged,
         }
     }
 }
 
+impl Strategy for NelderMead {
+    /// Every reflection, expansion and contraction depends on the result of
+    /// the one before it.
+    fn batch_hint(&self) -> usize {
+        1
+    }
+
+    fn step(&mut self, config: &SolverConfig, history: &[EvalTrace]) -> StrategyAction {
+        // Collect keys for deterministic ordering
+        let mut keys: Vec<String> = config.bounds.keys().cloned().collect();
+        keys.sort();
+        self.dim = keys.len();
+        self.update_dims(config, &keys);
+        let point = |i: usize| -> ParamVec {
+            keys.iter()
+                .map(|k| history[i].params.get(k).copied().unwrap_or(f64::NAN))
+                .collect()
+        };
+        match self.advance(config, history, &point, &keys) {
+            NMStep::Evaluate(points) => StrategyAction::Evaluate(
+                points.iter().map(|v| self.vec_to_dict(v, &keys)).collect(),
+            ),
+            NMStep::Wait => StrategyAction::Wait,
+            NMStep::Converged => StrategyAction::Converged,
+        }
+    }
+
+    /// Reads the bounds and `strategy_params` on the first dense step only;
+    /// later steps do no per-key work.
+    fn step_dense(
+        &mut self,
+        config: &SolverConfig,
+        history: &DenseHistory<'_>,
+    ) -> Option<DenseAction> {
+        if self.dense_keys.len() != history.registry.len() {
+            self.dense_keys = (0..history.registry.len())
+                .filter_map(|id| history.registry.get_name(id as ParamId))
+                .map(str::to_string)
+                .collect();
+            self.dim = self.dense_keys.len();
+            let keys = std::mem::take(&mut self.dense_keys);
+            self.update_dims(config, &keys);
+            self.dense_keys = keys;
+        }
+        let keys = std::mem::take(&mut self.dense_keys);
+        let step = self.advance(
+            config,
+            history.traces,
+            &|i| history.points[i].clone(),
+            &keys,
+        );
+        self.dense_keys = keys;
+        Some(match step {
+            NMStep::Evaluate(points) => {
+                DenseAction::Evaluate(points.iter().map(|v| self.coords_to_point(v)).collect())
+            }
+            NMStep::Wait => DenseAction::Wait,
+            NMStep::Converged => DenseAction::Converged,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::ProbeBudget;
 
     #[test]
     fn test_nm_coefficients_default() {
@@ -918,6 +1398,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(deprecated)]
     fn test_nm_check_convergence_true() {
         let mut nm = NelderMead::new(2, vec![false; 2]);
         nm.tolerance = 0.0001;
@@ -987,14 +1468,31 @@ mod tests {
                 min: 0.0,
                 max: 1.0,
                 scale: crate::config::Scale::Linear,
+                active_when: None,
             },
         );
         let config = SolverConfig {
             seed: 42,
             budget: 10,
             bounds,
-            probe_ratio: 0.2,
+            probe_ratio: ProbeBudget::Fixed(0.2),
             strategy_params: None,
+            constraints: Vec::new(),
+            selection: Default::default(),
+            cost_budget: None,
+            observation_noise: None,
+            max_restarts: 0,
+            non_finite: Default::default(),
+            repeats: 0,
+            repeat_reducer: Default::default(),
+            objective_transform: None,
+            fixed: Default::default(),
+            target_value: None,
+            target_tol: 0.0,
+            history_cap: None,
+            invalid_params: Default::default(),
+            infeasibility_penalty: 0.0,
+            initial_points: Vec::new(),
         };
 
         nm.clamp_to_bounds(&mut vec, &config, &["x".to_string()]);
@@ -1013,14 +1511,31 @@ mod tests {
                 min: 0.0,
                 max: 1.0,
                 scale: crate::config::Scale::Periodic,
+                active_when: None,
             },
         );
         let config = SolverConfig {
             seed: 42,
             budget: 10,
             bounds,
-            probe_ratio: 0.2,
+            probe_ratio: ProbeBudget::Fixed(0.2),
             strategy_params: None,
+            constraints: Vec::new(),
+            selection: Default::default(),
+            cost_budget: None,
+            observation_noise: None,
+            max_restarts: 0,
+            non_finite: Default::default(),
+            repeats: 0,
+            repeat_reducer: Default::default(),
+            objective_transform: None,
+            fixed: Default::default(),
+            target_value: None,
+            target_tol: 0.0,
+            history_cap: None,
+            invalid_params: Default::default(),
+            infeasibility_penalty: 0.0,
+            initial_points: Vec::new(),
         };
 
         nm.clamp_to_bounds(&mut vec, &config, &["x".to_string()]);
@@ -1094,6 +1609,7 @@ mod tests {
                 min: 0.0,
                 max: 1.0,
                 scale: crate::config::Scale::Linear,
+                active_when: None,
             },
         );
         bounds.insert(
@@ -1102,14 +1618,31 @@ mod tests {
                 min: 0.0,
                 max: 1.0,
                 scale: crate::config::Scale::Linear,
+                active_when: None,
             },
         );
         SolverConfig {
             seed: 42,
             budget: 100,
             bounds,
-            probe_ratio: 0.2,
+            probe_ratio: ProbeBudget::Fixed(0.2),
             strategy_params: None,
+            constraints: Vec::new(),
+            selection: Default::default(),
+            cost_budget: None,
+            observation_noise: None,
+            max_restarts: 0,
+            non_finite: Default::default(),
+            repeats: 0,
+            repeat_reducer: Default::default(),
+            objective_transform: None,
+            fixed: Default::default(),
+            target_value: None,
+            target_tol: 0.0,
+            history_cap: None,
+            invalid_params: Default::default(),
+            infeasibility_penalty: 0.0,
+            initial_points: Vec::new(),
         }
     }
 
@@ -1131,6 +1664,14 @@ mod tests {
                 .collect(),
                 value: (i as f64 / 10.0 - 0.5).powi(2), // Parabola
                 cost: 1.0,
+                repeats: None,
+                penalized: false,
+                failed: false,
+                feasibility: None,
+                penalty: None,
+                fidelity: None,
+                kind: None,
+                source: None,
             })
             .collect();
 
@@ -1166,6 +1707,14 @@ mod tests {
                     .collect(),
                 value: (i as f64 / 10.0 - 0.5).powi(2),
                 cost: 1.0,
+                repeats: None,
+                penalized: false,
+                failed: false,
+                feasibility: None,
+                penalty: None,
+                fidelity: None,
+                kind: None,
+                source: None,
             })
             .collect();
 
@@ -1191,6 +1740,263 @@ mod tests {
         }
     }
 
+    /// Drive NM on a bowl centred at (0.4, 0.6) until the initial simplex is
+    /// built, and return its vertices.
+    fn initial_simplex(config: &SolverConfig) -> Vec<Vec<f64>> {
+        let mut nm = NelderMead::new(2, vec![false; 2]);
+        let objective = |p: &HashMap<String, f64>| (p["x"] - 0.4).powi(2) + (p["y"] - 0.6).powi(2);
+        let mut history: Vec<EvalTrace> = [0.2, 0.4, 0.6, 0.8]
+            .iter()
+            .map(|&v| [("x".to_string(), v), ("y".to_string(), 1.0 - v)].into())
+            .map(|params: HashMap<String, f64>| EvalTrace {
+                eval_id: 0,
+                value: objective(&params),
+                params,
+                cost: 1.0,
+                repeats: None,
+                penalized: false,
+                failed: false,
+                feasibility: None,
+                penalty: None,
+                fidelity: None,
+                kind: None,
+                source: None,
+            })
+            .collect();
+        for _ in 0..50 {
+            let StrategyAction::Evaluate(points) = nm.step(config, &history) else {
+                break;
+            };
+            if matches!(nm.state, NMState::SimplexBuild { .. }) {
+                return nm.simplex.iter().map(|(_, v)| v.clone()).collect();
+            }
+            for params in points {
+                history.push(EvalTrace {
+                    eval_id: history.len() as u64,
+                    value: objective(&params),
+                    params,
+                    cost: 1.0,
+                    repeats: None,
+                    penalized: false,
+                    failed: false,
+                    feasibility: None,
+                    penalty: None,
+                    fidelity: None,
+                    kind: None,
+                    source: None,
+                });
+            }
+        }
+        panic!("NM never built its initial simplex");
+    }
+
+    fn diameter(simplex: &[Vec<f64>]) -> f64 {
+        let mut diameter: f64 = 0.0;
+        for a in simplex {
+            for b in simplex {
+                let d: f64 = a.iter().zip(b).map(|(x, y)| (x - y).powi(2)).sum();
+                diameter = diameter.max(d.sqrt());
+            }
+        }
+        diameter
+    }
+
+    #[test]
+    fn test_nm_simplex_scale_widens_initial_simplex() {
+        let default = initial_simplex(&make_solver_config_2d());
+
+        let mut config = make_solver_config_2d();
+        config.strategy_params = Some(HashMap::from([(SIMPLEX_SCALE_KEY.to_string(), 0.2)]));
+        let wide = initial_simplex(&config);
+
+        assert!((diameter(&default) - DEFAULT_SIMPLEX_SCALE * 2f64.sqrt()).abs() < 1e-9);
+        assert!(diameter(&wide) > diameter(&default));
+        assert!((diameter(&wide) - 0.2 * 2f64.sqrt()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_nm_simplex_scale_per_param() {
+        let mut config = make_solver_config_2d();
+        config.strategy_params = Some(HashMap::from([
+            (format!("{}.y", SIMPLEX_SCALE_KEY), 0.3),
+            // Out of range: ignored in favour of the default.
+            (format!("{}.x", SIMPLEX_SCALE_KEY), 0.0),
+        ]));
+        let simplex = initial_simplex(&config);
+        let base = &simplex[0];
+
+        // Keys sort as [x, y]: vertex 1 steps along x, vertex 2 along y.
+        assert!(((simplex[1][0] - base[0]).abs() - DEFAULT_SIMPLEX_SCALE).abs() < 1e-9);
+        assert_eq!(simplex[1][1], base[1]);
+        assert_eq!(simplex[2][0], base[0]);
+        assert!(((simplex[2][1] - base[1]).abs() - 0.3).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_nm_simplex_scale_is_a_fraction_of_the_range() {
+        let mut config = make_solver_config_2d();
+        config.bounds.get_mut("x").unwrap().max = 10.0;
+        let simplex = initial_simplex(&config);
+        let base = &simplex[0];
+
+        // x spans 10 units, so the default step covers 0.5 of them.
+        assert!(((simplex[1][0] - base[0]).abs() - DEFAULT_SIMPLEX_SCALE * 10.0).abs() < 1e-9);
+        assert!(((simplex[2][1] - base[1]).abs() - DEFAULT_SIMPLEX_SCALE).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_check_rejects_simplex_scale_outside_unit_interval() {
+        let mut config = make_solver_config_2d();
+        config.strategy_params = Some(HashMap::from([
+            (SIMPLEX_SCALE_KEY.to_string(), 1.0),
+            (format!("{}.x", SIMPLEX_SCALE_KEY), 0.2),
+        ]));
+        assert!(NelderMead::check(&config).is_ok());
+
+        for bad in [0.0, 1.5, f64::NAN] {
+            config.strategy_params =
+                Some(HashMap::from([(format!("{}.y", SIMPLEX_SCALE_KEY), bad)]));
+            let err = NelderMead::check(&config).unwrap_err();
+            assert!(err.contains("nm_simplex_scale.y"), "{}", err);
+        }
+        config.strategy_params = Some(HashMap::from([(SIMPLEX_SCALE_KEY.to_string(), -0.1)]));
+        assert!(NelderMead::check(&config).is_err());
+    }
+
+    #[test]
+    fn test_nm_simplex_scale_flips_at_upper_bound() {
+        let mut config = make_solver_config_2d();
+        config.strategy_params = Some(HashMap::from([(format!("{}.x", SIMPLEX_SCALE_KEY), 0.25)]));
+        let mut nm = NelderMead::new(2, vec![false; 2]);
+        nm.update_dims(&config, &["x".to_string(), "y".to_string()]);
+        assert_eq!(nm.simplex_scales, vec![0.25, DEFAULT_SIMPLEX_SCALE]);
+        // At the upper bound the step is clamped away, so the vertex goes
+        // down by the configured scale instead.
+        let up = nm.offset(0, 1.0, 0.25);
+        assert_eq!(up, 1.0);
+        assert!((nm.offset(0, 1.0, -0.25) - 0.75).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_nm_convergence_is_relative_to_each_range() {
+        // x spans [0, 1e6] and y spans [0, 1e-6]: absolute distances say
+        // nothing about how far the simplex has shrunk in either.
+        let mut config = make_solver_config_2d();
+        for (key, max) in [("x", 1e6), ("y", 1e-6)] {
+            config.bounds.get_mut(key).unwrap().max = max;
+        }
+        let keys = ["x".to_string(), "y".to_string()];
+        let mut nm = NelderMead::new(2, vec![false; 2]);
+        nm.update_dims(&config, &keys);
+        nm.f_tol = 0.0;
+
+        // 1e-3 apart in x is 1e-9 of its range.
+        nm.simplex = vec![
+            (1.0, vec![3e5, 7e-7]),
+            (2.0, vec![3e5 + 1e-3, 7e-7]),
+            (3.0, vec![3e5, 7e-7 + 1e-15]),
+        ];
+        assert!(nm.check_convergence());
+
+        // 1e-9 apart in y is a thousandth of its range.
+        nm.simplex = vec![
+            (1.0, vec![3e5, 7e-7]),
+            (2.0, vec![3e5, 7e-7 + 1e-9]),
+            (3.0, vec![3e5, 7e-7]),
+        ];
+        assert!(!nm.check_convergence());
+    }
+
+    #[test]
+    fn test_nm_collapsed_simplex_is_reinitialized() {
+        let config = make_solver_config_2d();
+        let keys = ["x".to_string(), "y".to_string()];
+        // Vertices 1e-11 apart whose values still differ by O(1), with x_tol
+        // lowered so only the values decide convergence.
+        let degenerate = vec![
+            (0.0, vec![0.3, 0.3]),
+            (1.0, vec![0.3 + 1e-11, 0.3]),
+            (2.0, vec![0.3, 0.3 + 1e-11]),
+        ];
+        let mut nm = NelderMead::with_seed_points(2, degenerate.clone(), vec![false; 2]);
+        nm.update_dims(&config, &keys);
+        nm.x_tol = 0.0;
+        assert!(!nm.check_convergence());
+
+        let NMStep::Evaluate(vertices) = nm.begin_iteration(&config, &keys) else {
+            panic!("expected the re-expanded vertices");
+        };
+        assert_eq!(nm.reinits(), 1);
+        assert_eq!(vertices.len(), 2);
+        assert!(matches!(nm.state, NMState::SimplexBuild { .. }));
+        assert_eq!(nm.simplex[0], (0.0, vec![0.3, 0.3]));
+        assert!((nm.diameter() - DEFAULT_SIMPLEX_SCALE).abs() < 1e-12);
+
+        // Once the vertices are evaluated the simplex iterates again.
+        let history: Vec<EvalTrace> = vertices
+            .into_iter()
+            .enumerate()
+            .map(|(i, vertex)| EvalTrace {
+                eval_id: i as u64,
+                params: nm.vec_to_dict(&vertex, &keys),
+                value: 1.0 + i as f64,
+                cost: 1.0,
+                repeats: None,
+                penalized: false,
+                failed: false,
+                feasibility: None,
+                penalty: None,
+                fidelity: None,
+                kind: None,
+                source: None,
+            })
+            .collect();
+        assert!(matches!(
+            nm.step(&config, &history),
+            StrategyAction::Evaluate(_)
+        ));
+        assert!(matches!(nm.state, NMState::Reflection { .. }));
+
+        // With no re-expansions left, a collapsed simplex ends the run.
+        let mut nm = NelderMead::with_seed_points(2, degenerate, vec![false; 2]);
+        nm.update_dims(&config, &keys);
+        nm.x_tol = 0.0;
+        nm.max_reinits = 0;
+        assert!(matches!(
+            nm.begin_iteration(&config, &keys),
+            NMStep::Converged
+        ));
+        assert_eq!(nm.reinits(), 0);
+    }
+
+    #[test]
+    fn test_nm_sphere_at_origin_converges_without_reexpansion() {
+        // Shrinking onto the optimum of a sphere at the origin: the values
+        // never converge relative to f_best = 0, so the simplex has to reach
+        // x_tol without being mistaken for a collapsed one on the way down.
+        let config = make_solver_config_2d();
+        let keys = ["x".to_string(), "y".to_string()];
+        let sphere = |v: &[f64]| v.iter().map(|x| x * x).sum::<f64>();
+        let vertices = [vec![0.0, 0.0], vec![0.05, 0.0], vec![0.0, 0.05]];
+        let simplex = vertices.iter().map(|v| (sphere(v), v.clone())).collect();
+        let mut nm = NelderMead::with_seed_points(2, simplex, vec![false; 2]);
+        nm.update_dims(&config, &keys);
+
+        let mut shrinks = 0;
+        while matches!(nm.begin_iteration(&config, &keys), NMStep::Evaluate(_)) {
+            assert!(matches!(nm.state, NMState::Reflection { .. }));
+            let shrunk = nm.compute_shrunk_points();
+            for (vertex, point) in nm.simplex.iter_mut().skip(1).zip(shrunk) {
+                *vertex = (sphere(&point), point);
+            }
+            shrinks += 1;
+            assert!(shrinks < 100, "no convergence after {} shrinks", shrinks);
+        }
+        assert!(matches!(nm.state, NMState::Converged));
+        assert_eq!(nm.reinits(), 0);
+        assert!(nm.diameter() < DEFAULT_X_TOL);
+    }
+
     #[test]
     fn test_nm_full_step_cycle() {
         // Test running NM through multiple iterations
@@ -1206,6 +2012,14 @@ mod tests {
                     .collect(),
                 value: (i as f64 / 10.0 - 0.5).powi(2),
                 cost: 1.0,
+                repeats: None,
+                penalized: false,
+                failed: false,
+                feasibility: None,
+                penalty: None,
+                fidelity: None,
+                kind: None,
+                source: None,
             })
             .collect();
 
@@ -1223,6 +2037,14 @@ mod tests {
                             params: c.clone(),
                             value: (x - 0.5).powi(2) + (y - 0.5).powi(2),
                             cost: 1.0,
+                            repeats: None,
+                            penalized: false,
+                            failed: false,
+                            feasibility: None,
+                            penalty: None,
+                            fidelity: None,
+                            kind: None,
+                            source: None,
                         });
                     }
                 }
@@ -1237,6 +2059,198 @@ mod tests {
         }
     }
 
+    /// Subscriber recording the name of every span and the fields of every
+    /// event, as strings.
+    #[derive(Clone, Default)]
+    struct Recorder {
+        spans: std::sync::Arc<std::sync::Mutex<Vec<String>>>,
+        events: std::sync::Arc<std::sync::Mutex<Vec<HashMap<String, String>>>>,
+    }
+
+    struct FieldVisitor<'a>(&'a mut HashMap<String, String>);
+
+    impl tracing::field::Visit for FieldVisitor<'_> {
+        fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+            self.0.insert(field.name().to_string(), value.to_string());
+        }
+
+        fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+            self.0
+                .insert(field.name().to_string(), format!("{:?}", value));
+        }
+    }
+
+    impl tracing::Subscriber for Recorder {
+        fn enabled(&self, _: &tracing::Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+            let mut spans = self.spans.lock().unwrap();
+            spans.push(span.metadata().name().to_string());
+            tracing::span::Id::from_u64(spans.len() as u64)
+        }
+
+        fn record(&self, _: &tracing::span::Id, _: &tracing::span::Record<'_>) {}
+
+        fn record_follows_from(&self, _: &tracing::span::Id, _: &tracing::span::Id) {}
+
+        fn event(&self, event: &tracing::Event<'_>) {
+            let mut fields = HashMap::new();
+            event.record(&mut FieldVisitor(&mut fields));
+            self.events.lock().unwrap().push(fields);
+        }
+
+        fn enter(&self, _: &tracing::span::Id) {}
+
+        fn exit(&self, _: &tracing::span::Id) {}
+    }
+
+    #[test]
+    fn test_nm_emits_reflection_event_with_fields() {
+        let mut nm = NelderMead::new(2, vec![false; 2]);
+        let config = make_solver_config_2d();
+        let sphere = |params: &HashMap<String, f64>| {
+            (params["x"] - 0.3).powi(2) + (params["y"] - 0.6).powi(2)
+        };
+        let trace = |eval_id: usize, params: HashMap<String, f64>| EvalTrace {
+            eval_id: eval_id as u64,
+            value: sphere(&params),
+            params,
+            cost: 1.0,
+            repeats: None,
+            penalized: false,
+            failed: false,
+            feasibility: None,
+            penalty: None,
+            fidelity: None,
+            kind: None,
+            source: None,
+        };
+        let mut history: Vec<EvalTrace> = (0..10)
+            .map(|i| {
+                let params = HashMap::from([
+                    ("x".to_string(), i as f64 / 10.0),
+                    ("y".to_string(), 0.9 - i as f64 / 20.0),
+                ]);
+                trace(i, params)
+            })
+            .collect();
+
+        let recorder = Recorder::default();
+        tracing::subscriber::with_default(recorder.clone(), || {
+            for _ in 0..30 {
+                match nm.step(&config, &history) {
+                    StrategyAction::Evaluate(candidates) => {
+                        for params in candidates {
+                            history.push(trace(history.len(), params));
+                        }
+                    }
+                    StrategyAction::Converged => break,
+                    StrategyAction::Wait => (),
+                }
+            }
+        });
+
+        assert!(recorder
+            .spans
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|s| s == "nelder_mead"));
+        let events = recorder.events.lock().unwrap();
+        let reflection = events
+            .iter()
+            .find(|fields| fields.get("state").map(String::as_str) == Some("reflection"))
+            .expect("a reflection event");
+        assert_eq!(reflection["message"], "nelder-mead transition");
+        for field in ["value", "accepted", "next", "best", "worst", "diameter"] {
+            assert!(reflection.contains_key(field), "missing field {}", field);
+        }
+        let diameter: f64 = reflection["diameter"].parse().unwrap();
+        assert!(diameter > 0.0);
+        let value: f64 = reflection["value"].parse().unwrap();
+        assert!(value.is_finite());
+    }
+
+    #[test]
+    fn test_nm_dense_step_matches_named_step() {
+        use crate::strategies::{dense_point, param_registry};
+
+        let mut config = make_solver_config_2d();
+        config.bounds.get_mut("y").unwrap().min = 1e-3;
+        config.bounds.get_mut("y").unwrap().scale = Scale::Log;
+        let registry = param_registry(&config);
+        let mut named = NelderMead::new(2, vec![false; 2]);
+        let mut dense = NelderMead::new(2, vec![false; 2]);
+
+        let mut history: Vec<EvalTrace> = (0..6)
+            .map(|i| EvalTrace {
+                eval_id: i as u64,
+                params: [
+                    ("x".to_string(), i as f64 / 6.0),
+                    ("y".to_string(), 0.5 / (i + 1) as f64),
+                ]
+                .into_iter()
+                .collect(),
+                value: (i as f64 / 6.0 - 0.4).powi(2),
+                cost: 1.0,
+                repeats: None,
+                penalized: false,
+                failed: false,
+                feasibility: None,
+                penalty: None,
+                fidelity: None,
+                kind: None,
+                source: None,
+            })
+            .collect();
+        let mut points: Vec<ParamVec> = history
+            .iter()
+            .map(|t| dense_point(&registry, &t.params))
+            .collect();
+
+        for _ in 0..40 {
+            let expected = named.step(&config, &history);
+            let view = DenseHistory {
+                registry: &registry,
+                traces: &history,
+                points: &points,
+            };
+            let actual = dense
+                .step_dense(&config, &view)
+                .unwrap()
+                .into_action(&registry);
+            let candidates = match (expected, actual) {
+                (StrategyAction::Evaluate(a), StrategyAction::Evaluate(b)) => {
+                    assert_eq!(a, b);
+                    a
+                }
+                (StrategyAction::Converged, StrategyAction::Converged) => break,
+                _ => panic!("named and dense steps disagree"),
+            };
+            for params in candidates {
+                let value = (params["x"] - 0.4).powi(2) + (params["y"].ln() + 3.0).powi(2);
+                points.push(dense_point(&registry, &params));
+                history.push(EvalTrace {
+                    eval_id: history.len() as u64,
+                    params,
+                    value,
+                    cost: 1.0,
+                    repeats: None,
+                    penalized: false,
+                    failed: false,
+                    feasibility: None,
+                    penalty: None,
+                    fidelity: None,
+                    kind: None,
+                    source: None,
+                });
+            }
+        }
+        assert!(history.len() > 20);
+    }
+
     #[test]
     fn test_nm_shrink_operation() {
         // Test Shrink state explicitly
@@ -1283,6 +2297,14 @@ mod tests {
                 .collect(),
                 value: 1.0 + i as f64 * 0.1,
                 cost: 1.0,
+                repeats: None,
+                penalized: false,
+                failed: false,
+                feasibility: None,
+                penalty: None,
+                fidelity: None,
+                kind: None,
+                source: None,
             })
             .collect();
 
@@ -1309,6 +2331,14 @@ mod tests {
                         params: c.clone(),
                         value: (x - 0.3).powi(2) + (y - 0.3).powi(2),
                         cost: 1.0,
+                        repeats: None,
+                        penalized: false,
+                        failed: false,
+                        feasibility: None,
+                        penalty: None,
+                        fidelity: None,
+                        kind: None,
+                        source: None,
                     });
                 }
             }
@@ -1339,6 +2369,14 @@ mod tests {
                 .collect(),
                 value: 0.5 + i as f64 * 0.1,
                 cost: 1.0,
+                repeats: None,
+                penalized: false,
+                failed: false,
+                feasibility: None,
+                penalty: None,
+                fidelity: None,
+                kind: None,
+                source: None,
             })
             .collect();
 
@@ -1360,6 +2398,14 @@ mod tests {
                         params: c.clone(),
                         value: 0.01 * (*x + *y), // Very low values
                         cost: 1.0,
+                        repeats: None,
+                        penalized: false,
+                        failed: false,
+                        feasibility: None,
+                        penalty: None,
+                        fidelity: None,
+                        kind: None,
+                        source: None,
                     });
                 }
             }
@@ -1381,6 +2427,14 @@ mod tests {
                     .collect(),
                 value: 0.5 + (i as f64 / 10.0 - 0.5).abs(),
                 cost: 1.0,
+                repeats: None,
+                penalized: false,
+                failed: false,
+                feasibility: None,
+                penalty: None,
+                fidelity: None,
+                kind: None,
+                source: None,
             })
             .collect();
 
@@ -1405,6 +2459,14 @@ mod tests {
                         params: c.clone(),
                         value: 0.8 + (*x + *y) * 0.1,
                         cost: 1.0,
+                        repeats: None,
+                        penalized: false,
+                        failed: false,
+                        feasibility: None,
+                        penalty: None,
+                        fidelity: None,
+                        kind: None,
+                        source: None,
                     });
                 }
             }
@@ -1445,6 +2507,14 @@ mod tests {
                 .collect(),
             value: 0.4,
             cost: 1.0,
+            repeats: None,
+            penalized: false,
+            failed: false,
+            feasibility: None,
+            penalty: None,
+            fidelity: None,
+            kind: None,
+            source: None,
         }];
 
         // Step through shrink
@@ -1484,6 +2554,14 @@ mod tests {
                 .collect(),
             value: 0.7, // Better than worst (0.9) -> accept
             cost: 1.0,
+            repeats: None,
+            penalized: false,
+            failed: false,
+            feasibility: None,
+            penalty: None,
+            fidelity: None,
+            kind: None,
+            source: None,
         }];
 
         let _ = nm.step(&config, &history);
@@ -1493,4 +2571,139 @@ mod tests {
                 || matches!(nm.state, NMState::CoordinatePrepass { .. })
         );
     }
+
+    #[test]
+    fn test_nm_noise_margin_blocks_marginal_expansion() {
+        // A reflection only 0.05 below the best vertex triggers expansion when
+        // comparisons are exact, but not when the observation noise is 0.1.
+        let run = |observation_noise: Option<f64>| {
+            let mut nm = NelderMead::new(2, vec![false; 2]);
+            nm.simplex = vec![
+                (1.0, vec![0.2, 0.2]),
+                (2.0, vec![0.6, 0.2]),
+                (3.0, vec![0.2, 0.6]),
+            ];
+            nm.state = NMState::Reflection {
+                centroid: vec![0.4, 0.2],
+                reflection: vec![0.6, 0.0],
+                best: 1.0,
+                second_worst: 2.0,
+                worst: 3.0,
+            };
+            let mut config = make_solver_config_2d();
+            config.observation_noise = observation_noise;
+            let history = vec![EvalTrace {
+                eval_id: 0,
+                params: [("x".to_string(), 0.6), ("y".to_string(), 0.0)]
+                    .into_iter()
+                    .collect(),
+                value: 0.95,
+                cost: 1.0,
+                repeats: None,
+                penalized: false,
+                failed: false,
+                feasibility: None,
+                penalty: None,
+                fidelity: None,
+                kind: None,
+                source: None,
+            }];
+            nm.step(&config, &history);
+            matches!(nm.state, NMState::Expansion { .. })
+        };
+
+        assert!(run(None));
+        assert!(!run(Some(0.1)));
+    }
+
+    #[test]
+    fn test_noise_margin_shrinks_with_repeats() {
+        let mut config = make_solver_config_2d();
+        let mut trace = EvalTrace {
+            eval_id: 0,
+            params: HashMap::new(),
+            value: 1.0,
+            cost: 1.0,
+            repeats: None,
+            penalized: false,
+            failed: false,
+            feasibility: None,
+            penalty: None,
+            fidelity: None,
+            kind: None,
+            source: None,
+        };
+        assert_eq!(noise_margin(&config, std::slice::from_ref(&trace)), 0.0);
+
+        config.observation_noise = Some(0.2);
+        assert!((noise_margin(&config, std::slice::from_ref(&trace)) - 0.2).abs() < 1e-12);
+
+        trace.repeats = Some(4);
+        assert!((noise_margin(&config, std::slice::from_ref(&trace)) - 0.1).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_nm_reflection_is_geometric_for_log_scale() {
+        let mut bounds = HashMap::new();
+        bounds.insert(
+            "lr".to_string(),
+            crate::config::Domain {
+                min: 1e-5,
+                max: 1e-1,
+                scale: crate::config::Scale::Log,
+                active_when: None,
+            },
+        );
+        let mut config = make_solver_config_2d();
+        config.bounds = bounds;
+        let keys = vec!["lr".to_string()];
+
+        let mut nm = NelderMead::new(1, vec![false]);
+        nm.update_dims(&config, &keys);
+        let point =
+            |lr: f64| nm.dict_to_vec(&[("lr".to_string(), lr)].into_iter().collect(), &keys);
+        nm.simplex = vec![(1.0, point(1e-3)), (2.0, point(1e-2))];
+        nm.state = NMState::SimplexBuild { evals_received: 1 };
+
+        let StrategyAction::Evaluate(candidates) = nm.step(&config, &[]) else {
+            panic!("expected a reflection candidate");
+        };
+        // Reflecting 1e-2 through 1e-3 lands on 1e-4 in log space; a linear
+        // reflection (-8e-3) would have been clamped to the lower bound.
+        let lr = candidates[0]["lr"];
+        assert!((lr.log10() + 4.0).abs() < 1e-9, "got {}", lr);
+    }
+
+    #[test]
+    fn test_nm_log_coordinates_round_trip() {
+        let mut config = make_solver_config_2d();
+        config.bounds.get_mut("y").unwrap().scale = crate::config::Scale::Log;
+        config.bounds.get_mut("y").unwrap().min = 1e-3;
+        let keys = vec!["x".to_string(), "y".to_string()];
+        let mut nm = NelderMead::new(2, vec![false; 2]);
+        nm.update_dims(&config, &keys);
+
+        let params: HashMap<String, f64> = [("x".to_string(), 0.3), ("y".to_string(), 0.01)]
+            .into_iter()
+            .collect();
+        let vec = nm.dict_to_vec(&params, &keys);
+        assert_eq!(vec[0], 0.3);
+        assert!((vec[1] - 1.0 / 3.0).abs() < 1e-12);
+        let back = nm.vec_to_dict(&vec, &keys);
+        assert_eq!(back["x"], 0.3);
+        assert!((back["y"] - 0.01).abs() < 1e-15);
+    }
+
+    #[test]
+    fn test_nm_fills_inactive_params_with_domain_midpoint() {
+        let mut config = make_solver_config_2d();
+        config.bounds.get_mut("y").unwrap().min = -4.0;
+        config.bounds.get_mut("y").unwrap().max = 2.0;
+        let keys = vec!["x".to_string(), "y".to_string()];
+        let mut nm = NelderMead::new(2, vec![false; 2]);
+        nm.update_dims(&config, &keys);
+
+        let params: HashMap<String, f64> = [("x".to_string(), 0.25)].into_iter().collect();
+        assert_eq!(nm.dict_to_vec(&params, &keys), vec![0.25, -1.0]);
+    }
 }
@@ -0,0 +1,652 @@
+//! Surrogate Suggestions
+//!
+//! Wraps another strategy: whenever it proposes a batch, fits a cubic RBF
+//! interpolant over the evaluated points in unit space and appends the point
+//! an [`Acquisition`] scores highest under it as one extra candidate (by
+//! default the interpolant's minimum). The extra point hedges the inner
+//! strategy's local moves; the inner strategy never sees it.
+
+use crate::artifact::EvalTrace;
+use crate::config::SolverConfig;
+use crate::rng::{derive_seed, get_rng_stream, SURROGATE_STREAM};
+use crate::strategies::acquisition::{
+    best_candidate, Acquisition, AcquisitionKind, Prediction, SurrogateModel,
+};
+use crate::strategies::{cmp_points, Strategy, StrategyAction};
+use rand::Rng;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+
+/// Points the interpolant is fitted on at most (the best ones are kept), so
+/// the dense solve stays cheap.
+const MAX_FIT_POINTS: usize = 200;
+
+/// Diagonal regularization of the RBF system, relative to unit values.
+const RIDGE: f64 = 1e-10;
+
+/// Random starting points of the minimum search, next to the best fitted
+/// points.
+const RANDOM_STARTS: usize = 8;
+/// Best fitted points used as starting points of the minimum search.
+const BEST_STARTS: usize = 3;
+/// Initial and final step of the compass search, in unit space.
+const SEARCH_STEP: f64 = 0.1;
+const SEARCH_MIN_STEP: f64 = 1e-4;
+
+/// Configuration for surrogate suggestions
+#[derive(Debug, Clone, PartialEq)]
+pub struct SurrogateConfig {
+    /// Evaluations needed before the first suggestion (at least `dim + 2`).
+    pub min_points: usize,
+    /// A minimum closer than this (unit-space distance) to an evaluated
+    /// point is not suggested: it would add little information.
+    pub min_distance: f64,
+    /// Score the suggestion maximizes. The interpolant carries no
+    /// uncertainty, so the default LCB is its minimum.
+    pub acquisition: AcquisitionKind,
+}
+
+impl Default for SurrogateConfig {
+    fn default() -> Self {
+        Self {
+            min_points: 10,
+            min_distance: 1e-3,
+            acquisition: AcquisitionKind::default(),
+        }
+    }
+}
+
+impl SurrogateConfig {
+    /// `strategy_params` key; a positive value enables surrogate suggestions.
+    pub const PARAM_KEY: &'static str = "surrogate";
+    pub const MIN_POINTS_KEY: &'static str = "surrogate_min_points";
+    pub const ACQUISITION_KEY: &'static str = "surrogate_acquisition";
+
+    /// Read `surrogate`, `surrogate_min_points` and `surrogate_acquisition`
+    /// from `strategy_params`.
+    /// `None` unless `surrogate` is positive.
+    pub fn from_config(config: &SolverConfig) -> Option<Self> {
+        if !config
+            .strategy_param(Self::PARAM_KEY)
+            .is_some_and(|flag| flag > 0.0)
+        {
+            return None;
+        }
+        let mut surrogate = Self::default();
+        if let Some(min_points) = config.strategy_param(Self::MIN_POINTS_KEY) {
+            if min_points >= 1.0 {
+                surrogate.min_points = min_points.round() as usize;
+            }
+        }
+        if let Some(acquisition) = config
+            .strategy_param(Self::ACQUISITION_KEY)
+            .and_then(AcquisitionKind::from_code)
+        {
+            surrogate.acquisition = acquisition;
+        }
+        Some(surrogate)
+    }
+}
+
+/// Cubic radial basis function interpolant with a linear tail:
+/// `s(x) = Σ λ_i |x - x_i|³ + c_0 + Σ c_j x_j`.
+pub struct RbfSurrogate {
+    centers: Vec<Vec<f64>>,
+    weights: Vec<f64>,
+    /// `c_0` followed by one coefficient per dimension.
+    tail: Vec<f64>,
+    /// Values are standardized before fitting; predictions are mapped back.
+    mean: f64,
+    scale: f64,
+}
+
+impl RbfSurrogate {
+    /// Interpolate `(point, value)` pairs. Points closer than 1e-9 to an
+    /// earlier one are dropped. `None` with fewer than `dim + 2` distinct
+    /// points or a singular system.
+    pub fn fit(data: &[(Vec<f64>, f64)]) -> Option<Self> {
+        let dim = data.first()?.0.len();
+        let mut centers: Vec<Vec<f64>> = Vec::new();
+        let mut values: Vec<f64> = Vec::new();
+        for (point, value) in data {
+            if centers.iter().all(|c| distance(c, point) > 1e-9) {
+                centers.push(point.clone());
+                values.push(*value);
+            }
+        }
+        let n = centers.len();
+        if n < dim + 2 {
+            return None;
+        }
+
+        let mean = values.iter().sum::<f64>() / n as f64;
+        let spread = (values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n as f64).sqrt();
+        let scale = if spread > 0.0 { spread } else { 1.0 };
+
+        // [Φ P; Pᵀ 0] [λ; c] = [y; 0]
+        let size = n + dim + 1;
+        let mut a = vec![vec![0.0; size]; size];
+        let mut b = vec![0.0; size];
+        for i in 0..n {
+            for j in 0..n {
+                a[i][j] = distance(&centers[i], &centers[j]).powi(3);
+            }
+            a[i][i] += RIDGE;
+            a[i][n] = 1.0;
+            a[n][i] = 1.0;
+            for k in 0..dim {
+                a[i][n + 1 + k] = centers[i][k];
+                a[n + 1 + k][i] = centers[i][k];
+            }
+            b[i] = (values[i] - mean) / scale;
+        }
+        let solution = solve(a, b)?;
+        Some(Self {
+            centers,
+            weights: solution[..n].to_vec(),
+            tail: solution[n..].to_vec(),
+            mean,
+            scale,
+        })
+    }
+
+    /// Interpolated value at `point`.
+    pub fn predict(&self, point: &[f64]) -> f64 {
+        let radial: f64 = self
+            .centers
+            .iter()
+            .zip(&self.weights)
+            .map(|(center, weight)| weight * distance(center, point).powi(3))
+            .sum();
+        let linear: f64 = self.tail[0]
+            + self.tail[1..]
+                .iter()
+                .zip(point)
+                .map(|(c, x)| c * x)
+                .sum::<f64>();
+        self.mean + self.scale * (radial + linear)
+    }
+
+    /// Minimum of the interpolant over the unit cube, by compass search from
+    /// each of `starts`. Returns the best point found and its predicted value.
+    pub fn argmin(&self, starts: &[Vec<f64>]) -> Option<(Vec<f64>, f64)> {
+        starts
+            .iter()
+            .map(|start| compass_search(start.clone(), |point| self.predict(point)))
+            .min_by(|a, b| a.1.total_cmp(&b.1).then_with(|| cmp_points(&a.0, &b.0)))
+    }
+}
+
+/// Local minimum of `objective` over the unit cube, by compass search from
+/// `point`. Returns the point and its objective.
+fn compass_search(mut point: Vec<f64>, objective: impl Fn(&[f64]) -> f64) -> (Vec<f64>, f64) {
+    let mut value = objective(&point);
+    let mut step = SEARCH_STEP;
+    while step > SEARCH_MIN_STEP {
+        let mut improved = false;
+        for k in 0..point.len() {
+            for direction in [1.0, -1.0] {
+                let mut trial = point.clone();
+                trial[k] = (trial[k] + direction * step).clamp(0.0, 1.0);
+                let trial_value = objective(&trial);
+                if trial_value < value {
+                    point = trial;
+                    value = trial_value;
+                    improved = true;
+                }
+            }
+        }
+        if !improved {
+            step /= 2.0;
+        }
+    }
+    (point, value)
+}
+
+/// The interpolant passes through every fitted point and carries no
+/// uncertainty, so its predictions have zero variance.
+impl SurrogateModel for RbfSurrogate {
+    fn posterior(&self, point: &[f64]) -> Prediction {
+        Prediction {
+            mean: self.predict(point),
+            variance: 0.0,
+        }
+    }
+}
+
+fn distance(a: &[f64], b: &[f64]) -> f64 {
+    a.iter()
+        .zip(b)
+        .map(|(x, y)| (x - y).powi(2))
+        .sum::<f64>()
+        .sqrt()
+}
+
+/// Solve `a x = b` by Gaussian elimination with partial pivoting.
+fn solve(mut a: Vec<Vec<f64>>, mut b: Vec<f64>) -> Option<Vec<f64>> {
+    let n = b.len();
+    for col in 0..n {
+        let pivot = (col..n).max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))?;
+        if a[pivot][col].abs() < 1e-14 {
+            return None;
+        }
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+        for row in col + 1..n {
+            let factor = a[row][col] / a[col][col];
+            if factor == 0.0 {
+                continue;
+            }
+            let (upper, lower) = a.split_at_mut(row);
+            for (x, p) in lower[0][col..].iter_mut().zip(&upper[col][col..]) {
+                *x -= factor * p;
+            }
+            b[row] -= factor * b[col];
+        }
+    }
+    let mut x = vec![0.0; n];
+    for row in (0..n).rev() {
+        let rest: f64 = (row + 1..n).map(|k| a[row][k] * x[k]).sum();
+        x[row] = (b[row] - rest) / a[row][row];
+    }
+    x.iter().all(|v| v.is_finite()).then_some(x)
+}
+
+/// Surrogate suggestions on top of an inner strategy.
+///
+/// Each batch of the inner strategy gets the interpolant's minimum appended.
+/// Evaluations of those extra points are removed from the history the inner
+/// strategy sees, so strategies that read their own last results (like
+/// Nelder-Mead) are unaffected. A suggestion is recognized once, when its
+/// evaluation first shows up in the history, and remembered by eval id; one
+/// that was never evaluated is forgotten when the next batch is handed out.
+pub struct Surrogate {
+    inner: Box<dyn Strategy>,
+    config: SurrogateConfig,
+    acquisition: Box<dyn Acquisition>,
+    /// Suggestions of the last batch whose evaluations have not shown up yet.
+    pending: Vec<HashMap<String, f64>>,
+    /// Eval ids of evaluated suggestions.
+    suggested_ids: HashSet<u64>,
+    /// History length already scanned for pending suggestions.
+    scanned: usize,
+}
+
+impl Surrogate {
+    pub fn new(inner: Box<dyn Strategy>, config: SurrogateConfig) -> Self {
+        Self {
+            inner,
+            acquisition: config.acquisition.build(),
+            config,
+            pending: Vec::new(),
+            suggested_ids: HashSet::new(),
+            scanned: 0,
+        }
+    }
+
+    /// Match the traces added since the last step against the pending
+    /// suggestions. A history shorter than the scanned prefix (a restart
+    /// segment or a compaction) is scanned again from the start.
+    fn record_evaluations(&mut self, config: &SolverConfig, history: &[EvalTrace]) {
+        if self.scanned > history.len() {
+            self.scanned = 0;
+        }
+        for trace in &history[self.scanned..] {
+            if self.pending.is_empty() {
+                break;
+            }
+            let matched = self.pending.iter().position(|point| {
+                config
+                    .bounds
+                    .keys()
+                    .all(|k| trace.params.get(k) == point.get(k))
+            });
+            if let Some(i) = matched {
+                self.pending.swap_remove(i);
+                self.suggested_ids.insert(trace.eval_id);
+            }
+        }
+        self.scanned = history.len();
+    }
+
+    /// `history` without the evaluations of suggested points.
+    fn inner_history<'a>(&self, history: &'a [EvalTrace]) -> Cow<'a, [EvalTrace]> {
+        if self.suggested_ids.is_empty() {
+            return Cow::Borrowed(history);
+        }
+        Cow::Owned(
+            history
+                .iter()
+                .filter(|trace| !self.suggested_ids.contains(&trace.eval_id))
+                .cloned()
+                .collect(),
+        )
+    }
+
+    /// The point the acquisition scores highest under the interpolant of
+    /// `history`, if there is enough data and it is not next to an evaluated
+    /// point. Each start is improved by compass search on the score first.
+    /// Parameters inactive at that point are left out, as they are from
+    /// every candidate.
+    fn suggest(
+        &self,
+        config: &SolverConfig,
+        history: &[EvalTrace],
+    ) -> Option<HashMap<String, f64>> {
+        let mut keys: Vec<&String> = config.bounds.keys().collect();
+        keys.sort();
+        let mut data: Vec<(Vec<f64>, f64)> = history
+            .iter()
+            .filter(|t| t.is_full_fidelity() && !t.penalized && !t.failed && t.value.is_finite())
+            .map(|t| {
+                let point = keys
+                    .iter()
+                    .map(|k| {
+                        t.params
+                            .get(*k)
+                            .map_or(0.5, |&v| config.bounds[*k].to_unit(v))
+                    })
+                    .collect();
+                (point, t.value)
+            })
+            .collect();
+        if data.len() < self.config.min_points.max(keys.len() + 2) {
+            return None;
+        }
+        data.sort_by(|a, b| a.1.total_cmp(&b.1).then_with(|| cmp_points(&a.0, &b.0)));
+        data.truncate(MAX_FIT_POINTS);
+        let rbf = RbfSurrogate::fit(&data)?;
+
+        let mut rng = get_rng_stream(
+            derive_seed(config.seed, SURROGATE_STREAM),
+            history.len() as u64,
+        );
+        let mut starts: Vec<Vec<f64>> = data
+            .iter()
+            .take(BEST_STARTS)
+            .map(|(point, _)| point.clone())
+            .collect();
+        starts.extend((0..RANDOM_STARTS).map(|_| (0..keys.len()).map(|_| rng.random()).collect()));
+        let incumbent = data[0].1;
+        let score = |point: &[f64]| -self.acquisition.score(rbf.posterior(point), incumbent);
+        let finishes: Vec<Vec<f64>> = starts
+            .into_iter()
+            .map(|start| compass_search(start, score).0)
+            .collect();
+        let best = best_candidate(&rbf, self.acquisition.as_ref(), &finishes, incumbent)?;
+        let suggestion = &finishes[best];
+        if data
+            .iter()
+            .any(|(point, _)| distance(point, suggestion) < self.config.min_distance)
+        {
+            return None;
+        }
+        Some(
+            config.active_params(
+                keys.iter()
+                    .zip(suggestion)
+                    .map(|(k, &unit)| ((*k).clone(), config.bounds[*k].from_unit(unit)))
+                    .collect(),
+            ),
+        )
+    }
+}
+
+impl Strategy for Surrogate {
+    fn batch_hint(&self) -> usize {
+        self.inner.batch_hint()
+    }
+
+    fn step(&mut self, config: &SolverConfig, history: &[EvalTrace]) -> StrategyAction {
+        self.record_evaluations(config, history);
+        let inner_history = self.inner_history(history);
+        let mut points = match self.inner.step(config, &inner_history) {
+            StrategyAction::Evaluate(points) => points,
+            action => return action,
+        };
+        self.pending.clear();
+        if let Some(candidate) = self.suggest(config, history) {
+            self.pending.push(candidate.clone());
+            points.push(candidate);
+        }
+        StrategyAction::Evaluate(points)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Domain, ProbeBudget, Scale};
+
+    fn objective(x: f64, y: f64) -> f64 {
+        (x - 0.3).powi(2) + 2.0 * (y - 0.6).powi(2) + 0.5 * (x - 0.3) * (y - 0.6)
+    }
+
+    /// Element `i` of the base-`base` Halton sequence.
+    fn halton(mut i: usize, base: usize) -> f64 {
+        let mut result = 0.0;
+        let mut f = 1.0;
+        while i > 0 {
+            f /= base as f64;
+            result += f * (i % base) as f64;
+            i /= base;
+        }
+        result
+    }
+
+    fn config() -> SolverConfig {
+        let bounds = ["x", "y"]
+            .into_iter()
+            .map(|k| {
+                (
+                    k.to_string(),
+                    Domain {
+                        min: 0.0,
+                        max: 1.0,
+                        scale: Scale::Linear,
+                        active_when: None,
+                    },
+                )
+            })
+            .collect();
+        SolverConfig {
+            seed: 7,
+            budget: 100,
+            bounds,
+            probe_ratio: ProbeBudget::Fixed(0.2),
+            strategy_params: None,
+            constraints: Vec::new(),
+            selection: Default::default(),
+            cost_budget: None,
+            observation_noise: None,
+            max_restarts: 0,
+            non_finite: Default::default(),
+            repeats: 0,
+            repeat_reducer: Default::default(),
+            objective_transform: None,
+            fixed: Default::default(),
+            target_value: None,
+            target_tol: 0.0,
+            history_cap: None,
+            invalid_params: Default::default(),
+            infeasibility_penalty: 0.0,
+            initial_points: Vec::new(),
+        }
+    }
+
+    fn trace(eval_id: u64, x: f64, y: f64) -> EvalTrace {
+        EvalTrace {
+            eval_id,
+            params: HashMap::from([("x".to_string(), x), ("y".to_string(), y)]),
+            value: objective(x, y),
+            cost: 1.0,
+            repeats: None,
+            penalized: false,
+            failed: false,
+            feasibility: None,
+            penalty: None,
+            fidelity: None,
+            kind: None,
+            source: None,
+        }
+    }
+
+    /// Proposes one fixed point per step and records the history it saw.
+    struct Fixed {
+        seen: std::sync::Arc<std::sync::Mutex<Vec<usize>>>,
+    }
+
+    impl Strategy for Fixed {
+        fn step(&mut self, _config: &SolverConfig, history: &[EvalTrace]) -> StrategyAction {
+            self.seen.lock().unwrap().push(history.len());
+            StrategyAction::Evaluate(vec![HashMap::from([
+                ("x".to_string(), 0.9),
+                ("y".to_string(), 0.9),
+            ])])
+        }
+    }
+
+    #[test]
+    fn test_rbf_minimum_near_optimum_after_15_points() {
+        let data: Vec<(Vec<f64>, f64)> = (1..=15)
+            .map(|i| {
+                let (x, y) = (halton(i, 2), halton(i, 3));
+                (vec![x, y], objective(x, y))
+            })
+            .collect();
+        let rbf = RbfSurrogate::fit(&data).unwrap();
+        for (point, value) in &data {
+            assert!((rbf.predict(point) - value).abs() < 1e-6);
+        }
+        let starts = vec![vec![0.5, 0.5], vec![0.1, 0.9], vec![0.9, 0.1]];
+        let (minimum, _) = rbf.argmin(&starts).unwrap();
+        let error = distance(&minimum, &[0.3, 0.6]);
+        assert!(error < 0.05, "minimum {:?} is {} away", minimum, error);
+    }
+
+    #[test]
+    fn test_surrogate_appends_candidate_hidden_from_inner() {
+        let config = config();
+        let seen = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let inner = Fixed { seen: seen.clone() };
+        let mut surrogate = Surrogate::new(Box::new(inner), SurrogateConfig::default());
+
+        let mut history: Vec<EvalTrace> = (1..=5)
+            .map(|i| trace(i as u64, halton(i, 2), halton(i, 3)))
+            .collect();
+        // Too little data for a fit: the inner batch passes through.
+        let StrategyAction::Evaluate(points) = surrogate.step(&config, &history) else {
+            panic!("expected a batch");
+        };
+        assert_eq!(points.len(), 1);
+
+        history.extend((6..=15).map(|i| trace(i as u64, halton(i, 2), halton(i, 3))));
+        let StrategyAction::Evaluate(points) = surrogate.step(&config, &history) else {
+            panic!("expected a batch");
+        };
+        assert_eq!(points.len(), 2);
+        let suggestion = &points[1];
+        assert!((suggestion["x"] - 0.3).abs() < 0.05 && (suggestion["y"] - 0.6).abs() < 0.05);
+
+        for (i, params) in points.into_iter().enumerate() {
+            let (x, y) = (params["x"], params["y"]);
+            history.push(trace(16 + i as u64, x, y));
+        }
+        surrogate.step(&config, &history);
+        // The inner strategy saw its own evaluation, not the suggestion.
+        assert_eq!(*seen.lock().unwrap(), vec![5, 15, 16]);
+    }
+
+    #[test]
+    fn test_unevaluated_suggestion_is_forgotten() {
+        let config = config();
+        let seen = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut surrogate = Surrogate::new(Box::new(Fixed { seen }), SurrogateConfig::default());
+        let mut history: Vec<EvalTrace> = (1..=15)
+            .map(|i| trace(i as u64, halton(i, 2), halton(i, 3)))
+            .collect();
+
+        for step in 0..3 {
+            let StrategyAction::Evaluate(points) = surrogate.step(&config, &history) else {
+                panic!("expected a batch");
+            };
+            assert_eq!(points.len(), 2);
+            assert_eq!(surrogate.pending.len(), 1);
+            // Only the inner strategy's point comes back.
+            history.push(trace(16 + step, points[0]["x"], points[0]["y"]));
+        }
+        assert!(surrogate.suggested_ids.is_empty());
+    }
+
+    #[test]
+    fn test_suggestion_leaves_out_inactive_params() {
+        let mut config = config();
+        // `z` only applies below y = 0.5, away from the optimum.
+        config.bounds.insert(
+            "z".to_string(),
+            Domain {
+                min: 0.0,
+                max: 1.0,
+                scale: Scale::Linear,
+                active_when: Some(std::collections::BTreeMap::from([(
+                    "y".to_string(),
+                    vec![0.0],
+                )])),
+            },
+        );
+        let seen = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let inner = Fixed { seen: seen.clone() };
+        let mut surrogate = Surrogate::new(Box::new(inner), SurrogateConfig::default());
+        let mut history: Vec<EvalTrace> = (1..=15)
+            .map(|i| {
+                let mut trace = trace(i as u64, halton(i, 2), halton(i, 3));
+                trace.params = config.active_params(
+                    trace
+                        .params
+                        .into_iter()
+                        .chain([("z".to_string(), halton(i, 5))])
+                        .collect(),
+                );
+                trace
+            })
+            .collect();
+
+        let StrategyAction::Evaluate(points) = surrogate.step(&config, &history) else {
+            panic!("expected a batch");
+        };
+        assert_eq!(points.len(), 2);
+        assert!(points[1]["y"] > 0.5);
+        assert!(!points[1].contains_key("z"));
+
+        for (i, params) in points.into_iter().enumerate() {
+            history.push(trace(16 + i as u64, params["x"], params["y"]));
+        }
+        surrogate.step(&config, &history);
+        // The suggestion's evaluation was recognized and hidden.
+        assert_eq!(*seen.lock().unwrap(), vec![15, 16]);
+    }
+
+    #[test]
+    fn test_expected_improvement_suggestion_from_config() {
+        let mut config = config();
+        config.strategy_params = Some(HashMap::from([
+            (SurrogateConfig::PARAM_KEY.to_string(), 1.0),
+            (SurrogateConfig::ACQUISITION_KEY.to_string(), 1.0),
+        ]));
+        let surrogate_config = SurrogateConfig::from_config(&config).unwrap();
+        assert_eq!(surrogate_config.acquisition, AcquisitionKind::Ei);
+
+        let seen = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut surrogate = Surrogate::new(Box::new(Fixed { seen }), surrogate_config);
+        let history: Vec<EvalTrace> = (1..=15)
+            .map(|i| trace(i as u64, halton(i, 2), halton(i, 3)))
+            .collect();
+        let StrategyAction::Evaluate(points) = surrogate.step(&config, &history) else {
+            panic!("expected a batch");
+        };
+        // Without predictive variance, EI is the improvement on the best
+        // value, so it also leads to the interpolant's minimum.
+        let suggestion = &points[1];
+        assert!((suggestion["x"] - 0.3).abs() < 0.05 && (suggestion["y"] - 0.6).abs() < 0.05);
+    }
+}
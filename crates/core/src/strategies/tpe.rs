@@ -18,12 +18,63 @@ pub enum BandwidthRule {
     Fixed,
 }
 
+impl BandwidthRule {
+    /// Decode `strategy_params.tpe_bandwidth`: 0 = Scott, 1 = Silverman,
+    /// 2 = fixed. Returns None for unknown codes.
+    pub fn from_code(code: f64) -> Option<Self> {
+        if code.fract() != 0.0 {
+            return None;
+        }
+        match code as i64 {
+            0 => Some(Self::Scott),
+            1 => Some(Self::Silverman),
+            2 => Some(Self::Fixed),
+            _ => None,
+        }
+    }
+}
+
+/// Transform of the objective values before TPE's good/bad split.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SplitTransform {
+    /// The best `ceil(n × gamma)` values are good.
+    #[default]
+    None,
+    /// Standardize values; those below the standard normal's `gamma`-quantile
+    /// are good. Invariant to shifting and scaling the objective.
+    ZScore,
+    /// Rank values, ties sharing their average rank; those ranked in the
+    /// best `gamma` fraction are good and ties are never split. Invariant to
+    /// any increasing transform of the objective.
+    Rank,
+}
+
+impl SplitTransform {
+    /// Decode `strategy_params.tpe_transform`: 0 = none, 1 = z-score,
+    /// 2 = rank. Returns None for unknown codes.
+    pub fn from_code(code: f64) -> Option<Self> {
+        if code.fract() != 0.0 {
+            return None;
+        }
+        match code as i64 {
+            0 => Some(Self::None),
+            1 => Some(Self::ZScore),
+            2 => Some(Self::Rank),
+            _ => None,
+        }
+    }
+}
+
 #[allow(dead_code)]
 pub struct TPE {
     dim: usize,
     gamma: f64,
     candidates: usize,
     pub bandwidth_rule: BandwidthRule,
+    /// Fraction of each parameter's range used as bandwidth under
+    /// [`BandwidthRule::Fixed`].
+    fixed_bandwidth: f64,
+    transform: SplitTransform,
 }
 
 impl TPE {
@@ -33,16 +84,202 @@ impl TPE {
             gamma: 0.25, // Top 25%
             candidates: 24,
             bandwidth_rule: BandwidthRule::Scott,
+            fixed_bandwidth: 0.1,
+            transform: SplitTransform::None,
+        }
+    }
+
+    /// `strategy_params` key for the fraction of history in the good set.
+    pub const QUANTILE_KEY: &'static str = "tpe_quantile";
+    /// `strategy_params` key for the [`SplitTransform`] code.
+    pub const TRANSFORM_KEY: &'static str = "tpe_transform";
+    /// `strategy_params` key for the [`BandwidthRule`] code.
+    pub const BANDWIDTH_KEY: &'static str = "tpe_bandwidth";
+    /// `strategy_params` key for the range fraction of the fixed bandwidth.
+    pub const FIXED_BANDWIDTH_KEY: &'static str = "tpe_fixed_bandwidth";
+    /// `strategy_params` key for the candidates drawn per step, which is
+    /// also the history needed before the densities are fitted.
+    pub const CANDIDATES_KEY: &'static str = "tpe_candidates";
+    /// Largest accepted `tpe_candidates`: each candidate costs a pass over
+    /// the history per parameter.
+    pub const MAX_CANDIDATES: usize = 10_000;
+
+    /// TPE configured from `strategy_params`; keys that are absent or
+    /// invalid keep the [`new`](Self::new) defaults.
+    pub fn from_config(dim: usize, config: &SolverConfig) -> Self {
+        let mut tpe = Self::new(dim);
+        if let Some(gamma) = config
+            .strategy_param(Self::QUANTILE_KEY)
+            .filter(|g| *g > 0.0 && *g < 1.0)
+        {
+            tpe.gamma = gamma;
+        }
+        if let Some(transform) = config
+            .strategy_param(Self::TRANSFORM_KEY)
+            .and_then(SplitTransform::from_code)
+        {
+            tpe.transform = transform;
+        }
+        if let Some(rule) = config
+            .strategy_param(Self::BANDWIDTH_KEY)
+            .and_then(BandwidthRule::from_code)
+        {
+            tpe.bandwidth_rule = rule;
+        }
+        if let Some(fraction) = config
+            .strategy_param(Self::FIXED_BANDWIDTH_KEY)
+            .filter(|f| *f > 0.0 && *f <= 1.0)
+        {
+            tpe.fixed_bandwidth = fraction;
+        }
+        if let Some(candidates) = config
+            .strategy_param(Self::CANDIDATES_KEY)
+            .filter(|c| *c >= 1.0 && *c <= Self::MAX_CANDIDATES as f64 && c.fract() == 0.0)
+        {
+            tpe.candidates = candidates as usize;
+        }
+        tpe
+    }
+
+    /// Reject TPE `strategy_params` that [`from_config`](Self::from_config)
+    /// would ignore: a `tpe_quantile` outside (0, 1), unknown
+    /// `tpe_transform` or `tpe_bandwidth` codes, a `tpe_fixed_bandwidth`
+    /// outside (0, 1] or a `tpe_candidates` that is not an integer in
+    /// [1, [`MAX_CANDIDATES`](Self::MAX_CANDIDATES)].
+    pub fn check(config: &SolverConfig) -> Result<(), String> {
+        if let Some(gamma) = config.strategy_param(Self::QUANTILE_KEY) {
+            if !(gamma > 0.0 && gamma < 1.0) {
+                return Err(format!(
+                    "strategy_params.{} must be in (0, 1), got {}",
+                    Self::QUANTILE_KEY,
+                    gamma
+                ));
+            }
+        }
+        if let Some(code) = config.strategy_param(Self::TRANSFORM_KEY) {
+            if SplitTransform::from_code(code).is_none() {
+                return Err(format!(
+                    "strategy_params.{} must be 0 (none), 1 (zscore) or 2 (rank), got {}",
+                    Self::TRANSFORM_KEY,
+                    code
+                ));
+            }
+        }
+        if let Some(code) = config.strategy_param(Self::BANDWIDTH_KEY) {
+            if BandwidthRule::from_code(code).is_none() {
+                return Err(format!(
+                    "strategy_params.{} must be 0 (scott), 1 (silverman) or 2 (fixed), got {}",
+                    Self::BANDWIDTH_KEY,
+                    code
+                ));
+            }
+        }
+        if let Some(fraction) = config.strategy_param(Self::FIXED_BANDWIDTH_KEY) {
+            if !(fraction > 0.0 && fraction <= 1.0) {
+                return Err(format!(
+                    "strategy_params.{} must be in (0, 1], got {}",
+                    Self::FIXED_BANDWIDTH_KEY,
+                    fraction
+                ));
+            }
+        }
+        match config.strategy_param(Self::CANDIDATES_KEY) {
+            Some(count)
+                if !(count >= 1.0
+                    && count <= Self::MAX_CANDIDATES as f64
+                    && count.fract() == 0.0) =>
+            {
+                Err(format!(
+                    "strategy_params.{} must be an integer in [1, {}], got {}",
+                    Self::CANDIDATES_KEY,
+                    Self::MAX_CANDIDATES,
+                    count
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Which of `values` (minimized) are good: at least 2 and at most all
+    /// but one. The rank split widens to the second-best tie group when
+    /// `gamma` selects fewer; otherwise a threshold that leaves too few on
+    /// either side falls back to the plain `ceil(n × gamma)` split.
+    pub fn partition(values: &[f64], gamma: f64, transform: SplitTransform) -> Vec<bool> {
+        let mut order: Vec<usize> = (0..values.len()).collect();
+        order.sort_by(|&a, &b| values[a].total_cmp(&values[b]));
+        Self::partition_ordered(values, &order, gamma, transform)
+    }
+
+    /// [`partition`](Self::partition) with the indices of `values` already
+    /// sorted by value in `order`.
+    fn partition_ordered(
+        values: &[f64],
+        order: &[usize],
+        gamma: f64,
+        transform: SplitTransform,
+    ) -> Vec<bool> {
+        let n = values.len();
+        if n == 0 {
+            return Vec::new();
         }
+        let by_count = || {
+            let count = ((n as f64 * gamma).ceil() as usize).max(2);
+            let mut good = vec![false; n];
+            for &i in order.iter().take(count) {
+                good[i] = true;
+            }
+            good
+        };
+
+        let good: Vec<bool> = match transform {
+            SplitTransform::None => return by_count(),
+            SplitTransform::ZScore => {
+                let finite: Vec<f64> = values.iter().copied().filter(|v| v.is_finite()).collect();
+                let mean = finite.iter().sum::<f64>() / finite.len().max(1) as f64;
+                let std = (finite.iter().map(|v| (v - mean).powi(2)).sum::<f64>()
+                    / finite.len().max(1) as f64)
+                    .sqrt();
+                if std <= 0.0 {
+                    return by_count();
+                }
+                let threshold = normal_quantile(gamma);
+                values
+                    .iter()
+                    .map(|v| (v - mean) / std <= threshold)
+                    .collect()
+            }
+            SplitTransform::Rank => {
+                // Average 1-based rank of each run of tied values.
+                let mut ranks = vec![0.0; n];
+                let mut start = 0;
+                while start < n {
+                    let mut end = start + 1;
+                    while end < n && values[order[end]] == values[order[start]] {
+                        end += 1;
+                    }
+                    let rank = (start + 1 + end) as f64 / 2.0;
+                    for &i in &order[start..end] {
+                        ranks[i] = rank;
+                    }
+                    start = end;
+                }
+                // Widen to the second-best tie group if needed, not split it.
+                let threshold = (gamma * n as f64).max(ranks[order[1.min(n - 1)]]);
+                ranks.iter().map(|&r| r <= threshold).collect()
+            }
+        };
+        let count = good.iter().filter(|&&g| g).count();
+        if count < 2 || count >= n {
+            return by_count();
+        }
+        good
     }
 
     /// Create TPE with a specific bandwidth rule
     pub fn with_bandwidth_rule(dim: usize, rule: BandwidthRule) -> Self {
         Self {
-            dim,
-            gamma: 0.25,
-            candidates: 24,
             bandwidth_rule: rule,
+            ..Self::new(dim)
         }
     }
 
@@ -70,7 +307,6 @@ impl TPE {
     /// Silverman's Rule bandwidth: σ = 0.9 × min(stddev, IQR/1.34) × n^(-1/5)
     ///
     /// More robust to outliers than Scott's Rule.
-    #[allow(dead_code)]
     pub fn silverman_bandwidth(values: &[f64]) -> f64 {
         if values.len() < 4 {
             return Self::scotts_bandwidth(values); // Fall back to Scott's
@@ -83,7 +319,7 @@ impl TPE {
 
         // Compute IQR
         let mut sorted = values.to_vec();
-        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        sorted.sort_by(|a, b| a.total_cmp(b));
         let q1_idx = values.len() / 4;
         let q3_idx = 3 * values.len() / 4;
         let iqr = sorted[q3_idx] - sorted[q1_idx];
@@ -96,7 +332,6 @@ impl TPE {
     }
 
     /// Fixed bandwidth: percentage of range (legacy behavior)
-    #[allow(dead_code)]
     pub fn fixed_bandwidth(range: f64, percentage: f64) -> f64 {
         (range * percentage).max(1e-6)
     }
@@ -106,7 +341,7 @@ impl TPE {
         match self.bandwidth_rule {
             BandwidthRule::Scott => Self::scotts_bandwidth(values),
             BandwidthRule::Silverman => Self::silverman_bandwidth(values),
-            BandwidthRule::Fixed => Self::fixed_bandwidth(range, 0.1),
+            BandwidthRule::Fixed => Self::fixed_bandwidth(range, self.fixed_bandwidth),
         }
     }
 
@@ -129,7 +364,23 @@ impl TPE {
     }
 }
 
+/// Inverse of the standard normal CDF (Abramowitz & Stegun 26.2.23, absolute
+/// error below 4.5e-4), for `p` in (0, 1).
+fn normal_quantile(p: f64) -> f64 {
+    if p > 0.5 {
+        return -normal_quantile(1.0 - p);
+    }
+    let t = (-2.0 * p.ln()).sqrt();
+    -(t - (2.515517 + 0.802853 * t + 0.010328 * t * t)
+        / (1.0 + 1.432788 * t + 0.189269 * t * t + 0.001308 * t * t * t))
+}
+
 impl Strategy for TPE {
+    /// Each candidate maximizes the acquisition over the full history.
+    fn batch_hint(&self) -> usize {
+        1
+    }
+
     fn step(&mut self, config: &SolverConfig, history: &[EvalTrace]) -> StrategyAction {
         if history.len() < self.candidates {
             // Not enough data to build model, fallback to random sampling
@@ -145,43 +396,51 @@ impl Strategy for TPE {
 
         let mut rng = get_rng(config.seed + history.len() as u64);
 
-        // 1. Sort by value
-        let mut sorted: Vec<_> = history.iter().collect();
-        sorted.sort_by(|a, b| a.value.partial_cmp(&b.value).unwrap());
-
-        let split_idx = (history.len() as f64 * self.gamma).ceil() as usize;
-        let split_idx = split_idx.max(2); // Min 2 good points
-        let (good, bad) = sorted.split_at(split_idx);
+        // 1. Split into good and bad by value, in canonical order so tied
+        // values land on the same side whatever order they were told in
+        let param_order = config.param_order();
+        let mut order: Vec<usize> = (0..history.len()).collect();
+        order.sort_by(|&a, &b| param_order.cmp_traces(&history[a], &history[b]));
+        let values: Vec<f64> = history.iter().map(|t| t.value).collect();
+        let is_good = Self::partition_ordered(&values, &order, self.gamma, self.transform);
+
+        // 2. For each param, the good and bad values (in canonical order)
+        // and the bandwidth of the 1D GMMs; traces where it was inactive
+        // (conditional parameters) carry no information.
+        let densities: Vec<_> = config
+            .bounds
+            .iter()
+            .map(|(name, domain)| {
+                let (mut good_vals, mut bad_vals) = (Vec::new(), Vec::new());
+                for &i in &order {
+                    if let Some(&value) = history[i].params.get(name) {
+                        if is_good[i] {
+                            good_vals.push(value);
+                        } else {
+                            bad_vals.push(value);
+                        }
+                    }
+                }
+                // Compute adaptive bandwidth using Scott's Rule (or selected rule)
+                let sigma = self.compute_bandwidth(&good_vals, domain.max - domain.min);
+                (name, domain, good_vals, bad_vals, sigma)
+            })
+            .collect();
 
-        // For each param, build 1D GMM
         let mut best_candidate = HashMap::new();
         let mut best_ei = -1.0;
 
-        let mut candidates_vec = Vec::new();
-
         for _ in 0..self.candidates {
             let mut candidate = HashMap::new();
             let mut log_l = 0.0;
             let mut log_g = 0.0;
 
-            for (name, domain) in &config.bounds {
-                // Collect values for this dimension
-                let good_vals: Vec<f64> = good
-                    .iter()
-                    .map(|t| *t.params.get(name).unwrap_or(&0.0))
-                    .collect();
-                let bad_vals: Vec<f64> = bad
-                    .iter()
-                    .map(|t| *t.params.get(name).unwrap_or(&0.0))
-                    .collect();
-
-                // Compute adaptive bandwidth using Scott's Rule (or selected rule)
-                let range = domain.max - domain.min;
-                let sigma = self.compute_bandwidth(&good_vals, range);
+            for (name, domain, good_vals, bad_vals, sigma) in &densities {
+                let sigma = *sigma;
 
                 // Sample from l(x) (Good)
-                let val = Self::sample_gmm(&mut rng, &good_vals, sigma, domain.min, domain.max);
-                candidate.insert(name.clone(), val);
+                let val = Self::sample_gmm(&mut rng, good_vals, sigma, domain.min, domain.max);
+                candidate.insert((*name).clone(), val);
 
                 // Compute Likelihoods
                 let l_prob: f64 = good_vals
@@ -205,7 +464,6 @@ impl Strategy for TPE {
 
             // EI ~ l(x) / g(x) -> log EI ~ log l - log g
             let ei = log_l - log_g;
-            candidates_vec.push(candidate.clone());
             if ei > best_ei || best_candidate.is_empty() {
                 best_ei = ei;
                 best_candidate = candidate;
@@ -341,6 +599,93 @@ mod tests {
         assert_eq!(bw_empty, 1.0, "Should return fallback 1.0 for empty data");
     }
 
+    #[test]
+    fn test_rank_partition_invariant_under_monotonic_rescaling() {
+        // Bimodal values with ties and an outlier.
+        let values = vec![
+            0.1, 0.1, 0.3, 5.0, 5.2, 0.2, 4.8, 0.1, 1e6, 5.1, 0.25, 4.9, 0.15, 5.0, 0.3, 0.2,
+        ];
+        let rescaled: Vec<f64> = values.iter().map(|v: &f64| 7.0 + 3.0 * v.ln()).collect();
+        for gamma in [0.1, 0.25, 0.4] {
+            let good = TPE::partition(&values, gamma, SplitTransform::Rank);
+            assert_eq!(good, TPE::partition(&rescaled, gamma, SplitTransform::Rank));
+            // Ties are never split across the two sets.
+            for (i, a) in values.iter().enumerate() {
+                for (j, b) in values.iter().enumerate() {
+                    if a == b {
+                        assert_eq!(good[i], good[j]);
+                    }
+                }
+            }
+        }
+        // The z-score split is invariant to affine rescaling.
+        let affine: Vec<f64> = values.iter().map(|v| -2.0 + 0.5 * v).collect();
+        assert_eq!(
+            TPE::partition(&values, 0.25, SplitTransform::ZScore),
+            TPE::partition(&affine, 0.25, SplitTransform::ZScore)
+        );
+    }
+
+    #[test]
+    fn test_gamma_moves_partition_boundary() {
+        let values: Vec<f64> = (0..40).map(|i| ((i * 17) % 40) as f64).collect();
+        let good_count = |gamma| {
+            TPE::partition(&values, gamma, SplitTransform::None)
+                .iter()
+                .filter(|&&g| g)
+                .count()
+        };
+        assert_eq!(good_count(0.15), 6);
+        assert_eq!(good_count(0.25), 10);
+        // The good set is always the best values, below the boundary.
+        let good = TPE::partition(&values, 0.15, SplitTransform::None);
+        for (value, good) in values.iter().zip(good) {
+            assert_eq!(good, *value < 6.0);
+        }
+    }
+
+    #[test]
+    fn test_from_config_reads_strategy_params() {
+        let mut config: SolverConfig = serde_json::from_value(serde_json::json!({
+            "seed": 42,
+            "budget": 100,
+            "bounds": {"x": {"min": 0.0, "max": 10.0}},
+        }))
+        .unwrap();
+        config.strategy_params = Some(HashMap::from([
+            (TPE::QUANTILE_KEY.to_string(), 0.2),
+            (TPE::BANDWIDTH_KEY.to_string(), 2.0),
+            (TPE::FIXED_BANDWIDTH_KEY.to_string(), 0.05),
+            (TPE::CANDIDATES_KEY.to_string(), 12.0),
+        ]));
+        assert!(TPE::check(&config).is_ok());
+        let tpe = TPE::from_config(1, &config);
+        assert_eq!(tpe.gamma, 0.2);
+        assert_eq!(tpe.bandwidth_rule, BandwidthRule::Fixed);
+        assert_eq!(tpe.candidates, 12);
+        assert!((tpe.compute_bandwidth(&[0.5], 10.0) - 0.5).abs() < 1e-12);
+
+        for (key, value) in [
+            (TPE::QUANTILE_KEY, 0.0),
+            (TPE::QUANTILE_KEY, 1.0),
+            (TPE::BANDWIDTH_KEY, 3.0),
+            (TPE::FIXED_BANDWIDTH_KEY, 0.0),
+            (TPE::CANDIDATES_KEY, 2.5),
+            (TPE::CANDIDATES_KEY, 1e12),
+        ] {
+            config.strategy_params = Some(HashMap::from([(key.to_string(), value)]));
+            let err = TPE::check(&config).unwrap_err();
+            assert!(err.contains(key), "{}", err);
+        }
+    }
+
+    #[test]
+    fn test_normal_quantile() {
+        assert!(normal_quantile(0.5).abs() < 1e-3);
+        assert!((normal_quantile(0.25) + 0.6745).abs() < 1e-3);
+        assert!((normal_quantile(0.975) - 1.96).abs() < 1e-3);
+    }
+
     #[test]
     fn test_tpe_pdf_function() {
         // Test the Gaussian PDF calculation
@@ -19,6 +19,14 @@ fn trace(value: f64) -> EvalTrace {
         params: HashMap::new(),
         value,
         cost: 1.0,
+        repeats: None,
+        penalized: false,
+        failed: false,
+        feasibility: None,
+        penalty: None,
+        fidelity: None,
+        kind: None,
+        source: None,
     }
 }
 
@@ -67,7 +75,11 @@ fn rastrigin_samples() -> Vec<EvalTrace> {
 
 #[test]
 fn test_variance_classifier_structured_low_cv() {
-    let classifier = VarianceClassifier { threshold: 2.0 };
+    let classifier = VarianceClassifier {
+        threshold: 2.0,
+        noise_std: 0.0,
+        trim_fraction: 0.0,
+    };
     let samples: Vec<EvalTrace> = (0..10).map(|i| trace(10.0 + (i as f64) * 0.01)).collect();
 
     let (landscape, cv) = classifier.classify(&samples);
@@ -81,7 +93,11 @@ fn test_variance_classifier_chaotic_high_cv() {
     // High coefficient of variation should be Chaotic
     // CV = stddev / mean. For extreme values we get CV > 1.0
     // Using a threshold of 1.0 as a reasonable cutoff
-    let classifier = VarianceClassifier { threshold: 1.0 };
+    let classifier = VarianceClassifier {
+        threshold: 1.0,
+        noise_std: 0.0,
+        trim_fraction: 0.0,
+    };
     let samples: Vec<EvalTrace> = vec![
         trace(0.01),
         trace(1000.0),
@@ -97,6 +113,25 @@ fn test_variance_classifier_chaotic_high_cv() {
     assert_eq!(landscape, Landscape::Chaotic);
 }
 
+#[test]
+fn test_variance_classifier_trim_ignores_single_outlier() {
+    // A crashed run recorded as a huge penalty among smooth values.
+    let mut samples: Vec<EvalTrace> = (0..20).map(|i| trace(10.0 + (i as f64) * 0.01)).collect();
+    samples.insert(7, trace(1e9));
+
+    let untrimmed = VarianceClassifier::default();
+    assert_eq!(untrimmed.classify(&samples).0, Landscape::Chaotic);
+
+    let trimmed = VarianceClassifier::default().with_trim(Some(0.05));
+    let (landscape, cv) = trimmed.classify(&samples);
+    assert_eq!(landscape, Landscape::Structured);
+    assert!(cv < 0.01, "cv = {}", cv);
+    assert_eq!(trimmed.classify(&samples), (landscape, cv));
+
+    // Too few values to trim: the outlier still counts.
+    assert_eq!(trimmed.classify(&samples[..8]).0, Landscape::Chaotic);
+}
+
 #[test]
 fn test_classifier_deterministic() {
     let classifier = VarianceClassifier::default();
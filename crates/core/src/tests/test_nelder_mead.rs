@@ -7,7 +7,7 @@
 //! - Probe seeding
 
 use crate::artifact::EvalTrace;
-use crate::config::{Domain, Scale, SolverConfig};
+use crate::config::{Domain, ProbeBudget, Scale, SolverConfig};
 use crate::strategies::nelder_mead::NelderMead;
 use crate::strategies::{Strategy, StrategyAction};
 use std::collections::HashMap;
@@ -24,6 +24,14 @@ fn trace(value: f64, x: f64, y: f64) -> EvalTrace {
         params,
         value,
         cost: 1.0,
+        repeats: None,
+        penalized: false,
+        failed: false,
+        feasibility: None,
+        penalty: None,
+        fidelity: None,
+        kind: None,
+        source: None,
     }
 }
 
@@ -36,6 +44,7 @@ fn test_config_2d() -> SolverConfig {
             min: -5.0,
             max: 5.0,
             scale: Scale::Linear,
+            active_when: None,
         },
     );
     bounds.insert(
@@ -44,6 +53,7 @@ fn test_config_2d() -> SolverConfig {
             min: -5.0,
             max: 5.0,
             scale: Scale::Linear,
+            active_when: None,
         },
     );
 
@@ -51,8 +61,24 @@ fn test_config_2d() -> SolverConfig {
         bounds,
         budget: 100,
         seed: 42,
-        probe_ratio: 0.2,
+        probe_ratio: ProbeBudget::Fixed(0.2),
         strategy_params: None,
+        constraints: Vec::new(),
+        selection: Default::default(),
+        cost_budget: None,
+        observation_noise: None,
+        max_restarts: 0,
+        non_finite: Default::default(),
+        repeats: 0,
+        repeat_reducer: Default::default(),
+        objective_transform: None,
+        fixed: Default::default(),
+        target_value: None,
+        target_tol: 0.0,
+        history_cap: None,
+        invalid_params: Default::default(),
+        infeasibility_penalty: 0.0,
+        initial_points: Vec::new(),
     }
 }
 
@@ -112,6 +138,30 @@ fn test_nelder_mead_deterministic() {
     }
 }
 
+#[test]
+fn test_nelder_mead_ties_independent_of_history_order() {
+    let config = test_config_2d();
+    let history = vec![
+        trace(1.0, 0.5, 0.0),
+        trace(1.0, 0.0, 0.5),
+        trace(1.0, -0.5, -0.5),
+        trace(2.0, 1.0, 1.0),
+    ];
+    let mut reversed = history.clone();
+    reversed.reverse();
+
+    let mut nm1 = NelderMead::new(2, vec![false; 2]);
+    let mut nm2 = NelderMead::new(2, vec![false; 2]);
+    let action1 = nm1.step(&config, &history);
+    let action2 = nm2.step(&config, &reversed);
+
+    assert_eq!(nm1.simplex, nm2.simplex);
+    match (action1, action2) {
+        (StrategyAction::Evaluate(c1), StrategyAction::Evaluate(c2)) => assert_eq!(c1, c2),
+        _ => panic!("Both should propose the same candidates"),
+    }
+}
+
 // ============================================================================
 // NELDER-MEAD OPERATION TESTS (Implementation complete)
 // ============================================================================
@@ -183,6 +233,7 @@ fn test_nelder_mead_shrink_calculation() {
 }
 
 #[test]
+#[allow(deprecated)]
 fn test_nelder_mead_convergence_detection() {
     // Should detect convergence when simplex diameter < ε
     let dim = 2;
@@ -203,6 +254,7 @@ fn test_nelder_mead_convergence_detection() {
 }
 
 #[test]
+#[allow(deprecated)]
 fn test_nelder_mead_no_convergence_when_spread() {
     // Should NOT converge when simplex is spread
     let dim = 2;
@@ -239,3 +291,53 @@ fn test_nelder_mead_seeded_from_probe_points() {
     assert_eq!(nm.simplex[1], seeds[1]);
     assert_eq!(nm.simplex[2], seeds[2]);
 }
+
+#[test]
+fn test_nelder_mead_periodic_optimum_across_boundary() {
+    // Angle in degrees with its optimum at 345: the basin straddles the ends
+    // of the domain, so the simplex has to work across the seam.
+    let mut config = test_config_2d();
+    config.bounds.insert(
+        "x".to_string(),
+        Domain {
+            min: 0.0,
+            max: 360.0,
+            scale: Scale::Periodic,
+            active_when: None,
+        },
+    );
+    config.bounds.insert(
+        "y".to_string(),
+        Domain {
+            min: 0.0,
+            max: 1.0,
+            scale: Scale::Linear,
+            active_when: None,
+        },
+    );
+    let objective = |x: f64, y: f64| 1.0 - (x - 345.0).to_radians().cos() + (y - 0.5).powi(2);
+
+    let mut history: Vec<EvalTrace> = [(10.0, 0.4), (30.0, 0.6), (90.0, 0.5), (200.0, 0.3)]
+        .into_iter()
+        .map(|(x, y)| trace(objective(x, y), x, y))
+        .collect();
+    let mut nm = NelderMead::new(2, vec![true, false]);
+    while history.len() < 150 {
+        let StrategyAction::Evaluate(points) = nm.step(&config, &history) else {
+            break;
+        };
+        for params in points {
+            let (x, y) = (params["x"], params["y"]);
+            assert!((0.0..360.0).contains(&x), "angle {} left the domain", x);
+            history.push(trace(objective(x, y), x, y));
+        }
+    }
+
+    let best = history
+        .iter()
+        .min_by(|a, b| a.value.total_cmp(&b.value))
+        .unwrap();
+    let angle = best.params["x"];
+    let distance = (angle - 345.0).abs().min(360.0 - (angle - 345.0).abs());
+    assert!(distance < 5.0, "best angle {} is not near 345", angle);
+}
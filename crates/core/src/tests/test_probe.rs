@@ -5,7 +5,7 @@
 //! - Deterministic sampling
 //! - Multi-scale coverage
 
-use crate::config::{Domain, Scale, SolverConfig};
+use crate::config::{Domain, ProbeBudget, Scale, SolverConfig};
 use crate::probe::{Probe, UniformProbe};
 use std::collections::HashMap;
 
@@ -18,6 +18,7 @@ fn test_config() -> SolverConfig {
             min: -5.0,
             max: 5.0,
             scale: Scale::Linear,
+            active_when: None,
         },
     );
 
@@ -25,8 +26,24 @@ fn test_config() -> SolverConfig {
         bounds,
         budget: 50,
         seed: 42,
-        probe_ratio: 0.2,
+        probe_ratio: ProbeBudget::Fixed(0.2),
         strategy_params: None,
+        constraints: Vec::new(),
+        selection: Default::default(),
+        cost_budget: None,
+        observation_noise: None,
+        max_restarts: 0,
+        non_finite: Default::default(),
+        repeats: 0,
+        repeat_reducer: Default::default(),
+        objective_transform: None,
+        fixed: Default::default(),
+        target_value: None,
+        target_tol: 0.0,
+        history_cap: None,
+        invalid_params: Default::default(),
+        infeasibility_penalty: 0.0,
+        initial_points: Vec::new(),
     }
 }
 
@@ -69,7 +86,7 @@ fn test_uniform_probe_sample_count() {
     let probe = UniformProbe;
 
     let samples = probe.sample(&config);
-    let expected = (config.budget as f64 * config.probe_ratio).ceil() as usize;
+    let expected = config.probe_count();
 
     assert_eq!(
         samples.len(),
@@ -148,3 +165,237 @@ fn test_prime_index_probe_respects_bounds_integration() {
         assert!(*x >= -5.0 && *x <= 5.0, "Sample should be within bounds");
     }
 }
+
+// ============================================================================
+// SOBOL PROBE TESTS
+// ============================================================================
+
+/// 4-D unit-cube config for discrepancy comparisons
+fn test_config_4d(budget: u64) -> SolverConfig {
+    let mut bounds = HashMap::new();
+    for name in ["a", "b", "c", "d"] {
+        bounds.insert(
+            name.to_string(),
+            Domain {
+                min: 0.0,
+                max: 1.0,
+                scale: Scale::Linear,
+                active_when: None,
+            },
+        );
+    }
+
+    SolverConfig {
+        bounds,
+        budget,
+        seed: 42,
+        probe_ratio: ProbeBudget::Fixed(0.2),
+        strategy_params: None,
+        constraints: Vec::new(),
+        selection: Default::default(),
+        cost_budget: None,
+        observation_noise: None,
+        max_restarts: 0,
+        non_finite: Default::default(),
+        repeats: 0,
+        repeat_reducer: Default::default(),
+        objective_transform: None,
+        fixed: Default::default(),
+        target_value: None,
+        target_tol: 0.0,
+        history_cap: None,
+        invalid_params: Default::default(),
+        infeasibility_penalty: 0.0,
+        initial_points: Vec::new(),
+    }
+}
+
+/// L2 star discrepancy (Warnock's closed form) of points in [0, 1]^d
+fn l2_star_discrepancy(points: &[Vec<f64>]) -> f64 {
+    let n = points.len() as f64;
+    let d = points[0].len() as i32;
+
+    let term1 = 3f64.powi(-d);
+    let term2: f64 = points
+        .iter()
+        .map(|p| p.iter().map(|x| (1.0 - x * x) / 2.0).product::<f64>())
+        .sum::<f64>()
+        * 2.0
+        / n;
+    let mut term3 = 0.0;
+    for p in points {
+        for q in points {
+            term3 += p
+                .iter()
+                .zip(q.iter())
+                .map(|(x, y)| 1.0 - x.max(*y))
+                .product::<f64>();
+        }
+    }
+    term3 /= n * n;
+
+    (term1 - term2 + term3).sqrt()
+}
+
+fn as_unit_points(samples: &[HashMap<String, f64>]) -> Vec<Vec<f64>> {
+    samples
+        .iter()
+        .map(|s| ["a", "b", "c", "d"].iter().map(|k| s[*k]).collect())
+        .collect()
+}
+
+#[test]
+fn test_sobol_probe_deterministic() {
+    use crate::probe::SobolProbe;
+
+    let config = test_config_4d(100);
+    let probe = SobolProbe::new();
+
+    let samples1 = probe.sample(&config);
+    let samples2 = probe.sample(&config);
+
+    assert_eq!(samples1, samples2, "Same seed should produce same samples");
+}
+
+#[test]
+fn test_sobol_probe_seed_changes_scramble() {
+    use crate::probe::SobolProbe;
+
+    let config_a = test_config_4d(100);
+    let mut config_b = test_config_4d(100);
+    config_b.seed = 7;
+
+    let probe = SobolProbe::new();
+    assert_ne!(probe.sample(&config_a), probe.sample(&config_b));
+}
+
+#[test]
+fn test_sobol_probe_respects_bounds() {
+    use crate::probe::SobolProbe;
+
+    let config = test_config();
+    let samples = SobolProbe::new().sample(&config);
+    let expected = config.probe_count();
+
+    assert_eq!(samples.len(), expected);
+    for sample in samples {
+        let x = sample.get("x").unwrap();
+        assert!(*x >= -5.0 && *x <= 5.0, "Sample should be within bounds");
+    }
+}
+
+#[test]
+fn test_sobol_probe_lower_discrepancy_than_uniform() {
+    // Scrambled Sobol should leave smaller gaps than uniform random in 4-D,
+    // averaged over several seeds to keep the comparison robust.
+    use crate::probe::SobolProbe;
+
+    let mut sobol_total = 0.0;
+    let mut uniform_total = 0.0;
+    for seed in 0..5 {
+        let mut config = test_config_4d(640); // 128 probe points
+        config.seed = seed;
+
+        let sobol = as_unit_points(&SobolProbe::new().sample(&config));
+        let uniform = as_unit_points(&UniformProbe.sample(&config));
+
+        sobol_total += l2_star_discrepancy(&sobol);
+        uniform_total += l2_star_discrepancy(&uniform);
+    }
+
+    assert!(
+        sobol_total < uniform_total * 0.5,
+        "Sobol discrepancy {} should be well below uniform {}",
+        sobol_total,
+        uniform_total
+    );
+}
+
+#[test]
+fn test_sobol_probe_high_dimension_fallback() {
+    // Dimensions beyond the direction-number table still produce in-bounds values
+    use crate::probe::SobolProbe;
+
+    let mut bounds = HashMap::new();
+    for i in 0..(SobolProbe::MAX_DIMS + 3) {
+        bounds.insert(
+            format!("p{:02}", i),
+            Domain {
+                min: -1.0,
+                max: 1.0,
+                scale: Scale::Linear,
+                active_when: None,
+            },
+        );
+    }
+    let config = SolverConfig {
+        bounds,
+        budget: 50,
+        seed: 3,
+        probe_ratio: ProbeBudget::Fixed(0.2),
+        strategy_params: None,
+        constraints: Vec::new(),
+        selection: Default::default(),
+        cost_budget: None,
+        observation_noise: None,
+        max_restarts: 0,
+        non_finite: Default::default(),
+        repeats: 0,
+        repeat_reducer: Default::default(),
+        objective_transform: None,
+        fixed: Default::default(),
+        target_value: None,
+        target_tol: 0.0,
+        history_cap: None,
+        invalid_params: Default::default(),
+        infeasibility_penalty: 0.0,
+        initial_points: Vec::new(),
+    };
+
+    for sample in SobolProbe::new().sample(&config) {
+        assert_eq!(sample.len(), SobolProbe::MAX_DIMS + 3);
+        for v in sample.values() {
+            assert!((-1.0..=1.0).contains(v));
+        }
+    }
+}
+
+#[test]
+fn test_successive_probe_batches_are_distinct_and_reproducible() {
+    use crate::probe::ProbeKind;
+
+    let mut config = test_config();
+    config.bounds.insert(
+        "y".to_string(),
+        Domain {
+            min: 1e-3,
+            max: 1.0,
+            scale: Scale::Log,
+            active_when: None,
+        },
+    );
+    for kind in [
+        ProbeKind::Uniform,
+        ProbeKind::PrimeIndex,
+        ProbeKind::PrimeSqrtSlopesRot,
+        ProbeKind::Sobol,
+    ] {
+        let probe = kind.build();
+        let first = probe.sample(&config);
+        assert_eq!(probe.sample_from(&config, 0), first, "{:?}", kind);
+
+        let second = probe.sample_from(&config, first.len());
+        assert!(!second.is_empty(), "{:?}", kind);
+        assert!(
+            second.iter().all(|point| !first.contains(point)),
+            "{:?} repeated a point from the first batch",
+            kind
+        );
+        assert_eq!(
+            probe.sample_from(&config, first.len()),
+            second,
+            "{:?}",
+            kind
+        );
+    }
+}
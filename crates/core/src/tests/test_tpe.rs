@@ -6,7 +6,7 @@
 //! - Deterministic sampling given seed
 
 use crate::artifact::EvalTrace;
-use crate::config::{Domain, Scale, SolverConfig};
+use crate::config::{Domain, ProbeBudget, Scale, SolverConfig};
 use crate::strategies::tpe::{BandwidthRule, TPE};
 use crate::strategies::{Strategy, StrategyAction};
 use std::collections::HashMap;
@@ -22,6 +22,14 @@ fn trace(value: f64, x: f64) -> EvalTrace {
         params,
         value,
         cost: 1.0,
+        repeats: None,
+        penalized: false,
+        failed: false,
+        feasibility: None,
+        penalty: None,
+        fidelity: None,
+        kind: None,
+        source: None,
     }
 }
 
@@ -34,6 +42,7 @@ fn test_config() -> SolverConfig {
             min: -5.0,
             max: 5.0,
             scale: Scale::Linear,
+            active_when: None,
         },
     );
 
@@ -41,8 +50,24 @@ fn test_config() -> SolverConfig {
         bounds,
         budget: 100,
         seed: 42,
-        probe_ratio: 0.2,
+        probe_ratio: ProbeBudget::Fixed(0.2),
         strategy_params: None,
+        constraints: Vec::new(),
+        selection: Default::default(),
+        cost_budget: None,
+        observation_noise: None,
+        max_restarts: 0,
+        non_finite: Default::default(),
+        repeats: 0,
+        repeat_reducer: Default::default(),
+        objective_transform: None,
+        fixed: Default::default(),
+        target_value: None,
+        target_tol: 0.0,
+        history_cap: None,
+        invalid_params: Default::default(),
+        infeasibility_penalty: 0.0,
+        initial_points: Vec::new(),
     }
 }
 
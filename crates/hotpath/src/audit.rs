@@ -4,8 +4,7 @@
 //! IX.2 - Events MUST include correlation IDs.
 
 use crossbeam_queue::ArrayQueue;
-use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 
 /// Audit policy defining how events are recorded.
 ///
@@ -88,6 +87,44 @@ pub enum EventType {
     Rollback,
     SafeModeEntered,
     SafeModeExited,
+    /// Homeostasis damped or restored the learning rate.
+    Homeostasis,
+}
+
+/// How important an event is to keep when the audit queue saturates.
+///
+/// Ordered from least to most important, so `Low < Normal < Critical`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AuditPriority {
+    /// Routine telemetry (digests); first to go under pressure.
+    Low,
+    /// Proposals and controller transitions.
+    Normal,
+    /// State changes (apply, rollback, SafeMode); evicted last.
+    Critical,
+}
+
+impl AuditPriority {
+    /// All priorities, lowest first.
+    pub const ALL: [AuditPriority; 3] = [Self::Low, Self::Normal, Self::Critical];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+impl EventType {
+    /// Priority used for prioritized draining and eviction.
+    pub fn priority(&self) -> AuditPriority {
+        match self {
+            EventType::Digest => AuditPriority::Low,
+            EventType::Proposal | EventType::Homeostasis => AuditPriority::Normal,
+            EventType::Apply
+            | EventType::Rollback
+            | EventType::SafeModeEntered
+            | EventType::SafeModeExited => AuditPriority::Critical,
+        }
+    }
 }
 
 /// Structured audit event (fixed-size, no heap allocation).
@@ -140,67 +177,167 @@ pub enum EnqueueResult {
     HighWaterMark,
     /// Queue is full (triggers SafeMode).
     Full,
+    /// Queue was full; a lower-priority event was dropped to make room.
+    Evicted,
 }
 
 /// Lock-free audit queue with drop counting.
 ///
 /// Constitution: VIII.5 - No blocking I/O in hot path.
 /// AC-17: When queue is full, adaptation halts; events are counted, never silently dropped.
+///
+/// Events sit in one bounded FIFO lane per [`AuditPriority`], so eviction
+/// pops the oldest event of a lower lane without touching the others. Each
+/// lane is allocated with the full capacity up front; a shared slot count
+/// bounds their total.
 pub struct AuditQueue {
-    queue: Arc<ArrayQueue<AuditEvent>>,
+    /// Events with their enqueue sequence number, one lane per priority,
+    /// lowest first.
+    lanes: [ArrayQueue<(u64, AuditEvent)>; 3],
+    /// Slots taken across all lanes. Reserved before an event is pushed and
+    /// released after it is popped, so the lanes never hold more.
+    len: AtomicUsize,
+    next_seq: AtomicU64,
     capacity: usize,
     high_water_mark: usize,
     /// Count of events that couldn't be enqueued (for monitoring).
     drop_count: AtomicU64,
+    /// Dropped events per `AuditPriority`, indexed lowest first.
+    dropped_by_priority: [AtomicU64; 3],
+    /// Count of events handed out by `drain`/`drain_prioritized`.
+    drained_count: AtomicU64,
 }
 
 impl AuditQueue {
     /// Create a new audit queue with given capacity.
     pub fn new(capacity: usize) -> Self {
         Self {
-            queue: Arc::new(ArrayQueue::new(capacity)),
+            lanes: std::array::from_fn(|_| ArrayQueue::new(capacity)),
+            len: AtomicUsize::new(0),
+            next_seq: AtomicU64::new(0),
             capacity,
             high_water_mark: (capacity * 80) / 100,
             drop_count: AtomicU64::new(0),
+            dropped_by_priority: Default::default(),
+            drained_count: AtomicU64::new(0),
         }
     }
 
+    fn record_drop(&self, priority: AuditPriority) {
+        self.drop_count.fetch_add(1, Ordering::Relaxed);
+        self.dropped_by_priority[priority.index()].fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Take a slot and push `event` into its lane, or hand it back if the
+    /// queue is full.
+    fn try_push(&self, event: AuditEvent) -> Result<EnqueueResult, AuditEvent> {
+        let Ok(len) = self
+            .len
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |len| {
+                (len < self.capacity).then_some(len + 1)
+            })
+        else {
+            return Err(event);
+        };
+        self.push_reserved(event);
+        Ok(if len + 1 >= self.high_water_mark {
+            EnqueueResult::HighWaterMark
+        } else {
+            EnqueueResult::Ok
+        })
+    }
+
+    /// Push `event` into a slot already taken for it. Its lane cannot be
+    /// full, since all lanes together hold at most `len` events.
+    fn push_reserved(&self, event: AuditEvent) {
+        let seq = self.next_seq.fetch_add(1, Ordering::Relaxed);
+        let _ = self.lanes[event.event_type.priority().index()].push((seq, event));
+    }
+
+    fn pop(&self, priority: AuditPriority) -> Option<(u64, AuditEvent)> {
+        let event = self.lanes[priority.index()].pop()?;
+        self.len.fetch_sub(1, Ordering::AcqRel);
+        Some(event)
+    }
+
     /// Enqueue an event (non-blocking).
     ///
     /// Returns Full if queue is at capacity. Never blocks.
     pub fn enqueue(&self, event: AuditEvent) -> EnqueueResult {
-        match self.queue.push(event) {
-            Ok(()) => {
-                if self.queue.len() >= self.high_water_mark {
-                    EnqueueResult::HighWaterMark
-                } else {
-                    EnqueueResult::Ok
-                }
-            }
-            Err(_) => {
-                self.drop_count.fetch_add(1, Ordering::Relaxed);
-                EnqueueResult::Full
+        self.try_push(event).unwrap_or_else(|event| {
+            self.record_drop(event.event_type.priority());
+            EnqueueResult::Full
+        })
+    }
+
+    /// Enqueue an event, evicting a lower-priority one if the queue is full.
+    ///
+    /// When full, the oldest queued event of the lowest priority below the new
+    /// event's is dropped (and counted) to make room, so Apply/Rollback events
+    /// are never lost while Digest events are still queued. Returns Full, and
+    /// drops the new event, only if nothing queued has a lower priority.
+    ///
+    /// Lock-free and allocation-free: the evicted event's slot passes to the
+    /// new one.
+    pub fn enqueue_or_evict(&self, event: AuditEvent) -> EnqueueResult {
+        let event = match self.try_push(event) {
+            Ok(result) => return result,
+            Err(event) => event,
+        };
+        let priority = event.event_type.priority();
+        for lower in AuditPriority::ALL.into_iter().take_while(|p| *p < priority) {
+            if self.lanes[lower.index()].pop().is_some() {
+                self.record_drop(lower);
+                self.push_reserved(event);
+                return EnqueueResult::Evicted;
             }
         }
+        self.record_drop(priority);
+        EnqueueResult::Full
     }
 
-    /// Drain events for async flush (cold path).
+    /// Drain events for async flush (cold path), in enqueue order.
     pub fn drain(&self) -> Vec<AuditEvent> {
-        let mut events = Vec::new();
-        while let Some(event) = self.queue.pop() {
-            events.push(event);
+        let mut events = Vec::with_capacity(self.len());
+        for priority in AuditPriority::ALL {
+            while let Some(event) = self.pop(priority) {
+                events.push(event);
+            }
+        }
+        events.sort_unstable_by_key(|&(seq, _)| seq);
+        self.drained_count
+            .fetch_add(events.len() as u64, Ordering::Relaxed);
+        events.into_iter().map(|(_, event)| event).collect()
+    }
+
+    /// Drain at most `max` events, highest priority first (cold path).
+    ///
+    /// Events of equal priority keep their queue order. Whatever is not
+    /// returned stays queued, so a slow consumer sees Apply/Rollback events
+    /// before the Digest backlog.
+    pub fn drain_prioritized(&self, max: usize) -> Vec<AuditEvent> {
+        let mut events = Vec::with_capacity(max.min(self.len()));
+        for priority in AuditPriority::ALL.into_iter().rev() {
+            while events.len() < max {
+                match self.pop(priority) {
+                    Some((_, event)) => events.push(event),
+                    None => break,
+                }
+            }
         }
+        self.drained_count
+            .fetch_add(events.len() as u64, Ordering::Relaxed);
         events
     }
 
     /// Current queue length.
     pub fn len(&self) -> usize {
-        self.queue.len()
+        self.len.load(Ordering::Acquire)
     }
 
     /// Check if queue is empty.
     pub fn is_empty(&self) -> bool {
-        self.queue.is_empty()
+        self.len() == 0
     }
 
     /// Queue capacity.
@@ -212,6 +349,16 @@ impl AuditQueue {
     pub fn drop_count(&self) -> u64 {
         self.drop_count.load(Ordering::Relaxed)
     }
+
+    /// Number of dropped events of the given priority (for monitoring).
+    pub fn dropped_count(&self, priority: AuditPriority) -> u64 {
+        self.dropped_by_priority[priority.index()].load(Ordering::Relaxed)
+    }
+
+    /// Number of events handed out by `drain` and `drain_prioritized`.
+    pub fn drained_count(&self) -> u64 {
+        self.drained_count.load(Ordering::Relaxed)
+    }
 }
 
 #[cfg(test)]
@@ -355,4 +502,136 @@ mod tests {
         let policy = AuditPolicy::default();
         assert_eq!(policy, AuditPolicy::InMemoryRequired);
     }
+
+    #[test]
+    fn test_event_priorities() {
+        assert_eq!(EventType::Digest.priority(), AuditPriority::Low);
+        assert_eq!(EventType::Proposal.priority(), AuditPriority::Normal);
+        assert_eq!(EventType::Apply.priority(), AuditPriority::Critical);
+        assert_eq!(EventType::Rollback.priority(), AuditPriority::Critical);
+        assert!(AuditPriority::Low < AuditPriority::Critical);
+    }
+
+    #[test]
+    fn test_full_queue_counts_drops_by_priority() {
+        let queue = AuditQueue::new(1);
+        queue.enqueue(AuditEvent::new(EventType::Apply, 0, 1, 1));
+
+        queue.enqueue(AuditEvent::new(EventType::Digest, 1, 1, 1));
+        queue.enqueue(AuditEvent::new(EventType::Proposal, 2, 1, 1));
+
+        assert_eq!(queue.drop_count(), 2);
+        assert_eq!(queue.dropped_count(AuditPriority::Low), 1);
+        assert_eq!(queue.dropped_count(AuditPriority::Normal), 1);
+        assert_eq!(queue.dropped_count(AuditPriority::Critical), 0);
+    }
+
+    #[test]
+    fn test_saturation_evicts_digest_before_critical() {
+        let queue = AuditQueue::new(4);
+        for i in 0..4 {
+            queue.enqueue(AuditEvent::new(EventType::Digest, i, 1, 1));
+        }
+
+        // Every critical event displaces a digest while any remain.
+        for i in 0..4 {
+            let event_type = if i % 2 == 0 {
+                EventType::Apply
+            } else {
+                EventType::Rollback
+            };
+            let result = queue.enqueue_or_evict(AuditEvent::new(event_type, 10 + i, 1, 1));
+            assert_eq!(result, EnqueueResult::Evicted);
+        }
+        assert_eq!(queue.dropped_count(AuditPriority::Low), 4);
+        assert_eq!(queue.dropped_count(AuditPriority::Critical), 0);
+
+        // No lower-priority victim left: a new digest is the one dropped,
+        // and a further critical event is refused rather than evicting a peer.
+        assert_eq!(
+            queue.enqueue_or_evict(AuditEvent::new(EventType::Digest, 20, 1, 1)),
+            EnqueueResult::Full
+        );
+        assert_eq!(
+            queue.enqueue_or_evict(AuditEvent::new(EventType::Apply, 21, 1, 1)),
+            EnqueueResult::Full
+        );
+        assert_eq!(queue.dropped_count(AuditPriority::Low), 5);
+        assert_eq!(queue.dropped_count(AuditPriority::Critical), 1);
+
+        let events = queue.drain();
+        assert_eq!(events.len(), 4);
+        assert!(events
+            .iter()
+            .all(|e| e.event_type.priority() == AuditPriority::Critical));
+        assert_eq!(
+            events.iter().map(|e| e.timestamp_us).collect::<Vec<_>>(),
+            vec![10, 11, 12, 13]
+        );
+    }
+
+    #[test]
+    fn test_evicts_oldest_of_lowest_priority() {
+        let queue = AuditQueue::new(3);
+        queue.enqueue(AuditEvent::new(EventType::Proposal, 1, 1, 1));
+        queue.enqueue(AuditEvent::new(EventType::Digest, 2, 1, 1));
+        queue.enqueue(AuditEvent::new(EventType::Digest, 3, 1, 1));
+
+        queue.enqueue_or_evict(AuditEvent::new(EventType::Apply, 4, 1, 1));
+
+        let stamps: Vec<u64> = queue.drain().iter().map(|e| e.timestamp_us).collect();
+        assert_eq!(stamps, vec![1, 3, 4]);
+    }
+
+    #[test]
+    fn test_drain_prioritized() {
+        let queue = AuditQueue::new(10);
+        queue.enqueue(AuditEvent::new(EventType::Digest, 1, 1, 1));
+        queue.enqueue(AuditEvent::new(EventType::Apply, 2, 1, 1));
+        queue.enqueue(AuditEvent::new(EventType::Proposal, 3, 1, 1));
+        queue.enqueue(AuditEvent::new(EventType::Rollback, 4, 1, 1));
+        queue.enqueue(AuditEvent::new(EventType::Digest, 5, 1, 1));
+
+        let first: Vec<u64> = queue
+            .drain_prioritized(3)
+            .iter()
+            .map(|e| e.timestamp_us)
+            .collect();
+        assert_eq!(first, vec![2, 4, 3]);
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue.drained_count(), 3);
+
+        let rest: Vec<u64> = queue.drain().iter().map(|e| e.timestamp_us).collect();
+        assert_eq!(rest, vec![1, 5]);
+        assert_eq!(queue.drained_count(), 5);
+    }
+
+    #[test]
+    fn test_concurrent_eviction_accounts_for_every_event() {
+        let queue = AuditQueue::new(8);
+        std::thread::scope(|scope| {
+            for thread in 0..4u64 {
+                let queue = &queue;
+                scope.spawn(move || {
+                    for i in 0..200 {
+                        let event_type = match (thread + i) % 3 {
+                            0 => EventType::Digest,
+                            1 => EventType::Proposal,
+                            _ => EventType::Apply,
+                        };
+                        queue.enqueue_or_evict(AuditEvent::new(event_type, i, thread, 1));
+                        if i % 50 == 0 {
+                            queue.drain_prioritized(2);
+                        }
+                    }
+                });
+            }
+        });
+
+        assert!(queue.len() <= queue.capacity());
+        let queued = queue.drain().len() as u64;
+        assert_eq!(queue.drained_count() + queue.drop_count(), 800);
+        assert!(queued <= 8);
+        assert!(queue.is_empty());
+    }
 }
@@ -4,8 +4,9 @@
 
 use smallvec::SmallVec;
 
+use std::collections::VecDeque;
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, Mutex, RwLock};
 
 /// Stable parameter identifier (u16 = up to 65K params).
 ///
@@ -22,16 +23,93 @@ pub fn param_vec(values: &[f64]) -> ParamVec {
     ParamVec::from_slice(values)
 }
 
+/// How a bounded parameter is mapped onto the unit interval.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ParamScale {
+    /// Equal real-space steps are equal unit-space steps.
+    #[default]
+    Linear,
+    /// Equal unit-space steps multiply the value by the same factor.
+    /// Requires `min > 0`.
+    Log,
+}
+
+/// Real-space range of a parameter.
+///
+/// A registry carrying domains hands out ParamVecs in unit space, so SPSA's
+/// fixed learning rate means the same thing for a 1e-4 learning rate and a
+/// 256-unit layer width.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ParamDomain {
+    pub min: f64,
+    pub max: f64,
+    pub scale: ParamScale,
+}
+
+impl ParamDomain {
+    /// Linear domain over [min, max].
+    pub fn linear(min: f64, max: f64) -> Self {
+        Self {
+            min,
+            max,
+            scale: ParamScale::Linear,
+        }
+    }
+
+    /// Log-scaled domain over [min, max] (min > 0).
+    pub fn log(min: f64, max: f64) -> Self {
+        Self {
+            min,
+            max,
+            scale: ParamScale::Log,
+        }
+    }
+
+    /// Map a real value to unit space (0 = min, 1 = max).
+    ///
+    /// Values outside the domain map outside [0, 1]; a degenerate domain maps
+    /// everything to 0.
+    pub fn normalize(&self, value: f64) -> f64 {
+        let (lo, hi, v) = match self.scale {
+            ParamScale::Linear => (self.min, self.max, value),
+            ParamScale::Log => (
+                self.min.ln(),
+                self.max.ln(),
+                value.max(f64::MIN_POSITIVE).ln(),
+            ),
+        };
+        if hi > lo {
+            (v - lo) / (hi - lo)
+        } else {
+            0.0
+        }
+    }
+
+    /// Map a unit-space value back to real space. Inverse of `normalize`.
+    pub fn denormalize(&self, unit: f64) -> f64 {
+        match self.scale {
+            ParamScale::Linear => self.min + unit * (self.max - self.min),
+            ParamScale::Log => (self.min.ln() + unit * (self.max.ln() - self.min.ln())).exp(),
+        }
+    }
+}
+
 /// Mapping between human-readable parameter names and dense IDs.
 ///
 /// Created once at initialization and immutable thereafter.
 /// Implemented without HashMap to satisfy Hot Path invariants.
+///
+/// Parameters given a [`ParamDomain`] are normalized to unit space by
+/// `to_param_vec` and mapped back to real space by `to_kv`; the rest pass
+/// through unchanged.
 #[derive(Clone, Debug)]
 pub struct ParamRegistry {
     // Sorted (Name, ParamId) dictionary for O(log N) lookup
     name_map: Vec<(String, ParamId)>,
     // Dense lookup by ID (index = ParamId)
     id_to_name: Vec<String>,
+    // Optional real-space domain by ID
+    domains: Vec<Option<ParamDomain>>,
 }
 
 impl ParamRegistry {
@@ -48,9 +126,47 @@ impl ParamRegistry {
         // Sort by name for binary search
         name_map.sort_by(|a, b| a.0.cmp(&b.0));
 
+        let domains = vec![None; id_to_name.len()];
         Self {
             name_map,
             id_to_name,
+            domains,
+        }
+    }
+
+    /// Attach a real-space domain to a parameter. Unknown names are ignored.
+    pub fn with_domain(mut self, name: &str, domain: ParamDomain) -> Self {
+        if let Some(id) = self.get_id(name) {
+            self.domains[id as usize] = Some(domain);
+        }
+        self
+    }
+
+    /// Real-space domain of a parameter, if one was attached.
+    pub fn domain(&self, id: ParamId) -> Option<&ParamDomain> {
+        self.domains.get(id as usize).and_then(|d| d.as_ref())
+    }
+
+    /// Whether any parameter carries a domain.
+    pub fn has_domains(&self) -> bool {
+        self.domains.iter().any(|d| d.is_some())
+    }
+
+    /// Map a real value of parameter `id` to unit space.
+    #[inline]
+    pub fn normalize(&self, id: ParamId, value: f64) -> f64 {
+        match self.domain(id) {
+            Some(domain) => domain.normalize(value),
+            None => value,
+        }
+    }
+
+    /// Map a unit-space value of parameter `id` back to real space.
+    #[inline]
+    pub fn denormalize(&self, id: ParamId, unit: f64) -> f64 {
+        match self.domain(id) {
+            Some(domain) => domain.denormalize(unit),
+            None => unit,
         }
     }
 
@@ -78,7 +194,8 @@ impl ParamRegistry {
     }
 
     /// Convert a named map (as slice) to a ParamVec.
-    /// Used at boundary.
+    /// Used at boundary. Values are normalized to unit space where the
+    /// parameter has a domain; missing names are zero-filled (in unit space).
     pub fn to_param_vec(&self, map: &[(String, f64)]) -> ParamVec {
         let mut vec = ParamVec::with_capacity(self.len());
         // Initialize with default (0.0?) or require all params?
@@ -96,12 +213,11 @@ impl ParamRegistry {
         // If we strictly ban HashMap types in signatures... use `&[(String, f64)]`.
 
         // Naive for now:
-        for name in &self.id_to_name {
+        for (id, name) in self.id_to_name.iter().enumerate() {
             let val = map
                 .iter()
                 .find(|(n, _)| n == name)
-                .map(|(_, v)| v)
-                .copied()
+                .map(|&(_, v)| self.normalize(id as ParamId, v))
                 .unwrap_or(0.0);
             vec.push(val);
         }
@@ -109,12 +225,13 @@ impl ParamRegistry {
     }
 
     /// Convert a ParamVec to a list of named values.
-    /// Used at boundary.
+    /// Used at boundary. Parameters with a domain are mapped back to real space.
     pub fn to_kv(&self, vec: &ParamVec) -> Vec<(String, f64)> {
         self.id_to_name
             .iter()
             .zip(vec.iter())
-            .map(|(name, &value)| (name.clone(), value))
+            .enumerate()
+            .map(|(id, (name, &value))| (name.clone(), self.denormalize(id as ParamId, value)))
             .collect()
     }
 }
@@ -145,6 +262,47 @@ impl ConfigSnapshot {
     }
 }
 
+/// A past generation paired with the objective observed while it was live.
+#[derive(Clone, Debug)]
+pub struct GenerationRecord {
+    /// Snapshot that was live for this generation.
+    pub snapshot: Arc<ConfigSnapshot>,
+    /// Mean objective recorded while the snapshot was live (lower is better).
+    pub objective: f64,
+    /// Number of objective samples folded into `objective`.
+    pub samples: u32,
+}
+
+impl GenerationRecord {
+    /// Generation of the recorded snapshot.
+    pub fn generation(&self) -> u64 {
+        self.snapshot.generation
+    }
+}
+
+/// Objective samples of the generation currently live, tagged with its
+/// snapshot so the mean is always taken over one generation's samples.
+#[derive(Default)]
+struct LiveGeneration {
+    snapshot: Option<Arc<ConfigSnapshot>>,
+    sum: f64,
+    samples: u32,
+}
+
+impl LiveGeneration {
+    /// Record for this generation, if it has any samples yet.
+    fn record(&self) -> Option<GenerationRecord> {
+        if self.samples == 0 {
+            return None;
+        }
+        Some(GenerationRecord {
+            snapshot: self.snapshot.clone()?,
+            objective: self.sum / self.samples as f64,
+            samples: self.samples,
+        })
+    }
+}
+
 /// Thread-safe atomic configuration.
 ///
 /// Constitution: II.18 - Atomic Configuration Contract
@@ -156,16 +314,33 @@ pub struct AtomicConfig {
     inner: RwLock<Arc<ConfigSnapshot>>,
     generation: AtomicU64,
     baseline: RwLock<Option<Arc<ConfigSnapshot>>>,
+    /// Closed generations, oldest first. Locked after `live`, never before.
+    history: RwLock<VecDeque<GenerationRecord>>,
+    history_capacity: usize,
+    live: Mutex<LiveGeneration>,
 }
 
 impl AtomicConfig {
     /// Create a new atomic config with initial parameters.
     pub fn new(params: ParamVec) -> Self {
+        Self::with_history(params, 0)
+    }
+
+    /// Create an atomic config that retains the last `capacity` generations
+    /// with their objectives for [`rollback_to_best`](Self::rollback_to_best).
+    ///
+    /// The ring is allocated up front so recording never reallocates.
+    /// Samples for the live generation accumulate under their own small
+    /// lock; the ring is only written when the generation changes.
+    pub fn with_history(params: ParamVec, capacity: usize) -> Self {
         let snapshot = Arc::new(ConfigSnapshot::new(params));
         Self {
             inner: RwLock::new(snapshot),
             generation: AtomicU64::new(0),
             baseline: RwLock::new(None),
+            history: RwLock::new(VecDeque::with_capacity(capacity)),
+            history_capacity: capacity,
+            live: Mutex::new(LiveGeneration::default()),
         }
     }
 
@@ -208,6 +383,77 @@ impl AtomicConfig {
         *self.inner.write().unwrap() = new_snapshot;
         Some(new_gen)
     }
+
+    /// Maximum number of generations retained for rollback-to-best.
+    pub fn history_capacity(&self) -> usize {
+        self.history_capacity
+    }
+
+    /// Number of generations currently retained.
+    pub fn history_len(&self) -> usize {
+        let live = self.live.lock().unwrap();
+        let history = self.history.read().unwrap();
+        history.len() + usize::from(live.samples > 0)
+    }
+
+    /// Record an objective observed under the current generation.
+    ///
+    /// Repeated samples for the same generation are averaged. When the ring
+    /// is full the oldest generation is evicted. Non-finite values and
+    /// configs created without history are ignored, as are samples taken
+    /// under a snapshot older than the live generation (a swap raced them).
+    pub fn record_objective(&self, objective: f64) {
+        if self.history_capacity == 0 || !objective.is_finite() {
+            return;
+        }
+        let current = self.snapshot();
+        let mut live = self.live.lock().unwrap();
+        let live_generation = live.snapshot.as_ref().map(|s| s.generation);
+        if live_generation.is_some_and(|g| g > current.generation) {
+            return;
+        }
+        if live_generation != Some(current.generation) {
+            let mut history = self.history.write().unwrap();
+            if let Some(record) = live.record() {
+                history.push_back(record);
+            }
+            // The live generation counts against the capacity too.
+            while history.len() >= self.history_capacity {
+                history.pop_front();
+            }
+            *live = LiveGeneration {
+                snapshot: Some(current),
+                sum: 0.0,
+                samples: 0,
+            };
+        }
+        live.sum += objective;
+        live.samples += 1;
+    }
+
+    /// Best retained generation (lowest objective).
+    ///
+    /// Ties go to the most recent generation.
+    pub fn best_generation(&self) -> Option<GenerationRecord> {
+        let live = self.live.lock().unwrap();
+        let history = self.history.read().unwrap();
+        history
+            .iter()
+            .cloned()
+            .chain(live.record())
+            .rev()
+            .min_by(|a, b| a.objective.total_cmp(&b.objective))
+    }
+
+    /// Roll back to the parameters of the best retained generation.
+    ///
+    /// Like [`rollback`](Self::rollback), this publishes a new generation
+    /// rather than reusing the old one. Returns the new generation, or None if
+    /// no objective has been recorded.
+    pub fn rollback_to_best(&self) -> Option<u64> {
+        let best = self.best_generation()?;
+        Some(self.swap(best.snapshot.params.clone()))
+    }
 }
 
 // Ensure AtomicConfig is Send + Sync (AC-10)
@@ -278,6 +524,91 @@ mod tests {
         assert!(result.is_none());
     }
 
+    #[test]
+    fn test_rollback_to_best_picks_lowest_objective() {
+        let config = AtomicConfig::with_history(ParamVec::from_slice(&[0.5]), 8);
+        config.record_objective(3.0);
+
+        config.swap(ParamVec::from_slice(&[0.6]));
+        config.record_objective(1.0);
+        config.record_objective(2.0); // averaged with 1.0 -> 1.5
+
+        config.swap(ParamVec::from_slice(&[0.7]));
+        config.record_objective(1.2);
+
+        config.swap(ParamVec::from_slice(&[0.8]));
+        config.record_objective(4.0);
+
+        let best = config.best_generation().unwrap();
+        assert_eq!(best.generation(), 2);
+        assert_eq!(best.objective, 1.2);
+
+        let gen = config.rollback_to_best().unwrap();
+        assert_eq!(gen, 4);
+        assert_eq!(config.generation(), 4);
+        assert_eq!(config.snapshot().params[0], 0.7);
+    }
+
+    #[test]
+    fn test_rollback_to_best_tie_prefers_most_recent() {
+        let config = AtomicConfig::with_history(ParamVec::from_slice(&[0.1]), 8);
+        config.record_objective(1.0);
+        config.swap(ParamVec::from_slice(&[0.2]));
+        config.record_objective(2.0);
+        config.swap(ParamVec::from_slice(&[0.3]));
+        config.record_objective(1.0);
+        config.swap(ParamVec::from_slice(&[0.4]));
+        config.record_objective(5.0);
+
+        assert_eq!(config.best_generation().unwrap().generation(), 2);
+        config.rollback_to_best().unwrap();
+        assert_eq!(config.snapshot().params[0], 0.3);
+    }
+
+    #[test]
+    fn test_generation_history_is_bounded() {
+        let config = AtomicConfig::with_history(ParamVec::from_slice(&[0.0]), 2);
+        config.record_objective(0.1);
+        for i in 1..=3 {
+            config.swap(ParamVec::from_slice(&[i as f64]));
+            config.record_objective(1.0 + i as f64);
+        }
+        assert_eq!(config.history_len(), 2);
+        // Generation 0 (objective 0.1) has been evicted.
+        assert_eq!(config.best_generation().unwrap().generation(), 2);
+    }
+
+    #[test]
+    fn test_record_objective_from_many_threads() {
+        let config = Arc::new(AtomicConfig::with_history(ParamVec::from_slice(&[0.5]), 4));
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let config = Arc::clone(&config);
+                std::thread::spawn(move || {
+                    for _ in 0..250 {
+                        config.record_objective(2.0);
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        let best = config.best_generation().unwrap();
+        assert_eq!(best.samples, 1000);
+        assert_eq!(best.objective, 2.0);
+        assert_eq!(config.history_len(), 1);
+    }
+
+    #[test]
+    fn test_rollback_to_best_without_history() {
+        let config = AtomicConfig::new(ParamVec::from_slice(&[0.5]));
+        config.record_objective(1.0);
+        assert_eq!(config.history_len(), 0);
+        assert!(config.rollback_to_best().is_none());
+        assert_eq!(config.generation(), 0);
+    }
+
     #[test]
     fn test_to_param_vec_and_to_kv() {
         let registry = ParamRegistry::new(["alpha", "beta"]);
@@ -295,6 +626,44 @@ mod tests {
         assert!((kv[0].1 - 1.5).abs() < 1e-10);
     }
 
+    #[test]
+    fn test_domains_normalize_at_boundary() {
+        let registry = ParamRegistry::new(["lr", "width", "raw"])
+            .with_domain("lr", ParamDomain::log(1e-4, 1e-1))
+            .with_domain("width", ParamDomain::linear(0.0, 256.0));
+        assert!(registry.has_domains());
+        assert!(registry.domain(2).is_none());
+
+        let map = vec![
+            ("lr".to_string(), 1e-3),
+            ("width".to_string(), 64.0),
+            ("raw".to_string(), 7.0),
+        ];
+        let unit = registry.to_param_vec(&map);
+        assert!((unit[0] - 1.0 / 3.0).abs() < 1e-12);
+        assert!((unit[1] - 0.25).abs() < 1e-12);
+        assert_eq!(unit[2], 7.0);
+
+        let kv = registry.to_kv(&unit);
+        assert!((kv[0].1 - 1e-3).abs() < 1e-15);
+        assert!((kv[1].1 - 64.0).abs() < 1e-12);
+        assert_eq!(kv[2].1, 7.0);
+    }
+
+    #[test]
+    fn test_unit_delta_on_log_param_is_multiplicative() {
+        // Three decades: a unit step of 0.1 is 10^0.3 in real space.
+        let registry = ParamRegistry::new(["lr"]).with_domain("lr", ParamDomain::log(1e-4, 1e-1));
+        let mut unit = registry.to_param_vec(&[("lr".to_string(), 1e-3)]);
+        unit[0] += 0.1;
+        let stepped = registry.to_kv(&unit)[0].1;
+        assert!((stepped / 1e-3 - 10f64.powf(0.3)).abs() < 1e-9);
+
+        unit[0] -= 0.2;
+        let stepped = registry.to_kv(&unit)[0].1;
+        assert!((1e-3 / stepped - 10f64.powf(0.3)).abs() < 1e-9);
+    }
+
     #[test]
     fn test_param_vec_helper() {
         let pv = param_vec(&[1.0, 2.0, 3.0]);
@@ -21,9 +21,17 @@ pub enum SafeModeReason {
 /// Exit condition for SafeMode.
 #[derive(Clone, Debug)]
 pub enum SafeModeExit {
-    Timer { remaining_us: u64 },
+    Timer {
+        remaining_us: u64,
+    },
     ManualReset,
-    ObjectiveRecovery { required_improvement: f64 },
+    /// Exit once an objective of at most `entry_objective -
+    /// required_improvement` is recorded, or when the timer runs out.
+    ObjectiveRecovery {
+        required_improvement: f64,
+        entry_objective: f64,
+        remaining_us: u64,
+    },
 }
 
 /// SafeMode latch state.
@@ -98,7 +106,8 @@ impl ControlSafety {
     pub fn try_exit_safe_mode(&mut self, now_us: u64) -> bool {
         if let Some(ref mode) = self.safe_mode {
             match &mode.exit_condition {
-                SafeModeExit::Timer { remaining_us } => {
+                SafeModeExit::Timer { remaining_us }
+                | SafeModeExit::ObjectiveRecovery { remaining_us, .. } => {
                     let elapsed = now_us.saturating_sub(mode.entered_at_us);
                     if elapsed >= *remaining_us {
                         self.safe_mode = None;
@@ -108,9 +117,6 @@ impl ControlSafety {
                 SafeModeExit::ManualReset => {
                     // Requires explicit call to reset
                 }
-                SafeModeExit::ObjectiveRecovery { .. } => {
-                    // Checked separately
-                }
             }
         }
         false
@@ -212,23 +218,57 @@ impl ControlSafety {
     }
 
     /// Record an objective value for regression detection.
+    ///
+    /// Under `Guardrails::regression_recovery`, this is also where SafeMode
+    /// entered for a regression ends early: a value far enough below the one
+    /// it was entered at clears it.
     pub fn record_objective(&mut self, value: f64, now_us: u64) {
         if let Some(last) = self.last_objective {
             // Worsening = higher value (assuming minimization)
             if value > last + 0.01 {
                 self.consecutive_regressions += 1;
                 if self.consecutive_regressions >= self.guardrails.regression_count_limit {
-                    self.enter_safe_mode(
-                        SafeModeReason::ObjectiveRegression,
-                        now_us,
-                        self.guardrails.cooldown_after_flip_us,
-                    );
+                    self.enter_regression_safe_mode(value, now_us);
                 }
             } else {
                 self.consecutive_regressions = 0;
             }
         }
         self.last_objective = Some(value);
+
+        if let Some(SafeMode {
+            exit_condition:
+                SafeModeExit::ObjectiveRecovery {
+                    required_improvement,
+                    entry_objective,
+                    ..
+                },
+            ..
+        }) = self.safe_mode
+        {
+            if value <= entry_objective - required_improvement {
+                self.safe_mode = None;
+            }
+        }
+    }
+
+    /// Enter SafeMode for an objective regression to `value`, with the
+    /// regression cooldown and, if configured, the recovery exit.
+    fn enter_regression_safe_mode(&mut self, value: f64, now_us: u64) {
+        let cooldown_us = self
+            .guardrails
+            .regression_cooldown_us
+            .unwrap_or(self.guardrails.cooldown_after_flip_us);
+        self.enter_safe_mode(SafeModeReason::ObjectiveRegression, now_us, cooldown_us);
+        if let (Some(required_improvement), Some(mode)) =
+            (self.guardrails.regression_recovery, self.safe_mode.as_mut())
+        {
+            mode.exit_condition = SafeModeExit::ObjectiveRecovery {
+                required_improvement,
+                entry_objective: value,
+                remaining_us: cooldown_us,
+            };
+        }
     }
 }
 
@@ -340,6 +380,81 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_regression_safe_mode_exits_on_recovery() {
+        let mut cs = ControlSafety::new(
+            Guardrails {
+                regression_count_limit: 2,
+                regression_cooldown_us: Some(1_000_000),
+                regression_recovery: Some(0.3),
+                ..Default::default()
+            },
+            1,
+        );
+
+        cs.record_objective(0.5, 1000);
+        cs.record_objective(0.6, 2000);
+        cs.record_objective(0.9, 3000); // → SafeMode at 0.9
+        assert!(matches!(
+            cs.safe_mode().unwrap().exit_condition,
+            SafeModeExit::ObjectiveRecovery { entry_objective, .. } if entry_objective == 0.9
+        ));
+
+        // Improving, but not yet 0.3 below the entry value.
+        cs.record_objective(0.8, 4000);
+        cs.record_objective(0.65, 5000);
+        assert!(cs.is_safe_mode());
+        assert!(!cs.try_exit_safe_mode(6000));
+
+        cs.record_objective(0.55, 7000);
+        assert!(!cs.is_safe_mode());
+    }
+
+    #[test]
+    fn test_regression_safe_mode_cooldown_still_applies() {
+        let mut cs = ControlSafety::new(
+            Guardrails {
+                regression_count_limit: 1,
+                regression_cooldown_us: Some(500),
+                regression_recovery: Some(10.0),
+                ..Default::default()
+            },
+            1,
+        );
+
+        cs.record_objective(0.5, 1000);
+        cs.record_objective(0.7, 2000);
+        assert!(cs.is_safe_mode());
+        assert!(!cs.try_exit_safe_mode(2400));
+        assert!(cs.try_exit_safe_mode(2500));
+    }
+
+    #[test]
+    fn test_record_regression_value_uses_tail() {
+        use crate::telemetry::{Reducer, TelemetryDigest};
+
+        let mut cs = ControlSafety::new(
+            Guardrails {
+                regression_count_limit: 2,
+                cooldown_after_flip_us: 1000,
+                ..Default::default()
+            },
+            1,
+        );
+
+        // Every window averages 1.0, but its top two samples keep growing.
+        let window = |tail: f64| {
+            let mut samples = vec![(20.0 - 2.0 * tail) / 18.0; 18];
+            samples.extend([tail, tail]);
+            TelemetryDigest::from_samples(&samples, Reducer::Mean)
+        };
+        cs.record_objective(window(1.0).regression_value(), 1000);
+        cs.record_objective(window(2.0).regression_value(), 2000);
+        assert!(!cs.is_safe_mode());
+        cs.record_objective(window(3.0).regression_value(), 3000);
+        assert!(cs.is_safe_mode());
+    }
+
     #[test]
     fn test_record_objective_improvement_resets() {
         let mut cs = ControlSafety::new(
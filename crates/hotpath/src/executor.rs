@@ -3,10 +3,11 @@
 //! Constitution: II.17 - All updates MUST pass through SafetyExecutor.
 
 use crate::{
-    config_atomic::{AtomicConfig, ConfigSnapshot, ParamId, ParamVec},
+    config_atomic::{AtomicConfig, ConfigSnapshot, ParamId, ParamRegistry, ParamVec},
     control_safety::ControlSafety,
     proposer::Proposal,
 };
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use std::time::Instant;
 
@@ -49,6 +50,51 @@ pub enum Violation {
     },
     AuditQueueFull,
     NoBaseline,
+    /// The proposal was computed against a config generation that is no
+    /// longer live (see `Guardrails::max_generation_lag`).
+    StaleProposal {
+        expected_gen: u64,
+        actual_gen: u64,
+    },
+}
+
+impl Violation {
+    /// Variant names, indexed by [`kind_index`](Self::kind_index).
+    pub const KINDS: [&'static str; 11] = [
+        "DeltaTooLarge",
+        "RateLimitExceeded",
+        "OutOfBounds",
+        "UnknownParameter",
+        "Thrashing",
+        "BudgetExhausted",
+        "ObjectiveRegression",
+        "ConstraintViolation",
+        "AuditQueueFull",
+        "NoBaseline",
+        "StaleProposal",
+    ];
+
+    /// Dense index of the variant, for per-kind counters.
+    pub fn kind_index(&self) -> usize {
+        match self {
+            Self::DeltaTooLarge { .. } => 0,
+            Self::RateLimitExceeded { .. } => 1,
+            Self::OutOfBounds { .. } => 2,
+            Self::UnknownParameter { .. } => 3,
+            Self::Thrashing { .. } => 4,
+            Self::BudgetExhausted { .. } => 5,
+            Self::ObjectiveRegression { .. } => 6,
+            Self::ConstraintViolation { .. } => 7,
+            Self::AuditQueueFull => 8,
+            Self::NoBaseline => 9,
+            Self::StaleProposal { .. } => 10,
+        }
+    }
+
+    /// Variant name, e.g. `"DeltaTooLarge"`.
+    pub fn kind(&self) -> &'static str {
+        Self::KINDS[self.kind_index()]
+    }
 }
 
 /// Receipt from successful apply.
@@ -65,8 +111,22 @@ pub struct RollbackReceipt {
     pub reason: String,
 }
 
-/// Guardrails configuration.
+/// A delta component changed by [`SafetyExecutor::clamp_valid_subset`].
 #[derive(Clone, Debug)]
+pub struct DeltaAdjustment {
+    pub param_id: ParamId,
+    /// Component as proposed.
+    pub requested: f64,
+    /// Component after clamping (0.0 if it was zeroed).
+    pub applied: f64,
+    /// Violations the requested component triggered.
+    pub violations: Vec<Violation>,
+}
+
+/// Guardrails configuration. Fields missing when deserializing take their
+/// [`Default`] (balanced) value.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Guardrails {
     /// Maximum delta per parameter per step (fraction).
     pub max_delta_per_step: f64,
@@ -82,8 +142,19 @@ pub struct Guardrails {
     pub max_cumulative_delta_per_minute: f64,
     /// Consecutive regressions before SafeMode.
     pub regression_count_limit: u32,
-    /// Per-parameter bounds: (min, max).
+    /// Cooldown of SafeMode entered for objective regression (microseconds);
+    /// `None` uses `cooldown_after_flip_us`.
+    pub regression_cooldown_us: Option<u64>,
+    /// Leave SafeMode entered for objective regression before its cooldown
+    /// ends once the objective falls this far below its value at entry.
+    /// `None` waits out the cooldown.
+    pub regression_recovery: Option<f64>,
+    /// Per-parameter bounds: (min, max), in real space (see
+    /// [`SafetyExecutor::with_registry`]).
     pub bounds: Option<Vec<(f64, f64)>>,
+    /// Generations the live config may have advanced past the one a proposal
+    /// was computed against before it is rejected as stale.
+    pub max_generation_lag: u64,
 }
 
 impl Default for Guardrails {
@@ -96,7 +167,10 @@ impl Default for Guardrails {
             cooldown_after_flip_us: 30_000_000,
             max_cumulative_delta_per_minute: 0.5,
             regression_count_limit: 5,
+            regression_cooldown_us: None,
+            regression_recovery: None,
             bounds: None,
+            max_generation_lag: 0,
         }
     }
 }
@@ -111,7 +185,10 @@ impl Guardrails {
             cooldown_after_flip_us: 60_000_000,
             max_cumulative_delta_per_minute: 0.25,
             regression_count_limit: 3,
+            regression_cooldown_us: None,
+            regression_recovery: None,
             bounds: None,
+            max_generation_lag: 0,
         }
     }
 
@@ -128,7 +205,10 @@ impl Guardrails {
             cooldown_after_flip_us: 10_000_000,
             max_cumulative_delta_per_minute: 1.0,
             regression_count_limit: 8,
+            regression_cooldown_us: None,
+            regression_recovery: None,
             bounds: None,
+            max_generation_lag: 1,
         }
     }
 }
@@ -200,6 +280,7 @@ pub struct SafetyExecutor {
     control_safety: ControlSafety,
     last_apply_us: u64,
     update_count_window: Vec<u64>,
+    registry: Option<ParamRegistry>,
 }
 
 impl SafetyExecutor {
@@ -212,39 +293,150 @@ impl SafetyExecutor {
             control_safety: ControlSafety::new(guardrails, num_params),
             last_apply_us: 0,
             update_count_window: Vec::new(),
+            registry: None,
+        }
+    }
+
+    /// Treat the config as unit-space values described by `registry`'s
+    /// domains. Deltas still apply in unit space, but `Guardrails::bounds`
+    /// are checked and clamped against the real-space values.
+    pub fn with_registry(mut self, registry: ParamRegistry) -> Self {
+        self.registry = Some(registry);
+        self
+    }
+
+    /// Real-space value of parameter `i` stored as `value`.
+    #[inline]
+    fn real_value(&self, i: usize, value: f64) -> f64 {
+        match &self.registry {
+            Some(registry) => registry.denormalize(i as ParamId, value),
+            None => value,
+        }
+    }
+
+    /// Stored (possibly unit-space) value of parameter `i` at real `value`.
+    #[inline]
+    fn stored_value(&self, i: usize, value: f64) -> f64 {
+        match &self.registry {
+            Some(registry) => registry.normalize(i as ParamId, value),
+            None => value,
         }
     }
 
     /// Validate a delta against guardrails.
     pub fn validate_delta(&self, delta: &ParamVec, current: &ParamVec) -> Result<(), Violation> {
         for (i, (&d, &c)) in delta.iter().zip(current.iter()).enumerate() {
-            let param_id = i as ParamId;
-
-            // Check delta magnitude
-            if d.abs() > self.guardrails.max_delta_per_step {
-                return Err(Violation::DeltaTooLarge {
-                    param_id,
-                    delta: d.abs(),
-                    max: self.guardrails.max_delta_per_step,
-                });
+            let (too_large, out_of_bounds) = self.component_violations(i, d, c);
+            if let Some(violation) = too_large.or(out_of_bounds) {
+                return Err(violation);
             }
+        }
+        Ok(())
+    }
 
-            // Check bounds
-            if let Some(ref bounds) = self.guardrails.bounds {
-                if let Some(&(min, max)) = bounds.get(i) {
-                    let new_value = c + d;
-                    if new_value < min || new_value > max {
-                        return Err(Violation::OutOfBounds {
-                            param_id,
-                            value: new_value,
-                            min,
-                            max,
-                        });
-                    }
+    /// Validate a delta without stopping at the first problem.
+    ///
+    /// Returns every violation, in parameter order; a component can report
+    /// both `DeltaTooLarge` and `OutOfBounds`. Empty means `validate_delta`
+    /// would accept the delta.
+    pub fn validate_delta_all(&self, delta: &ParamVec, current: &ParamVec) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        for (i, (&d, &c)) in delta.iter().zip(current.iter()).enumerate() {
+            let (too_large, out_of_bounds) = self.component_violations(i, d, c);
+            violations.extend(too_large);
+            violations.extend(out_of_bounds);
+        }
+        violations
+    }
+
+    /// Shrink a delta until it passes `validate_delta`.
+    ///
+    /// Components larger than `max_delta_per_step` are clamped to it, then
+    /// components that would leave the bounds are cut back to land on the
+    /// nearest bound. Non-finite components are zeroed. Returns the adjusted
+    /// delta and one entry per modified component.
+    pub fn clamp_valid_subset(
+        &self,
+        delta: &ParamVec,
+        current: &ParamVec,
+    ) -> (ParamVec, Vec<DeltaAdjustment>) {
+        let max_step = self.guardrails.max_delta_per_step;
+        let mut adjusted = delta.clone();
+        let mut adjustments = Vec::new();
+        for (i, (d, &c)) in adjusted.iter_mut().zip(current.iter()).enumerate() {
+            let requested = *d;
+            let (too_large, out_of_bounds) = self.component_violations(i, requested, c);
+            if !requested.is_finite() {
+                *d = 0.0;
+            } else {
+                if too_large.is_some() {
+                    *d = requested.clamp(-max_step, max_step);
+                }
+                if let Some(Violation::OutOfBounds { min, max, .. }) =
+                    self.component_violations(i, *d, c).1
+                {
+                    let target = self.real_value(i, c + *d).clamp(min, max);
+                    *d = self.stored_value(i, target) - c;
                 }
             }
+            if *d != requested {
+                adjustments.push(DeltaAdjustment {
+                    param_id: i as ParamId,
+                    requested,
+                    applied: *d,
+                    violations: too_large.into_iter().chain(out_of_bounds).collect(),
+                });
+            }
         }
-        Ok(())
+        (adjusted, adjustments)
+    }
+
+    /// Guardrail violations of one delta component: (too large, out of bounds).
+    fn component_violations(
+        &self,
+        i: usize,
+        d: f64,
+        c: f64,
+    ) -> (Option<Violation>, Option<Violation>) {
+        let param_id = i as ParamId;
+
+        // Check delta magnitude
+        let too_large =
+            (d.abs() > self.guardrails.max_delta_per_step).then(|| Violation::DeltaTooLarge {
+                param_id,
+                delta: d.abs(),
+                max: self.guardrails.max_delta_per_step,
+            });
+
+        // Check bounds
+        let out_of_bounds = self
+            .guardrails
+            .bounds
+            .as_ref()
+            .and_then(|bounds| bounds.get(i))
+            .and_then(|&(min, max)| {
+                let new_value = self.real_value(i, c + d);
+                (new_value < min || new_value > max).then_some(Violation::OutOfBounds {
+                    param_id,
+                    value: new_value,
+                    min,
+                    max,
+                })
+            });
+
+        (too_large, out_of_bounds)
+    }
+
+    /// `Guardrails::bounds` in the space the config is stored in (unit space
+    /// under a registry).
+    pub fn stored_bounds(&self) -> Option<Vec<(f64, f64)>> {
+        self.guardrails.bounds.as_ref().map(|bounds| {
+            bounds
+                .iter()
+                .enumerate()
+                .map(|(i, &(min, max))| (self.stored_value(i, min), self.stored_value(i, max)))
+                .collect()
+        })
     }
 
     /// Clamp values to bounds.
@@ -252,7 +444,10 @@ impl SafetyExecutor {
         if let Some(ref bounds) = self.guardrails.bounds {
             for (i, value) in params.iter_mut().enumerate() {
                 if let Some(&(min, max)) = bounds.get(i) {
-                    *value = value.clamp(min, max);
+                    let real = self.real_value(i, *value);
+                    if real < min || real > max {
+                        *value = self.stored_value(i, real.clamp(min, max));
+                    }
                 }
             }
         }
@@ -300,6 +495,16 @@ impl SafeExecutor for SafetyExecutor {
         // Get current config
         let current = self.config.snapshot();
 
+        // Reject deltas derived from a config that is no longer live
+        if let Some(expected_gen) = proposal.generation() {
+            if current.generation.abs_diff(expected_gen) > self.guardrails.max_generation_lag {
+                return Err(Violation::StaleProposal {
+                    expected_gen,
+                    actual_gen: current.generation,
+                });
+            }
+        }
+
         // Validate delta
         self.validate_delta(&delta, &current.params)?;
 
@@ -464,6 +669,126 @@ mod tests {
         assert!(matches!(result, Err(Violation::OutOfBounds { .. })));
     }
 
+    #[test]
+    fn test_bounds_checked_in_real_space_with_registry() {
+        use crate::config_atomic::ParamDomain;
+
+        let registry = ParamRegistry::new(["lr"]).with_domain("lr", ParamDomain::log(1e-4, 1e-1));
+        let start = registry.to_param_vec(&[("lr".to_string(), 1e-3)]);
+        let config = Arc::new(AtomicConfig::new(start.clone()));
+        let guardrails = Guardrails {
+            bounds: Some(vec![(1e-4, 1.5e-3)]),
+            ..Default::default()
+        };
+        let mut executor = SafetyExecutor::new(config, guardrails).with_registry(registry.clone());
+
+        // +0.1 in unit space takes 1e-3 to ~2e-3, past the real-space cap.
+        match executor.validate_delta(&ParamVec::from_slice(&[0.1]), &start) {
+            Err(Violation::OutOfBounds { value, max, .. }) => {
+                assert!((value / 1e-3 - 10f64.powf(0.3)).abs() < 1e-9);
+                assert_eq!(max, 1.5e-3);
+            }
+            other => panic!("expected OutOfBounds, got {:?}", other),
+        }
+
+        // -0.1 stays in bounds and lands at 1e-3 / 10^0.3.
+        executor
+            .apply(Proposal::Update {
+                iteration: 1,
+                delta: ParamVec::from_slice(&[-0.1]),
+                gradient_estimate: ParamVec::from_slice(&[0.0]),
+                generation: None,
+            })
+            .unwrap();
+        let real = registry.to_kv(&executor.snapshot().params)[0].1;
+        assert!((1e-3 / real - 10f64.powf(0.3)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_clamp_to_bounds_with_registry_clamps_real_value() {
+        use crate::config_atomic::ParamDomain;
+
+        let registry =
+            ParamRegistry::new(["width"]).with_domain("width", ParamDomain::linear(0.0, 256.0));
+        let config = Arc::new(AtomicConfig::new(ParamVec::from_slice(&[0.5])));
+        let guardrails = Guardrails {
+            bounds: Some(vec![(16.0, 128.0)]),
+            ..Default::default()
+        };
+        let executor = SafetyExecutor::new(config, guardrails).with_registry(registry);
+
+        let mut params = ParamVec::from_slice(&[0.75]); // 192 units
+        executor.clamp_to_bounds(&mut params);
+        assert!((params[0] - 0.5).abs() < 1e-12);
+    }
+
+    fn mixed_executor() -> SafetyExecutor {
+        let config = Arc::new(AtomicConfig::new(ParamVec::from_slice(&[0.5, 0.95, 0.5])));
+        let guardrails = Guardrails {
+            bounds: Some(vec![(0.0, 1.0), (0.0, 1.0), (0.0, 1.0)]),
+            ..Default::default()
+        };
+        SafetyExecutor::new(config, guardrails)
+    }
+
+    #[test]
+    fn test_validate_delta_all_reports_every_violation() {
+        let executor = mixed_executor();
+        let current = ParamVec::from_slice(&[0.5, 0.95, 0.5]);
+        // Dim 0 too large, dim 1 leaves the bounds, dim 2 fine.
+        let delta = ParamVec::from_slice(&[0.3, 0.08, 0.05]);
+
+        let violations = executor.validate_delta_all(&delta, &current);
+        assert_eq!(violations.len(), 2);
+        assert!(matches!(
+            violations[0],
+            Violation::DeltaTooLarge { param_id: 0, .. }
+        ));
+        assert!(matches!(
+            violations[1],
+            Violation::OutOfBounds { param_id: 1, .. }
+        ));
+        // The fail-fast check still stops at the first one.
+        assert!(matches!(
+            executor.validate_delta(&delta, &current),
+            Err(Violation::DeltaTooLarge { param_id: 0, .. })
+        ));
+        assert!(executor
+            .validate_delta_all(&ParamVec::from_slice(&[0.05, 0.0, -0.05]), &current)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_clamp_valid_subset_keeps_valid_dims() {
+        let executor = mixed_executor();
+        let current = ParamVec::from_slice(&[0.5, 0.95, 0.5]);
+        let delta = ParamVec::from_slice(&[0.3, 0.08, 0.05]);
+
+        let (clamped, adjustments) = executor.clamp_valid_subset(&delta, &current);
+        assert!((clamped[0] - 0.1).abs() < 1e-12);
+        assert!((clamped[1] - 0.05).abs() < 1e-12);
+        assert_eq!(clamped[2], 0.05);
+        assert!(executor.validate_delta(&clamped, &current).is_ok());
+
+        assert_eq!(adjustments.len(), 2);
+        assert_eq!(adjustments[0].param_id, 0);
+        assert_eq!(adjustments[0].requested, 0.3);
+        assert!(matches!(
+            adjustments[0].violations[..],
+            [Violation::DeltaTooLarge { .. }]
+        ));
+        assert_eq!(adjustments[1].param_id, 1);
+        assert!(matches!(
+            adjustments[1].violations[..],
+            [Violation::OutOfBounds { .. }]
+        ));
+
+        let (zeroed, adjustments) =
+            executor.clamp_valid_subset(&ParamVec::from_slice(&[f64::NAN, 0.0, 0.0]), &current);
+        assert_eq!(zeroed[0], 0.0);
+        assert_eq!(adjustments.len(), 1);
+    }
+
     #[test]
     fn test_snapshot_returns_current_config() {
         let config = Arc::new(AtomicConfig::new(ParamVec::from_slice(&[0.3, 0.7])));
@@ -507,6 +832,7 @@ mod tests {
             iteration: 1,
             delta: ParamVec::from_slice(&[0.05, -0.05]),
             gradient_estimate: ParamVec::from_slice(&[0.1, -0.1]),
+            generation: None,
         };
 
         let result = executor.apply(proposal);
@@ -545,6 +871,7 @@ mod tests {
         let proposal = Proposal::ApplyPlus {
             perturbation_id: 1,
             delta: ParamVec::from_slice(&[0.02, 0.0]),
+            generation: None,
         };
 
         let result = executor.apply(proposal);
@@ -564,6 +891,7 @@ mod tests {
         let proposal = Proposal::ApplyMinus {
             perturbation_id: 1,
             delta: ParamVec::from_slice(&[-0.02, 0.0]),
+            generation: None,
         };
 
         let result = executor.apply(proposal);
@@ -584,9 +912,50 @@ mod tests {
             iteration: 1,
             delta: ParamVec::from_slice(&[0.5, 0.0]), // 0.5 > 0.1 limit
             gradient_estimate: ParamVec::from_slice(&[0.5, 0.0]),
+            generation: None,
         };
 
         let result = executor.apply(proposal);
         assert!(matches!(result, Err(Violation::DeltaTooLarge { .. })));
     }
+
+    #[test]
+    fn test_apply_rejects_stale_proposal() {
+        use crate::proposer::Proposal;
+
+        let config = Arc::new(AtomicConfig::new(ParamVec::from_slice(&[0.5, 0.5])));
+        let mut executor = SafetyExecutor::new(config.clone(), Guardrails::default());
+        let proposal = Proposal::ApplyPlus {
+            perturbation_id: 1,
+            delta: ParamVec::from_slice(&[0.02, 0.0]),
+            generation: None,
+        }
+        .with_generation(config.generation());
+
+        // Another writer swaps the config before the proposal arrives.
+        config.swap(ParamVec::from_slice(&[0.3, 0.3]));
+        let result = executor.apply(proposal.clone());
+        assert!(matches!(
+            result,
+            Err(Violation::StaleProposal {
+                expected_gen: 0,
+                actual_gen: 1,
+            })
+        ));
+        assert_eq!(executor.snapshot().params.as_slice(), &[0.3, 0.3]);
+
+        // A one-generation lag is tolerated when configured.
+        let mut lenient = SafetyExecutor::new(
+            config.clone(),
+            Guardrails {
+                max_generation_lag: 1,
+                ..Default::default()
+            },
+        );
+        assert_eq!(lenient.apply(proposal.clone()).unwrap().new_generation, 2);
+        assert!(matches!(
+            lenient.apply(proposal),
+            Err(Violation::StaleProposal { actual_gen: 2, .. })
+        ));
+    }
 }
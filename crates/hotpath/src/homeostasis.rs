@@ -1,11 +1,183 @@
-//! Homeostatic regime caching (stub for future implementation).
+//! Homeostasis: variance-driven cooldown and regime caching.
 //!
-//! This module defines the contract for caching stable configurations
-//! to enable fast re-entry when similar conditions are detected.
+//! [`HomeostasisController`] watches the short-window variance of the
+//! objective and damps the SPSA learning rate when it spikes relative to a
+//! baseline, so the engine stops chasing noise.
 //!
-//! Implementation is deferred to post-005.
+//! Regime caching (fast re-entry into stable configurations) is still a stub;
+//! implementation is deferred to post-005.
 
 use crate::config_atomic::ParamVec;
+use crate::telemetry::{TelemetryDigest, TelemetryRingBuffer};
+use serde::{Deserialize, Serialize};
+
+/// Configuration for the homeostasis cooldown. Fields missing when
+/// deserializing take their default.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HomeostasisConfig {
+    /// Whether variance-driven damping is active. Off by default: damping
+    /// changes the learning rate schedule, so callers opt in.
+    pub enabled: bool,
+    /// Number of recent digests in the rolling variance window.
+    pub window: usize,
+    /// Damp once rolling variance exceeds `variance_multiplier * baseline`.
+    pub variance_multiplier: f64,
+    /// Learning rate multiplier applied while damped (0.0 to 1.0).
+    pub damping_factor: f64,
+    /// EWMA weight used to track the baseline variance while calm.
+    pub baseline_smoothing: f64,
+}
+
+impl Default for HomeostasisConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            window: 16,
+            variance_multiplier: 4.0,
+            damping_factor: 0.25,
+            baseline_smoothing: 0.05,
+        }
+    }
+}
+
+/// Change in damping state reported by [`HomeostasisController::observe`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HomeostasisTransition {
+    /// Variance spiked; learning rate is now damped.
+    Damped,
+    /// Variance settled; learning rate is restored.
+    Recovered,
+}
+
+/// Persistable [`HomeostasisController`] state.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct HomeostasisState {
+    /// Objective values in the rolling window, oldest first.
+    pub recent_objectives: Vec<f64>,
+    /// Baseline variance, if established.
+    pub baseline_variance: Option<f64>,
+    /// Whether the learning rate is damped.
+    pub damped: bool,
+    /// Number of times damping has been triggered.
+    pub damping_count: u64,
+}
+
+/// Tracks rolling objective variance and decides when to damp adaptation.
+///
+/// The first full window establishes the baseline variance. Afterwards the
+/// baseline follows calm windows via an EWMA and is frozen while damped.
+pub struct HomeostasisController {
+    config: HomeostasisConfig,
+    window: TelemetryRingBuffer,
+    baseline_variance: Option<f64>,
+    damped: bool,
+    damping_count: u64,
+}
+
+impl HomeostasisController {
+    /// Create a new controller. Allocates the rolling window once.
+    pub fn new(config: HomeostasisConfig) -> Self {
+        let window = TelemetryRingBuffer::new(config.window.max(2));
+        Self {
+            config,
+            window,
+            baseline_variance: None,
+            damped: false,
+            damping_count: 0,
+        }
+    }
+
+    /// Feed one digest; returns a transition if the damping state changed.
+    pub fn observe(&mut self, digest: &TelemetryDigest) -> Option<HomeostasisTransition> {
+        if !self.config.enabled {
+            return None;
+        }
+        self.window.push(digest.clone());
+        let variance = self.rolling_variance()?;
+
+        let Some(baseline) = self.baseline_variance else {
+            self.baseline_variance = Some(variance);
+            return None;
+        };
+        let threshold = baseline.max(f64::EPSILON) * self.config.variance_multiplier;
+
+        if !self.damped && variance > threshold {
+            self.damped = true;
+            self.damping_count += 1;
+            return Some(HomeostasisTransition::Damped);
+        }
+        if self.damped && variance <= threshold {
+            self.damped = false;
+            return Some(HomeostasisTransition::Recovered);
+        }
+        if !self.damped {
+            let s = self.config.baseline_smoothing;
+            self.baseline_variance = Some(baseline + s * (variance - baseline));
+        }
+        None
+    }
+
+    /// Sample variance of the objective over a full window, if available.
+    pub fn rolling_variance(&self) -> Option<f64> {
+        let n = self.window.len();
+        if n < self.config.window.max(2) {
+            return None;
+        }
+        let mean = self.window.iter().map(|d| d.objective_value).sum::<f64>() / n as f64;
+        let sum_sq = self
+            .window
+            .iter()
+            .map(|d| (d.objective_value - mean).powi(2))
+            .sum::<f64>();
+        Some(sum_sq / (n - 1) as f64)
+    }
+
+    /// Baseline variance, once the first window has filled.
+    pub fn baseline_variance(&self) -> Option<f64> {
+        self.baseline_variance
+    }
+
+    /// Whether the learning rate is currently damped.
+    pub fn is_damped(&self) -> bool {
+        self.damped
+    }
+
+    /// Number of times damping has been triggered.
+    pub fn damping_count(&self) -> u64 {
+        self.damping_count
+    }
+
+    /// Capture controller state for persistence.
+    pub fn snapshot(&self) -> HomeostasisState {
+        HomeostasisState {
+            recent_objectives: self.window.iter().map(|d| d.objective_value).collect(),
+            baseline_variance: self.baseline_variance,
+            damped: self.damped,
+            damping_count: self.damping_count,
+        }
+    }
+
+    /// Restore state captured by `snapshot`, keeping the current config.
+    pub fn restore(&mut self, state: HomeostasisState) {
+        self.window.clear();
+        for value in state.recent_objectives {
+            self.window.push(TelemetryDigest::new(0, value, 0));
+        }
+        self.baseline_variance = state.baseline_variance;
+        self.damped = state.damped;
+        self.damping_count = state.damping_count;
+    }
+
+    /// Learning rate multiplier for the current state.
+    pub fn learning_rate_scale(&self) -> f64 {
+        if self.damped {
+            self.config.damping_factor
+        } else {
+            1.0
+        }
+    }
+}
 
 /// Homeostasis cache entry.
 #[derive(Clone, Debug)]
@@ -112,4 +284,64 @@ mod tests {
         // Note: stub insert is a no-op, but we test the API
         // In real implementation, this would assert cache.len() == 1
     }
+
+    fn feed(controller: &mut HomeostasisController, values: &[f64]) -> Vec<HomeostasisTransition> {
+        values
+            .iter()
+            .enumerate()
+            .filter_map(|(i, &v)| controller.observe(&TelemetryDigest::new(i as u64, v, 1)))
+            .collect()
+    }
+
+    fn enabled() -> HomeostasisConfig {
+        HomeostasisConfig {
+            enabled: true,
+            ..Default::default()
+        }
+    }
+
+    fn calm(n: usize) -> Vec<f64> {
+        (0..n).map(|i| 1.0 + 0.01 * ((i % 4) as f64)).collect()
+    }
+
+    #[test]
+    fn test_controller_establishes_baseline() {
+        let mut controller = HomeostasisController::new(enabled());
+        assert!(feed(&mut controller, &calm(15)).is_empty());
+        assert!(controller.baseline_variance().is_none());
+
+        assert!(feed(&mut controller, &calm(1)).is_empty());
+        assert!(controller.baseline_variance().is_some());
+        assert!(!controller.is_damped());
+        assert_eq!(controller.learning_rate_scale(), 1.0);
+    }
+
+    #[test]
+    fn test_controller_damps_on_variance_spike_and_recovers() {
+        let config = enabled();
+        let mut controller = HomeostasisController::new(config.clone());
+        feed(&mut controller, &calm(32));
+
+        let noisy: Vec<f64> = (0..8)
+            .map(|i| if i % 2 == 0 { 5.0 } else { -5.0 })
+            .collect();
+        let transitions = feed(&mut controller, &noisy);
+        assert_eq!(transitions, vec![HomeostasisTransition::Damped]);
+        assert!(controller.is_damped());
+        assert_eq!(controller.learning_rate_scale(), config.damping_factor);
+
+        let transitions = feed(&mut controller, &calm(config.window));
+        assert_eq!(transitions, vec![HomeostasisTransition::Recovered]);
+        assert_eq!(controller.damping_count(), 1);
+    }
+
+    #[test]
+    fn test_controller_disabled_by_default() {
+        let config = HomeostasisConfig::default();
+        assert!(!config.enabled);
+        let mut controller = HomeostasisController::new(config);
+        feed(&mut controller, &calm(32));
+        assert!(feed(&mut controller, &[100.0, -100.0, 100.0, -100.0]).is_empty());
+        assert!(controller.baseline_variance().is_none());
+    }
 }
@@ -12,16 +12,24 @@ pub mod spsa;
 pub mod telemetry;
 
 // Re-exports for API compatibility with arqonhpo_core::adaptive_engine
-pub use audit::{AuditEvent, AuditPolicy, AuditQueue, EnqueueResult, EventType};
+pub use audit::{AuditEvent, AuditPolicy, AuditPriority, AuditQueue, EnqueueResult, EventType};
 pub use config_atomic::{
-    param_vec, AtomicConfig, ConfigSnapshot, ParamId, ParamRegistry, ParamVec,
+    param_vec, AtomicConfig, ConfigSnapshot, GenerationRecord, ParamDomain, ParamId, ParamRegistry,
+    ParamScale, ParamVec,
 };
 pub use control_safety::{ControlSafety, SafeMode, SafeModeExit, SafeModeReason};
 pub use executor::{
-    ApplyReceipt, Guardrails, RollbackPolicy, RollbackReceipt, SafeExecutor, SafetyExecutor,
-    Violation,
+    ApplyReceipt, DeltaAdjustment, Guardrails, RollbackPolicy, RollbackReceipt, SafeExecutor,
+    SafetyExecutor, Violation,
 };
-pub use orchestrator::{AdaptiveEngine, AdaptiveEngineConfig};
-pub use proposer::{AdaptiveProposer, NoChangeReason, Proposal, ProposalResult};
-pub use spsa::{Spsa, SpsaConfig, SpsaState};
-pub use telemetry::{DigestValidity, TelemetryDigest, TelemetryRingBuffer};
+pub use homeostasis::{
+    HomeostasisConfig, HomeostasisController, HomeostasisState, HomeostasisTransition,
+};
+pub use orchestrator::{
+    AdaptiveEngine, AdaptiveEngineConfig, AdaptiveEngineState, EngineStats, RejectionBackoff,
+};
+pub use proposer::{
+    AdaptiveProposer, NoChangeReason, Proposal, ProposalResult, PROPOSAL_SCHEMA_VERSION,
+};
+pub use spsa::{Spsa, SpsaConfig, SpsaSnapshot, SpsaState, SpsaStateSnapshot};
+pub use telemetry::{DigestSummary, DigestValidity, Reducer, TelemetryDigest, TelemetryRingBuffer};
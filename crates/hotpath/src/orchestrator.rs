@@ -3,27 +3,79 @@
 //! Constitution: II.16-23 - Tier 2 Adaptive Engine
 
 use crate::{
-    config_atomic::{AtomicConfig, ConfigSnapshot, ParamVec},
-    executor::{ApplyReceipt, Guardrails, SafeExecutor, SafetyExecutor, Violation},
+    audit::{AuditEvent, AuditQueue, EventType},
+    config_atomic::{AtomicConfig, ConfigSnapshot, ParamRegistry, ParamVec},
+    executor::{
+        ApplyReceipt, Guardrails, RollbackReceipt, SafeExecutor, SafetyExecutor, Violation,
+    },
+    homeostasis::{
+        HomeostasisConfig, HomeostasisController, HomeostasisState, HomeostasisTransition,
+    },
     proposer::{AdaptiveProposer, NoChangeReason, Proposal, ProposalError, ProposalResult},
-    spsa::{Spsa, SpsaConfig, SpsaState},
+    spsa::{Spsa, SpsaConfig, SpsaSnapshot, SpsaState},
     telemetry::TelemetryDigest,
 };
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
 use std::sync::Arc;
 
 /// Configuration for AdaptiveEngine.
-#[derive(Clone, Debug)]
+///
+/// Serializes to a JSON object keyed by field name, nested objects for
+/// `spsa`, `guardrails`, `homeostasis` and `rejection_backoff`. Fields
+/// missing when deserializing, at any level, take their default, so a file
+/// only needs the settings it changes.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct AdaptiveEngineConfig {
     /// SPSA configuration.
     pub spsa: SpsaConfig,
     /// Guardrails for safety executor.
     pub guardrails: Guardrails,
-    /// Seed for RNG.
-    pub seed: u64,
+    /// Identifies the run in audit events.
+    pub run_id: u64,
+    /// Seed of the SPSA perturbation stream, independent of `run_id`.
+    pub rng_seed: u64,
     /// Initial learning rate.
     pub learning_rate: f64,
     /// Initial perturbation scale.
     pub perturbation_scale: f64,
+    /// Variance-driven learning rate cooldown (off unless `enabled`).
+    pub homeostasis: HomeostasisConfig,
+    /// Number of recent generations retained for rollback-to-best (0 disables).
+    pub generation_history: usize,
+    /// Perturbation shrinking after `DeltaTooLarge` rejections.
+    pub rejection_backoff: RejectionBackoff,
+}
+
+/// How SPSA adapts its perturbation scale to the executor's step limit.
+///
+/// After `rejections_to_shrink` consecutive `DeltaTooLarge` rejections of
+/// perturbations, `c` is multiplied by `shrink_factor` (never below
+/// `min_factor` of its schedule). Every accepted perturbation multiplies it
+/// by `recovery_factor`, up to the unshrunk schedule.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RejectionBackoff {
+    /// Consecutive rejections before shrinking (0 disables the backoff).
+    pub rejections_to_shrink: u32,
+    /// Multiplier applied to `c` on each shrink, in (0, 1).
+    pub shrink_factor: f64,
+    /// Multiplier applied to `c` after an accepted perturbation (>= 1).
+    pub recovery_factor: f64,
+    /// Lower limit of the shrink, as a fraction of the scheduled `c_k`.
+    pub min_factor: f64,
+}
+
+impl Default for RejectionBackoff {
+    fn default() -> Self {
+        Self {
+            rejections_to_shrink: 3,
+            shrink_factor: 0.5,
+            recovery_factor: 1.25,
+            min_factor: 1e-3,
+        }
+    }
 }
 
 impl Default for AdaptiveEngineConfig {
@@ -31,9 +83,75 @@ impl Default for AdaptiveEngineConfig {
         Self {
             spsa: SpsaConfig::default(),
             guardrails: Guardrails::default(),
-            seed: 42,
+            run_id: 0,
+            rng_seed: 42,
             learning_rate: 0.1,
             perturbation_scale: 0.01,
+            homeostasis: HomeostasisConfig::default(),
+            generation_history: 32,
+            rejection_backoff: RejectionBackoff::default(),
+        }
+    }
+}
+
+/// Persistable AdaptiveEngine state.
+///
+/// Produced by [`AdaptiveEngine::snapshot_state`] and consumed by
+/// [`AdaptiveEngine::restore_state`] so a restarted service resumes the SPSA
+/// decay schedule instead of starting over at `k = 0`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AdaptiveEngineState {
+    /// SPSA optimizer state (schedule position, state machine, RNG).
+    pub spsa: SpsaSnapshot,
+    /// Perturbation currently under evaluation, if any.
+    pub current_delta: Option<Vec<f64>>,
+    /// Parameters the perturbation under evaluation is centered on.
+    #[serde(default)]
+    pub baseline: Option<Vec<f64>>,
+    /// Homeostasis window and damping state.
+    #[serde(default)]
+    pub homeostasis: HomeostasisState,
+    /// Rejections counted toward the next perturbation shrink.
+    #[serde(default)]
+    pub consecutive_rejections: u32,
+}
+
+/// Proposal outcomes counted by [`AdaptiveEngine`] since it was created.
+///
+/// Keys are the variant names of [`Violation`] and [`NoChangeReason`]; kinds
+/// that never occurred are left out.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct EngineStats {
+    /// Proposals that reached the live config.
+    pub applied: u64,
+    /// Proposals refused by the safety executor, by violation kind.
+    pub rejected_by_violation: BTreeMap<&'static str, u64>,
+    /// `NoChange` proposals returned by `observe`, by reason.
+    pub no_change_by_reason: BTreeMap<&'static str, u64>,
+}
+
+/// Dense outcome counters behind [`EngineStats`].
+#[derive(Clone, Debug, Default)]
+struct OutcomeCounters {
+    applied: u64,
+    rejected: [u64; Violation::KINDS.len()],
+    no_change: [u64; NoChangeReason::KINDS.len()],
+}
+
+impl OutcomeCounters {
+    fn stats(&self) -> EngineStats {
+        let named = |names: &[&'static str], counts: &[u64]| {
+            names
+                .iter()
+                .zip(counts)
+                .filter(|(_, &count)| count > 0)
+                .map(|(&name, &count)| (name, count))
+                .collect()
+        };
+        EngineStats {
+            applied: self.applied,
+            rejected_by_violation: named(&Violation::KINDS, &self.rejected),
+            no_change_by_reason: named(&NoChangeReason::KINDS, &self.no_change),
         }
     }
 }
@@ -42,6 +160,17 @@ impl Default for AdaptiveEngineConfig {
 pub struct SpsaProposer {
     spsa: Spsa,
     current_delta: Option<ParamVec>,
+    /// θ of the iteration in flight, frozen when its +Δ is proposed. The −Δ
+    /// and the update are relative to the live config but land at θ − Δ and
+    /// θ + step, whatever the +Δ did to the config in between.
+    baseline: Option<ParamVec>,
+    backoff: RejectionBackoff,
+    /// Perturbations rejected as too large since the last shrink or accept.
+    consecutive_rejections: u32,
+    /// Parameters new perturbations are centered on.
+    position: ParamVec,
+    /// Feasible region for perturbations, in the same space as `position`.
+    bounds: Option<Vec<(f64, f64)>>,
 }
 
 impl SpsaProposer {
@@ -50,13 +179,65 @@ impl SpsaProposer {
         Self {
             spsa,
             current_delta: None,
+            baseline: None,
+            backoff: RejectionBackoff::default(),
+            consecutive_rejections: 0,
+            position: ParamVec::new(),
+            bounds: None,
+        }
+    }
+
+    /// Keep perturbations inside `bounds` (see [`Spsa::generate_perturbation`]).
+    pub fn with_bounds(mut self, bounds: Option<Vec<(f64, f64)>>) -> Self {
+        self.set_bounds(bounds);
+        self
+    }
+
+    /// Replace the feasible region, and the range widths perturbations are
+    /// scaled by when `SpsaConfig::scale_by_range` is set.
+    fn set_bounds(&mut self, bounds: Option<Vec<(f64, f64)>>) {
+        if self.spsa.config().scale_by_range {
+            self.spsa.set_ranges(bounds.as_deref());
         }
+        self.bounds = bounds;
+    }
+
+    /// Center the next perturbation on `params`.
+    pub fn set_position(&mut self, params: &[f64]) {
+        self.position = ParamVec::from_slice(params);
+    }
+
+    /// Use `backoff` to adapt the perturbation scale to rejections.
+    pub fn with_backoff(mut self, backoff: RejectionBackoff) -> Self {
+        self.backoff = backoff;
+        self
     }
 
     /// Get SPSA state for inspection.
     pub fn spsa_state(&self) -> &SpsaState {
         self.spsa.state()
     }
+
+    /// Drop the perturbation in flight and its baseline.
+    fn clear_perturbation(&mut self) {
+        self.current_delta = None;
+        self.baseline = None;
+    }
+
+    /// Delta that moves the live config (`position`) to `baseline + step`.
+    ///
+    /// Without a baseline (state restored from before it was persisted, or
+    /// no position set) the config is assumed to sit at θ + `live`.
+    fn relative_to_baseline(&self, live: &ParamVec, step: &ParamVec) -> ParamVec {
+        match &self.baseline {
+            Some(baseline) if baseline.len() == step.len() && self.position.len() == step.len() => {
+                (0..step.len())
+                    .map(|i| baseline[i] + step[i] - self.position[i])
+                    .collect()
+            }
+            _ => step.iter().zip(live).map(|(&s, &l)| s - l).collect(),
+        }
+    }
 }
 
 impl AdaptiveProposer for SpsaProposer {
@@ -67,12 +248,16 @@ impl AdaptiveProposer for SpsaProposer {
         match self.spsa.state() {
             SpsaState::Ready => {
                 // Generate new perturbation and start plus phase
-                let delta = self.spsa.generate_perturbation();
+                let delta = self
+                    .spsa
+                    .generate_perturbation(&self.position, self.bounds.as_deref());
                 self.current_delta = Some(delta.clone());
+                self.baseline = Some(self.position.clone());
                 self.spsa.start_plus_perturbation(delta.clone());
                 Ok(Proposal::ApplyPlus {
                     perturbation_id: self.spsa.perturbation_counter(),
                     delta,
+                    generation: None,
                 })
             }
             SpsaState::WaitingPlus { .. } => {
@@ -80,12 +265,14 @@ impl AdaptiveProposer for SpsaProposer {
                 if self.spsa.has_enough_samples() {
                     // Complete plus window, transition to minus
                     let _ = self.spsa.complete_eval_window();
-                    // Apply minus delta
+                    // Move to θ − Δ, not −Δ from the +Δ-shifted config, so
+                    // y+ and y− are measured symmetrically around θ
                     if let Some(ref delta) = self.current_delta {
-                        let minus_delta: ParamVec = delta.iter().map(|&d| -d).collect();
+                        let minus: ParamVec = delta.iter().map(|&d| -d).collect();
                         Ok(Proposal::ApplyMinus {
                             perturbation_id: self.spsa.perturbation_counter(),
-                            delta: minus_delta,
+                            delta: self.relative_to_baseline(delta, &minus),
+                            generation: None,
                         })
                     } else {
                         Err(ProposalError::InternalError(
@@ -102,11 +289,20 @@ impl AdaptiveProposer for SpsaProposer {
                 if self.spsa.has_enough_samples() {
                     // Complete minus window, compute update
                     if let Some((_gradient, update_delta)) = self.spsa.complete_eval_window() {
-                        self.current_delta = None;
+                        // The config sits at θ − Δ; step from θ.
+                        let delta = match self.current_delta.take() {
+                            Some(plus) => {
+                                let minus: ParamVec = plus.iter().map(|&d| -d).collect();
+                                self.relative_to_baseline(&minus, &update_delta)
+                            }
+                            None => update_delta.clone(),
+                        };
+                        self.baseline = None;
                         Ok(Proposal::Update {
                             iteration: self.spsa.iteration(),
-                            delta: update_delta.clone(),
+                            delta,
                             gradient_estimate: update_delta,
+                            generation: None,
                         })
                     } else {
                         Ok(Proposal::NoChange {
@@ -131,6 +327,50 @@ impl AdaptiveProposer for SpsaProposer {
     fn iteration(&self) -> u64 {
         self.spsa.iteration()
     }
+
+    fn on_apply_result(&mut self, perturbation_id: Option<u64>, result: Result<(), &Violation>) {
+        let Some(id) = perturbation_id else {
+            return;
+        };
+        let factor = self.spsa.perturbation_scale_factor();
+        match result {
+            Ok(()) => {
+                self.consecutive_rejections = 0;
+                self.spsa
+                    .set_perturbation_scale_factor(factor * self.backoff.recovery_factor);
+            }
+            Err(Violation::StaleProposal { .. }) => {
+                // The config moved under the ±Δ, so neither arm of this
+                // iteration measures around θ any more; start a fresh one.
+                if matches!(
+                    self.spsa.state(),
+                    SpsaState::WaitingPlus { perturbation_id, .. }
+                        | SpsaState::WaitingMinus { perturbation_id, .. }
+                        if *perturbation_id == id
+                ) {
+                    self.spsa.abandon_iteration();
+                    self.clear_perturbation();
+                }
+            }
+            Err(Violation::DeltaTooLarge { .. }) if self.backoff.rejections_to_shrink > 0 => {
+                // A rejected +Δ never went live, so its eval window would
+                // measure nothing; start over with the next digest.
+                if matches!(self.spsa.state(), SpsaState::WaitingPlus { perturbation_id, .. } if *perturbation_id == id)
+                {
+                    self.spsa.abandon_perturbation();
+                    self.clear_perturbation();
+                }
+                self.consecutive_rejections += 1;
+                if self.consecutive_rejections >= self.backoff.rejections_to_shrink {
+                    self.consecutive_rejections = 0;
+                    self.spsa.set_perturbation_scale_factor(
+                        (factor * self.backoff.shrink_factor).max(self.backoff.min_factor),
+                    );
+                }
+            }
+            Err(_) => {}
+        }
+    }
 }
 
 /// High-level adaptive engine orchestrating SPSA, Proposer, and Executor.
@@ -138,35 +378,185 @@ pub struct AdaptiveEngine {
     proposer: SpsaProposer,
     config: Arc<AtomicConfig>,
     executor: SafetyExecutor,
+    homeostasis: HomeostasisController,
+    audit: Option<Arc<AuditQueue>>,
+    run_id: u64,
+    /// Timestamp of the latest digest, used to stamp apply/rollback events.
+    last_timestamp_us: u64,
+    paused: bool,
+    /// Digests observed while paused, oldest first.
+    paused_digests: VecDeque<TelemetryDigest>,
+    outcomes: OutcomeCounters,
 }
 
+/// Digests kept while paused; older ones are dropped first.
+const PAUSED_DIGEST_CAPACITY: usize = 1024;
+
 impl AdaptiveEngine {
     /// Create a new AdaptiveEngine.
     pub fn new(engine_config: AdaptiveEngineConfig, initial_params: ParamVec) -> Self {
-        let config = Arc::new(AtomicConfig::new(initial_params.clone()));
+        let config = Arc::new(AtomicConfig::with_history(
+            initial_params.clone(),
+            engine_config.generation_history,
+        ));
         let num_params = initial_params.len();
 
         let spsa = Spsa::new(
-            engine_config.seed,
+            engine_config.rng_seed,
             num_params,
             engine_config.learning_rate,
             engine_config.perturbation_scale,
             engine_config.spsa.clone(),
         );
 
-        let proposer = SpsaProposer::new(spsa);
         let executor = SafetyExecutor::new(config.clone(), engine_config.guardrails);
+        let proposer = SpsaProposer::new(spsa)
+            .with_backoff(engine_config.rejection_backoff)
+            .with_bounds(executor.stored_bounds());
+        let homeostasis = HomeostasisController::new(engine_config.homeostasis);
 
         Self {
             proposer,
             config,
             executor,
+            homeostasis,
+            audit: None,
+            run_id: engine_config.run_id,
+            last_timestamp_us: 0,
+            paused: false,
+            paused_digests: VecDeque::new(),
+            outcomes: OutcomeCounters::default(),
+        }
+    }
+
+    /// Run SPSA in the unit space of `registry`'s domains.
+    ///
+    /// `initial_params` and every snapshot are then unit-space values (build
+    /// them with [`ParamRegistry::to_param_vec`] and read them back with
+    /// [`ParamRegistry::to_kv`]); guardrail bounds stay in real space.
+    pub fn with_registry(mut self, registry: ParamRegistry) -> Self {
+        self.executor = self.executor.with_registry(registry);
+        self.proposer.set_bounds(self.executor.stored_bounds());
+        self
+    }
+
+    /// Attach an audit queue that receives homeostasis, apply and rollback
+    /// events.
+    pub fn with_audit_queue(mut self, audit: Arc<AuditQueue>) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+
+    /// Record an audit event without ever stalling adaptation.
+    ///
+    /// A saturated queue makes room by evicting lower-priority events (see
+    /// [`AuditQueue::enqueue_or_evict`]); anything that still does not fit is
+    /// counted in the queue's per-priority drop counters.
+    fn audit(&self, event: AuditEvent) {
+        if let Some(audit) = &self.audit {
+            let _ = audit.enqueue_or_evict(event);
         }
     }
 
     /// Observe a telemetry digest and potentially get a proposal.
+    ///
+    /// Each digest first passes through the homeostasis controller, which
+    /// damps the SPSA learning rate while objective variance is spiking. The
+    /// objective is also recorded against the live generation so
+    /// [`rollback_to_best`](Self::rollback_to_best) can find it later.
     pub fn observe(&mut self, digest: TelemetryDigest) -> ProposalResult {
-        self.proposer.observe(digest)
+        let result = self.observe_digest(digest);
+        if let Ok(Proposal::NoChange { reason }) = &result {
+            self.outcomes.no_change[reason.kind_index()] += 1;
+        }
+        result
+    }
+
+    fn observe_digest(&mut self, digest: TelemetryDigest) -> ProposalResult {
+        if self.paused {
+            if self.paused_digests.len() == PAUSED_DIGEST_CAPACITY {
+                self.paused_digests.pop_front();
+            }
+            self.paused_digests.push_back(digest);
+            return Ok(Proposal::NoChange {
+                reason: NoChangeReason::Paused,
+            });
+        }
+        self.config.record_objective(digest.objective_value);
+        self.last_timestamp_us = digest.timestamp_us;
+        if let Some(transition) = self.homeostasis.observe(&digest) {
+            self.proposer
+                .spsa
+                .set_learning_rate_scale(self.homeostasis.learning_rate_scale());
+            let payload = match transition {
+                HomeostasisTransition::Damped => "variance spike: learning rate damped",
+                HomeostasisTransition::Recovered => "variance settled: learning rate restored",
+            };
+            self.audit(
+                AuditEvent::new(
+                    EventType::Homeostasis,
+                    digest.timestamp_us,
+                    self.run_id,
+                    self.config.generation(),
+                )
+                .with_payload(payload),
+            );
+        }
+        let snapshot = self.config.snapshot();
+        self.proposer.set_position(&snapshot.params);
+        self.proposer
+            .observe(digest)
+            .map(|proposal| proposal.with_generation(snapshot.generation))
+    }
+
+    /// Stop adapting and freeze the live config.
+    ///
+    /// Until [`resume`](Self::resume), `observe` buffers digests and returns
+    /// `NoChange { reason: Paused }`, and `apply` leaves the config alone.
+    /// SPSA state is kept.
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    /// Resume adaptation, returning the digests buffered while paused (up to
+    /// the last 1024).
+    ///
+    /// The ±Δ pair in flight when the engine paused is dropped: its windows
+    /// would mix samples from before and after the pause, and a perturbation
+    /// "applied" while paused never reached the config. The next digest
+    /// starts a fresh perturbation at the same iteration.
+    pub fn resume(&mut self) -> Vec<TelemetryDigest> {
+        if self.paused {
+            self.paused = false;
+            self.proposer.spsa.abandon_iteration();
+            self.proposer.clear_perturbation();
+        }
+        self.paused_digests.drain(..).collect()
+    }
+
+    /// Restart the SPSA perturbation stream from `new_seed`.
+    ///
+    /// Like [`resume`](Self::resume), the ±Δ pair in flight is dropped and the
+    /// next digest starts a fresh perturbation at the same iteration. The
+    /// config, its generations, the schedule and audit state are untouched.
+    pub fn reseed(&mut self, new_seed: u64) {
+        self.proposer.spsa.reseed(new_seed);
+        self.proposer.clear_perturbation();
+    }
+
+    /// Whether adaptation is paused.
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Counts of applied, rejected and no-change proposals so far.
+    pub fn stats(&self) -> EngineStats {
+        self.outcomes.stats()
+    }
+
+    /// Homeostasis controller state for inspection.
+    pub fn homeostasis(&self) -> &HomeostasisController {
+        &self.homeostasis
     }
 
     /// Get current configuration snapshot.
@@ -175,19 +565,109 @@ impl AdaptiveEngine {
     }
 
     /// Apply a proposal through the safety executor.
+    ///
+    /// The verdict on `ApplyPlus`/`ApplyMinus` proposals is fed back to the
+    /// proposer, which shrinks its perturbations while they keep being
+    /// rejected as too large (see [`RejectionBackoff`]). While paused this is
+    /// a no-op that reports the current generation.
     pub fn apply(&mut self, proposal: Proposal) -> Result<ApplyReceipt, Violation> {
-        self.executor.apply(proposal)
+        if self.paused {
+            return Ok(ApplyReceipt {
+                new_generation: self.config.generation(),
+                apply_latency_us: 0,
+            });
+        }
+        let proposal_id = match &proposal {
+            Proposal::ApplyPlus {
+                perturbation_id, ..
+            }
+            | Proposal::ApplyMinus {
+                perturbation_id, ..
+            } => Some(*perturbation_id),
+            _ => None,
+        };
+        let result = self.executor.apply(proposal);
+        self.proposer
+            .on_apply_result(proposal_id, result.as_ref().map(|_| ()));
+        match &result {
+            Ok(_) => self.outcomes.applied += 1,
+            Err(violation) => self.outcomes.rejected[violation.kind_index()] += 1,
+        }
+        let receipt = result?;
+        let event = AuditEvent::new(
+            EventType::Apply,
+            self.last_timestamp_us,
+            self.run_id,
+            receipt.new_generation,
+        );
+        self.audit(match proposal_id {
+            Some(id) => event.with_proposal_id(id),
+            None => event,
+        });
+        Ok(receipt)
+    }
+
+    /// Restore the parameters of the best recorded generation.
+    ///
+    /// Intended for SafeMode recovery after an objective regression: rather
+    /// than returning to a manually set baseline, jump back to the generation
+    /// that produced the lowest observed objective.
+    pub fn rollback_to_best(&mut self) -> Result<RollbackReceipt, Violation> {
+        match self.config.rollback_to_best() {
+            Some(new_gen) => {
+                self.audit(
+                    AuditEvent::new(
+                        EventType::Rollback,
+                        self.last_timestamp_us,
+                        self.run_id,
+                        new_gen,
+                    )
+                    .with_payload("rollback to best generation"),
+                );
+                Ok(RollbackReceipt {
+                    reverted_to_generation: new_gen,
+                    reason: "Rollback to best generation".to_string(),
+                })
+            }
+            None => Err(Violation::NoBaseline),
+        }
     }
 
     /// Get SPSA state for inspection.
     pub fn spsa_state(&self) -> &SpsaState {
         self.proposer.spsa_state()
     }
+
+    /// Capture the proposer state for persistence across restarts.
+    pub fn snapshot_state(&self) -> AdaptiveEngineState {
+        AdaptiveEngineState {
+            spsa: self.proposer.spsa.snapshot(),
+            current_delta: self.proposer.current_delta.as_ref().map(|d| d.to_vec()),
+            baseline: self.proposer.baseline.as_ref().map(|b| b.to_vec()),
+            homeostasis: self.homeostasis.snapshot(),
+            consecutive_rejections: self.proposer.consecutive_rejections,
+        }
+    }
+
+    /// Restore proposer state captured by `snapshot_state`.
+    ///
+    /// The live configuration is not touched; the caller is expected to
+    /// construct the engine with the parameters it wants to resume from.
+    pub fn restore_state(&mut self, state: AdaptiveEngineState) {
+        self.homeostasis.restore(state.homeostasis);
+        let mut spsa = Spsa::from_snapshot(state.spsa);
+        spsa.set_learning_rate_scale(self.homeostasis.learning_rate_scale());
+        self.proposer.spsa = spsa;
+        self.proposer.current_delta = state.current_delta.map(ParamVec::from_vec);
+        self.proposer.baseline = state.baseline.map(ParamVec::from_vec);
+        self.proposer.consecutive_rejections = state.consecutive_rejections;
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::audit::AuditPriority;
     use crate::telemetry::TelemetryDigest;
 
     fn default_digest(objective_value: f64) -> TelemetryDigest {
@@ -197,7 +677,8 @@ mod tests {
     #[test]
     fn test_adaptive_engine_config_default() {
         let config = AdaptiveEngineConfig::default();
-        assert_eq!(config.seed, 42);
+        assert_eq!(config.rng_seed, 42);
+        assert_eq!(config.run_id, 0);
         assert_eq!(config.learning_rate, 0.1);
         assert_eq!(config.perturbation_scale, 0.01);
     }
@@ -223,6 +704,7 @@ mod tests {
             Proposal::ApplyPlus {
                 perturbation_id,
                 delta,
+                ..
             } => {
                 assert!(perturbation_id > 0);
                 assert_eq!(delta.len(), 2);
@@ -305,6 +787,306 @@ mod tests {
         assert_eq!(delta.len(), 2);
     }
 
+    #[test]
+    fn test_adaptive_engine_rollback_to_best_generation() {
+        let mut engine = AdaptiveEngine::new(
+            AdaptiveEngineConfig::default(),
+            ParamVec::from_slice(&[0.5, 0.5]),
+        );
+        let _ = engine.observe(default_digest(2.0));
+        engine.config.swap(ParamVec::from_slice(&[0.9, 0.1]));
+        let _ = engine.observe(default_digest(0.5));
+        engine.config.swap(ParamVec::from_slice(&[0.2, 0.8]));
+        let _ = engine.observe(default_digest(3.0));
+
+        let receipt = engine.rollback_to_best().unwrap();
+        assert_eq!(receipt.reverted_to_generation, 3);
+        assert_eq!(engine.snapshot().params.as_slice(), &[0.9, 0.1]);
+    }
+
+    #[test]
+    fn test_adaptive_engine_rollback_to_best_disabled() {
+        let config = AdaptiveEngineConfig {
+            generation_history: 0,
+            ..Default::default()
+        };
+        let mut engine = AdaptiveEngine::new(config, ParamVec::from_slice(&[0.5]));
+        let _ = engine.observe(default_digest(1.0));
+        assert!(matches!(
+            engine.rollback_to_best(),
+            Err(Violation::NoBaseline)
+        ));
+    }
+
+    #[test]
+    fn test_delta_too_large_rejections_shrink_perturbations() {
+        let config = AdaptiveEngineConfig {
+            guardrails: Guardrails {
+                max_delta_per_step: 0.001,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut engine = AdaptiveEngine::new(config, ParamVec::from_slice(&[0.5, 0.5]));
+
+        let mut accepted = None;
+        for step in 0..50 {
+            let proposal = engine.observe(default_digest(1.0)).unwrap();
+            if !matches!(proposal, Proposal::ApplyPlus { .. }) {
+                continue;
+            }
+            match engine.apply(proposal) {
+                Ok(_) => {
+                    accepted = Some(step);
+                    break;
+                }
+                Err(violation) => assert!(matches!(violation, Violation::DeltaTooLarge { .. })),
+            }
+        }
+
+        assert!(
+            accepted.is_some(),
+            "perturbations never fit max_delta_per_step"
+        );
+        assert!(engine.proposer.spsa.perturbation_scale_factor() < 1.0);
+        assert!(matches!(engine.spsa_state(), SpsaState::WaitingPlus { .. }));
+    }
+
+    #[test]
+    fn test_restore_state_keeps_shrunk_perturbation_scale() {
+        let config = AdaptiveEngineConfig {
+            guardrails: Guardrails {
+                max_delta_per_step: 1e-6,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut engine = AdaptiveEngine::new(config.clone(), ParamVec::from_slice(&[0.5, 0.5]));
+        for _ in 0..10 {
+            let proposal = engine.observe(default_digest(1.0)).unwrap();
+            if matches!(proposal, Proposal::ApplyPlus { .. }) {
+                assert!(engine.apply(proposal).is_err());
+            }
+        }
+        let factor = engine.proposer.spsa.perturbation_scale_factor();
+        assert!(factor < 1.0);
+
+        let json = serde_json::to_string(&engine.snapshot_state()).unwrap();
+        let state: AdaptiveEngineState = serde_json::from_str(&json).unwrap();
+        let mut restored = AdaptiveEngine::new(config, ParamVec::from_slice(&[0.5, 0.5]));
+        restored.restore_state(state);
+        assert_eq!(restored.proposer.spsa.perturbation_scale_factor(), factor);
+        assert_eq!(
+            restored.proposer.consecutive_rejections,
+            engine.proposer.consecutive_rejections
+        );
+    }
+
+    #[test]
+    fn test_stale_proposal_rejected_after_intervening_swap() {
+        let mut engine = AdaptiveEngine::new(
+            AdaptiveEngineConfig::default(),
+            ParamVec::from_slice(&[0.5, 0.5]),
+        );
+        let stale = engine.observe(default_digest(1.0)).unwrap();
+        assert_eq!(stale.generation(), Some(0));
+
+        // The config advances (e.g. a rollback) before the +Δ is applied.
+        engine.config.swap(ParamVec::from_slice(&[0.4, 0.4]));
+        engine.config.swap(ParamVec::from_slice(&[0.6, 0.6]));
+        assert!(matches!(
+            engine.apply(stale),
+            Err(Violation::StaleProposal {
+                expected_gen: 0,
+                actual_gen: 2,
+            })
+        ));
+        assert_eq!(engine.snapshot().params.as_slice(), &[0.6, 0.6]);
+        assert_eq!(
+            engine.stats().rejected_by_violation,
+            BTreeMap::from([("StaleProposal", 1)])
+        );
+
+        // The stale +Δ is dropped and a fresh one targets the live config.
+        let fresh = engine.observe(default_digest(1.0)).unwrap();
+        assert!(matches!(fresh, Proposal::ApplyPlus { .. }));
+        assert_eq!(fresh.generation(), Some(2));
+        assert_eq!(engine.apply(fresh).unwrap().new_generation, 3);
+    }
+
+    #[test]
+    fn test_stale_minus_abandons_the_iteration() {
+        let mut engine = AdaptiveEngine::new(
+            AdaptiveEngineConfig::default(),
+            ParamVec::from_slice(&[0.5, 0.5]),
+        );
+        let minus = loop {
+            let proposal = engine.observe(default_digest(1.0)).unwrap();
+            if matches!(proposal, Proposal::ApplyMinus { .. }) {
+                break proposal;
+            }
+            engine.apply(proposal).unwrap();
+        };
+        let iteration = engine.proposer.iteration();
+
+        // The config advances between the +Δ and the −Δ.
+        engine.config.swap(ParamVec::from_slice(&[0.4, 0.4]));
+        engine.config.swap(ParamVec::from_slice(&[0.6, 0.6]));
+        assert!(matches!(
+            engine.apply(minus),
+            Err(Violation::StaleProposal { .. })
+        ));
+        assert!(matches!(engine.spsa_state(), SpsaState::Ready));
+        assert!(engine.proposer.current_perturbation().is_none());
+
+        // No gradient comes from the half-measured pair: a fresh +Δ starts
+        // the same iteration over.
+        let fresh = engine.observe(default_digest(1.0)).unwrap();
+        assert!(matches!(fresh, Proposal::ApplyPlus { .. }));
+        assert_eq!(engine.proposer.iteration(), iteration);
+        engine.apply(fresh).unwrap();
+    }
+
+    #[test]
+    fn test_engine_stats_count_outcomes() {
+        let config = AdaptiveEngineConfig {
+            guardrails: Guardrails {
+                max_delta_per_step: 0.001,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut engine = AdaptiveEngine::new(config, ParamVec::from_slice(&[0.5, 0.5]));
+        assert_eq!(engine.stats(), EngineStats::default());
+
+        // The default perturbation is 0.01 per parameter: too large.
+        let proposal = engine.observe(default_digest(1.0)).unwrap();
+        assert!(engine.apply(proposal).is_err());
+        for _ in 0..2 {
+            engine
+                .apply(Proposal::ApplyPlus {
+                    perturbation_id: 99,
+                    delta: ParamVec::from_slice(&[0.0005, -0.0005]),
+                    generation: None,
+                })
+                .unwrap();
+        }
+        // A fresh +Δ, then a digest that does not complete its window.
+        let proposal = engine.observe(default_digest(1.0)).unwrap();
+        assert!(matches!(proposal, Proposal::ApplyPlus { .. }));
+        let waiting = engine.observe(default_digest(1.0)).unwrap();
+        assert!(matches!(waiting, Proposal::NoChange { .. }));
+        engine.pause();
+        engine.observe(default_digest(1.0)).unwrap();
+        // Applying while paused is not an outcome.
+        engine
+            .apply(Proposal::ApplyPlus {
+                perturbation_id: 100,
+                delta: ParamVec::from_slice(&[0.0005, 0.0005]),
+                generation: None,
+            })
+            .unwrap();
+
+        let stats = engine.stats();
+        assert_eq!(stats.applied, 2);
+        assert_eq!(
+            stats.rejected_by_violation,
+            BTreeMap::from([("DeltaTooLarge", 1)])
+        );
+        assert_eq!(
+            stats.no_change_by_reason,
+            BTreeMap::from([("EvalTimeout", 1), ("Paused", 1)])
+        );
+        let json = serde_json::to_value(&stats).unwrap();
+        assert_eq!(json["rejected_by_violation"]["DeltaTooLarge"], 1);
+    }
+
+    #[test]
+    fn test_paused_engine_freezes_and_buffers() {
+        let mut engine = AdaptiveEngine::new(
+            AdaptiveEngineConfig::default(),
+            ParamVec::from_slice(&[0.5]),
+        );
+        let proposal = engine.observe(default_digest(1.0)).unwrap();
+        assert!(matches!(proposal, Proposal::ApplyPlus { .. }));
+        let iteration = engine.proposer.iteration();
+        let counter = engine.proposer.spsa.perturbation_counter();
+
+        engine.pause();
+        assert!(engine.is_paused());
+        let generation = engine.snapshot().generation;
+        // A proposal issued before the pause is not applied.
+        let receipt = engine.apply(proposal).unwrap();
+        assert_eq!(receipt.new_generation, generation);
+        for i in 0..20 {
+            let result = engine.observe(default_digest(i as f64)).unwrap();
+            assert!(matches!(
+                result,
+                Proposal::NoChange {
+                    reason: NoChangeReason::Paused
+                }
+            ));
+        }
+        assert_eq!(engine.proposer.iteration(), iteration);
+        assert_eq!(engine.proposer.spsa.perturbation_counter(), counter);
+        assert_eq!(engine.snapshot().params.as_slice(), &[0.5]);
+
+        let buffered = engine.resume();
+        assert!(!engine.is_paused());
+        assert_eq!(buffered.len(), 20);
+        assert_eq!(buffered[3].objective_value, 3.0);
+        // The interrupted +Δ window is restarted, not continued into −Δ.
+        assert!(matches!(engine.spsa_state(), SpsaState::Ready));
+        assert!(matches!(
+            engine.observe(default_digest(1.0)).unwrap(),
+            Proposal::ApplyPlus { .. }
+        ));
+        assert_eq!(engine.proposer.iteration(), iteration);
+    }
+
+    #[test]
+    fn test_perturbation_respects_guardrail_bounds() {
+        let config = AdaptiveEngineConfig {
+            guardrails: Guardrails {
+                bounds: Some(vec![(0.0, 1.0), (0.0, 1.0)]),
+                ..Default::default()
+            },
+            perturbation_scale: 0.05,
+            ..Default::default()
+        };
+        let mut engine = AdaptiveEngine::new(config, ParamVec::from_slice(&[0.99, 0.5]));
+        let Proposal::ApplyPlus { delta, .. } = engine.observe(default_digest(1.0)).unwrap() else {
+            panic!("expected ApplyPlus");
+        };
+        assert!((delta[0].abs() - 0.01).abs() < 1e-12);
+        assert!((delta[1].abs() - 0.05).abs() < 1e-12);
+        assert!(engine
+            .apply(Proposal::ApplyPlus {
+                perturbation_id: 1,
+                delta,
+                generation: None,
+            })
+            .is_ok());
+    }
+
+    #[test]
+    fn test_rejection_backoff_disabled_keeps_scale() {
+        let spsa = Spsa::new(42, 2, 0.1, 0.01, SpsaConfig::default());
+        let mut proposer = SpsaProposer::new(spsa).with_backoff(RejectionBackoff {
+            rejections_to_shrink: 0,
+            ..Default::default()
+        });
+        let violation = Violation::DeltaTooLarge {
+            param_id: 0,
+            delta: 0.01,
+            max: 0.001,
+        };
+        for _ in 0..5 {
+            proposer.on_apply_result(Some(1), Err(&violation));
+        }
+        assert_eq!(proposer.spsa.perturbation_scale_factor(), 1.0);
+    }
+
     #[test]
     fn test_spsa_proposer_waiting_plus_no_samples() {
         let spsa = Spsa::new(42, 2, 0.1, 0.01, SpsaConfig::default());
@@ -325,6 +1107,45 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_plus_and_minus_measured_symmetrically_around_theta() {
+        let theta = [0.5, 0.4];
+        let mut engine = AdaptiveEngine::new(
+            AdaptiveEngineConfig::default(),
+            ParamVec::from_slice(&theta),
+        );
+        let (mut plus, mut minus, mut perturbation, mut step) = (None, None, None, None);
+        while step.is_none() {
+            let params = engine.snapshot().params.clone();
+            let objective = (params[0] - 0.2).powi(2) + (params[1] - 0.7).powi(2);
+            let proposal = engine.observe(default_digest(objective)).unwrap();
+            let applied = proposal.clone();
+            engine.apply(proposal).unwrap();
+            let live = engine.snapshot().params.clone();
+            match applied {
+                Proposal::ApplyPlus { delta, .. } => {
+                    perturbation = Some(delta);
+                    plus = Some(live);
+                }
+                Proposal::ApplyMinus { .. } => minus = Some(live),
+                Proposal::Update {
+                    gradient_estimate, ..
+                } => step = Some(gradient_estimate),
+                Proposal::NoChange { .. } => {}
+            }
+        }
+
+        let (plus, minus) = (plus.unwrap(), minus.unwrap());
+        let (delta, step) = (perturbation.unwrap(), step.unwrap());
+        for i in 0..theta.len() {
+            assert!((plus[i] - (theta[i] + delta[i])).abs() < 1e-12);
+            assert!((minus[i] - (theta[i] - delta[i])).abs() < 1e-12);
+            // The update steps from θ, not from θ − Δ.
+            assert!((engine.snapshot().params[i] - (theta[i] + step[i])).abs() < 1e-12);
+        }
+        assert!(matches!(engine.spsa_state(), SpsaState::Ready));
+    }
+
     #[test]
     fn test_adaptive_engine_apply() {
         let config = AdaptiveEngineConfig::default();
@@ -340,4 +1161,220 @@ mod tests {
         // NoChange should be applied successfully
         assert!(result.is_ok());
     }
+
+    fn proposal_delta(proposal: &Proposal) -> Option<Vec<f64>> {
+        match proposal {
+            Proposal::ApplyPlus { delta, .. }
+            | Proposal::ApplyMinus { delta, .. }
+            | Proposal::Update { delta, .. } => Some(delta.to_vec()),
+            Proposal::NoChange { .. } => None,
+        }
+    }
+
+    #[test]
+    fn test_partial_engine_config_json_fills_defaults() {
+        let config: AdaptiveEngineConfig = serde_json::from_str(
+            r#"{
+                "run_id": 7,
+                "learning_rate": 0.05,
+                "spsa": {"alpha": 0.7, "scale_by_range": true},
+                "guardrails": {"max_delta_per_step": 0.2, "bounds": [[0.0, 1.0], [-1.0, 1.0]]},
+                "homeostasis": {"enabled": true}
+            }"#,
+        )
+        .unwrap();
+
+        let defaults = AdaptiveEngineConfig::default();
+        assert_eq!(config.run_id, 7);
+        assert_eq!(config.learning_rate, 0.05);
+        assert_eq!(config.rng_seed, defaults.rng_seed);
+        assert_eq!(config.perturbation_scale, defaults.perturbation_scale);
+        assert_eq!(config.generation_history, defaults.generation_history);
+        assert_eq!(config.rejection_backoff, defaults.rejection_backoff);
+        assert_eq!(
+            config.spsa,
+            SpsaConfig {
+                alpha: 0.7,
+                scale_by_range: true,
+                ..SpsaConfig::default()
+            }
+        );
+        assert_eq!(
+            config.guardrails,
+            Guardrails {
+                max_delta_per_step: 0.2,
+                bounds: Some(vec![(0.0, 1.0), (-1.0, 1.0)]),
+                ..Guardrails::default()
+            }
+        );
+        assert!(config.homeostasis.enabled);
+        assert_eq!(config.homeostasis.window, defaults.homeostasis.window);
+
+        let json = serde_json::to_string(&config).unwrap();
+        assert_eq!(
+            serde_json::from_str::<AdaptiveEngineConfig>(&json).unwrap(),
+            config
+        );
+        assert_eq!(
+            serde_json::from_str::<AdaptiveEngineConfig>("{}").unwrap(),
+            defaults
+        );
+    }
+
+    #[test]
+    fn test_restored_engine_matches_uninterrupted_engine() {
+        let config = AdaptiveEngineConfig::default();
+        let initial_params = ParamVec::from_slice(&[0.5, 0.5]);
+        let mut engine = AdaptiveEngine::new(config.clone(), initial_params.clone());
+
+        // Drive the engine through more than one full SPSA iteration
+        for i in 0..13 {
+            let _ = engine.observe(default_digest(1.0 + i as f64 * 0.1));
+        }
+        assert!(engine.proposer.iteration() > 0);
+
+        let json = serde_json::to_string(&engine.snapshot_state()).unwrap();
+        let state: AdaptiveEngineState = serde_json::from_str(&json).unwrap();
+
+        let mut restored = AdaptiveEngine::new(config, initial_params);
+        restored.restore_state(state);
+        assert_eq!(restored.proposer.iteration(), engine.proposer.iteration());
+
+        for i in 0..12 {
+            let digest = default_digest(2.0 - i as f64 * 0.1);
+            let expected = engine.observe(digest.clone()).unwrap();
+            let actual = restored.observe(digest).unwrap();
+            assert_eq!(proposal_delta(&actual), proposal_delta(&expected));
+        }
+    }
+
+    #[test]
+    fn test_noisy_digests_trigger_homeostasis_damping() {
+        let audit = Arc::new(AuditQueue::new(16));
+        let config = AdaptiveEngineConfig {
+            homeostasis: HomeostasisConfig {
+                enabled: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut engine = AdaptiveEngine::new(config, ParamVec::from_slice(&[0.5, 0.5]))
+            .with_audit_queue(audit.clone());
+
+        // Calm stream establishes the baseline variance
+        for i in 0..32 {
+            let _ = engine.observe(default_digest(1.0 + 0.01 * (i % 3) as f64));
+        }
+        assert!(!engine.homeostasis().is_damped());
+        assert_eq!(engine.proposer.spsa.learning_rate_scale(), 1.0);
+
+        // Oscillating objective: damp instead of chasing noise
+        for i in 0..8 {
+            let _ = engine.observe(default_digest(if i % 2 == 0 { 10.0 } else { -10.0 }));
+        }
+        assert!(engine.homeostasis().is_damped());
+        let scale = engine.proposer.spsa.learning_rate_scale();
+        assert!(scale < 1.0);
+        let k = engine.proposer.spsa.iteration();
+        assert!(
+            (engine.proposer.spsa.effective_learning_rate(k)
+                - scale * engine.proposer.spsa.learning_rate(k))
+            .abs()
+                < 1e-15
+        );
+
+        let events = audit.drain();
+        assert_eq!(events.len(), 1);
+        assert!(matches!(events[0].event_type, EventType::Homeostasis));
+    }
+
+    /// Deltas proposed over `digests` digests with a drifting objective.
+    fn proposal_deltas(engine: &mut AdaptiveEngine, digests: usize) -> Vec<Option<Vec<f64>>> {
+        (0..digests)
+            .map(|i| {
+                let proposal = engine
+                    .observe(default_digest(1.0 + i as f64 * 0.1))
+                    .unwrap();
+                proposal_delta(&proposal)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_run_id_does_not_change_perturbations() {
+        let audit = Arc::new(AuditQueue::new(16));
+        let config = |run_id| AdaptiveEngineConfig {
+            run_id,
+            rng_seed: 7,
+            ..Default::default()
+        };
+        let initial_params = ParamVec::from_slice(&[0.5, 0.5, 0.5]);
+        let mut a =
+            AdaptiveEngine::new(config(1), initial_params.clone()).with_audit_queue(audit.clone());
+        let mut b = AdaptiveEngine::new(config(2), initial_params);
+        let deltas = proposal_deltas(&mut a, 24);
+        assert!(deltas.iter().any(Option::is_some));
+        assert_eq!(deltas, proposal_deltas(&mut b, 24));
+
+        a.apply(Proposal::ApplyPlus {
+            perturbation_id: 1,
+            delta: ParamVec::from_slice(&[0.01, 0.01, 0.01]),
+            generation: None,
+        })
+        .unwrap();
+        assert!(audit.drain().iter().all(|event| event.run_id == 1));
+    }
+
+    #[test]
+    fn test_reseed_changes_stream_deterministically() {
+        let initial_params = ParamVec::from_slice(&[0.5, 0.5, 0.5]);
+        let mut engines: Vec<AdaptiveEngine> = (0..3)
+            .map(|_| AdaptiveEngine::new(AdaptiveEngineConfig::default(), initial_params.clone()))
+            .collect();
+        for engine in &mut engines {
+            let _ = proposal_deltas(engine, 3);
+        }
+        assert!(!matches!(engines[0].spsa_state(), SpsaState::Ready));
+
+        engines[0].reseed(99);
+        engines[1].reseed(99);
+        assert!(matches!(engines[0].spsa_state(), SpsaState::Ready));
+        let reseeded = proposal_deltas(&mut engines[0], 48);
+        assert_eq!(reseeded, proposal_deltas(&mut engines[1], 48));
+        assert_ne!(reseeded, proposal_deltas(&mut engines[2], 48));
+    }
+
+    #[test]
+    fn test_saturated_audit_keeps_apply_and_rollback() {
+        let audit = Arc::new(AuditQueue::new(4));
+        let mut engine = AdaptiveEngine::new(
+            AdaptiveEngineConfig::default(),
+            ParamVec::from_slice(&[0.5, 0.5]),
+        )
+        .with_audit_queue(audit.clone());
+        for i in 0..4 {
+            audit.enqueue(AuditEvent::new(EventType::Digest, i, 0, 0));
+        }
+
+        let _ = engine.observe(default_digest(1.0));
+        let proposal = Proposal::ApplyPlus {
+            perturbation_id: 7,
+            delta: ParamVec::from_slice(&[0.01, -0.01]),
+            generation: None,
+        };
+        engine
+            .apply(proposal)
+            .expect("apply should pass guardrails");
+        engine
+            .rollback_to_best()
+            .expect("a generation was recorded");
+
+        assert_eq!(audit.dropped_count(AuditPriority::Low), 2);
+        assert_eq!(audit.dropped_count(AuditPriority::Critical), 0);
+        let events = audit.drain_prioritized(2);
+        assert!(matches!(events[0].event_type, EventType::Apply));
+        assert_eq!(events[0].proposal_id, Some(7));
+        assert!(matches!(events[1].event_type, EventType::Rollback));
+        assert_eq!(audit.len(), 2);
+    }
 }
@@ -1,49 +1,159 @@
 //! Adaptive Proposer trait and implementations.
 //!
 //! Constitution: II.20 - Tier 2 MUST NOT directly mutate production state.
+//!
+//! Proposals serialize to tagged JSON so the Tier-1 executor can run in a
+//! separate process from the proposer. Schema version
+//! [`PROPOSAL_SCHEMA_VERSION`]:
+//!
+//! ```text
+//! {"type":"apply_plus","perturbation_id":3,"delta":[0.1,-0.1],"generation":7}
+//! {"type":"apply_minus","perturbation_id":3,"delta":[0.1,-0.1]}
+//! {"type":"update","iteration":4,"delta":[...],"gradient_estimate":[...]}
+//! {"type":"no_change","reason":"safe_mode"}
+//! ```
+//!
+//! `generation` is left out when unset. A [`ProposalResult`] is
+//! `{"Ok":<proposal>}` or `{"Err":{"type":"invalid_digest","message":"..."}}`.
+
+use crate::{config_atomic::ParamVec, executor::Violation, telemetry::TelemetryDigest};
+use serde::{Deserialize, Serialize};
+
+/// Version of the JSON form of [`Proposal`], [`NoChangeReason`] and
+/// [`ProposalError`]. Bumped on any change an older reader cannot parse.
+pub const PROPOSAL_SCHEMA_VERSION: u32 = 1;
 
-use crate::{config_atomic::ParamVec, telemetry::TelemetryDigest};
+/// Serde for [`ParamVec`] as a plain array of numbers.
+mod param_vec {
+    use super::ParamVec;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(vec: &ParamVec, serializer: S) -> Result<S::Ok, S::Error> {
+        vec.as_slice().serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<ParamVec, D::Error> {
+        Vec::<f64>::deserialize(deserializer).map(ParamVec::from_vec)
+    }
+}
 
 /// Error from proposal generation.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "message", rename_all = "snake_case")]
 pub enum ProposalError {
     InvalidDigest(String),
     InternalError(String),
 }
 
 /// Reason for NoChange proposal.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum NoChangeReason {
     EvalTimeout,
     SafeMode,
     ConstraintViolation,
     CooldownActive,
     BudgetExhausted,
+    /// Adaptation is paused (see `AdaptiveEngine::pause`).
+    Paused,
+}
+
+impl NoChangeReason {
+    /// Variant names, indexed by [`kind_index`](Self::kind_index).
+    pub const KINDS: [&'static str; 6] = [
+        "EvalTimeout",
+        "SafeMode",
+        "ConstraintViolation",
+        "CooldownActive",
+        "BudgetExhausted",
+        "Paused",
+    ];
+
+    /// Dense index of the variant, for per-reason counters.
+    pub fn kind_index(&self) -> usize {
+        match self {
+            Self::EvalTimeout => 0,
+            Self::SafeMode => 1,
+            Self::ConstraintViolation => 2,
+            Self::CooldownActive => 3,
+            Self::BudgetExhausted => 4,
+            Self::Paused => 5,
+        }
+    }
+
+    /// Variant name, e.g. `"SafeMode"`.
+    pub fn kind(&self) -> &'static str {
+        Self::KINDS[self.kind_index()]
+    }
 }
 
 /// Proposal from Tier 2 to Tier 1.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
 pub enum Proposal {
     /// Apply +Δ perturbation for y+ evaluation.
     ApplyPlus {
         perturbation_id: u64,
+        #[serde(with = "param_vec")]
         delta: ParamVec,
+        /// Config generation the delta was computed against (see
+        /// [`Proposal::generation`]).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        generation: Option<u64>,
     },
-    /// Apply −Δ perturbation for y− evaluation.
+    /// Move to θ − Δ for y− evaluation, θ being the config the matching
+    /// `ApplyPlus` was proposed against. `delta` is relative to the live
+    /// config, so after an applied +Δ it is −2Δ.
     ApplyMinus {
         perturbation_id: u64,
+        #[serde(with = "param_vec")]
         delta: ParamVec,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        generation: Option<u64>,
     },
-    /// Apply real gradient-based update.
+    /// Apply real gradient-based update. `delta` moves the live config
+    /// (θ − Δ after a perturbation pair) to θ plus the step.
     Update {
         iteration: u64,
+        #[serde(with = "param_vec")]
         delta: ParamVec,
+        #[serde(with = "param_vec")]
         gradient_estimate: ParamVec,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        generation: Option<u64>,
     },
     /// No change (timeout, safe mode, etc.).
     NoChange { reason: NoChangeReason },
 }
 
+impl Proposal {
+    /// Config generation the delta was computed against.
+    ///
+    /// The executor rejects the proposal with `Violation::StaleProposal` if
+    /// the live generation has moved further than
+    /// `Guardrails::max_generation_lag` from it. `None` skips the check.
+    pub fn generation(&self) -> Option<u64> {
+        match self {
+            Self::ApplyPlus { generation, .. }
+            | Self::ApplyMinus { generation, .. }
+            | Self::Update { generation, .. } => *generation,
+            Self::NoChange { .. } => None,
+        }
+    }
+
+    /// Stamp the proposal with the generation it was computed against.
+    /// `NoChange` carries no delta and is returned unchanged.
+    pub fn with_generation(mut self, config_generation: u64) -> Self {
+        match &mut self {
+            Self::ApplyPlus { generation, .. }
+            | Self::ApplyMinus { generation, .. }
+            | Self::Update { generation, .. } => *generation = Some(config_generation),
+            Self::NoChange { .. } => {}
+        }
+        self
+    }
+}
+
 /// Result of observing telemetry.
 pub type ProposalResult = Result<Proposal, ProposalError>;
 
@@ -62,6 +172,11 @@ pub trait AdaptiveProposer {
 
     /// Get the current SPSA iteration count.
     fn iteration(&self) -> u64;
+
+    /// Learn from the executor's verdict on a proposal. `perturbation_id` is
+    /// set for `ApplyPlus`/`ApplyMinus`; `result` carries the violation that
+    /// rejected it, if any.
+    fn on_apply_result(&mut self, _perturbation_id: Option<u64>, _result: Result<(), &Violation>) {}
 }
 
 #[cfg(test)]
@@ -75,6 +190,7 @@ mod tests {
         let p = Proposal::ApplyPlus {
             perturbation_id: 1,
             delta: delta.clone(),
+            generation: None,
         };
 
         match p {
@@ -86,4 +202,74 @@ mod tests {
             _ => panic!("Expected ApplyPlus"),
         }
     }
+
+    #[test]
+    fn test_proposal_json_round_trip() {
+        let delta = ParamVec::from_slice(&[0.1, -0.2]);
+        let proposals = [
+            Proposal::ApplyPlus {
+                perturbation_id: 3,
+                delta: delta.clone(),
+                generation: Some(7),
+            },
+            Proposal::ApplyMinus {
+                perturbation_id: 3,
+                delta: delta.clone(),
+                generation: None,
+            },
+            Proposal::Update {
+                iteration: 4,
+                delta: delta.clone(),
+                gradient_estimate: ParamVec::from_slice(&[1.5, 0.0]),
+                generation: Some(8),
+            },
+            Proposal::NoChange {
+                reason: NoChangeReason::CooldownActive,
+            },
+        ];
+        for proposal in proposals {
+            let json = serde_json::to_string(&proposal).unwrap();
+            let back: Proposal = serde_json::from_str(&json).unwrap();
+            assert_eq!(back, proposal, "{}", json);
+        }
+
+        let result: ProposalResult = Err(ProposalError::InvalidDigest("empty".into()));
+        let json = serde_json::to_string(&result).unwrap();
+        assert_eq!(
+            serde_json::from_str::<ProposalResult>(&json).unwrap(),
+            result
+        );
+    }
+
+    #[test]
+    fn test_proposal_schema_v1() {
+        let proposal: Proposal =
+            serde_json::from_str(r#"{"type":"apply_plus","perturbation_id":3,"delta":[0.5,-0.5]}"#)
+                .unwrap();
+        assert_eq!(
+            proposal,
+            Proposal::ApplyPlus {
+                perturbation_id: 3,
+                delta: ParamVec::from_slice(&[0.5, -0.5]),
+                generation: None,
+            }
+        );
+        let json = serde_json::to_value(Proposal::NoChange {
+            reason: NoChangeReason::SafeMode,
+        })
+        .unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"type": "no_change", "reason": "safe_mode"})
+        );
+        let json = serde_json::to_value(Err::<Proposal, _>(ProposalError::InternalError(
+            "boom".into(),
+        )))
+        .unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"Err": {"type": "internal_error", "message": "boom"}})
+        );
+        assert_eq!(PROPOSAL_SCHEMA_VERSION, 1);
+    }
 }
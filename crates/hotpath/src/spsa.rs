@@ -7,6 +7,7 @@ use crate::config_atomic::ParamVec;
 use rand::prelude::*;
 use rand::SeedableRng;
 use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
 
 /// SPSA state machine states.
 #[derive(Clone, Debug, PartialEq)]
@@ -28,8 +29,82 @@ pub enum SpsaState {
     },
 }
 
-/// SPSA configuration.
-#[derive(Clone, Debug)]
+/// Serializable mirror of [`SpsaState`] (dense vectors stored as `Vec<f64>`).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum SpsaStateSnapshot {
+    Ready,
+    WaitingPlus {
+        perturbation_id: u64,
+        delta: Vec<f64>,
+        accumulated: Vec<f64>,
+    },
+    WaitingMinus {
+        perturbation_id: u64,
+        delta: Vec<f64>,
+        y_plus: f64,
+        accumulated: Vec<f64>,
+    },
+}
+
+impl From<&SpsaState> for SpsaStateSnapshot {
+    fn from(state: &SpsaState) -> Self {
+        match state {
+            SpsaState::Ready => Self::Ready,
+            SpsaState::WaitingPlus {
+                perturbation_id,
+                delta,
+                accumulated,
+            } => Self::WaitingPlus {
+                perturbation_id: *perturbation_id,
+                delta: delta.to_vec(),
+                accumulated: accumulated.clone(),
+            },
+            SpsaState::WaitingMinus {
+                perturbation_id,
+                delta,
+                y_plus,
+                accumulated,
+            } => Self::WaitingMinus {
+                perturbation_id: *perturbation_id,
+                delta: delta.to_vec(),
+                y_plus: *y_plus,
+                accumulated: accumulated.clone(),
+            },
+        }
+    }
+}
+
+impl From<SpsaStateSnapshot> for SpsaState {
+    fn from(snapshot: SpsaStateSnapshot) -> Self {
+        match snapshot {
+            SpsaStateSnapshot::Ready => Self::Ready,
+            SpsaStateSnapshot::WaitingPlus {
+                perturbation_id,
+                delta,
+                accumulated,
+            } => Self::WaitingPlus {
+                perturbation_id,
+                delta: ParamVec::from_vec(delta),
+                accumulated,
+            },
+            SpsaStateSnapshot::WaitingMinus {
+                perturbation_id,
+                delta,
+                y_plus,
+                accumulated,
+            } => Self::WaitingMinus {
+                perturbation_id,
+                delta: ParamVec::from_vec(delta),
+                y_plus,
+                accumulated,
+            },
+        }
+    }
+}
+
+/// SPSA configuration. Fields missing when deserializing take their default.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct SpsaConfig {
     /// Minimum digests to collect per perturbation.
     pub eval_window_digests: usize,
@@ -43,6 +118,10 @@ pub struct SpsaConfig {
     pub gamma: f64,
     /// Stability constant A.
     pub stability_a: f64,
+    /// Scale each parameter's perturbation and update by the width of its
+    /// bounds (see [`Spsa::set_ranges`]), so `c` and `a` act on the
+    /// range-normalized space.
+    pub scale_by_range: bool,
 }
 
 impl Default for SpsaConfig {
@@ -54,13 +133,58 @@ impl Default for SpsaConfig {
             alpha: 0.602,
             gamma: 0.101,
             stability_a: 10.0,
+            scale_by_range: false,
         }
     }
 }
 
+/// Full serializable SPSA state.
+///
+/// Captures the decay schedule position (`iteration`), the schedule constants,
+/// the eval-window state machine and the exact RNG stream position, so a
+/// restored optimizer continues with the same perturbations it would have
+/// generated had it never stopped.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SpsaSnapshot {
+    /// ChaCha8 seed bytes.
+    pub rng_seed: [u8; 32],
+    /// ChaCha8 stream id.
+    pub rng_stream: u64,
+    /// ChaCha8 word position within the stream.
+    pub rng_word_pos: u128,
+    /// Eval-window state machine.
+    pub state: SpsaStateSnapshot,
+    /// Iteration counter `k` driving the decay schedules.
+    pub iteration: u64,
+    /// Perturbation counter.
+    pub perturbation_counter: u64,
+    /// Schedule configuration (`A`, `α`, `γ`, eval windows).
+    pub config: SpsaConfig,
+    /// Initial learning rate `a`.
+    pub initial_learning_rate: f64,
+    /// Initial perturbation scale `c`.
+    pub initial_perturbation_scale: f64,
+    /// Number of parameters.
+    pub num_params: usize,
+    /// Per-parameter range widths (see [`Spsa::set_ranges`]).
+    #[serde(default)]
+    pub ranges: Option<Vec<f64>>,
+    /// Multiplier on `c_k` left by rejection backoff (1.0 when unshrunk).
+    #[serde(default = "unit_scale")]
+    pub perturbation_scale_factor: f64,
+}
+
+fn unit_scale() -> f64 {
+    1.0
+}
+
 /// SPSA optimizer (Tier 2 component).
 ///
 /// Constitution: II.16 - SPSA Specification
+///
+/// Serializes through [`SpsaSnapshot`] so the schedule survives restarts.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(into = "SpsaSnapshot", from = "SpsaSnapshot")]
 pub struct Spsa {
     rng: ChaCha8Rng,
     state: SpsaState,
@@ -70,6 +194,13 @@ pub struct Spsa {
     initial_learning_rate: f64,
     initial_perturbation_scale: f64,
     num_params: usize,
+    /// Multiplier on `a_k`, lowered by homeostasis while the objective is noisy.
+    learning_rate_scale: f64,
+    /// Multiplier on `c_k`, lowered while the executor rejects perturbations
+    /// as too large.
+    perturbation_scale_factor: f64,
+    /// Width of each parameter's range, when perturbations are scaled by it.
+    ranges: Option<ParamVec>,
 }
 
 impl Spsa {
@@ -90,9 +221,90 @@ impl Spsa {
             initial_learning_rate: learning_rate,
             initial_perturbation_scale: perturbation_scale,
             num_params,
+            learning_rate_scale: 1.0,
+            perturbation_scale_factor: 1.0,
+            ranges: None,
         }
     }
 
+    /// Capture the full optimizer state.
+    pub fn snapshot(&self) -> SpsaSnapshot {
+        SpsaSnapshot {
+            rng_seed: self.rng.get_seed(),
+            rng_stream: self.rng.get_stream(),
+            rng_word_pos: self.rng.get_word_pos(),
+            state: SpsaStateSnapshot::from(&self.state),
+            iteration: self.iteration,
+            perturbation_counter: self.perturbation_counter,
+            config: self.config.clone(),
+            initial_learning_rate: self.initial_learning_rate,
+            initial_perturbation_scale: self.initial_perturbation_scale,
+            num_params: self.num_params,
+            ranges: self.ranges.as_ref().map(|ranges| ranges.to_vec()),
+            perturbation_scale_factor: self.perturbation_scale_factor,
+        }
+    }
+
+    /// Rebuild an optimizer from a captured state.
+    pub fn from_snapshot(snapshot: SpsaSnapshot) -> Self {
+        let mut rng = ChaCha8Rng::from_seed(snapshot.rng_seed);
+        rng.set_stream(snapshot.rng_stream);
+        rng.set_word_pos(snapshot.rng_word_pos);
+        Self {
+            rng,
+            state: snapshot.state.into(),
+            iteration: snapshot.iteration,
+            perturbation_counter: snapshot.perturbation_counter,
+            config: snapshot.config,
+            initial_learning_rate: snapshot.initial_learning_rate,
+            initial_perturbation_scale: snapshot.initial_perturbation_scale,
+            num_params: snapshot.num_params,
+            learning_rate_scale: 1.0,
+            perturbation_scale_factor: snapshot
+                .perturbation_scale_factor
+                .clamp(f64::MIN_POSITIVE, 1.0),
+            ranges: snapshot.ranges.map(ParamVec::from_vec),
+        }
+    }
+
+    /// Work in the space where every parameter's range has width 1.
+    ///
+    /// Each component of a perturbation becomes `c_k` times the width of
+    /// that parameter's `bounds`, and each update `a_k` times the gradient
+    /// times the width squared, so a parameter spanning `[16, 512]` and one
+    /// spanning `[1e-5, 1e-1]` both move by the same fraction of their range.
+    /// Widths that are not positive and finite count as 1. `None` goes back
+    /// to one absolute scale for all parameters.
+    pub fn set_ranges(&mut self, bounds: Option<&[(f64, f64)]>) {
+        self.ranges = bounds.map(|bounds| {
+            bounds
+                .iter()
+                .map(|&(min, max)| {
+                    let width = max - min;
+                    if width.is_finite() && width > 0.0 {
+                        width
+                    } else {
+                        1.0
+                    }
+                })
+                .collect()
+        });
+    }
+
+    /// Range width of parameter `i` (1 without [`set_ranges`](Self::set_ranges)).
+    fn range(&self, i: usize) -> f64 {
+        self.ranges
+            .as_ref()
+            .and_then(|ranges| ranges.get(i))
+            .copied()
+            .unwrap_or(1.0)
+    }
+
+    /// Schedule and eval-window configuration.
+    pub fn config(&self) -> &SpsaConfig {
+        &self.config
+    }
+
     /// Get current iteration count.
     pub fn iteration(&self) -> u64 {
         self.iteration
@@ -114,20 +326,87 @@ impl Spsa {
         self.initial_learning_rate / (k_f + 1.0 + self.config.stability_a).powf(self.config.alpha)
     }
 
+    /// Learning rate actually applied at iteration k: `a_k * learning_rate_scale`.
+    pub fn effective_learning_rate(&self, k: u64) -> f64 {
+        self.learning_rate(k) * self.learning_rate_scale
+    }
+
+    /// Current multiplier on the learning rate (1.0 = undamped).
+    pub fn learning_rate_scale(&self) -> f64 {
+        self.learning_rate_scale
+    }
+
+    /// Set the learning rate multiplier, clamped to [0, 1].
+    pub fn set_learning_rate_scale(&mut self, scale: f64) {
+        self.learning_rate_scale = scale.clamp(0.0, 1.0);
+    }
+
     /// Compute perturbation scale for iteration k.
     pub fn perturbation_scale(&self, k: u64) -> f64 {
         let k_f = k as f64;
         self.initial_perturbation_scale / (k_f + 1.0).powf(self.config.gamma)
     }
 
+    /// Perturbation scale actually used at iteration k: `c_k * perturbation_scale_factor`.
+    pub fn effective_perturbation_scale(&self, k: u64) -> f64 {
+        self.perturbation_scale(k) * self.perturbation_scale_factor
+    }
+
+    /// Current multiplier on the perturbation scale (1.0 = unshrunk).
+    pub fn perturbation_scale_factor(&self) -> f64 {
+        self.perturbation_scale_factor
+    }
+
+    /// Set the perturbation scale multiplier, clamped to (0, 1].
+    pub fn set_perturbation_scale_factor(&mut self, factor: f64) {
+        self.perturbation_scale_factor = factor.clamp(f64::MIN_POSITIVE, 1.0);
+    }
+
+    /// Drop a +Δ perturbation that never went live and return to `Ready`,
+    /// so the next observation starts a fresh one.
+    pub fn abandon_perturbation(&mut self) {
+        if matches!(self.state, SpsaState::WaitingPlus { .. }) {
+            self.state = SpsaState::Ready;
+        }
+    }
+
+    /// Drop the in-flight ±Δ pair, whichever window it is in, and return to
+    /// `Ready`. The iteration count, schedule and RNG are kept.
+    pub fn abandon_iteration(&mut self) {
+        self.state = SpsaState::Ready;
+    }
+
+    /// Restart the RNG from `seed` and drop the in-flight ±Δ pair. The
+    /// iteration count and schedule are kept.
+    pub fn reseed(&mut self, seed: u64) {
+        self.rng = ChaCha8Rng::seed_from_u64(seed);
+        self.state = SpsaState::Ready;
+    }
+
     /// Generate a perturbation vector using ±1 Bernoulli distribution.
-    pub fn generate_perturbation(&mut self) -> ParamVec {
-        let c_k = self.perturbation_scale(self.iteration);
+    ///
+    /// With `bounds`, each component is shrunk to the distance from
+    /// `current` to the nearer bound, so both θ+Δ and θ−Δ stay feasible and
+    /// the finite difference remains symmetric. A component pinned at a
+    /// bound gets no perturbation (and no gradient this iteration). The
+    /// signs are drawn the same way either way, keeping the RNG stream
+    /// independent of the bounds. With [`set_ranges`](Self::set_ranges),
+    /// `c_k` is first scaled by each parameter's range width.
+    pub fn generate_perturbation(
+        &mut self,
+        current: &[f64],
+        bounds: Option<&[(f64, f64)]>,
+    ) -> ParamVec {
+        let c_k = self.effective_perturbation_scale(self.iteration);
         let mut delta = ParamVec::with_capacity(self.num_params);
 
-        for _ in 0..self.num_params {
+        for i in 0..self.num_params {
             let sign = if self.rng.random::<bool>() { 1.0 } else { -1.0 };
-            delta.push(sign * c_k);
+            let room = match (bounds.and_then(|b| b.get(i)), current.get(i)) {
+                (Some(&(min, max)), Some(&theta)) => (max - theta).min(theta - min).max(0.0),
+                _ => f64::INFINITY,
+            };
+            delta.push(sign * (c_k * self.range(i)).min(room));
         }
 
         self.perturbation_counter += 1;
@@ -185,7 +464,7 @@ impl Spsa {
         }
 
         let mut sorted: Vec<f64> = values.to_vec();
-        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        sorted.sort_by(|a, b| a.total_cmp(b));
 
         let trim_count = ((values.len() as f64) * trim_percent).ceil() as usize;
         let trimmed = &sorted[trim_count..sorted.len().saturating_sub(trim_count)];
@@ -228,14 +507,18 @@ impl Spsa {
                 let y_minus = Self::aggregate_objectives(&accumulated, 0.1);
 
                 // Compute gradient: g_k = (y+ - y-) / (2 * Δ)
-                let a_k = self.learning_rate(self.iteration);
+                let a_k = self.effective_learning_rate(self.iteration);
                 let mut gradient = ParamVec::with_capacity(self.num_params);
                 let mut update_delta = ParamVec::with_capacity(self.num_params);
 
-                for &d in delta.iter() {
-                    let g = (y_plus - y_minus) / (2.0 * d);
+                for (i, &d) in delta.iter().enumerate() {
+                    let g = if d == 0.0 {
+                        0.0
+                    } else {
+                        (y_plus - y_minus) / (2.0 * d)
+                    };
                     gradient.push(g);
-                    update_delta.push(-a_k * g);
+                    update_delta.push(-a_k * g * self.range(i).powi(2));
                 }
 
                 self.iteration += 1;
@@ -248,6 +531,18 @@ impl Spsa {
     }
 }
 
+impl From<Spsa> for SpsaSnapshot {
+    fn from(spsa: Spsa) -> Self {
+        spsa.snapshot()
+    }
+}
+
+impl From<SpsaSnapshot> for Spsa {
+    fn from(snapshot: SpsaSnapshot) -> Self {
+        Self::from_snapshot(snapshot)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -257,8 +552,8 @@ mod tests {
         let mut spsa1 = Spsa::new(42, 3, 0.1, 0.01, SpsaConfig::default());
         let mut spsa2 = Spsa::new(42, 3, 0.1, 0.01, SpsaConfig::default());
 
-        let delta1 = spsa1.generate_perturbation();
-        let delta2 = spsa2.generate_perturbation();
+        let delta1 = spsa1.generate_perturbation(&[], None);
+        let delta2 = spsa2.generate_perturbation(&[], None);
 
         assert_eq!(delta1.as_slice(), delta2.as_slice());
     }
@@ -266,7 +561,7 @@ mod tests {
     #[test]
     fn test_spsa_perturbation_signs() {
         let mut spsa = Spsa::new(123, 5, 0.1, 1.0, SpsaConfig::default());
-        let delta = spsa.generate_perturbation();
+        let delta = spsa.generate_perturbation(&[], None);
 
         // All values should be ±1.0 (since perturbation_scale starts at 1.0)
         for &d in delta.iter() {
@@ -295,4 +590,110 @@ mod tests {
         // Should average [2.0, 3.0, 4.0] = 3.0
         assert!((result - 3.0).abs() < 1e-10);
     }
+
+    #[test]
+    fn test_snapshot_roundtrip_preserves_rng_and_schedule() {
+        let mut spsa = Spsa::new(7, 3, 0.1, 0.05, SpsaConfig::default());
+        let delta = spsa.generate_perturbation(&[], None);
+        spsa.start_plus_perturbation(delta);
+        spsa.record_objective(1.5);
+
+        let mut restored = Spsa::from_snapshot(spsa.snapshot());
+        assert_eq!(restored.iteration(), spsa.iteration());
+        assert_eq!(restored.state(), spsa.state());
+        assert_eq!(
+            restored.generate_perturbation(&[], None).as_slice(),
+            spsa.generate_perturbation(&[], None).as_slice()
+        );
+    }
+
+    /// Run one full ±Δ iteration of `spsa` on `f` around `theta`.
+    fn gradient_at(spsa: &mut Spsa, theta: f64, f: impl Fn(f64) -> f64, bounds: (f64, f64)) -> f64 {
+        let delta = spsa.generate_perturbation(&[theta], Some(&[bounds]));
+        let d = delta[0];
+        assert!(
+            theta + d <= bounds.1 && theta + d >= bounds.0,
+            "θ+Δ = {}",
+            theta + d
+        );
+        assert!(
+            theta - d <= bounds.1 && theta - d >= bounds.0,
+            "θ−Δ = {}",
+            theta - d
+        );
+
+        spsa.start_plus_perturbation(delta);
+        spsa.record_objective(f(theta + d));
+        assert!(spsa.complete_eval_window().is_none());
+        spsa.record_objective(f(theta - d));
+        spsa.complete_eval_window().unwrap().0[0]
+    }
+
+    #[test]
+    fn test_perturbation_stays_symmetric_near_bound() {
+        let config = SpsaConfig {
+            eval_window_digests: 1,
+            ..Default::default()
+        };
+        let mut spsa = Spsa::new(42, 1, 0.1, 0.05, config);
+        // Increasing objective: the gradient estimate must stay positive
+        // although c_k overshoots the upper bound.
+        for _ in 0..8 {
+            let g = gradient_at(&mut spsa, 0.995, |x| 3.0 * x, (0.0, 1.0));
+            assert!((g - 3.0).abs() < 1e-9, "gradient {}", g);
+        }
+
+        let g = gradient_at(&mut spsa, 1.0, |x| 3.0 * x, (0.0, 1.0));
+        assert_eq!(g, 0.0);
+    }
+
+    #[test]
+    fn test_ranges_scale_each_dimension() {
+        let bounds = [(1e-5, 1e-1), (16.0, 512.0)];
+        let config = SpsaConfig {
+            eval_window_digests: 1,
+            ..Default::default()
+        };
+        let mut spsa = Spsa::new(3, 2, 0.1, 0.01, config);
+        spsa.set_ranges(Some(&bounds));
+        let c_k = spsa.effective_perturbation_scale(0);
+
+        let center = [0.05, 264.0];
+        let delta = spsa.generate_perturbation(&center, Some(&bounds));
+        for (d, (min, max)) in delta.iter().zip(bounds) {
+            assert!((d.abs() / (max - min) - c_k).abs() < 1e-12, "Δ = {}", d);
+        }
+
+        // One SPSA difference estimates every component, so with unit slope
+        // along the first normalized axis each update moves a_k of its range.
+        let f = |theta: &[f64]| theta[0] / 0.09999;
+        let plus: Vec<f64> = center.iter().zip(&delta).map(|(t, d)| t + d).collect();
+        let minus: Vec<f64> = center.iter().zip(&delta).map(|(t, d)| t - d).collect();
+        let a_k = spsa.effective_learning_rate(0);
+        spsa.start_plus_perturbation(delta.clone());
+        spsa.record_objective(f(&plus));
+        assert!(spsa.complete_eval_window().is_none());
+        spsa.record_objective(f(&minus));
+        let (_, update) = spsa.complete_eval_window().unwrap();
+        for (u, (min, max)) in update.iter().zip(bounds) {
+            let fraction = u / (max - min);
+            assert!((fraction.abs() / a_k - 1.0).abs() < 1e-6, "update {}", u);
+        }
+
+        let restored = Spsa::from_snapshot(spsa.snapshot());
+        assert_eq!(restored.range(1), 496.0);
+    }
+
+    #[test]
+    fn test_bounds_do_not_shift_rng_stream() {
+        let mut bounded = Spsa::new(9, 2, 0.1, 0.05, SpsaConfig::default());
+        let mut free = Spsa::new(9, 2, 0.1, 0.05, SpsaConfig::default());
+        for _ in 0..4 {
+            let b = bounded.generate_perturbation(&[0.01, 0.5], Some(&[(0.0, 1.0), (0.0, 1.0)]));
+            let f = free.generate_perturbation(&[], None);
+            assert_eq!(b[0].signum(), f[0].signum());
+            assert!((b[0].abs() - 0.01).abs() < 1e-12);
+            assert_eq!(b[1], f[1]);
+        }
+    }
 }
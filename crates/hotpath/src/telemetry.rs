@@ -36,6 +36,42 @@ pub struct TelemetryDigest {
     pub error_rate: Option<f64>,
     /// Optional: constraint margin (positive = satisfied, negative = violated).
     pub constraint_margin: Option<f64>,
+    /// Optional: distribution of the window's samples (see [`TelemetryDigest::from_samples`]).
+    pub summary: Option<DigestSummary>,
+}
+
+/// Summary statistics of one window of samples.
+///
+/// Stored as `f32` to keep [`TelemetryDigest`] within its 128-byte contract.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct DigestSummary {
+    /// Number of finite samples summarized.
+    pub count: u32,
+    pub min: f32,
+    pub max: f32,
+    pub p50: f32,
+    pub p95: f32,
+    pub p99: f32,
+}
+
+/// How a window of samples is reduced to the scalar `objective_value`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Reducer {
+    #[default]
+    Mean,
+    Min,
+    Max,
+    P50,
+    P95,
+    P99,
+}
+
+/// Percentile of sorted samples with linear interpolation between closest ranks.
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    let rank = p * (sorted.len() - 1) as f64;
+    let lo = rank.floor() as usize;
+    let hi = rank.ceil() as usize;
+    sorted[lo] + (sorted[hi] - sorted[lo]) * (rank - lo as f64)
 }
 
 // Compile-time size assertion (AC-9)
@@ -55,6 +91,57 @@ impl TelemetryDigest {
         }
     }
 
+    /// Build a digest from one window of raw samples.
+    ///
+    /// Non-finite samples are ignored. `objective_value` is the `reducer`
+    /// applied to the rest, or NaN (with no summary) if none remain.
+    /// Timestamp and generation are left at zero for the caller to set.
+    pub fn from_samples(samples: &[f64], reducer: Reducer) -> Self {
+        let mut sorted: Vec<f64> = samples.iter().copied().filter(|v| v.is_finite()).collect();
+        if sorted.is_empty() {
+            return Self::new(0, f64::NAN, 0);
+        }
+        sorted.sort_by(|a, b| a.total_cmp(b));
+
+        let min = sorted[0];
+        let max = sorted[sorted.len() - 1];
+        let p50 = percentile(&sorted, 0.50);
+        let p95 = percentile(&sorted, 0.95);
+        let p99 = percentile(&sorted, 0.99);
+        let objective_value = match reducer {
+            Reducer::Mean => sorted.iter().sum::<f64>() / sorted.len() as f64,
+            Reducer::Min => min,
+            Reducer::Max => max,
+            Reducer::P50 => p50,
+            Reducer::P95 => p95,
+            Reducer::P99 => p99,
+        };
+
+        Self {
+            objective_value,
+            summary: Some(DigestSummary {
+                count: sorted.len().min(u32::MAX as usize) as u32,
+                min: min as f32,
+                max: max as f32,
+                p50: p50 as f32,
+                p95: p95 as f32,
+                p99: p99 as f32,
+            }),
+            ..Default::default()
+        }
+    }
+
+    /// Value used for regression detection: the window's p95 when a
+    /// summary is attached, otherwise `objective_value`. Feeding it to
+    /// `ControlSafety::record_objective` makes a worsening tail count as a
+    /// regression even when the mean holds.
+    pub fn regression_value(&self) -> f64 {
+        match self.summary {
+            Some(summary) => summary.p95 as f64,
+            None => self.objective_value,
+        }
+    }
+
     /// Validate digest against expected generation and timing windows.
     pub fn validate(
         &self,
@@ -300,4 +387,67 @@ mod tests {
         assert_eq!(digest.config_generation, 0);
         assert!(digest.latency_p99_us.is_none());
     }
+
+    #[test]
+    fn test_from_samples_percentile_interpolation() {
+        // 1..=10: rank for p is p * 9 between sorted samples.
+        let samples: Vec<f64> = (1..=10).rev().map(|v| v as f64).collect();
+        let digest = TelemetryDigest::from_samples(&samples, Reducer::Mean);
+        let summary = digest.summary.unwrap();
+
+        assert_eq!(summary.count, 10);
+        assert_eq!(summary.min, 1.0);
+        assert_eq!(summary.max, 10.0);
+        assert!((summary.p50 - 5.5).abs() < 1e-6);
+        assert!((summary.p95 - 9.55).abs() < 1e-6);
+        assert!((summary.p99 - 9.91).abs() < 1e-6);
+        assert!((digest.objective_value - 5.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_from_samples_reducers() {
+        let samples = [4.0, 1.0, 3.0, 2.0, 100.0];
+        let value = |r| TelemetryDigest::from_samples(&samples, r).objective_value;
+
+        assert_eq!(value(Reducer::Mean), 22.0);
+        assert_eq!(value(Reducer::Min), 1.0);
+        assert_eq!(value(Reducer::Max), 100.0);
+        assert_eq!(value(Reducer::P50), 3.0);
+        assert!((value(Reducer::P95) - 80.8).abs() < 1e-9);
+        assert!((value(Reducer::P99) - 96.16).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_from_samples_single_and_empty() {
+        let single = TelemetryDigest::from_samples(&[7.0], Reducer::P99);
+        assert_eq!(single.objective_value, 7.0);
+        assert_eq!(single.summary.unwrap().p50, 7.0);
+
+        let empty = TelemetryDigest::from_samples(&[f64::NAN, f64::INFINITY], Reducer::Mean);
+        assert!(empty.objective_value.is_nan());
+        assert!(empty.summary.is_none());
+    }
+
+    #[test]
+    fn test_regression_value_prefers_p95() {
+        let plain = TelemetryDigest::new(0, 2.0, 0);
+        assert_eq!(plain.regression_value(), 2.0);
+
+        let samples: Vec<f64> = (0..=100).map(|v| v as f64).collect();
+        let digest = TelemetryDigest::from_samples(&samples, Reducer::Mean);
+        assert_eq!(digest.objective_value, 50.0);
+        assert_eq!(digest.regression_value(), 95.0);
+    }
+
+    #[test]
+    fn test_ring_buffer_keeps_summary() {
+        let mut buf = TelemetryRingBuffer::new(2);
+        buf.push(TelemetryDigest::from_samples(
+            &[1.0, 2.0, 3.0],
+            Reducer::P50,
+        ));
+        let stored = buf.iter().next().unwrap();
+        assert_eq!(stored.summary.unwrap().count, 3);
+        assert_eq!(stored.objective_value, 2.0);
+    }
 }
@@ -21,8 +21,8 @@ fn test_spsa_determinism() {
     let mut spsa_b = Spsa::new(seed, dim, 0.1, 0.1, config.clone());
 
     // --- Step 1: Generate Perturbation ---
-    let delta_a1 = spsa_a.generate_perturbation();
-    let delta_b1 = spsa_b.generate_perturbation();
+    let delta_a1 = spsa_a.generate_perturbation(&[], None);
+    let delta_b1 = spsa_b.generate_perturbation(&[], None);
 
     assert_eq!(
         delta_a1.as_slice(),
@@ -27,7 +27,7 @@ enum Commands {
     /// Initialize ArqonShip in the current repository
     Init,
     /// Build the Codebase Oracle (Graph + Vectors)
-    Scan,
+    Scan(ScanArgs),
     /// Query the Codebase Oracle
     Chat(ChatArgs),
     /// Autonomous Self-Healing CI
@@ -36,6 +36,17 @@ enum Commands {
     Ship(ShipArgs),
 }
 
+#[derive(Args)]
+struct ScanArgs {
+    /// Keep watching the repository and re-index files as they change
+    #[arg(long)]
+    watch: bool,
+
+    /// Quiet period (ms) that ends a burst of changes in watch mode
+    #[arg(long, default_value_t = oracle::watch::DEFAULT_DEBOUNCE.as_millis() as u64)]
+    debounce_ms: u64,
+}
+
 #[derive(Args)]
 struct ChatArgs {
     /// The query string
@@ -45,6 +56,10 @@ struct ChatArgs {
     /// Use CLI output mode instead of TUI (default for now)
     #[arg(long)]
     cli: bool,
+
+    /// Weight of the call-graph signal when ranking results (0-1)
+    #[arg(long, default_value_t = oracle::query::DEFAULT_GRAPH_WEIGHT)]
+    graph_weight: f32,
 }
 
 #[derive(Args)]
@@ -98,11 +113,18 @@ async fn main() -> Result<()> {
 
     match &cli.command {
         Commands::Init => handle_init(&cli.config)?,
-        Commands::Scan => {
+        Commands::Scan(args) => {
             let root = std::env::current_dir().into_diagnostic()?;
-            oracle::scan_codebase(&root)
-                .await
-                .map_err(|e| miette::miette!("{:?}", e))?;
+            if args.watch {
+                let debounce = std::time::Duration::from_millis(args.debounce_ms);
+                oracle::watch::watch_codebase(&root, debounce)
+                    .await
+                    .map_err(|e| miette::miette!("{:?}", e))?;
+            } else {
+                oracle::scan_codebase(&root)
+                    .await
+                    .map_err(|e| miette::miette!("{:?}", e))?;
+            }
         }
         Commands::Chat(args) => {
             let root = std::env::current_dir().into_diagnostic()?;
@@ -114,14 +136,18 @@ async fn main() -> Result<()> {
                 vector_path.to_str().unwrap(),
             )
             .await
-            .map_err(|e| miette::miette!("{:?}", e))?;
+            .map_err(|e| miette::miette!("{:?}", e))?
+            .with_graph_weight(args.graph_weight);
 
             let results = engine
                 .query(&args.query)
                 .await
                 .map_err(|e| miette::miette!("{:?}", e))?;
             for res in results {
-                println!("[{}] {} (Score: {:.3})", res.path, res.name, res.score);
+                println!(
+                    "[{}] {} (Score: {:.3}, Vector: {:.3})",
+                    res.path, res.name, res.score, res.vector_score
+                );
             }
         }
         Commands::Heal(args) => {
@@ -155,13 +181,27 @@ async fn main() -> Result<()> {
                 .map_err(|e| miette::miette!("{:?}", e))?;
 
             // Run Loop
-            let mut healing_loop = HealingLoop::new(store, root, args.max_attempts)
+            let mut healing_loop = HealingLoop::new(store, root.clone(), args.max_attempts)
                 .map_err(|e| miette::miette!("{:?}", e))?;
             let outcome = healing_loop
                 .run(&failure)
                 .map_err(|e| miette::miette!("{:?}", e))?;
 
             println!("Heal outcome: {:?}", outcome);
+            if !matches!(outcome, heal::r#loop::HealOutcome::Success) {
+                let violation = ship::checks::Violation {
+                    check: "heal".to_string(),
+                    message: format!("{}: {}", failure.test_name, failure.error_message),
+                    file: Some(failure.file_path.clone()),
+                    line: failure.line,
+                };
+                let checker = ship::ConstitutionCheck::new(root);
+                if ship::checks::in_github_actions() {
+                    print!("{}", checker.report_github_annotations(&[violation]));
+                } else {
+                    print!("{}", checker.report_pretty(&[violation]));
+                }
+            }
         }
         Commands::Ship(args) => {
             let root = std::env::current_dir().into_diagnostic()?;
@@ -169,7 +209,15 @@ async fn main() -> Result<()> {
 
             if !args.skip_checks {
                 let checker = ship::ConstitutionCheck::new(root.clone());
-                if !checker.run_all().map_err(|e| miette::miette!("{:?}", e))? {
+                let violations = checker
+                    .collect_violations()
+                    .map_err(|e| miette::miette!("{:?}", e))?;
+                if !violations.is_empty() {
+                    if ship::checks::in_github_actions() {
+                        print!("{}", checker.report_github_annotations(&violations));
+                    } else {
+                        print!("{}", checker.report_pretty(&violations));
+                    }
                     println!("Constitution checks failed. Use --skip-checks to override.");
                     std::process::exit(1);
                 }
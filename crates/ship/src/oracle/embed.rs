@@ -4,6 +4,11 @@ use candle_nn::VarBuilder;
 use candle_transformers::models::bert::{BertModel, Config, DTYPE};
 use tokenizers::Tokenizer;
 
+/// Text embedding backend used by the oracle scan.
+pub trait Embedder {
+    fn embed(&mut self, text: &str) -> Result<Vec<f32>>;
+}
+
 pub struct MiniLM {
     model: BertModel,
     tokenizer: Tokenizer,
@@ -64,6 +69,12 @@ impl MiniLM {
     }
 }
 
+impl Embedder for MiniLM {
+    fn embed(&mut self, text: &str) -> Result<Vec<f32>> {
+        MiniLM::embed(self, text)
+    }
+}
+
 fn normalize(tensor: &Tensor) -> Result<Tensor> {
     let sum_sq = tensor.sqr()?.sum_all()?.sqrt()?;
     Ok(tensor.broadcast_div(&sum_sq)?)
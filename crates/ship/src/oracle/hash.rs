@@ -1,8 +1,12 @@
 use sha2::{Digest, Sha256};
 
 pub fn compute_hash(content: &str) -> String {
+    compute_bytes_hash(content.as_bytes())
+}
+
+pub fn compute_bytes_hash(bytes: &[u8]) -> String {
     let mut hasher = Sha256::new();
-    hasher.update(content.as_bytes());
+    hasher.update(bytes);
     format!("{:x}", hasher.finalize())
 }
 
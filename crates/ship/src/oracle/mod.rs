@@ -2,115 +2,401 @@ pub mod edges;
 pub mod embed;
 pub mod graph;
 pub mod hash;
+pub mod incremental;
 pub mod parser;
 pub mod parser_py;
 pub mod schema;
 pub mod store;
 pub mod vector_store;
+pub mod watch;
 
 use anyhow::Result;
 use ignore::WalkBuilder; // Add 'ignore' crate for .gitignore support
 use indicatif::{ProgressBar, ProgressStyle};
+use std::collections::HashSet;
 use std::path::Path; // Add 'indicatif'
 
+pub use incremental::ScanStats;
 pub use store::OracleStore;
 pub use vector_store::VectorStore;
 pub mod query;
 
-pub async fn scan_codebase(root: &Path) -> Result<()> {
+pub async fn scan_codebase(root: &Path) -> Result<ScanStats> {
+    let mut embedding_model = embed::MiniLM::new()?;
+    let stats = scan_codebase_with(root, &mut embedding_model).await?;
+    println!(
+        "Scanned {} files ({} unchanged, {} removed), embedded {} nodes",
+        stats.files_scanned, stats.files_skipped, stats.files_removed, stats.nodes_embedded
+    );
+    Ok(stats)
+}
+
+/// Stores, parsers and pending vector writes shared by every file of a scan
+/// or watch session.
+pub struct Indexer<'e, E: embed::Embedder> {
+    store: OracleStore,
+    vector_store: VectorStore,
+    graph_builder: graph::GraphBuilder,
+    edge_builder: edges::EdgeBuilder,
+    embedding_model: &'e mut E,
+    pending_ids: Vec<i64>,
+    pending_vectors: Vec<Vec<f32>>,
+    pending_texts: Vec<String>,
+    /// Node IDs whose vectors are deleted at the next [`Indexer::flush`].
+    stale_ids: Vec<i64>,
+}
+
+impl<'e, E: embed::Embedder> Indexer<'e, E> {
+    /// Vectors written to the store per batch.
+    const BATCH_SIZE: usize = 50;
+
+    /// Open (creating if needed) the graph and vector stores under
+    /// `root/.arqon`.
+    pub async fn open(root: &Path, embedding_model: &'e mut E) -> Result<Self> {
+        std::fs::create_dir_all(root.join(".arqon"))?;
+        let store = OracleStore::open(root.join(".arqon/graph.db"))?;
+
+        let vector_path = root.join(".arqon/vectors.lance");
+        let vector_uri = vector_path.to_str().unwrap();
+        let mut vector_store = VectorStore::new(vector_uri).await?;
+        vector_store.create_table_if_not_exists().await?;
+
+        Ok(Self {
+            store,
+            vector_store,
+            graph_builder: graph::GraphBuilder::new()?,
+            edge_builder: edges::EdgeBuilder::new()?,
+            embedding_model,
+            pending_ids: Vec::new(),
+            pending_vectors: Vec::new(),
+            pending_texts: Vec::new(),
+            stale_ids: Vec::new(),
+        })
+    }
+
+    /// Graph store the indexer writes to.
+    pub fn store(&self) -> &OracleStore {
+        &self.store
+    }
+
+    /// Re-parse and re-embed one file from `bytes`, replacing its old nodes.
+    ///
+    /// Files whose content hash matches the last scan are left alone and
+    /// counted in `files_skipped`. Returns whether the file was processed.
+    pub async fn index_file(
+        &mut self,
+        relative_path: &str,
+        bytes: Vec<u8>,
+        stats: &mut ScanStats,
+    ) -> Result<bool> {
+        let content_hash = hash::compute_bytes_hash(&bytes);
+        if !incremental::should_process(&self.store, relative_path, &content_hash)? {
+            stats.files_skipped += 1;
+            return Ok(false);
+        }
+        stats.files_scanned += 1;
+
+        let content = String::from_utf8(bytes)?;
+        self.stale_ids
+            .extend(self.store.remove_nodes_for_path(relative_path)?);
+
+        // Extract Nodes
+        let nodes = self.graph_builder.extract_nodes(relative_path, &content);
+        for node in &nodes {
+            let node_id = self.store.insert_node(node)?;
+
+            // Create text for embedding: name + docstring
+            let embed_text = if let Some(ref doc) = node.docstring {
+                format!("{} {}: {}", node.node_type, node.name, doc)
+            } else {
+                format!("{} {}", node.node_type, node.name)
+            };
+
+            // Embed and queue for batch insert
+            if let Ok(vec) = self.embedding_model.embed(&embed_text) {
+                self.pending_ids.push(node_id);
+                self.pending_vectors.push(vec);
+                self.pending_texts.push(embed_text);
+                stats.nodes_embedded += 1;
+            }
+
+            // Flush batch if full
+            if self.pending_ids.len() >= Self::BATCH_SIZE {
+                self.write_pending().await?;
+            }
+        }
+
+        // Extract Edges
+        let edges = self.edge_builder.extract_edges(relative_path, &content);
+        for edge in edges {
+            self.store.insert_edge(&edge)?;
+        }
+
+        self.store.upsert_file(relative_path, &content_hash)?;
+        Ok(true)
+    }
+
+    /// Drop a deleted file's nodes, edges and hash row; its vectors go at
+    /// the next [`Indexer::flush`].
+    pub fn remove_file(&mut self, relative_path: &str, stats: &mut ScanStats) -> Result<()> {
+        self.stale_ids
+            .extend(self.store.forget_file(relative_path)?);
+        stats.files_removed += 1;
+        Ok(())
+    }
+
+    /// Paths recorded by previous scans.
+    pub fn tracked_files(&self) -> Result<Vec<String>> {
+        Ok(self.store.tracked_files()?)
+    }
+
+    /// Write queued vectors and delete those of replaced or removed nodes.
+    pub async fn flush(&mut self) -> Result<()> {
+        if !self.pending_ids.is_empty() {
+            self.write_pending().await?;
+        }
+        let stale_ids = std::mem::take(&mut self.stale_ids);
+        self.vector_store.delete_ids(&stale_ids).await?;
+        Ok(())
+    }
+
+    async fn write_pending(&mut self) -> Result<()> {
+        self.vector_store
+            .add_embeddings(
+                std::mem::take(&mut self.pending_ids),
+                std::mem::take(&mut self.pending_vectors),
+                std::mem::take(&mut self.pending_texts),
+            )
+            .await
+    }
+}
+
+/// Walker over what a scan of `root` visits: hidden entries and anything
+/// matched by `.gitignore`, `.ignore` or git's excludes are skipped.
+fn source_walker(root: &Path) -> WalkBuilder {
+    WalkBuilder::new(root)
+}
+
+/// Whether the oracle indexes `path`: a `.rs` or `.py` file.
+fn is_source_file(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext == "rs" || ext == "py")
+}
+
+/// Scan `root` with the given embedder.
+///
+/// Files whose content hash matches the previous scan are skipped entirely.
+/// Changed files have their old nodes and vectors replaced, and files that no
+/// longer exist are pruned. Edges from unchanged files into a changed file
+/// are not rebuilt until the calling file changes too.
+pub async fn scan_codebase_with<E: embed::Embedder>(
+    root: &Path,
+    embedding_model: &mut E,
+) -> Result<ScanStats> {
     println!("Scanning codebase at {:?}", root);
 
     // 1. Init Stores
-    let db_path = root.join(".arqon/graph.db");
-    let mut store = OracleStore::open(db_path)?;
-
-    let vector_path = root.join(".arqon/vectors.lance");
-    let vector_uri = vector_path.to_str().unwrap();
-    let mut vector_store = VectorStore::new(vector_uri).await?;
-    vector_store.create_table_if_not_exists().await?;
+    let mut indexer = Indexer::open(root, embedding_model).await?;
 
     // 2. Walk Files
-    let walker = WalkBuilder::new(root)
-        // .hidden(false) // config overrides
-        .build();
+    let walker = source_walker(root).build();
 
     let pb = ProgressBar::new_spinner();
     pb.set_style(ProgressStyle::default_spinner());
 
-    let mut graph_builder = graph::GraphBuilder::new()?;
-    let mut edge_builder = edges::EdgeBuilder::new()?;
-    let mut embedding_model = embed::MiniLM::new()?;
-
-    // Batch vectors for efficient insertion
-    let mut pending_ids: Vec<i64> = Vec::new();
-    let mut pending_vectors: Vec<Vec<f32>> = Vec::new();
-    let mut pending_texts: Vec<String> = Vec::new();
-    const BATCH_SIZE: usize = 50;
+    let mut stats = ScanStats::default();
+    let mut seen_paths: HashSet<String> = HashSet::new();
 
     for result in walker {
         match result {
             Ok(entry) => {
                 let path = entry.path();
-                if path.is_file() {
-                    if let Some(ext) = path.extension() {
-                        let ext_str = ext.to_string_lossy();
-                        if ext_str == "rs" || ext_str == "py" {
-                            pb.set_message(format!("Processing {:?}", path.file_name().unwrap()));
-
-                            let content = std::fs::read_to_string(path)?;
-                            let relative_path = path.strip_prefix(root)?.to_string_lossy();
-
-                            // 3. Extract Nodes
-                            let nodes = graph_builder.extract_nodes(&relative_path, &content);
-                            for node in &nodes {
-                                let node_id = store.insert_node(node)?;
-
-                                // Create text for embedding: name + docstring
-                                let embed_text = if let Some(ref doc) = node.docstring {
-                                    format!("{} {}: {}", node.node_type, node.name, doc)
-                                } else {
-                                    format!("{} {}", node.node_type, node.name)
-                                };
-
-                                // Embed and queue for batch insert
-                                if let Ok(vec) = embedding_model.embed(&embed_text) {
-                                    pending_ids.push(node_id);
-                                    pending_vectors.push(vec);
-                                    pending_texts.push(embed_text);
-                                }
-
-                                // Flush batch if full
-                                if pending_ids.len() >= BATCH_SIZE {
-                                    vector_store
-                                        .add_embeddings(
-                                            std::mem::take(&mut pending_ids),
-                                            std::mem::take(&mut pending_vectors),
-                                            std::mem::take(&mut pending_texts),
-                                        )
-                                        .await?;
-                                }
-                            }
-
-                            // 4. Extract Edges
-                            let edges = edge_builder.extract_edges(&relative_path, &content);
-                            for edge in edges {
-                                store.insert_edge(&edge)?;
-                            }
-                        }
-                    }
+                if path.is_file() && is_source_file(path) {
+                    let relative_path = path.strip_prefix(root)?.to_string_lossy().into_owned();
+                    seen_paths.insert(relative_path.clone());
+
+                    // 3. Parse, embed and link changed files
+                    pb.set_message(format!("Processing {:?}", path.file_name().unwrap()));
+                    let bytes = std::fs::read(path)?;
+                    indexer
+                        .index_file(&relative_path, bytes, &mut stats)
+                        .await?;
                 }
             }
             Err(err) => eprintln!("Error walking path: {}", err),
         }
     }
 
-    // Flush any remaining vectors
-    if !pending_ids.is_empty() {
-        vector_store
-            .add_embeddings(pending_ids, pending_vectors, pending_texts)
-            .await?;
+    // 4. Prune files deleted since the last scan
+    for tracked in indexer.tracked_files()? {
+        if !seen_paths.contains(&tracked) {
+            indexer.remove_file(&tracked, &mut stats)?;
+        }
     }
+    indexer.flush().await?;
 
     pb.finish_with_message("Scan complete.");
-    Ok(())
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    /// Deterministic stand-in for MiniLM that counts embed calls.
+    struct CountingEmbedder {
+        calls: usize,
+    }
+
+    impl embed::Embedder for CountingEmbedder {
+        fn embed(&mut self, text: &str) -> Result<Vec<f32>> {
+            self.calls += 1;
+            let mut vec = vec![0.0; 384];
+            vec[text.len() % 384] = 1.0;
+            Ok(vec)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_second_scan_skips_unchanged_files() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(
+            root.join("src/lib.rs"),
+            "fn alpha() { beta(); }\nfn beta() {}\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("src/other.rs"), "struct Gamma;\n").unwrap();
+
+        let mut embedder = CountingEmbedder { calls: 0 };
+        let first = scan_codebase_with(root, &mut embedder).await.unwrap();
+        assert_eq!(first.files_scanned, 2);
+        assert!(first.nodes_embedded > 0);
+        let embedded_after_first = embedder.calls;
+
+        let second = scan_codebase_with(root, &mut embedder).await.unwrap();
+        assert_eq!(second.files_scanned, 0);
+        assert_eq!(second.files_skipped, 2);
+        assert_eq!(second.nodes_embedded, 0);
+        assert_eq!(embedder.calls, embedded_after_first);
+    }
+
+    #[tokio::test]
+    async fn test_scan_prunes_deleted_and_rescans_changed_files() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(root.join("src/lib.rs"), "fn alpha() {}\n").unwrap();
+        std::fs::write(root.join("src/gone.rs"), "fn doomed() {}\n").unwrap();
+
+        let mut embedder = CountingEmbedder { calls: 0 };
+        scan_codebase_with(root, &mut embedder).await.unwrap();
+
+        std::fs::remove_file(root.join("src/gone.rs")).unwrap();
+        std::fs::write(root.join("src/lib.rs"), "fn alpha() {}\nfn delta() {}\n").unwrap();
+        let stats = scan_codebase_with(root, &mut embedder).await.unwrap();
+        assert_eq!(stats.files_scanned, 1);
+        assert_eq!(stats.files_removed, 1);
+
+        let store = OracleStore::open(root.join(".arqon/graph.db")).unwrap();
+        assert!(store.node_ids_for_path("src/gone.rs").unwrap().is_empty());
+        assert_eq!(store.node_ids_for_path("src/lib.rs").unwrap().len(), 2);
+
+        let vectors = VectorStore::new(root.join(".arqon/vectors.lance").to_str().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(vectors.count().await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_watch_changes_reindex_only_touched_files() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(root.join("src/lib.rs"), "fn alpha() {}\n").unwrap();
+        std::fs::write(root.join("src/other.rs"), "struct Gamma;\n").unwrap();
+        std::fs::write(root.join("src/gone.rs"), "fn doomed() {}\n").unwrap();
+
+        let mut embedder = CountingEmbedder { calls: 0 };
+        scan_codebase_with(root, &mut embedder).await.unwrap();
+        let store = OracleStore::open(root.join(".arqon/graph.db")).unwrap();
+        let lib_before = store.node_ids_for_path("src/lib.rs").unwrap();
+        let other_before = store.node_ids_for_path("src/other.rs").unwrap();
+        let calls_before = embedder.calls;
+
+        std::fs::write(root.join("src/lib.rs"), "fn alpha() {}\nfn delta() {}\n").unwrap();
+        std::fs::remove_file(root.join("src/gone.rs")).unwrap();
+        let changed = [
+            root.join("src/lib.rs"),
+            root.join("src/gone.rs"),
+            root.join("src/other.rs"),
+            root.join(".arqon/graph.db"),
+        ]
+        .into_iter()
+        .collect();
+        let mut indexer = Indexer::open(root, &mut embedder).await.unwrap();
+        let stats = watch::apply_changes(&mut indexer, root, &changed)
+            .await
+            .unwrap();
+        drop(indexer);
+        assert_eq!(stats.files_scanned, 1);
+        assert_eq!(stats.files_skipped, 1);
+        assert_eq!(stats.files_removed, 1);
+        assert_eq!(embedder.calls, calls_before + 2);
+
+        let lib_after = store.node_ids_for_path("src/lib.rs").unwrap();
+        assert_eq!(lib_after.len(), 2);
+        assert!(lib_after.iter().all(|id| !lib_before.contains(id)));
+        assert_eq!(
+            store.node_ids_for_path("src/other.rs").unwrap(),
+            other_before
+        );
+        assert!(store.node_ids_for_path("src/gone.rs").unwrap().is_empty());
+
+        let vectors = VectorStore::new(root.join(".arqon/vectors.lance").to_str().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(vectors.count().await.unwrap(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_watch_changes_skip_ignored_and_unreadable_files() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        // .gitignore only applies inside a git repository.
+        std::fs::create_dir_all(root.join(".git")).unwrap();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::create_dir_all(root.join("generated")).unwrap();
+        std::fs::write(root.join(".gitignore"), "generated/\n").unwrap();
+        std::fs::write(root.join("src/lib.rs"), "fn alpha() {}\n").unwrap();
+
+        let mut embedder = CountingEmbedder { calls: 0 };
+        scan_codebase_with(root, &mut embedder).await.unwrap();
+
+        std::fs::write(root.join("generated/gen.rs"), "fn generated() {}\n").unwrap();
+        std::fs::write(root.join("src/bad.rs"), [0xff, 0xfe, 0xfd]).unwrap();
+        std::fs::write(root.join("src/lib.rs"), "fn alpha() {}\nfn beta() {}\n").unwrap();
+        let changed = [
+            root.join("generated/gen.rs"),
+            root.join("src/bad.rs"),
+            root.join("src/lib.rs"),
+        ]
+        .into_iter()
+        .collect();
+        let mut indexer = Indexer::open(root, &mut embedder).await.unwrap();
+        watch::apply_changes(&mut indexer, root, &changed)
+            .await
+            .unwrap();
+        drop(indexer);
+
+        let store = OracleStore::open(root.join(".arqon/graph.db")).unwrap();
+        assert!(store
+            .node_ids_for_path("generated/gen.rs")
+            .unwrap()
+            .is_empty());
+        assert!(store.node_ids_for_path("src/bad.rs").unwrap().is_empty());
+        assert_eq!(store.node_ids_for_path("src/lib.rs").unwrap().len(), 2);
+    }
 }
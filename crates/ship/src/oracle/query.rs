@@ -4,21 +4,83 @@ use crate::oracle::vector_store::VectorStore;
 use anyhow::Result;
 use std::fs;
 
+/// Default weight of the call-graph signal in the combined score.
+pub const DEFAULT_GRAPH_WEIGHT: f32 = 0.2;
+
+/// Number of results returned by [`QueryEngine::query`].
+const RESULT_LIMIT: usize = 5;
+
+/// Vector hits fetched per returned result, so reranking can promote
+/// well-connected nodes from just outside the top results.
+const RERANK_POOL_FACTOR: usize = 4;
+
 pub struct QueryEngine {
     store: OracleStore,
     vector_store: VectorStore,
     model: MiniLM,
     root: std::path::PathBuf,
+    graph_weight: f32,
 }
 
 #[derive(Debug)]
 pub struct QueryResult {
     pub name: String,
     pub path: String,
+    /// Combined vector + graph score in [0, 1].
     pub score: f32,
+    /// Normalized vector similarity in [0, 1], before reranking.
+    pub vector_score: f32,
     pub snippet: String,
 }
 
+/// A vector hit rescored with the call-graph signal.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RankedHit {
+    pub id: i64,
+    pub vector_score: f32,
+    /// Incoming `calls` edges, log-scaled against the best-connected hit.
+    pub graph_score: f32,
+    pub score: f32,
+}
+
+/// Rerank vector hits by blending similarity with incoming `calls` edges.
+///
+/// `score = (1 - graph_weight) * vector_score + graph_weight * graph_score`.
+/// Ties on the combined score keep the vector search order.
+pub fn rerank(
+    store: &OracleStore,
+    hits: &[(i64, f32)],
+    graph_weight: f32,
+) -> Result<Vec<RankedHit>> {
+    let weight = graph_weight.clamp(0.0, 1.0);
+    let degrees = hits
+        .iter()
+        .map(|&(id, _)| store.incoming_edge_count(id, "calls"))
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    let max_degree = degrees.iter().copied().max().unwrap_or(0);
+
+    let mut ranked: Vec<RankedHit> = hits
+        .iter()
+        .zip(&degrees)
+        .map(|(&(id, raw), &degree)| {
+            let vector_score = raw.clamp(0.0, 1.0);
+            let graph_score = if max_degree == 0 {
+                0.0
+            } else {
+                ((degree as f32).ln_1p() / (max_degree as f32).ln_1p()).clamp(0.0, 1.0)
+            };
+            RankedHit {
+                id,
+                vector_score,
+                graph_score,
+                score: (1.0 - weight) * vector_score + weight * graph_score,
+            }
+        })
+        .collect();
+    ranked.sort_by(|a, b| b.score.total_cmp(&a.score));
+    Ok(ranked)
+}
+
 impl QueryEngine {
     pub async fn new(db_path: &str, vector_uri: &str) -> Result<Self> {
         let store = OracleStore::open(db_path)?;
@@ -31,27 +93,44 @@ impl QueryEngine {
             vector_store,
             model,
             root,
+            graph_weight: DEFAULT_GRAPH_WEIGHT,
         })
     }
 
+    /// Set the weight of the call-graph signal (0 = pure vector search).
+    pub fn with_graph_weight(mut self, graph_weight: f32) -> Self {
+        self.graph_weight = graph_weight.clamp(0.0, 1.0);
+        self
+    }
+
     pub async fn query(&mut self, text: &str) -> Result<Vec<QueryResult>> {
         // 1. Embed query
         let vec = self.model.embed(text)?;
 
         // 2. Vector Search
-        let hits = self.vector_store.search(vec, 5).await?;
+        let hits = self
+            .vector_store
+            .search(vec, RESULT_LIMIT * RERANK_POOL_FACTOR)
+            .await?;
 
-        // 3. Enrich with Graph Data
+        // 3. Rerank with the call graph
+        let ranked = rerank(&self.store, &hits, self.graph_weight)?;
+
+        // 4. Enrich with Graph Data
         let mut results = Vec::new();
-        for (id, score) in hits {
-            if let Some(node) = self.store.get_node_by_id(id) {
+        for hit in ranked {
+            if results.len() == RESULT_LIMIT {
+                break;
+            }
+            if let Some(node) = self.store.get_node_by_id(hit.id) {
                 // Read snippet from file
                 let snippet = self.get_snippet(&node.path, node.start_line, node.end_line);
 
                 results.push(QueryResult {
                     name: node.name,
                     path: node.path,
-                    score,
+                    score: hit.score,
+                    vector_score: hit.vector_score,
                     snippet,
                 });
             }
@@ -72,3 +151,71 @@ impl QueryEngine {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oracle::edges::GraphEdge;
+    use crate::oracle::graph::GraphNode;
+    use tempfile::tempdir;
+
+    fn node(name: &str, line: usize) -> GraphNode {
+        GraphNode {
+            path: "src/lib.rs".to_string(),
+            node_type: "function".to_string(),
+            name: name.to_string(),
+            start_line: line,
+            end_line: line + 2,
+            signature_hash: format!("hash_{}", name),
+            docstring: None,
+        }
+    }
+
+    fn calls(source: &str, target: &str) -> GraphEdge {
+        GraphEdge {
+            source_node_name: source.to_string(),
+            target_node_name: target.to_string(),
+            edge_type: "calls".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_rerank_prefers_frequently_called_node() {
+        let dir = tempdir().unwrap();
+        let mut store = OracleStore::open(dir.path().join("graph.db")).unwrap();
+
+        let isolated = store.insert_node(&node("isolated", 1)).unwrap();
+        let popular = store.insert_node(&node("popular", 10)).unwrap();
+        for (i, caller) in ["c1", "c2", "c3", "c4"].iter().enumerate() {
+            store.insert_node(&node(caller, 20 + 5 * i)).unwrap();
+            store.insert_edge(&calls(caller, "popular")).unwrap();
+        }
+
+        // Equal vector similarity; the isolated node comes first from search
+        let hits = vec![(isolated, 0.8), (popular, 0.8)];
+        let ranked = rerank(&store, &hits, DEFAULT_GRAPH_WEIGHT).unwrap();
+
+        assert_eq!(ranked[0].id, popular);
+        assert_eq!(ranked[1].id, isolated);
+        assert_eq!(ranked[0].vector_score, 0.8);
+        assert!(ranked[0].score > ranked[1].score);
+        assert!(ranked.iter().all(|h| (0.0..=1.0).contains(&h.score)));
+    }
+
+    #[test]
+    fn test_rerank_zero_weight_keeps_vector_order() {
+        let dir = tempdir().unwrap();
+        let mut store = OracleStore::open(dir.path().join("graph.db")).unwrap();
+
+        let first = store.insert_node(&node("first", 1)).unwrap();
+        let second = store.insert_node(&node("second", 10)).unwrap();
+        store.insert_node(&node("caller", 20)).unwrap();
+        store.insert_edge(&calls("caller", "second")).unwrap();
+
+        let hits = vec![(first, 0.9), (second, 0.7)];
+        let ranked = rerank(&store, &hits, 0.0).unwrap();
+        assert_eq!(ranked[0].id, first);
+        assert_eq!(ranked[0].score, 0.9);
+        assert_eq!(ranked[1].graph_score, 1.0);
+    }
+}
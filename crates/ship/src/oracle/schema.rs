@@ -30,6 +30,12 @@ pub const SCHEMA_V1: &str = r#"
     CREATE INDEX IF NOT EXISTS idx_edges_source ON edges(source_id);
     CREATE INDEX IF NOT EXISTS idx_edges_target ON edges(target_id);
 
+    -- Per-file content hashes for incremental scans
+    CREATE TABLE IF NOT EXISTS files (
+        path TEXT PRIMARY KEY,
+        content_hash TEXT NOT NULL
+    );
+
     -- Healing Audit Log
     CREATE TABLE IF NOT EXISTS healing_attempts (
         run_id TEXT PRIMARY KEY,
@@ -67,6 +67,68 @@ impl OracleStore {
         Ok(())
     }
 
+    /// Content hash recorded for a file by the last scan
+    pub fn file_hash(&self, path: &str) -> Result<Option<String>> {
+        self.conn
+            .query_row(
+                "SELECT content_hash FROM files WHERE path = ?1",
+                params![path],
+                |row| row.get(0),
+            )
+            .optional()
+    }
+
+    /// Record the content hash of a scanned file
+    pub fn upsert_file(&mut self, path: &str, content_hash: &str) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO files (path, content_hash) VALUES (?1, ?2)
+             ON CONFLICT(path) DO UPDATE SET content_hash=excluded.content_hash",
+            params![path, content_hash],
+        )?;
+        Ok(())
+    }
+
+    /// All file paths recorded by previous scans
+    pub fn tracked_files(&self) -> Result<Vec<String>> {
+        let mut stmt = self.conn.prepare("SELECT path FROM files ORDER BY path")?;
+        let rows = stmt.query_map([], |row| row.get(0))?;
+        rows.collect()
+    }
+
+    /// IDs of all nodes extracted from a file
+    pub fn node_ids_for_path(&self, path: &str) -> Result<Vec<i64>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id FROM nodes WHERE path = ?1 ORDER BY id")?;
+        let rows = stmt.query_map(params![path], |row| row.get(0))?;
+        rows.collect()
+    }
+
+    /// Delete a file's nodes and any edges touching them.
+    ///
+    /// Returns the removed node IDs so their vectors can be pruned too.
+    /// The file's hash row is kept; see [`OracleStore::forget_file`].
+    pub fn remove_nodes_for_path(&mut self, path: &str) -> Result<Vec<i64>> {
+        let ids = self.node_ids_for_path(path)?;
+        let tx = self.conn.transaction()?;
+        tx.execute(
+            "DELETE FROM edges WHERE source_id IN (SELECT id FROM nodes WHERE path = ?1)
+             OR target_id IN (SELECT id FROM nodes WHERE path = ?1)",
+            params![path],
+        )?;
+        tx.execute("DELETE FROM nodes WHERE path = ?1", params![path])?;
+        tx.commit()?;
+        Ok(ids)
+    }
+
+    /// Drop a deleted file's nodes, edges and hash row.
+    pub fn forget_file(&mut self, path: &str) -> Result<Vec<i64>> {
+        let ids = self.remove_nodes_for_path(path)?;
+        self.conn
+            .execute("DELETE FROM files WHERE path = ?1", params![path])?;
+        Ok(ids)
+    }
+
     /// Get a node by its ID
     pub fn get_node_by_id(&self, id: i64) -> Option<GraphNode> {
         self.conn.query_row(
@@ -86,6 +148,16 @@ impl OracleStore {
         ).ok()
     }
 
+    /// Number of edges of `edge_type` pointing at a node
+    pub fn incoming_edge_count(&self, id: i64, edge_type: &str) -> Result<usize> {
+        let count: i64 = self.conn.query_row(
+            "SELECT COUNT(*) FROM edges WHERE target_id = ?1 AND type = ?2",
+            params![id, edge_type],
+            |row| row.get(0),
+        )?;
+        Ok(count as usize)
+    }
+
     /// Get function signatures from the same file or nearby lines
     pub fn get_related_signatures(&self, file_path: &str, near_line: Option<u32>) -> Vec<String> {
         let line = near_line.unwrap_or(0) as i64;
@@ -222,6 +294,31 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_incoming_edge_count() {
+        let dir = tempdir().unwrap();
+        let mut store = OracleStore::open(dir.path().join("test.db")).unwrap();
+
+        let callee = store
+            .insert_node(&make_test_node("callee", "src/lib.rs", 1, 5))
+            .unwrap();
+        for (i, caller) in ["a", "b"].iter().enumerate() {
+            store
+                .insert_node(&make_test_node(caller, "src/lib.rs", 10 + i, 12 + i))
+                .unwrap();
+            store
+                .insert_edge(&GraphEdge {
+                    source_node_name: caller.to_string(),
+                    target_node_name: "callee".to_string(),
+                    edge_type: "calls".to_string(),
+                })
+                .unwrap();
+        }
+
+        assert_eq!(store.incoming_edge_count(callee, "calls").unwrap(), 2);
+        assert_eq!(store.incoming_edge_count(callee, "imports").unwrap(), 0);
+    }
+
     #[test]
     fn test_insert_edge_missing_nodes() {
         let dir = tempdir().unwrap();
@@ -258,6 +355,56 @@ mod tests {
         assert!(related.iter().any(|s| s.contains("func_b")));
     }
 
+    #[test]
+    fn test_file_hash_round_trip() {
+        let dir = tempdir().unwrap();
+        let mut store = OracleStore::open(dir.path().join("test.db")).unwrap();
+
+        assert_eq!(store.file_hash("src/lib.rs").unwrap(), None);
+        store.upsert_file("src/lib.rs", "abc").unwrap();
+        store.upsert_file("src/lib.rs", "def").unwrap();
+        assert_eq!(
+            store.file_hash("src/lib.rs").unwrap().as_deref(),
+            Some("def")
+        );
+        assert_eq!(
+            store.tracked_files().unwrap(),
+            vec!["src/lib.rs".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_forget_file_prunes_nodes_and_edges() {
+        let dir = tempdir().unwrap();
+        let mut store = OracleStore::open(dir.path().join("test.db")).unwrap();
+
+        let gone = store
+            .insert_node(&make_test_node("caller", "src/gone.rs", 1, 5))
+            .unwrap();
+        store
+            .insert_node(&make_test_node("callee", "src/kept.rs", 1, 5))
+            .unwrap();
+        store.upsert_file("src/gone.rs", "abc").unwrap();
+        store
+            .insert_edge(&GraphEdge {
+                source_node_name: "caller".to_string(),
+                target_node_name: "callee".to_string(),
+                edge_type: "calls".to_string(),
+            })
+            .unwrap();
+
+        let removed = store.forget_file("src/gone.rs").unwrap();
+        assert_eq!(removed, vec![gone]);
+        assert!(store.node_ids_for_path("src/gone.rs").unwrap().is_empty());
+        assert_eq!(store.node_ids_for_path("src/kept.rs").unwrap().len(), 1);
+        assert!(store.tracked_files().unwrap().is_empty());
+        let edges: i64 = store
+            .conn
+            .query_row("SELECT COUNT(*) FROM edges", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(edges, 0);
+    }
+
     #[test]
     fn test_get_related_signatures_empty_file() {
         let dir = tempdir().unwrap();
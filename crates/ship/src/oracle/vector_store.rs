@@ -7,7 +7,7 @@ use arrow_schema::FieldRef;
 use futures::StreamExt;
 use lancedb::arrow::arrow_schema::{DataType, Field, Schema};
 use lancedb::query::{ExecutableQuery, QueryBase};
-use lancedb::{connect, Connection, Table};
+use lancedb::{connect, Connection, DistanceType, Table};
 use std::sync::Arc;
 
 /// Vector dimension for MiniLM embeddings
@@ -106,6 +106,32 @@ impl VectorStore {
         Ok(())
     }
 
+    /// Remove embeddings for the given node IDs
+    pub async fn delete_ids(&mut self, ids: &[i64]) -> Result<()> {
+        let Some(table) = self.table.as_ref() else {
+            return Ok(());
+        };
+        if ids.is_empty() {
+            return Ok(());
+        }
+
+        let id_list = ids
+            .iter()
+            .map(|id| id.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        table.delete(&format!("id IN ({})", id_list)).await?;
+        Ok(())
+    }
+
+    /// Number of stored embeddings
+    pub async fn count(&self) -> Result<usize> {
+        match self.table.as_ref() {
+            Some(table) => Ok(table.count_rows(None).await?),
+            None => Ok(0),
+        }
+    }
+
     /// Search for similar vectors
     ///
     /// # Arguments
@@ -113,7 +139,8 @@ impl VectorStore {
     /// * `limit` - Maximum number of results
     ///
     /// # Returns
-    /// Vector of (id, similarity_score) tuples
+    /// Vector of (id, similarity_score) tuples, where the score is cosine
+    /// similarity rescaled into [0, 1]
     pub async fn search(&self, query_vec: Vec<f32>, limit: usize) -> Result<Vec<(i64, f32)>> {
         let table = self
             .table
@@ -123,6 +150,7 @@ impl VectorStore {
         // Perform vector search
         let mut results = table
             .vector_search(query_vec)?
+            .distance_type(DistanceType::Cosine)
             .limit(limit)
             .execute()
             .await?;
@@ -136,18 +164,15 @@ impl VectorStore {
             // Get the ID column
             if let Some(id_col) = batch.column_by_name("id") {
                 if let Some(id_array) = id_col.as_any().downcast_ref::<Int64Array>() {
-                    // LanceDB returns _distance column for L2 distance
+                    // LanceDB returns cosine distance (1 - cos) in _distance
                     let dist_col = batch.column_by_name("_distance");
 
                     for i in 0..id_array.len() {
                         let id = id_array.value(i);
 
-                        // Convert distance to similarity score
-                        // L2 distance: lower is better, so we use 1/(1+d)
                         let score = if let Some(d) = &dist_col {
                             if let Some(dist_array) = d.as_any().downcast_ref::<Float32Array>() {
-                                let distance = dist_array.value(i);
-                                1.0 / (1.0 + distance)
+                                cosine_distance_to_score(dist_array.value(i))
                             } else {
                                 0.0
                             }
@@ -165,10 +190,28 @@ impl VectorStore {
     }
 }
 
+/// Map a cosine distance in [0, 2] to a similarity score in [0, 1].
+pub fn cosine_distance_to_score(distance: f32) -> f32 {
+    if distance.is_finite() {
+        (1.0 - distance / 2.0).clamp(0.0, 1.0)
+    } else {
+        0.0
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_cosine_distance_to_score_range() {
+        assert_eq!(cosine_distance_to_score(0.0), 1.0);
+        assert_eq!(cosine_distance_to_score(1.0), 0.5);
+        assert_eq!(cosine_distance_to_score(2.0), 0.0);
+        assert_eq!(cosine_distance_to_score(2.5), 0.0);
+        assert_eq!(cosine_distance_to_score(f32::NAN), 0.0);
+    }
+
     #[tokio::test]
     async fn test_vector_store_schema() {
         let schema = VectorStore::schema();
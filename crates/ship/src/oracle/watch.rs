@@ -0,0 +1,157 @@
+//! Keep the oracle live: re-index files as they change on disk.
+
+use crate::oracle::embed::{Embedder, MiniLM};
+use crate::oracle::{is_source_file, scan_codebase_with, source_walker, Indexer, ScanStats};
+use anyhow::Result;
+use notify::{RecursiveMode, Watcher};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// Quiet period that ends a burst of change events.
+pub const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Scan `root`, then keep re-indexing it as files change until Ctrl-C.
+pub async fn watch_codebase(root: &Path, debounce: Duration) -> Result<()> {
+    let mut embedding_model = MiniLM::new()?;
+    let stats = scan_codebase_with(root, &mut embedding_model).await?;
+    println!(
+        "Scanned {} files ({} unchanged, {} removed), embedded {} nodes",
+        stats.files_scanned, stats.files_skipped, stats.files_removed, stats.nodes_embedded
+    );
+    watch_codebase_with(root, &mut embedding_model, debounce).await
+}
+
+/// Watch `root` and re-index changed files with the given embedder.
+///
+/// Events are collected until none arrives for `debounce`, so a save that
+/// touches several files (or one file several times) costs one update.
+pub async fn watch_codebase_with<E: Embedder>(
+    root: &Path,
+    embedding_model: &mut E,
+    debounce: Duration,
+) -> Result<()> {
+    let root = root.canonicalize()?;
+    let mut indexer = Indexer::open(&root, embedding_model).await?;
+
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let mut watcher = notify::recommended_watcher(move |event| {
+        let _ = tx.send(event);
+    })?;
+    watcher.watch(&root, RecursiveMode::Recursive)?;
+    println!("Watching {:?} for changes (Ctrl-C to stop)", root);
+
+    loop {
+        let first = tokio::select! {
+            event = rx.recv() => event,
+            _ = tokio::signal::ctrl_c() => break,
+        };
+        let Some(first) = first else {
+            break;
+        };
+        let mut changed = BTreeSet::new();
+        collect_paths(&mut changed, first);
+        while let Ok(Some(event)) = tokio::time::timeout(debounce, rx.recv()).await {
+            collect_paths(&mut changed, event);
+        }
+
+        let stats = apply_changes(&mut indexer, &root, &changed).await?;
+        if stats.files_scanned > 0 || stats.files_removed > 0 {
+            println!(
+                "Re-indexed {} files ({} removed), embedded {} nodes",
+                stats.files_scanned, stats.files_removed, stats.nodes_embedded
+            );
+        }
+    }
+    Ok(())
+}
+
+fn collect_paths(changed: &mut BTreeSet<PathBuf>, event: notify::Result<notify::Event>) {
+    match event {
+        Ok(event) => changed.extend(event.paths),
+        Err(err) => eprintln!("Watch error: {}", err),
+    }
+}
+
+/// Bring the index up to date with `paths` (absolute, or relative to
+/// `root`), which changed since it was last written.
+///
+/// Source files that still exist are re-parsed and re-embedded if their
+/// content hash changed. Tracked files that are gone, or that lived under a
+/// removed directory, are pruned with their vectors. Paths a scan would not
+/// visit (hidden, git-ignored, or under `target`) are ignored. A file that
+/// cannot be read or indexed is reported and skipped; the rest still apply.
+pub async fn apply_changes<E: Embedder>(
+    indexer: &mut Indexer<'_, E>,
+    root: &Path,
+    paths: &BTreeSet<PathBuf>,
+) -> Result<ScanStats> {
+    let mut stats = ScanStats::default();
+    let mut tracked: BTreeSet<String> = indexer.tracked_files()?.into_iter().collect();
+    for path in paths {
+        let absolute = if path.is_absolute() {
+            path.clone()
+        } else {
+            root.join(path)
+        };
+        let Some(relative) = watched_path(root, &absolute) else {
+            continue;
+        };
+        if absolute.is_file() {
+            if is_source_file(&relative) && scanned(root, &absolute) {
+                let relative = relative.to_string_lossy().into_owned();
+                let bytes = match std::fs::read(&absolute) {
+                    Ok(bytes) => bytes,
+                    Err(err) => {
+                        eprintln!("Error reading {}: {}", relative, err);
+                        continue;
+                    }
+                };
+                if let Err(err) = indexer.index_file(&relative, bytes, &mut stats).await {
+                    eprintln!("Error indexing {}: {}", relative, err);
+                    continue;
+                }
+                tracked.insert(relative);
+            }
+        } else if !absolute.exists() {
+            let gone: Vec<String> = tracked
+                .iter()
+                .filter(|file| Path::new(file).starts_with(&relative))
+                .cloned()
+                .collect();
+            for file in gone {
+                if let Err(err) = indexer.remove_file(&file, &mut stats) {
+                    eprintln!("Error removing {}: {}", file, err);
+                    continue;
+                }
+                tracked.remove(&file);
+            }
+        }
+    }
+    indexer.flush().await?;
+    Ok(stats)
+}
+
+/// Whether a scan of `root` visits the file `path`. The scan's walker only
+/// descends into `path`'s ancestors, so ignored directories are pruned as in
+/// a full scan without listing the rest of the tree.
+fn scanned(root: &Path, path: &Path) -> bool {
+    let target = path.to_path_buf();
+    source_walker(root)
+        .filter_entry(move |entry| target.starts_with(entry.path()))
+        .build()
+        .filter_map(Result::ok)
+        .any(|entry| entry.path() == path)
+}
+
+/// `path` relative to `root`, unless it lies outside it or under a hidden
+/// or `target` directory.
+fn watched_path(root: &Path, path: &Path) -> Option<PathBuf> {
+    let relative = path.strip_prefix(root).ok()?;
+    let ignored = relative.components().any(|component| {
+        let name = component.as_os_str().to_string_lossy();
+        name.starts_with('.') || name == "target"
+    });
+    (!ignored && relative.components().next().is_some()).then(|| relative.to_path_buf())
+}
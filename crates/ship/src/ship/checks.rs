@@ -1,6 +1,19 @@
 use anyhow::Result;
+use std::fmt::Write as _;
 use std::process::Command;
 
+/// A failed constitution check, pinned to a source location when known.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    /// Name of the check that failed, e.g. `clean-git`.
+    pub check: String,
+    pub message: String,
+    /// Path relative to the repository root.
+    pub file: Option<String>,
+    /// 1-based line number within `file`.
+    pub line: Option<u32>,
+}
+
 pub struct ConstitutionCheck {
     root: std::path::PathBuf,
 }
@@ -12,16 +25,21 @@ impl ConstitutionCheck {
 
     /// Check if git working directory is clean
     pub fn check_clean_git(&self) -> Result<bool> {
+        let is_clean = self.dirty_files()?.is_empty();
+        if !is_clean {
+            eprintln!("Git working directory is not clean");
+        }
+        Ok(is_clean)
+    }
+
+    /// Files with uncommitted changes, from `git status --porcelain`.
+    fn dirty_files(&self) -> Result<Vec<String>> {
         let output = Command::new("git")
             .args(["status", "--porcelain"])
             .current_dir(&self.root)
             .output()?;
 
-        let is_clean = output.stdout.is_empty();
-        if !is_clean {
-            eprintln!("Git working directory is not clean");
-        }
-        Ok(is_clean)
+        Ok(parse_porcelain(&String::from_utf8_lossy(&output.stdout)))
     }
 
     /// Check if all tests pass
@@ -36,18 +54,7 @@ impl ConstitutionCheck {
 
     /// Check for untagged TODOs/FIXMEs (simplified check)
     pub fn check_no_untagged_debt(&self) -> Result<bool> {
-        let output = Command::new("grep")
-            .args(["-rn", "TODO", "--include=*.rs", "."])
-            .current_dir(&self.root)
-            .output()?;
-
-        let todos = String::from_utf8_lossy(&output.stdout);
-        // Allow TODOs if they have a tracking issue like "TODO(#123)"
-        let untagged_count = todos
-            .lines()
-            .filter(|line| !line.contains("TODO(#") && !line.contains("TODO["))
-            .count();
-
+        let untagged_count = self.untagged_debt()?.len();
         if untagged_count > 0 {
             eprintln!("Found {} untagged TODO items", untagged_count);
             return Ok(false);
@@ -56,6 +63,18 @@ impl ConstitutionCheck {
         Ok(true)
     }
 
+    /// Untagged TODOs, one violation per line.
+    pub fn untagged_debt(&self) -> Result<Vec<Violation>> {
+        let output = Command::new("grep")
+            .args(["-rn", "TODO", "--include=*.rs", "."])
+            .current_dir(&self.root)
+            .output()?;
+
+        Ok(parse_untagged_todos(&String::from_utf8_lossy(
+            &output.stdout,
+        )))
+    }
+
     /// Run all constitution checks
     pub fn run_all(&self) -> Result<bool> {
         let clean = self.check_clean_git()?;
@@ -65,4 +84,190 @@ impl ConstitutionCheck {
 
         Ok(clean && tests)
     }
+
+    /// Run the same checks as `run_all`, returning each failure.
+    pub fn collect_violations(&self) -> Result<Vec<Violation>> {
+        let mut violations: Vec<Violation> = self
+            .dirty_files()?
+            .into_iter()
+            .map(|file| Violation {
+                check: "clean-git".to_string(),
+                message: "Uncommitted changes".to_string(),
+                file: Some(file),
+                line: None,
+            })
+            .collect();
+        if !self.check_tests_pass()? {
+            violations.push(Violation {
+                check: "tests-pass".to_string(),
+                message: "cargo test failed".to_string(),
+                file: None,
+                line: None,
+            });
+        }
+        Ok(violations)
+    }
+
+    /// Render violations as GitHub Actions workflow commands, one per line,
+    /// so they show up as annotations on the PR diff.
+    ///
+    /// ```text
+    /// ::error file=src/lib.rs,line=12::Untagged TODO
+    /// ```
+    pub fn report_github_annotations(&self, violations: &[Violation]) -> String {
+        let mut out = String::new();
+        for violation in violations {
+            let mut properties = Vec::new();
+            if let Some(file) = &violation.file {
+                properties.push(format!("file={}", escape_property(file)));
+                if let Some(line) = violation.line {
+                    properties.push(format!("line={}", line));
+                }
+            }
+            let separator = if properties.is_empty() { "" } else { " " };
+            let _ = writeln!(
+                out,
+                "::error{}{}::{}",
+                separator,
+                properties.join(","),
+                escape_data(&violation.message)
+            );
+        }
+        out
+    }
+
+    /// Render violations for a terminal.
+    pub fn report_pretty(&self, violations: &[Violation]) -> String {
+        let mut out = String::new();
+        for violation in violations {
+            let location = match (&violation.file, violation.line) {
+                (Some(file), Some(line)) => format!(" {}:{}", file, line),
+                (Some(file), None) => format!(" {}", file),
+                _ => String::new(),
+            };
+            let _ = writeln!(
+                out,
+                "  [{}]{} {}",
+                violation.check, location, violation.message
+            );
+        }
+        out
+    }
+}
+
+/// Whether we are running inside GitHub Actions.
+pub fn in_github_actions() -> bool {
+    std::env::var_os("GITHUB_ACTIONS").is_some()
+}
+
+/// Paths from `git status --porcelain` output (the new path for renames).
+fn parse_porcelain(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| line.get(3..))
+        .map(|path| match path.split_once(" -> ") {
+            Some((_, to)) => to.to_string(),
+            None => path.to_string(),
+        })
+        .collect()
+}
+
+/// Untagged TODOs from `grep -rn` output (`./path:line:text`).
+fn parse_untagged_todos(output: &str) -> Vec<Violation> {
+    output
+        .lines()
+        // Allow TODOs if they have a tracking issue like "TODO(#123)"
+        .filter(|line| !line.contains("TODO(#") && !line.contains("TODO["))
+        .map(|line| {
+            let mut parts = line.splitn(3, ':');
+            let file = parts.next().unwrap_or_default();
+            let line_no = parts.next().and_then(|n| n.parse().ok());
+            let text = parts.next().unwrap_or_default().trim();
+            Violation {
+                check: "untagged-debt".to_string(),
+                message: format!("Untagged TODO: {}", text),
+                file: Some(file.trim_start_matches("./").to_string()),
+                line: line_no,
+            }
+        })
+        .collect()
+}
+
+/// Escape a workflow command message.
+fn escape_data(value: &str) -> String {
+    value
+        .replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escape a workflow command property value.
+fn escape_property(value: &str) -> String {
+    escape_data(value).replace(':', "%3A").replace(',', "%2C")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checker() -> ConstitutionCheck {
+        ConstitutionCheck::new(std::path::PathBuf::from("."))
+    }
+
+    #[test]
+    fn test_github_annotation_format() {
+        let violations = vec![
+            Violation {
+                check: "untagged-debt".to_string(),
+                message: "Untagged TODO: handle retries".to_string(),
+                file: Some("crates/core/src/machine.rs".to_string()),
+                line: Some(42),
+            },
+            Violation {
+                check: "tests-pass".to_string(),
+                message: "cargo test failed\n2 failures".to_string(),
+                file: None,
+                line: None,
+            },
+        ];
+
+        assert_eq!(
+            checker().report_github_annotations(&violations),
+            "::error file=crates/core/src/machine.rs,line=42::Untagged TODO: handle retries\n\
+             ::error::cargo test failed%0A2 failures\n"
+        );
+    }
+
+    #[test]
+    fn test_github_annotation_escapes_properties() {
+        let violation = Violation {
+            check: "clean-git".to_string(),
+            message: "Uncommitted changes".to_string(),
+            file: Some("a,b:c.rs".to_string()),
+            line: None,
+        };
+        assert_eq!(
+            checker().report_github_annotations(&[violation]),
+            "::error file=a%2Cb%3Ac.rs::Uncommitted changes\n"
+        );
+    }
+
+    #[test]
+    fn test_parse_untagged_todos() {
+        let output = "./src/lib.rs:12:    // TODO: fix\n./src/main.rs:3:// TODO(#9): tracked\n";
+        let violations = parse_untagged_todos(output);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].file.as_deref(), Some("src/lib.rs"));
+        assert_eq!(violations[0].line, Some(12));
+        assert_eq!(violations[0].message, "Untagged TODO: // TODO: fix");
+    }
+
+    #[test]
+    fn test_parse_porcelain() {
+        let output = " M src/lib.rs\nR  old.rs -> new.rs\n?? notes.md\n";
+        assert_eq!(
+            parse_porcelain(output),
+            vec!["src/lib.rs", "new.rs", "notes.md"]
+        );
+    }
 }